@@ -4,6 +4,21 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use crate::metrics::AI_RATE_LIMIT_HITS;
 
+/// A provider didn't answer within its configured request timeout
+#[derive(Debug, Clone)]
+pub struct ProviderTimeout {
+    pub provider: String,
+    pub timeout: Duration,
+}
+
+impl std::fmt::Display for ProviderTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} request timeout after {}ms", self.provider, self.timeout.as_millis())
+    }
+}
+
+impl std::error::Error for ProviderTimeout {}
+
 #[derive(Debug, Clone)]
 pub enum CircuitState {
     Closed,
@@ -76,6 +91,27 @@ impl CircuitBreaker {
         }
     }
 
+    /// Like `call`, but gives up after `timeout` with a `ProviderTimeout`
+    /// error, which counts as a failure. `None` means no deadline.
+    pub async fn call_with_timeout<F, T>(&self, timeout: Option<Duration>, f: F) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: std::future::Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+    {
+        let Some(timeout) = timeout else {
+            return self.call(f).await;
+        };
+
+        self.call(async {
+            match tokio::time::timeout(timeout, f).await {
+                Ok(result) => result,
+                Err(_) => Err(Box::new(ProviderTimeout {
+                    provider: self.provider_name.clone(),
+                    timeout,
+                }) as Box<dyn std::error::Error + Send + Sync>),
+            }
+        }).await
+    }
+
     async fn get_state(&self) -> CircuitState {
         self.state.read().await.clone()
     }
@@ -141,4 +177,38 @@ impl CircuitBreaker {
         self.failure_count.store(0, Ordering::SeqCst);
         self.success_count.store(0, Ordering::SeqCst);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn mock_provider(delay: Duration) -> Result<&'static str, Box<dyn std::error::Error + Send + Sync>> {
+        tokio::time::sleep(delay).await;
+        Ok("verdict")
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fails_slow_provider_without_blocking_others() {
+        let slow = CircuitBreaker::new_with_name("slow".to_string(), 1, 1, 60);
+        let fast = CircuitBreaker::new_with_name("fast".to_string(), 1, 1, 60);
+        let timeout = Some(Duration::from_millis(50));
+
+        let start = Instant::now();
+        let (slow_result, fast_result) = tokio::join!(
+            slow.call_with_timeout(timeout, mock_provider(Duration::from_secs(5))),
+            fast.call_with_timeout(timeout, mock_provider(Duration::from_millis(5))),
+        );
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(fast_result.unwrap(), "verdict");
+
+        let error = slow_result.unwrap_err();
+        let timeout_error = error.downcast_ref::<ProviderTimeout>().expect("ProviderTimeout");
+        assert_eq!(timeout_error.provider, "slow");
+
+        // The timeout was recorded as a failure
+        assert!(slow.is_open().await);
+        assert!(!fast.is_open().await);
+    }
+}
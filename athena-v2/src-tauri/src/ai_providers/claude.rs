@@ -62,13 +62,8 @@ impl ClaudeProvider {
             register_circuit_breaker("claude", breaker_clone).await;
         });
 
-        // Configure retry with 5 attempts, 1s initial delay, exponential backoff
-        let retry_config = RetryConfig {
-            max_retries: 5,
-            initial_delay_ms: 1000,
-            max_delay_ms: 30000,
-            backoff_multiplier: 2.0,
-        };
+        // Retry transient failures with jittered exponential backoff
+        let retry_config = config.retry.clone().unwrap_or_else(RetryConfig::provider_default);
 
         Ok(Self {
             config,
@@ -186,6 +181,52 @@ Base your analysis on file characteristics, known malware patterns, and security
             processing_time_ms: 0, // Set by analyze() method after API call completes
         })
     }
+
+    /// Send a single prompt and return the model's text reply
+    async fn send_prompt(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let claude_request = ClaudeRequest {
+            model: self.config.model.clone(),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+            }],
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+            system: "You are an expert malware analyst with deep knowledge of malware families, attack techniques, and threat intelligence.".to_string(),
+        };
+
+        let base_url = self.config.base_url.as_deref().unwrap_or("https://api.anthropic.com");
+        let response = self.client
+            .post(format!("{}/v1/messages", base_url))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&claude_request)
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Claude API error ({}): {}", status, error_text)
+            )) as Box<dyn std::error::Error + Send + Sync>);
+        }
+
+        let claude_response: ClaudeResponse = response.json().await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let response_text = claude_response.content.first()
+            .ok_or_else(|| Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "No content in Claude response"
+            )) as Box<dyn std::error::Error + Send + Sync>)?
+            .text.clone();
+
+        Ok(response_text)
+    }
 }
 
 #[async_trait]
@@ -194,55 +235,21 @@ impl AIProvider for ClaudeProvider {
         "Claude"
     }
 
+    async fn complete(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
+            with_retry(&self.retry_config, || self.send_prompt(prompt), "Claude").await
+        }).await
+    }
+
     async fn analyze(&self, request: &AnalysisRequest) -> Result<AnalysisResponse, Box<dyn Error + Send + Sync>> {
         let start_time = Instant::now();
 
-        let analysis_result = self.circuit_breaker.call(async {
+        let analysis_result = self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
             // Wrap the API call with retry logic
             with_retry(
                 &self.retry_config,
                 || async {
-                    let claude_request = ClaudeRequest {
-                        model: self.config.model.clone(),
-                        messages: vec![Message {
-                            role: "user".to_string(),
-                            content: self.build_analysis_prompt(request),
-                        }],
-                        max_tokens: self.config.max_tokens,
-                        temperature: self.config.temperature,
-                        system: "You are an expert malware analyst with deep knowledge of malware families, attack techniques, and threat intelligence.".to_string(),
-                    };
-
-                    let base_url = self.config.base_url.as_deref().unwrap_or("https://api.anthropic.com");
-                    let response = self.client
-                        .post(format!("{}/v1/messages", base_url))
-                        .header("x-api-key", &self.config.api_key)
-                        .header("anthropic-version", "2023-06-01")
-                        .header("content-type", "application/json")
-                        .json(&claude_request)
-                        .send()
-                        .await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-                    let status = response.status();
-                    if !status.is_success() {
-                        let error_text = response.text().await
-                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                        return Err(Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            format!("Claude API error ({}): {}", status, error_text)
-                        )) as Box<dyn std::error::Error + Send + Sync>);
-                    }
-
-                    let claude_response: ClaudeResponse = response.json().await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                    let response_text = claude_response.content.first()
-                        .ok_or_else(|| Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "No content in Claude response"
-                        )) as Box<dyn std::error::Error + Send + Sync>)?
-                        .text.clone();
-
+                    let response_text = self.send_prompt(&self.build_analysis_prompt(request)).await?;
                     self.parse_claude_response(&response_text).await
                 },
                 "Claude",
@@ -412,6 +419,7 @@ mod tests {
             max_tokens: 4000,
             temperature: 0.3,
             timeout_secs: 120,
+            retry: None,
         }
     }
 
@@ -61,13 +61,8 @@ impl DeepSeekProvider {
             register_circuit_breaker("deepseek", breaker_clone).await;
         });
 
-        // Configure retry with 5 attempts, 1s initial delay, exponential backoff
-        let retry_config = RetryConfig {
-            max_retries: 5,
-            initial_delay_ms: 1000,
-            max_delay_ms: 30000,
-            backoff_multiplier: 2.0,
-        };
+        // Retry transient failures with jittered exponential backoff
+        let retry_config = config.retry.clone().unwrap_or_else(RetryConfig::provider_default);
 
         Ok(Self {
             config,
@@ -189,6 +184,56 @@ Provide a detailed JSON response with:
             processing_time_ms: 0, // Set by analyze() method after API call completes
         })
     }
+
+    /// Send a single prompt and return the model's text reply
+    async fn send_prompt(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let deepseek_request = DeepSeekRequest {
+            model: self.config.model.clone(),
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: self.build_system_prompt(),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: prompt.to_string(),
+                },
+            ],
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+        };
+
+        let base_url = self.config.base_url.as_deref().unwrap_or("https://api.deepseek.com");
+        let response = self.client
+            .post(format!("{}/v1/chat/completions", base_url))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Content-Type", "application/json")
+            .json(&deepseek_request)
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("DeepSeek API error ({}): {}", status, error_text)
+            )) as Box<dyn std::error::Error + Send + Sync>);
+        }
+
+        let deepseek_response: DeepSeekResponse = response.json().await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let response_text = deepseek_response.choices.first()
+            .ok_or_else(|| Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "No choices in DeepSeek response"
+            )) as Box<dyn std::error::Error + Send + Sync>)?
+            .message.content.clone();
+
+        Ok(response_text)
+    }
 }
 
 #[async_trait]
@@ -197,59 +242,21 @@ impl AIProvider for DeepSeekProvider {
         "DeepSeek"
     }
 
+    async fn complete(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
+            with_retry(&self.retry_config, || self.send_prompt(prompt), "DeepSeek").await
+        }).await
+    }
+
     async fn analyze(&self, request: &AnalysisRequest) -> Result<AnalysisResponse, Box<dyn Error + Send + Sync>> {
         let start_time = Instant::now();
 
-        let analysis_result = self.circuit_breaker.call(async {
+        let analysis_result = self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
             // Wrap the API call with retry logic
             with_retry(
                 &self.retry_config,
                 || async {
-                    let deepseek_request = DeepSeekRequest {
-                        model: self.config.model.clone(),
-                        messages: vec![
-                            Message {
-                                role: "system".to_string(),
-                                content: self.build_system_prompt(),
-                            },
-                            Message {
-                                role: "user".to_string(),
-                                content: self.build_analysis_prompt(request),
-                            },
-                        ],
-                        max_tokens: self.config.max_tokens,
-                        temperature: self.config.temperature,
-                    };
-
-                    let base_url = self.config.base_url.as_deref().unwrap_or("https://api.deepseek.com");
-                    let response = self.client
-                        .post(format!("{}/v1/chat/completions", base_url))
-                        .header("Authorization", format!("Bearer {}", self.config.api_key))
-                        .header("Content-Type", "application/json")
-                        .json(&deepseek_request)
-                        .send()
-                        .await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-                    let status = response.status();
-                    if !status.is_success() {
-                        let error_text = response.text().await
-                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                        return Err(Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            format!("DeepSeek API error ({}): {}", status, error_text)
-                        )) as Box<dyn std::error::Error + Send + Sync>);
-                    }
-
-                    let deepseek_response: DeepSeekResponse = response.json().await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                    let response_text = deepseek_response.choices.first()
-                        .ok_or_else(|| Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "No choices in DeepSeek response"
-                        )) as Box<dyn std::error::Error + Send + Sync>)?
-                        .message.content.clone();
-
+                    let response_text = self.send_prompt(&self.build_analysis_prompt(request)).await?;
                     self.parse_deepseek_response(&response_text).await
                 },
                 "DeepSeek",
@@ -415,6 +422,7 @@ mod tests {
             max_tokens: 8000,
             temperature: 0.1,
             timeout_secs: 60,
+            retry: None,
         }
     }
 
@@ -80,12 +80,8 @@ impl GeminiProvider {
             register_circuit_breaker("gemini", breaker_clone).await;
         });
 
-        let retry_config = RetryConfig {
-            max_retries: 5,
-            initial_delay_ms: 1000,
-            max_delay_ms: 30000,
-            backoff_multiplier: 2.0,
-        };
+        // Retry transient failures with jittered exponential backoff
+        let retry_config = config.retry.clone().unwrap_or_else(RetryConfig::provider_default);
 
         Ok(Self {
             config,
@@ -202,6 +198,62 @@ Base your analysis on file characteristics, known malware patterns, and security
             processing_time_ms: 0,
         })
     }
+
+    /// Send a single prompt and return the model's text reply
+    async fn send_prompt(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let gemini_request = GeminiRequest {
+            contents: vec![GeminiContent {
+                parts: vec![GeminiPart {
+                    text: prompt.to_string(),
+                }],
+            }],
+            generation_config: GenerationConfig {
+                temperature: self.config.temperature,
+                max_output_tokens: self.config.max_tokens,
+            },
+        };
+
+        let model = &self.config.model;
+        let base_url = self.config.base_url.as_deref()
+            .unwrap_or("https://generativelanguage.googleapis.com");
+
+        let url = format!(
+            "{}/v1beta/models/{}:generateContent?key={}",
+            base_url, model, self.config.api_key
+        );
+
+        let response = self.client
+            .post(&url)
+            .header("content-type", "application/json")
+            .json(&gemini_request)
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Gemini API error ({}): {}", status, error_text)
+            )) as Box<dyn std::error::Error + Send + Sync>);
+        }
+
+        let gemini_response: GeminiResponse = response.json().await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let response_text = gemini_response.candidates
+            .first()
+            .and_then(|c| c.content.parts.first())
+            .map(|p| p.text.clone())
+            .ok_or_else(|| Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "No content in Gemini response"
+            )) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        Ok(response_text)
+    }
 }
 
 #[async_trait]
@@ -210,64 +262,20 @@ impl AIProvider for GeminiProvider {
         "Gemini"
     }
 
+    async fn complete(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
+            with_retry(&self.retry_config, || self.send_prompt(prompt), "Gemini").await
+        }).await
+    }
+
     async fn analyze(&self, request: &AnalysisRequest) -> Result<AnalysisResponse, Box<dyn Error + Send + Sync>> {
         let start_time = Instant::now();
 
-        let analysis_result = self.circuit_breaker.call(async {
+        let analysis_result = self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
             with_retry(
                 &self.retry_config,
                 || async {
-                    let gemini_request = GeminiRequest {
-                        contents: vec![GeminiContent {
-                            parts: vec![GeminiPart {
-                                text: self.build_analysis_prompt(request),
-                            }],
-                        }],
-                        generation_config: GenerationConfig {
-                            temperature: self.config.temperature,
-                            max_output_tokens: self.config.max_tokens,
-                        },
-                    };
-
-                    let model = &self.config.model;
-                    let base_url = self.config.base_url.as_deref()
-                        .unwrap_or("https://generativelanguage.googleapis.com");
-
-                    let url = format!(
-                        "{}/v1beta/models/{}:generateContent?key={}",
-                        base_url, model, self.config.api_key
-                    );
-
-                    let response = self.client
-                        .post(&url)
-                        .header("content-type", "application/json")
-                        .json(&gemini_request)
-                        .send()
-                        .await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-                    let status = response.status();
-                    if !status.is_success() {
-                        let error_text = response.text().await
-                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                        return Err(Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            format!("Gemini API error ({}): {}", status, error_text)
-                        )) as Box<dyn std::error::Error + Send + Sync>);
-                    }
-
-                    let gemini_response: GeminiResponse = response.json().await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-                    let response_text = gemini_response.candidates
-                        .first()
-                        .and_then(|c| c.content.parts.first())
-                        .map(|p| p.text.clone())
-                        .ok_or_else(|| Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "No content in Gemini response"
-                        )) as Box<dyn std::error::Error + Send + Sync>)?;
-
+                    let response_text = self.send_prompt(&self.build_analysis_prompt(request)).await?;
                     self.parse_response(&response_text).await
                 },
                 "Gemini",
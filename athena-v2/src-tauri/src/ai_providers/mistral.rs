@@ -60,12 +60,8 @@ impl MistralProvider {
             register_circuit_breaker("mistral", breaker_clone).await;
         });
 
-        let retry_config = RetryConfig {
-            max_retries: 5,
-            initial_delay_ms: 1000,
-            max_delay_ms: 30000,
-            backoff_multiplier: 2.0,
-        };
+        // Retry transient failures with jittered exponential backoff
+        let retry_config = config.retry.clone().unwrap_or_else(RetryConfig::provider_default);
 
         Ok(Self {
             config,
@@ -182,6 +178,60 @@ Base your analysis on file characteristics, known malware patterns, and security
             processing_time_ms: 0,
         })
     }
+
+    /// Send a single prompt and return the model's text reply
+    async fn send_prompt(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let mistral_request = MistralRequest {
+            model: self.config.model.clone(),
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: "You are an expert malware analyst with deep knowledge of malware families, attack techniques, and threat intelligence.".to_string(),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: prompt.to_string(),
+                },
+            ],
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+        };
+
+        let base_url = self.config.base_url.as_deref()
+            .unwrap_or("https://api.mistral.ai");
+
+        let response = self.client
+            .post(format!("{}/v1/chat/completions", base_url))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("content-type", "application/json")
+            .json(&mistral_request)
+            .send()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Mistral API error ({}): {}", status, error_text)
+            )) as Box<dyn std::error::Error + Send + Sync>);
+        }
+
+        let mistral_response: MistralResponse = response.json().await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let response_text = mistral_response.choices
+            .first()
+            .map(|c| c.message.content.clone())
+            .ok_or_else(|| Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "No content in Mistral response"
+            )) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        Ok(response_text)
+    }
 }
 
 #[async_trait]
@@ -190,62 +240,20 @@ impl AIProvider for MistralProvider {
         "Mistral"
     }
 
+    async fn complete(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
+            with_retry(&self.retry_config, || self.send_prompt(prompt), "Mistral").await
+        }).await
+    }
+
     async fn analyze(&self, request: &AnalysisRequest) -> Result<AnalysisResponse, Box<dyn Error + Send + Sync>> {
         let start_time = Instant::now();
 
-        let analysis_result = self.circuit_breaker.call(async {
+        let analysis_result = self.circuit_breaker.call_with_timeout(Some(self.config.request_timeout()), async {
             with_retry(
                 &self.retry_config,
                 || async {
-                    let mistral_request = MistralRequest {
-                        model: self.config.model.clone(),
-                        messages: vec![
-                            Message {
-                                role: "system".to_string(),
-                                content: "You are an expert malware analyst with deep knowledge of malware families, attack techniques, and threat intelligence.".to_string(),
-                            },
-                            Message {
-                                role: "user".to_string(),
-                                content: self.build_analysis_prompt(request),
-                            },
-                        ],
-                        max_tokens: self.config.max_tokens,
-                        temperature: self.config.temperature,
-                    };
-
-                    let base_url = self.config.base_url.as_deref()
-                        .unwrap_or("https://api.mistral.ai");
-
-                    let response = self.client
-                        .post(format!("{}/v1/chat/completions", base_url))
-                        .header("Authorization", format!("Bearer {}", self.config.api_key))
-                        .header("content-type", "application/json")
-                        .json(&mistral_request)
-                        .send()
-                        .await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-                    let status = response.status();
-                    if !status.is_success() {
-                        let error_text = response.text().await
-                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                        return Err(Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            format!("Mistral API error ({}): {}", status, error_text)
-                        )) as Box<dyn std::error::Error + Send + Sync>);
-                    }
-
-                    let mistral_response: MistralResponse = response.json().await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-                    let response_text = mistral_response.choices
-                        .first()
-                        .map(|c| c.message.content.clone())
-                        .ok_or_else(|| Box::new(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "No content in Mistral response"
-                        )) as Box<dyn std::error::Error + Send + Sync>)?;
-
+                    let response_text = self.send_prompt(&self.build_analysis_prompt(request)).await?;
                     self.parse_response(&response_text).await
                 },
                 "Mistral",
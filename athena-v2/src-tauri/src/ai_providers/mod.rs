@@ -7,6 +7,7 @@ pub mod mistral;
 pub mod groq;
 pub mod queue_manager;
 pub mod retry;
+pub mod structured;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -60,7 +61,20 @@ pub struct AIProviderConfig {
     pub model: String,
     pub max_tokens: u32,
     pub temperature: f32,
+    /// Deadline for each HTTP call and for a whole analysis request,
+    /// retries included. A provider that misses it fails with
+    /// `ProviderTimeout`.
     pub timeout_secs: u64,
+    /// Retry policy for transient failures; providers fall back to
+    /// `RetryConfig::provider_default()`
+    #[serde(default)]
+    pub retry: Option<retry::RetryConfig>,
+}
+
+impl AIProviderConfig {
+    pub fn request_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout_secs)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +138,8 @@ pub trait AIProvider: Send + Sync {
     #[allow(dead_code)]
     fn name(&self) -> &str;
     async fn analyze(&self, request: &AnalysisRequest) -> Result<AnalysisResponse, Box<dyn Error + Send + Sync>>;
+    /// Send a free-form prompt and return the model's raw text reply
+    async fn complete(&self, prompt: &str) -> Result<String, Box<dyn Error + Send + Sync>>;
     #[allow(dead_code)]
     async fn health_check(&self) -> Result<bool, Box<dyn Error + Send + Sync>>;
     /// List available models from this provider's API
@@ -139,6 +155,7 @@ impl Default for AIProviderConfig {
             max_tokens: 4096,
             temperature: 0.3,
             timeout_secs: 30,
+            retry: None,
         }
     }
 }
@@ -1,9 +1,12 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::time::Duration;
 use tokio::time::sleep;
 
 /// Configuration for retry with exponential backoff
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RetryConfig {
     /// Maximum number of retry attempts
     pub max_retries: u32,
@@ -13,6 +16,9 @@ pub struct RetryConfig {
     pub max_delay_ms: u64,
     /// Multiplier for exponential backoff (typically 2.0)
     pub backoff_multiplier: f64,
+    /// Fraction of each delay (0.0-1.0) randomized up or down, so clients
+    /// that failed at the same moment don't all retry at the same moment
+    pub jitter: f64,
 }
 
 impl Default for RetryConfig {
@@ -22,10 +28,31 @@ impl Default for RetryConfig {
             initial_delay_ms: 1000,
             max_delay_ms: 30000,
             backoff_multiplier: 2.0,
+            jitter: 0.2,
         }
     }
 }
 
+impl RetryConfig {
+    /// Policy used by the AI providers unless overridden in their config
+    pub fn provider_default() -> Self {
+        Self {
+            max_retries: 5,
+            ..Self::default()
+        }
+    }
+}
+
+/// Randomize `delay_ms` by up to `jitter` of its value in either direction
+fn jittered_delay(delay_ms: u64, jitter: f64) -> u64 {
+    let spread = delay_ms as f64 * jitter.clamp(0.0, 1.0);
+    if spread <= 0.0 {
+        return delay_ms;
+    }
+    let offset = rand::thread_rng().gen_range(-spread..=spread);
+    (delay_ms as f64 + offset).max(0.0) as u64
+}
+
 /// Determines if an error is transient and should be retried
 pub fn is_retryable_error(error: &Box<dyn Error + Send + Sync>) -> bool {
     let error_msg = error.to_string().to_lowercase();
@@ -129,17 +156,18 @@ where
                 let should_retry = is_retryable_error(&error) && attempts <= config.max_retries;
 
                 if should_retry {
+                    let wait_ms = jittered_delay(delay_ms, config.jitter);
                     eprintln!(
                         "[WARN] {} request failed on attempt {}/{}: {} - retrying in {}ms",
                         provider_name,
                         attempts,
                         config.max_retries + 1,
                         error,
-                        delay_ms
+                        wait_ms
                     );
 
                     // Wait before retrying
-                    sleep(Duration::from_millis(delay_ms)).await;
+                    sleep(Duration::from_millis(wait_ms)).await;
 
                     // Calculate next delay with exponential backoff
                     delay_ms = ((delay_ms as f64) * config.backoff_multiplier) as u64;
@@ -190,67 +218,73 @@ mod tests {
         assert!(!is_retryable_error(&validation_error));
     }
 
-    // NOTE: These tests have been temporarily commented out due to borrow checker issues
-    // with capturing mutable variables in async closures. They need to be refactored
-    // to use Arc<Mutex<>> or similar for shared state.
-
-    // #[tokio::test]
-    // async fn test_retry_success_on_first_attempt() {
-    //     let config = RetryConfig::default();
-    //     let mut call_count = 0;
-    //
-    //     let result = with_retry(
-    //         &config,
-    //         || async {
-    //             call_count += 1;
-    //             Ok::<i32, Box<dyn Error + Send + Sync>>(42)
-    //         },
-    //         "test"
-    //     ).await;
-    //
-    //     assert!(result.is_ok());
-    //     assert_eq!(result.unwrap(), 42);
-    //     assert_eq!(call_count, 1);
-    // }
-    //
-    // #[tokio::test]
-    // async fn test_retry_success_after_retries() {
-    //     let config = RetryConfig::default();
-    //     let mut call_count = 0;
-    //
-    //     let result = with_retry(
-    //         &config,
-    //         || async {
-    //             call_count += 1;
-    //             if call_count < 3 {
-    //                 Err("Timeout".into())
-    //             } else {
-    //                 Ok::<i32, Box<dyn Error + Send + Sync>>(42)
-    //             }
-    //         },
-    //         "test"
-    //     ).await;
-    //
-    //     assert!(result.is_ok());
-    //     assert_eq!(result.unwrap(), 42);
-    //     assert_eq!(call_count, 3);
-    // }
-    //
-    // #[tokio::test]
-    // async fn test_no_retry_on_auth_error() {
-    //     let config = RetryConfig::default();
-    //     let mut call_count = 0;
-    //
-    //     let result = with_retry(
-    //         &config,
-    //         || async {
-    //             call_count += 1;
-    //             Err::<i32, Box<dyn Error + Send + Sync>>("Unauthorized (401)".into())
-    //         },
-    //         "test"
-    //     ).await;
-    //
-    //     assert!(result.is_err());
-    //     assert_eq!(call_count, 1); // Should not retry
-    // }
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Stands in for a provider whose first few completion requests fail
+    /// with a transient error
+    struct FlakyProvider {
+        failures_left: AtomicU32,
+        calls: AtomicU32,
+    }
+
+    impl FlakyProvider {
+        fn new(failures: u32) -> Self {
+            Self {
+                failures_left: AtomicU32::new(failures),
+                calls: AtomicU32::new(0),
+            }
+        }
+
+        async fn complete(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let remaining = self.failures_left.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures_left.store(remaining - 1, Ordering::SeqCst);
+                return Err("Mock API error (503 Service Unavailable)".into());
+            }
+            Ok("verdict".to_string())
+        }
+    }
+
+    fn fast_config(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_delay_ms: 5,
+            max_delay_ms: 20,
+            backoff_multiplier: 2.0,
+            jitter: 0.5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_transient_failures() {
+        let provider = FlakyProvider::new(2);
+        let config = fast_config(3);
+
+        let result = with_retry(&config, || provider.complete(), "mock").await;
+
+        assert_eq!(result.unwrap(), "verdict");
+        let calls = provider.calls.load(Ordering::SeqCst);
+        assert_eq!(calls, 3);
+        assert!(calls <= config.max_retries + 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_when_budget_exhausted() {
+        let provider = FlakyProvider::new(5);
+
+        let result = with_retry(&fast_config(1), || provider.complete(), "mock").await;
+
+        assert!(result.is_err());
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_jittered_delay_stays_within_bounds() {
+        for _ in 0..100 {
+            let delay = jittered_delay(1000, 0.2);
+            assert!((800..=1200).contains(&delay));
+        }
+        assert_eq!(jittered_delay(1000, 0.0), 1000);
+    }
 }
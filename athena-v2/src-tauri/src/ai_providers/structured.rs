@@ -0,0 +1,409 @@
+/**
+ * Structured Response Mode
+ * Asks the provider for JSON matching `verdict_schema()` and validates the
+ * reply into a typed `AiVerdict` instead of handing raw text downstream.
+ *
+ * Replies are normalized before validation (code fences, trailing commas,
+ * percentages, lowercase enum values, single strings where a list is
+ * expected). If a reply still doesn't validate, the model gets exactly one
+ * repair round with the validation errors.
+ */
+
+use super::{AIProvider, AnalysisRequest, IOCs, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use std::future::Future;
+
+const LIST_FIELDS: &[&str] = &["signatures", "behaviors", "recommendations"];
+const IOC_FIELDS: &[&str] = &["domains", "ips", "urls", "files", "registry_keys", "processes", "mutexes"];
+
+/// Validated verdict from a structured-mode response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiVerdict {
+    pub threat_level: ThreatLevel,
+    pub confidence: f32,
+    pub malware_family: Option<String>,
+    pub malware_type: Option<String>,
+    pub signatures: Vec<String>,
+    pub behaviors: Vec<String>,
+    pub iocs: IOCs,
+    pub recommendations: Vec<String>,
+    pub detailed_analysis: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum VerdictError {
+    /// The reply contains no JSON object
+    NoJson,
+    /// The JSON couldn't be parsed
+    Malformed(String),
+    /// The JSON parsed but doesn't match the schema
+    Invalid(Vec<String>),
+}
+
+impl std::fmt::Display for VerdictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerdictError::NoJson => write!(f, "Response contains no JSON object"),
+            VerdictError::Malformed(e) => write!(f, "Malformed JSON in response: {}", e),
+            VerdictError::Invalid(errors) => write!(f, "Response does not match schema: {}", errors.join("; ")),
+        }
+    }
+}
+
+impl Error for VerdictError {}
+
+/// JSON schema the model is asked to follow
+pub fn verdict_schema() -> Value {
+    let string_list = json!({ "type": "array", "items": { "type": "string" } });
+    let iocs: Map<String, Value> = IOC_FIELDS
+        .iter()
+        .map(|field| (field.to_string(), string_list.clone()))
+        .collect();
+
+    json!({
+        "type": "object",
+        "required": ["threat_level", "confidence"],
+        "properties": {
+            "threat_level": { "enum": ["Benign", "Suspicious", "Malicious", "Critical"] },
+            "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+            "malware_family": { "type": ["string", "null"] },
+            "malware_type": { "type": ["string", "null"] },
+            "signatures": string_list,
+            "behaviors": string_list,
+            "iocs": { "type": "object", "properties": iocs },
+            "recommendations": string_list,
+            "detailed_analysis": { "type": "string" }
+        }
+    })
+}
+
+/// Prompt asking for a verdict on `request` as schema-conforming JSON only
+pub fn structured_prompt(request: &AnalysisRequest) -> String {
+    format!(
+        r#"Analyze this potentially malicious file and provide a security verdict.
+
+File Information:
+- Name: {}
+- Size: {} bytes
+- Type: {}
+- SHA-256: {}
+
+Respond with a single JSON object and nothing else. It must conform to this JSON schema:
+{}"#,
+        request.file_name,
+        request.file_size,
+        request.file_type,
+        request.file_hash,
+        verdict_schema()
+    )
+}
+
+/// Prompt for the repair round, quoting the rejected reply and why it failed
+pub fn repair_prompt(reply: &str, error: &VerdictError) -> String {
+    format!(
+        r#"Your previous response could not be used: {}
+
+Previous response:
+{}
+
+Reply again with only a JSON object conforming to this JSON schema:
+{}"#,
+        error,
+        reply,
+        verdict_schema()
+    )
+}
+
+/// Extract, normalize and validate a verdict from a model reply
+pub fn parse_verdict(reply: &str) -> Result<AiVerdict, VerdictError> {
+    let json = extract_json(reply).ok_or(VerdictError::NoJson)?;
+    let mut value: Value = serde_json::from_str(&strip_trailing_commas(json))
+        .map_err(|e| VerdictError::Malformed(e.to_string()))?;
+
+    normalize(&mut value);
+
+    let errors = validate(&value);
+    if !errors.is_empty() {
+        return Err(VerdictError::Invalid(errors));
+    }
+
+    serde_json::from_value(value).map_err(|e| VerdictError::Invalid(vec![e.to_string()]))
+}
+
+/// Ask `provider` for a structured verdict on `request`
+pub async fn analyze_structured(
+    provider: &dyn AIProvider,
+    request: &AnalysisRequest,
+) -> Result<AiVerdict, Box<dyn Error + Send + Sync>> {
+    request_verdict(&structured_prompt(request), |prompt| async move {
+        provider.complete(&prompt).await
+    })
+    .await
+}
+
+/// Send `prompt` through `complete` and validate the reply, allowing one
+/// repair round if it doesn't validate
+pub async fn request_verdict<F, Fut>(prompt: &str, complete: F) -> Result<AiVerdict, Box<dyn Error + Send + Sync>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<String, Box<dyn Error + Send + Sync>>>,
+{
+    let reply = complete(prompt.to_string()).await?;
+    let error = match parse_verdict(&reply) {
+        Ok(verdict) => return Ok(verdict),
+        Err(error) => error,
+    };
+
+    let repaired = complete(repair_prompt(&reply, &error)).await?;
+    parse_verdict(&repaired).map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+}
+
+/// The outermost JSON object in `reply`, ignoring any surrounding prose or
+/// code fences
+fn extract_json(reply: &str) -> Option<&str> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    (end > start).then(|| &reply[start..=end])
+}
+
+/// Drop commas directly before a closing brace or bracket
+fn strip_trailing_commas(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in json.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '}' || c == ']' {
+            let trimmed = out.trim_end().len();
+            if out[..trimmed].ends_with(',') {
+                out.truncate(trimmed - 1);
+            }
+        }
+        out.push(c);
+    }
+
+    out
+}
+
+/// Coerce near-miss values into the shape the schema expects
+fn normalize(value: &mut Value) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+
+    if let Some(level) = obj.get("threat_level").and_then(Value::as_str) {
+        if let Some(canonical) = canonical_threat_level(level) {
+            obj.insert("threat_level".to_string(), json!(canonical));
+        }
+    }
+
+    if let Some(confidence) = obj.get("confidence").and_then(coerce_confidence) {
+        obj.insert("confidence".to_string(), json!(confidence));
+    }
+
+    for field in ["malware_family", "malware_type"] {
+        let empty = match obj.get(field) {
+            Some(Value::String(s)) => {
+                let s = s.trim();
+                s.is_empty() || s.eq_ignore_ascii_case("null") || s.eq_ignore_ascii_case("none") || s.eq_ignore_ascii_case("unknown")
+            }
+            None => true,
+            _ => false,
+        };
+        if empty {
+            obj.insert(field.to_string(), Value::Null);
+        }
+    }
+
+    for field in LIST_FIELDS {
+        normalize_list(obj, field);
+    }
+
+    let iocs = obj.entry("iocs").or_insert_with(|| json!({}));
+    if iocs.is_null() {
+        *iocs = json!({});
+    }
+    if let Some(iocs) = iocs.as_object_mut() {
+        for field in IOC_FIELDS {
+            normalize_list(iocs, field);
+        }
+    }
+
+    if matches!(obj.get("detailed_analysis"), None | Some(Value::Null)) {
+        obj.insert("detailed_analysis".to_string(), json!(""));
+    }
+}
+
+fn canonical_threat_level(level: &str) -> Option<&'static str> {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "benign" | "clean" | "safe" => Some("Benign"),
+        "suspicious" => Some("Suspicious"),
+        "malicious" => Some("Malicious"),
+        "critical" => Some("Critical"),
+        _ => None,
+    }
+}
+
+/// Accept `0.85`, `"0.85"`, `"85%"` and `85` as the same confidence
+fn coerce_confidence(value: &Value) -> Option<f64> {
+    let (number, percent) = match value {
+        Value::Number(n) => (n.as_f64()?, false),
+        Value::String(s) => {
+            let s = s.trim();
+            match s.strip_suffix('%') {
+                Some(p) => (p.trim().parse().ok()?, true),
+                None => (s.parse().ok()?, false),
+            }
+        }
+        _ => return None,
+    };
+
+    if percent || (number > 1.0 && number <= 100.0) {
+        Some(number / 100.0)
+    } else {
+        Some(number)
+    }
+}
+
+/// Missing or null becomes `[]`; a lone string becomes a one-element list
+fn normalize_list(obj: &mut Map<String, Value>, field: &str) {
+    let normalized = match obj.remove(field) {
+        None | Some(Value::Null) => json!([]),
+        Some(Value::String(s)) if s.trim().is_empty() => json!([]),
+        Some(Value::String(s)) => json!([s]),
+        Some(other) => other,
+    };
+    obj.insert(field.to_string(), normalized);
+}
+
+fn validate(value: &Value) -> Vec<String> {
+    let Some(obj) = value.as_object() else {
+        return vec!["response is not a JSON object".to_string()];
+    };
+    let mut errors = Vec::new();
+
+    match obj.get("threat_level") {
+        Some(Value::String(s)) if canonical_threat_level(s).is_some() => {}
+        Some(other) => errors.push(format!(
+            "threat_level must be one of Benign, Suspicious, Malicious, Critical (got {})",
+            other
+        )),
+        None => errors.push("threat_level is required".to_string()),
+    }
+
+    match obj.get("confidence").and_then(Value::as_f64) {
+        Some(c) if (0.0..=1.0).contains(&c) => {}
+        Some(c) => errors.push(format!("confidence must be between 0 and 1 (got {})", c)),
+        None => errors.push("confidence is required and must be a number".to_string()),
+    }
+
+    let is_string_list = |v: Option<&Value>| {
+        v.and_then(Value::as_array)
+            .map(|items| items.iter().all(Value::is_string))
+            .unwrap_or(false)
+    };
+
+    for field in LIST_FIELDS {
+        if !is_string_list(obj.get(*field)) {
+            errors.push(format!("{} must be a list of strings", field));
+        }
+    }
+
+    for field in IOC_FIELDS {
+        if !is_string_list(obj.get("iocs").and_then(|iocs| iocs.get(*field))) {
+            errors.push(format!("iocs.{} must be a list of strings", field));
+        }
+    }
+
+    if !obj.get("detailed_analysis").map(Value::is_string).unwrap_or(false) {
+        errors.push("detailed_analysis must be a string".to_string());
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_malformed_response_is_repaired_into_verdict() {
+        let reply = r#"Here is my assessment:
+```json
+{
+    "threat_level": "malicious",
+    "confidence": "85%",
+    "malware_family": "none",
+    "signatures": "Emotet loader",
+    "behaviors": ["process injection", "persistence",],
+    "iocs": { "domains": ["evil.example"], "ips": null },
+    "detailed_analysis": "Drops a second stage, then injects into explorer.exe",
+}
+```"#;
+
+        let verdict = parse_verdict(reply).unwrap();
+
+        assert!(matches!(verdict.threat_level, ThreatLevel::Malicious));
+        assert!((verdict.confidence - 0.85).abs() < f32::EPSILON);
+        assert!(verdict.malware_family.is_none());
+        assert_eq!(verdict.signatures, vec!["Emotet loader"]);
+        assert_eq!(verdict.behaviors, vec!["process injection", "persistence"]);
+        assert_eq!(verdict.iocs.domains, vec!["evil.example"]);
+        assert!(verdict.iocs.ips.is_empty());
+        assert!(verdict.recommendations.is_empty());
+    }
+
+    #[test]
+    fn test_schema_violations_are_reported() {
+        let err = parse_verdict(r#"{"threat_level": "catastrophic", "confidence": 250}"#).unwrap_err();
+        let VerdictError::Invalid(errors) = err else { panic!("expected Invalid, got {:?}", err) };
+        assert_eq!(errors.len(), 2);
+
+        assert!(matches!(parse_verdict("I could not analyze this file."), Err(VerdictError::NoJson)));
+    }
+
+    #[tokio::test]
+    async fn test_single_repair_round() {
+        let calls = AtomicUsize::new(0);
+        let verdict = request_verdict("analyze", |prompt| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    Ok("The file looks suspicious to me.".to_string())
+                } else {
+                    assert!(prompt.contains("could not be used"));
+                    Ok(r#"{"threat_level": "Suspicious", "confidence": 0.6}"#.to_string())
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(matches!(verdict.threat_level, ThreatLevel::Suspicious));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // A reply that still fails after the repair round is an error
+        let calls = AtomicUsize::new(0);
+        let result = request_verdict("analyze", |_| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Ok("no idea".to_string()) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
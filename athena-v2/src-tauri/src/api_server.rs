@@ -145,6 +145,8 @@ async fn analyze_file(
         app_handle.clone(),
         app_handle.state(),
         safe_path,
+        None,
+        None,
     )
     .await
     {
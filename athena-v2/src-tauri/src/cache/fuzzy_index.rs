@@ -0,0 +1,499 @@
+/**
+ * Fuzzy Hash Index
+ * Stores SSDEEP or TLSH hashes of known samples in SQLite so new samples can
+ * be clustered against the corpus.
+ *
+ * SSDEEP can only compare hashes whose block sizes are equal or differ by a
+ * factor of two, so the block size is stored alongside each hash and used to
+ * narrow the candidate set before scoring. TLSH hashes can all be compared
+ * with each other and are stored with a block size of 0.
+ *
+ * Each index works with one algorithm; indexes for both can share a database.
+ * SSDEEP digests come from the `ssdeep` crate and TLSH digests from
+ * `tlsh_hash` below, so both are computed from the sample itself.
+ */
+
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use anyhow::{Context, Result};
+
+/// Minimum similarity (0-100) a known sample needs to be reported
+pub const DEFAULT_MIN_SCORE: u8 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FuzzyAlgorithm {
+    SsDeep,
+    Tlsh,
+}
+
+impl FuzzyAlgorithm {
+    fn as_str(self) -> &'static str {
+        match self {
+            FuzzyAlgorithm::SsDeep => "ssdeep",
+            FuzzyAlgorithm::Tlsh => "tlsh",
+        }
+    }
+}
+
+/// Which fuzzy hash the index clusters on and how similar a match must be
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzyConfig {
+    pub algorithm: FuzzyAlgorithm,
+    /// Scores run 0-100 for both algorithms; TLSH distances are converted
+    /// as `100 - distance`, so anything 100 or more apart scores 0
+    pub min_score: u8,
+}
+
+impl Default for FuzzyConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: FuzzyAlgorithm::SsDeep,
+            min_score: DEFAULT_MIN_SCORE,
+        }
+    }
+}
+
+/// A known sample that scored at least the minimum against a new one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimilarSample {
+    pub sample_id: String,
+    pub score: u8,
+}
+
+pub struct FuzzyIndex {
+    conn: Mutex<Connection>,
+    config: FuzzyConfig,
+}
+
+impl FuzzyIndex {
+    pub fn new(db_path: &str) -> Result<Self> {
+        let conn = Connection::open(db_path)
+            .context("Failed to open fuzzy index database")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fuzzy_hashes (
+                sample_id TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                block_size INTEGER NOT NULL,
+                algorithm TEXT NOT NULL DEFAULT 'ssdeep',
+                PRIMARY KEY (sample_id, hash)
+            )",
+            [],
+        ).context("Failed to create fuzzy_hashes table")?;
+
+        // Databases created before TLSH support only hold SSDEEP hashes
+        if conn.prepare("SELECT algorithm FROM fuzzy_hashes LIMIT 0").is_err() {
+            conn.execute(
+                "ALTER TABLE fuzzy_hashes ADD COLUMN algorithm TEXT NOT NULL DEFAULT 'ssdeep'",
+                [],
+            ).context("Failed to add algorithm column")?;
+        }
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_fuzzy_block_size ON fuzzy_hashes(algorithm, block_size)",
+            [],
+        ).context("Failed to create index")?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            config: FuzzyConfig::default(),
+        })
+    }
+
+    pub fn with_config(mut self, config: FuzzyConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &FuzzyConfig {
+        &self.config
+    }
+
+    /// Record a sample's hash for the configured algorithm
+    pub fn insert(&self, sample_id: &str, hash: &str) -> Result<()> {
+        let block_size = self.block_size_of(hash)?;
+
+        let conn = self.conn.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire fuzzy index lock: {}", e))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO fuzzy_hashes (sample_id, hash, block_size, algorithm) VALUES (?1, ?2, ?3, ?4)",
+            params![sample_id, hash, block_size, self.config.algorithm.as_str()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Digest of `data` under the configured algorithm, if it is large and
+    /// varied enough to have one
+    pub fn hash(&self, data: &[u8]) -> Option<String> {
+        match self.config.algorithm {
+            FuzzyAlgorithm::SsDeep => ssdeep::hash(data).ok(),
+            FuzzyAlgorithm::Tlsh => tlsh_hash(data),
+        }
+    }
+
+    /// Find known samples whose hash scores at least the configured
+    /// `min_score` against `hash`, best match first
+    pub fn similar(&self, hash: &str) -> Result<Vec<(String, u8)>> {
+        self.query(hash, self.config.min_score)
+    }
+
+    /// Find known samples whose hash scores at least `min_score` (0-100)
+    /// against `hash`, best match first
+    pub fn query(&self, hash: &str, min_score: u8) -> Result<Vec<(String, u8)>> {
+        let block_size = self.block_size_of(hash)?;
+
+        let candidates: Vec<(String, String)> = {
+            let conn = self.conn.lock()
+                .map_err(|e| anyhow::anyhow!("Failed to acquire fuzzy index lock: {}", e))?;
+
+            let mut stmt = conn.prepare_cached(
+                "SELECT sample_id, hash FROM fuzzy_hashes WHERE algorithm = ?1 AND block_size IN (?2, ?3, ?4)"
+            )?;
+
+            let rows = stmt.query_map(
+                params![self.config.algorithm.as_str(), block_size, block_size * 2, block_size / 2],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+
+        let mut matches: Vec<(String, u8)> = candidates
+            .into_iter()
+            .filter_map(|(sample_id, known)| {
+                let score = self.compare(hash, &known)?;
+                (score > 0 && score >= min_score).then_some((sample_id, score))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(matches)
+    }
+
+    /// Number of hashes in the index
+    pub fn len(&self) -> Result<usize> {
+        let conn = self.conn.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire fuzzy index lock: {}", e))?;
+
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM fuzzy_hashes", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Similarity (0-100) of two hashes under the configured algorithm
+    pub fn compare(&self, a: &str, b: &str) -> Option<u8> {
+        match self.config.algorithm {
+            FuzzyAlgorithm::SsDeep => ssdeep::compare(a, b).ok(),
+            FuzzyAlgorithm::Tlsh => {
+                let distance = tlsh_distance(a, b)?;
+                Some(100u32.saturating_sub(distance) as u8)
+            }
+        }
+    }
+
+    fn block_size_of(&self, hash: &str) -> Result<i64> {
+        match self.config.algorithm {
+            FuzzyAlgorithm::SsDeep => Self::block_size(hash),
+            FuzzyAlgorithm::Tlsh => parse_tlsh(hash)
+                .map(|_| 0)
+                .ok_or_else(|| anyhow::anyhow!("Invalid TLSH hash: {}", hash)),
+        }
+    }
+
+    /// Parse the block size prefix of an SSDEEP hash (`blocksize:hash1:hash2`)
+    fn block_size(hash: &str) -> Result<i64> {
+        hash.split(':')
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|&size| size > 0)
+            .ok_or_else(|| anyhow::anyhow!("Invalid SSDEEP hash: {}", hash))
+    }
+}
+
+/// Pearson permutation TLSH hashes its byte triplets with
+const TLSH_V_TABLE: [u8; 256] = [
+    1, 87, 49, 12, 176, 178, 102, 166, 121, 193, 6, 84, 249, 230, 44, 163,
+    14, 197, 213, 181, 161, 85, 218, 80, 64, 239, 24, 226, 236, 142, 38, 200,
+    110, 177, 104, 103, 141, 253, 255, 50, 77, 101, 81, 18, 45, 96, 31, 222,
+    25, 107, 190, 70, 86, 237, 240, 34, 72, 242, 20, 214, 244, 227, 149, 235,
+    97, 234, 57, 22, 60, 250, 82, 175, 208, 5, 127, 199, 111, 62, 135, 248,
+    174, 169, 211, 58, 66, 154, 106, 195, 245, 171, 17, 187, 182, 179, 0, 243,
+    132, 56, 148, 75, 128, 133, 158, 100, 130, 126, 91, 13, 153, 246, 216, 219,
+    119, 68, 223, 78, 83, 88, 201, 99, 122, 11, 92, 32, 136, 114, 52, 10,
+    138, 30, 48, 183, 156, 35, 61, 26, 143, 74, 251, 94, 129, 162, 63, 152,
+    170, 7, 115, 167, 241, 206, 3, 150, 55, 59, 151, 220, 90, 53, 23, 131,
+    125, 173, 15, 238, 79, 95, 89, 16, 105, 137, 225, 224, 217, 160, 37, 123,
+    118, 73, 2, 157, 46, 116, 9, 145, 134, 228, 207, 212, 202, 215, 69, 229,
+    27, 188, 67, 124, 168, 252, 42, 4, 29, 108, 21, 247, 19, 205, 39, 203,
+    233, 40, 186, 147, 198, 192, 155, 33, 164, 191, 98, 204, 165, 180, 117, 76,
+    140, 36, 210, 172, 41, 54, 159, 8, 185, 232, 113, 196, 231, 47, 146, 120,
+    51, 65, 28, 144, 254, 221, 93, 189, 194, 139, 112, 43, 71, 109, 184, 209,
+];
+
+/// TLSH needs this many bytes before its bucket counts mean anything
+const TLSH_MIN_DATA_LENGTH: usize = 50;
+
+/// Buckets the digest body encodes; the hash spreads over twice as many
+const TLSH_BUCKETS: usize = 128;
+
+fn tlsh_mapping(salt: u8, i: u8, j: u8, k: u8) -> u8 {
+    let mut h = TLSH_V_TABLE[salt as usize];
+    h = TLSH_V_TABLE[(h ^ i) as usize];
+    h = TLSH_V_TABLE[(h ^ j) as usize];
+    TLSH_V_TABLE[(h ^ k) as usize]
+}
+
+/// Log-scale length field of the TLSH header
+fn tlsh_lvalue(len: usize) -> u8 {
+    let len = len as f64;
+    let value = if len <= 656.0 {
+        len.ln() / 1.5f64.ln()
+    } else if len <= 3199.0 {
+        len.ln() / 1.3f64.ln() - 8.72777
+    } else {
+        len.ln() / 1.1f64.ln() - 62.5472
+    };
+    (value.floor() as u32 & 0xFF) as u8
+}
+
+/// TLSH digest of `data` (128 buckets, 1-byte checksum, `T1` prefix), or
+/// `None` when it is too short or too uniform to fill the buckets
+pub fn tlsh_hash(data: &[u8]) -> Option<String> {
+    if data.len() < TLSH_MIN_DATA_LENGTH {
+        return None;
+    }
+
+    let mut buckets = [0u32; 256];
+    let mut checksum = 0u8;
+    for window in data.windows(5) {
+        // window[4] is the newest byte, window[0] the oldest
+        let [w4, w3, w2, w1, w0] = [window[0], window[1], window[2], window[3], window[4]];
+        checksum = tlsh_mapping(0, w0, w1, checksum);
+        for (salt, a, b) in [(2, w1, w2), (3, w1, w3), (5, w2, w3), (7, w2, w4), (11, w1, w4), (13, w3, w4)] {
+            buckets[tlsh_mapping(salt, w0, a, b) as usize] += 1;
+        }
+    }
+
+    let buckets = &buckets[..TLSH_BUCKETS];
+    if buckets.iter().filter(|&&count| count > 0).count() <= TLSH_BUCKETS / 2 {
+        return None;
+    }
+    let mut sorted = buckets.to_vec();
+    sorted.sort_unstable();
+    let (q1, q2, q3) = (sorted[TLSH_BUCKETS / 4 - 1], sorted[TLSH_BUCKETS / 2 - 1], sorted[TLSH_BUCKETS * 3 / 4 - 1]);
+    if q3 == 0 {
+        return None;
+    }
+
+    // Four buckets per byte, two bits each for the quartile they fall in;
+    // the digest lists the bytes last bucket first
+    let mut body = [0u8; TLSH_BUCKETS / 4];
+    for (i, code) in body.iter_mut().rev().enumerate() {
+        for (j, &count) in buckets[i * 4..i * 4 + 4].iter().enumerate() {
+            let quartile = if count > q3 { 3 } else if count > q2 { 2 } else if count > q1 { 1 } else { 0 };
+            *code |= quartile << (j * 2);
+        }
+    }
+
+    let q1_ratio = ((q1 as u64 * 100 / q3 as u64) % 16) as u8;
+    let q2_ratio = ((q2 as u64 * 100 / q3 as u64) % 16) as u8;
+    let header = [checksum.rotate_left(4), tlsh_lvalue(data.len()).rotate_left(4), (q1_ratio << 4) | q2_ratio];
+
+    let mut digest = String::with_capacity(72);
+    digest.push_str("T1");
+    for byte in header.iter().chain(body.iter()) {
+        digest.push_str(&format!("{:02X}", byte));
+    }
+    Some(digest)
+}
+
+/// Header and body of a TLSH hash
+struct Tlsh {
+    checksum: u8,
+    lvalue: u8,
+    q1_ratio: u8,
+    q2_ratio: u8,
+    body: [u8; 32],
+}
+
+/// Decode a 128-bucket, 1-byte checksum TLSH digest, with or without the
+/// `T1` version prefix
+fn parse_tlsh(hash: &str) -> Option<Tlsh> {
+    let hex = hash.strip_prefix("T1").unwrap_or(hash);
+    if hex.len() != 70 || !hex.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; 35];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    // Checksum and length are written nibble-swapped; the ratio byte
+    // carries Q1 in its high nibble once swapped the same way
+    Some(Tlsh {
+        checksum: bytes[0].rotate_left(4),
+        lvalue: bytes[1].rotate_left(4),
+        q1_ratio: bytes[2] >> 4,
+        q2_ratio: bytes[2] & 0x0F,
+        body: bytes[3..].try_into().ok()?,
+    })
+}
+
+/// TLSH distance between two digests, length included: 0 for identical
+/// files, growing without a fixed bound as they diverge
+fn tlsh_distance(a: &str, b: &str) -> Option<u32> {
+    let (a, b) = (parse_tlsh(a)?, parse_tlsh(b)?);
+
+    fn mod_diff(x: u8, y: u8, range: u32) -> u32 {
+        let (x, y) = (x as u32, y as u32);
+        let direct = x.abs_diff(y);
+        direct.min(range - direct)
+    }
+
+    let mut distance = 0;
+
+    distance += match mod_diff(a.lvalue, b.lvalue, 256) {
+        diff @ (0 | 1) => diff,
+        diff => diff * 12,
+    };
+    for (x, y) in [(a.q1_ratio, b.q1_ratio), (a.q2_ratio, b.q2_ratio)] {
+        distance += match mod_diff(x, y, 16) {
+            diff @ (0 | 1) => diff,
+            diff => (diff - 1) * 12,
+        };
+    }
+    if a.checksum != b.checksum {
+        distance += 1;
+    }
+
+    // Each body byte packs four 2-bit bucket quartiles; opposite quartiles
+    // count double
+    for (x, y) in a.body.iter().zip(b.body.iter()) {
+        for shift in [0, 2, 4, 6] {
+            distance += match ((x >> shift) & 3).abs_diff((y >> shift) & 3) {
+                3 => 6,
+                diff => diff as u32,
+            };
+        }
+    }
+
+    Some(distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(seed: u32, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state & 0xff) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_query_returns_similar_in_score_order() {
+        let index = FuzzyIndex::new(":memory:").unwrap();
+
+        let original = pseudo_random(0x1234_5678, 32 * 1024);
+
+        let mut lightly_patched = original.clone();
+        lightly_patched[1000..1064].copy_from_slice(&[0x90; 64]);
+
+        let mut heavily_patched = original.clone();
+        for chunk in heavily_patched.chunks_mut(4096).skip(1).step_by(2) {
+            chunk[..512].copy_from_slice(&[0xcc; 512]);
+        }
+
+        let unrelated = pseudo_random(0xdead_beef, 32 * 1024);
+
+        index.insert("light", &ssdeep::hash(&lightly_patched).unwrap()).unwrap();
+        index.insert("heavy", &ssdeep::hash(&heavily_patched).unwrap()).unwrap();
+        index.insert("unrelated", &ssdeep::hash(&unrelated).unwrap()).unwrap();
+        assert_eq!(index.len().unwrap(), 3);
+
+        let results = index.query(&ssdeep::hash(&original).unwrap(), 10).unwrap();
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+
+        assert_eq!(ids, vec!["light", "heavy"]);
+        assert!(results[0].1 > results[1].1);
+        assert!(results.iter().all(|(_, score)| *score >= 10));
+    }
+
+    #[test]
+    fn test_algorithm_and_min_score_from_config() {
+        let original = pseudo_random(0x1234_5678, 32 * 1024);
+        let mut patched = original.clone();
+        patched[1000..1064].copy_from_slice(&[0x90; 64]);
+
+        let ssdeep_index = FuzzyIndex::new(":memory:").unwrap();
+        let tlsh_index = FuzzyIndex::new(":memory:").unwrap()
+            .with_config(FuzzyConfig { algorithm: FuzzyAlgorithm::Tlsh, min_score: 0 });
+
+        let ssdeep_original = ssdeep_index.hash(&original).unwrap();
+        let ssdeep_patched = ssdeep_index.hash(&patched).unwrap();
+        let tlsh_original = tlsh_index.hash(&original).unwrap();
+        let tlsh_patched = tlsh_index.hash(&patched).unwrap();
+        assert!(tlsh_original.starts_with("T1") && tlsh_original.len() == 72);
+
+        let ssdeep_score = ssdeep_index.compare(&ssdeep_original, &ssdeep_patched).unwrap();
+        let tlsh_score = tlsh_index.compare(&tlsh_original, &tlsh_patched).unwrap();
+        assert!(ssdeep_score > 0);
+        assert!(tlsh_score > 0 && tlsh_score < 100);
+
+        let ssdeep_index = ssdeep_index
+            .with_config(FuzzyConfig { algorithm: FuzzyAlgorithm::SsDeep, min_score: ssdeep_score });
+        let tlsh_index = tlsh_index
+            .with_config(FuzzyConfig { algorithm: FuzzyAlgorithm::Tlsh, min_score: tlsh_score });
+        ssdeep_index.insert("patched", &ssdeep_patched).unwrap();
+        tlsh_index.insert("patched", &tlsh_patched).unwrap();
+
+        assert_eq!(ssdeep_index.similar(&ssdeep_original).unwrap(), vec![("patched".to_string(), ssdeep_score)]);
+        assert_eq!(tlsh_index.similar(&tlsh_original).unwrap(), vec![("patched".to_string(), tlsh_score)]);
+
+        // Each index only reads hashes of its own algorithm
+        assert!(tlsh_index.similar(&ssdeep_original).is_err());
+
+        assert!(ssdeep_index.query(&ssdeep_original, ssdeep_score + 1).unwrap().is_empty());
+        assert!(tlsh_index.query(&tlsh_original, tlsh_score + 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tlsh_tracks_similarity() {
+        let original = pseudo_random(0x1234_5678, 32 * 1024);
+        let mut patched = original.clone();
+        patched[1000..1064].copy_from_slice(&[0x90; 64]);
+        let unrelated = pseudo_random(0xdead_beef, 32 * 1024);
+
+        let digest = tlsh_hash(&original).unwrap();
+        assert_eq!(tlsh_hash(&original).as_ref(), Some(&digest));
+        assert_eq!(tlsh_distance(&digest, &digest), Some(0));
+
+        let near = tlsh_distance(&digest, &tlsh_hash(&patched).unwrap()).unwrap();
+        let far = tlsh_distance(&digest, &tlsh_hash(&unrelated).unwrap()).unwrap();
+        assert!(near < far, "patched {} vs unrelated {}", near, far);
+
+        // Too short, or too uniform to fill half the buckets
+        assert_eq!(tlsh_hash(&original[..TLSH_MIN_DATA_LENGTH - 1]), None);
+        assert_eq!(tlsh_hash(&[0u8; 4096]), None);
+    }
+
+    #[test]
+    fn test_insert_rejects_malformed_hash() {
+        let index = FuzzyIndex::new(":memory:").unwrap();
+        assert!(index.insert("bad", "not-a-hash").is_err());
+        assert!(index.is_empty().unwrap());
+    }
+}
@@ -10,6 +10,8 @@
  * - Use Mutex for thread-safe access (Connection is Send but not Sync)
  */
 
+pub mod fuzzy_index;
+
 use rusqlite::{Connection, params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
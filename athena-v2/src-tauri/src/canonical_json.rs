@@ -0,0 +1,74 @@
+//! Canonical JSON: one byte-for-byte form per logical value
+//!
+//! Signing and golden comparisons need two equal results to serialize
+//! identically, which plain `serde_json` doesn't promise: map key order
+//! follows insertion (or hashing) order, and `1.0` and `1` print
+//! differently. The canonical form sorts every object's keys, writes
+//! integral floats as integers, and has no whitespace.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+/// Largest integer an f64 holds exactly; integral floats beyond it keep
+/// their float form
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// `value` in canonical form as a compact JSON string
+pub fn to_canonical_json<T: Serialize>(value: &T) -> Result<String> {
+    let value = serde_json::to_value(value).context("Failed to serialize value to JSON")?;
+    Ok(serde_json::to_string(&canonicalize(value))?)
+}
+
+/// Sort object keys at every level and normalize numbers. Keys are
+/// reinserted in sorted order, which holds whether or not serde_json is
+/// keeping insertion order.
+pub fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(k, v)| (k, canonicalize(v))).collect::<Map<_, _>>())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        Value::Number(n) => Value::Number(normalize_number(n)),
+        other => other,
+    }
+}
+
+/// `7.0` and `-0.0` become `7` and `0`
+fn normalize_number(n: Number) -> Number {
+    match n.as_f64() {
+        Some(f) if n.is_f64() && f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER => Number::from(f as i64),
+        _ => n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Serialize)]
+    struct Verdict<M: Serialize> {
+        score: f64,
+        tags: M,
+    }
+
+    #[test]
+    fn test_differently_ordered_maps_produce_identical_json() {
+        let hashed: HashMap<&str, u32> = [("zeus", 3), ("emotet", 1), ("qakbot", 2)].into_iter().collect();
+        let sorted: BTreeMap<&str, u32> = [("qakbot", 2), ("zeus", 3), ("emotet", 1)].into_iter().collect();
+        let a = to_canonical_json(&Verdict { score: 7.0, tags: hashed }).unwrap();
+        let b = to_canonical_json(&Verdict { score: 7.0, tags: sorted }).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a, r#"{"score":7,"tags":{"emotet":1,"qakbot":2,"zeus":3}}"#);
+
+        // The same result parsed from JSON written in another order and
+        // with integral numbers spelled as floats
+        let reordered: Value = serde_json::from_str(r#"{"tags": {"zeus": 3.0, "qakbot": 2, "emotet": 1}, "score": 7}"#).unwrap();
+        assert_eq!(to_canonical_json(&reordered).unwrap(), a);
+
+        assert_eq!(to_canonical_json(&[-0.0, 0.5, 1e300]).unwrap(), "[0,0.5,1e300]");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::commands::encoded_args::{decode_command_line, DecodedArgument};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -15,6 +16,23 @@ pub struct BehavioralAnalysis {
     file_operations: Vec<FileOperation>,
     process_activity: Vec<ProcessBehavior>,
     registry_modifications: Vec<RegistryChange>,
+    host_iocs: Vec<HostIoc>,
+    /// Base64 and hex payloads decoded from process command lines
+    #[serde(default)]
+    decoded_arguments: Vec<DecodedArgument>,
+    verdict: BehaviorVerdict,
+}
+
+/// Detections below this confidence don't contribute threat categories
+/// unless the caller asks otherwise
+pub const DEFAULT_MIN_TECHNIQUE_CONFIDENCE: f32 = 0.0;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BehaviorVerdict {
+    /// "malicious", "suspicious" or "clean"
+    verdict: String,
+    threat_categories: Vec<String>,
+    mitre_techniques: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,6 +42,45 @@ pub struct BehaviorPattern {
     severity: String,
     confidence: f32,
     evidence: Vec<String>,
+    #[serde(default)]
+    mitre_technique: Option<String>,
+}
+
+/// A Windows API call observed in the sandbox, in execution order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiCallEvent {
+    api: String,
+    #[serde(default)]
+    arguments: HashMap<String, String>,
+}
+
+/// `CREATE_SUSPENDED` process creation flag
+const CREATE_SUSPENDED: u64 = 0x4;
+
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+/// Directory the task scheduler loads task XML from, lowercased
+const TASK_STORE: &str = r"\system32\tasks\";
+
+/// WMI consumer classes that execute code when their filter fires
+const WMI_CONSUMERS: [&str; 2] = ["CommandLineEventConsumer", "ActiveScriptEventConsumer"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HostIocType {
+    Mutex,
+    NamedPipe,
+    Event,
+}
+
+/// A named kernel object the sample creates or opens. These names are
+/// often unique to a family and make good host-based indicators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostIoc {
+    r#type: HostIocType,
+    value: String,
+    /// The API call that used the name, or "strings" if it was only found
+    /// in the sample's strings
+    source: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -121,6 +178,7 @@ pub async fn analyze_behavior(
     runtime: tauri::State<'_, std::sync::Arc<std::sync::Mutex<Option<crate::commands::wasm_runtime::WasmRuntime>>>>,
     file_hash: String,
     file_data: Vec<u8>,
+    min_technique_confidence: Option<f32>,
 ) -> Result<BehavioralAnalysis, String> {
     use crate::commands::wasm_runtime;
 
@@ -168,10 +226,28 @@ pub async fn analyze_behavior(
                             .collect()
                     })
                     .unwrap_or_default(),
+                mitre_technique: behavior["mitre_technique"].as_str().map(|s| s.to_string()),
             });
         }
     }
 
+    // Recognize specific injection techniques from the ordered API trace
+    let api_calls: Vec<ApiCallEvent> = sandbox_result["api_calls"]
+        .as_array()
+        .map(|api_array| {
+            api_array
+                .iter()
+                .filter_map(|call| match call.as_str() {
+                    Some(api) => Some(ApiCallEvent { api: api.to_string(), arguments: HashMap::new() }),
+                    None => serde_json::from_value(call.clone()).ok(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    behaviors.extend(detect_injection_techniques(&api_calls));
+
+    let host_iocs = extract_host_iocs(&api_calls, &file_data);
+
     // Extract network activity
     let mut network_activity = Vec::new();
     if let Some(network_array) = sandbox_result["network_activity"].as_array() {
@@ -218,6 +294,25 @@ pub async fn analyze_behavior(
         }
     }
 
+    let decoded_arguments: Vec<DecodedArgument> = process_activity
+        .iter()
+        .filter_map(|p| Some(decode_command_line(&p.process_name, p.command_line.as_deref()?)))
+        .flatten()
+        .collect();
+    if !decoded_arguments.is_empty() {
+        behaviors.push(BehaviorPattern {
+            r#type: "defense_evasion".to_string(),
+            description: "Encoded payload in process command line".to_string(),
+            severity: "high".to_string(),
+            confidence: 0.8,
+            evidence: decoded_arguments
+                .iter()
+                .map(|d| format!("{}: {}", d.process, d.decoded))
+                .collect(),
+            mitre_technique: Some("T1027".to_string()),
+        });
+    }
+
     // Extract persistence mechanisms
     let mut persistence = Vec::new();
     if let Some(persist_array) = sandbox_result["persistence"].as_array() {
@@ -231,6 +326,24 @@ pub async fn analyze_behavior(
         }
     }
 
+    // Scheduled task and WMI persistence the sandbox report doesn't name
+    let mut persistence_evidence: Vec<String> = process_activity
+        .iter()
+        .filter_map(|p| p.command_line.clone())
+        .chain(decoded_arguments.iter().map(|d| d.decoded.clone()))
+        .chain(api_calls.iter().flat_map(|c| c.arguments.values().cloned()))
+        .collect();
+    persistence_evidence.extend(
+        crate::commands::file_analysis::extract_strings(&file_data, 4)
+            .into_iter()
+            .map(|s| s.value),
+    );
+    for mechanism in detect_persistence(&file_operations, &api_calls, &persistence_evidence) {
+        if !persistence.iter().any(|p| p.technique == mechanism.technique && p.location == mechanism.location) {
+            persistence.push(mechanism);
+        }
+    }
+
     // Extract registry modifications
     let mut registry_modifications = Vec::new();
     if let Some(reg_array) = sandbox_result["registry_modifications"].as_array() {
@@ -250,6 +363,13 @@ pub async fn analyze_behavior(
     let risk_score = sandbox_result["risk_score"].as_u64().unwrap_or(0) as u32;
     let sandbox_escape = sandbox_result["sandbox_escape"].as_bool().unwrap_or(false);
 
+    let verdict = generate_verdict(
+        &behaviors,
+        &persistence,
+        risk_score,
+        min_technique_confidence.unwrap_or(DEFAULT_MIN_TECHNIQUE_CONFIDENCE),
+    );
+
     Ok(BehavioralAnalysis {
         id: file_hash,
         timestamp,
@@ -261,6 +381,277 @@ pub async fn analyze_behavior(
         file_operations,
         process_activity,
         registry_modifications,
+        host_iocs,
+        decoded_arguments,
+        verdict,
+    })
+}
+
+/// Summarize the detections into a verdict
+///
+/// Behaviors below `min_technique_confidence` stay in the report but don't
+/// add threat categories or techniques, so a handful of weak detections
+/// can't inflate the verdict.
+fn generate_verdict(
+    behaviors: &[BehaviorPattern],
+    persistence: &[PersistenceMechanism],
+    risk_score: u32,
+    min_technique_confidence: f32,
+) -> BehaviorVerdict {
+    let mut threat_categories = Vec::new();
+    let mut mitre_techniques = Vec::new();
+    let mut critical = false;
+
+    for behavior in behaviors.iter().filter(|b| b.confidence >= min_technique_confidence) {
+        if !threat_categories.contains(&behavior.r#type) {
+            threat_categories.push(behavior.r#type.clone());
+        }
+        if let Some(technique) = &behavior.mitre_technique {
+            if !mitre_techniques.contains(technique) {
+                mitre_techniques.push(technique.clone());
+            }
+        }
+        critical |= behavior.severity == "critical";
+    }
+
+    if !persistence.is_empty() && !threat_categories.iter().any(|c| c == "persistence") {
+        threat_categories.push("persistence".to_string());
+    }
+    for technique in persistence.iter().filter_map(|p| p.mitre_technique.as_ref()) {
+        if !mitre_techniques.contains(technique) {
+            mitre_techniques.push(technique.clone());
+        }
+    }
+
+    let verdict = if critical || risk_score >= 70 {
+        "malicious"
+    } else if !threat_categories.is_empty() || risk_score >= 30 {
+        "suspicious"
+    } else {
+        "clean"
+    };
+
+    BehaviorVerdict {
+        verdict: verdict.to_string(),
+        threat_categories,
+        mitre_techniques,
+    }
+}
+
+/// Recognize process injection techniques that are only identifiable by the
+/// order of their API calls
+fn detect_injection_techniques(calls: &[ApiCallEvent]) -> Vec<BehaviorPattern> {
+    let mut detections = Vec::new();
+
+    // Suspended process -> unmap original image -> write payload -> redirect
+    // the main thread -> resume
+    let hollowing: [&dyn Fn(&ApiCallEvent) -> bool; 5] = [
+        &|c| api_is(c, &["CreateProcess", "NtCreateUserProcess"]) && is_suspended_creation(c),
+        &|c| api_is(c, &["NtUnmapViewOfSection", "ZwUnmapViewOfSection"]),
+        &|c| api_is(c, &["WriteProcessMemory", "NtWriteVirtualMemory", "ZwWriteVirtualMemory"]),
+        &|c| api_is(c, &["SetThreadContext", "Wow64SetThreadContext", "NtSetContextThread"]),
+        &|c| api_is(c, &["ResumeThread", "NtResumeThread"]),
+    ];
+    if let Some(evidence) = match_sequence(calls, &hollowing) {
+        detections.push(BehaviorPattern {
+            r#type: "process_injection".to_string(),
+            description: "Process hollowing: suspended process image unmapped and replaced".to_string(),
+            severity: "critical".to_string(),
+            confidence: 0.95,
+            evidence,
+            mitre_technique: Some("T1055.012".to_string()),
+        });
+    }
+
+    // Payload stored in the global atom table, then pulled into the target
+    // through a queued APC
+    let atom_bombing: [&dyn Fn(&ApiCallEvent) -> bool; 2] = [
+        &|c| api_is(c, &["GlobalAddAtom"]),
+        &|c| api_is(c, &["NtQueueApcThread", "ZwQueueApcThread"]),
+    ];
+    if let Some(evidence) = match_sequence(calls, &atom_bombing) {
+        detections.push(BehaviorPattern {
+            r#type: "process_injection".to_string(),
+            description: "Atom bombing: code staged in the global atom table and injected via APC".to_string(),
+            severity: "high".to_string(),
+            confidence: 0.85,
+            evidence,
+            mitre_technique: Some("T1055.004".to_string()),
+        });
+    }
+
+    detections
+}
+
+/// Find calls matching each step in order (other calls may come between).
+/// Returns the matching calls as evidence.
+fn match_sequence(calls: &[ApiCallEvent], steps: &[&dyn Fn(&ApiCallEvent) -> bool]) -> Option<Vec<String>> {
+    let mut evidence = Vec::new();
+    let mut remaining = calls.iter().enumerate();
+
+    for step in steps {
+        let (index, call) = remaining.find(|(_, call)| step(call))?;
+        evidence.push(format!("#{} {}", index, call.api));
+    }
+
+    Some(evidence)
+}
+
+/// Whether `call` is one of `names`, ignoring the A/W/Ex/ExA/ExW suffixes
+fn api_is(call: &ApiCallEvent, names: &[&str]) -> bool {
+    let api = call.api.to_ascii_lowercase();
+    names.iter().any(|name| {
+        api.strip_prefix(&name.to_ascii_lowercase())
+            .is_some_and(|suffix| ["", "a", "w", "ex", "exa", "exw"].contains(&suffix))
+    })
+}
+
+/// Recognize scheduled task XML drops and WMI event subscriptions. The
+/// `schtasks` command line is reported by the sandbox; these are the forms
+/// that bypass it.
+///
+/// `evidence` is free text to search for WMI class names: command lines,
+/// API arguments and the sample's strings.
+fn detect_persistence(
+    file_operations: &[FileOperation],
+    calls: &[ApiCallEvent],
+    evidence: &[String],
+) -> Vec<PersistenceMechanism> {
+    let mut mechanisms: Vec<PersistenceMechanism> = Vec::new();
+
+    // Task definitions written straight into the task store are picked up
+    // by the scheduler without ever running schtasks.exe
+    let written_paths = file_operations
+        .iter()
+        .filter(|op| !op.operation.eq_ignore_ascii_case("read") && !op.operation.eq_ignore_ascii_case("delete"))
+        .map(|op| op.path.as_str())
+        .chain(
+            calls
+                .iter()
+                .filter(|c| api_is(c, &["CreateFile", "NtCreateFile", "MoveFile", "CopyFile"]))
+                .filter_map(object_name),
+        );
+    for path in written_paths {
+        let Some(start) = path.to_ascii_lowercase().find(TASK_STORE) else {
+            continue;
+        };
+        let task_name = &path[start + TASK_STORE.len()..];
+        if task_name.is_empty() || mechanisms.iter().any(|m| m.location == path) {
+            continue;
+        }
+        mechanisms.push(PersistenceMechanism {
+            technique: "Scheduled Task".to_string(),
+            location: path.to_string(),
+            details: format!("Task definition \"{}\" written to the task store", task_name),
+            mitre_technique: Some("T1053.005".to_string()),
+        });
+    }
+
+    // A permanent subscription needs a filter and a consumer that runs
+    // something; the binding between them is usually present too
+    let mentions = |class: &str| evidence.iter().any(|e| e.contains(class));
+    let consumers: Vec<&str> = WMI_CONSUMERS.iter().copied().filter(|c| mentions(c)).collect();
+    if mentions("__EventFilter") && !consumers.is_empty() {
+        let mut details = format!("__EventFilter with {}", consumers.join(", "));
+        if mentions("__FilterToConsumerBinding") {
+            details.push_str(" bound by __FilterToConsumerBinding");
+        }
+        mechanisms.push(PersistenceMechanism {
+            technique: "WMI Event Subscription".to_string(),
+            location: r"root\subscription".to_string(),
+            details,
+            mitre_technique: Some("T1546.003".to_string()),
+        });
+    }
+
+    mechanisms
+}
+
+/// Mutex, named pipe and event names from the API trace, plus any the
+/// sample's strings reveal that weren't exercised at runtime
+fn extract_host_iocs(calls: &[ApiCallEvent], data: &[u8]) -> Vec<HostIoc> {
+    let mut iocs: Vec<HostIoc> = Vec::new();
+    let mut add = |r#type: HostIocType, value: &str, source: &str| {
+        if !value.is_empty() && !iocs.iter().any(|i| i.r#type == r#type && i.value == value) {
+            iocs.push(HostIoc { r#type, value: value.to_string(), source: source.to_string() });
+        }
+    };
+
+    for call in calls {
+        let Some(name) = object_name(call) else {
+            continue;
+        };
+        if api_is(call, &["CreateMutex", "OpenMutex"]) {
+            add(HostIocType::Mutex, name, &call.api);
+        } else if api_is(call, &["CreateEvent", "OpenEvent"]) {
+            add(HostIocType::Event, name, &call.api);
+        } else if name.to_ascii_lowercase().starts_with(PIPE_PREFIX) {
+            add(HostIocType::NamedPipe, name, &call.api);
+        }
+    }
+
+    // Statically, a `Global\` or `Local\` object name can't be told apart
+    // by itself; go by which object APIs the sample references
+    let strings: Vec<String> = crate::commands::file_analysis::extract_strings(data, 4)
+        .into_iter()
+        .map(|s| s.value)
+        .collect();
+    let references = |apis: &[&str]| strings.iter().any(|s| apis.iter().any(|api| s.contains(api)));
+    let object_type = if references(&["CreateMutex", "OpenMutex"]) {
+        Some(HostIocType::Mutex)
+    } else if references(&["CreateEvent", "OpenEvent"]) {
+        Some(HostIocType::Event)
+    } else {
+        None
+    };
+
+    for s in &strings {
+        if let Some(start) = s.to_ascii_lowercase().find(PIPE_PREFIX) {
+            add(HostIocType::NamedPipe, s[start..].trim(), "strings");
+        } else if let Some(object_type) = object_type {
+            if is_kernel_object_name(s) {
+                add(object_type, s.trim(), "strings");
+            }
+        }
+    }
+
+    iocs
+}
+
+/// The object or file name argument of a call
+fn object_name(call: &ApiCallEvent) -> Option<&str> {
+    call.arguments
+        .iter()
+        .find(|(name, _)| ["lpname", "name", "lpfilename", "filename"].contains(&name.to_ascii_lowercase().as_str()))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Names in the session or global object namespace, e.g. `Global\qazwsx`
+fn is_kernel_object_name(s: &str) -> bool {
+    let rest = s
+        .strip_prefix("Global\\")
+        .or_else(|| s.strip_prefix("Local\\"))
+        .or_else(|| {
+            let session = s.strip_prefix("Session\\")?;
+            let (id, rest) = session.split_once('\\')?;
+            id.parse::<u32>().ok().map(|_| rest)
+        });
+    rest.is_some_and(|name| !name.is_empty() && !name.contains(['\\', ' ']))
+}
+
+fn is_suspended_creation(call: &ApiCallEvent) -> bool {
+    call.arguments.iter().any(|(name, value)| {
+        if value.contains("CREATE_SUSPENDED") {
+            return true;
+        }
+        if !name.to_ascii_lowercase().contains("flags") {
+            return false;
+        }
+        let flags = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => value.parse().ok(),
+        };
+        flags.is_some_and(|f| f & CREATE_SUSPENDED != 0)
     })
 }
 
@@ -428,6 +819,14 @@ pub struct ThreatAlert {
     pub indicators: Vec<String>,
 }
 
+/// Export the indicators from a file analysis as an OpenIOC 1.1 document
+#[command]
+pub async fn export_openioc_format(
+    result: crate::commands::file_analysis::FileAnalysisResult,
+) -> Result<String, String> {
+    Ok(crate::threat_intel::openioc::to_openioc(&result))
+}
+
 #[command]
 pub async fn export_stix_format(
     analysis_id: String,
@@ -951,4 +1350,220 @@ pub struct SharePlatformResult {
     pub message: String,
     pub event_id: Option<String>,
     pub url: Option<String>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(api: &str) -> ApiCallEvent {
+        ApiCallEvent { api: api.to_string(), arguments: HashMap::new() }
+    }
+
+    fn create_process(flags: &str) -> ApiCallEvent {
+        let mut c = call("CreateProcessW");
+        c.arguments.insert("dwCreationFlags".to_string(), flags.to_string());
+        c
+    }
+
+    #[test]
+    fn test_detects_process_hollowing() {
+        let calls = vec![
+            create_process("0x00000004"),
+            call("VirtualAllocEx"),
+            call("NtUnmapViewOfSection"),
+            call("WriteProcessMemory"),
+            call("GetThreadContext"),
+            call("SetThreadContext"),
+            call("ResumeThread"),
+        ];
+
+        let detections = detect_injection_techniques(&calls);
+
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].mitre_technique.as_deref(), Some("T1055.012"));
+        assert!(detections[0].description.starts_with("Process hollowing"));
+        assert_eq!(detections[0].evidence, vec![
+            "#0 CreateProcessW",
+            "#2 NtUnmapViewOfSection",
+            "#3 WriteProcessMemory",
+            "#5 SetThreadContext",
+            "#6 ResumeThread",
+        ]);
+    }
+
+    #[test]
+    fn test_hollowing_requires_order_and_suspended_process() {
+        // Not created suspended
+        let calls = vec![
+            create_process("0"),
+            call("NtUnmapViewOfSection"),
+            call("WriteProcessMemory"),
+            call("SetThreadContext"),
+            call("ResumeThread"),
+        ];
+        assert!(detect_injection_techniques(&calls).is_empty());
+
+        // Thread resumed before its context was changed
+        let calls = vec![
+            create_process("CREATE_SUSPENDED"),
+            call("NtUnmapViewOfSection"),
+            call("WriteProcessMemory"),
+            call("ResumeThread"),
+            call("SetThreadContext"),
+        ];
+        assert!(detect_injection_techniques(&calls).is_empty());
+    }
+
+    #[test]
+    fn test_verdict_excludes_low_confidence_techniques() {
+        let behaviors = vec![
+            BehaviorPattern {
+                r#type: "process_injection".to_string(),
+                description: "Remote thread created in another process".to_string(),
+                severity: "high".to_string(),
+                confidence: 0.3,
+                evidence: vec!["CreateRemoteThread".to_string()],
+                mitre_technique: Some("T1055".to_string()),
+            },
+            BehaviorPattern {
+                r#type: "communication".to_string(),
+                description: "HTTP beacon".to_string(),
+                severity: "medium".to_string(),
+                confidence: 0.9,
+                evidence: vec![],
+                mitre_technique: Some("T1071.001".to_string()),
+            },
+        ];
+
+        let default = generate_verdict(&behaviors, &[], 10, DEFAULT_MIN_TECHNIQUE_CONFIDENCE);
+        assert_eq!(default.threat_categories, vec!["process_injection", "communication"]);
+        assert_eq!(default.mitre_techniques, vec!["T1055", "T1071.001"]);
+
+        let raised = generate_verdict(&behaviors, &[], 10, 0.5);
+        assert_eq!(raised.threat_categories, vec!["communication"]);
+        assert_eq!(raised.mitre_techniques, vec!["T1071.001"]);
+        assert_eq!(raised.verdict, "suspicious");
+    }
+
+    fn call_with(api: &str, argument: &str, value: &str) -> ApiCallEvent {
+        let mut c = call(api);
+        c.arguments.insert(argument.to_string(), value.to_string());
+        c
+    }
+
+    #[test]
+    fn test_mutex_name_in_buffer_extracted() {
+        let mut data = b"MZ\x90\x00\x00kernel32.dll\x00CreateMutexW\x00\x00".to_vec();
+        data.extend_from_slice(b"Global\\QakBot_7f3a91\x00\x00");
+        data.extend_from_slice(b"\\\\.\\pipe\\msagent_5f\x00");
+
+        let iocs = extract_host_iocs(&[], &data);
+
+        assert_eq!(iocs, vec![
+            HostIoc {
+                r#type: HostIocType::Mutex,
+                value: "Global\\QakBot_7f3a91".to_string(),
+                source: "strings".to_string(),
+            },
+            HostIoc {
+                r#type: HostIocType::NamedPipe,
+                value: "\\\\.\\pipe\\msagent_5f".to_string(),
+                source: "strings".to_string(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_host_iocs_from_api_trace() {
+        let calls = vec![
+            call_with("CreateMutexW", "lpName", "Local\\zx81"),
+            call_with("OpenEventA", "lpName", "Global\\ready_evt"),
+            call_with("CreateFileW", "lpFileName", "\\\\.\\pipe\\postex_2a"),
+            call_with("CreateFileW", "lpFileName", "C:\\Users\\Public\\a.tmp"),
+            call_with("CreateMutexW", "lpName", "Local\\zx81"),
+        ];
+
+        let iocs = extract_host_iocs(&calls, b"");
+
+        let found: Vec<(HostIocType, &str, &str)> =
+            iocs.iter().map(|i| (i.r#type, i.value.as_str(), i.source.as_str())).collect();
+        assert_eq!(found, vec![
+            (HostIocType::Mutex, "Local\\zx81", "CreateMutexW"),
+            (HostIocType::Event, "Global\\ready_evt", "OpenEventA"),
+            (HostIocType::NamedPipe, "\\\\.\\pipe\\postex_2a", "CreateFileW"),
+        ]);
+    }
+
+    fn file_op(operation: &str, path: &str) -> FileOperation {
+        FileOperation {
+            operation: operation.to_string(),
+            path: path.to_string(),
+            process: "sample.exe".to_string(),
+            timestamp: 0,
+            suspicious: false,
+        }
+    }
+
+    #[test]
+    fn test_task_xml_drop_detected() {
+        let file_operations = vec![
+            file_op("read", r"C:\Windows\System32\Tasks\Microsoft\Windows\Defrag\ScheduledDefrag"),
+            file_op("write", r"C:\Windows\System32\Tasks\OneDrive Standalone Update Task"),
+            file_op("write", r"C:\Users\Public\update.xml"),
+        ];
+
+        let mechanisms = detect_persistence(&file_operations, &[], &[]);
+
+        assert_eq!(mechanisms.len(), 1);
+        assert_eq!(mechanisms[0].technique, "Scheduled Task");
+        assert_eq!(mechanisms[0].location, r"C:\Windows\System32\Tasks\OneDrive Standalone Update Task");
+        assert_eq!(mechanisms[0].mitre_technique.as_deref(), Some("T1053.005"));
+
+        // The same drop seen through the API trace
+        let calls = vec![call_with("CreateFileW", "lpFileName", r"c:\windows\system32\tasks\GoogleUpdateTaskUA")];
+        let mechanisms = detect_persistence(&[], &calls, &[]);
+        assert_eq!(mechanisms.len(), 1);
+        assert_eq!(mechanisms[0].mitre_technique.as_deref(), Some("T1053.005"));
+    }
+
+    #[test]
+    fn test_wmi_subscription_detected() {
+        let evidence = vec![
+            r"$f = Set-WmiInstance -Namespace root\subscription -Class __EventFilter -Arguments @{Name='upd'}".to_string(),
+            r"$c = Set-WmiInstance -Namespace root\subscription -Class CommandLineEventConsumer -Arguments @{CommandLineTemplate='powershell -w hidden -enc SQBFAFgA'}".to_string(),
+            r"Set-WmiInstance -Namespace root\subscription -Class __FilterToConsumerBinding -Arguments @{Filter=$f;Consumer=$c}".to_string(),
+        ];
+
+        let mechanisms = detect_persistence(&[], &[], &evidence);
+
+        assert_eq!(mechanisms.len(), 1);
+        assert_eq!(mechanisms[0].technique, "WMI Event Subscription");
+        assert_eq!(mechanisms[0].mitre_technique.as_deref(), Some("T1546.003"));
+        assert!(mechanisms[0].details.contains("CommandLineEventConsumer"));
+
+        // A filter alone runs nothing
+        assert!(detect_persistence(&[], &[], &evidence[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_detects_atom_bombing() {
+        let calls = vec![
+            call("OpenThread"),
+            call("GlobalAddAtomW"),
+            call("GlobalAddAtomW"),
+            call("NtQueueApcThread"),
+        ];
+
+        let detections = detect_injection_techniques(&calls);
+
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].mitre_technique.as_deref(), Some("T1055.004"));
+        assert!(detections[0].description.starts_with("Atom bombing"));
+        assert_eq!(detections[0].evidence, vec!["#1 GlobalAddAtomW", "#3 NtQueueApcThread"]);
+
+        // APC queued before anything was staged in the atom table
+        let calls = vec![call("NtQueueApcThread"), call("GlobalAddAtomA")];
+        assert!(detect_injection_techniques(&calls).is_empty());
+    }
+}
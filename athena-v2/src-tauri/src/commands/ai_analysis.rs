@@ -11,6 +11,7 @@ use crate::ai_providers::gemini::GeminiProvider;
 use crate::ai_providers::mistral::MistralProvider;
 use crate::ai_providers::groq::GroqProvider;
 use crate::ai_providers::queue_manager::QueueManager;
+use crate::ai_providers::retry::RetryConfig;
 use crate::cache::{SqliteCache, CacheConfig};
 use crate::commands::file_analysis::record_ai_request;
 use crate::secure_storage;
@@ -69,6 +70,13 @@ pub struct EnsembleSettings {
     pub consensus_threshold: u8,
     /// List of provider IDs to use in ensemble
     pub enabled_providers: Vec<String>,
+    /// Most providers queried at once; all of them when unset
+    #[serde(default)]
+    pub max_parallel: Option<usize>,
+    /// Stop waiting for providers after this many seconds and go with the
+    /// answers already in
+    #[serde(default)]
+    pub deadline_secs: Option<u64>,
 }
 
 impl Default for EnsembleSettings {
@@ -79,6 +87,8 @@ impl Default for EnsembleSettings {
             cross_validation: false,
             consensus_threshold: 75,
             enabled_providers: vec![],
+            max_parallel: None,
+            deadline_secs: None,
         }
     }
 }
@@ -94,6 +104,9 @@ pub struct EnsembleAnalysisResult {
     pub providers_queried: usize,
     /// Number of providers that succeeded
     pub providers_succeeded: usize,
+    /// Providers that failed, timed out or missed the deadline
+    #[serde(default)]
+    pub failed_providers: Vec<String>,
     /// Total processing time in milliseconds
     pub total_time_ms: u64,
 }
@@ -242,6 +255,11 @@ pub struct AIProviderConfig {
     max_tokens: Option<i32>,
     temperature: Option<f32>,
     enabled: bool,
+    /// Give up on this provider after this many seconds
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+    #[serde(default)]
+    retry: Option<RetryConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -305,7 +323,8 @@ pub async fn analyze_with_ai(
         }),
         max_tokens: config.max_tokens.unwrap_or(4096) as u32,
         temperature: config.temperature.unwrap_or(0.3),
-        timeout_secs: 30,
+        timeout_secs: config.timeout_secs.unwrap_or(30),
+        retry: config.retry,
     };
     
     // Create the appropriate provider (now returns Result per DeepWiki reqwest best practices)
@@ -541,6 +560,7 @@ async fn perform_health_check(
         max_tokens: config.max_tokens.unwrap_or(4096) as u32,
         temperature: config.temperature.unwrap_or(0.3),
         timeout_secs: 10,
+        retry: config.retry.clone(),
     };
 
     // Create provider instance and check health
@@ -739,6 +759,7 @@ pub async fn list_ai_models(provider: String) -> Result<Vec<ModelInfo>, String>
         max_tokens: config.and_then(|c| c.max_tokens).unwrap_or(4096) as u32,
         temperature: config.and_then(|c| c.temperature).unwrap_or(0.3),
         timeout_secs: 30,
+        retry: config.and_then(|c| c.retry.clone()),
     };
 
     // Create provider and fetch models
@@ -913,8 +934,8 @@ pub async fn analyze_with_ensemble(
         ));
     }
 
-    // Query all providers in parallel using tokio::join_all
-    let mut futures = Vec::new();
+    // Query the providers concurrently, each under its own timeout
+    let mut calls = Vec::new();
 
     for provider_id in &providers_to_query {
         let provider_id = provider_id.clone();
@@ -925,22 +946,17 @@ pub async fn analyze_with_ensemble(
             continue;
         };
         let request = request.clone();
+        let timeout = config.timeout_secs.map(std::time::Duration::from_secs);
 
-        let future = async move {
-            let result = analyze_with_ai(
-                provider_id.clone(),
-                config,
-                request,
-            ).await;
-            (provider_id, result)
-        };
-
-        futures.push(future);
+        calls.push((provider_id.clone(), timeout, analyze_with_ai(provider_id, config, request)));
     }
 
-    // Execute all queries in parallel
-    let results: Vec<(String, Result<AIAnalysisResult, String>)> =
-        futures::future::join_all(futures).await;
+    let results = fan_out(
+        calls,
+        settings.max_parallel.unwrap_or(providers_to_query.len()),
+        settings.deadline_secs.map(std::time::Duration::from_secs),
+    )
+    .await;
 
     // Separate successes and failures
     let mut successful_results: Vec<AIAnalysisResult> = Vec::new();
@@ -970,10 +986,57 @@ pub async fn analyze_with_ensemble(
         consensus,
         providers_queried: providers_to_query.len(),
         providers_succeeded: successful_results.len(),
+        failed_providers,
         total_time_ms,
     })
 }
 
+/// Run `calls` concurrently, at most `max_parallel` at a time, each under
+/// its own timeout. Once `deadline` passes, whatever has answered is
+/// returned and the rest are reported as failed.
+async fn fan_out<T, F>(
+    calls: Vec<(String, Option<std::time::Duration>, F)>,
+    max_parallel: usize,
+    deadline: Option<std::time::Duration>,
+) -> Vec<(String, Result<T, String>)>
+where
+    F: std::future::Future<Output = Result<T, String>>,
+{
+    use futures::StreamExt;
+
+    let provider_ids: Vec<String> = calls.iter().map(|(id, _, _)| id.clone()).collect();
+    let mut pending = futures::stream::iter(calls.into_iter().map(|(id, timeout, call)| async move {
+        let result = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, call)
+                .await
+                .unwrap_or_else(|_| Err(format!("Timed out after {}s", timeout.as_secs_f32()))),
+            None => call.await,
+        };
+        (id, result)
+    }))
+    .buffer_unordered(max_parallel.max(1));
+
+    let mut results = Vec::new();
+    let collect = async {
+        while let Some(result) = pending.next().await {
+            results.push(result);
+        }
+    };
+    match deadline {
+        Some(deadline) => {
+            let _ = tokio::time::timeout(deadline, collect).await;
+        }
+        None => collect.await,
+    }
+
+    for id in provider_ids {
+        if !results.iter().any(|(answered, _)| *answered == id) {
+            results.push((id, Err("Ensemble deadline reached before the provider answered".to_string())));
+        }
+    }
+    results
+}
+
 /// Aggregate multiple provider results into a consensus result
 fn aggregate_results(results: &[AIAnalysisResult], settings: &EnsembleSettings) -> ConsensusResult {
     if results.is_empty() {
@@ -1280,6 +1343,8 @@ mod tests {
                 max_tokens: None,
                 temperature: None,
                 enabled: false,
+                timeout_secs: None,
+                retry: None,
             },
         );
 
@@ -1304,6 +1369,8 @@ mod tests {
                 max_tokens: None,
                 temperature: None,
                 enabled: true,
+                timeout_secs: None,
+                retry: None,
             };
 
             let (circuit_state, healthy, error) = perform_health_check("unknown", &config).await;
@@ -1314,6 +1381,37 @@ mod tests {
             assert_eq!(error.unwrap(), "Unsupported provider");
         });
     }
+
+    async fn mock_provider(latency_ms: u64) -> Result<u64, String> {
+        tokio::time::sleep(std::time::Duration::from_millis(latency_ms)).await;
+        Ok(latency_ms)
+    }
+
+    #[tokio::test]
+    async fn test_ensemble_fan_out_takes_slowest_not_sum() {
+        let calls = || {
+            vec![
+                ("fast".to_string(), None, mock_provider(100)),
+                ("medium".to_string(), None, mock_provider(200)),
+                ("slow".to_string(), None, mock_provider(300)),
+            ]
+        };
+
+        let start = std::time::Instant::now();
+        let results = fan_out(calls(), 3, None).await;
+        let elapsed = start.elapsed();
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|(_, r)| r.is_ok()));
+        assert!(elapsed >= std::time::Duration::from_millis(300));
+        assert!(elapsed < std::time::Duration::from_millis(500), "took {:?}", elapsed);
+
+        // The slow provider misses the deadline; the others still count
+        let results = fan_out(calls(), 3, Some(std::time::Duration::from_millis(250))).await;
+        let failed: Vec<&str> = results.iter().filter(|(_, r)| r.is_err()).map(|(id, _)| id.as_str()).collect();
+        assert_eq!(failed, vec!["slow"]);
+        assert_eq!(results.len(), 3);
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,158 @@
+use tokio::task::JoinSet;
+
+/// Analyzer passes over one sample run at the same time by default
+pub const DEFAULT_ANALYSIS_WORKERS: usize = 4;
+
+/// One step of a pass. Steps call into WASM synchronously, so they run on
+/// the blocking thread pool rather than the async executor.
+pub type PassStep<T> = Box<dyn FnOnce() -> Result<T, String> + Send + 'static>;
+
+/// One analysis pass over a sample. Passes are independent of each other
+/// and may run concurrently; the steps inside a pass run strictly in order
+/// (e.g. disassembly, then CFG recovery, then decompilation).
+pub struct AnalysisPass<T> {
+    pub name: &'static str,
+    pub steps: Vec<PassStep<T>>,
+}
+
+impl<T> AnalysisPass<T> {
+    /// A pass with a single step
+    pub fn single(name: &'static str, step: impl FnOnce() -> Result<T, String> + Send + 'static) -> Self {
+        Self { name, steps: vec![Box::new(step)] }
+    }
+
+    /// A pass whose steps each depend on the one before
+    pub fn chain(name: &'static str, steps: Vec<PassStep<T>>) -> Self {
+        Self { name, steps }
+    }
+
+    /// Run the steps in order on the current thread. A failed step ends
+    /// the pass, since later steps depend on it.
+    fn run(self) -> Vec<T> {
+        let mut results = Vec::new();
+        for step in self.steps {
+            match step() {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    tracing::debug!("Analysis pass {} stopped: {}", self.name, e);
+                    break;
+                }
+            }
+        }
+        results
+    }
+}
+
+/// Run `passes` on blocking threads with at most `workers` in flight and
+/// merge their results in pass order. A pass that panics contributes no
+/// results; other passes carry on.
+pub async fn run_analysis_passes<T: Send + 'static>(passes: Vec<AnalysisPass<T>>, workers: usize) -> Vec<T> {
+    let mut results: Vec<Vec<T>> = passes.iter().map(|_| Vec::new()).collect();
+    let mut pending = passes.into_iter().enumerate();
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.len() < workers.max(1) {
+            let Some((index, pass)) = pending.next() else {
+                break;
+            };
+            running.spawn_blocking(move || (index, pass.run()));
+        }
+        match running.join_next().await {
+            Some(Ok((index, pass_results))) => results[index] = pass_results,
+            Some(Err(e)) => tracing::warn!("Analysis pass failed to complete: {}", e),
+            None => break,
+        }
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    type Timeline = Arc<Mutex<Vec<(&'static str, Instant, Instant)>>>;
+
+    /// A step that blocks its thread for `ms`, as a WASM call does, and
+    /// records when it ran
+    fn step(timeline: &Timeline, name: &'static str, ms: u64) -> PassStep<&'static str> {
+        let timeline = timeline.clone();
+        Box::new(move || {
+            let start = Instant::now();
+            std::thread::sleep(Duration::from_millis(ms));
+            timeline.lock().unwrap().push((name, start, Instant::now()));
+            Ok(name)
+        })
+    }
+
+    fn span(timeline: &Timeline, name: &str) -> (Instant, Instant) {
+        let timeline = timeline.lock().unwrap();
+        let (_, start, end) = timeline.iter().find(|(n, _, _)| *n == name).unwrap();
+        (*start, *end)
+    }
+
+    #[tokio::test]
+    async fn test_independent_passes_overlap_and_chains_stay_ordered() {
+        let timeline = Timeline::default();
+        let passes = vec![
+            AnalysisPass::single("patterns", step(&timeline, "pattern-match", 60)),
+            AnalysisPass::single("crypto", step(&timeline, "crypto-const", 60)),
+            AnalysisPass::single("network", step(&timeline, "network", 60)),
+            AnalysisPass::chain("code", vec![
+                step(&timeline, "disasm", 20),
+                step(&timeline, "cfg", 20),
+                step(&timeline, "decompile", 20),
+            ]),
+        ];
+
+        let results = run_analysis_passes(passes, 4).await;
+
+        // Merged in pass order regardless of finishing order
+        assert_eq!(results, vec!["pattern-match", "crypto-const", "network", "disasm", "cfg", "decompile"]);
+
+        let (pattern_start, pattern_end) = span(&timeline, "pattern-match");
+        let (crypto_start, crypto_end) = span(&timeline, "crypto-const");
+        let (network_start, _) = span(&timeline, "network");
+        assert!(crypto_start < pattern_end && pattern_start < crypto_end);
+        assert!(network_start < pattern_end);
+
+        let (_, disasm_end) = span(&timeline, "disasm");
+        let (cfg_start, cfg_end) = span(&timeline, "cfg");
+        let (decompile_start, _) = span(&timeline, "decompile");
+        assert!(disasm_end <= cfg_start);
+        assert!(cfg_end <= decompile_start);
+    }
+
+    #[tokio::test]
+    async fn test_single_worker_runs_sequentially() {
+        let timeline = Timeline::default();
+        let passes = vec![
+            AnalysisPass::single("patterns", step(&timeline, "pattern-match", 20)),
+            AnalysisPass::single("crypto", step(&timeline, "crypto-const", 20)),
+        ];
+
+        run_analysis_passes(passes, 1).await;
+
+        let (_, pattern_end) = span(&timeline, "pattern-match");
+        let (crypto_start, _) = span(&timeline, "crypto-const");
+        assert!(pattern_end <= crypto_start);
+    }
+
+    #[tokio::test]
+    async fn test_failed_step_ends_only_its_pass() {
+        let timeline = Timeline::default();
+        let passes = vec![
+            AnalysisPass::chain("code", vec![
+                Box::new(|| Err("unsupported architecture".to_string())),
+                step(&timeline, "cfg", 1),
+            ]),
+            AnalysisPass::single("crypto", step(&timeline, "crypto-const", 1)),
+            AnalysisPass::single("panics", || panic!("module trapped")),
+        ];
+
+        assert_eq!(run_analysis_passes(passes, 2).await, vec!["crypto-const"]);
+    }
+}
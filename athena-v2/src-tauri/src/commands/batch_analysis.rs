@@ -0,0 +1,230 @@
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::path::SafePathBuf;
+
+use crate::commands::file_analysis::{analyze_file, AnalysisConfig, FileAnalysisResult};
+use crate::threat_intel::correlation::{correlate_samples, CorrelationReport};
+
+/// How a batch of samples should be analyzed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysisProfile {
+    #[serde(default)]
+    pub config: AnalysisConfig,
+    /// Maximum number of files analyzed at the same time
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// SHA-256 hashes of known-good files to skip
+    #[serde(default)]
+    pub known_good_sha256: HashSet<String>,
+}
+
+fn default_max_concurrency() -> usize { 4 }
+
+impl Default for AnalysisProfile {
+    fn default() -> Self {
+        Self {
+            config: AnalysisConfig::default(),
+            max_concurrency: default_max_concurrency(),
+            known_good_sha256: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItemResult {
+    Analyzed { result: Box<FileAnalysisResult> },
+    SkippedKnownGood { sha256: String },
+    Failed { error: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub analyzed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchAnalysisResult {
+    pub results: Vec<(PathBuf, BatchItemResult)>,
+    pub summary: BatchSummary,
+    /// Analyzed samples grouped by the indicators they share
+    #[serde(default)]
+    pub correlation: CorrelationReport,
+}
+
+/// Analyze every path with at most `profile.max_concurrency` files in flight.
+/// Results are returned in input order.
+pub async fn analyze_batch(paths: &[PathBuf], profile: AnalysisProfile) -> BatchAnalysisResult {
+    let config = profile.config.clone();
+    analyze_batch_with(paths, &profile, move |path| {
+        let config = config.clone();
+        async move {
+            let safe_path = SafePathBuf::new(path)
+                .map_err(|e| format!("Invalid path: {}", e))?;
+            analyze_file(safe_path, Some(config)).await
+        }
+    })
+    .await
+}
+
+async fn analyze_batch_with<F, Fut>(
+    paths: &[PathBuf],
+    profile: &AnalysisProfile,
+    analyze: F,
+) -> BatchAnalysisResult
+where
+    F: Fn(PathBuf) -> Fut,
+    Fut: Future<Output = Result<FileAnalysisResult, String>>,
+{
+    let start = Instant::now();
+    let analyze = &analyze;
+
+    let results: Vec<(PathBuf, BatchItemResult)> = stream::iter(paths.iter().cloned())
+        .map(|path| async move {
+            let item = match sha256_file(&path).await {
+                Ok(hash) if profile.known_good_sha256.contains(&hash) => {
+                    BatchItemResult::SkippedKnownGood { sha256: hash }
+                }
+                Ok(_) => match analyze(path.clone()).await {
+                    Ok(result) => BatchItemResult::Analyzed { result: Box::new(result) },
+                    Err(error) => BatchItemResult::Failed { error },
+                },
+                Err(error) => BatchItemResult::Failed { error },
+            };
+            (path, item)
+        })
+        .buffered(profile.max_concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut summary = BatchSummary {
+        total: results.len(),
+        elapsed_ms: start.elapsed().as_millis() as u64,
+        ..Default::default()
+    };
+    for (_, item) in &results {
+        match item {
+            BatchItemResult::Analyzed { .. } => summary.analyzed += 1,
+            BatchItemResult::SkippedKnownGood { .. } => summary.skipped += 1,
+            BatchItemResult::Failed { .. } => summary.failed += 1,
+        }
+    }
+
+    let analyzed: Vec<&FileAnalysisResult> = results
+        .iter()
+        .filter_map(|(_, item)| match item {
+            BatchItemResult::Analyzed { result } => Some(result.as_ref()),
+            _ => None,
+        })
+        .collect();
+    let correlation = correlate_samples(&analyzed);
+
+    BatchAnalysisResult { results, summary, correlation }
+}
+
+async fn sha256_file(path: &Path) -> Result<String, String> {
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(format!("{:x}", Sha256::digest(&data)))
+}
+
+/// Analyze every regular file directly inside `directory`
+#[tauri::command]
+pub async fn analyze_directory(
+    directory: SafePathBuf,
+    profile: Option<AnalysisProfile>,
+) -> Result<BatchAnalysisResult, String> {
+    let mut paths = Vec::new();
+    let entries = std::fs::read_dir(directory.as_ref())
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(analyze_batch(&paths, profile.unwrap_or_default()).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file_analysis::calculate_hashes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn write_samples(dir: &TempDir) -> Vec<PathBuf> {
+        let a = dir.path().join("a.bin");
+        let b = dir.path().join("b.bin");
+        std::fs::write(&a, b"MZ first sample with some content").unwrap();
+        std::fs::write(&b, b"\x7FELF second sample with other content").unwrap();
+        vec![a, b]
+    }
+
+    #[tokio::test]
+    async fn test_analyze_batch_two_files() {
+        let dir = TempDir::new().unwrap();
+        let paths = write_samples(&dir);
+
+        let batch = analyze_batch(&paths, AnalysisProfile::default()).await;
+
+        assert_eq!(batch.results.len(), 2);
+        assert_eq!(batch.summary.total, 2);
+        assert_eq!(batch.summary.analyzed, 2);
+        assert_eq!(batch.results[0].0, paths[0]);
+        assert_eq!(batch.results[1].0, paths[1]);
+        assert!(batch.results.iter().all(|(_, r)| matches!(r, BatchItemResult::Analyzed { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_batch_bounds_concurrency_and_skips_known_good() {
+        let dir = TempDir::new().unwrap();
+        let mut paths = write_samples(&dir);
+        let known = dir.path().join("known.bin");
+        std::fs::write(&known, b"known good").unwrap();
+        paths.push(known);
+
+        let mut profile = AnalysisProfile {
+            max_concurrency: 1,
+            ..Default::default()
+        };
+        profile.known_good_sha256.insert(calculate_hashes(b"known good").sha256);
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let batch = analyze_batch_with(&paths, &profile, |path| {
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                let safe_path = SafePathBuf::new(path).map_err(|e| e.to_string())?;
+                analyze_file(safe_path, None).await
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+        assert_eq!(batch.summary.analyzed, 2);
+        assert_eq!(batch.summary.skipped, 1);
+        assert!(matches!(batch.results[2].1, BatchItemResult::SkippedKnownGood { .. }));
+    }
+}
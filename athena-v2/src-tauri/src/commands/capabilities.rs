@@ -0,0 +1,158 @@
+use crate::commands::file_analysis::Import;
+use serde::{Deserialize, Serialize};
+
+/// What a sample can do, judged by the APIs it imports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    Networking,
+    Crypto,
+    Process,
+    Registry,
+    Filesystem,
+    AntiAnalysis,
+}
+
+/// Imported APIs that fall into one capability
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityUsage {
+    pub capability: Capability,
+    pub count: usize,
+    /// `library!function`, in import order
+    pub apis: Vec<String>,
+}
+
+/// Capabilities a sample's imports touch, in `Capability` order. Buckets
+/// with no imports are left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CapabilitySummary {
+    pub capabilities: Vec<CapabilityUsage>,
+}
+
+impl CapabilitySummary {
+    pub fn count(&self, capability: Capability) -> usize {
+        self.capabilities
+            .iter()
+            .find(|c| c.capability == capability)
+            .map_or(0, |c| c.count)
+    }
+}
+
+/// A library (without `.dll`, `*` for any) and the function name prefixes
+/// from it that indicate a capability. No prefixes means every function.
+type Rule = (Capability, &'static str, &'static [&'static str]);
+
+const RULES: &[Rule] = &[
+    (Capability::Networking, "ws2_32", &[]),
+    (Capability::Networking, "wsock32", &[]),
+    (Capability::Networking, "mswsock", &[]),
+    (Capability::Networking, "wininet", &[]),
+    (Capability::Networking, "winhttp", &[]),
+    (Capability::Networking, "dnsapi", &[]),
+    (Capability::Networking, "iphlpapi", &[]),
+    (Capability::Networking, "urlmon", &["urldownload", "urlopen"]),
+    (Capability::Crypto, "bcrypt", &[]),
+    (Capability::Crypto, "ncrypt", &[]),
+    (Capability::Crypto, "crypt32", &[]),
+    (Capability::Crypto, "advapi32", &["crypt", "systemfunction032", "systemfunction036"]),
+    (Capability::Process, "*", &[
+        "createprocess", "openprocess", "terminateprocess", "createremotethread",
+        "virtualallocex", "writeprocessmemory", "readprocessmemory", "createtoolhelp32snapshot",
+        "process32first", "process32next", "queueuserapc", "setthreadcontext", "winexec",
+        "shellexecute", "ntcreateuserprocess", "ntopenprocess", "ntwritevirtualmemory",
+    ]),
+    (Capability::Registry, "advapi32", &["reg"]),
+    (Capability::Registry, "ntdll", &[
+        "ntcreatekey", "ntopenkey", "ntsetvaluekey", "ntqueryvaluekey", "ntdeletekey", "ntdeletevaluekey",
+    ]),
+    (Capability::Filesystem, "*", &[
+        "createfile", "readfile", "writefile", "deletefile", "copyfile", "movefile",
+        "findfirstfile", "findnextfile", "createdirectory", "removedirectory",
+        "setfileattributes", "gettemppath", "ntcreatefile", "ntwritefile",
+    ]),
+    (Capability::AntiAnalysis, "*", &[
+        "isdebuggerpresent", "checkremotedebuggerpresent", "ntqueryinformationprocess",
+        "ntsetinformationthread", "outputdebugstring", "gettickcount", "queryperformancecounter",
+        "getsystemfirmwaretable", "ntqueryobject",
+    ]),
+];
+
+const ORDER: [Capability; 6] = [
+    Capability::Networking,
+    Capability::Crypto,
+    Capability::Process,
+    Capability::Registry,
+    Capability::Filesystem,
+    Capability::AntiAnalysis,
+];
+
+/// Bucket imported APIs by capability so analysts can see at a glance what
+/// a sample touches. Each API counts once per capability.
+pub fn summarize_capabilities(imports: &[Import]) -> CapabilitySummary {
+    let mut usage: Vec<CapabilityUsage> = ORDER
+        .iter()
+        .map(|&capability| CapabilityUsage { capability, count: 0, apis: Vec::new() })
+        .collect();
+
+    for import in imports {
+        let library = import.library.to_ascii_lowercase();
+        let library = library.strip_suffix(".dll").unwrap_or(&library);
+
+        for function in &import.functions {
+            let name = function.to_ascii_lowercase();
+            let api = format!("{}!{}", library, function);
+
+            for (capability, rule_library, prefixes) in RULES {
+                let library_matches = *rule_library == "*" || *rule_library == library;
+                let function_matches = prefixes.is_empty() || prefixes.iter().any(|p| name.starts_with(p));
+                if !library_matches || !function_matches {
+                    continue;
+                }
+
+                let bucket = usage.iter_mut().find(|u| u.capability == *capability).expect("every capability is in ORDER");
+                if !bucket.apis.contains(&api) {
+                    bucket.apis.push(api.clone());
+                    bucket.count += 1;
+                }
+            }
+        }
+    }
+
+    usage.retain(|u| u.count > 0);
+    CapabilitySummary { capabilities: usage }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(library: &str, functions: &[&str]) -> Import {
+        Import {
+            library: library.to_string(),
+            functions: functions.iter().map(|f| f.to_string()).collect(),
+            suspicious: false,
+        }
+    }
+
+    #[test]
+    fn test_networking_imports_counted() {
+        let imports = vec![
+            import("ws2_32.dll", &["connect"]),
+            import("wininet.dll", &["InternetOpenA"]),
+            // Named pipes are IPC, not networking
+            import("kernel32.dll", &["ConnectNamedPipe", "CreateFileW", "IsDebuggerPresent"]),
+        ];
+
+        let summary = summarize_capabilities(&imports);
+
+        assert_eq!(summary.count(Capability::Networking), 2);
+        assert_eq!(summary.count(Capability::Filesystem), 1);
+        assert_eq!(summary.count(Capability::AntiAnalysis), 1);
+        assert_eq!(summary.count(Capability::Crypto), 0);
+        assert_eq!(summary.capabilities[0].apis, vec!["ws2_32!connect", "wininet!InternetOpenA"]);
+        assert_eq!(
+            summary.capabilities.iter().map(|c| c.capability).collect::<Vec<_>>(),
+            vec![Capability::Networking, Capability::Filesystem, Capability::AntiAnalysis],
+        );
+    }
+}
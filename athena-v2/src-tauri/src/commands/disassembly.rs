@@ -109,6 +109,40 @@ fn extract_strings_from_data(data: &[u8], min_length: usize) -> Vec<ExtractedStr
 
 const DISASSEMBLER_MODULE: &str = "disassembler";
 
+/// Disassembler architecture (as named in the WIT interface) and display
+/// name for the machine type in the file's headers. Raw code and
+/// unrecognised machines are treated as x86-64.
+fn detect_architecture(data: &[u8]) -> (&'static str, &'static str) {
+    use goblin::{elf::header as elf, mach::cputype, pe::header as pe, Object};
+
+    const X86: (&str, &str) = ("x86", "x86");
+    const X64: (&str, &str) = ("x64", "x86_64");
+    const ARM: (&str, &str) = ("arm", "arm");
+    const ARM64: (&str, &str) = ("arm64", "aarch64");
+
+    match Object::parse(data) {
+        Ok(Object::Elf(elf)) => match elf.header.e_machine {
+            elf::EM_386 => X86,
+            elf::EM_ARM => ARM,
+            elf::EM_AARCH64 => ARM64,
+            _ => X64,
+        },
+        Ok(Object::PE(pe)) => match pe.header.coff_header.machine {
+            pe::COFF_MACHINE_X86 => X86,
+            pe::COFF_MACHINE_ARM | pe::COFF_MACHINE_ARMNT | pe::COFF_MACHINE_THUMB => ARM,
+            pe::COFF_MACHINE_ARM64 => ARM64,
+            _ => X64,
+        },
+        Ok(Object::Mach(goblin::mach::Mach::Binary(macho))) => match macho.header.cputype {
+            cputype::CPU_TYPE_X86 => X86,
+            cputype::CPU_TYPE_ARM => ARM,
+            cputype::CPU_TYPE_ARM64 => ARM64,
+            _ => X64,
+        },
+        _ => X64,
+    }
+}
+
 #[tauri::command]
 pub async fn disassemble_file(
     runtime: State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
@@ -121,13 +155,17 @@ pub async fn disassemble_file(
 
     let start = offset.unwrap_or(0);
     let len = length.unwrap_or(100);
+    let (arch, architecture) = detect_architecture(&data);
 
-    // Call WASM disassembler module
+    // Call WASM disassembler module: a linear sweep of `len` instructions
+    // from `start`, so no recursive-descent seeds
     let disasm_options = serde_json::json!({
-        "arch": "x64",
+        "arch": arch,
         "syntax": "intel",
-        "show_bytes": true,
-        "max_instructions": len
+        "show-bytes": true,
+        "max-instructions": len,
+        "mode": "linear-sweep",
+        "seeds": [],
     });
 
     let args = vec![
@@ -204,7 +242,7 @@ pub async fn disassemble_file(
         },
     ];
 
-    let architecture = "x86_64".to_string();
+    let architecture = architecture.to_string();
 
     // Record disassembly metrics
     INSTRUCTIONS_DISASSEMBLED
@@ -234,10 +272,11 @@ pub async fn get_control_flow_graph(
     let data = fs::read(file_path.as_ref()).map_err(|e| format!("Failed to read file: {}", e))?;
 
     // Call WASM disassembler for CFG analysis
+    let (arch, _) = detect_architecture(&data);
     let args = vec![
         serde_json::json!(data),
         serde_json::json!(function_address),
-        serde_json::json!("x64")
+        serde_json::json!(arch)
     ];
 
     let result = crate::commands::wasm_runtime::execute_wasm_function(
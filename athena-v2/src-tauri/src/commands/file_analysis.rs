@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use sha2::Digest;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 use base64::{Engine as _, engine::general_purpose};
@@ -18,6 +18,13 @@ use tauri::path::SafePathBuf;
 use crate::signature_verify::{verify_pe_signature, verify_elf_signature, SignatureInfo};
 use crate::metrics::{FILE_OPERATION_DURATION, FILE_OPERATION_COUNTER, FILE_SIZE_HISTOGRAM};
 use crate::commands::ai_analysis;
+use crate::commands::string_categories;
+use crate::commands::imphash_families;
+use crate::commands::capabilities::{summarize_capabilities, CapabilitySummary};
+use crate::commands::mapped_file::MappedFile;
+use crate::html::escape_html;
+use crate::cache::fuzzy_index::{self, FuzzyAlgorithm, FuzzyConfig, FuzzyIndex, SimilarSample};
+use std::sync::OnceLock;
 
 /// Configuration for file analysis
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -46,6 +53,99 @@ pub struct AnalysisConfig {
     pub sandbox_cpu: f64,
     #[serde(default = "default_true")]
     pub capture_network: bool,
+    /// Section entropy above which a section is flagged, defaults to
+    /// `DEFAULT_SECTION_ENTROPY_THRESHOLD`
+    #[serde(default)]
+    pub section_entropy_threshold: Option<f64>,
+    /// Which PE header anomalies to report
+    #[serde(default)]
+    pub pe_header_checks: PeHeaderChecks,
+    /// Fuzzy hash and minimum score used to find similar known samples
+    #[serde(default)]
+    pub fuzzy: FuzzyConfig,
+    /// Caps the file-processor module's parser applies to header counts
+    #[serde(default)]
+    pub parser_limits: ParserLimits,
+    /// How much work the file-processor module may spend on carved files
+    #[serde(default)]
+    pub analysis_budget: AnalysisBudget,
+    /// Bytes the pattern-matcher module scans from the start of the file;
+    /// all of it when unset
+    #[serde(default)]
+    pub max_scan_bytes: Option<u64>,
+}
+
+/// Caps on the sections, load commands and resources an executable's
+/// headers may declare, passed to the file-processor module's
+/// `parse-file-with-limits`. Defaults match the module's own.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct ParserLimits {
+    pub max_sections: u32,
+    pub max_load_commands: u32,
+    pub max_resources: u32,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_sections: 1024,
+            max_load_commands: 1024,
+            max_resources: 4096,
+        }
+    }
+}
+
+/// Limits on the file-processor module's `analyze-recursive`, which parses
+/// the files carved from a sample and the files carved from those. Defaults
+/// match the module's own.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct AnalysisBudget {
+    /// Carved files parsed, over all depths
+    pub max_subfiles: u32,
+    /// Bytes parsed, the sample itself included
+    pub max_total_bytes: u64,
+    pub max_wall_ms: u64,
+}
+
+impl Default for AnalysisBudget {
+    fn default() -> Self {
+        Self {
+            max_subfiles: 256,
+            max_total_bytes: 256 * 1024 * 1024,
+            max_wall_ms: 30_000,
+        }
+    }
+}
+
+/// Which PE header characteristics are reported as anomalies
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PeHeaderChecks {
+    /// `DllCharacteristics` without `DYNAMIC_BASE`
+    pub missing_aslr: bool,
+    /// `DllCharacteristics` without `NX_COMPAT`
+    pub missing_dep: bool,
+    /// A DLL with `IMAGE_FILE_RELOCS_STRIPPED`, which can't be rebased
+    pub stripped_relocs_on_dll: bool,
+    /// Subsystems that aren't flagged; empty to skip the check
+    pub expected_subsystems: Vec<u16>,
+}
+
+impl Default for PeHeaderChecks {
+    fn default() -> Self {
+        Self {
+            missing_aslr: true,
+            missing_dep: true,
+            stripped_relocs_on_dll: true,
+            expected_subsystems: vec![
+                pe::subsystem::IMAGE_SUBSYSTEM_NATIVE,
+                pe::subsystem::IMAGE_SUBSYSTEM_WINDOWS_GUI,
+                pe::subsystem::IMAGE_SUBSYSTEM_WINDOWS_CUI,
+            ],
+        }
+    }
 }
 
 fn default_true() -> bool { true }
@@ -62,12 +162,21 @@ pub struct FileAnalysisResult {
     pub format_info: FormatInfo,
     pub sections: Vec<Section>,
     pub imports: Vec<Import>,
+    /// What the imports say the sample can do
+    #[serde(default)]
+    pub capabilities: CapabilitySummary,
     pub exports: Vec<Export>,
     pub strings: Vec<ExtractedString>,
     pub entropy: f64,
     pub hashes: FileHashes,
     pub signatures: Vec<Signature>,
     pub anomalies: Vec<Anomaly>,
+    /// Families known to share the sample's imphash
+    #[serde(default)]
+    pub family_hints: Vec<String>,
+    /// Previously analyzed samples with a similar fuzzy hash
+    #[serde(default)]
+    pub similar_samples: Vec<SimilarSample>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -126,6 +235,22 @@ pub struct Section {
     pub entropy: f64,
     pub characteristics: Vec<String>,
     pub suspicious: bool,
+    /// Why the section is suspicious
+    #[serde(default)]
+    pub flags: Vec<SectionFlag>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionFlag {
+    HighEntropy,
+    Writable,
+    WritableExecutable,
+    PackerName,
+    /// Executable but not one of the sections compilers put code in
+    UnexpectedExecutable,
+    /// Much larger in memory than on disk, so filled in at runtime
+    RawSizeMismatch,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -157,6 +282,8 @@ pub struct FileHashes {
     pub sha1: String,
     pub sha256: String,
     pub ssdeep: Option<String>,
+    #[serde(default)]
+    pub tlsh: Option<String>,
     pub imphash: Option<String>,
 }
 
@@ -200,67 +327,126 @@ pub fn calculate_entropy(data: &[u8]) -> f64 {
     entropy
 }
 
-pub fn extract_strings(data: &[u8], min_length: usize) -> Vec<ExtractedString> {
-    let mut strings = Vec::new();
-    let mut current_string = Vec::new();
-    let mut current_offset = 0;
-
-    for (offset, &byte) in data.iter().enumerate() {
-        if byte.is_ascii_graphic() || byte == b' ' {
-            if current_string.is_empty() {
-                current_offset = offset;
+/// What `strings_iter` looks for
+#[derive(Debug, Clone, Copy)]
+pub struct StringOptions {
+    pub min_length: usize,
+    pub ascii: bool,
+    pub utf16le: bool,
+}
+
+impl Default for StringOptions {
+    fn default() -> Self {
+        Self { min_length: 4, ascii: true, utf16le: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringPass {
+    Ascii,
+    Utf16Le,
+    Done,
+}
+
+/// Lazily yields the strings in a buffer: every ASCII string, then every
+/// UTF-16LE one. Only as much of the buffer is read as the strings taken
+/// need.
+pub struct StringsIter<'a> {
+    data: &'a [u8],
+    opts: StringOptions,
+    pass: StringPass,
+    pos: usize,
+}
+
+impl<'a> StringsIter<'a> {
+    /// How far into the buffer the current pass has read
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    fn string(&self, value: String, offset: usize, encoding: &str) -> ExtractedString {
+        ExtractedString {
+            suspicious: is_suspicious_string(&value),
+            category: categorize_string(&value),
+            value,
+            offset: offset as u64,
+            encoding: encoding.to_string(),
+        }
+    }
+
+    fn next_ascii(&mut self) -> Option<ExtractedString> {
+        while self.pos < self.data.len() {
+            let start = self.pos;
+            let run = self.data[start..].iter().take_while(|&&b| b.is_ascii_graphic() || b == b' ').count();
+            if run == 0 {
+                self.pos += 1;
+                continue;
             }
-            current_string.push(byte);
-        } else if !current_string.is_empty() {
-            if current_string.len() >= min_length {
-                if let Ok(s) = String::from_utf8(current_string.clone()) {
-                    let suspicious = is_suspicious_string(&s);
-                    let category = categorize_string(&s);
-                    
-                    strings.push(ExtractedString {
-                        value: s,
-                        offset: current_offset as u64,
-                        encoding: "ASCII".to_string(),
-                        suspicious,
-                        category,
-                    });
-                }
+            self.pos = start + run;
+
+            // A run that reaches the end of the buffer isn't terminated
+            if self.pos == self.data.len() {
+                return None;
+            }
+            if run >= self.opts.min_length {
+                let value = String::from_utf8_lossy(&self.data[start..self.pos]).into_owned();
+                return Some(self.string(value, start, "ASCII"));
             }
-            current_string.clear();
         }
+        None
     }
 
-    // Check for UTF-16 strings
-    let mut i = 0;
-    while i < data.len() - 1 {
-        if data[i].is_ascii_graphic() && data[i + 1] == 0 {
-            let mut utf16_string = Vec::new();
-            let start_offset = i;
-            
-            while i < data.len() - 1 && data[i].is_ascii_graphic() && data[i + 1] == 0 {
-                utf16_string.push(data[i]);
-                i += 2;
+    fn next_utf16le(&mut self) -> Option<ExtractedString> {
+        while self.pos + 1 < self.data.len() {
+            let start = self.pos;
+            let mut end = start;
+            while end + 1 < self.data.len() && self.data[end].is_ascii_graphic() && self.data[end + 1] == 0 {
+                end += 2;
             }
-            
-            if utf16_string.len() >= min_length {
-                if let Ok(s) = String::from_utf8(utf16_string) {
-                    let suspicious = is_suspicious_string(&s);
-                    let category = categorize_string(&s);
-                    
-                    strings.push(ExtractedString {
-                        value: s,
-                        offset: start_offset as u64,
-                        encoding: "UTF-16LE".to_string(),
-                        suspicious,
-                        category,
-                    });
-                }
+            // The byte the run stopped at can't start another run
+            self.pos = end + 1;
+
+            if (end - start) / 2 >= self.opts.min_length.max(1) {
+                let value = self.data[start..end].iter().step_by(2).map(|&b| b as char).collect();
+                return Some(self.string(value, start, "UTF-16LE"));
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for StringsIter<'_> {
+    type Item = ExtractedString;
+
+    fn next(&mut self) -> Option<ExtractedString> {
+        loop {
+            let found = match self.pass {
+                StringPass::Ascii if self.opts.ascii => self.next_ascii(),
+                StringPass::Utf16Le if self.opts.utf16le => self.next_utf16le(),
+                StringPass::Done => return None,
+                _ => None,
+            };
+            if found.is_some() {
+                return found;
             }
+
+            self.pass = match self.pass {
+                StringPass::Ascii => StringPass::Utf16Le,
+                _ => StringPass::Done,
+            };
+            self.pos = 0;
         }
-        i += 1;
     }
+}
 
-    strings
+/// Strings in `data`, yielded as they're found so callers can filter or
+/// stop early without holding every string of a large file
+pub fn strings_iter(data: &[u8], opts: StringOptions) -> StringsIter<'_> {
+    StringsIter { data, opts, pass: StringPass::Ascii, pos: 0 }
+}
+
+pub fn extract_strings(data: &[u8], min_length: usize) -> Vec<ExtractedString> {
+    strings_iter(data, StringOptions { min_length, ..StringOptions::default() }).collect()
 }
 
 fn is_suspicious_string(s: &str) -> bool {
@@ -280,19 +466,7 @@ fn is_suspicious_string(s: &str) -> bool {
 }
 
 fn categorize_string(s: &str) -> Option<String> {
-    if s.starts_with("http://") || s.starts_with("https://") {
-        Some("URL".to_string())
-    } else if s.contains('@') && s.contains('.') {
-        Some("Email".to_string())
-    } else if s.starts_with("\\\\") || (s.len() > 3 && &s[1..3] == ":\\") {
-        Some("Path".to_string())
-    } else if s.starts_with("HKEY_") || s.starts_with("HKLM\\") || s.starts_with("HKCU\\") {
-        Some("Registry".to_string())
-    } else if s.ends_with(".exe") || s.ends_with(".dll") || s.ends_with(".sys") {
-        Some("Executable".to_string())
-    } else {
-        None
-    }
+    string_categories::categorize(s)
 }
 
 /// Check if data appears to be a text file (high ratio of printable ASCII)
@@ -354,10 +528,156 @@ pub fn calculate_hashes(data: &[u8]) -> FileHashes {
         sha1,
         sha256,
         ssdeep,
+        tlsh: fuzzy_index::tlsh_hash(data),
         imphash: None, // Will be calculated in parse_pe if applicable
     }
 }
 
+/// Fuzzy hash indexes of every analyzed sample, one per algorithm, kept in
+/// one database beside the analysis cache. `None` if it couldn't be opened.
+static SSDEEP_INDEX: OnceLock<Option<FuzzyIndex>> = OnceLock::new();
+static TLSH_INDEX: OnceLock<Option<FuzzyIndex>> = OnceLock::new();
+
+fn fuzzy_index_for(algorithm: FuzzyAlgorithm) -> Option<&'static FuzzyIndex> {
+    let cell = match algorithm {
+        FuzzyAlgorithm::SsDeep => &SSDEEP_INDEX,
+        FuzzyAlgorithm::Tlsh => &TLSH_INDEX,
+    };
+    cell.get_or_init(|| {
+        let db_path = fuzzy_index_path();
+        match FuzzyIndex::new(&db_path.to_string_lossy()) {
+            Ok(index) => Some(index.with_config(FuzzyConfig { algorithm, ..FuzzyConfig::default() })),
+            Err(e) => {
+                eprintln!("Warning: fuzzy index unavailable, similar samples won't be reported: {}", e);
+                None
+            }
+        }
+    })
+    .as_ref()
+}
+
+#[cfg(not(test))]
+fn fuzzy_index_path() -> PathBuf {
+    crate::cache::CacheConfig::default().db_path.with_file_name("fuzzy_index.db")
+}
+
+/// Tests analyze throwaway samples, which must not land in the user's index
+#[cfg(test)]
+fn fuzzy_index_path() -> PathBuf {
+    static DIR: OnceLock<tempfile::TempDir> = OnceLock::new();
+    DIR.get_or_init(|| tempfile::TempDir::new().expect("temp dir for the fuzzy index"))
+        .path()
+        .join("fuzzy_index.db")
+}
+
+/// Known samples whose fuzzy hash scores at least `min_score`
+/// against this one, then add this one to the index
+fn cluster_sample(index: &FuzzyIndex, hashes: &FileHashes, min_score: u8) -> Vec<SimilarSample> {
+    let hash = match index.config().algorithm {
+        FuzzyAlgorithm::SsDeep => hashes.ssdeep.as_deref(),
+        FuzzyAlgorithm::Tlsh => hashes.tlsh.as_deref(),
+    };
+    let Some(hash) = hash else {
+        return Vec::new();
+    };
+
+    let similar = index
+        .query(hash, min_score)
+        .unwrap_or_else(|e| {
+            eprintln!("Warning: fuzzy index query failed: {}", e);
+            Vec::new()
+        })
+        .into_iter()
+        .filter(|(sample_id, _)| *sample_id != hashes.sha256)
+        .map(|(sample_id, score)| SimilarSample { sample_id, score })
+        .collect();
+    if let Err(e) = index.insert(&hashes.sha256, hash) {
+        eprintln!("Warning: failed to add sample to fuzzy index: {}", e);
+    }
+    similar
+}
+
+/// Digest algorithms selectable through [`HashSetSpec`]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    fn digest_hex(&self, data: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Md5 => format!("{:x}", md5::compute(data)),
+            HashAlgorithm::Sha1 => format!("{:x}", sha1::Sha1::digest(data)),
+            HashAlgorithm::Sha256 => format!("{:x}", sha2::Sha256::digest(data)),
+            HashAlgorithm::Sha512 => format!("{:x}", sha2::Sha512::digest(data)),
+            HashAlgorithm::Blake3 => blake3::hash(data).to_hex().to_string(),
+        }
+    }
+}
+
+/// Which digests to compute, so callers don't pay for hashes they won't use
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HashSetSpec {
+    pub algorithms: std::collections::BTreeSet<HashAlgorithm>,
+}
+
+impl HashSetSpec {
+    pub fn new(algorithms: &[HashAlgorithm]) -> Self {
+        Self {
+            algorithms: algorithms.iter().copied().collect(),
+        }
+    }
+
+    pub fn all() -> Self {
+        Self::new(&[
+            HashAlgorithm::Md5,
+            HashAlgorithm::Sha1,
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Sha512,
+            HashAlgorithm::Blake3,
+        ])
+    }
+}
+
+impl Default for HashSetSpec {
+    fn default() -> Self {
+        Self::new(&[HashAlgorithm::Md5, HashAlgorithm::Sha1, HashAlgorithm::Sha256])
+    }
+}
+
+/// Compute only the digests requested in `spec`, keyed by algorithm name
+pub fn calculate_hash_set(data: &[u8], spec: &HashSetSpec) -> std::collections::BTreeMap<String, String> {
+    spec.algorithms
+        .iter()
+        .map(|alg| (alg.name().to_string(), alg.digest_hex(data)))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn calculate_file_hashes(
+    file_path: SafePathBuf,
+    spec: Option<HashSetSpec>,
+) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let data = MappedFile::open(file_path.as_ref())
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(calculate_hash_set(&data, &spec.unwrap_or_default()))
+}
+
 #[tauri::command]
 pub async fn analyze_file(
     file_path: SafePathBuf,
@@ -369,13 +689,15 @@ pub async fn analyze_file(
     let path = file_path.as_ref();
 
     // Use provided config or defaults
-    let _config = config.unwrap_or_default();
+    let config = config.unwrap_or_default();
+    let entropy_threshold = config.section_entropy_threshold.unwrap_or(DEFAULT_SECTION_ENTROPY_THRESHOLD);
+    let pe_header_checks = config.pe_header_checks.clone();
 
     // Log the analysis configuration
     let filename = path.file_name()
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| "unknown".to_string());
-    println!("Analyzing file: {} with config: {:?}", filename, _config);
+    println!("Analyzing file: {} with config: {:?}", filename, config);
 
     // Read file metadata
     let metadata = std::fs::metadata(&path)
@@ -431,8 +753,9 @@ pub async fn analyze_file(
         );
     }
 
-    // Read file contents
-    let mut file = File::open(&path)
+    // Map file contents rather than copying them, so large samples are
+    // paged in as the analyzers touch them
+    let mapped = MappedFile::open(path)
         .map_err(|e| {
             FILE_OPERATION_COUNTER
                 .with_label_values(&["analyze_file", "error"])
@@ -441,36 +764,21 @@ pub async fn analyze_file(
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_else(|| "unknown".to_string());
             format!(
-                "Could not open file '{}'. Please check that the file is not locked by another program. Error: {}",
+                "Could not read file '{}'. Please check that the file is not locked by another program. Error: {}",
                 filename,
                 e
             )
         })?;
+    let buffer = mapped.as_slice();
 
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)
-        .map_err(|e| {
-            FILE_OPERATION_COUNTER
-                .with_label_values(&["analyze_file", "error"])
-                .inc();
-            let filename = path.file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| "unknown".to_string());
-            format!(
-                "Could not read file '{}'. The file may be corrupted or too large to process. Error: {}",
-                filename,
-                e
-            )
-        })?;
-    
     // Calculate file entropy
-    let entropy = calculate_entropy(&buffer);
+    let entropy = calculate_entropy(buffer);
     
     // Calculate hashes
-    let hashes = calculate_hashes(&buffer);
+    let hashes = calculate_hashes(buffer);
     
     // Extract strings
-    let strings = extract_strings(&buffer, 6);
+    let strings = extract_strings(buffer, 6);
     
     // Get MIME type
     let mime_type = mime_guess::from_path(&path)
@@ -481,7 +789,7 @@ pub async fn analyze_file(
     let magic_bytes = if buffer.len() >= 16 {
         hex::encode(&buffer[..16])
     } else {
-        hex::encode(&buffer)
+        hex::encode(buffer)
     };
     
     let file_info = FileInfo {
@@ -497,17 +805,17 @@ pub async fn analyze_file(
     };
     
     // Parse binary format
-    let (format_info, sections, imports, exports, anomalies, imphash) = match Object::parse(&buffer) {
+    let (format_info, sections, imports, exports, anomalies, imphash) = match Object::parse(buffer) {
         Ok(Object::PE(pe)) => {
-            let (fi, s, i, e, a, ih) = parse_pe(pe, &buffer, &path);
+            let (fi, s, i, e, a, ih) = parse_pe(pe, buffer, &path, entropy_threshold, &pe_header_checks);
             (fi, s, i, e, a, ih)
         },
         Ok(Object::Elf(elf)) => {
-            let (fi, s, i, e, a) = parse_elf(elf, &buffer, &path);
+            let (fi, s, i, e, a) = parse_elf(elf, buffer, &path, entropy_threshold);
             (fi, s, i, e, a, None)
         },
         Ok(Object::Mach(mach)) => {
-            let (fi, s, i, e, a) = parse_mach(mach, &buffer);
+            let (fi, s, i, e, a) = parse_mach(mach, buffer, entropy_threshold);
             (fi, s, i, e, a, None)
         },
         Ok(_) | Err(_) => {
@@ -545,9 +853,17 @@ pub async fn analyze_file(
     if let Some(ih) = imphash {
         final_hashes.imphash = Some(ih);
     }
+    let family_hints = final_hashes.imphash.as_deref()
+        .map(imphash_families::family_hints)
+        .unwrap_or_default();
+    let similar_samples = fuzzy_index_for(config.fuzzy.algorithm)
+        .map(|index| cluster_sample(index, &final_hashes, config.fuzzy.min_score))
+        .unwrap_or_default();
     
     // Detect signatures using pattern matching
-    let signatures = detect_signatures(&buffer);
+    let signatures = detect_signatures(buffer);
+
+    let capabilities = summarize_capabilities(&imports);
 
     // Record successful metrics
     let duration = start_time.elapsed();
@@ -571,17 +887,27 @@ pub async fn analyze_file(
         format_info,
         sections,
         imports,
+        capabilities,
         exports,
         strings,
         entropy,
         hashes: final_hashes,
         signatures,
         anomalies,
+        family_hints,
+        similar_samples,
     })
 }
 
-fn parse_pe(pe: pe::PE, data: &[u8], path: &Path) -> (FormatInfo, Vec<Section>, Vec<Import>, Vec<Export>, Vec<Anomaly>, Option<String>) {
-    let anomalies = Vec::new();
+fn parse_pe(pe: pe::PE, data: &[u8], path: &Path, entropy_threshold: f64, header_checks: &PeHeaderChecks) -> (FormatInfo, Vec<Section>, Vec<Import>, Vec<Export>, Vec<Anomaly>, Option<String>) {
+    let windows_fields = pe.header.optional_header.map(|h| h.windows_fields);
+    let mut anomalies = pe_header_anomalies(
+        pe.header.coff_header.characteristics,
+        windows_fields.map(|w| w.subsystem),
+        windows_fields.map(|w| w.dll_characteristics),
+        header_checks,
+    );
+    anomalies.extend(tls_callback_anomaly(&pe));
 
     // Verify digital signature
     let signature_info = verify_pe_signature(path, data).ok();
@@ -610,9 +936,7 @@ fn parse_pe(pe: pe::PE, data: &[u8], path: &Path) -> (FormatInfo, Vec<Section>,
             (section.pointer_to_raw_data + section.size_of_raw_data) as usize];
         let section_entropy = calculate_entropy(section_data);
         
-        let suspicious = section_entropy > 7.0 || 
-            section.characteristics & 0x80000000 != 0 || // IMAGE_SCN_MEM_WRITE
-            section.name().unwrap_or("").starts_with(".UPX");
+        let flags = suspicious_section_flags(section, section_entropy, entropy_threshold);
         
         // Parse section characteristics flags
         let mut characteristics = Vec::new();
@@ -636,7 +960,8 @@ fn parse_pe(pe: pe::PE, data: &[u8], path: &Path) -> (FormatInfo, Vec<Section>,
             raw_size: section.size_of_raw_data as u64,
             entropy: section_entropy,
             characteristics,
-            suspicious,
+            suspicious: !flags.is_empty(),
+            flags,
         });
     }
     
@@ -653,8 +978,10 @@ fn parse_pe(pe: pe::PE, data: &[u8], path: &Path) -> (FormatInfo, Vec<Section>,
             .or_insert_with(Vec::new)
             .push(function_name.clone());
 
-        // Add to imphash data: library.function format
-        imphash_data.push(format!("{}.{}", library, function_name));
+        // Add to imphash data in import table order; goblin names imports
+        // by ordinal "ORDINAL <n>"
+        let function = (!import.name.starts_with("ORDINAL ")).then_some(import.name.as_ref());
+        imphash_data.push(imphash_families::imphash_entry(import.dll, function, import.ordinal));
     }
 
     // Convert HashMap to Import structs
@@ -668,14 +995,7 @@ fn parse_pe(pe: pe::PE, data: &[u8], path: &Path) -> (FormatInfo, Vec<Section>,
         });
     }
 
-    // Calculate imphash as MD5 of sorted, comma-separated library.function pairs
-    let imphash = if !imphash_data.is_empty() {
-        imphash_data.sort();
-        let imphash_string = imphash_data.join(",");
-        Some(format!("{:x}", md5::compute(imphash_string.as_bytes())))
-    } else {
-        None
-    };
+    let imphash = imphash_families::imphash(&imphash_data);
 
     // Parse exports
     let mut exports = Vec::new();
@@ -692,7 +1012,7 @@ fn parse_pe(pe: pe::PE, data: &[u8], path: &Path) -> (FormatInfo, Vec<Section>,
     (format_info, sections, imports, exports, anomalies, imphash)
 }
 
-fn parse_elf(elf: elf::Elf, data: &[u8], path: &Path) -> (FormatInfo, Vec<Section>, Vec<Import>, Vec<Export>, Vec<Anomaly>) {
+fn parse_elf(elf: elf::Elf, data: &[u8], path: &Path, entropy_threshold: f64) -> (FormatInfo, Vec<Section>, Vec<Import>, Vec<Export>, Vec<Anomaly>) {
     // Verify digital signature (GPG/PGP)
     let signature_info = verify_elf_signature(path, data).ok();
 
@@ -752,9 +1072,12 @@ fn parse_elf(elf: elf::Elf, data: &[u8], path: &Path) -> (FormatInfo, Vec<Sectio
             };
 
             // Check for suspicious sections
-            let suspicious = section_entropy > 7.0 ||
-                name.starts_with(".upx") ||
-                (section.sh_flags & 0x2 != 0 && section.sh_flags & 0x4 != 0); // WRITE + EXEC
+            let mut flags = Vec::new();
+            if section_entropy > entropy_threshold { flags.push(SectionFlag::HighEntropy); }
+            if name.starts_with(".upx") { flags.push(SectionFlag::PackerName); }
+            if section.sh_flags & 0x2 != 0 && section.sh_flags & 0x4 != 0 { // WRITE + EXEC
+                flags.push(SectionFlag::WritableExecutable);
+            }
 
             let mut characteristics = Vec::new();
             if section.sh_flags & 0x1 != 0 { characteristics.push("WRITE".to_string()); }
@@ -768,7 +1091,8 @@ fn parse_elf(elf: elf::Elf, data: &[u8], path: &Path) -> (FormatInfo, Vec<Sectio
                 raw_size: section.sh_size,
                 entropy: section_entropy,
                 characteristics,
-                suspicious,
+                suspicious: !flags.is_empty(),
+                flags,
             });
         }
     }
@@ -854,7 +1178,7 @@ fn parse_elf(elf: elf::Elf, data: &[u8], path: &Path) -> (FormatInfo, Vec<Sectio
     (format_info, sections, imports, exports, anomalies)
 }
 
-fn parse_mach(mach: mach::Mach, data: &[u8]) -> (FormatInfo, Vec<Section>, Vec<Import>, Vec<Export>, Vec<Anomaly>) {
+fn parse_mach(mach: mach::Mach, data: &[u8], entropy_threshold: f64) -> (FormatInfo, Vec<Section>, Vec<Import>, Vec<Export>, Vec<Anomaly>) {
     // Validate we have file data for analysis
     let file_size = data.len();
 
@@ -921,7 +1245,7 @@ fn parse_mach(mach: mach::Mach, data: &[u8]) -> (FormatInfo, Vec<Section>, Vec<I
                     0.0
                 };
 
-                let suspicious = section_entropy > 7.0;
+                let flags = if section_entropy > entropy_threshold { vec![SectionFlag::HighEntropy] } else { Vec::new() };
                 let mut characteristics = Vec::new();
 
                 if section.flags & 0x1 != 0 { characteristics.push("REGULAR".to_string()); }
@@ -934,7 +1258,8 @@ fn parse_mach(mach: mach::Mach, data: &[u8]) -> (FormatInfo, Vec<Section>, Vec<I
                     raw_size: section.size,
                     entropy: section_entropy,
                     characteristics,
-                    suspicious,
+                    suspicious: !flags.is_empty(),
+                    flags,
                 });
             }
         }
@@ -990,6 +1315,143 @@ fn parse_mach(mach: mach::Mach, data: &[u8]) -> (FormatInfo, Vec<Section>, Vec<I
     (format_info, sections, imports, exports, anomalies)
 }
 
+/// Section entropy above which a section looks packed or encrypted
+pub const DEFAULT_SECTION_ENTROPY_THRESHOLD: f64 = 7.0;
+
+/// A code section this many times larger in memory than on disk is mostly
+/// written at runtime, as unpacking stubs do
+const RAW_SIZE_RATIO: u64 = 10;
+
+/// Prefixes of the executable sections compilers and linkers emit
+const CODE_SECTION_NAMES: &[&str] = &[".text", "CODE", ".init", ".fini", "PAGE", "INIT"];
+
+/// Prefixes of section names left by common packers, lowercase
+const PACKER_SECTION_NAMES: &[&str] = &[".upx", "upx", ".aspack", ".adata", ".themida", ".vmp", ".petite", ".mpress", ".nsp"];
+
+/// Why a PE section looks suspicious; empty if it doesn't
+pub fn suspicious_section_flags(section: &pe::section_table::SectionTable, entropy: f64, entropy_threshold: f64) -> Vec<SectionFlag> {
+    const IMAGE_SCN_CNT_CODE: u32 = 0x00000020;
+    const IMAGE_SCN_MEM_EXECUTE: u32 = 0x20000000;
+    const IMAGE_SCN_MEM_WRITE: u32 = 0x80000000;
+
+    let name = section.name().unwrap_or("");
+    let lower_name = name.to_ascii_lowercase();
+    let executable = section.characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0;
+    let writable = section.characteristics & IMAGE_SCN_MEM_WRITE != 0;
+
+    let mut flags = Vec::new();
+    if entropy > entropy_threshold {
+        flags.push(SectionFlag::HighEntropy);
+    }
+    if writable {
+        flags.push(if executable { SectionFlag::WritableExecutable } else { SectionFlag::Writable });
+    }
+    if PACKER_SECTION_NAMES.iter().any(|p| lower_name.starts_with(p)) {
+        flags.push(SectionFlag::PackerName);
+    }
+    if executable && !CODE_SECTION_NAMES.iter().any(|p| name.starts_with(p)) {
+        flags.push(SectionFlag::UnexpectedExecutable);
+    }
+
+    // Data sections legitimately carry large uninitialized tails, so only
+    // code is held to this
+    let virtual_size = section.virtual_size as u64;
+    let raw_size = section.size_of_raw_data as u64;
+    if executable && virtual_size >= 0x1000 && virtual_size > raw_size.saturating_mul(RAW_SIZE_RATIO) {
+        flags.push(SectionFlag::RawSizeMismatch);
+    }
+
+    flags
+}
+
+/// Anomalies in the COFF characteristics and optional header subsystem and
+/// DLL characteristics. Images without an optional header (objects) have no
+/// subsystem or DLL characteristics to check.
+pub fn pe_header_anomalies(
+    characteristics: u16,
+    subsystem: Option<u16>,
+    dll_characteristics: Option<u16>,
+    checks: &PeHeaderChecks,
+) -> Vec<Anomaly> {
+    use pe::characteristic::{IMAGE_FILE_DLL, IMAGE_FILE_RELOCS_STRIPPED};
+    use pe::dll_characteristic::{IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE, IMAGE_DLLCHARACTERISTICS_NX_COMPAT};
+
+    let anomaly = |description: String, severity: &str, field: &str, value: u16| Anomaly {
+        category: "PE Header".to_string(),
+        description,
+        severity: severity.to_string(),
+        details: HashMap::from([(field.to_string(), serde_json::json!(format!("{:#06x}", value)))]),
+    };
+
+    let mut anomalies = Vec::new();
+    let is_dll = characteristics & IMAGE_FILE_DLL != 0;
+
+    if checks.stripped_relocs_on_dll && is_dll && characteristics & IMAGE_FILE_RELOCS_STRIPPED != 0 {
+        anomalies.push(anomaly(
+            "DLL has its relocations stripped, so it only loads at its preferred base".to_string(),
+            "medium",
+            "characteristics",
+            characteristics,
+        ));
+    }
+
+    if let Some(dll_characteristics) = dll_characteristics {
+        if checks.missing_aslr && dll_characteristics & IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE == 0 {
+            anomalies.push(anomaly(
+                "ASLR is disabled (no DYNAMIC_BASE)".to_string(),
+                "low",
+                "dll_characteristics",
+                dll_characteristics,
+            ));
+        }
+        if checks.missing_dep && dll_characteristics & IMAGE_DLLCHARACTERISTICS_NX_COMPAT == 0 {
+            anomalies.push(anomaly(
+                "DEP is disabled (no NX_COMPAT)".to_string(),
+                "low",
+                "dll_characteristics",
+                dll_characteristics,
+            ));
+        }
+    }
+
+    if let Some(subsystem) = subsystem {
+        if !checks.expected_subsystems.is_empty() && !checks.expected_subsystems.contains(&subsystem) {
+            anomalies.push(anomaly(
+                format!("Unusual subsystem {}", subsystem),
+                "medium",
+                "subsystem",
+                subsystem,
+            ));
+        }
+    }
+
+    anomalies
+}
+
+/// TLS callbacks run before the entry point, so malware uses them to unpack
+/// or check for a debugger before anyone breaking on the entry point gets
+/// control. Few legitimate binaries have them.
+pub fn tls_callback_anomaly(pe: &pe::PE) -> Option<Anomaly> {
+    let callbacks = &pe.tls_data.as_ref()?.callbacks;
+    if callbacks.is_empty() {
+        return None;
+    }
+
+    let image_base = pe.image_base;
+    let addresses: Vec<String> = callbacks.iter().map(|va| format!("{:#x}", va)).collect();
+    let rvas: Vec<String> = callbacks.iter().map(|va| format!("{:#x}", va.saturating_sub(image_base))).collect();
+
+    Some(Anomaly {
+        category: "TLS Callbacks".to_string(),
+        description: format!("{} TLS callback(s) run before the entry point", callbacks.len()),
+        severity: "medium".to_string(),
+        details: HashMap::from([
+            ("callbacks".to_string(), serde_json::json!(addresses)),
+            ("callback_rvas".to_string(), serde_json::json!(rvas)),
+        ]),
+    })
+}
+
 #[allow(dead_code)]
 fn is_suspicious_import(library: &str, functions: &[String]) -> bool {
     let suspicious_libs = ["ntdll.dll", "kernel32.dll", "advapi32.dll"];
@@ -1283,10 +1745,10 @@ async fn generate_html_report(data: serde_json::Value, output_path: PathBuf) ->
     </div>
 </body>
 </html>"#,
-        metadata.get("fileName").and_then(|v| v.as_str()).unwrap_or("Unknown"),
-        metadata.get("analysisDate").and_then(|v| v.as_str()).unwrap_or("Unknown"),
-        metadata.get("template").and_then(|v| v.as_str()).unwrap_or("Custom"),
-        serde_json::to_string_pretty(&sections).unwrap_or_default()
+        escape_html(metadata.get("fileName").and_then(|v| v.as_str()).unwrap_or("Unknown")),
+        escape_html(metadata.get("analysisDate").and_then(|v| v.as_str()).unwrap_or("Unknown")),
+        escape_html(metadata.get("template").and_then(|v| v.as_str()).unwrap_or("Custom")),
+        escape_html(&serde_json::to_string_pretty(&sections).unwrap_or_default())
     );
 
     std::fs::write(&output_path, html)
@@ -1443,6 +1905,33 @@ mod tests {
         // So we don't assert on it here to avoid flaky tests
     }
 
+    #[test]
+    fn test_calculate_hash_set_only_requested() {
+        let spec = HashSetSpec::new(&[HashAlgorithm::Blake3, HashAlgorithm::Sha256]);
+        let hashes = calculate_hash_set(b"abc", &spec);
+
+        assert_eq!(hashes.len(), 2);
+        assert!(!hashes.contains_key("md5"));
+        assert!(!hashes.contains_key("sha1"));
+        assert_eq!(
+            hashes["sha256"],
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hashes["blake3"],
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn test_hash_set_spec_deserializes_lowercase() {
+        let spec: HashSetSpec = serde_json::from_str(r#"{"algorithms":["sha512","md5"]}"#).unwrap();
+        let hashes = calculate_hash_set(b"", &spec);
+
+        assert_eq!(hashes.keys().collect::<Vec<_>>(), vec!["md5", "sha512"]);
+        assert_eq!(hashes["md5"], "d41d8cd98f00b204e9800998ecf8427e");
+    }
+
     #[test]
     fn test_extract_strings_ascii() {
         let data = b"Hello\x00World\x00Testing\x00";
@@ -1469,6 +1958,20 @@ mod tests {
         assert!(strings.iter().any(|s| s.value == "Hello"));
     }
 
+    #[test]
+    fn test_strings_iter_stops_early() {
+        let mut data = b"cmd.exe /c whoami\x00net user\x00".to_vec();
+        for _ in 0..100_000 {
+            data.extend_from_slice(b"filler text\x00");
+        }
+
+        let mut strings = strings_iter(&data, StringOptions::default());
+        let first: Vec<String> = strings.by_ref().take(2).map(|s| s.value).collect();
+
+        assert_eq!(first, vec!["cmd.exe /c whoami", "net user"]);
+        assert!(strings.offset() < 64);
+    }
+
     #[test]
     fn test_is_suspicious_string_malicious() {
         assert!(is_suspicious_string("cmd.exe"));
@@ -1519,4 +2022,156 @@ mod tests {
     fn test_categorize_string_none() {
         assert_eq!(categorize_string("normal text"), None);
     }
-}
\ No newline at end of file
+
+    fn pe_section(name: &[u8; 8], virtual_size: u32, raw_size: u32, characteristics: u32) -> pe::section_table::SectionTable {
+        pe::section_table::SectionTable {
+            name: *name,
+            virtual_size,
+            size_of_raw_data: raw_size,
+            characteristics,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_text_section_with_raw_size_mismatch_flagged() {
+        // Code + execute + read, 1 MB in memory but only 512 bytes on disk
+        let section = pe_section(b".text\0\0\0", 0x100000, 0x200, 0x60000020);
+
+        let flags = suspicious_section_flags(&section, 1.2, DEFAULT_SECTION_ENTROPY_THRESHOLD);
+
+        assert_eq!(flags, vec![SectionFlag::RawSizeMismatch]);
+    }
+
+    #[test]
+    fn test_section_flags() {
+        let text = pe_section(b".text\0\0\0", 0x5000, 0x5000, 0x60000020);
+        assert!(suspicious_section_flags(&text, 6.5, DEFAULT_SECTION_ENTROPY_THRESHOLD).is_empty());
+        assert_eq!(suspicious_section_flags(&text, 6.5, 6.0), vec![SectionFlag::HighEntropy]);
+
+        let stub = pe_section(b".stub\0\0\0", 0x1000, 0x1000, 0x60000020);
+        assert_eq!(
+            suspicious_section_flags(&stub, 5.0, DEFAULT_SECTION_ENTROPY_THRESHOLD),
+            vec![SectionFlag::UnexpectedExecutable],
+        );
+
+        // A large .bss-style tail on a data section is normal
+        let data = pe_section(b".rdata\0\0", 0x10000, 0x200, 0x40000040);
+        assert!(suspicious_section_flags(&data, 3.0, DEFAULT_SECTION_ENTROPY_THRESHOLD).is_empty());
+    }
+
+    #[test]
+    fn test_pe_without_nx_compat_flagged() {
+        use pe::dll_characteristic::{IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE, IMAGE_DLLCHARACTERISTICS_NX_COMPAT};
+        let exe = pe::characteristic::IMAGE_FILE_EXECUTABLE_IMAGE;
+        let gui = Some(pe::subsystem::IMAGE_SUBSYSTEM_WINDOWS_GUI);
+
+        let anomalies = pe_header_anomalies(exe, gui, Some(IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE), &PeHeaderChecks::default());
+
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].category, "PE Header");
+        assert_eq!(anomalies[0].description, "DEP is disabled (no NX_COMPAT)");
+        assert_eq!(anomalies[0].details["dll_characteristics"], "0x0040");
+
+        let hardened = IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE | IMAGE_DLLCHARACTERISTICS_NX_COMPAT;
+        assert!(pe_header_anomalies(exe, gui, Some(hardened), &PeHeaderChecks::default()).is_empty());
+
+        let checks = PeHeaderChecks { missing_dep: false, ..PeHeaderChecks::default() };
+        assert!(pe_header_anomalies(exe, gui, Some(IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE), &checks).is_empty());
+    }
+
+    #[test]
+    fn test_stripped_relocs_dll_and_subsystem_flagged() {
+        use pe::characteristic::{IMAGE_FILE_DLL, IMAGE_FILE_RELOCS_STRIPPED};
+        let hardened = pe::dll_characteristic::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE
+            | pe::dll_characteristic::IMAGE_DLLCHARACTERISTICS_NX_COMPAT;
+
+        let anomalies = pe_header_anomalies(
+            IMAGE_FILE_DLL | IMAGE_FILE_RELOCS_STRIPPED,
+            Some(pe::subsystem::IMAGE_SUBSYSTEM_EFI_APPLICATION),
+            Some(hardened),
+            &PeHeaderChecks::default(),
+        );
+
+        let descriptions: Vec<&str> = anomalies.iter().map(|a| a.description.as_str()).collect();
+        assert_eq!(descriptions, vec![
+            "DLL has its relocations stripped, so it only loads at its preferred base",
+            "Unusual subsystem 10",
+        ]);
+    }
+
+    /// A PE32 with one section at RVA 0x1000 holding a TLS directory whose
+    /// AddressOfCallBacks points at `callbacks`
+    fn pe32_with_tls(callbacks: &[u32]) -> Vec<u8> {
+        const IMAGE_BASE: u32 = 0x400000;
+        let mut image = vec![0u8; 0x400];
+        let put = |image: &mut Vec<u8>, offset: usize, bytes: &[u8]| image[offset..offset + bytes.len()].copy_from_slice(bytes);
+
+        put(&mut image, 0, b"MZ");
+        put(&mut image, 0x3C, &0x40u32.to_le_bytes());
+        put(&mut image, 0x40, b"PE\0\0");
+        // COFF: i386, one section, 0xE0-byte optional header, executable
+        put(&mut image, 0x44, &0x14Cu16.to_le_bytes());
+        put(&mut image, 0x46, &1u16.to_le_bytes());
+        put(&mut image, 0x54, &0xE0u16.to_le_bytes());
+        put(&mut image, 0x56, &0x0102u16.to_le_bytes());
+        // Optional header
+        put(&mut image, 0x58, &0x10Bu16.to_le_bytes());
+        put(&mut image, 0x58 + 16, &0x1000u32.to_le_bytes()); // AddressOfEntryPoint
+        put(&mut image, 0x58 + 28, &IMAGE_BASE.to_le_bytes());
+        put(&mut image, 0x58 + 32, &0x1000u32.to_le_bytes()); // SectionAlignment
+        put(&mut image, 0x58 + 36, &0x200u32.to_le_bytes()); // FileAlignment
+        put(&mut image, 0x58 + 56, &0x2000u32.to_le_bytes()); // SizeOfImage
+        put(&mut image, 0x58 + 60, &0x200u32.to_le_bytes()); // SizeOfHeaders
+        put(&mut image, 0x58 + 68, &3u16.to_le_bytes()); // Subsystem
+        put(&mut image, 0x58 + 92, &16u32.to_le_bytes()); // NumberOfRvaAndSizes
+        put(&mut image, 0x58 + 96 + 9 * 8, &0x1000u32.to_le_bytes()); // TLS directory
+        put(&mut image, 0x58 + 96 + 9 * 8 + 4, &24u32.to_le_bytes());
+        // Section header
+        put(&mut image, 0x138, b".tls\0\0\0\0");
+        put(&mut image, 0x140, &0x200u32.to_le_bytes());
+        put(&mut image, 0x144, &0x1000u32.to_le_bytes());
+        put(&mut image, 0x148, &0x200u32.to_le_bytes());
+        put(&mut image, 0x14C, &0x200u32.to_le_bytes());
+        put(&mut image, 0x15C, &0xC0000040u32.to_le_bytes());
+        // IMAGE_TLS_DIRECTORY32 at RVA 0x1000, callback array at RVA 0x1020
+        put(&mut image, 0x200 + 12, &(IMAGE_BASE + 0x1020).to_le_bytes());
+        for (i, callback) in callbacks.iter().chain(&[0]).enumerate() {
+            put(&mut image, 0x220 + i * 4, &callback.to_le_bytes());
+        }
+        image
+    }
+
+    #[test]
+    fn test_tls_callbacks_enumerated() {
+        let data = pe32_with_tls(&[0x401050, 0x401080]);
+        let pe = pe::PE::parse(&data).unwrap();
+
+        let anomaly = tls_callback_anomaly(&pe).unwrap();
+
+        assert_eq!(anomaly.category, "TLS Callbacks");
+        assert_eq!(anomaly.description, "2 TLS callback(s) run before the entry point");
+        assert_eq!(anomaly.details["callbacks"], serde_json::json!(["0x401050", "0x401080"]));
+        assert_eq!(anomaly.details["callback_rvas"], serde_json::json!(["0x1050", "0x1080"]));
+
+        let without = pe32_with_tls(&[]);
+        assert!(tls_callback_anomaly(&pe::PE::parse(&without).unwrap()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_html_report_escapes_sample_data() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("report.html");
+        let data = serde_json::json!({
+            "metadata": { "fileName": "<img src=x onerror=alert(1)>.exe" },
+            "sections": { "strings": ["</pre><script>steal()</script>"] },
+        });
+
+        generate_html_report(data, output.clone()).await.unwrap();
+
+        let html = std::fs::read_to_string(&output).unwrap();
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;.exe"));
+        assert!(html.contains("&lt;/pre&gt;&lt;script&gt;steal()&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
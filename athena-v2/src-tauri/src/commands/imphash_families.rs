@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::RwLock;
+use tauri::AppHandle;
+
+use crate::commands::samples::validate_path;
+
+/// File in the app data directory holding known-family imphashes, loaded
+/// at startup
+pub const FAMILIES_FILE_NAME: &str = "imphash_families.json";
+
+/// An imphash and the malware family known to produce it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImphashFamily {
+    pub imphash: String,
+    pub family: String,
+}
+
+/// Imphashes of known families. Only a few long-lived samples are built
+/// in: a family's imphash changes whenever its builder or toolchain does,
+/// so the map is meant to be extended from current threat intel.
+#[derive(Debug, Clone)]
+pub struct ImphashFamilies {
+    families: BTreeMap<String, Vec<String>>,
+}
+
+impl ImphashFamilies {
+    pub fn empty() -> Self {
+        Self { families: BTreeMap::new() }
+    }
+
+    pub fn defaults() -> Self {
+        let defaults = [
+            ("9ecee117164e0b870a53dd187cdd7174", "WannaCry"),
+            ("829da329ce140d873b4a8bde2cbfaa7e", "CobaltStrike"),
+        ];
+
+        let mut families = Self::empty();
+        families
+            .extend(
+                defaults
+                    .iter()
+                    .map(|(imphash, family)| ImphashFamily {
+                        imphash: imphash.to_string(),
+                        family: family.to_string(),
+                    })
+                    .collect(),
+            )
+            .expect("default imphashes are valid");
+        families
+    }
+
+    /// Add `entries`; an imphash shared by several families hints at all of them
+    pub fn extend(&mut self, entries: Vec<ImphashFamily>) -> Result<()> {
+        for entry in &entries {
+            if entry.imphash.len() != 32 || !entry.imphash.chars().all(|c| c.is_ascii_hexdigit()) {
+                anyhow::bail!("Invalid imphash for family {}: {}", entry.family, entry.imphash);
+            }
+        }
+        for entry in entries {
+            let families = self.families.entry(entry.imphash.to_ascii_lowercase()).or_default();
+            if !families.contains(&entry.family) {
+                families.push(entry.family);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a JSON array of `{imphash, family}` entries
+    pub fn parse_entries(json: &str) -> Result<Vec<ImphashFamily>> {
+        serde_json::from_str(json).context("Failed to parse imphash families")
+    }
+
+    /// Families whose imphash is `imphash`
+    pub fn family_hints(&self, imphash: &str) -> Vec<String> {
+        self.families
+            .get(&imphash.to_ascii_lowercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn entries(&self) -> Vec<ImphashFamily> {
+        self.families
+            .iter()
+            .flat_map(|(imphash, families)| {
+                families.iter().map(|family| ImphashFamily {
+                    imphash: imphash.clone(),
+                    family: family.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for ImphashFamilies {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
+
+/// One import as pefile hashes it: the DLL without its `.dll`, `.ocx` or
+/// `.sys` extension and the function, both lowercased. Imports by ordinal
+/// are named `ord<n>`; pefile's names for ws2_32 and oleaut32 ordinals
+/// aren't looked up.
+pub fn imphash_entry(dll: &str, function: Option<&str>, ordinal: u16) -> String {
+    let dll = dll.to_lowercase();
+    let library = match dll.rsplit_once('.') {
+        Some((stem, "dll" | "ocx" | "sys")) => stem,
+        _ => dll.as_str(),
+    };
+    match function {
+        Some(function) => format!("{}.{}", library, function.to_lowercase()),
+        None => format!("{}.ord{}", library, ordinal),
+    }
+}
+
+/// MD5 of the import entries joined in import table order, comparable with
+/// published imphashes
+pub fn imphash(entries: &[String]) -> Option<String> {
+    if entries.is_empty() {
+        return None;
+    }
+    Some(format!("{:x}", md5::compute(entries.join(",").as_bytes())))
+}
+
+static FAMILIES: Lazy<RwLock<ImphashFamilies>> = Lazy::new(|| RwLock::new(ImphashFamilies::defaults()));
+
+/// Families whose imphash is `imphash` under the active map
+pub fn family_hints(imphash: &str) -> Vec<String> {
+    FAMILIES.read().unwrap_or_else(|e| e.into_inner()).family_hints(imphash)
+}
+
+/// Add entries from a JSON file to the active map
+pub fn load_families_file(path: &Path) -> Result<usize> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let entries = ImphashFamilies::parse_entries(&json)?;
+    let count = entries.len();
+    FAMILIES.write().unwrap_or_else(|e| e.into_inner()).extend(entries)?;
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn get_imphash_families() -> Vec<ImphashFamily> {
+    FAMILIES.read().unwrap_or_else(|e| e.into_inner()).entries()
+}
+
+#[tauri::command]
+pub fn add_imphash_family(imphash: String, family: String) -> Result<(), String> {
+    FAMILIES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .extend(vec![ImphashFamily { imphash, family }])
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn load_imphash_families(path: String, app: AppHandle) -> Result<usize, String> {
+    let path = validate_path(&path, &app)?;
+    load_families_file(&path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn reset_imphash_families() {
+    *FAMILIES.write().unwrap_or_else(|e| e.into_inner()) = ImphashFamilies::defaults();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_imphash_hints_family() {
+        let mut families = ImphashFamilies::empty();
+        let entries = ImphashFamilies::parse_entries(r#"[
+            {"imphash": "0A1B2C3D4E5F60718293A4B5C6D7E8F9", "family": "AgentTesla"},
+            {"imphash": "0a1b2c3d4e5f60718293a4b5c6d7e8f9", "family": "Formbook"},
+            {"imphash": "ffeeddccbbaa99887766554433221100", "family": "Emotet"}
+        ]"#)
+        .unwrap();
+        families.extend(entries).unwrap();
+
+        assert_eq!(
+            families.family_hints("0a1b2c3d4e5f60718293a4b5c6d7e8f9"),
+            vec!["AgentTesla".to_string(), "Formbook".to_string()]
+        );
+        assert_eq!(families.family_hints("FFEEDDCCBBAA99887766554433221100"), vec!["Emotet".to_string()]);
+        assert!(families.family_hints("00000000000000000000000000000000").is_empty());
+        assert_eq!(families.entries().len(), 3);
+    }
+
+    #[test]
+    fn test_malformed_imphash_rejected() {
+        let mut families = ImphashFamilies::empty();
+        let result = families.extend(vec![ImphashFamily {
+            imphash: "not-an-md5".to_string(),
+            family: "Broken".to_string(),
+        }]);
+
+        assert!(result.is_err());
+        assert!(families.entries().is_empty());
+    }
+
+    #[test]
+    fn test_default_families_built_in() {
+        let families = ImphashFamilies::default();
+
+        assert_eq!(families.family_hints("9ECEE117164E0B870A53DD187CDD7174"), vec!["WannaCry".to_string()]);
+        assert!(families.family_hints("00000000000000000000000000000000").is_empty());
+        assert!(ImphashFamilies::empty().entries().is_empty());
+    }
+
+    #[test]
+    fn test_imphash_entries_normalized() {
+        assert_eq!(imphash_entry("KERNEL32.dll", Some("VirtualAlloc"), 0), "kernel32.virtualalloc");
+        assert_eq!(imphash_entry("WS2_32.DLL", None, 115), "ws2_32.ord115");
+        assert_eq!(imphash_entry("msvbvm60.drv", Some("Foo"), 0), "msvbvm60.drv.foo");
+
+        let entries = vec!["kernel32.virtualalloc".to_string(), "user32.messageboxa".to_string()];
+        assert_eq!(
+            imphash(&entries).unwrap(),
+            format!("{:x}", md5::compute(b"kernel32.virtualalloc,user32.messageboxa"))
+        );
+        assert_eq!(imphash(&[]), None);
+    }
+}
@@ -3,6 +3,7 @@ use std::fs::{OpenOptions, create_dir_all};
 use std::io::Write;
 use std::path::PathBuf;
 use tauri::Manager;
+use crate::log_config::{self, LogLevel};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LogMessage {
@@ -71,9 +72,9 @@ pub async fn log_frontend_message(
     file.write_all(log_line.as_bytes())
         .map_err(|e| format!("Failed to write to log file: {}", e))?;
 
-    // Also print to stderr for development
-    #[cfg(debug_assertions)]
-    eprintln!("[FRONTEND] {}", log_line.trim());
+    // Also pass it to the backend log, subject to its minimum level
+    let level = level.parse().unwrap_or(LogLevel::Info);
+    log_config::log(level, "frontend", log_line.trim());
 
     Ok(())
 }
@@ -117,8 +118,19 @@ pub async fn log_frontend_error(
     file.write_all(log_line.as_bytes())
         .map_err(|e| format!("Failed to write to error log file: {}", e))?;
 
-    // Always print errors to stderr
-    eprintln!("[FRONTEND ERROR] {} - {}", message, error);
+    tracing::error!(source = "frontend", "{} - {}", message, error);
 
     Ok(())
 }
+
+#[tauri::command]
+pub fn get_log_level() -> Option<LogLevel> {
+    log_config::current_level()
+}
+
+/// Change the backend's minimum log level (error, warn, info, debug, trace)
+#[tauri::command]
+pub fn set_log_level(level: String) -> Result<(), String> {
+    let level: LogLevel = level.parse().map_err(|e: anyhow::Error| e.to_string())?;
+    log_config::set_level(level).map_err(|e| e.to_string())
+}
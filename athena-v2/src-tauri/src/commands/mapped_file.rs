@@ -0,0 +1,103 @@
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// A sample's contents, memory-mapped where possible so large files are
+/// paged in on demand instead of copied into a `Vec<u8>`
+pub enum MappedFile {
+    Mapped(Mmap),
+    /// Empty files and things that can't be mapped (pipes, some network
+    /// filesystems) are read into memory instead
+    Buffered(Vec<u8>),
+}
+
+impl MappedFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() || metadata.len() == 0 {
+            return std::fs::read(path).map(MappedFile::Buffered);
+        }
+
+        // SAFETY: the mapping is read-only. A sample truncated by another
+        // process while mapped would fault on access, the same risk every
+        // mmap-based scanner takes for large files.
+        match unsafe { Mmap::map(&file) } {
+            Ok(mmap) => Ok(MappedFile::Mapped(mmap)),
+            Err(e) => {
+                tracing::debug!("Falling back to reading {}: {}", path.display(), e);
+                std::fs::read(path).map(MappedFile::Buffered)
+            }
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            MappedFile::Mapped(mmap) => &mmap[..],
+            MappedFile::Buffered(data) => &data[..],
+        }
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        matches!(self, MappedFile::Mapped(_))
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file_analysis::{calculate_entropy, calculate_hashes, extract_strings};
+    use std::io::Write;
+
+    #[test]
+    fn test_mapped_analysis_matches_in_memory() {
+        let mut data = b"MZ\x90\x00".to_vec();
+        data.extend((0..4096u32).map(|i| (i * 31 % 251) as u8));
+        data.extend_from_slice(b"\x00http://update.example.com/payload.bin\x00");
+        data.extend_from_slice(b"CreateRemoteThread\x00VirtualAllocEx\x00");
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        file.flush().unwrap();
+
+        let mapped = MappedFile::open(file.path()).unwrap();
+
+        assert!(mapped.is_mapped());
+        assert_eq!(mapped.as_slice(), data.as_slice());
+        assert_eq!(calculate_entropy(&mapped), calculate_entropy(&data));
+        assert_eq!(
+            serde_json::to_value(calculate_hashes(&mapped)).unwrap(),
+            serde_json::to_value(calculate_hashes(&data)).unwrap(),
+        );
+        assert_eq!(
+            serde_json::to_value(extract_strings(&mapped, 6)).unwrap(),
+            serde_json::to_value(extract_strings(&data, 6)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_empty_file_is_buffered() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        let mapped = MappedFile::open(file.path()).unwrap();
+
+        assert!(!mapped.is_mapped());
+        assert!(mapped.is_empty());
+    }
+}
@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, BufReader, BufRead};
 use std::path::{Path, PathBuf};
-use tauri::{AppHandle, Manager};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
 use crate::commands::file_analysis::ExtractedString;
+use crate::quarantine::{QuarantineStorage, StoredSample};
 
 /// Validate that a path is within allowed directories to prevent directory traversal
 fn validate_path(path: &str, app: &AppHandle) -> Result<PathBuf, String> {
@@ -40,6 +43,8 @@ fn validate_path(path: &str, app: &AppHandle) -> Result<PathBuf, String> {
 const MAX_MEMORY_DUMP_SIZE: u64 = 500 * 1024 * 1024; // 500MB limit
 const MIN_STRING_LENGTH: usize = 4;
 const MAX_STRING_LENGTH: usize = 512;
+const PE_PAGE_SIZE: usize = 0x1000;
+const MAX_CARVED_IMAGE_SIZE: usize = 64 * 1024 * 1024; // Ignore garbage headers
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MemoryRegion {
@@ -95,6 +100,98 @@ pub async fn get_memory_regions(app: AppHandle, file_path: String) -> Result<Vec
     parse_raw_memory_dump(&path, metadata.len())
 }
 
+/// A region whose protection differs between two snapshots
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProtectionChange {
+    pub region: MemoryRegion,
+    pub old_permissions: String,
+    /// Writable and executable now but not before
+    pub became_rwx: bool,
+}
+
+/// Differences between two memory snapshots of the same process
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MemoryRegionDiff {
+    pub allocated: Vec<MemoryRegion>,
+    pub freed: Vec<MemoryRegion>,
+    pub protection_changed: Vec<ProtectionChange>,
+    /// Start addresses of regions that were allocated RWX or became RWX,
+    /// a strong code injection indicator
+    pub rwx_regions: Vec<u64>,
+}
+
+impl MemoryRegionDiff {
+    pub fn injection_suspected(&self) -> bool {
+        !self.rwx_regions.is_empty()
+    }
+}
+
+/// Compare two memory snapshots
+///
+/// Regions are matched by start address. A region whose extent changed is
+/// reported as freed and reallocated.
+///
+/// # Arguments
+/// * `before` - Regions from the earlier snapshot
+/// * `after` - Regions from the later snapshot
+///
+/// # Returns
+/// * `MemoryRegionDiff` - Allocated, freed and protection-changed regions
+#[tauri::command]
+pub fn diff_memory_regions(before: Vec<MemoryRegion>, after: Vec<MemoryRegion>) -> MemoryRegionDiff {
+    let before_by_start: HashMap<u64, &MemoryRegion> =
+        before.iter().map(|r| (r.start_address, r)).collect();
+    let after_by_start: HashMap<u64, &MemoryRegion> =
+        after.iter().map(|r| (r.start_address, r)).collect();
+
+    let mut diff = MemoryRegionDiff::default();
+
+    for region in &after {
+        match before_by_start.get(&region.start_address) {
+            Some(old) if old.end_address == region.end_address => {
+                if old.permissions != region.permissions {
+                    let became_rwx = is_rwx(&region.permissions) && !is_rwx(&old.permissions);
+                    if became_rwx {
+                        diff.rwx_regions.push(region.start_address);
+                    }
+                    diff.protection_changed.push(ProtectionChange {
+                        region: region.clone(),
+                        old_permissions: old.permissions.clone(),
+                        became_rwx,
+                    });
+                }
+            }
+            _ => {
+                if is_rwx(&region.permissions) {
+                    diff.rwx_regions.push(region.start_address);
+                }
+                diff.allocated.push(region.clone());
+            }
+        }
+    }
+
+    for region in &before {
+        match after_by_start.get(&region.start_address) {
+            Some(new) if new.end_address == region.end_address => {}
+            _ => diff.freed.push(region.clone()),
+        }
+    }
+
+    diff
+}
+
+/// Whether a protection string allows both writing and executing.
+/// Accepts Linux maps style (`rwxp`) and Windows constants
+/// (`PAGE_EXECUTE_READWRITE`).
+fn is_rwx(permissions: &str) -> bool {
+    let upper = permissions.to_ascii_uppercase();
+    if upper.contains("EXECUTE_READWRITE") || upper.contains("EXECUTE_WRITECOPY") {
+        return true;
+    }
+    let flags: Vec<char> = permissions.chars().take(3).collect();
+    flags.len() == 3 && flags[1] == 'w' && flags[2] == 'x'
+}
+
 /// Extract strings from memory dump
 ///
 /// Extracts ASCII and Unicode strings from a memory dump file.
@@ -176,6 +273,231 @@ pub async fn extract_strings_from_dump(
     Ok(strings)
 }
 
+/// PE image recovered from a memory region and rebuilt into file layout
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CarvedImage {
+    /// Address the image was found at
+    pub address: u64,
+    pub region_start: u64,
+    pub is_64bit: bool,
+    pub section_count: usize,
+    /// Rebuilt image, ready to write to disk
+    pub bytes: Vec<u8>,
+}
+
+/// Carve injected PE images out of a memory dump
+///
+/// Scans RWX and private (not file-backed) regions for page-aligned MZ/PE
+/// headers. Region addresses are treated as offsets into `dump`, which is
+/// how `get_memory_regions` describes raw dumps. Each image found is
+/// converted from its memory-mapped layout back to file layout: sections
+/// are moved to `FileAlignment` boundaries and the section table's raw
+/// pointers and sizes are rewritten to match.
+pub fn carve_pe_from_memory(dump: &[u8], regions: &[MemoryRegion]) -> Vec<CarvedImage> {
+    let mut images = Vec::new();
+
+    for region in regions {
+        if !is_rwx(&region.permissions) && region.mapped_file.is_some() {
+            continue;
+        }
+
+        let start = region.start_address as usize;
+        let end = (region.end_address as usize).min(dump.len());
+        if start >= end {
+            continue;
+        }
+
+        for offset in (start..end).step_by(PE_PAGE_SIZE) {
+            if let Some((is_64bit, section_count, bytes)) = rebuild_pe(&dump[offset..end]) {
+                images.push(CarvedImage {
+                    address: offset as u64,
+                    region_start: region.start_address,
+                    is_64bit,
+                    section_count,
+                    bytes,
+                });
+            }
+        }
+    }
+
+    images
+}
+
+/// Carve injected PE images out of a raw memory dump
+///
+/// # Arguments
+/// * `file_path` - Path to memory dump file
+///
+/// # Returns
+/// * `Result<Vec<CarvedImage>, String>` - Rebuilt images or error
+#[tauri::command]
+pub async fn carve_pe_images_from_dump(app: AppHandle, file_path: String) -> Result<Vec<CarvedImage>, String> {
+    let (dump, regions) = read_raw_memory_dump(&app, &file_path)?;
+    Ok(carve_pe_from_memory(&dump, &regions))
+}
+
+/// Store carved images in quarantine as children of the dump they came from
+pub fn quarantine_carved_images(
+    storage: &QuarantineStorage,
+    parent_sha256: &str,
+    images: &[CarvedImage],
+) -> anyhow::Result<Vec<StoredSample>> {
+    images
+        .iter()
+        .map(|image| {
+            let filename = format!("carved_{:#x}.bin", image.address);
+            storage.store_derived_sample(&image.bytes, &filename, parent_sha256, "carved_pe")
+        })
+        .collect()
+}
+
+/// Reject anything but a SHA-256 hex digest before it becomes a quarantine
+/// path; the storage shards on its first four characters and joins it as a
+/// file name
+fn validate_sha256(sha256: &str) -> Result<(), String> {
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid SHA-256: {:?}", sha256));
+    }
+    Ok(())
+}
+
+/// Carve injected PE images out of a quarantined memory dump and quarantine
+/// them, linked to the dump's provenance
+#[tauri::command]
+pub async fn carve_pe_images_from_sample(
+    storage: State<'_, Arc<Mutex<QuarantineStorage>>>,
+    sha256: String,
+) -> Result<Vec<StoredSample>, String> {
+    validate_sha256(&sha256)?;
+    let storage = storage.lock().map_err(|e| e.to_string())?;
+    let path = storage.get_sample_path(&sha256);
+
+    let dump = storage
+        .read_sample(&sha256)
+        .map_err(|e| format!("Failed to read sample: {}", e))?;
+    if dump.len() as u64 > MAX_MEMORY_DUMP_SIZE {
+        return Err(format!(
+            "File too large: {} bytes (max: {} bytes)",
+            dump.len(),
+            MAX_MEMORY_DUMP_SIZE
+        ));
+    }
+    let regions = parse_raw_memory_dump(&path, dump.len() as u64)?;
+
+    let images = carve_pe_from_memory(&dump, &regions);
+    quarantine_carved_images(&storage, &sha256, &images)
+        .map_err(|e| format!("Failed to quarantine carved images: {}", e))
+}
+
+/// Read a raw memory dump along with the regions detected in it
+pub(crate) fn read_raw_memory_dump(
+    app: &AppHandle,
+    file_path: &str,
+) -> Result<(Vec<u8>, Vec<MemoryRegion>), String> {
+    // Validate path to prevent directory traversal
+    let path = validate_path(file_path, app)?;
+
+    let metadata = std::fs::metadata(&path)
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+
+    if metadata.len() > MAX_MEMORY_DUMP_SIZE {
+        return Err(format!(
+            "File too large: {} bytes (max: {} bytes)",
+            metadata.len(),
+            MAX_MEMORY_DUMP_SIZE
+        ));
+    }
+
+    let dump = std::fs::read(&path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let regions = parse_raw_memory_dump(&path, metadata.len())?;
+
+    Ok((dump, regions))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn align_up(value: usize, alignment: usize) -> usize {
+    if alignment == 0 {
+        value
+    } else {
+        value.div_ceil(alignment) * alignment
+    }
+}
+
+/// Rebuild a memory-mapped PE starting at `mapped[0]` into file layout.
+/// Returns (is_64bit, section_count, bytes).
+fn rebuild_pe(mapped: &[u8]) -> Option<(bool, usize, Vec<u8>)> {
+    if mapped.get(0..2)? != b"MZ" {
+        return None;
+    }
+
+    let pe_offset = read_u32(mapped, 0x3C)? as usize;
+    if pe_offset > PE_PAGE_SIZE || mapped.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    let coff = pe_offset + 4;
+    let section_count = read_u16(mapped, coff + 2)? as usize;
+    let optional_size = read_u16(mapped, coff + 16)? as usize;
+    let optional = coff + 20;
+
+    let is_64bit = match read_u16(mapped, optional)? {
+        0x10b => false,
+        0x20b => true,
+        _ => return None,
+    };
+
+    let file_alignment = read_u32(mapped, optional + 36)? as usize;
+    let size_of_headers = read_u32(mapped, optional + 60)? as usize;
+    if section_count == 0 || section_count > 96 || !file_alignment.is_power_of_two() {
+        return None;
+    }
+
+    let section_table = optional + optional_size;
+    let headers_end = section_table + section_count * 40;
+    let headers_len = align_up(size_of_headers.max(headers_end), file_alignment);
+    if headers_end > mapped.len() || headers_len > MAX_CARVED_IMAGE_SIZE {
+        return None;
+    }
+
+    let mut out = vec![0u8; headers_len];
+    let copied = headers_len.min(mapped.len());
+    out[..copied].copy_from_slice(&mapped[..copied]);
+
+    for index in 0..section_count {
+        let header = section_table + index * 40;
+        let virtual_size = read_u32(mapped, header + 8)? as usize;
+        let virtual_address = read_u32(mapped, header + 12)? as usize;
+        let raw_size = read_u32(mapped, header + 16)? as usize;
+
+        // In memory the section spans its virtual size; take what was mapped
+        let wanted = if virtual_size > 0 { virtual_size } else { raw_size };
+        let available = mapped.len().saturating_sub(virtual_address).min(wanted);
+        let new_raw_size = align_up(available, file_alignment);
+        let new_pointer = if new_raw_size > 0 { out.len() } else { 0 };
+
+        if out.len() + new_raw_size > MAX_CARVED_IMAGE_SIZE {
+            return None;
+        }
+        if available > 0 {
+            out.extend_from_slice(&mapped[virtual_address..virtual_address + available]);
+            out.resize(new_pointer + new_raw_size, 0);
+        }
+
+        out[header + 16..header + 20].copy_from_slice(&(new_raw_size as u32).to_le_bytes());
+        out[header + 20..header + 24].copy_from_slice(&(new_pointer as u32).to_le_bytes());
+    }
+
+    Some((is_64bit, section_count, out))
+}
+
 // Parse /proc/[pid]/maps format
 fn parse_proc_maps(path: &Path) -> Result<Vec<MemoryRegion>, String> {
     let file = File::open(path)
@@ -491,6 +813,18 @@ fn categorize_string(s: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_validate_sha256() {
+        assert!(validate_sha256(&"ab".repeat(32)).is_ok());
+        assert!(validate_sha256(&"AB".repeat(32)).is_ok());
+
+        assert!(validate_sha256("").is_err());
+        assert!(validate_sha256("abc").is_err());
+        assert!(validate_sha256(&"ab".repeat(33)).is_err());
+        assert!(validate_sha256(&format!("../../{}", "a".repeat(58))).is_err());
+        assert!(validate_sha256(&"zz".repeat(32)).is_err());
+    }
+
     #[test]
     fn test_is_printable_ascii() {
         assert!(is_printable_ascii(65)); // 'A'
@@ -533,4 +867,156 @@ mod tests {
         assert_eq!(classify_region_type("/lib/x86_64-linux-gnu/libc.so.6"), "shared_library");
         assert_eq!(classify_region_type("/usr/bin/program"), "mapped_file");
     }
+
+    fn region(start: u64, end: u64, permissions: &str) -> MemoryRegion {
+        MemoryRegion {
+            start_address: start,
+            end_address: end,
+            size: end - start,
+            permissions: permissions.to_string(),
+            region_type: "anonymous".to_string(),
+            mapped_file: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_memory_regions_flags_rwx_flip() {
+        let before = vec![
+            region(0x400000, 0x401000, "r-xp"),
+            region(0x10000, 0x20000, "rw-p"),
+            region(0x30000, 0x31000, "rw-p"),
+        ];
+        let after = vec![
+            region(0x400000, 0x401000, "r-xp"),
+            region(0x10000, 0x20000, "rwxp"),
+            region(0x50000, 0x51000, "r--p"),
+        ];
+
+        let diff = diff_memory_regions(before, after);
+
+        assert_eq!(diff.protection_changed.len(), 1);
+        let change = &diff.protection_changed[0];
+        assert_eq!(change.region.start_address, 0x10000);
+        assert_eq!(change.old_permissions, "rw-p");
+        assert!(change.became_rwx);
+
+        assert_eq!(diff.allocated.len(), 1);
+        assert_eq!(diff.allocated[0].start_address, 0x50000);
+        assert_eq!(diff.freed.len(), 1);
+        assert_eq!(diff.freed[0].start_address, 0x30000);
+
+        assert_eq!(diff.rwx_regions, vec![0x10000]);
+        assert!(diff.injection_suspected());
+    }
+
+    #[test]
+    fn test_is_rwx() {
+        assert!(is_rwx("rwxp"));
+        assert!(is_rwx("PAGE_EXECUTE_READWRITE"));
+        assert!(!is_rwx("r-xp"));
+        assert!(!is_rwx("PAGE_EXECUTE_READ"));
+    }
+
+    /// A PE32 as the loader maps it: headers in the first page, `.text` at
+    /// RVA 0x1000 and `.data` at RVA 0x2000, with stale raw pointers
+    fn mapped_pe() -> Vec<u8> {
+        let mut image = vec![0u8; 0x3000];
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+
+        let coff = 0x84;
+        image[coff..coff + 2].copy_from_slice(&0x14Cu16.to_le_bytes());
+        image[coff + 2..coff + 4].copy_from_slice(&2u16.to_le_bytes());
+        image[coff + 16..coff + 18].copy_from_slice(&0xE0u16.to_le_bytes());
+        image[coff + 18..coff + 20].copy_from_slice(&0x0102u16.to_le_bytes());
+
+        let opt = coff + 20;
+        image[opt..opt + 2].copy_from_slice(&0x10Bu16.to_le_bytes());
+        image[opt + 16..opt + 20].copy_from_slice(&0x1000u32.to_le_bytes());
+        image[opt + 28..opt + 32].copy_from_slice(&0x400000u32.to_le_bytes());
+        image[opt + 32..opt + 36].copy_from_slice(&0x1000u32.to_le_bytes());
+        image[opt + 36..opt + 40].copy_from_slice(&0x200u32.to_le_bytes());
+        image[opt + 56..opt + 60].copy_from_slice(&0x3000u32.to_le_bytes());
+        image[opt + 60..opt + 64].copy_from_slice(&0x200u32.to_le_bytes());
+        image[opt + 68..opt + 70].copy_from_slice(&2u16.to_le_bytes());
+        image[opt + 92..opt + 96].copy_from_slice(&16u32.to_le_bytes());
+
+        let sections = opt + 0xE0;
+        for (i, (name, rva, size, flags)) in [
+            (b".text\0\0\0", 0x1000u32, 0x80u32, 0xE000_0020u32),
+            (b".data\0\0\0", 0x2000, 0x40, 0xC000_0040),
+        ].iter().enumerate() {
+            let h = sections + i * 40;
+            image[h..h + 8].copy_from_slice(*name);
+            image[h + 8..h + 12].copy_from_slice(&size.to_le_bytes());
+            image[h + 12..h + 16].copy_from_slice(&rva.to_le_bytes());
+            image[h + 16..h + 20].copy_from_slice(&0x200u32.to_le_bytes());
+            // Raw pointers from the original file no longer match anything
+            image[h + 20..h + 24].copy_from_slice(&0xDEAD_0000u32.to_le_bytes());
+            image[h + 36..h + 40].copy_from_slice(&flags.to_le_bytes());
+        }
+
+        image[0x1000..0x1080].fill(0xCC);
+        image[0x2000..0x2040].fill(0x41);
+        image
+    }
+
+    #[test]
+    fn test_carved_image_provenance_links_to_parent() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let storage = QuarantineStorage::new(temp_dir.path()).unwrap();
+
+        let mut dump = vec![0u8; 0x8000];
+        dump[0x3000..0x6000].copy_from_slice(&mapped_pe());
+        let parent = storage.store_sample(&dump, "lsass.dmp").unwrap();
+        let parent_id = parent.metadata.provenance.unwrap().sample_id;
+
+        let regions = vec![region(0x2000, 0x8000, "rwxp")];
+        let images = carve_pe_from_memory(&dump, &regions);
+        let children = quarantine_carved_images(&storage, &parent.sha256, &images).unwrap();
+
+        assert_eq!(children.len(), 1);
+        let provenance = children[0].metadata.provenance.as_ref().unwrap();
+        assert_eq!(provenance.source, "carved_pe");
+        assert_eq!(provenance.parent_sample_id.as_deref(), Some(parent_id.as_str()));
+        assert_ne!(provenance.sample_id, parent_id);
+
+        // Recorded in the stored metadata, not just the returned value
+        let stored = storage.load_metadata(&children[0].sha256).unwrap().unwrap();
+        assert_eq!(stored.provenance.as_ref(), Some(provenance));
+    }
+
+    #[test]
+    fn test_carve_pe_from_memory_rebuilds_file_layout() {
+        let mut dump = vec![0u8; 0x8000];
+        dump[0x3000..0x6000].copy_from_slice(&mapped_pe());
+
+        let regions = vec![
+            region(0x0000, 0x2000, "r-xp"),
+            region(0x2000, 0x8000, "rwxp"),
+        ];
+
+        let images = carve_pe_from_memory(&dump, &regions);
+        assert_eq!(images.len(), 1);
+
+        let image = &images[0];
+        assert_eq!(image.address, 0x3000);
+        assert_eq!(image.region_start, 0x2000);
+        assert!(!image.is_64bit);
+        assert_eq!(image.section_count, 2);
+
+        let pe = goblin::pe::PE::parse(&image.bytes).expect("rebuilt headers should parse");
+        assert_eq!(pe.sections.len(), 2);
+
+        let text = &pe.sections[0];
+        assert_eq!(text.pointer_to_raw_data, 0x200);
+        assert_eq!(text.size_of_raw_data, 0x200);
+        assert!(image.bytes[0x200..0x280].iter().all(|&b| b == 0xCC));
+
+        let data = &pe.sections[1];
+        assert_eq!(data.pointer_to_raw_data, 0x400);
+        assert!(image.bytes[0x400..0x440].iter().all(|&b| b == 0x41));
+        assert_eq!(image.bytes.len(), 0x600);
+    }
 }
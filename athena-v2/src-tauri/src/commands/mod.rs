@@ -1,6 +1,14 @@
 pub mod file_ops;
 pub mod file_analysis;
+pub mod mapped_file;
+pub mod string_categories;
+pub mod encoded_args;
+pub mod imphash_families;
+pub mod capabilities;
+pub mod batch_analysis;
 pub mod wasm_file_bridge;
+pub mod analysis_passes;
+pub mod self_test;
 pub mod yara_scanner;
 pub mod yara_rules;
 pub mod system;
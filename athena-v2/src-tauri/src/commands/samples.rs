@@ -10,7 +10,7 @@ use std::sync::{Arc, Mutex};
 use tauri::{State, AppHandle, Manager};
 
 /// Validate that a path is within allowed directories to prevent directory traversal
-fn validate_path(path: &str, app: &AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn validate_path(path: &str, app: &AppHandle) -> Result<PathBuf, String> {
     let path_buf = Path::new(path);
 
     // Canonicalize the path to resolve any .. or symlinks
@@ -88,6 +88,7 @@ impl From<SampleMetadata> for SampleSummary {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UploadResult {
     pub sha256: String,
+    pub sample_id: Option<String>,
     pub is_duplicate: bool,
     pub file_type: String,
     pub size: u64,
@@ -150,6 +151,7 @@ pub async fn register_sample(
     };
 
     Ok(UploadResult {
+        sample_id: stored.metadata.provenance.map(|p| p.sample_id),
         sha256: stored.sha256,
         is_duplicate: stored.is_duplicate,
         file_type: file_type_str,
@@ -17,6 +17,8 @@ use crate::sandbox::{
     // Anti-evasion types
     anti_evasion::{AntiEvasionManager, EvasionAttempt, VmArtifact},
 };
+use crate::html::escape_html;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
@@ -451,65 +453,158 @@ pub fn format_sandbox_error(error_type: String, details: String) -> Result<Strin
     Ok(error.to_string())
 }
 
-/// Aggregate execution report into a threat score
+/// Points `calculate_threat_score` gives each kind of evidence, and the
+/// scores at which the risk level steps up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreatScoreConfig {
+    /// Points per behavioral event, by severity
+    pub critical_event: f64,
+    pub high_event: f64,
+    pub medium_event: f64,
+    pub low_event: f64,
+    pub other_event: f64,
+    /// Points per MITRE technique, scaled by its confidence
+    pub mitre_attack: f64,
+    /// Points once for any ptrace call
+    pub process_injection: f64,
+    /// Behavioral events worth at least this much are listed as factors
+    pub factor_threshold: f64,
+    pub medium_risk: f64,
+    pub high_risk: f64,
+    pub critical_risk: f64,
+}
+
+impl Default for ThreatScoreConfig {
+    fn default() -> Self {
+        Self {
+            critical_event: 25.0,
+            high_event: 15.0,
+            medium_event: 8.0,
+            low_event: 3.0,
+            other_event: 1.0,
+            mitre_attack: 20.0,
+            process_injection: 30.0,
+            factor_threshold: 15.0,
+            medium_risk: 25.0,
+            high_risk: 50.0,
+            critical_risk: 75.0,
+        }
+    }
+}
+
+/// Aggregate execution report into a threat score, using the default
+/// weights unless `config` is given
 #[command]
-pub fn calculate_threat_score(report: ExecutionReport) -> Result<ThreatScoreResult, String> {
-    let mut score: f64 = 0.0;
+pub fn calculate_threat_score(report: ExecutionReport, config: Option<ThreatScoreConfig>) -> Result<ThreatScoreResult, String> {
+    Ok(score_report(&report, &config.unwrap_or_default()))
+}
+
+pub fn score_report(report: &ExecutionReport, config: &ThreatScoreConfig) -> ThreatScoreResult {
     let mut factors = Vec::new();
+    let mut factor_scores = BTreeMap::new();
+    let mut explanation = Vec::new();
 
     // Score based on behavioral events
+    let mut behavior_score = 0.0;
     for event in &report.behavioral_events {
         let event_score = match event.severity.as_str() {
-            "Critical" => 25.0,
-            "High" => 15.0,
-            "Medium" => 8.0,
-            "Low" => 3.0,
-            _ => 1.0,
+            "Critical" => config.critical_event,
+            "High" => config.high_event,
+            "Medium" => config.medium_event,
+            "Low" => config.low_event,
+            _ => config.other_event,
         };
-        score += event_score;
-        if event_score >= 15.0 {
+        behavior_score += event_score;
+        if event_score >= config.factor_threshold {
             factors.push(format!("{}: {}", event.severity, event.description.chars().take(50).collect::<String>()));
         }
+        explanation.push(ScoreContribution {
+            factor: format!("{} behavior: {}", event.severity, event.description),
+            raw_value: 1.0,
+            weight: event_score,
+            points: event_score,
+        });
     }
+    factor_scores.insert("behavioral_events".to_string(), behavior_score);
 
     // Score based on MITRE attacks
+    let mut attack_score = 0.0;
     for attack in &report.mitre_attacks {
-        let attack_score = attack.confidence * 20.0;
-        score += attack_score;
+        let points = attack.confidence * config.mitre_attack;
+        attack_score += points;
         factors.push(format!("MITRE {}: {}", attack.id, attack.name));
+        explanation.push(ScoreContribution {
+            factor: format!("MITRE {}: {}", attack.id, attack.name),
+            raw_value: attack.confidence,
+            weight: config.mitre_attack,
+            points,
+        });
     }
+    factor_scores.insert("mitre_attacks".to_string(), attack_score);
 
     // Score based on suspicious syscalls
+    let mut injection_score = 0.0;
     if let Some(ptrace_count) = report.syscall_summary.get("ptrace") {
         if *ptrace_count > 0 {
-            score += 30.0;
+            injection_score = config.process_injection;
             factors.push(format!("Process injection detected ({} ptrace calls)", ptrace_count));
+            explanation.push(ScoreContribution {
+                factor: format!("Process injection ({} ptrace calls)", ptrace_count),
+                raw_value: 1.0,
+                weight: config.process_injection,
+                points: config.process_injection,
+            });
         }
     }
+    factor_scores.insert("process_injection".to_string(), injection_score);
 
     // Normalize score to 0-100
+    let score: f64 = factor_scores.values().sum();
     let normalized_score = (score.min(100.0)).max(0.0);
+    if normalized_score != score {
+        // Keep the explanation adding up to the reported score
+        explanation.push(ScoreContribution {
+            factor: "Score limited to 0-100".to_string(),
+            raw_value: score - normalized_score,
+            weight: -1.0,
+            points: normalized_score - score,
+        });
+    }
 
-    let risk_level = if normalized_score >= 75.0 {
+    let risk_level = if normalized_score >= config.critical_risk {
         "Critical"
-    } else if normalized_score >= 50.0 {
+    } else if normalized_score >= config.high_risk {
         "High"
-    } else if normalized_score >= 25.0 {
+    } else if normalized_score >= config.medium_risk {
         "Medium"
     } else {
         "Low"
     };
 
-    Ok(ThreatScoreResult {
+    ThreatScoreResult {
         score: normalized_score,
         risk_level: risk_level.to_string(),
         contributing_factors: factors,
+        factor_scores,
+        verdict_explanation: explanation,
         behavioral_events_count: report.behavioral_events.len(),
         mitre_attacks_count: report.mitre_attacks.len(),
         file_operations_count: report.file_operations.len(),
         network_connections_count: report.network_connections.len(),
         processes_created_count: report.processes_created.len(),
-    })
+    }
+}
+
+/// One piece of evidence and the points it added to the threat score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreContribution {
+    pub factor: String,
+    /// 1 for something seen, or a MITRE technique's confidence
+    pub raw_value: f64,
+    /// Weight from the `ThreatScoreConfig`; `points` is `raw_value * weight`
+    pub weight: f64,
+    pub points: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -517,6 +612,12 @@ pub struct ThreatScoreResult {
     pub score: f64,
     pub risk_level: String,
     pub contributing_factors: Vec<String>,
+    /// Points from each kind of evidence before the score is capped at 100
+    #[serde(default)]
+    pub factor_scores: BTreeMap<String, f64>,
+    /// Every contribution to `score`, summing to it
+    #[serde(default)]
+    pub verdict_explanation: Vec<ScoreContribution>,
     pub behavioral_events_count: usize,
     pub mitre_attacks_count: usize,
     pub file_operations_count: usize,
@@ -613,12 +714,16 @@ pub struct VolatilityStatus {
 /// - VM detection (checking /proc/scsi, DMI, Docker markers)
 /// - Debugger detection (ptrace TRACEME)
 /// - Sleep evasion (long sleep calls to timeout sandbox)
+/// - Geofencing (branching on the keyboard layout or locale), found in the
+///   sample's code when `file_path` is given, since the trace has no
+///   Windows API calls
 ///
 /// Returns a list of detected evasion attempts with timestamps, descriptions, and
 /// whether the anti-evasion measures successfully blocked them.
 #[command]
 pub fn detect_sandbox_evasion(
     report: ExecutionReport,
+    file_path: Option<SafePathBuf>,
 ) -> Result<Vec<EvasionAttempt>, String> {
     let manager = AntiEvasionManager::new();
     let mut evasion_attempts = Vec::new();
@@ -645,6 +750,11 @@ pub fn detect_sandbox_evasion(
         }
     }
 
+    if let Some(path) = file_path {
+        let data = std::fs::read(path.as_ref()).map_err(|e| format!("Failed to read sample: {}", e))?;
+        evasion_attempts.extend(manager.detect_geofencing_in_binary(&data));
+    }
+
     // Also check the syscall summary for high-level patterns
     for (syscall_name, count) in &report.syscall_summary {
         // Check for suspicious patterns like many openat calls (VM detection attempts)
@@ -769,6 +879,202 @@ pub struct VideoRecordingInfo {
     pub file_size_bytes: u64,
 }
 
+/// Render a sandbox report as one self-contained HTML page (inline CSS and
+/// JS, no external references) fit for emailing. Everything the sample
+/// produced is escaped, so opening the page never runs the sample's markup.
+#[command]
+pub fn generate_sandbox_html_report(report: ExecutionReport) -> Result<String, String> {
+    Ok(generate_html_report(&report))
+}
+
+/// Page styles and timeline filter, inlined so the report stands alone
+const HTML_REPORT_STYLE: &str = r#"
+        body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; margin: 0; padding: 20px; background: #1a1a2e; color: #eee; }
+        .container { max-width: 1200px; margin: 0 auto; }
+        h1 { color: #ff69b4; border-bottom: 2px solid #ff69b4; padding-bottom: 10px; }
+        h2 { color: #00d4ff; margin-top: 30px; }
+        .section { background: #16213e; padding: 20px; border-radius: 8px; margin-bottom: 15px; }
+        .verdict { font-size: 20px; font-weight: bold; }
+        table { width: 100%; border-collapse: collapse; font-size: 13px; }
+        th, td { text-align: left; padding: 6px 8px; border-bottom: 1px solid #2a2a4e; vertical-align: top; }
+        code { background: #0f0f23; padding: 1px 4px; border-radius: 3px; word-break: break-all; }
+        ul.tree, ul.tree ul { list-style: none; padding-left: 20px; }
+        .severity-critical { color: #ff4444; }
+        .severity-high { color: #ff8800; }
+        .severity-medium { color: #ffcc00; }
+        .severity-low { color: #00cc00; }
+        .severity-info { color: #aaaaaa; }
+"#;
+
+const HTML_REPORT_SCRIPT: &str = r#"
+        document.getElementById('severity-filter').addEventListener('change', function (e) {
+            var wanted = e.target.value;
+            document.querySelectorAll('#timeline tbody tr').forEach(function (row) {
+                row.style.display = !wanted || row.dataset.severity === wanted ? '' : 'none';
+            });
+        });
+"#;
+
+/// Build the page for `generate_sandbox_html_report`
+pub fn generate_html_report(report: &ExecutionReport) -> String {
+    use std::collections::BTreeSet;
+    use std::fmt::Write;
+
+    let verdict = score_report(report, &ThreatScoreConfig::default());
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n    <meta charset=\"UTF-8\">\n");
+    // Nothing outside the page may load, even if escaping were ever bypassed
+    html.push_str("    <meta http-equiv=\"Content-Security-Policy\" content=\"default-src 'none'; style-src 'unsafe-inline'; script-src 'unsafe-inline'\">\n");
+    let _ = writeln!(html, "    <title>Athena Sandbox Report - {}</title>", escape_html(&report.session_id));
+    let _ = writeln!(html, "    <style>{}    </style>\n</head>\n<body>\n<div class=\"container\">", HTML_REPORT_STYLE);
+    html.push_str("<h1>Athena Sandbox Report</h1>\n");
+
+    // Verdict
+    let _ = writeln!(
+        html,
+        "<div class=\"section\">\n<p class=\"verdict {}\">Verdict: {} ({:.0}/100)</p>",
+        severity_class(&verdict.risk_level),
+        escape_html(&verdict.risk_level),
+        verdict.score
+    );
+    let _ = writeln!(
+        html,
+        "<p>Session <code>{}</code>, exit code {}, ran for {} ms</p>",
+        escape_html(&report.session_id),
+        report.exit_code,
+        report.execution_time_ms
+    );
+    if !verdict.contributing_factors.is_empty() {
+        html.push_str("<ul>\n");
+        for factor in &verdict.contributing_factors {
+            let _ = writeln!(html, "<li>{}</li>", escape_html(factor));
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</div>\n");
+
+    // IOCs
+    let destinations: BTreeSet<String> = report
+        .network_connections
+        .iter()
+        .map(|c| format!("{}:{} ({})", c.destination, c.port, c.protocol))
+        .collect();
+    let written_files: BTreeSet<&str> = report
+        .file_operations
+        .iter()
+        .filter(|op| matches!(op.operation.as_str(), "CREATE" | "MODIFY" | "DELETE"))
+        .map(|op| op.path.as_str())
+        .collect();
+    html.push_str("<h2>Indicators of Compromise</h2>\n<div class=\"section\">\n<table>\n<thead><tr><th>Type</th><th>Value</th></tr></thead>\n<tbody>\n");
+    for destination in &destinations {
+        let _ = writeln!(html, "<tr><td>Network</td><td><code>{}</code></td></tr>", escape_html(destination));
+    }
+    for path in &written_files {
+        let _ = writeln!(html, "<tr><td>File</td><td><code>{}</code></td></tr>", escape_html(path));
+    }
+    for process in &report.processes_created {
+        let _ = writeln!(html, "<tr><td>Process</td><td><code>{}</code></td></tr>", escape_html(&process.command_line));
+    }
+    html.push_str("</tbody>\n</table>\n</div>\n");
+
+    // Process tree
+    html.push_str("<h2>Process Tree</h2>\n<div class=\"section\">\n<ul class=\"tree\">\n");
+    let pids: BTreeSet<u32> = report.processes_created.iter().map(|p| p.pid).collect();
+    let mut shown = BTreeSet::new();
+    for root in report
+        .processes_created
+        .iter()
+        .filter(|p| !matches!(p.parent_pid, Some(parent) if pids.contains(&parent)))
+    {
+        push_process_node(&mut html, root, &report.processes_created, &mut shown);
+    }
+    html.push_str("</ul>\n</div>\n");
+
+    // Timeline
+    let mut events: Vec<&BehaviorEvent> = report.behavioral_events.iter().collect();
+    events.sort_by_key(|e| e.timestamp);
+    html.push_str("<h2>Timeline</h2>\n<div class=\"section\">\n");
+    html.push_str("<label>Severity <select id=\"severity-filter\"><option value=\"\">All</option>");
+    for severity in ["critical", "high", "medium", "low", "info"] {
+        let _ = write!(html, "<option value=\"{0}\">{0}</option>", severity);
+    }
+    html.push_str("</select></label>\n<table id=\"timeline\">\n<thead><tr><th>Time</th><th>Severity</th><th>Event</th><th>Description</th><th>MITRE</th></tr></thead>\n<tbody>\n");
+    for event in events {
+        let class = severity_class(&event.severity);
+        let _ = writeln!(
+            html,
+            "<tr data-severity=\"{}\"><td>{}</td><td class=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            class.trim_start_matches("severity-"),
+            event.timestamp,
+            class,
+            escape_html(&event.severity),
+            escape_html(&event.event_type),
+            escape_html(&event.description),
+            escape_html(event.mitre_attack_id.as_deref().unwrap_or(""))
+        );
+    }
+    html.push_str("</tbody>\n</table>\n</div>\n");
+
+    // MITRE ATT&CK mapping
+    html.push_str("<h2>MITRE ATT&amp;CK</h2>\n<div class=\"section\">\n<table>\n<thead><tr><th>Technique</th><th>Name</th><th>Tactic</th><th>Confidence</th><th>Description</th></tr></thead>\n<tbody>\n");
+    for attack in &report.mitre_attacks {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.0}%</td><td>{}</td></tr>",
+            escape_html(&attack.id),
+            escape_html(&attack.name),
+            escape_html(&get_tactic_for_technique(&attack.id)),
+            attack.confidence * 100.0,
+            escape_html(&attack.description)
+        );
+    }
+    html.push_str("</tbody>\n</table>\n</div>\n");
+
+    let _ = writeln!(html, "</div>\n<script>{}</script>\n</body>\n</html>", HTML_REPORT_SCRIPT);
+    html
+}
+
+/// `process` and its descendants as nested list items; `shown` guards
+/// against PID reuse making the tree a cycle
+fn push_process_node(html: &mut String, process: &ProcessInfo, processes: &[ProcessInfo], shown: &mut std::collections::BTreeSet<u32>) {
+    use std::fmt::Write;
+
+    if !shown.insert(process.pid) {
+        return;
+    }
+    let _ = write!(
+        html,
+        "<li>{} <code>{}</code> (pid {})",
+        escape_html(&process.name),
+        escape_html(&process.command_line),
+        process.pid
+    );
+    let children: Vec<&ProcessInfo> = processes
+        .iter()
+        .filter(|p| p.parent_pid == Some(process.pid) && p.pid != process.pid)
+        .collect();
+    if !children.is_empty() {
+        html.push_str("\n<ul>\n");
+        for child in children {
+            push_process_node(html, child, processes, shown);
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</li>\n");
+}
+
+/// CSS class for a severity or risk level; anything unknown is "info"
+fn severity_class(severity: &str) -> &'static str {
+    match severity.to_ascii_lowercase().as_str() {
+        "critical" => "severity-critical",
+        "high" => "severity-high",
+        "medium" => "severity-medium",
+        "low" => "severity-low",
+        _ => "severity-info",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -982,7 +1288,7 @@ mod tests {
             video_recording: None,
         };
 
-        let result = calculate_threat_score(low_threat_report).unwrap();
+        let result = calculate_threat_score(low_threat_report, None).unwrap();
         assert!(result.score < 25.0);
         assert_eq!(result.risk_level, "Low");
 
@@ -1028,12 +1334,121 @@ mod tests {
             video_recording: None,
         };
 
-        let result = calculate_threat_score(high_threat_report).unwrap();
+        let result = calculate_threat_score(high_threat_report, None).unwrap();
         assert!(result.score >= 50.0);
         assert!(result.risk_level == "High" || result.risk_level == "Critical");
         assert!(result.contributing_factors.len() > 0);
     }
 
+    #[test]
+    fn test_threat_score_weights_configurable() {
+        let report = ExecutionReport {
+            session_id: "test-3".to_string(),
+            exit_code: 0,
+            execution_time_ms: 1000,
+            behavioral_events: vec![
+                BehaviorEvent {
+                    timestamp: 1000,
+                    event_type: "connect".to_string(),
+                    description: "C2 connection".to_string(),
+                    severity: "High".to_string(),
+                    mitre_attack_id: Some("T1071".to_string()),
+                },
+            ],
+            file_operations: vec![],
+            network_connections: vec![],
+            processes_created: vec![],
+            syscall_summary: HashMap::new(),
+            stdout: String::new(),
+            stderr: String::new(),
+            mitre_attacks: vec![
+                MitreAttack {
+                    id: "T1071".to_string(),
+                    name: "Application Layer Protocol".to_string(),
+                    description: "Detected".to_string(),
+                    confidence: 0.5,
+                },
+            ],
+            memory_dumps: vec![],
+            video_recording: None,
+        };
+
+        let default = score_report(&report, &ThreatScoreConfig::default());
+        assert_eq!(default.score, 25.0);
+        assert_eq!(default.risk_level, "Medium");
+        assert_eq!(default.factor_scores["behavioral_events"], 15.0);
+        assert_eq!(default.factor_scores["mitre_attacks"], 10.0);
+        assert_eq!(default.factor_scores["process_injection"], 0.0);
+
+        // Doubling the weight of high-severity events adds another 15 points
+        let config = ThreatScoreConfig { high_event: 30.0, ..Default::default() };
+        let heavier = score_report(&report, &config);
+        assert_eq!(heavier.score, 40.0);
+        assert_eq!(heavier.factor_scores["behavioral_events"], 30.0);
+        assert_eq!(heavier.factor_scores["mitre_attacks"], 10.0);
+
+        // Risk levels follow the configured thresholds
+        let config = ThreatScoreConfig { high_risk: 20.0, ..Default::default() };
+        assert_eq!(score_report(&report, &config).risk_level, "High");
+    }
+
+    #[test]
+    fn test_verdict_explanation_sums_to_score() {
+        let event = |severity: &str, description: &str| BehaviorEvent {
+            timestamp: 1000,
+            event_type: "exec".to_string(),
+            description: description.to_string(),
+            severity: severity.to_string(),
+            mitre_attack_id: None,
+        };
+        let mut syscalls = HashMap::new();
+        syscalls.insert("ptrace".to_string(), 3);
+
+        let mut report = ExecutionReport {
+            session_id: "test-4".to_string(),
+            exit_code: 0,
+            execution_time_ms: 1000,
+            behavioral_events: vec![event("Medium", "Dropped file"), event("Low", "Read /etc/hosts")],
+            file_operations: vec![],
+            network_connections: vec![],
+            processes_created: vec![],
+            syscall_summary: syscalls,
+            stdout: String::new(),
+            stderr: String::new(),
+            mitre_attacks: vec![
+                MitreAttack {
+                    id: "T1055".to_string(),
+                    name: "Process Injection".to_string(),
+                    description: "Detected".to_string(),
+                    confidence: 0.75,
+                },
+            ],
+            memory_dumps: vec![],
+            video_recording: None,
+        };
+
+        let result = score_report(&report, &ThreatScoreConfig::default());
+        let explained: f64 = result.verdict_explanation.iter().map(|c| c.points).sum();
+        assert_eq!(result.score, 56.0);
+        assert_eq!(explained, result.score);
+        assert_eq!(result.verdict_explanation.len(), 4);
+        assert!(result.verdict_explanation.iter().all(|c| c.points == c.raw_value * c.weight));
+
+        let injection = &result.verdict_explanation[3];
+        assert_eq!(injection.factor, "Process injection (3 ptrace calls)");
+        assert_eq!(injection.points, 30.0);
+
+        // Past 100 the cap shows up as its own contribution
+        report.behavioral_events.extend((0..4).map(|_| event("Critical", "Encrypted user files")));
+        let result = score_report(&report, &ThreatScoreConfig::default());
+        let explained: f64 = result.verdict_explanation.iter().map(|c| c.points).sum();
+        assert_eq!(result.score, 100.0);
+        assert_eq!(explained, result.score);
+        let cap = result.verdict_explanation.last().unwrap();
+        assert_eq!(cap.factor, "Score limited to 0-100");
+        assert_eq!(cap.points, -56.0);
+    }
+
     #[test]
     fn test_detect_sandbox_evasion() {
         let mut syscalls = HashMap::new();
@@ -1078,7 +1493,7 @@ mod tests {
             video_recording: None,
         };
 
-        let result = detect_sandbox_evasion(report).unwrap();
+        let result = detect_sandbox_evasion(report, None).unwrap();
 
         // Should detect at least the VM detection and debugger check
         assert!(result.len() >= 2);
@@ -1137,9 +1552,66 @@ mod tests {
             video_recording: None,
         };
 
-        let result = detect_sandbox_evasion(report).unwrap();
+        let result = detect_sandbox_evasion(report, None).unwrap();
 
         // Should detect no evasion attempts
         assert_eq!(result.len(), 0);
     }
+
+    #[test]
+    fn test_html_report_escapes_sample_content() {
+        let payload = "<script>alert(document.cookie)</script>";
+        let report = ExecutionReport {
+            session_id: "session-1".to_string(),
+            exit_code: 0,
+            execution_time_ms: 1500,
+            behavioral_events: vec![BehaviorEvent {
+                timestamp: 1000,
+                event_type: "execve".to_string(),
+                description: format!("sh -c \"echo '{}'\"", payload),
+                severity: "High".to_string(),
+                mitre_attack_id: Some("T1059".to_string()),
+            }],
+            file_operations: vec![FileOperation {
+                timestamp: 1100,
+                operation: "CREATE".to_string(),
+                path: format!("/tmp/{}.html", payload),
+            }],
+            network_connections: vec![NetworkConnection {
+                timestamp: 1200,
+                protocol: "TCP".to_string(),
+                source: "10.0.0.2".to_string(),
+                destination: "203.0.113.7".to_string(),
+                port: 443,
+                connection_type: "TCP".to_string(),
+            }],
+            processes_created: vec![
+                ProcessInfo { pid: 100, name: "sample".to_string(), command_line: "./sample".to_string(), parent_pid: None },
+                ProcessInfo { pid: 101, name: "sh".to_string(), command_line: payload.to_string(), parent_pid: Some(100) },
+            ],
+            syscall_summary: HashMap::new(),
+            stdout: String::new(),
+            stderr: String::new(),
+            mitre_attacks: vec![MitreAttack {
+                id: "T1059".to_string(),
+                name: "Command and Scripting Interpreter".to_string(),
+                description: payload.to_string(),
+                confidence: 0.9,
+            }],
+            memory_dumps: vec![],
+            video_recording: None,
+        };
+
+        let html = generate_sandbox_html_report(report).unwrap();
+
+        assert!(!html.contains(payload));
+        assert!(html.contains("&lt;script&gt;alert(document.cookie)&lt;/script&gt;"));
+        assert!(html.contains("/tmp/&lt;script&gt;"));
+        // The page's own script is the only one
+        assert_eq!(html.matches("<script>").count(), 1);
+        assert!(!html.contains("src=") && !html.contains("href="));
+        assert!(html.contains("203.0.113.7:443 (TCP)"));
+        assert!(html.contains("<li>sh <code>"));
+        assert!(html.contains("<td>Execution</td>"));
+    }
 }
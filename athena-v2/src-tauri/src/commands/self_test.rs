@@ -0,0 +1,312 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::State;
+
+use crate::commands::wasm_file_bridge::{load_security_module, SECURITY_MODULES};
+use crate::commands::wasm_runtime::{self, WasmRuntime};
+
+/// A known-input/known-output call that shows a module works
+pub struct SmokeTest {
+    pub module: &'static str,
+    /// Constructor of the resource `function` is a method of. The resource
+    /// is created first and its handle passed ahead of `args`.
+    pub constructor: Option<&'static str>,
+    pub function: &'static str,
+    pub args: Vec<Value>,
+    /// What the output should be, for the report
+    pub expected: &'static str,
+    pub check: fn(&Value) -> bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSelfTest {
+    pub module: String,
+    pub passed: bool,
+    pub load_ms: u64,
+    pub run_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub modules: Vec<ModuleSelfTest>,
+    pub total_ms: u64,
+}
+
+/// Where smoke tests load and call modules
+#[async_trait]
+pub trait ModuleHost: Send + Sync {
+    async fn load(&self, module: &str) -> Result<(), String>;
+    async fn call(&self, module: &str, function: &str, args: Vec<Value>) -> Result<Value, String>;
+    /// Create a resource with `constructor` and call `method` on it
+    async fn call_method(&self, module: &str, constructor: &str, method: &str, args: Vec<Value>) -> Result<Value, String>;
+}
+
+/// The WASM runtime managed by Tauri
+pub struct RuntimeHost<'a> {
+    runtime: State<'a, Arc<Mutex<Option<WasmRuntime>>>>,
+}
+
+#[async_trait]
+impl<'a> ModuleHost for RuntimeHost<'a> {
+    async fn load(&self, module: &str) -> Result<(), String> {
+        load_security_module(&self.runtime, module).await.map(|_| ())
+    }
+
+    async fn call(&self, module: &str, function: &str, args: Vec<Value>) -> Result<Value, String> {
+        let result = wasm_runtime::execute_wasm_function(
+            self.runtime.clone(),
+            module.to_string(),
+            function.to_string(),
+            args,
+        )
+        .await?;
+        output_json(result)
+    }
+
+    async fn call_method(&self, module: &str, constructor: &str, method: &str, args: Vec<Value>) -> Result<Value, String> {
+        let runtime = Arc::clone(self.runtime.inner());
+        let (module, constructor, method) = (module.to_string(), constructor.to_string(), method.to_string());
+
+        // Resources only live within a session, so both calls share one
+        tokio::task::spawn_blocking(move || {
+            let mut session = wasm_runtime::new_wasm_session(&runtime, &module)?;
+            let handle = output_json(wasm_runtime::call_in_session(&mut session, &constructor, &[])?)?;
+            if handle.get("_resource_handle").is_none() {
+                return Err(format!("{} returned {}, not a resource", constructor, handle));
+            }
+            let method_args: Vec<Value> = std::iter::once(handle).chain(args).collect();
+            output_json(wasm_runtime::call_in_session(&mut session, &method, &method_args)?)
+        })
+        .await
+        .map_err(|e| format!("{} did not complete: {}", method, e))?
+    }
+}
+
+fn output_json(result: wasm_runtime::WasmExecutionResult) -> Result<Value, String> {
+    if !result.success {
+        return Err(result.error.unwrap_or_else(|| "Execution failed".to_string()));
+    }
+    let output = result.output.unwrap_or_default();
+    serde_json::from_str(&output).map_err(|e| format!("Unreadable output {:?}: {}", output, e))
+}
+
+/// One smoke test per module in `SECURITY_MODULES`
+pub fn smoke_tests() -> Vec<SmokeTest> {
+    vec![
+        SmokeTest {
+            module: "analysis-engine",
+            constructor: None,
+            function: "deobfuscator#is-obfuscated",
+            args: vec![json!("eval(atob('YWxlcnQoMSk='))")],
+            expected: "true",
+            check: |output| *output == Value::Bool(true),
+        },
+        SmokeTest {
+            module: "crypto",
+            constructor: None,
+            function: "hash#sha256",
+            args: vec![json!(b"abc")],
+            expected: "ba7816bf…15ad",
+            check: |output| *output == json!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        },
+        SmokeTest {
+            module: "deobfuscator",
+            constructor: None,
+            function: "deobfuscator#get-supported-techniques",
+            args: vec![],
+            expected: "a non-empty technique list",
+            check: |output| output.as_array().is_some_and(|techniques| !techniques.is_empty()),
+        },
+        SmokeTest {
+            module: "file-processor",
+            constructor: None,
+            function: "detector#detect-format",
+            args: vec![json!(b"MZ\x90\x00\x03\x00\x00\x00"), Value::Null],
+            expected: "pe32",
+            check: |output| *output == json!("pe32"),
+        },
+        SmokeTest {
+            module: "network",
+            constructor: Some("network#new"),
+            function: "network#detect-protocol",
+            args: vec![json!(&b"GET /index.html HTTP/1.1\r\nHost: example.com\r\n\r\n"[..])],
+            expected: "protocol-type HTTP",
+            check: |output| output["_ok"]["protocol-type"] == json!("HTTP"),
+        },
+        SmokeTest {
+            module: "pattern-matcher",
+            constructor: Some("pattern-matcher#new"),
+            function: "pattern-matcher#scan",
+            args: vec![json!(b"eval(atob('YWxlcnQoMSk='))")],
+            expected: "a js_eval_base64 match",
+            check: |output| output["_ok"]["matches"]
+                .as_array()
+                .is_some_and(|matches| matches.iter().any(|m| m["rule-id"] == json!("js_eval_base64"))),
+        },
+        SmokeTest {
+            module: "sandbox",
+            constructor: Some("sandbox#new"),
+            function: "sandbox#create-instance",
+            args: vec![Value::Null],
+            expected: "instance sandbox-1",
+            check: |output| output["_ok"] == json!("sandbox-1"),
+        },
+    ]
+}
+
+async fn call(host: &dyn ModuleHost, test: &SmokeTest) -> Result<Value, String> {
+    match test.constructor {
+        Some(constructor) => host.call_method(test.module, constructor, test.function, test.args.clone()).await,
+        None => host.call(test.module, test.function, test.args.clone()).await,
+    }
+}
+
+/// Load each module and run its smoke test, recording every failure rather
+/// than stopping at the first
+pub async fn run_smoke_tests(host: &dyn ModuleHost, tests: &[SmokeTest]) -> SelfTestReport {
+    let start = Instant::now();
+    let mut modules = Vec::new();
+
+    for test in tests {
+        let load_start = Instant::now();
+        let loaded = host.load(test.module).await;
+        let load_ms = load_start.elapsed().as_millis() as u64;
+
+        let run_start = Instant::now();
+        let outcome = match loaded {
+            Ok(()) => match call(host, test).await {
+                Ok(output) if (test.check)(&output) => Ok(()),
+                Ok(output) => Err(format!("{} returned {}, expected {}", test.function, output, test.expected)),
+                Err(e) => Err(format!("{} failed: {}", test.function, e)),
+            },
+            Err(e) => Err(format!("Load failed: {}", e)),
+        };
+        let run_ms = run_start.elapsed().as_millis() as u64;
+
+        modules.push(ModuleSelfTest {
+            module: test.module.to_string(),
+            passed: outcome.is_ok(),
+            load_ms,
+            run_ms,
+            error: outcome.err(),
+        });
+    }
+
+    SelfTestReport {
+        passed: modules.iter().all(|m| m.passed),
+        modules,
+        total_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+/// Reload every WASM module from disk and check it gives the expected
+/// output for a known input, so an install can be verified quickly
+#[tauri::command]
+pub async fn run_self_test(
+    runtime: State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
+) -> Result<SelfTestReport, String> {
+    let host = RuntimeHost { runtime };
+    Ok(run_smoke_tests(&host, &smoke_tests()).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Healthy modules echo a fixed answer; the rest can't be loaded
+    struct StubHost {
+        healthy: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl ModuleHost for StubHost {
+        async fn load(&self, module: &str) -> Result<(), String> {
+            if self.healthy.contains(&module) {
+                Ok(())
+            } else {
+                Err(format!("{}.wasm: bad magic number", module))
+            }
+        }
+
+        async fn call(&self, _module: &str, _function: &str, _args: Vec<Value>) -> Result<Value, String> {
+            Ok(json!("pong"))
+        }
+
+        async fn call_method(&self, _module: &str, constructor: &str, method: &str, _args: Vec<Value>) -> Result<Value, String> {
+            Ok(json!(format!("{} then {}", constructor, method)))
+        }
+    }
+
+    fn ping(module: &'static str) -> SmokeTest {
+        SmokeTest {
+            module,
+            constructor: None,
+            function: "ping",
+            args: vec![],
+            expected: "pong",
+            check: |output| *output == json!("pong"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_reflects_healthy_and_failing_modules() {
+        let host = StubHost { healthy: vec!["crypto"] };
+
+        let report = run_smoke_tests(&host, &[ping("crypto"), ping("sandbox")]).await;
+
+        assert!(!report.passed);
+        assert_eq!(report.modules.len(), 2);
+        assert_eq!(report.modules[0].module, "crypto");
+        assert!(report.modules[0].passed);
+        assert_eq!(report.modules[0].error, None);
+        assert_eq!(report.modules[1].module, "sandbox");
+        assert!(!report.modules[1].passed);
+        assert_eq!(report.modules[1].error.as_deref(), Some("Load failed: sandbox.wasm: bad magic number"));
+    }
+
+    #[tokio::test]
+    async fn test_resource_method_called_on_constructed_resource() {
+        let host = StubHost { healthy: vec!["network"] };
+        let test = SmokeTest {
+            module: "network",
+            constructor: Some("network#new"),
+            function: "network#get-version",
+            args: vec![],
+            expected: "the version",
+            check: |output| *output == json!("network#new then network#get-version"),
+        };
+
+        assert!(run_smoke_tests(&host, &[test]).await.passed);
+    }
+
+    #[test]
+    fn test_resource_checks_reject_placeholder() {
+        // What a resource looks like when returned outside a session
+        let placeholder = json!({"_resource": true, "_error": "Resource returned without session context"});
+        let tests = smoke_tests();
+        for test in tests.iter().filter(|t| t.constructor.is_some()) {
+            assert!(!(test.check)(&placeholder), "{} accepts a placeholder", test.module);
+        }
+
+        let sandbox = tests.iter().find(|t| t.module == "sandbox").unwrap();
+        assert!((sandbox.check)(&json!({"_ok": "sandbox-1"})));
+        let network = tests.iter().find(|t| t.module == "network").unwrap();
+        assert!((network.check)(&json!({"_ok": {"protocol-type": "HTTP", "is-encrypted": false}})));
+        let matcher = tests.iter().find(|t| t.module == "pattern-matcher").unwrap();
+        assert!((matcher.check)(&json!({"_ok": {"matches": [{"rule-id": "js_eval_base64"}]}})));
+        assert!(!(matcher.check)(&json!({"_ok": {"matches": []}})));
+    }
+
+    #[test]
+    fn test_smoke_test_per_module() {
+        let tests = smoke_tests();
+        for (module, _, _) in SECURITY_MODULES {
+            assert!(tests.iter().any(|t| t.module == module), "no smoke test for {}", module);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::RwLock;
+use tauri::AppHandle;
+
+use crate::commands::samples::validate_path;
+
+/// File in the app data directory holding extra rules, loaded at startup
+pub const RULES_FILE_NAME: &str = "string_categories.json";
+
+/// A regex and the category given to strings it matches
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StringCategoryRule {
+    pub pattern: String,
+    pub category: String,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: StringCategoryRule,
+    regex: Regex,
+}
+
+impl CompiledRule {
+    fn new(rule: StringCategoryRule) -> Result<Self> {
+        let regex = Regex::new(&rule.pattern)
+            .with_context(|| format!("Invalid pattern for category {}: {}", rule.category, rule.pattern))?;
+        Ok(Self { rule, regex })
+    }
+}
+
+/// Ordered rules for categorizing extracted strings. The first matching
+/// rule wins; rules added at runtime are checked before the defaults.
+#[derive(Debug, Clone)]
+pub struct StringCategoryRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl StringCategoryRules {
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn defaults() -> Self {
+        let defaults = [
+            (r"^https?://", "URL"),
+            (r"(?s)@.*\.|\..*@", "Email"),
+            (r"(?s)^\\\\|^.:\\.", "Path"),
+            (r"^(HKEY_|HKLM\\|HKCU\\)", "Registry"),
+            (r"\.(exe|dll|sys)$", "Executable"),
+        ];
+
+        let rules = defaults
+            .iter()
+            .map(|(pattern, category)| {
+                CompiledRule::new(StringCategoryRule {
+                    pattern: pattern.to_string(),
+                    category: category.to_string(),
+                })
+                .expect("default string category patterns are valid")
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Add `rules` ahead of the existing ones
+    pub fn prepend(&mut self, rules: Vec<StringCategoryRule>) -> Result<()> {
+        let compiled = rules.into_iter().map(CompiledRule::new).collect::<Result<Vec<_>>>()?;
+        self.rules.splice(0..0, compiled);
+        Ok(())
+    }
+
+    /// Parse a JSON array of `{pattern, category}` rules
+    pub fn parse_rules(json: &str) -> Result<Vec<StringCategoryRule>> {
+        serde_json::from_str(json).context("Failed to parse string category rules")
+    }
+
+    pub fn categorize(&self, s: &str) -> Option<String> {
+        self.rules
+            .iter()
+            .find(|r| r.regex.is_match(s))
+            .map(|r| r.rule.category.clone())
+    }
+
+    pub fn rules(&self) -> Vec<StringCategoryRule> {
+        self.rules.iter().map(|r| r.rule.clone()).collect()
+    }
+}
+
+impl Default for StringCategoryRules {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
+
+static RULES: Lazy<RwLock<StringCategoryRules>> = Lazy::new(|| RwLock::new(StringCategoryRules::defaults()));
+
+/// Category of `s` under the active rule set
+pub fn categorize(s: &str) -> Option<String> {
+    RULES.read().unwrap_or_else(|e| e.into_inner()).categorize(s)
+}
+
+/// Add rules from a JSON file to the active rule set
+pub fn load_rules_file(path: &Path) -> Result<usize> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let rules = StringCategoryRules::parse_rules(&json)?;
+    let count = rules.len();
+    RULES.write().unwrap_or_else(|e| e.into_inner()).prepend(rules)?;
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn get_string_category_rules() -> Vec<StringCategoryRule> {
+    RULES.read().unwrap_or_else(|e| e.into_inner()).rules()
+}
+
+#[tauri::command]
+pub fn add_string_category_rule(pattern: String, category: String) -> Result<(), String> {
+    RULES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .prepend(vec![StringCategoryRule { pattern, category }])
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn load_string_category_rules(path: String, app: AppHandle) -> Result<usize, String> {
+    let path = validate_path(&path, &app)?;
+    load_rules_file(&path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn reset_string_category_rules() {
+    *RULES.write().unwrap_or_else(|e| e.into_inner()) = StringCategoryRules::defaults();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_rule_categorizes_named_pipe() {
+        let mut rules = StringCategoryRules::defaults();
+        let pipe = r"\\.\pipe\msagent_5f";
+
+        // Without the rule a pipe name is just a UNC-style path
+        assert_eq!(rules.categorize(pipe), Some("Path".to_string()));
+
+        let custom = StringCategoryRules::parse_rules(r#"[
+            {"pattern": "^\\\\\\\\\\.\\\\pipe\\\\", "category": "NamedPipe"},
+            {"pattern": "^Global\\\\", "category": "MutexName"}
+        ]"#)
+        .unwrap();
+        rules.prepend(custom).unwrap();
+
+        assert_eq!(rules.categorize(pipe), Some("NamedPipe".to_string()));
+        assert_eq!(rules.categorize(r"Global\8a3f2c1e"), Some("MutexName".to_string()));
+        // Defaults still apply to everything else
+        assert_eq!(rules.categorize(r"\\server\share"), Some("Path".to_string()));
+        assert_eq!(rules.categorize("https://example.com"), Some("URL".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_pattern_rejected() {
+        let mut rules = StringCategoryRules::empty();
+        let result = rules.prepend(vec![StringCategoryRule {
+            pattern: "([unclosed".to_string(),
+            category: "Broken".to_string(),
+        }]);
+
+        assert!(result.is_err());
+        assert_eq!(rules.categorize("([unclosed"), None);
+    }
+}
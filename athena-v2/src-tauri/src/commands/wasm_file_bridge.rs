@@ -5,7 +5,9 @@ use tauri::{State, AppHandle};
 use tauri::path::SafePathBuf;
 use std::sync::Mutex;
 use crate::commands::wasm_runtime::WasmRuntime;
-use crate::commands::file_analysis::FileAnalysisResult;
+use crate::commands::file_analysis::{AnalysisConfig, FileAnalysisResult};
+use crate::commands::mapped_file::MappedFile;
+use crate::commands::analysis_passes::{run_analysis_passes, AnalysisPass, DEFAULT_ANALYSIS_WORKERS};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WasmFileAnalysis {
@@ -46,11 +48,24 @@ const NETWORK_MODULE: &str = "network";
 const PATTERN_MATCHER: &str = "pattern-matcher";
 const SANDBOX_MODULE: &str = "sandbox";
 
+/// Modules loaded at startup: (module id, crate directory, wasm file name)
+pub(crate) const SECURITY_MODULES: [(&str, &str, &str); 7] = [
+    (ANALYSIS_ENGINE, "analysis-engine", "athena_analysis_engine"),
+    (CRYPTO_MODULE, "crypto", "athena_crypto"),
+    (DEOBFUSCATOR, "deobfuscator", "athena_deobfuscator"),
+    (FILE_PROCESSOR, "file-processor", "athena_file_processor"),
+    (NETWORK_MODULE, "network", "athena_network"),
+    (PATTERN_MATCHER, "pattern-matcher", "athena_pattern_matcher"),
+    (SANDBOX_MODULE, "sandbox", "athena_sandbox"),
+];
+
 #[tauri::command]
 pub async fn analyze_file_with_wasm(
     _app: AppHandle,
     runtime: State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
     file_path: SafePathBuf,
+    max_concurrency: Option<usize>,
+    config: Option<AnalysisConfig>,
 ) -> Result<EnhancedFileAnalysis, String> {
     let _start = std::time::Instant::now();
 
@@ -61,91 +76,131 @@ pub async fn analyze_file_with_wasm(
     let safe_path_for_analysis = SafePathBuf::new(validated_path.to_path_buf())
         .map_err(|e| format!("Invalid path: {}", e))?;
 
+    let config = config.unwrap_or_default();
+    let parser_limits = config.parser_limits;
+    let analysis_budget = config.analysis_budget;
+    let max_scan_bytes = config.max_scan_bytes;
+    let filename = validated_path.file_name().map(|name| name.to_string_lossy().into_owned());
+
     // First, perform basic file analysis
-    let basic_analysis = crate::commands::file_analysis::analyze_file(safe_path_for_analysis, None)
+    let basic_analysis = crate::commands::file_analysis::analyze_file(safe_path_for_analysis, Some(config))
         .await?;
 
-    // Read file data for WASM analysis
-    let file_data = std::fs::read(validated_path)
+    // Map file data for WASM analysis
+    let file_data = MappedFile::open(validated_path)
         .map_err(|e| format!("Failed to read file: {}", e))?;
 
-    let mut wasm_analyses = Vec::new();
-
     // Check if WASM runtime is initialized
     let has_runtime = {
         let runtime_guard = runtime.lock().map_err(|e| e.to_string())?;
         runtime_guard.is_some()
     };
 
-    if has_runtime {
-        // ========================================================================
-        // STATELESS MODULES - Simple function calls, no resources needed
-        // ========================================================================
-
-        // 1. Analysis Engine - Core malware analysis
-        // WIT: athena:analysis-engine/analyzer exports analyze(content: list<u8>)
-        if let Ok(result) = run_wasm_analysis(
-            &runtime,
-            ANALYSIS_ENGINE,
-            "analyze",  // Will try "analyzer#analyze" via fallback
-            &file_data,
-        ).await {
-            wasm_analyses.push(result);
-        }
+    let wasm_analyses = if has_runtime {
+        // Each pass runs on its own blocking thread, so it needs its own
+        // handle on the runtime and the mapped file
+        let runtime = Arc::clone(runtime.inner());
+        let file_data = Arc::new(file_data);
+        let shared = || (Arc::clone(&runtime), Arc::clone(&file_data));
+
+        // The modules don't depend on each other's output, so each is its
+        // own pass and they can run side by side
+        let passes = vec![
+            // ====================================================================
+            // STATELESS MODULES - Simple function calls, no resources needed
+            // ====================================================================
+
+            // 1. Analysis Engine - Core malware analysis
+            // WIT: athena:analysis-engine/analyzer exports analyze(content: list<u8>)
+            AnalysisPass::single("analysis-engine", {
+                let (runtime, file_data) = shared();
+                move || run_wasm_analysis(
+                    &runtime,
+                    ANALYSIS_ENGINE,
+                    "analyze",  // Will try "analyzer#analyze" via fallback
+                    file_data.as_slice(),
+                )
+            }),
 
-        // 2. Crypto Module - Hash calculation
-        // WIT: athena:crypto/hash exports sha256(data: list<u8>)
-        if let Ok(result) = run_wasm_analysis(
-            &runtime,
-            CRYPTO_MODULE,
-            "sha256",  // Will try "hash#sha256" via fallback
-            &file_data,
-        ).await {
-            wasm_analyses.push(result);
-        }
+            // 2. Crypto Module - Hash calculation
+            // WIT: athena:crypto/hash exports sha256(data: list<u8>)
+            AnalysisPass::single("crypto", {
+                let (runtime, file_data) = shared();
+                move || run_wasm_analysis(
+                    &runtime,
+                    CRYPTO_MODULE,
+                    "sha256",  // Will try "hash#sha256" via fallback
+                    file_data.as_slice(),
+                )
+            }),
 
-        // 3. File Processor - Parse file
-        // WIT: athena:file-processor/parser exports parse-file(buffer: list<u8>, format-hint: option<file-format>)
-        if let Ok(result) = run_wasm_analysis_with_option(
-            &runtime,
-            FILE_PROCESSOR,
-            "parse-file",  // Will try "parser#parse-file" via fallback
-            &file_data,
-            None, // No format hint - let the parser detect
-        ).await {
-            wasm_analyses.push(result);
-        }
+            // 3. File Processor - Parse the file and everything carved from it
+            // WIT: athena:file-processor/parser exports analyze-recursive(buffer: list<u8>,
+            //      filename: option<string>, budget: option<analysis-budget>, limits: option<parser-limits>)
+            AnalysisPass::single("file-processor", {
+                let (runtime, file_data) = shared();
+                move || run_wasm_analysis_with_options(
+                    &runtime,
+                    FILE_PROCESSOR,
+                    "analyze-recursive",  // Will try "parser#analyze-recursive" via fallback
+                    file_data.as_slice(),
+                    vec![
+                        filename.map(serde_json::Value::String),
+                        Some(serde_json::json!({
+                            "max-subfiles": analysis_budget.max_subfiles,
+                            "max-total-bytes": analysis_budget.max_total_bytes,
+                            "max-wall-ms": analysis_budget.max_wall_ms,
+                        })),
+                        Some(serde_json::json!({
+                            "max-sections": parser_limits.max_sections,
+                            "max-load-commands": parser_limits.max_load_commands,
+                            "max-resources": parser_limits.max_resources,
+                        })),
+                    ],
+                )
+            }),
 
-        // ========================================================================
-        // RESOURCE-BASED MODULES - Require session for resource lifecycle
-        // ========================================================================
-
-        // 4. Deobfuscator - Uses resource-based API
-        // WIT: athena:deobfuscator/deobfuscator resource with detect() method
-        if let Ok(result) = run_resource_analysis(
-            &runtime,
-            DEOBFUSCATOR,
-            "new",              // Constructor function to create resource
-            "detect",           // Method to call on resource (deobfuscator#detect)
-            &file_data,
-            true,               // Convert bytes to string for deobfuscator
-        ).await {
-            wasm_analyses.push(result);
-        }
+            // ====================================================================
+            // RESOURCE-BASED MODULES - Require session for resource lifecycle
+            // ====================================================================
+
+            // 4. Deobfuscator - Uses resource-based API
+            // WIT: athena:deobfuscator/deobfuscator resource with detect() method
+            AnalysisPass::single("deobfuscator", {
+                let (runtime, file_data) = shared();
+                move || run_resource_analysis(
+                    &runtime,
+                    DEOBFUSCATOR,
+                    "new",              // Constructor function to create resource
+                    &[],
+                    "detect",           // Method to call on resource (deobfuscator#detect)
+                    file_data.as_slice(),
+                    true,               // Convert bytes to string for deobfuscator
+                )
+            }),
 
-        // 5. Pattern Matcher - Uses resource-based API
-        // WIT: athena:pattern-matcher/pattern-matcher resource with scan() method
-        if let Ok(result) = run_resource_analysis(
-            &runtime,
-            PATTERN_MATCHER,
-            "new",              // Constructor function to create matcher resource
-            "scan",             // Method to call on resource (pattern-matcher#scan)
-            &file_data,
-            false,              // Keep as bytes for pattern matching
-        ).await {
-            wasm_analyses.push(result);
-        }
-    }
+            // 5. Pattern Matcher - Uses resource-based API
+            // WIT: athena:pattern-matcher/pattern-matcher resource with scan() method,
+            //      created by new-with-config(config: matcher-config)
+            AnalysisPass::single("pattern-matcher", {
+                let (runtime, file_data) = shared();
+                let matcher_config = matcher_config(max_scan_bytes);
+                move || run_resource_analysis(
+                    &runtime,
+                    PATTERN_MATCHER,
+                    "new-with-config",  // Constructor function to create matcher resource
+                    &[matcher_config],
+                    "scan",             // Method to call on resource (pattern-matcher#scan)
+                    file_data.as_slice(),
+                    false,              // Keep as bytes for pattern matching
+                )
+            }),
+        ];
+
+        run_analysis_passes(passes, max_concurrency.unwrap_or(DEFAULT_ANALYSIS_WORKERS)).await
+    } else {
+        Vec::new()
+    };
 
     // Calculate combined risk score
     let combined_risk_score = calculate_combined_risk_score(&basic_analysis, &wasm_analyses);
@@ -161,9 +216,19 @@ pub async fn analyze_file_with_wasm(
     })
 }
 
+/// Pattern-matcher `matcher-config` record. `max-scan-bytes` is an
+/// `option<u64>`, so the limit is tagged rather than left to number inference
+fn matcher_config(max_scan_bytes: Option<u64>) -> serde_json::Value {
+    let max_scan_bytes = match max_scan_bytes {
+        Some(limit) => serde_json::json!({"_some": {"_u64": limit}}),
+        None => serde_json::json!({"_none": true}),
+    };
+    serde_json::json!({"max-scan-bytes": max_scan_bytes})
+}
+
 /// Run stateless WASM analysis with simple function call
-async fn run_wasm_analysis(
-    runtime: &State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
+fn run_wasm_analysis(
+    runtime: &Mutex<Option<WasmRuntime>>,
     module_name: &str,
     function_name: &str,
     file_data: &[u8],
@@ -174,12 +239,12 @@ async fn run_wasm_analysis(
     let args = vec![serde_json::json!(file_data)];
 
     // Execute WASM function
-    let result = crate::commands::wasm_runtime::execute_wasm_function(
-        runtime.clone(),
-        module_name.to_string(),
-        function_name.to_string(),
-        args,
-    ).await?;
+    let result = crate::commands::wasm_runtime::call_wasm_function(
+        runtime,
+        module_name,
+        function_name,
+        &args,
+    )?;
 
     let execution_time_ms = start.elapsed().as_millis() as u64;
 
@@ -196,32 +261,30 @@ async fn run_wasm_analysis(
     })
 }
 
-/// Run stateless WASM analysis with an optional second parameter
-async fn run_wasm_analysis_with_option(
-    runtime: &State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
+/// Run stateless WASM analysis with optional parameters after the file data
+fn run_wasm_analysis_with_options(
+    runtime: &Mutex<Option<WasmRuntime>>,
     module_name: &str,
     function_name: &str,
     file_data: &[u8],
-    option_value: Option<serde_json::Value>,
+    option_values: Vec<Option<serde_json::Value>>,
 ) -> Result<WasmFileAnalysis, String> {
     let start = std::time::Instant::now();
 
-    // Build args: first is the file data, second is optional parameter
-    let args = vec![
-        serde_json::json!(file_data),
-        match option_value {
-            Some(v) => serde_json::json!({"_some": v}),
-            None => serde_json::json!({"_none": true}),
-        },
-    ];
+    // Build args: first is the file data, then each optional parameter
+    let mut args = vec![serde_json::json!(file_data)];
+    args.extend(option_values.into_iter().map(|value| match value {
+        Some(v) => serde_json::json!({"_some": v}),
+        None => serde_json::json!({"_none": true}),
+    }));
 
     // Execute WASM function
-    let result = crate::commands::wasm_runtime::execute_wasm_function(
-        runtime.clone(),
-        module_name.to_string(),
-        function_name.to_string(),
-        args,
-    ).await?;
+    let result = crate::commands::wasm_runtime::call_wasm_function(
+        runtime,
+        module_name,
+        function_name,
+        &args,
+    )?;
 
     let execution_time_ms = start.elapsed().as_millis() as u64;
 
@@ -238,60 +301,40 @@ async fn run_wasm_analysis_with_option(
     })
 }
 
-/// Run resource-based WASM analysis using session API
-/// This creates a session, instantiates the resource, calls a method, and cleans up
-async fn run_resource_analysis(
-    runtime: &State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
+/// Run resource-based WASM analysis in a private session
+/// This instantiates the module, creates the resource, calls a method on it
+/// and drops everything with the session
+fn run_resource_analysis(
+    runtime: &Mutex<Option<WasmRuntime>>,
     module_name: &str,
     constructor_name: &str,
+    constructor_args: &[serde_json::Value],
     method_name: &str,
     file_data: &[u8],
     convert_to_string: bool,
 ) -> Result<WasmFileAnalysis, String> {
     let start = std::time::Instant::now();
 
-    // 1. Create a session for this module
-    let session_info = crate::commands::wasm_runtime::create_wasm_session(
-        runtime.clone(),
-        module_name.to_string(),
-    ).await?;
-
-    let session_id = session_info.session_id.clone();
+    // 1. Create a session for this module. It isn't shared, so it stays out
+    // of the global session table and its lock.
+    let mut session = crate::commands::wasm_runtime::new_wasm_session(runtime, module_name)?;
 
     // 2. Call the constructor to create the resource
-    let constructor_result = crate::commands::wasm_runtime::execute_session_function(
-        session_id.clone(),
-        constructor_name.to_string(),
-        vec![], // No args for constructor
-    ).await;
-
-    let resource_handle = match constructor_result {
-        Ok(result) => {
-            // Parse the output to get the resource handle
-            if let Some(output) = result.output.as_ref() {
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(output) {
-                    if let Some(handle) = parsed.get("_resource_handle").and_then(|v| v.as_str()) {
-                        handle.to_string()
-                    } else {
-                        // Constructor might return the resource directly
-                        // Clean up and return error
-                        let _ = crate::commands::wasm_runtime::destroy_wasm_session(session_id).await;
-                        return Err("Constructor did not return a resource handle".to_string());
-                    }
-                } else {
-                    let _ = crate::commands::wasm_runtime::destroy_wasm_session(session_id).await;
-                    return Err("Failed to parse constructor output".to_string());
-                }
-            } else {
-                let _ = crate::commands::wasm_runtime::destroy_wasm_session(session_id).await;
-                return Err("Constructor returned no output".to_string());
-            }
-        }
-        Err(e) => {
-            let _ = crate::commands::wasm_runtime::destroy_wasm_session(session_id).await;
-            return Err(format!("Constructor failed: {}", e));
-        }
-    };
+    let constructor_result = crate::commands::wasm_runtime::call_in_session(
+        &mut session,
+        constructor_name,
+        constructor_args,
+    ).map_err(|e| format!("Constructor failed: {}", e))?;
+
+    // Parse the output to get the resource handle
+    let output = constructor_result.output
+        .ok_or("Constructor returned no output")?;
+    let parsed = serde_json::from_str::<serde_json::Value>(&output)
+        .map_err(|_| "Failed to parse constructor output".to_string())?;
+    let resource_handle = parsed.get("_resource_handle")
+        .and_then(|v| v.as_str())
+        .ok_or("Constructor did not return a resource handle")?
+        .to_string();
 
     // 3. Call the method on the resource
     let method_args = if convert_to_string {
@@ -307,14 +350,14 @@ async fn run_resource_analysis(
         ]
     };
 
-    let method_result = crate::commands::wasm_runtime::execute_session_function(
-        session_id.clone(),
-        method_name.to_string(),
-        method_args,
-    ).await;
+    let method_result = crate::commands::wasm_runtime::call_in_session(
+        &mut session,
+        method_name,
+        &method_args,
+    );
 
-    // 4. Clean up the session (this will drop all resources)
-    let _ = crate::commands::wasm_runtime::destroy_wasm_session(session_id).await;
+    // 4. Dropping the session drops all its resources
+    drop(session);
 
     let execution_time_ms = start.elapsed().as_millis() as u64;
 
@@ -442,48 +485,74 @@ fn generate_ml_predictions(wasm_analyses: &[WasmFileAnalysis]) -> Option<MlPredi
 pub async fn load_wasm_security_modules(
     runtime: State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
 ) -> Result<Vec<String>, String> {
-    // Load Component Model WASM files from wasm-modules directory
-    let modules = vec![
-        (ANALYSIS_ENGINE, "analysis-engine", "athena_analysis_engine"),
-        (CRYPTO_MODULE, "crypto", "athena_crypto"),
-        (DEOBFUSCATOR, "deobfuscator", "athena_deobfuscator"),
-        (FILE_PROCESSOR, "file-processor", "athena_file_processor"),
-        (NETWORK_MODULE, "network", "athena_network"),
-        (PATTERN_MATCHER, "pattern-matcher", "athena_pattern_matcher"),
-        (SANDBOX_MODULE, "sandbox", "athena_sandbox"),
-    ];
-
     let mut loaded_modules = Vec::new();
 
-    for (name, module_dir, wasm_name) in modules {
-        // Component Model WASM path (relative to athena-v2/src-tauri/src/commands/ working directory)
-        let wasm_path = format!("../../wasm-modules/core/{}/target/wasm32-wasip1/release/{}.wasm",
-            module_dir, wasm_name);
-
-        // Use Component::from_file approach (recommended by Wasmtime docs)
-        let safe_path = match SafePathBuf::new(wasm_path.clone().into()) {
-            Ok(p) => p,
-            Err(e) => {
-                eprintln!("Invalid WASM path {}: {}", wasm_path, e);
-                continue;
-            }
-        };
-
-        match crate::commands::wasm_runtime::load_wasm_module_from_file(
-            runtime.clone(),
-            name.to_string(),
-            safe_path,
-        ).await {
-            Ok(_) => {
+    for (name, _, _) in SECURITY_MODULES {
+        match load_security_module(&runtime, name).await {
+            Ok(wasm_path) => {
                 loaded_modules.push(name.to_string());
-                println!("Successfully loaded Component Model WASM module: {} from {}", name, wasm_path);
+                tracing::info!("Successfully loaded Component Model WASM module: {} from {}", name, wasm_path);
             },
             Err(e) => {
-                eprintln!("Failed to load WASM module {} from {}: {}", name, wasm_path, e);
+                tracing::warn!("{}", e);
                 // For development, continue without the module
             }
         }
     }
 
     Ok(loaded_modules)
-}
\ No newline at end of file
+}
+
+/// Load (or reload) one of `SECURITY_MODULES` from its build output,
+/// returning the path it was loaded from
+pub(crate) async fn load_security_module(
+    runtime: &State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
+    name: &str,
+) -> Result<String, String> {
+    let (_, module_dir, wasm_name) = SECURITY_MODULES
+        .iter()
+        .find(|(module, _, _)| *module == name)
+        .ok_or_else(|| format!("Unknown WASM module: {}", name))?;
+
+    // Component Model WASM path (relative to athena-v2/src-tauri/src/commands/ working directory)
+    let wasm_path = format!("../../wasm-modules/core/{}/target/wasm32-wasip1/release/{}.wasm",
+        module_dir, wasm_name);
+
+    // Use Component::from_file approach (recommended by Wasmtime docs)
+    let safe_path = SafePathBuf::new(wasm_path.clone().into())
+        .map_err(|e| format!("Invalid WASM path {}: {}", wasm_path, e))?;
+
+    crate::commands::wasm_runtime::load_wasm_module_from_file(
+        runtime.clone(),
+        name.to_string(),
+        safe_path,
+    ).await
+    .map_err(|e| format!("Failed to load WASM module {} from {}: {}", name, wasm_path, e))?;
+
+    Ok(wasm_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::wasm_runtime::json_to_component_val;
+    use wasmtime::component::Val;
+
+    #[test]
+    fn test_matcher_config_max_scan_bytes_is_u64() {
+        for limit in [4096, u64::MAX] {
+            let config = json_to_component_val(&matcher_config(Some(limit)), 0).unwrap();
+            let Val::Record(fields) = config else { panic!("expected a record, got {:?}", config) };
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields[0].0, "max-scan-bytes");
+            assert!(
+                matches!(&fields[0].1, Val::Option(Some(v)) if **v == Val::U64(limit)),
+                "{:?}",
+                fields[0].1
+            );
+        }
+
+        let config = json_to_component_val(&matcher_config(None), 0).unwrap();
+        assert!(matches!(config, Val::Record(ref fields) if fields[0].1 == Val::Option(None)));
+    }
+}
@@ -180,13 +180,13 @@ impl ResourceLimiter for WasmStore {
 
     fn memory_grow_failed(&mut self, error: anyhow::Error) -> anyhow::Result<()> {
         // Log the memory growth failure for malware analysis tracking
-        eprintln!("WASM memory growth failed: {}", error);
+        tracing::warn!("WASM memory growth failed: {}", error);
         Ok(())
     }
 
     fn table_grow_failed(&mut self, error: anyhow::Error) -> anyhow::Result<()> {
         // Log the table growth failure for malware analysis tracking
-        eprintln!("WASM table growth failed: {}", error);
+        tracing::warn!("WASM table growth failed: {}", error);
         Ok(())
     }
 
@@ -567,7 +567,7 @@ pub async fn load_wasm_module_from_file(
 
 /// Convert JSON value to Component Model Val
 /// This handles the common types used in our WIT interfaces
-fn json_to_component_val(json_val: &serde_json::Value, arg_index: usize) -> Result<ComponentVal, String> {
+pub(crate) fn json_to_component_val(json_val: &serde_json::Value, arg_index: usize) -> Result<ComponentVal, String> {
     json_to_component_val_internal(json_val, arg_index, None)
 }
 
@@ -649,6 +649,15 @@ fn json_to_component_val_internal(
                 }
             }
 
+            // Check for explicit u64: {"_u64": value}, since bare numbers
+            // are inferred as the narrowest signed type that fits
+            if let Some(value) = obj.get("_u64") {
+                return value
+                    .as_u64()
+                    .map(ComponentVal::U64)
+                    .ok_or_else(|| format!("Argument {}: {} is not a valid u64", arg_index, value));
+            }
+
             // Check for Option wrapper: {"_option": value} or {"_some": value} or {"_none": true}
             if let Some(inner) = obj.get("_some") {
                 let inner_val = json_to_component_val_internal(inner, arg_index, session)?;
@@ -874,21 +883,44 @@ pub async fn execute_wasm_function(
     function_name: String,
     args: Vec<serde_json::Value>,
 ) -> Result<WasmExecutionResult, String> {
-    let start = std::time::Instant::now();
-    
+    call_wasm_function(runtime.inner(), &module_id, &function_name, &args)
+}
+
+/// The engine and pre-instantiated component for `module_id`. Both are
+/// cheap handles, so callers can drop the runtime lock before executing.
+fn module_handles(
+    runtime: &Mutex<Option<WasmRuntime>>,
+    module_id: &str,
+) -> Result<(Engine, InstancePre<WasmStore>), String> {
     let runtime_guard = runtime.lock().map_err(|e| e.to_string())?;
     let runtime = runtime_guard
         .as_ref()
         .ok_or("WASM runtime not initialized")?;
 
-    // Get the InstancePre from HashMap
     let modules = runtime.modules.lock().map_err(|e| e.to_string())?;
     let instance_pre = modules
-        .get(&module_id)
-        .ok_or("Module not found")?;
+        .get(module_id)
+        .ok_or(format!("Module '{}' not found. Load it first.", module_id))?
+        .clone();
+    Ok((runtime.engine.clone(), instance_pre))
+}
+
+/// Synchronous body of `execute_wasm_function`. The runtime lock is only
+/// held while looking the module up, so calls on different threads run in
+/// parallel.
+pub(crate) fn call_wasm_function(
+    runtime: &Mutex<Option<WasmRuntime>>,
+    module_id: &str,
+    function_name: &str,
+    args: &[serde_json::Value],
+) -> Result<WasmExecutionResult, String> {
+    let start = std::time::Instant::now();
+    let module_id = module_id.to_string();
+
+    let (engine, instance_pre) = module_handles(runtime, &module_id)?;
 
     // Create a new Store for this execution (per-request pattern per DeepWiki)
-    let mut store = Store::new(&runtime.engine, WasmStore::new());
+    let mut store = Store::new(&engine, WasmStore::new());
     store.limiter(|state| &mut state.limiter);
 
     // CRITICAL: Set fuel limit to prevent infinite loops and CPU exhaustion
@@ -900,12 +932,9 @@ pub async fn execute_wasm_function(
     let instance = instance_pre.instantiate(&mut store)
         .map_err(|e| format!("Failed to instantiate component: {}", e))?;
 
-    // Drop the lock ASAP to avoid holding it during execution
-    drop(modules);
-
     // Get the function from the instance
     // Try direct function name first, then try nested interfaces
-    let func = instance.get_func(&mut store, &function_name)
+    let func = instance.get_func(&mut store, function_name)
         .or_else(|| {
             // Try common interface patterns for our modules
             // WIT interfaces like "athena:crypto/hash#sha256" become nested exports
@@ -973,7 +1002,7 @@ pub async fn execute_wasm_function(
     // Record metrics
     {
         let mut metrics = METRICS.lock().unwrap_or_else(|poisoned| {
-            eprintln!("METRICS mutex was poisoned, recovering...");
+            tracing::warn!("METRICS mutex was poisoned, recovering...");
             poisoned.into_inner()
         });
         let tracker = metrics.entry(module_id.clone())
@@ -1043,7 +1072,7 @@ pub async fn get_wasm_memory_usage(
     // Since we create Store per-execution and don't store them,
     // we return the peak memory usage from metrics instead
     let metrics = METRICS.lock().unwrap_or_else(|poisoned| {
-        eprintln!("METRICS mutex was poisoned, recovering...");
+        tracing::warn!("METRICS mutex was poisoned, recovering...");
         poisoned.into_inner()
     });
 
@@ -1057,7 +1086,7 @@ pub async fn get_wasm_memory_usage(
 #[tauri::command]
 pub async fn get_wasm_metrics(module_id: String) -> Result<WasmMetrics, String> {
     let metrics = METRICS.lock().unwrap_or_else(|poisoned| {
-        eprintln!("METRICS mutex was poisoned, recovering...");
+        tracing::warn!("METRICS mutex was poisoned, recovering...");
         poisoned.into_inner()
     });
 
@@ -1069,7 +1098,7 @@ pub async fn get_wasm_metrics(module_id: String) -> Result<WasmMetrics, String>
 #[tauri::command]
 pub async fn get_all_wasm_metrics() -> Result<HashMap<String, WasmMetrics>, String> {
     let metrics = METRICS.lock().unwrap_or_else(|poisoned| {
-        eprintln!("METRICS mutex was poisoned, recovering...");
+        tracing::warn!("METRICS mutex was poisoned, recovering...");
         poisoned.into_inner()
     });
 
@@ -1081,7 +1110,7 @@ pub async fn get_all_wasm_metrics() -> Result<HashMap<String, WasmMetrics>, Stri
 #[tauri::command]
 pub async fn reset_wasm_metrics(module_id: String) -> Result<String, String> {
     let mut metrics = METRICS.lock().unwrap_or_else(|poisoned| {
-        eprintln!("METRICS mutex was poisoned, recovering...");
+        tracing::warn!("METRICS mutex was poisoned, recovering...");
         poisoned.into_inner()
     });
 
@@ -1095,7 +1124,7 @@ pub async fn reset_wasm_metrics(module_id: String) -> Result<String, String> {
 #[tauri::command]
 pub async fn reset_all_wasm_metrics() -> Result<String, String> {
     let mut metrics = METRICS.lock().unwrap_or_else(|poisoned| {
-        eprintln!("METRICS mutex was poisoned, recovering...");
+        tracing::warn!("METRICS mutex was poisoned, recovering...");
         poisoned.into_inner()
     });
     let count = metrics.len();
@@ -1114,21 +1143,31 @@ pub async fn create_wasm_session(
     runtime: State<'_, Arc<Mutex<Option<WasmRuntime>>>>,
     module_id: String,
 ) -> Result<SessionInfo, String> {
-    let runtime_guard = runtime.lock().map_err(|e| e.to_string())?;
-    let runtime_ref = runtime_guard
-        .as_ref()
-        .ok_or("WASM runtime not initialized")?;
+    let session = new_wasm_session(runtime.inner(), &module_id)?;
+    let info = SessionInfo {
+        session_id: session.session_id.clone(),
+        module_id: session.module_id.clone(),
+        created_at: session.created_at.to_rfc3339(),
+    };
 
-    // Get the InstancePre for the module
-    let modules = runtime_ref.modules.lock().map_err(|e| e.to_string())?;
-    let instance_pre = modules
-        .get(&module_id)
-        .ok_or(format!("Module '{}' not found. Load it first.", module_id))?
-        .clone();
-    drop(modules);
+    // Store session
+    let mut sessions = SESSIONS.lock().map_err(|e| e.to_string())?;
+    sessions.insert(info.session_id.clone(), session);
+
+    Ok(info)
+}
+
+/// A session that isn't registered in `SESSIONS`, for host code that needs
+/// resources across a few calls without going through the global session
+/// lock
+pub(crate) fn new_wasm_session(
+    runtime: &Mutex<Option<WasmRuntime>>,
+    module_id: &str,
+) -> Result<WasmSession, String> {
+    let (engine, instance_pre) = module_handles(runtime, module_id)?;
 
     // Create a new Store for this session
-    let mut store = Store::new(&runtime_ref.engine, WasmStore::new());
+    let mut store = Store::new(&engine, WasmStore::new());
     store.limiter(|state| &mut state.limiter);
 
     // CRITICAL: Set fuel limit for session-based execution
@@ -1139,27 +1178,13 @@ pub async fn create_wasm_session(
     let instance = instance_pre.instantiate(&mut store)
         .map_err(|e| format!("Failed to instantiate component: {}", e))?;
 
-    // Create session
-    let session_id = format!("session-{}", Uuid::new_v4());
-    let created_at = Utc::now();
-
-    let session = WasmSession {
-        session_id: session_id.clone(),
-        module_id: module_id.clone(),
+    Ok(WasmSession {
+        session_id: format!("session-{}", Uuid::new_v4()),
+        module_id: module_id.to_string(),
         store,
         instance,
         resource_handles: HashMap::new(),
-        created_at,
-    };
-
-    // Store session
-    let mut sessions = SESSIONS.lock().map_err(|e| e.to_string())?;
-    sessions.insert(session_id.clone(), session);
-
-    Ok(SessionInfo {
-        session_id,
-        module_id,
-        created_at: created_at.to_rfc3339(),
+        created_at: Utc::now(),
     })
 }
 
@@ -1171,8 +1196,6 @@ pub async fn execute_session_function(
     function_name: String,
     args: Vec<serde_json::Value>,
 ) -> Result<WasmExecutionResult, String> {
-    let start = std::time::Instant::now();
-
     // Get mutable access to the session
     let mut sessions = SESSIONS.lock().map_err(|e| e.to_string())?;
     let session = sessions
@@ -1190,12 +1213,24 @@ pub async fn execute_session_function(
         return Err(format!("Session '{}' has expired (max age: {} minutes)", expired_id, SESSION_TTL_SECS / 60));
     }
 
+    call_in_session(session, &function_name, &args)
+}
+
+/// Call `function_name` on a session's instance, resolving resource handles
+/// in `args` and storing returned resources in the session
+pub(crate) fn call_in_session(
+    session: &mut WasmSession,
+    function_name: &str,
+    args: &[serde_json::Value],
+) -> Result<WasmExecutionResult, String> {
+    let start = std::time::Instant::now();
+
     // CRITICAL: Refill fuel before each execution (sessions reuse the same store)
     session.store.set_fuel(DEFAULT_FUEL_UNITS)
         .map_err(|e| format!("Failed to set fuel limit: {}", e))?;
 
     // Get the function from the instance
-    let func = session.instance.get_func(&mut session.store, &function_name)
+    let func = session.instance.get_func(&mut session.store, function_name)
         .or_else(|| {
             // Try common interface patterns for our modules
             for interface in ["hash", "hmac", "aes", "rsa", "utils", "ecdsa",
@@ -1256,7 +1291,7 @@ pub async fn execute_session_function(
     // Record metrics
     {
         let mut metrics = METRICS.lock().unwrap_or_else(|poisoned| {
-            eprintln!("METRICS mutex was poisoned, recovering...");
+            tracing::warn!("METRICS mutex was poisoned, recovering...");
             poisoned.into_inner()
         });
         let tracker = metrics.entry(session.module_id.clone())
@@ -1,7 +1,10 @@
-use tauri::{Manager, AppHandle, State, Emitter};
+use tauri::{Manager, AppHandle, State, Emitter, Url};
+use tauri::path::SafePathBuf;
 use tokio::sync::mpsc;
-use std::sync::Arc;
-use crate::workflow::{Job, JobStore, JobExecutor, JobStatus, WorkflowType};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use crate::quarantine::QuarantineStorage;
+use crate::workflow::{Job, JobStore, JobExecutor, JobStatus, NotificationSinks, WatchFolder, WorkflowType};
 use crate::metrics::{WORKFLOW_JOB_COUNTER, ACTIVE_WORKFLOW_JOBS};
 
 #[tauri::command]
@@ -9,17 +12,27 @@ pub async fn start_job(
     app: AppHandle,
     workflow_type: WorkflowType,
     input: serde_json::Value,
+    completion_webhook: Option<Url>,
 ) -> Result<String, String> {
     // Create job
-    let job = Job::new(workflow_type.clone(), input);
+    let job = Job::new(workflow_type.clone(), input).with_completion_webhook(completion_webhook);
     let job_id = job.id.clone();
 
     // Get job store
     let store = app.state::<Arc<JobStore>>();
     store.create_job(&job).map_err(|e| e.to_string())?;
 
+    spawn_job(&app, &job);
+
+    Ok(job_id)
+}
+
+/// Record metrics for a newly created job and execute it in the background
+pub(crate) fn spawn_job(app: &AppHandle, job: &Job) -> tokio::task::JoinHandle<()> {
+    let store = app.state::<Arc<JobStore>>();
+
     // Record job creation metrics
-    let workflow_type_str = format!("{:?}", workflow_type);
+    let workflow_type_str = format!("{:?}", job.workflow_type);
     WORKFLOW_JOB_COUNTER
         .with_label_values(&[&workflow_type_str, "created"])
         .inc();
@@ -45,7 +58,11 @@ pub async fn start_job(
         wasm_runtime.inner().clone(),
         yara_state.inner().clone(),
     );
-    let job_id_clone = job_id.clone();
+    let executor = match app.try_state::<NotificationSinks>() {
+        Some(sinks) => executor.with_notification_sinks(sinks.inner().clone()),
+        None => executor,
+    };
+    let job_id = job.id.clone();
     let app_clone = app.clone();
 
     tokio::spawn(async move {
@@ -59,12 +76,10 @@ pub async fn start_job(
         });
 
         // Execute job
-        if let Err(e) = executor.execute_job(job_id_clone).await {
-            eprintln!("Job execution failed: {}", e);
+        if let Err(e) = executor.execute_job(job_id.clone()).await {
+            tracing::error!("Job {} execution failed: {}", job_id, e);
         }
-    });
-
-    Ok(job_id)
+    })
 }
 
 #[tauri::command]
@@ -122,3 +137,59 @@ pub async fn get_active_jobs(
 ) -> Result<Vec<Job>, String> {
     store.get_active_jobs().map_err(|e| e.to_string())
 }
+
+/// The directory currently being watched, if any
+#[derive(Default)]
+pub struct WatchFolderState(Mutex<Option<(PathBuf, notify::RecommendedWatcher)>>);
+
+/// Automatically quarantine and analyze files dropped into `directory`.
+/// Emits `watch-folder-job-finished` with the final job when each analysis ends.
+#[tauri::command]
+pub async fn start_watch_folder(
+    app: AppHandle,
+    state: State<'_, WatchFolderState>,
+    directory: SafePathBuf,
+) -> Result<(), String> {
+    let dir = directory.as_ref().to_path_buf();
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()));
+    }
+
+    let folder = Arc::new(WatchFolder::new(
+        dir.clone(),
+        app.state::<Arc<Mutex<QuarantineStorage>>>().inner().clone(),
+        app.state::<Arc<JobStore>>().inner().clone(),
+    ));
+
+    let app_clone = app.clone();
+    let watcher = folder
+        .watch(move |job| {
+            let handle = spawn_job(&app_clone, &job);
+            let app = app_clone.clone();
+            tokio::spawn(async move {
+                let _ = handle.await;
+                let finished = app.state::<Arc<JobStore>>().get_job(&job.id).ok().flatten();
+                let _ = app.emit("watch-folder-job-finished", finished.unwrap_or(job));
+            });
+        })
+        .map_err(|e| e.to_string())?;
+
+    // Replacing the previous watcher drops it, which stops it
+    *state.0.lock().map_err(|e| e.to_string())? = Some((dir, watcher));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_watch_folder(
+    state: State<'_, WatchFolderState>,
+) -> Result<(), String> {
+    state.0.lock().map_err(|e| e.to_string())?.take();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_watch_folder(
+    state: State<'_, WatchFolderState>,
+) -> Result<Option<PathBuf>, String> {
+    Ok(state.0.lock().map_err(|e| e.to_string())?.as_ref().map(|(dir, _)| dir.clone()))
+}
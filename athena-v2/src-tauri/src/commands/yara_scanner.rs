@@ -1,25 +1,116 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use tauri::State;
+use tauri::{AppHandle, State};
 use tauri::path::SafePathBuf;
 use yara_x;
 use crate::metrics::{YARA_SCAN_DURATION, YARA_MATCHES_FOUND, YARA_RULES_LOADED};
+use super::mapped_file::MappedFile;
+use super::memory_analysis::{read_raw_memory_dump, MemoryRegion};
 use super::yara_rules::{RANSOMWARE_RULES, TROJAN_RULES, EXPLOIT_RULES, PACKER_RULES};
 
+/// Rule sets `load_yara_rules` keeps compiled
+const COMPILE_CACHE_CAPACITY: usize = 16;
+
+/// Rule set holding the rules `initialize_yara_scanner` compiles
+pub const BUILTIN_RULE_SET: &str = "builtin";
+
+/// Rule set `load_yara_rules` fills when no namespace is given
+const DEFAULT_RULE_SET: &str = "default";
+
 /// Global state for compiled YARA rules
 pub struct YaraState {
-    pub rules: Option<yara_x::Rules>,
+    /// Compiled rule sets by name: the built-in rules and one per namespace
+    /// loaded through `load_yara_rules`. Each is compiled on its own, so
+    /// loading one leaves the others compiled.
+    pub rule_sets: BTreeMap<String, Arc<yara_x::Rules>>,
     pub rules_count: usize,
+    pub compile_cache: YaraCompileCache,
 }
 
 impl YaraState {
     pub fn new() -> Self {
         YaraState {
-            rules: None,
+            rule_sets: BTreeMap::new(),
             rules_count: 0,
+            compile_cache: YaraCompileCache::default(),
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !self.rule_sets.is_empty()
+    }
+
+    /// Add `rules` as the rule set `name`, replacing any set of that name
+    pub fn set_rule_set(&mut self, name: &str, rules: Arc<yara_x::Rules>) {
+        self.rule_sets.insert(name.to_string(), rules);
+        self.rules_count = self.rule_sets.values().map(|rules| count_rules(rules)).sum();
+    }
+
+    /// Run every loaded rule set over `data`
+    pub fn scan(&self, data: &[u8]) -> Result<Vec<YaraMatch>, String> {
+        if !self.is_loaded() {
+            return Err("YARA rules not initialized. Call initialize_yara_scanner first.".to_string());
         }
+
+        let mut matches = Vec::new();
+        for rules in self.rule_sets.values() {
+            let mut scanner = yara_x::Scanner::new(rules);
+            let scan_results = scanner.scan(data)
+                .map_err(|e| format!("Scan failed: {}", e))?;
+            matches.extend(scan_results.matching_rules().map(|rule| convert_rule_match(&rule)));
+        }
+        Ok(matches)
+    }
+
+    /// Run the loaded rules over each region of a memory dump
+    ///
+    /// Regions are scanned separately so every match is attributed to the
+    /// region that holds it, and a payload that only exists in private memory
+    /// is reported against that region. Region addresses are treated as
+    /// offsets into `dump`, as in `carve_pe_from_memory`. String match offsets
+    /// in the result are virtual addresses.
+    pub fn scan_memory(&self, dump: &[u8], regions: &[MemoryRegion]) -> Result<Vec<YaraMemoryMatch>, String> {
+        if !self.is_loaded() {
+            return Err("YARA rules not initialized. Call initialize_yara_scanner first.".to_string());
+        }
+        let mut scanners: Vec<yara_x::Scanner> = self.rule_sets.values()
+            .map(|rules| yara_x::Scanner::new(rules))
+            .collect();
+        let mut matches = Vec::new();
+
+        for region in regions {
+            let start = region.start_address as usize;
+            let end = (region.end_address as usize).min(dump.len());
+            if start >= end {
+                continue;
+            }
+
+            for scanner in &mut scanners {
+                let scan_results = scanner.scan(&dump[start..end])
+                    .map_err(|e| format!("Scan failed at {:#x}: {}", region.start_address, e))?;
+
+                for rule in scan_results.matching_rules() {
+                    let mut yara_match = convert_rule_match(&rule);
+                    for string in &mut yara_match.strings {
+                        string.offset += region.start_address;
+                    }
+
+                    matches.push(YaraMemoryMatch {
+                        virtual_address: yara_match.strings.iter()
+                            .map(|s| s.offset)
+                            .min()
+                            .unwrap_or(region.start_address),
+                        region: region.clone(),
+                        yara_match,
+                    });
+                }
+            }
+        }
+
+        Ok(matches)
     }
 }
 
@@ -49,6 +140,133 @@ pub struct YaraStringMatch {
     pub matched_data: Option<String>,
 }
 
+/// A rule that matched inside a memory region
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct YaraMemoryMatch {
+    /// Region holding the matched data
+    pub region: MemoryRegion,
+    /// Address of the first matched string
+    pub virtual_address: u64,
+    pub yara_match: YaraMatch,
+}
+
+/// How `load_yara_rules` used the compile cache
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct YaraCompileCacheStats {
+    /// Loads that reused an identical, already compiled rule file
+    pub hits: usize,
+    /// Loads that had to compile
+    pub compiles: usize,
+    /// Compiled rule files currently held
+    pub cached_rule_sets: usize,
+}
+
+/// Compiled rule files by SHA-256 of their namespace and source. Each
+/// namespace is its own rule set, so loading a new rule file compiles only
+/// that file, and loading one again skips compilation.
+#[derive(Default)]
+pub struct YaraCompileCache {
+    compiled: HashMap<[u8; 32], Arc<yara_x::Rules>>,
+    order: VecDeque<[u8; 32]>,
+    stats: YaraCompileCacheStats,
+}
+
+impl YaraCompileCache {
+    /// The rules compiled for `key`, if they are still cached
+    pub fn get(&mut self, key: &[u8; 32]) -> Option<Arc<yara_x::Rules>> {
+        let rules = self.compiled.get(key).map(Arc::clone)?;
+        self.stats.hits += 1;
+        Some(rules)
+    }
+
+    /// Keep `rules` compiled for `key`; evicts the oldest rule file when full
+    pub fn insert(&mut self, key: [u8; 32], rules: Arc<yara_x::Rules>) {
+        self.stats.compiles += 1;
+        if self.compiled.insert(key, rules).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > COMPILE_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.compiled.remove(&oldest);
+            }
+        }
+        self.stats.cached_rule_sets = self.compiled.len();
+    }
+
+    pub fn stats(&self) -> YaraCompileCacheStats {
+        self.stats
+    }
+
+    pub fn key(source: &str, namespace: Option<&str>) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        match namespace {
+            Some(ns) => {
+                hasher.update([1]);
+                hasher.update((ns.len() as u64).to_le_bytes());
+                hasher.update(ns.as_bytes());
+            }
+            None => hasher.update([0]),
+        }
+        hasher.update(source.as_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// Load `source` as the rule set for `namespace`, reusing its compiled
+/// rules if the same file was loaded before. Compilation runs without the
+/// state locked, so scans carry on meanwhile. Returns the total rule count.
+pub fn load_rule_set(
+    yara_state: &Mutex<YaraState>,
+    source: &str,
+    namespace: Option<&str>,
+) -> Result<usize, String> {
+    let key = YaraCompileCache::key(source, namespace);
+    let cached = yara_state.lock()
+        .map_err(|e| format!("Failed to lock YARA state: {}", e))?
+        .compile_cache
+        .get(&key);
+
+    let cache_hit = cached.is_some();
+    let compiled = match cached {
+        Some(rules) => rules,
+        None => Arc::new(compile_rules(source, namespace)?),
+    };
+
+    let mut state = yara_state.lock()
+        .map_err(|e| format!("Failed to lock YARA state: {}", e))?;
+    if !cache_hit {
+        state.compile_cache.insert(key, Arc::clone(&compiled));
+    }
+    state.set_rule_set(namespace.unwrap_or(DEFAULT_RULE_SET), compiled);
+    Ok(state.rules_count)
+}
+
+/// Compile `source`, into `namespace` if given
+fn compile_rules(source: &str, namespace: Option<&str>) -> Result<yara_x::Rules, String> {
+    let mut compiler = yara_x::Compiler::new();
+
+    // If namespace is provided, create it
+    if let Some(ns) = namespace {
+        compiler.new_namespace(ns);
+    }
+
+    // Add the new rules
+    compiler.add_source(source)
+        .map_err(|e| format!("Failed to compile rules: {}", e))?;
+
+    // Check for compilation errors
+    let errors = compiler.errors();
+    if !errors.is_empty() {
+        let error_msg = errors.iter()
+            .map(|e| format!("{}: {}", e.code(), e.title()))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(format!("Compilation errors: {}", error_msg));
+    }
+
+    Ok(compiler.build())
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct YaraRuleSet {
     pub name: String,
@@ -232,13 +450,12 @@ rule Keylogger_Indicators {
     }
 
     let rules = compiler.build();
-    let rules_count = count_rules(&rules);
 
     let mut state = yara_state.lock()
         .map_err(|e| format!("Failed to lock YARA state: {}", e))?;
 
-    state.rules = Some(rules);
-    state.rules_count = rules_count;
+    state.set_rule_set(BUILTIN_RULE_SET, Arc::new(rules));
+    let rules_count = state.rules_count;
 
     // Record metrics for loaded default rules
     YARA_RULES_LOADED
@@ -254,35 +471,7 @@ pub async fn load_yara_rules(
     rules_content: String,
     namespace: Option<String>,
 ) -> Result<(), String> {
-    let mut compiler = yara_x::Compiler::new();
-
-    // If namespace is provided, create it
-    if let Some(ns) = namespace {
-        compiler.new_namespace(&ns);
-    }
-
-    // Add the new rules
-    compiler.add_source(rules_content.as_str())
-        .map_err(|e| format!("Failed to compile rules: {}", e))?;
-
-    // Check for compilation errors
-    let errors = compiler.errors();
-    if !errors.is_empty() {
-        let error_msg = errors.iter()
-            .map(|e| format!("{}: {}", e.code(), e.title()))
-            .collect::<Vec<_>>()
-            .join("; ");
-        return Err(format!("Compilation errors: {}", error_msg));
-    }
-
-    let rules = compiler.build();
-    let rules_count = count_rules(&rules);
-
-    let mut state = yara_state.lock()
-        .map_err(|e| format!("Failed to lock YARA state: {}", e))?;
-
-    state.rules = Some(rules);
-    state.rules_count = rules_count;
+    let rules_count = load_rule_set(&yara_state, &rules_content, namespace.as_deref())?;
 
     // Record metrics for loaded rules
     YARA_RULES_LOADED
@@ -292,6 +481,16 @@ pub async fn load_yara_rules(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_yara_compile_cache_stats(
+    yara_state: State<'_, Arc<Mutex<YaraState>>>,
+) -> Result<YaraCompileCacheStats, String> {
+    let state = yara_state.lock()
+        .map_err(|e| format!("Failed to lock YARA state: {}", e))?;
+
+    Ok(state.compile_cache.stats())
+}
+
 #[tauri::command]
 pub async fn load_default_yara_rules(
     yara_state: State<'_, Arc<Mutex<YaraState>>>,
@@ -299,7 +498,7 @@ pub async fn load_default_yara_rules(
     let state = yara_state.lock()
         .map_err(|e| format!("Failed to lock YARA state: {}", e))?;
 
-    let is_loaded = state.rules.is_some();
+    let is_loaded = state.is_loaded();
 
     let rule_sets = vec![
         YaraRuleSet {
@@ -337,18 +536,17 @@ pub async fn scan_file_with_yara(
             format!("Failed to lock YARA state: {}", e)
         })?;
 
-    let rules = state.rules.as_ref()
-        .ok_or_else(|| {
-            YARA_SCAN_DURATION
-                .with_label_values(&["default", "error"])
-                .observe(start.elapsed().as_secs_f64());
-            "YARA rules not initialized. Call initialize_yara_scanner first.".to_string()
-        })?;
+    if !state.is_loaded() {
+        YARA_SCAN_DURATION
+            .with_label_values(&["default", "error"])
+            .observe(start.elapsed().as_secs_f64());
+        return Err("YARA rules not initialized. Call initialize_yara_scanner first.".to_string());
+    }
 
     let rules_loaded = state.rules_count;
 
-    // Read file
-    let data = std::fs::read(file_path.as_ref())
+    // Map the file so large samples aren't copied before scanning
+    let data = MappedFile::open(file_path.as_ref())
         .map_err(|e| {
             YARA_SCAN_DURATION
                 .with_label_values(&["default", "error"])
@@ -356,59 +554,14 @@ pub async fn scan_file_with_yara(
             format!("Failed to read file: {}", e)
         })?;
 
-    // Create scanner with compiled rules
-    let mut scanner = yara_x::Scanner::new(rules);
-
-    // Scan the data
-    let scan_results = scanner.scan(&data)
-        .map_err(|e| format!("Scan failed: {}", e))?;
+    // Scan the data with every loaded rule set
+    let matches = state.scan(&data)?;
 
     let scan_time_ms = start.elapsed().as_millis() as u64;
 
-    // Convert YARA-X results to our format
-    let mut matches = Vec::new();
-
-    for rule in scan_results.matching_rules() {
-        let mut meta = HashMap::new();
-
-        // Extract metadata
-        for (key, value) in rule.metadata() {
-            let value_str = match value {
-                yara_x::MetaValue::Integer(i) => i.to_string(),
-                yara_x::MetaValue::Float(f) => f.to_string(),
-                yara_x::MetaValue::Bool(b) => b.to_string(),
-                yara_x::MetaValue::String(s) => s.to_string(),
-                yara_x::MetaValue::Bytes(b) => format!("{:?}", b),
-            };
-            meta.insert(key.to_string(), value_str);
-        }
-
-        // Extract matched strings
-        let mut strings = Vec::new();
-        for pattern in rule.patterns() {
-            for m in pattern.matches() {
-                let range = m.range();
-                let matched_bytes = m.data();
-
-                strings.push(YaraStringMatch {
-                    identifier: pattern.identifier().to_string(),
-                    offset: range.start as u64,
-                    length: matched_bytes.len(),
-                    matched_data: String::from_utf8(matched_bytes.to_vec()).ok(),
-                });
-            }
-        }
-
-        matches.push(YaraMatch {
-            rule_name: rule.identifier().to_string(),
-            namespace: Some(rule.namespace().to_string()),
-            tags: vec![], // YARA-X doesn't expose tags in the current API
-            meta: meta.clone(),
-            strings,
-        });
-
+    for yara_match in &matches {
         // Record match metrics by severity
-        let severity = meta.get("severity")
+        let severity = yara_match.meta.get("severity")
             .map(|s| s.as_str())
             .unwrap_or("unknown");
 
@@ -436,6 +589,62 @@ pub async fn scan_file_with_yara(
     })
 }
 
+/// Convert a YARA-X rule match to our format
+fn convert_rule_match(rule: &yara_x::Rule) -> YaraMatch {
+    let mut meta = HashMap::new();
+
+    // Extract metadata
+    for (key, value) in rule.metadata() {
+        let value_str = match value {
+            yara_x::MetaValue::Integer(i) => i.to_string(),
+            yara_x::MetaValue::Float(f) => f.to_string(),
+            yara_x::MetaValue::Bool(b) => b.to_string(),
+            yara_x::MetaValue::String(s) => s.to_string(),
+            yara_x::MetaValue::Bytes(b) => format!("{:?}", b),
+        };
+        meta.insert(key.to_string(), value_str);
+    }
+
+    // Extract matched strings
+    let mut strings = Vec::new();
+    for pattern in rule.patterns() {
+        for m in pattern.matches() {
+            let range = m.range();
+            let matched_bytes = m.data();
+
+            strings.push(YaraStringMatch {
+                identifier: pattern.identifier().to_string(),
+                offset: range.start as u64,
+                length: matched_bytes.len(),
+                matched_data: String::from_utf8(matched_bytes.to_vec()).ok(),
+            });
+        }
+    }
+
+    YaraMatch {
+        rule_name: rule.identifier().to_string(),
+        namespace: Some(rule.namespace().to_string()),
+        tags: vec![], // YARA-X doesn't expose tags in the current API
+        meta,
+        strings,
+    }
+}
+
+/// Scan each region of a raw memory dump with the loaded rules
+#[tauri::command]
+pub async fn scan_memory_dump_with_yara(
+    app: AppHandle,
+    yara_state: State<'_, Arc<Mutex<YaraState>>>,
+    file_path: String,
+) -> Result<Vec<YaraMemoryMatch>, String> {
+    let (dump, regions) = read_raw_memory_dump(&app, &file_path)?;
+
+    let state = yara_state.lock()
+        .map_err(|e| format!("Failed to lock YARA state: {}", e))?;
+
+    state.scan_memory(&dump, &regions)
+}
+
 #[tauri::command]
 pub async fn get_yara_rule_sets(
     yara_state: State<'_, Arc<Mutex<YaraState>>>,
@@ -892,6 +1101,102 @@ rule Complex {
         assert_eq!(analyze_condition_complexity(complex_rule), "Complex");
     }
 
+    fn memory_region(start: u64, end: u64, permissions: &str, mapped_file: Option<&str>) -> MemoryRegion {
+        MemoryRegion {
+            start_address: start,
+            end_address: end,
+            size: end - start,
+            permissions: permissions.to_string(),
+            region_type: if mapped_file.is_some() { "mapped_file" } else { "private" }.to_string(),
+            mapped_file: mapped_file.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_scan_memory_attributes_match_to_private_region() {
+        let mut compiler = yara_x::Compiler::new();
+        compiler.add_source(r#"
+rule InMemory_Beacon {
+    meta:
+        severity = "high"
+    strings:
+        $cfg = "beacon-config:sleep=60"
+    condition:
+        $cfg
+}
+        "#).unwrap();
+        let mut state = YaraState::new();
+        state.set_rule_set(BUILTIN_RULE_SET, Arc::new(compiler.build()));
+
+        // Mapped image at 0x0, private allocation at 0x2000 holding the payload
+        let mut dump = vec![0u8; 0x4000];
+        dump[0x100..0x10C].copy_from_slice(b"clean module");
+        let payload = b"beacon-config:sleep=60";
+        dump[0x2345..0x2345 + payload.len()].copy_from_slice(payload);
+
+        let regions = vec![
+            memory_region(0x0, 0x2000, "r-xp", Some("C:\\Windows\\System32\\kernel32.dll")),
+            memory_region(0x2000, 0x4000, "rwxp", None),
+        ];
+
+        let matches = state.scan_memory(&dump, &regions).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        let m = &matches[0];
+        assert_eq!(m.yara_match.rule_name, "InMemory_Beacon");
+        assert_eq!(m.region.start_address, 0x2000);
+        assert!(m.region.mapped_file.is_none());
+        assert_eq!(m.virtual_address, 0x2345);
+        assert_eq!(m.yara_match.strings[0].offset, 0x2345);
+        assert_eq!(m.yara_match.strings[0].length, payload.len());
+    }
+
+    #[test]
+    fn test_scan_memory_requires_rules() {
+        assert!(YaraState::new().scan_memory(&[0u8; 16], &[]).is_err());
+    }
+
+    fn literal_rule(name: &str, literal: &str) -> String {
+        format!("rule {} {{ strings: $a = \"{}\" condition: $a }}\n", name, literal)
+    }
+
+    #[test]
+    fn test_adding_a_rule_file_compiles_only_that_file() {
+        let state = Mutex::new(YaraState::new());
+        let credentials = literal_rule("Mimikatz", "sekurlsa::logonpasswords") + &literal_rule("Beacon", "beacon.dll");
+
+        assert_eq!(load_rule_set(&state, &credentials, Some("credentials")).unwrap(), 2);
+        let compiled = Arc::clone(&state.lock().unwrap().rule_sets["credentials"]);
+
+        // One new rule in its own file compiles just that rule
+        assert_eq!(load_rule_set(&state, &literal_rule("PsExec", "PSEXESVC"), Some("lateral")).unwrap(), 3);
+        {
+            let state = state.lock().unwrap();
+            assert!(Arc::ptr_eq(&compiled, &state.rule_sets["credentials"]));
+            assert_eq!(state.compile_cache.stats(), YaraCompileCacheStats { hits: 0, compiles: 2, cached_rule_sets: 2 });
+
+            let names: Vec<String> = state.scan(b"beacon.dll then PSEXESVC").unwrap()
+                .into_iter()
+                .map(|m| m.rule_name)
+                .collect();
+            assert_eq!(names, vec!["Beacon", "PsExec"]);
+        }
+
+        // Reloading an unchanged file reuses its compiled rules
+        load_rule_set(&state, &credentials, Some("credentials")).unwrap();
+        let state_guard = state.lock().unwrap();
+        assert!(Arc::ptr_eq(&compiled, &state_guard.rule_sets["credentials"]));
+        assert_eq!(state_guard.compile_cache.stats(), YaraCompileCacheStats { hits: 1, compiles: 2, cached_rule_sets: 2 });
+        drop(state_guard);
+
+        // A rule file that fails to compile isn't cached or loaded
+        assert!(load_rule_set(&state, "rule Broken {", Some("broken")).is_err());
+        let state = state.lock().unwrap();
+        assert_eq!(state.compile_cache.stats().cached_rule_sets, 2);
+        assert!(!state.rule_sets.contains_key("broken"));
+        assert_eq!(state.rules_count, 3);
+    }
+
     #[tokio::test]
     #[ignore] // Requires Tauri State which cannot be constructed in unit tests
     async fn test_builtin_rules_loaded() {
@@ -0,0 +1,33 @@
+//! Escaping for sample-derived text written into HTML and XML reports
+
+/// Escape the characters that are special in markup so file names, strings
+/// and JSON from a sample render as text. The output is valid in both HTML
+/// and XML text and attribute values.
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        // Test escaping of all special HTML characters
+        let input = r#"<script>alert("XSS & 'attack'")</script>"#;
+        let expected = "&lt;script&gt;alert(&quot;XSS &amp; &#39;attack&#39;&quot;)&lt;/script&gt;";
+        assert_eq!(escape_html(input), expected);
+
+        // Test normal text (should remain unchanged)
+        assert_eq!(escape_html("Hello World"), "Hello World");
+
+        // Test JSON-like content
+        let json = r#"{"key": "value", "array": [1, 2, 3]}"#;
+        let expected_json = "{&quot;key&quot;: &quot;value&quot;, &quot;array&quot;: [1, 2, 3]}";
+        assert_eq!(escape_html(json), expected_json);
+    }
+}
@@ -7,9 +7,13 @@
 pub mod ai_providers;
 pub mod api_server;
 pub mod cache;
+pub mod canonical_json;
 pub mod commands;
+pub mod html;
+pub mod log_config;
 pub mod metrics;
 pub mod quarantine;
+pub mod result_signing;
 pub mod sandbox;
 pub mod secure_storage;
 pub mod signature_verify;
@@ -0,0 +1,199 @@
+//! Leveled logging for the backend, built on `tracing`
+//!
+//! The minimum level comes from `ATHENA_LOG_LEVEL` at startup and can be
+//! changed at runtime, so production builds can drop debug noise without a
+//! restart.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, Registry};
+
+pub const LOG_LEVEL_ENV: &str = "ATHENA_LOG_LEVEL";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// `ATHENA_LOG_LEVEL`, or `debug` in development and `info` in release
+    pub fn from_env() -> Self {
+        std::env::var(LOG_LEVEL_ENV)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(if cfg!(debug_assertions) { LogLevel::Debug } else { LogLevel::Info })
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            other => Err(anyhow!("Unknown log level: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        };
+        f.write_str(name)
+    }
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Changes the minimum level of a subscriber built by `subscriber`
+#[derive(Clone)]
+pub struct LevelHandle {
+    handle: reload::Handle<LevelFilter, Registry>,
+}
+
+impl LevelHandle {
+    pub fn set(&self, level: LogLevel) -> Result<()> {
+        self.handle
+            .reload(LevelFilter::from(level))
+            .map_err(|e| anyhow!("Failed to change log level: {}", e))
+    }
+
+    pub fn get(&self) -> Option<LogLevel> {
+        let filter = self.handle.clone_current()?;
+        [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace]
+            .into_iter()
+            .find(|level| LevelFilter::from(*level) == filter)
+    }
+}
+
+/// A formatting subscriber writing to `writer` that drops events below `level`
+pub fn subscriber<W>(level: LogLevel, writer: W) -> (impl Subscriber + Send + Sync, LevelHandle)
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let (filter, handle) = reload::Layer::new(LevelFilter::from(level));
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(writer).with_target(true));
+    (subscriber, LevelHandle { handle })
+}
+
+static GLOBAL: OnceCell<LevelHandle> = OnceCell::new();
+
+/// Install the global subscriber, logging to stderr. Later calls are no-ops.
+pub fn init(level: LogLevel) {
+    GLOBAL.get_or_init(|| {
+        let (subscriber, handle) = subscriber(level, std::io::stderr);
+        if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
+            eprintln!("WARNING: Failed to install log subscriber: {}", e);
+        }
+        handle
+    });
+}
+
+/// Change the global minimum level
+pub fn set_level(level: LogLevel) -> Result<()> {
+    GLOBAL
+        .get()
+        .ok_or_else(|| anyhow!("Logging has not been initialized"))?
+        .set(level)
+}
+
+pub fn current_level() -> Option<LogLevel> {
+    GLOBAL.get().and_then(LevelHandle::get)
+}
+
+/// Log `message` from `source` (a WASM module, the frontend) at a level
+/// only known at runtime; the `tracing` macros need it at compile time
+pub fn log(level: LogLevel, source: &str, message: &str) {
+    match level {
+        LogLevel::Error => tracing::error!(source, "{}", message),
+        LogLevel::Warn => tracing::warn!(source, "{}", message),
+        LogLevel::Info => tracing::info!(source, "{}", message),
+        LogLevel::Debug => tracing::debug!(source, "{}", message),
+        LogLevel::Trace => tracing::trace!(source, "{}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_warn_level_suppresses_info() {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let (subscriber, handle) = subscriber(LogLevel::Info, move || writer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("loaded 9 modules");
+            handle.set(LogLevel::Warn).unwrap();
+            tracing::info!("cache warmed");
+            log(LogLevel::Warn, "sandbox", "module fell back to interpreter");
+        });
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("loaded 9 modules"));
+        assert!(!output.contains("cache warmed"));
+        assert!(output.contains("WARN"));
+        assert!(output.contains("module fell back to interpreter"));
+        assert!(output.contains("sandbox"));
+        assert_eq!(handle.get(), Some(LogLevel::Warn));
+    }
+
+    #[test]
+    fn test_parse_log_level() {
+        assert_eq!("WARNING".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+        assert_eq!(" debug ".parse::<LogLevel>().unwrap(), LogLevel::Debug);
+        assert!("verbose".parse::<LogLevel>().is_err());
+        assert!(LogLevel::Error < LogLevel::Trace);
+    }
+}
@@ -1,6 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
+mod log_config;
 mod ai_providers;
 mod signature_verify;
 mod workflow;
@@ -11,15 +12,19 @@ mod metrics;
 mod sandbox;
 mod quarantine;
 mod secure_storage;
+mod html;
 use commands::system_monitor::SystemMonitor;
 use commands::wasm_runtime::WasmRuntime;
 use commands::yara_scanner::YaraState;
 use quarantine::QuarantineStorage;
-use workflow::JobStore;
+use workflow::notifications::{FileSink, NotificationSink, NotificationSinks, StdoutSink, WebhookSink};
+use workflow::{JobStore, RetentionPolicy};
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
 
 fn main() {
+    log_config::init(log_config::LogLevel::from_env());
+
     // Initialize job store with graceful error handling
     let job_store = match JobStore::new("jobs.db") {
         Ok(store) => Arc::new(store),
@@ -39,7 +44,7 @@ fn main() {
 
     // Create the app data directory if it doesn't exist
     if let Err(e) = std::fs::create_dir_all(&app_data_dir) {
-        eprintln!("WARNING: Failed to create app data directory: {}", e);
+        tracing::warn!("Failed to create app data directory: {}", e);
     }
 
     let quarantine_storage = match QuarantineStorage::new(&app_data_dir) {
@@ -52,6 +57,47 @@ fn main() {
         }
     };
 
+    // Team-specific string categories on top of the defaults
+    let string_rules = app_data_dir.join(commands::string_categories::RULES_FILE_NAME);
+    if string_rules.exists() {
+        if let Err(e) = commands::string_categories::load_rules_file(&string_rules) {
+            tracing::warn!("Failed to load string category rules: {}", e);
+        }
+    }
+
+    // Known-family imphashes for family hints
+    let imphash_families = app_data_dir.join(commands::imphash_families::FAMILIES_FILE_NAME);
+    if imphash_families.exists() {
+        if let Err(e) = commands::imphash_families::load_families_file(&imphash_families) {
+            tracing::warn!("Failed to load imphash families: {}", e);
+        }
+    }
+
+    // Alert on high-severity results from batch and watch-folder jobs
+    let mut sinks: Vec<Arc<dyn NotificationSink>> = vec![
+        Arc::new(StdoutSink),
+        Arc::new(FileSink::new(app_data_dir.join("notifications.jsonl"))),
+    ];
+    if let Ok(url) = std::env::var("ATHENA_NOTIFY_WEBHOOK") {
+        match WebhookSink::new(url) {
+            Ok(sink) => sinks.push(Arc::new(sink)),
+            Err(e) => tracing::warn!("Failed to configure notification webhook: {}", e),
+        }
+    }
+
+    // Retire old analysis results in the background so startup isn't held up
+    let retention_store = job_store.clone();
+    std::thread::spawn(move || {
+        let policy = RetentionPolicy::from_env();
+        match retention_store.run_retention(&policy) {
+            Ok(report) => tracing::info!(
+                "Result retention ({} days): archived {}, deleted {}",
+                policy.max_age_days, report.archived, report.deleted
+            ),
+            Err(e) => tracing::warn!("Result retention failed: {}", e),
+        }
+    });
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
@@ -60,6 +106,8 @@ fn main() {
         .manage(Arc::new(Mutex::new(YaraState::new())))
         .manage(job_store)
         .manage(quarantine_storage)
+        .manage(commands::workflow::WatchFolderState::default())
+        .manage(NotificationSinks(sinks))
         .invoke_handler(tauri::generate_handler![
             commands::file_ops::upload_file,
             commands::file_ops::get_file_metadata,
@@ -68,6 +116,14 @@ fn main() {
             commands::file_ops::read_file_text,
             commands::file_ops::write_file_text,
             commands::file_ops::create_temp_file,
+            commands::string_categories::get_string_category_rules,
+            commands::string_categories::add_string_category_rule,
+            commands::string_categories::load_string_category_rules,
+            commands::string_categories::reset_string_category_rules,
+            commands::imphash_families::get_imphash_families,
+            commands::imphash_families::add_imphash_family,
+            commands::imphash_families::load_imphash_families,
+            commands::imphash_families::reset_imphash_families,
             commands::system::get_system_status,
             commands::network::analyze_network_packet,
             commands::network::export_network_capture,
@@ -130,18 +186,24 @@ fn main() {
             commands::advanced_analysis::analyze_behavior,
             commands::advanced_analysis::get_threat_intelligence,
             commands::advanced_analysis::get_threat_attribution,
+            commands::advanced_analysis::export_openioc_format,
             commands::advanced_analysis::export_stix_format,
             commands::advanced_analysis::create_threat_alert,
             commands::advanced_analysis::generate_campaign_report,
             commands::advanced_analysis::share_threat_intelligence,
             commands::file_analysis::analyze_file,
+            commands::file_analysis::calculate_file_hashes,
+            commands::batch_analysis::analyze_directory,
             commands::file_analysis::get_analysis_stats,
             commands::wasm_file_bridge::analyze_file_with_wasm,
             commands::wasm_file_bridge::load_wasm_security_modules,
+            commands::self_test::run_self_test,
             commands::yara_scanner::initialize_yara_scanner,
             commands::yara_scanner::load_yara_rules,
+            commands::yara_scanner::get_yara_compile_cache_stats,
             commands::yara_scanner::load_default_yara_rules,
             commands::yara_scanner::scan_file_with_yara,
+            commands::yara_scanner::scan_memory_dump_with_yara,
             commands::yara_scanner::get_yara_rule_sets,
             commands::yara_scanner::validate_yara_rule,
             commands::yara_scanner::auto_generate_yara_rules,
@@ -153,12 +215,17 @@ fn main() {
             // Frontend logging commands
             commands::logging::log_frontend_message,
             commands::logging::log_frontend_error,
+            commands::logging::get_log_level,
+            commands::logging::set_log_level,
             commands::workflow::start_job,
             commands::workflow::get_job_status,
             commands::workflow::list_jobs,
             commands::workflow::cancel_job,
             commands::workflow::delete_job,
             commands::workflow::get_active_jobs,
+            commands::workflow::start_watch_folder,
+            commands::workflow::stop_watch_folder,
+            commands::workflow::get_watch_folder,
             // Container management commands
             commands::container::check_docker_available,
             commands::container::create_sandbox_container,
@@ -181,6 +248,7 @@ fn main() {
             commands::sandbox_commands::get_mitre_attack_details,
             commands::sandbox_commands::format_sandbox_error,
             commands::sandbox_commands::calculate_threat_score,
+            commands::sandbox_commands::generate_sandbox_html_report,
             // Volatility memory forensics
             commands::sandbox_commands::analyze_memory_with_volatility,
             commands::sandbox_commands::check_volatility_available,
@@ -192,6 +260,9 @@ fn main() {
             // Memory analysis commands
             commands::memory_analysis::get_memory_regions,
             commands::memory_analysis::extract_strings_from_dump,
+            commands::memory_analysis::diff_memory_regions,
+            commands::memory_analysis::carve_pe_images_from_dump,
+            commands::memory_analysis::carve_pe_images_from_sample,
             // Sample management commands (quarantine storage)
             commands::samples::register_sample,
             commands::samples::list_staged_samples,
@@ -219,30 +290,30 @@ fn main() {
             // Initialize WASM runtime on startup using AppHandle
             let init_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                println!("Initializing WASM runtime...");
+                tracing::info!("Initializing WASM runtime...");
                 match crate::commands::wasm_runtime::initialize_wasm_runtime(
                     init_handle.state()
                 ).await {
-                    Ok(msg) => println!("{}", msg),
-                    Err(e) => eprintln!("Failed to initialize WASM runtime: {}", e),
+                    Ok(msg) => tracing::info!("{}", msg),
+                    Err(e) => tracing::error!("Failed to initialize WASM runtime: {}", e),
                 }
 
                 // Load security modules
-                println!("Loading WASM security modules...");
+                tracing::info!("Loading WASM security modules...");
                 match crate::commands::wasm_file_bridge::load_wasm_security_modules(
                     init_handle.state()
                 ).await {
-                    Ok(modules) => println!("Loaded {} WASM modules: {:?}", modules.len(), modules),
-                    Err(e) => eprintln!("Failed to load WASM modules: {}", e),
+                    Ok(modules) => tracing::info!("Loaded {} WASM modules: {:?}", modules.len(), modules),
+                    Err(e) => tracing::error!("Failed to load WASM modules: {}", e),
                 }
             });
 
             // Start embedded API server
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                println!("Starting API server on port 3000...");
+                tracing::info!("Starting API server on port 3000...");
                 if let Err(e) = api_server::start_api_server(app_handle, 3000).await {
-                    eprintln!("API server error: {}", e);
+                    tracing::error!("API server error: {}", e);
                 }
             });
 
@@ -224,6 +224,15 @@ pub static ACTIVE_WORKFLOW_JOBS: Lazy<GaugeVec> = Lazy::new(|| {
     .expect("Failed to register ACTIVE_WORKFLOW_JOBS metric")
 });
 
+pub static RETENTION_RESULTS: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "athena_retention_results_total",
+        "Analysis results retired by the retention policy",
+        &["action"]
+    )
+    .expect("Failed to register RETENTION_RESULTS metric")
+});
+
 /// Disassembly metrics
 pub static DISASSEMBLY_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
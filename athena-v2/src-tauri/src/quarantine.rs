@@ -66,6 +66,28 @@ pub struct SampleMetadata {
     pub tags: Vec<String>,
     pub notes: Option<String>,
     pub analysis_count: u32,
+    /// Absent for samples registered before provenance was recorded
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+}
+
+/// Chain-of-custody record for a sample
+///
+/// Artifacts derived from a sample (carved images, decoded layers) point
+/// back to it through `parent_sample_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Provenance {
+    pub sample_id: String,
+    /// Where the sample came from, e.g. "upload", "watch_folder", "carved_pe"
+    pub source: String,
+    pub received_at: DateTime<Utc>,
+    pub analyst: Option<String>,
+    pub parent_sample_id: Option<String>,
+}
+
+/// Deterministic sample ID: the same content always gets the same ID
+pub fn sample_id(sha256: &str) -> String {
+    format!("ATH-{}", &sha256[..16.min(sha256.len())].to_uppercase())
 }
 
 /// Detected file type based on magic bytes
@@ -139,6 +161,39 @@ impl QuarantineStorage {
 
     /// Store a malware sample securely using hash-based naming
     pub fn store_sample(&self, data: &[u8], original_filename: &str) -> Result<StoredSample> {
+        self.store_sample_with_provenance(data, original_filename, "upload", None, None)
+    }
+
+    /// Store an artifact derived from an already quarantined sample, linking
+    /// its provenance to the parent's sample ID
+    pub fn store_derived_sample(
+        &self,
+        data: &[u8],
+        filename: &str,
+        parent_sha256: &str,
+        source: &str,
+    ) -> Result<StoredSample> {
+        let parent = self
+            .load_metadata(parent_sha256)?
+            .with_context(|| format!("Parent sample not found: {}", parent_sha256))?;
+        let parent_id = parent
+            .provenance
+            .map(|p| p.sample_id)
+            .unwrap_or_else(|| sample_id(&parent.sha256));
+
+        self.store_sample_with_provenance(data, filename, source, None, Some(parent_id))
+    }
+
+    /// Store a sample, recording where it came from. A duplicate keeps the
+    /// provenance of its first registration.
+    pub fn store_sample_with_provenance(
+        &self,
+        data: &[u8],
+        original_filename: &str,
+        source: &str,
+        analyst: Option<String>,
+        parent_sample_id: Option<String>,
+    ) -> Result<StoredSample> {
         // Calculate hashes
         let sha256 = hex::encode(Sha256::digest(data));
         let sha1 = hex::encode(sha1::Sha1::digest(data));
@@ -184,6 +239,19 @@ impl QuarantineStorage {
         let file_type = detect_file_type(data);
         let mime_type = detect_mime_type(data, original_filename);
 
+        let existing_provenance = if is_duplicate {
+            self.load_metadata(&sha256).ok().flatten().and_then(|m| m.provenance)
+        } else {
+            None
+        };
+        let provenance = existing_provenance.unwrap_or_else(|| Provenance {
+            sample_id: sample_id(&sha256),
+            source: source.to_string(),
+            received_at: Utc::now(),
+            analyst,
+            parent_sample_id,
+        });
+
         // Create metadata
         let metadata = SampleMetadata {
             sha256: sha256.clone(),
@@ -199,6 +267,7 @@ impl QuarantineStorage {
             tags: Vec::new(),
             notes: None,
             analysis_count: if is_duplicate { 1 } else { 0 },
+            provenance: Some(provenance),
         };
 
         // Store metadata separately
@@ -882,4 +951,23 @@ mod tests {
         assert_eq!(stored.sha256, stored2.sha256);
     }
 
+    #[test]
+    fn test_provenance_recorded_and_kept_for_duplicates() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = QuarantineStorage::new(temp_dir.path()).unwrap();
+        let data = b"MZ\x90\x00provenance sample";
+
+        let first = storage
+            .store_sample_with_provenance(data, "a.exe", "upload", Some("analyst1".to_string()), None)
+            .unwrap();
+        let provenance = first.metadata.provenance.clone().unwrap();
+        assert_eq!(provenance.sample_id, sample_id(&first.sha256));
+        assert_eq!(provenance.source, "upload");
+        assert_eq!(provenance.analyst.as_deref(), Some("analyst1"));
+        assert!(provenance.parent_sample_id.is_none());
+
+        let again = storage.store_sample_with_provenance(data, "b.exe", "watch_folder", None, None).unwrap();
+        assert_eq!(again.metadata.provenance, Some(provenance));
+    }
+
 }
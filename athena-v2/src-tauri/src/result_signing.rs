@@ -0,0 +1,136 @@
+//! Detached Ed25519 signatures over analysis results
+//!
+//! A result that leaves Athena (exported, sent to a SIEM, attached to a
+//! ticket) can carry a signature so whoever receives it can check it wasn't
+//! altered on the way. The signature covers the result's canonical JSON, so
+//! map ordering can't change the signed bytes.
+
+use crate::canonical_json::to_canonical_json;
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// A result and the signature over it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedResult<T> {
+    pub result: T,
+    pub algorithm: String,
+    /// Hex public key of the signer, so a consumer holding several keys
+    /// knows which to verify against. Verification never trusts it.
+    pub public_key: String,
+    /// Base64 signature over `to_canonical_json(result)`
+    pub signature: String,
+}
+
+/// Sign `result` with `private_key`
+pub fn sign_result<T: Serialize>(result: T, private_key: &SigningKey) -> Result<SignedResult<T>> {
+    let signature = private_key.sign(to_canonical_json(&result)?.as_bytes());
+    Ok(SignedResult {
+        result,
+        algorithm: SIGNATURE_ALGORITHM.to_string(),
+        public_key: hex::encode(private_key.verifying_key().to_bytes()),
+        signature: general_purpose::STANDARD.encode(signature.to_bytes()),
+    })
+}
+
+/// Whether `signed.signature` is `public_key`'s signature over the result as
+/// it stands now. Any change to the result, however small, fails.
+pub fn verify_result<T: Serialize>(signed: &SignedResult<T>, public_key: &VerifyingKey) -> bool {
+    if signed.algorithm != SIGNATURE_ALGORITHM {
+        return false;
+    }
+    let Ok(signature_bytes) = general_purpose::STANDARD.decode(&signed.signature) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(&signature_bytes) else {
+        return false;
+    };
+    let Ok(message) = to_canonical_json(&signed.result) else {
+        return false;
+    };
+    public_key.verify_strict(message.as_bytes(), &signature).is_ok()
+}
+
+/// A signing key from its 32-byte seed in hex
+pub fn signing_key_from_hex(seed: &str) -> Result<SigningKey> {
+    Ok(SigningKey::from_bytes(&key_bytes(seed)?))
+}
+
+/// A public key from its 32 bytes in hex
+pub fn verifying_key_from_hex(public_key: &str) -> Result<VerifyingKey> {
+    VerifyingKey::from_bytes(&key_bytes(public_key)?).map_err(|e| anyhow!("Invalid Ed25519 public key: {}", e))
+}
+
+fn key_bytes(hex_key: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(hex_key.trim()).context("Key is not valid hex")?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow!("Ed25519 keys are 32 bytes, got {}", bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file_analysis::{FileAnalysisResult, FileHashes, FileInfo, FormatInfo};
+
+    fn analysis_result() -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_info: FileInfo {
+                name: "invoice.exe".to_string(),
+                size: 1024,
+                mime_type: "application/x-dosexec".to_string(),
+                magic_bytes: "4D5A".to_string(),
+                creation_time: None,
+                modification_time: None,
+            },
+            format_info: FormatInfo::Unknown,
+            sections: vec![],
+            imports: vec![],
+            capabilities: Default::default(),
+            exports: vec![],
+            strings: vec![],
+            entropy: 7.2,
+            hashes: FileHashes {
+                md5: "d41d8cd98f00b204e9800998ecf8427e".to_string(),
+                sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+                sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+                ssdeep: None,
+                tlsh: None,
+                imphash: None,
+            },
+            signatures: vec![],
+            anomalies: vec![],
+            family_hints: vec!["emotet".to_string()],
+            similar_samples: vec![],
+        }
+    }
+
+    #[test]
+    fn test_tampered_result_fails_verification() {
+        let private_key = signing_key_from_hex(&"4a".repeat(32)).unwrap();
+        let public_key = verifying_key_from_hex(&hex::encode(private_key.verifying_key().to_bytes())).unwrap();
+
+        let signed = sign_result(analysis_result(), &private_key).unwrap();
+        assert_eq!(signed.public_key, hex::encode(public_key.to_bytes()));
+        assert!(verify_result(&signed, &public_key));
+
+        // Survives a round trip through JSON, as a consumer would receive it
+        let json = serde_json::to_string(&signed).unwrap();
+        let received: SignedResult<FileAnalysisResult> = serde_json::from_str(&json).unwrap();
+        assert!(verify_result(&received, &public_key));
+
+        let mut tampered = received.clone();
+        tampered.result.family_hints.clear();
+        assert!(!verify_result(&tampered, &public_key));
+
+        let mut tampered = received;
+        tampered.result.entropy = 1.0;
+        assert!(!verify_result(&tampered, &public_key));
+
+        let other_key = signing_key_from_hex(&"5b".repeat(32)).unwrap().verifying_key();
+        assert!(!verify_result(&signed, &other_key));
+    }
+}
@@ -101,6 +101,9 @@ pub struct EvasionAttempt {
     pub trigger: String,
     /// Whether the anti-evasion measure blocked it
     pub blocked: bool,
+    /// Locale the sample compared against, for geofencing checks
+    #[serde(default)]
+    pub checked_locale: Option<String>,
 }
 
 /// Types of evasion techniques that malware may use
@@ -124,8 +127,52 @@ pub enum EvasionTechnique {
     SleepEvasion,
     /// Checking for hooks (syscall interception)
     HookDetection,
+    /// Checking keyboard layout or locale to skip certain regions
+    Geofencing,
 }
 
+/// APIs that reveal the keyboard layout or locale of the machine
+const LOCALE_CHECK_APIS: &[&str] = &[
+    "GetKeyboardLayout",
+    "GetKeyboardLayoutList",
+    "GetUserDefaultLangID",
+    "GetSystemDefaultLangID",
+    "GetUserDefaultLCID",
+    "GetSystemDefaultLCID",
+    "GetUserDefaultUILanguage",
+];
+
+/// Events that act on the result of a check: a compare or branch, or
+/// bailing out altogether
+const BRANCH_EVENTS: &[&str] = &["cmp", "branch", "ExitProcess", "TerminateProcess", "exit", "exit_group"];
+
+/// How many events after a locale query may still be its branch
+const GEOFENCE_WINDOW: usize = 3;
+
+/// Bytes of code after a locale API call that may still hold the compare
+/// acting on its result
+const GEOFENCE_CODE_WINDOW: usize = 32;
+
+/// Section characteristic marking code
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+/// Language IDs of the CIS region most often excluded by geofencing
+const CIS_LANGIDS: &[(u16, &str)] = &[
+    (0x0419, "ru-RU"),
+    (0x0819, "ru-MD"),
+    (0x0422, "uk-UA"),
+    (0x0423, "be-BY"),
+    (0x043F, "kk-KZ"),
+    (0x0443, "uz-Latn-UZ"),
+    (0x0428, "tg-Cyrl-TJ"),
+    (0x042B, "hy-AM"),
+    (0x042C, "az-Latn-AZ"),
+    (0x0437, "ka-GE"),
+    (0x0440, "ky-KG"),
+    (0x0442, "tk-TM"),
+    (0x0818, "ro-MD"),
+];
+
 /// Manager for anti-evasion configuration and script generation
 pub struct AntiEvasionManager {
     config: AntiEvasionConfig,
@@ -449,6 +496,7 @@ echo "[ANTI-EVASION] Tier 2 behavioral simulation active"
                     description: "Attempting to read VM detection files".to_string(),
                     trigger: format!("{}({})", syscall, args),
                     blocked: self.config.hide_vm_artifacts,
+                    checked_locale: None,
                 });
             }
             if args.contains("/.dockerenv") || args.contains("/proc/1/cgroup") {
@@ -458,6 +506,7 @@ echo "[ANTI-EVASION] Tier 2 behavioral simulation active"
                     description: "Checking for Docker container markers".to_string(),
                     trigger: format!("{}({})", syscall, args),
                     blocked: self.config.hide_vm_artifacts,
+                    checked_locale: None,
                 });
             }
         }
@@ -470,6 +519,7 @@ echo "[ANTI-EVASION] Tier 2 behavioral simulation active"
                 description: "Anti-debugging via ptrace TRACEME".to_string(),
                 trigger: syscall.to_string(),
                 blocked: false,
+                checked_locale: None,
             });
         }
 
@@ -483,12 +533,175 @@ echo "[ANTI-EVASION] Tier 2 behavioral simulation active"
                     description: "Long sleep detected (potential timeout evasion)".to_string(),
                     trigger: format!("{}({})", syscall, args),
                     blocked: self.config.sleep_acceleration > 1.0,
+                    checked_locale: None,
                 });
             }
         }
 
         None
     }
+
+    /// Find locale or keyboard-layout queries whose result the sample
+    /// branches on. `calls` is the trace in order as (API or event, args);
+    /// the locale is taken from the compare operand, else the query result.
+    pub fn detect_geofencing(&self, calls: &[(&str, &str)]) -> Vec<EvasionAttempt> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut attempts = Vec::new();
+        for (i, (api, args)) in calls.iter().enumerate() {
+            if !LOCALE_CHECK_APIS.contains(api) {
+                continue;
+            }
+            let Some((branch, branch_args)) = calls[i + 1..]
+                .iter()
+                .take(GEOFENCE_WINDOW)
+                .find(|(name, _)| BRANCH_EVENTS.contains(name))
+            else {
+                continue;
+            };
+
+            let checked_locale = locale_from_args(branch_args).or_else(|| locale_from_args(args));
+            let description = match &checked_locale {
+                Some(locale) => format!("Branches on {} result (checks for {})", api, locale),
+                None => format!("Branches on {} result", api),
+            };
+            attempts.push(EvasionAttempt {
+                timestamp: now,
+                technique_type: EvasionTechnique::Geofencing,
+                description,
+                trigger: format!("{}({}) -> {}({})", api, args, branch, branch_args),
+                blocked: false,
+                checked_locale,
+            });
+        }
+        attempts
+    }
+
+    /// Find geofencing in a PE sample's code: calls through the import
+    /// table to a locale API whose result is compared against an immediate
+    /// and branched on. The sandbox traces Linux syscalls, so the Windows
+    /// API sequence `detect_geofencing` needs has to come from the binary.
+    pub fn detect_geofencing_in_binary(&self, data: &[u8]) -> Vec<EvasionAttempt> {
+        let trace = locale_call_trace(data);
+        let calls: Vec<(&str, &str)> = trace
+            .iter()
+            .map(|(event, args)| (event.as_str(), args.as_str()))
+            .collect();
+        self.detect_geofencing(&calls)
+    }
+}
+
+/// Locale API calls in `data`'s executable sections, each followed by the
+/// compare and conditional branch after it, as `detect_geofencing` events
+fn locale_call_trace(data: &[u8]) -> Vec<(String, String)> {
+    let Ok(pe) = goblin::pe::PE::parse(data) else {
+        return Vec::new();
+    };
+    let slots: std::collections::HashMap<u64, &str> = pe
+        .imports
+        .iter()
+        .filter_map(|import| {
+            let api = LOCALE_CHECK_APIS.iter().find(|api| **api == import.name)?;
+            Some((import.offset as u64, *api))
+        })
+        .collect();
+    if slots.is_empty() {
+        return Vec::new();
+    }
+
+    let mut trace = Vec::new();
+    for section in pe.sections.iter().filter(|s| s.characteristics & IMAGE_SCN_MEM_EXECUTE != 0) {
+        let start = section.pointer_to_raw_data as usize;
+        let end = start.saturating_add(section.size_of_raw_data as usize).min(data.len());
+        let Some(code) = data.get(start..end) else {
+            continue;
+        };
+        let section_rva = section.virtual_address as u64;
+
+        for i in 0..code.len().saturating_sub(6) {
+            // call [slot]: RIP-relative on x64, an absolute VA on x86
+            if code[i..i + 2] != [0xFF, 0x15] {
+                continue;
+            }
+            let disp = i32::from_le_bytes([code[i + 2], code[i + 3], code[i + 4], code[i + 5]]);
+            let slot = if pe.is_64 {
+                (section_rva + i as u64 + 6).wrapping_add(disp as i64 as u64)
+            } else {
+                (disp as u32 as u64).wrapping_sub(pe.image_base)
+            };
+            let Some(api) = slots.get(&slot) else {
+                continue;
+            };
+
+            let after = &code[i + 6..code.len().min(i + 6 + GEOFENCE_CODE_WINDOW)];
+            if let Some((compare, branch)) = compare_and_branch(after) {
+                trace.push((api.to_string(), String::new()));
+                trace.push(("cmp".to_string(), compare));
+                trace.push(("branch".to_string(), branch));
+            }
+        }
+    }
+    trace
+}
+
+/// The first `cmp ax/eax/reg, imm` in `code` directly followed by a
+/// conditional jump, as (operands, jump mnemonic)
+fn compare_and_branch(code: &[u8]) -> Option<(String, String)> {
+    const REGS32: [&str; 8] = ["eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi"];
+    const REGS16: [&str; 8] = ["ax", "cx", "dx", "bx", "sp", "bp", "si", "di"];
+    const JCC: [&str; 16] = [
+        "jo", "jno", "jb", "jae", "je", "jne", "jbe", "ja", "js", "jns", "jp", "jnp", "jl", "jge", "jle", "jg",
+    ];
+
+    let u16_at = |at: usize| code.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as u32);
+    let u32_at = |at: usize| code.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    for j in 0..code.len() {
+        let decoded = match code[j..] {
+            // cmp ax, imm16
+            [0x66, 0x3D, ..] => u16_at(j + 2).map(|imm| ("ax", imm, 4)),
+            // cmp r16, imm16
+            [0x66, 0x81, modrm, ..] if modrm & 0xF8 == 0xF8 => {
+                u16_at(j + 3).map(|imm| (REGS16[(modrm & 7) as usize], imm, 5))
+            }
+            // cmp eax, imm32
+            [0x3D, ..] => u32_at(j + 1).map(|imm| ("eax", imm, 5)),
+            // cmp r32, imm32
+            [0x81, modrm, ..] if modrm & 0xF8 == 0xF8 => {
+                u32_at(j + 2).map(|imm| (REGS32[(modrm & 7) as usize], imm, 6))
+            }
+            _ => None,
+        };
+        let Some((reg, imm, len)) = decoded else {
+            continue;
+        };
+        let branch = match code.get(j + len..j + len + 2) {
+            Some([op @ 0x70..=0x7F, _]) => JCC[(op & 0xF) as usize],
+            Some([0x0F, op @ 0x80..=0x8F]) => JCC[(op & 0xF) as usize],
+            _ => continue,
+        };
+        return Some((format!("{}, 0x{:X}", reg, imm), branch.to_string()));
+    }
+    None
+}
+
+/// Locale named by the first hex LANGID, LCID or HKL in `args`
+fn locale_from_args(args: &str) -> Option<String> {
+    let value = args
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .find_map(|token| {
+            let hex = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X"))?;
+            u64::from_str_radix(hex, 16).ok()
+        })?;
+    // LCIDs and HKLs carry the language ID in their low word
+    let langid = (value & 0xFFFF) as u16;
+    Some(match CIS_LANGIDS.iter().find(|(id, _)| *id == langid) {
+        Some((_, name)) => name.to_string(),
+        None => format!("LANGID 0x{:04X}", langid),
+    })
 }
 
 impl Default for AntiEvasionManager {
@@ -563,6 +776,128 @@ mod tests {
         assert_eq!(attempt.unwrap().technique_type, EvasionTechnique::DebuggerCheck);
     }
 
+    #[test]
+    fn test_detect_geofencing_locale_check() {
+        let manager = AntiEvasionManager::new();
+
+        let attempts = manager.detect_geofencing(&[
+            ("GetModuleHandleW", "NULL"),
+            ("GetKeyboardLayout", "0 = 0x04190419"),
+            ("cmp", "ax, 0x419"),
+            ("ExitProcess", "0"),
+        ]);
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].technique_type, EvasionTechnique::Geofencing);
+        assert_eq!(attempts[0].checked_locale.as_deref(), Some("ru-RU"));
+
+        // A locale query nothing acts on is not geofencing
+        let attempts = manager.detect_geofencing(&[
+            ("GetUserDefaultLangID", "= 0x0409"),
+            ("CreateFileW", "C:\\Users\\Public\\a.txt"),
+        ]);
+        assert!(attempts.is_empty());
+    }
+
+    /// A 32-bit PE importing `api` whose code is `code`, with the call
+    /// into the import table written wherever `code` holds `CALL_SLOT`
+    fn pe_calling(api: &str, code: &[u8]) -> Vec<u8> {
+        const IMAGE_BASE: u32 = 0x0040_0000;
+        const TEXT_RVA: u32 = 0x1000;
+        const IDATA_RVA: u32 = 0x2000;
+
+        let mut pe = vec![0u8; 0x600];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        // File header: i386, two sections, 0xE0-byte optional header
+        pe[0x84..0x86].copy_from_slice(&0x014Cu16.to_le_bytes());
+        pe[0x86..0x88].copy_from_slice(&2u16.to_le_bytes());
+        pe[0x94..0x96].copy_from_slice(&0xE0u16.to_le_bytes());
+        pe[0x96..0x98].copy_from_slice(&0x0102u16.to_le_bytes());
+        // Optional header
+        let opt = 0x98;
+        pe[opt..opt + 2].copy_from_slice(&0x010Bu16.to_le_bytes());
+        pe[opt + 16..opt + 20].copy_from_slice(&TEXT_RVA.to_le_bytes());
+        pe[opt + 28..opt + 32].copy_from_slice(&IMAGE_BASE.to_le_bytes());
+        pe[opt + 32..opt + 36].copy_from_slice(&0x1000u32.to_le_bytes());
+        pe[opt + 36..opt + 40].copy_from_slice(&0x200u32.to_le_bytes());
+        pe[opt + 40..opt + 42].copy_from_slice(&4u16.to_le_bytes());
+        pe[opt + 48..opt + 50].copy_from_slice(&4u16.to_le_bytes());
+        pe[opt + 56..opt + 60].copy_from_slice(&0x3000u32.to_le_bytes());
+        pe[opt + 60..opt + 64].copy_from_slice(&0x200u32.to_le_bytes());
+        pe[opt + 68..opt + 70].copy_from_slice(&3u16.to_le_bytes());
+        pe[opt + 92..opt + 96].copy_from_slice(&16u32.to_le_bytes());
+        // Import directory
+        pe[opt + 104..opt + 108].copy_from_slice(&IDATA_RVA.to_le_bytes());
+        pe[opt + 108..opt + 112].copy_from_slice(&40u32.to_le_bytes());
+
+        let section = |pe: &mut Vec<u8>, at: usize, name: &[u8], rva: u32, raw: u32, flags: u32| {
+            pe[at..at + name.len()].copy_from_slice(name);
+            pe[at + 8..at + 12].copy_from_slice(&0x200u32.to_le_bytes());
+            pe[at + 12..at + 16].copy_from_slice(&rva.to_le_bytes());
+            pe[at + 16..at + 20].copy_from_slice(&0x200u32.to_le_bytes());
+            pe[at + 20..at + 24].copy_from_slice(&raw.to_le_bytes());
+            pe[at + 36..at + 40].copy_from_slice(&flags.to_le_bytes());
+        };
+        section(&mut pe, 0x178, b".text", TEXT_RVA, 0x200, 0x6000_0020);
+        section(&mut pe, 0x1A0, b".idata", IDATA_RVA, 0x400, 0xC000_0040);
+
+        // .idata: descriptor, null descriptor, ILT, IAT, hint/name, DLL name
+        let idata = 0x400;
+        let (ilt, iat, hint, dll) = (IDATA_RVA + 0x40, IDATA_RVA + 0x50, IDATA_RVA + 0x60, IDATA_RVA + 0x80);
+        pe[idata..idata + 4].copy_from_slice(&ilt.to_le_bytes());
+        pe[idata + 12..idata + 16].copy_from_slice(&dll.to_le_bytes());
+        pe[idata + 16..idata + 20].copy_from_slice(&iat.to_le_bytes());
+        pe[idata + 0x40..idata + 0x44].copy_from_slice(&hint.to_le_bytes());
+        pe[idata + 0x50..idata + 0x54].copy_from_slice(&hint.to_le_bytes());
+        pe[idata + 0x62..idata + 0x62 + api.len()].copy_from_slice(api.as_bytes());
+        pe[idata + 0x80..idata + 0x8C].copy_from_slice(b"user32.dll\0\0");
+
+        let slot_va = IMAGE_BASE + iat;
+        let code: Vec<u8> = code
+            .windows(CALL_SLOT.len())
+            .enumerate()
+            .fold(code.to_vec(), |mut out, (i, window)| {
+                if window == CALL_SLOT {
+                    out[i + 2..i + 6].copy_from_slice(&slot_va.to_le_bytes());
+                }
+                out
+            });
+        pe[0x200..0x200 + code.len()].copy_from_slice(&code);
+        pe
+    }
+
+    /// `call [slot]`, patched by `pe_calling`
+    const CALL_SLOT: [u8; 6] = [0xFF, 0x15, 0xEE, 0xEE, 0xEE, 0xEE];
+
+    #[test]
+    fn test_detect_geofencing_in_binary() {
+        let manager = AntiEvasionManager::new();
+
+        let mut code = CALL_SLOT.to_vec();
+        code.extend_from_slice(&[
+            0x66, 0x3D, 0x19, 0x04, // cmp ax, 0x419
+            0x74, 0x02,             // je skip
+            0x6A, 0x00,             // push 0
+            0xC3,                   // ret
+        ]);
+        let attempts = manager.detect_geofencing_in_binary(&pe_calling("GetKeyboardLayout", &code));
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].technique_type, EvasionTechnique::Geofencing);
+        assert_eq!(attempts[0].checked_locale.as_deref(), Some("ru-RU"));
+        assert_eq!(attempts[0].trigger, "GetKeyboardLayout() -> cmp(ax, 0x419)");
+
+        // The same call with its result only stored is not geofencing
+        let mut code = CALL_SLOT.to_vec();
+        code.extend_from_slice(&[0xA3, 0x00, 0x30, 0x40, 0x00, 0xC3]); // mov [0x403000], eax; ret
+        assert!(manager.detect_geofencing_in_binary(&pe_calling("GetKeyboardLayout", &code)).is_empty());
+
+        // Nor is a compare after an unrelated import
+        let mut code = CALL_SLOT.to_vec();
+        code.extend_from_slice(&[0x66, 0x3D, 0x19, 0x04, 0x74, 0x02, 0xC3]);
+        assert!(manager.detect_geofencing_in_binary(&pe_calling("GetTickCount", &code)).is_empty());
+    }
+
     #[test]
     fn test_artifacts_list() {
         let manager = AntiEvasionManager::new();
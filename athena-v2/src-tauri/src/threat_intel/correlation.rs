@@ -0,0 +1,257 @@
+use crate::commands::file_analysis::FileAnalysisResult;
+use crate::threat_intel::openioc::url_host;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// Hosts found in nearly every signed or manifest-carrying binary (CRL and
+/// OCSP endpoints, XML namespaces). Sharing one says nothing about a campaign.
+const COMMON_HOSTS: &[&str] = &[
+    "microsoft.com",
+    "windowsupdate.com",
+    "digicert.com",
+    "verisign.com",
+    "symantec.com",
+    "sectigo.com",
+    "globalsign.com",
+    "w3.org",
+];
+
+/// Kernel object namespaces; a string in one of them is almost always a
+/// mutex or event name
+const MUTEX_PREFIXES: &[&str] = &["Global\\", "Local\\"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndicatorKind {
+    Domain,
+    Ip,
+    Mutex,
+    Imphash,
+}
+
+/// An indicator seen in more than one sample of the batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedIndicator {
+    pub kind: IndicatorKind,
+    pub value: String,
+    /// SHA-256 of every sample carrying it
+    pub samples: Vec<String>,
+}
+
+/// Samples linked, directly or through each other, by shared indicators.
+/// A sample sharing nothing forms a cluster of its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleCluster {
+    /// SHA-256 of each sample in the cluster
+    pub samples: Vec<String>,
+    pub shared_indicators: Vec<SharedIndicator>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorrelationReport {
+    /// Largest cluster first
+    pub clusters: Vec<SampleCluster>,
+}
+
+impl CorrelationReport {
+    /// Clusters of more than one sample, i.e. likely campaigns
+    pub fn campaigns(&self) -> impl Iterator<Item = &SampleCluster> {
+        self.clusters.iter().filter(|c| c.samples.len() > 1)
+    }
+}
+
+/// Group the samples of a batch that share C2 domains or IPs, mutex names
+/// or an imphash
+pub fn correlate_batch(results: &[FileAnalysisResult]) -> CorrelationReport {
+    correlate_samples(&results.iter().collect::<Vec<_>>())
+}
+
+/// `correlate_batch` over borrowed results
+pub fn correlate_samples(results: &[&FileAnalysisResult]) -> CorrelationReport {
+    // Which samples carry each indicator, by index into `results`
+    let mut carriers: BTreeMap<(IndicatorKind, String), Vec<usize>> = BTreeMap::new();
+    for (index, result) in results.iter().enumerate() {
+        for indicator in indicators(result) {
+            let samples = carriers.entry(indicator).or_default();
+            if !samples.contains(&index) {
+                samples.push(index);
+            }
+        }
+    }
+    carriers.retain(|_, samples| samples.len() > 1);
+
+    let mut parent: Vec<usize> = (0..results.len()).collect();
+    for samples in carriers.values() {
+        for &other in &samples[1..] {
+            union(&mut parent, samples[0], other);
+        }
+    }
+
+    let mut clusters: BTreeMap<usize, SampleCluster> = BTreeMap::new();
+    for (index, result) in results.iter().enumerate() {
+        let root = find(&mut parent, index);
+        clusters
+            .entry(root)
+            .or_insert_with(|| SampleCluster { samples: Vec::new(), shared_indicators: Vec::new() })
+            .samples
+            .push(result.hashes.sha256.clone());
+    }
+    for ((kind, value), samples) in carriers {
+        let root = find(&mut parent, samples[0]);
+        clusters.get_mut(&root).unwrap().shared_indicators.push(SharedIndicator {
+            kind,
+            value,
+            samples: samples.iter().map(|&i| results[i].hashes.sha256.clone()).collect(),
+        });
+    }
+
+    let mut clusters: Vec<SampleCluster> = clusters.into_values().collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.samples.len()));
+    CorrelationReport { clusters }
+}
+
+/// The indicators of `result` worth correlating on
+fn indicators(result: &FileAnalysisResult) -> Vec<(IndicatorKind, String)> {
+    let mut indicators = Vec::new();
+
+    for s in &result.strings {
+        let value = s.value.trim();
+        if s.category.as_deref() == Some("URL") {
+            let Some(host) = url_host(value) else { continue };
+            match host.parse::<IpAddr>() {
+                Ok(ip) => push_ip(&mut indicators, ip),
+                Err(_) if host.contains('.') && !is_common_host(host) => {
+                    indicators.push((IndicatorKind::Domain, host.to_lowercase()));
+                }
+                Err(_) => {}
+            }
+        } else if let Ok(ip) = value.parse::<IpAddr>() {
+            push_ip(&mut indicators, ip);
+        } else if MUTEX_PREFIXES.iter().any(|p| value.strip_prefix(p).is_some_and(|name| !name.is_empty())) {
+            indicators.push((IndicatorKind::Mutex, value.to_string()));
+        }
+    }
+
+    if let Some(imphash) = &result.hashes.imphash {
+        indicators.push((IndicatorKind::Imphash, imphash.to_lowercase()));
+    }
+    indicators
+}
+
+/// Private, loopback and unspecified addresses are shared by everything
+fn push_ip(indicators: &mut Vec<(IndicatorKind, String)>, ip: IpAddr) {
+    let local = match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_unspecified() || v4.is_link_local() || v4.is_broadcast(),
+        IpAddr::V6(v6) => v6.is_loopback() || v6.is_unspecified(),
+    };
+    if !local {
+        indicators.push((IndicatorKind::Ip, ip.to_string()));
+    }
+}
+
+fn is_common_host(host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    COMMON_HOSTS
+        .iter()
+        .any(|common| host == *common || host.ends_with(&format!(".{}", common)))
+}
+
+fn find(parent: &mut [usize], mut node: usize) -> usize {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[b.max(a)] = a.min(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file_analysis::{ExtractedString, FileHashes, FileInfo, FormatInfo};
+
+    fn sample(sha256: &str, strings: &[(&str, Option<&str>)]) -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_info: FileInfo {
+                name: format!("{}.exe", &sha256[..8]),
+                size: 1024,
+                mime_type: "application/x-dosexec".to_string(),
+                magic_bytes: "4D5A".to_string(),
+                creation_time: None,
+                modification_time: None,
+            },
+            format_info: FormatInfo::Unknown,
+            sections: vec![],
+            imports: vec![],
+            capabilities: Default::default(),
+            exports: vec![],
+            strings: strings
+                .iter()
+                .map(|(value, category)| ExtractedString {
+                    value: value.to_string(),
+                    offset: 0,
+                    encoding: "ascii".to_string(),
+                    suspicious: true,
+                    category: category.map(str::to_string),
+                })
+                .collect(),
+            entropy: 6.5,
+            hashes: FileHashes {
+                md5: String::new(),
+                sha1: String::new(),
+                sha256: sha256.to_string(),
+                ssdeep: None,
+                tlsh: None,
+                imphash: None,
+            },
+            signatures: vec![],
+            anomalies: vec![],
+            family_hints: vec![],
+            similar_samples: vec![],
+        }
+    }
+
+    #[test]
+    fn test_shared_c2_domain_clusters_samples() {
+        let first = "a".repeat(64);
+        let second = "b".repeat(64);
+        let unrelated = "c".repeat(64);
+        let results = vec![
+            sample(&first, &[
+                ("http://c2.evil-domain.com/gate.php", Some("URL")),
+                ("http://crl.microsoft.com/pki/crl.crl", Some("URL")),
+            ]),
+            sample(&unrelated, &[
+                ("https://updates.example.org/check", Some("URL")),
+                ("http://crl.microsoft.com/pki/crl.crl", Some("URL")),
+                ("127.0.0.1", None),
+            ]),
+            sample(&second, &[
+                ("https://C2.evil-domain.com:8443/beacon", Some("URL")),
+                ("127.0.0.1", None),
+            ]),
+        ];
+
+        let report = correlate_batch(&results);
+
+        assert_eq!(report.clusters.len(), 2);
+        let campaign = &report.clusters[0];
+        assert_eq!(campaign.samples, vec![first.clone(), second.clone()]);
+        assert_eq!(campaign.shared_indicators, vec![SharedIndicator {
+            kind: IndicatorKind::Domain,
+            value: "c2.evil-domain.com".to_string(),
+            samples: vec![first, second],
+        }]);
+
+        assert_eq!(report.clusters[1].samples, vec![unrelated]);
+        assert!(report.clusters[1].shared_indicators.is_empty());
+        assert_eq!(report.campaigns().count(), 1);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod correlation;
+pub mod openioc;
 pub mod stix_parser;
 
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,272 @@
+use crate::commands::file_analysis::FileAnalysisResult;
+use crate::html::escape_html;
+use chrono::{SecondsFormat, Utc};
+use std::fmt::Write;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+const OPENIOC_NAMESPACE: &str = "http://openioc.org/schemas/OpenIOC_1.1";
+
+/// One `IndicatorItem`: where to look and what to match
+struct IocTerm {
+    document: &'static str,
+    search: &'static str,
+    content_type: &'static str,
+    value: String,
+}
+
+/// Render the indicators extracted from `result` as an OpenIOC 1.1 document.
+///
+/// Each kind of indicator (file hashes, domains, IPs, file paths) becomes an
+/// `OR` group, since any one of them identifies the sample, and the groups are
+/// themselves combined with `OR`.
+pub fn to_openioc(result: &FileAnalysisResult) -> String {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let iocs = collect_terms(result);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<OpenIOC xmlns=\"{}\" id=\"{}\" last-modified=\"{}\" published-date=\"{}\">",
+        OPENIOC_NAMESPACE,
+        Uuid::new_v4(),
+        now,
+        now
+    );
+
+    xml.push_str("  <metadata>\n");
+    let _ = writeln!(
+        xml,
+        "    <short_description>{}</short_description>",
+        escape_html(&result.file_info.name)
+    );
+    let _ = writeln!(
+        xml,
+        "    <description>Indicators extracted by Athena from {} (SHA-256 {})</description>",
+        escape_html(&result.file_info.name),
+        escape_html(&result.hashes.sha256)
+    );
+    xml.push_str("    <keywords/>\n");
+    xml.push_str("    <authored_by>Athena</authored_by>\n");
+    let _ = writeln!(xml, "    <authored_date>{}</authored_date>", now);
+    xml.push_str("    <links/>\n");
+    xml.push_str("  </metadata>\n");
+
+    xml.push_str("  <criteria>\n");
+    let _ = writeln!(xml, "    <Indicator id=\"{}\" operator=\"OR\">", Uuid::new_v4());
+    for group in iocs.iter().filter(|g| !g.is_empty()) {
+        let _ = writeln!(xml, "      <Indicator id=\"{}\" operator=\"OR\">", Uuid::new_v4());
+        for term in group {
+            write_indicator_item(&mut xml, term);
+        }
+        xml.push_str("      </Indicator>\n");
+    }
+    xml.push_str("    </Indicator>\n");
+    xml.push_str("  </criteria>\n");
+    xml.push_str("  <parameters/>\n");
+    xml.push_str("</OpenIOC>\n");
+
+    xml
+}
+
+fn write_indicator_item(xml: &mut String, term: &IocTerm) {
+    let _ = writeln!(
+        xml,
+        "        <IndicatorItem id=\"{}\" condition=\"is\" preserve-case=\"false\" negate=\"false\">",
+        Uuid::new_v4()
+    );
+    let _ = writeln!(
+        xml,
+        "          <Context document=\"{}\" search=\"{}\" type=\"mir\"/>",
+        term.document, term.search
+    );
+    let _ = writeln!(
+        xml,
+        "          <Content type=\"{}\">{}</Content>",
+        term.content_type,
+        escape_html(&term.value)
+    );
+    xml.push_str("        </IndicatorItem>\n");
+}
+
+/// Indicators grouped by kind: hashes, domains, IPs, file paths
+fn collect_terms(result: &FileAnalysisResult) -> [Vec<IocTerm>; 4] {
+    let mut hashes = Vec::new();
+    for (search, content_type, value) in [
+        ("FileItem/Md5sum", "md5", &result.hashes.md5),
+        ("FileItem/Sha1sum", "string", &result.hashes.sha1),
+        ("FileItem/Sha256sum", "string", &result.hashes.sha256),
+    ] {
+        if !value.is_empty() {
+            hashes.push(IocTerm {
+                document: "FileItem",
+                search,
+                content_type,
+                value: value.to_lowercase(),
+            });
+        }
+    }
+
+    let mut domains: Vec<String> = Vec::new();
+    let mut ips: Vec<String> = Vec::new();
+    let mut paths: Vec<String> = Vec::new();
+
+    for s in &result.strings {
+        let value = s.value.trim();
+        let host = match s.category.as_deref() {
+            Some("URL") => url_host(value),
+            Some("Path") => {
+                push_unique(&mut paths, value.to_string());
+                None
+            }
+            _ => Some(value),
+        };
+
+        if let Some(host) = host {
+            if host.parse::<IpAddr>().is_ok() {
+                push_unique(&mut ips, host.to_string());
+            } else if s.category.as_deref() == Some("URL") && host.contains('.') {
+                push_unique(&mut domains, host.to_lowercase());
+            }
+        }
+    }
+
+    let terms = |values: Vec<String>, document, search, content_type| {
+        values
+            .into_iter()
+            .map(|value| IocTerm { document, search, content_type, value })
+            .collect::<Vec<_>>()
+    };
+
+    [
+        hashes,
+        terms(domains, "Network", "Network/DNS", "string"),
+        terms(ips, "PortItem", "PortItem/remoteIP", "IP"),
+        terms(paths, "FileItem", "FileItem/FullPath", "string"),
+    ]
+}
+
+/// Host part of an http(s) URL
+pub(crate) fn url_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://")?.1;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+fn push_unique(values: &mut Vec<String>, value: String) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file_analysis::{ExtractedString, FileHashes, FileInfo, FormatInfo};
+
+    fn string(value: &str, category: Option<&str>) -> ExtractedString {
+        ExtractedString {
+            value: value.to_string(),
+            offset: 0,
+            encoding: "ascii".to_string(),
+            suspicious: true,
+            category: category.map(str::to_string),
+        }
+    }
+
+    fn analysis_result() -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_info: FileInfo {
+                name: "invoice<1>.exe".to_string(),
+                size: 1024,
+                mime_type: "application/x-dosexec".to_string(),
+                magic_bytes: "4D5A".to_string(),
+                creation_time: None,
+                modification_time: None,
+            },
+            format_info: FormatInfo::Unknown,
+            sections: vec![],
+            imports: vec![],
+            capabilities: Default::default(),
+            exports: vec![],
+            strings: vec![
+                string("http://c2.evil-domain.com:8080/gate.php", Some("URL")),
+                string("https://203.0.113.7/payload", Some("URL")),
+                string("198.51.100.23", None),
+                string("C:\\Users\\Public\\svchost.exe", Some("Path")),
+                string("kernel32.dll", Some("Executable")),
+            ],
+            entropy: 7.2,
+            hashes: FileHashes {
+                md5: "D41D8CD98F00B204E9800998ECF8427E".to_string(),
+                sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+                sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+                ssdeep: None,
+                tlsh: None,
+                imphash: None,
+            },
+            signatures: vec![],
+            anomalies: vec![],
+            family_hints: vec![],
+            similar_samples: vec![],
+        }
+    }
+
+    /// Every opened element is closed in order and nothing is left open
+    fn assert_well_formed(xml: &str) {
+        let body = xml.strip_prefix("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n").expect("XML declaration");
+        let mut stack: Vec<&str> = Vec::new();
+        let mut rest = body;
+
+        while let Some(start) = rest.find('<') {
+            assert!(!rest[..start].contains(['<', '>']), "stray markup");
+            let end = rest[start..].find('>').expect("unterminated tag") + start;
+            let tag = &rest[start + 1..end];
+            let name = tag.trim_start_matches('/').split_whitespace().next().unwrap().trim_end_matches('/');
+
+            if tag.starts_with('/') {
+                assert_eq!(stack.pop(), Some(name), "mismatched closing tag");
+            } else if !tag.ends_with('/') {
+                stack.push(name);
+            }
+            rest = &rest[end + 1..];
+        }
+
+        assert!(stack.is_empty(), "unclosed elements: {:?}", stack);
+    }
+
+    #[test]
+    fn test_openioc_contains_sha256_indicator() {
+        let xml = to_openioc(&analysis_result());
+
+        assert_well_formed(&xml);
+        assert!(xml.contains("<OpenIOC xmlns=\"http://openioc.org/schemas/OpenIOC_1.1\""));
+        assert!(xml.contains(
+            "<Context document=\"FileItem\" search=\"FileItem/Sha256sum\" type=\"mir\"/>\n          \
+             <Content type=\"string\">e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855</Content>"
+        ));
+        assert!(xml.contains("<Content type=\"md5\">d41d8cd98f00b204e9800998ecf8427e</Content>"));
+        assert!(xml.contains("invoice&lt;1&gt;.exe"));
+    }
+
+    #[test]
+    fn test_openioc_groups_network_and_file_indicators() {
+        let xml = to_openioc(&analysis_result());
+
+        assert!(xml.contains("<Content type=\"string\">c2.evil-domain.com</Content>"));
+        assert!(xml.contains("<Content type=\"IP\">203.0.113.7</Content>"));
+        assert!(xml.contains("<Content type=\"IP\">198.51.100.23</Content>"));
+        assert!(xml.contains("<Content type=\"string\">C:\\Users\\Public\\svchost.exe</Content>"));
+        assert!(!xml.contains("kernel32.dll"));
+
+        // Top-level OR plus one OR group each for hashes, domains, IPs and paths
+        assert_eq!(xml.matches("operator=\"OR\"").count(), 5);
+        assert!(!xml.contains("operator=\"AND\""));
+    }
+}
@@ -0,0 +1,166 @@
+use super::schema::{Job, JobStatus, WorkflowType};
+use crate::ai_providers::retry::{with_retry, RetryConfig};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::error::Error;
+use std::time::Duration;
+use tauri::Url;
+
+/// Output fields that are small enough to include in the summary as-is
+const SUMMARY_SECTIONS: &[&str] = &["file_info", "threat_assessment"];
+
+/// Body POSTed to a job's completion webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionPayload {
+    pub job_id: String,
+    pub workflow_type: WorkflowType,
+    pub status: JobStatus,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    /// Scalar fields of the job output plus its file info and threat
+    /// assessment. Bulky sections (strings, imports, per-file results) are
+    /// left out; fetch the job for those.
+    pub summary: serde_json::Value,
+}
+
+impl CompletionPayload {
+    pub fn for_job(job: &Job) -> Self {
+        Self {
+            job_id: job.id.clone(),
+            workflow_type: job.workflow_type.clone(),
+            status: job.status.clone(),
+            completed_at: job.completed_at,
+            error: job.error.clone(),
+            summary: job.output.as_ref().map(summarize).unwrap_or(serde_json::Value::Null),
+        }
+    }
+}
+
+fn summarize(output: &serde_json::Value) -> serde_json::Value {
+    let Some(fields) = output.as_object() else {
+        return output.clone();
+    };
+
+    let summary = fields
+        .iter()
+        .filter(|(key, value)| {
+            !(value.is_array() || value.is_object()) || SUMMARY_SECTIONS.contains(&key.as_str())
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    serde_json::Value::Object(summary)
+}
+
+/// POST the completion payload for `job` to `url`, retrying connection
+/// failures, rate limiting and server errors with backoff
+pub async fn send_completion_webhook(url: &Url, job: &Job, retry: &RetryConfig) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .connect_timeout(Duration::from_secs(5))
+        .build()?;
+    let payload = CompletionPayload::for_job(job);
+    let (client, payload) = (&client, &payload);
+
+    with_retry(
+        retry,
+        move || async move {
+            let response = client
+                .post(url.clone())
+                .json(payload)
+                .send()
+                .await
+                .map_err(|e| -> Box<dyn Error + Send + Sync> {
+                    if e.is_connect() || e.is_timeout() {
+                        format!("connection to completion webhook failed: {}", e).into()
+                    } else {
+                        Box::new(e)
+                    }
+                })?;
+
+            let status = response.status();
+            if !status.is_success() {
+                // The status code in the message decides whether it is retried
+                return Err(format!("completion webhook returned {}", status).into());
+            }
+            Ok::<(), Box<dyn Error + Send + Sync>>(())
+        },
+        "Completion webhook",
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to deliver completion webhook for job {}: {}", job.id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct MockServer {
+        attempts: Arc<Mutex<u32>>,
+        received: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    /// Fails the first delivery with 503 to exercise the retry
+    async fn receive(
+        State(server): State<MockServer>,
+        Json(body): Json<serde_json::Value>,
+    ) -> StatusCode {
+        let mut attempts = server.attempts.lock().unwrap();
+        *attempts += 1;
+        if *attempts == 1 {
+            return StatusCode::SERVICE_UNAVAILABLE;
+        }
+        server.received.lock().unwrap().push(body);
+        StatusCode::OK
+    }
+
+    #[tokio::test]
+    async fn test_completion_webhook_receives_payload() {
+        let server = MockServer::default();
+        let app = Router::new()
+            .route("/hooks/athena", post(receive))
+            .with_state(server.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let url: Url = format!("http://{}/hooks/athena", addr).parse().unwrap();
+        let mut job = Job::new(
+            WorkflowType::FileAnalysis,
+            serde_json::json!({"file_path": "/quarantine/ab/abcd"}),
+        )
+        .with_completion_webhook(Some(url.clone()));
+        job.start();
+        job.complete(serde_json::json!({
+            "status": "complete",
+            "file_info": {"sha256": "abcd", "size": 2048},
+            "strings": ["a", "b"],
+            "threat_assessment": {"threat_level": "critical", "malware_detected": true},
+            "analysis_time_ms": 42,
+        }));
+
+        let retry = RetryConfig {
+            max_retries: 2,
+            initial_delay_ms: 10,
+            max_delay_ms: 10,
+            backoff_multiplier: 1.0,
+            jitter: 0.0,
+        };
+        send_completion_webhook(&url, &job, &retry).await.unwrap();
+
+        assert_eq!(*server.attempts.lock().unwrap(), 2);
+        let received = server.received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["job_id"], job.id);
+        assert_eq!(received[0]["status"], "Completed");
+        assert_eq!(received[0]["summary"]["threat_assessment"]["threat_level"], "critical");
+        assert_eq!(received[0]["summary"]["analysis_time_ms"], 42);
+        assert!(received[0]["summary"].get("strings").is_none());
+    }
+}
@@ -1,5 +1,8 @@
 use super::schema::{Job, WorkflowType};
 use super::job_store::JobStore;
+use super::notifications::{NotificationSinks, Severity};
+use super::completion_webhook::send_completion_webhook;
+use crate::ai_providers::retry::RetryConfig;
 use anyhow::Result;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -8,6 +11,8 @@ use sha2::Digest;
 use tauri::{AppHandle, Emitter};
 use tauri::path::SafePathBuf;
 use crate::metrics::WORKFLOW_EXECUTION_DURATION;
+use crate::html::escape_html;
+use crate::commands::yara_scanner::BUILTIN_RULE_SET;
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ProgressUpdate {
@@ -22,6 +27,7 @@ pub struct JobExecutor {
     progress_tx: mpsc::UnboundedSender<ProgressUpdate>,
     wasm_runtime: Arc<tokio::sync::Mutex<Option<crate::commands::wasm_runtime::WasmRuntime>>>,
     yara_state: Arc<tokio::sync::Mutex<crate::commands::yara_scanner::YaraState>>,
+    notification_sinks: NotificationSinks,
 }
 
 impl JobExecutor {
@@ -38,9 +44,16 @@ impl JobExecutor {
             progress_tx,
             wasm_runtime,
             yara_state,
+            notification_sinks: NotificationSinks::default(),
         }
     }
 
+    /// Notify these sinks when a job finishes with a high-severity result
+    pub fn with_notification_sinks(mut self, sinks: NotificationSinks) -> Self {
+        self.notification_sinks = sinks;
+        self
+    }
+
     pub async fn execute_job(&self, job_id: String) -> Result<()> {
         // Get job
         let mut job = self.store.get_job(&job_id)?
@@ -83,6 +96,17 @@ impl JobExecutor {
             self.store.add_log(&job.id, log)?;
         }
 
+        self.notification_sinks.notify_job(&job).await;
+
+        // Deliver in the background so retries don't hold up the caller
+        if let Some(url) = job.completion_webhook.clone() {
+            tokio::spawn(async move {
+                if let Err(e) = send_completion_webhook(&url, &job, &RetryConfig::default()).await {
+                    tracing::warn!("Completion webhook for job {} failed: {}", job.id, e);
+                }
+            });
+        }
+
         Ok(())
     }
 
@@ -298,6 +322,7 @@ impl JobExecutor {
                         "status": "scanned",
                         "matches": result.matches.len(),
                         "threat_detected": has_threats,
+                        "severity": Severity::from_yara_matches(&result.matches),
                         "scan_time_ms": result.scan_time_ms,
                     }));
                 }
@@ -561,7 +586,7 @@ impl JobExecutor {
 
         // Escape HTML to prevent injection attacks
         let sections_json = serde_json::to_string_pretty(&sections).unwrap_or_default();
-        let sections_escaped = Self::escape_html(&sections_json);
+        let sections_escaped = escape_html(&sections_json);
 
         let html = format!(r#"<!DOCTYPE html>
 <html lang="en">
@@ -602,15 +627,6 @@ impl JobExecutor {
         Ok(())
     }
 
-    /// Escape HTML special characters to prevent injection attacks
-    fn escape_html(s: &str) -> String {
-        s.replace('&', "&amp;")
-         .replace('<', "&lt;")
-         .replace('>', "&gt;")
-         .replace('"', "&quot;")
-         .replace('\'', "&#39;")
-    }
-
     fn send_progress(&self, job_id: &str, progress: f64, message: String) {
         let update = ProgressUpdate {
             job_id: job_id.to_string(),
@@ -627,15 +643,13 @@ impl JobExecutor {
 
     /// Helper method to scan a file with YARA, handling initialization if needed
     async fn scan_file_with_yara(&self, file_path: &str) -> Result<crate::commands::yara_scanner::YaraScanResult> {
-        use std::collections::HashMap;
-
         let start = std::time::Instant::now();
 
         // Lock the YARA state
         let mut yara_state = self.yara_state.lock().await;
 
         // Initialize YARA rules if not already done
-        if yara_state.rules.is_none() {
+        if !yara_state.is_loaded() {
             let mut compiler = yara_x::Compiler::new();
 
             // Add default rules (same as in yara_scanner.rs initialize_yara_scanner)
@@ -669,74 +683,21 @@ rule Suspicious_Executable {
                 .map_err(|e| anyhow::anyhow!("Failed to compile YARA rules: {}", e))?;
 
             let rules = compiler.build();
-            let rules_count = self.count_yara_rules(&rules);
-
-            yara_state.rules = Some(rules);
-            yara_state.rules_count = rules_count;
+            yara_state.set_rule_set(BUILTIN_RULE_SET, Arc::new(rules));
         }
 
-        // Get the compiled rules
-        let rules = yara_state.rules.as_ref()
-            .ok_or_else(|| anyhow::anyhow!("YARA rules not initialized"))?;
-
         let rules_loaded = yara_state.rules_count;
 
         // Read file
         let data = std::fs::read(file_path)
             .map_err(|e| anyhow::anyhow!("Failed to read file: {}", e))?;
 
-        // Create scanner with compiled rules
-        let mut scanner = yara_x::Scanner::new(rules);
-
-        // Scan the data
-        let scan_results = scanner.scan(&data)
-            .map_err(|e| anyhow::anyhow!("Scan failed: {}", e))?;
+        // Scan the data with every loaded rule set
+        let matches = yara_state.scan(&data)
+            .map_err(|e| anyhow::anyhow!(e))?;
 
         let scan_time_ms = start.elapsed().as_millis() as u64;
 
-        // Convert YARA-X results to our format
-        let mut matches = Vec::new();
-
-        for rule in scan_results.matching_rules() {
-            let mut meta = HashMap::new();
-
-            // Extract metadata
-            for (key, value) in rule.metadata() {
-                let value_str = match value {
-                    yara_x::MetaValue::Integer(i) => i.to_string(),
-                    yara_x::MetaValue::Float(f) => f.to_string(),
-                    yara_x::MetaValue::Bool(b) => b.to_string(),
-                    yara_x::MetaValue::String(s) => s.to_string(),
-                    yara_x::MetaValue::Bytes(b) => format!("{:?}", b),
-                };
-                meta.insert(key.to_string(), value_str);
-            }
-
-            // Extract matched strings
-            let mut strings = Vec::new();
-            for pattern in rule.patterns() {
-                for m in pattern.matches() {
-                    let range = m.range();
-                    let matched_bytes = m.data();
-
-                    strings.push(crate::commands::yara_scanner::YaraStringMatch {
-                        identifier: pattern.identifier().to_string(),
-                        offset: range.start as u64,
-                        length: matched_bytes.len(),
-                        matched_data: String::from_utf8(matched_bytes.to_vec()).ok(),
-                    });
-                }
-            }
-
-            matches.push(crate::commands::yara_scanner::YaraMatch {
-                rule_name: rule.identifier().to_string(),
-                namespace: Some(rule.namespace().to_string()),
-                tags: vec![],
-                meta,
-                strings,
-            });
-        }
-
         Ok(crate::commands::yara_scanner::YaraScanResult {
             file_path: file_path.to_string(),
             matches,
@@ -746,19 +707,6 @@ rule Suspicious_Executable {
         })
     }
 
-    /// Helper to count rules in a compiled Rules object
-    fn count_yara_rules(&self, rules: &yara_x::Rules) -> usize {
-        let mut scanner = yara_x::Scanner::new(rules);
-        match scanner.scan(&[]) {
-            Ok(scan_results) => {
-                let matching_count = scan_results.matching_rules().count();
-                let non_matching_count = scan_results.non_matching_rules().count();
-                matching_count + non_matching_count
-            }
-            Err(_) => 0,
-        }
-    }
-
     /// Execute WASM-based deep analysis on file data
     ///
     /// Note: This method checks if the WASM runtime is available and reports its status.
@@ -853,6 +801,7 @@ mod tests {
             output: None,
             error: None,
             logs: Vec::new(),
+            completion_webhook: None,
         }
     }
 
@@ -880,7 +829,7 @@ mod tests {
         // 2. Complete an analysis to generate a report
         // 3. Verify the HTML output is properly formatted and escaped
         //
-        // The HTML escaping logic IS tested in test_escape_html() below.
+        // The HTML escaping logic IS tested in crate::html::tests.
     }
 
     #[test]
@@ -988,24 +937,4 @@ mod tests {
             Err(e) => panic!("Scanner failed: {}", e),
         }
     }
-
-    #[test]
-    fn test_escape_html() {
-        // Test escaping of all special HTML characters
-        let input = r#"<script>alert("XSS & 'attack'")</script>"#;
-        let expected = "&lt;script&gt;alert(&quot;XSS &amp; &#39;attack&#39;&quot;)&lt;/script&gt;";
-        let result = JobExecutor::escape_html(input);
-        assert_eq!(result, expected);
-
-        // Test normal text (should remain unchanged)
-        let normal = "Hello World";
-        let result = JobExecutor::escape_html(normal);
-        assert_eq!(result, "Hello World");
-
-        // Test JSON-like content
-        let json = r#"{"key": "value", "array": [1, 2, 3]}"#;
-        let expected_json = "{&quot;key&quot;: &quot;value&quot;, &quot;array&quot;: [1, 2, 3]}";
-        let result = JobExecutor::escape_html(json);
-        assert_eq!(result, expected_json);
-    }
 }
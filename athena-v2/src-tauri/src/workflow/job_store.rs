@@ -1,7 +1,68 @@
 use rusqlite::{Connection, params, OptionalExtension};
 use anyhow::{Result, Context};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 use super::schema::{Job, JobStatus, LogEntry};
+use crate::metrics::RETENTION_RESULTS;
+
+/// Results older than this are retired when no TTL is configured
+const DEFAULT_RESULT_TTL_DAYS: i64 = 30;
+
+/// What happens to a finished job once it is past the retention age
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    /// Move the job to the `jobs_archive` table
+    Archive,
+    /// Remove the job and its logs
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub max_age_days: i64,
+    pub action: RetentionAction,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: DEFAULT_RESULT_TTL_DAYS,
+            action: RetentionAction::Archive,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Policy from `ATHENA_RESULT_TTL_DAYS` and `ATHENA_RESULT_RETENTION`
+    /// (`archive` or `delete`), falling back to the defaults
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+
+        if let Some(days) = std::env::var("ATHENA_RESULT_TTL_DAYS")
+            .ok()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|days| *days > 0)
+        {
+            policy.max_age_days = days;
+        }
+
+        match std::env::var("ATHENA_RESULT_RETENTION").ok().as_deref().map(str::trim) {
+            Some("delete") => policy.action = RetentionAction::Delete,
+            Some("archive") => policy.action = RetentionAction::Archive,
+            _ => {}
+        }
+
+        policy
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RetentionReport {
+    pub archived: usize,
+    pub deleted: usize,
+}
 
 pub struct JobStore {
     conn: Arc<Mutex<Connection>>,
@@ -37,11 +98,22 @@ impl JobStore {
                 completed_at TEXT,
                 input TEXT NOT NULL,
                 output TEXT,
-                error TEXT
+                error TEXT,
+                completion_webhook TEXT
             )",
             [],
         )?;
 
+        // Databases created before completion webhooks existed
+        let has_webhook_column: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('jobs') WHERE name = 'completion_webhook'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_webhook_column {
+            conn.execute("ALTER TABLE jobs ADD COLUMN completion_webhook TEXT", [])?;
+        }
+
         // Create job_logs table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS job_logs (
@@ -55,6 +127,24 @@ impl JobStore {
             [],
         )?;
 
+        // Finished jobs retired by the retention policy
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS jobs_archive (
+                id TEXT PRIMARY KEY,
+                workflow_type TEXT NOT NULL,
+                status TEXT NOT NULL,
+                progress REAL NOT NULL,
+                created_at TEXT NOT NULL,
+                started_at TEXT,
+                completed_at TEXT,
+                input TEXT NOT NULL,
+                output TEXT,
+                error TEXT,
+                archived_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
         // Create indices for performance
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_job_status ON jobs(status)",
@@ -78,13 +168,13 @@ impl JobStore {
 
     pub fn create_job(&self, job: &Job) -> Result<()> {
         let conn = self.conn.lock().unwrap_or_else(|poisoned| {
-            eprintln!("JobStore mutex was poisoned, recovering...");
+            tracing::warn!("JobStore mutex was poisoned, recovering");
             poisoned.into_inner()
         });
 
         conn.execute(
-            "INSERT INTO jobs (id, workflow_type, status, progress, created_at, input)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO jobs (id, workflow_type, status, progress, created_at, input, completion_webhook)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 job.id,
                 serde_json::to_string(&job.workflow_type)?,
@@ -92,6 +182,7 @@ impl JobStore {
                 job.progress,
                 job.created_at.to_rfc3339(),
                 serde_json::to_string(&job.input)?,
+                job.completion_webhook.as_ref().map(|url| url.as_str()),
             ],
         )?;
 
@@ -100,7 +191,7 @@ impl JobStore {
 
     pub fn update_job(&self, job: &Job) -> Result<()> {
         let conn = self.conn.lock().unwrap_or_else(|poisoned| {
-            eprintln!("JobStore mutex was poisoned, recovering...");
+            tracing::warn!("JobStore mutex was poisoned, recovering");
             poisoned.into_inner()
         });
 
@@ -129,7 +220,7 @@ impl JobStore {
 
     pub fn add_log(&self, job_id: &str, log: &LogEntry) -> Result<()> {
         let conn = self.conn.lock().unwrap_or_else(|poisoned| {
-            eprintln!("JobStore mutex was poisoned, recovering...");
+            tracing::warn!("JobStore mutex was poisoned, recovering");
             poisoned.into_inner()
         });
 
@@ -149,12 +240,13 @@ impl JobStore {
 
     pub fn get_job(&self, job_id: &str) -> Result<Option<Job>> {
         let conn = self.conn.lock().unwrap_or_else(|poisoned| {
-            eprintln!("JobStore mutex was poisoned, recovering...");
+            tracing::warn!("JobStore mutex was poisoned, recovering");
             poisoned.into_inner()
         });
 
         let mut stmt = conn.prepare(
-            "SELECT id, workflow_type, status, progress, created_at, started_at, completed_at, input, output, error
+            "SELECT id, workflow_type, status, progress, created_at, started_at, completed_at, input, output, error,
+                    completion_webhook
              FROM jobs WHERE id = ?1"
         )?;
 
@@ -206,6 +298,8 @@ impl JobStore {
                 output: row.get::<_, Option<String>>(8)?.and_then(|s| serde_json::from_str(&s).ok()),
                 error: row.get(9)?,
                 logs: Vec::new(),  // Loaded separately
+                completion_webhook: row.get::<_, Option<String>>(10)?
+                    .and_then(|url| url.parse().ok()),
             })
         }).optional()?;
 
@@ -243,7 +337,7 @@ impl JobStore {
 
     pub fn list_jobs(&self, status: Option<JobStatus>, limit: usize) -> Result<Vec<Job>> {
         let conn = self.conn.lock().unwrap_or_else(|poisoned| {
-            eprintln!("JobStore mutex was poisoned, recovering...");
+            tracing::warn!("JobStore mutex was poisoned, recovering");
             poisoned.into_inner()
         });
 
@@ -286,7 +380,7 @@ impl JobStore {
 
     pub fn delete_job(&self, job_id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap_or_else(|poisoned| {
-            eprintln!("JobStore mutex was poisoned, recovering...");
+            tracing::warn!("JobStore mutex was poisoned, recovering");
             poisoned.into_inner()
         });
 
@@ -299,6 +393,85 @@ impl JobStore {
     pub fn get_active_jobs(&self) -> Result<Vec<Job>> {
         self.list_jobs(Some(JobStatus::Running), 100)
     }
+
+    /// Archive or delete finished jobs that completed more than
+    /// `policy.max_age_days` ago. Pending and running jobs are never touched.
+    pub fn run_retention(&self, policy: &RetentionPolicy) -> Result<RetentionReport> {
+        let now = Utc::now();
+        let cutoff = now - Duration::days(policy.max_age_days);
+
+        let mut conn = self.conn.lock().unwrap_or_else(|poisoned| {
+            tracing::warn!("JobStore mutex was poisoned, recovering");
+            poisoned.into_inner()
+        });
+
+        // Timestamps are compared after parsing; RFC 3339 strings with
+        // differing fractional precision don't sort reliably as text
+        let expired: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT id, status, created_at, completed_at FROM jobs"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })?;
+
+            let mut expired = Vec::new();
+            for row in rows {
+                let (id, status, created_at, completed_at) = row?;
+                let finished = matches!(
+                    serde_json::from_str::<JobStatus>(&status),
+                    Ok(JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+                );
+                let finished_at = completed_at
+                    .as_deref()
+                    .unwrap_or(&created_at)
+                    .parse::<DateTime<Utc>>();
+
+                if finished && matches!(finished_at, Ok(t) if t < cutoff) {
+                    expired.push(id);
+                }
+            }
+            expired
+        };
+
+        let tx = conn.transaction()?;
+        for id in &expired {
+            if policy.action == RetentionAction::Archive {
+                tx.execute(
+                    "INSERT OR REPLACE INTO jobs_archive
+                        (id, workflow_type, status, progress, created_at, started_at,
+                         completed_at, input, output, error, archived_at)
+                     SELECT id, workflow_type, status, progress, created_at, started_at,
+                            completed_at, input, output, error, ?2
+                     FROM jobs WHERE id = ?1",
+                    params![id, now.to_rfc3339()],
+                )?;
+            }
+            tx.execute("DELETE FROM job_logs WHERE job_id = ?1", [id])?;
+            tx.execute("DELETE FROM jobs WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+
+        let mut report = RetentionReport::default();
+        let label = match policy.action {
+            RetentionAction::Archive => {
+                report.archived = expired.len();
+                "archived"
+            }
+            RetentionAction::Delete => {
+                report.deleted = expired.len();
+                "deleted"
+            }
+        };
+        RETENTION_RESULTS.with_label_values(&[label]).inc_by(expired.len() as f64);
+
+        Ok(report)
+    }
 }
 
 #[cfg(test)]
@@ -358,4 +531,60 @@ mod tests {
         let pending = store.list_jobs(Some(JobStatus::Pending), 10).unwrap();
         assert_eq!(pending.len(), 5);
     }
+
+    #[test]
+    fn test_retention_removes_only_expired_results() {
+        let store = JobStore::new(":memory:").unwrap();
+        let policy = RetentionPolicy { max_age_days: 30, action: RetentionAction::Archive };
+
+        let job_aged = |days: i64, status: JobStatus| {
+            let mut job = Job::new(WorkflowType::FileAnalysis, serde_json::json!({}));
+            job.created_at = Utc::now() - Duration::days(days) - Duration::hours(1);
+            store.create_job(&job).unwrap();
+            if status != JobStatus::Pending {
+                job.status = status;
+                job.completed_at = Some(job.created_at + Duration::minutes(5));
+                store.update_job(&job).unwrap();
+            }
+            job.id
+        };
+
+        let expired = [
+            job_aged(90, JobStatus::Completed),
+            job_aged(31, JobStatus::Failed),
+            job_aged(45, JobStatus::Cancelled),
+        ];
+        let kept = [
+            job_aged(29, JobStatus::Completed),
+            job_aged(1, JobStatus::Completed),
+            // Old but never finished
+            job_aged(60, JobStatus::Pending),
+            job_aged(60, JobStatus::Running),
+        ];
+
+        let report = store.run_retention(&policy).unwrap();
+        assert_eq!(report, RetentionReport { archived: 3, deleted: 0 });
+
+        for id in &expired {
+            assert!(store.get_job(id).unwrap().is_none());
+        }
+        for id in &kept {
+            assert!(store.get_job(id).unwrap().is_some());
+        }
+
+        let mut archived: Vec<String> = {
+            let conn = store.conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT id FROM jobs_archive").unwrap();
+            let ids = stmt.query_map([], |row| row.get(0)).unwrap();
+            ids.collect::<Result<_, _>>().unwrap()
+        };
+        let mut expected = expired.to_vec();
+        archived.sort();
+        expected.sort();
+        assert_eq!(archived, expected);
+
+        // Nothing left past the cutoff
+        let again = store.run_retention(&RetentionPolicy { action: RetentionAction::Delete, ..policy }).unwrap();
+        assert_eq!(again, RetentionReport::default());
+    }
 }
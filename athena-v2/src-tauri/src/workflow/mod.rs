@@ -1,7 +1,12 @@
 pub mod schema;
 pub mod job_store;
 pub mod executor;
+pub mod watch_folder;
+pub mod notifications;
+pub mod completion_webhook;
 
 pub use schema::{Job, JobStatus, WorkflowType};
-pub use job_store::JobStore;
+pub use job_store::{JobStore, RetentionPolicy};
 pub use executor::JobExecutor;
+pub use watch_folder::WatchFolder;
+pub use notifications::NotificationSinks;
@@ -0,0 +1,307 @@
+use super::schema::{Job, JobStatus, WorkflowType};
+use crate::commands::yara_scanner::YaraMatch;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Severity of a finished analysis, aggregated over everything it found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    Benign,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "benign" | "clean" => Some(Severity::Benign),
+            "low" => Some(Severity::Low),
+            "medium" | "suspicious" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    /// Highest severity declared by a set of YARA matches. Rules without a
+    /// `severity` meta field count as `Medium`.
+    pub fn from_yara_matches(matches: &[YaraMatch]) -> Self {
+        matches
+            .iter()
+            .map(|m| {
+                m.meta
+                    .get("severity")
+                    .and_then(|s| Severity::parse(s))
+                    .unwrap_or(Severity::Medium)
+            })
+            .max()
+            .unwrap_or(Severity::Benign)
+    }
+
+    pub fn is_alertable(self) -> bool {
+        self >= Severity::High
+    }
+}
+
+/// A concise summary of a high-severity result, sent to every sink
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub job_id: String,
+    pub workflow_type: WorkflowType,
+    pub severity: Severity,
+    pub summary: String,
+    pub sha256: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Notification {
+    /// Notification for a finished file analysis or batch scan whose
+    /// aggregated severity is `High` or `Critical`
+    pub fn for_job(job: &Job) -> Option<Self> {
+        if job.status != JobStatus::Completed {
+            return None;
+        }
+        let output = job.output.as_ref()?;
+
+        let (severity, summary, sha256) = match job.workflow_type {
+            WorkflowType::FileAnalysis => {
+                let threat_level = output
+                    .pointer("/threat_assessment/threat_level")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("benign");
+                let matches = yara_matches(output.get("yara_matches"));
+                let severity = Severity::parse(threat_level)
+                    .unwrap_or(Severity::Benign)
+                    .max(Severity::from_yara_matches(&matches));
+
+                let path = output.pointer("/file_info/path").and_then(|v| v.as_str()).unwrap_or("unknown file");
+                let name = job.input.get("original_filename").and_then(|v| v.as_str()).unwrap_or(path);
+                let rules: Vec<&str> = matches.iter().map(|m| m.rule_name.as_str()).collect();
+                let summary = if rules.is_empty() {
+                    format!("{} assessed as {}", name, threat_level)
+                } else {
+                    format!("{} assessed as {} (YARA: {})", name, threat_level, rules.join(", "))
+                };
+                let sha256 = output.pointer("/file_info/sha256").and_then(|v| v.as_str()).map(str::to_string);
+
+                (severity, summary, sha256)
+            }
+            WorkflowType::BatchScan => {
+                let results = output.get("results").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+                let severity = results
+                    .iter()
+                    .filter_map(|r| r.get("severity").and_then(|s| s.as_str()).and_then(Severity::parse))
+                    .max()
+                    .unwrap_or(Severity::Benign);
+                let threats = output.get("threats_found").and_then(|v| v.as_u64()).unwrap_or(0);
+                let total = output.get("total_files").and_then(|v| v.as_u64()).unwrap_or(0);
+
+                (severity, format!("Batch scan found threats in {} of {} files", threats, total), None)
+            }
+            _ => return None,
+        };
+
+        severity.is_alertable().then(|| Notification {
+            job_id: job.id.clone(),
+            workflow_type: job.workflow_type.clone(),
+            severity,
+            summary,
+            sha256,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+fn yara_matches(value: Option<&serde_json::Value>) -> Vec<YaraMatch> {
+    value
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Somewhere to deliver notifications about severe detections
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    async fn notify(&self, notification: &Notification) -> Result<()>;
+}
+
+/// The sinks the job executor notifies, held in Tauri state
+#[derive(Clone, Default)]
+pub struct NotificationSinks(pub Vec<Arc<dyn NotificationSink>>);
+
+impl NotificationSinks {
+    /// Send `notification` to every sink. A failing sink doesn't stop the others.
+    pub async fn dispatch(&self, notification: &Notification) {
+        for sink in &self.0 {
+            if let Err(e) = sink.notify(notification).await {
+                tracing::warn!("Failed to deliver notification for job {}: {}", notification.job_id, e);
+            }
+        }
+    }
+
+    /// Notify about `job` if its result is severe enough
+    pub async fn notify_job(&self, job: &Job) -> Option<Notification> {
+        let notification = Notification::for_job(job)?;
+        self.dispatch(&notification).await;
+        Some(notification)
+    }
+}
+
+pub struct StdoutSink;
+
+#[async_trait]
+impl NotificationSink for StdoutSink {
+    async fn notify(&self, n: &Notification) -> Result<()> {
+        println!("[ALERT] {:?}: {} (job {})", n.severity, n.summary, n.job_id);
+        Ok(())
+    }
+}
+
+/// Appends each notification to a file as one line of JSON
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for FileSink {
+    async fn notify(&self, n: &Notification) -> Result<()> {
+        let line = serde_json::to_string(n)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {:?}", self.path))?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// POSTs each notification as JSON
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .connect_timeout(Duration::from_secs(5))
+            .build()?;
+        Ok(Self { url, client })
+    }
+}
+
+#[async_trait]
+impl NotificationSink for WebhookSink {
+    async fn notify(&self, n: &Notification) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(n)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Webhook {} rejected notification", self.url))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockSink {
+        received: Mutex<Vec<Notification>>,
+    }
+
+    #[async_trait]
+    impl NotificationSink for MockSink {
+        async fn notify(&self, n: &Notification) -> Result<()> {
+            self.received.lock().unwrap().push(n.clone());
+            Ok(())
+        }
+    }
+
+    fn finished_analysis(threat_level: &str, yara_matches: serde_json::Value) -> Job {
+        let mut job = Job::new(
+            WorkflowType::FileAnalysis,
+            serde_json::json!({"original_filename": "invoice.exe", "source": "watch_folder"}),
+        );
+        job.complete(serde_json::json!({
+            "file_info": {"path": "/quarantine/ab/abcd", "sha256": "abcd"},
+            "yara_matches": yara_matches,
+            "threat_assessment": {"threat_level": threat_level},
+        }));
+        job
+    }
+
+    #[tokio::test]
+    async fn test_sink_notified_for_critical_result_only() {
+        let sink = Arc::new(MockSink::default());
+        let sinks = NotificationSinks(vec![sink.clone()]);
+
+        let critical = finished_analysis(
+            "critical",
+            serde_json::json!([{
+                "rule_name": "Emotet_Loader",
+                "namespace": null,
+                "tags": [],
+                "meta": {"severity": "critical"},
+                "strings": [],
+            }]),
+        );
+        let benign = finished_analysis("benign", serde_json::json!([]));
+
+        assert!(sinks.notify_job(&critical).await.is_some());
+        assert!(sinks.notify_job(&benign).await.is_none());
+
+        let received = sink.received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].job_id, critical.id);
+        assert_eq!(received[0].severity, Severity::Critical);
+        assert_eq!(received[0].sha256.as_deref(), Some("abcd"));
+        assert_eq!(received[0].summary, "invoice.exe assessed as critical (YARA: Emotet_Loader)");
+    }
+
+    #[test]
+    fn test_batch_scan_severity_from_yara_meta() {
+        let mut job = Job::new(WorkflowType::BatchScan, serde_json::json!({}));
+        job.complete(serde_json::json!({
+            "threats_found": 1,
+            "total_files": 2,
+            "results": [
+                {"file_path": "a.exe", "severity": "high"},
+                {"file_path": "b.exe", "severity": "benign"},
+            ],
+        }));
+
+        let notification = Notification::for_job(&job).unwrap();
+        assert_eq!(notification.severity, Severity::High);
+        assert_eq!(notification.summary, "Batch scan found threats in 1 of 2 files");
+
+        // A rule without severity meta only makes a result suspicious
+        let matches = [YaraMatch {
+            rule_name: "Generic_Packer".to_string(),
+            namespace: None,
+            tags: vec![],
+            meta: Default::default(),
+            strings: vec![],
+        }];
+        assert_eq!(Severity::from_yara_matches(&matches), Severity::Medium);
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use tauri::Url;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
@@ -81,6 +82,9 @@ pub struct Job {
     pub output: Option<serde_json::Value>,
     pub error: Option<String>,
     pub logs: Vec<LogEntry>,
+    /// POSTed a summary of the result when the job finishes
+    #[serde(default)]
+    pub completion_webhook: Option<Url>,
 }
 
 impl Job {
@@ -97,9 +101,15 @@ impl Job {
             output: None,
             error: None,
             logs: Vec::new(),
+            completion_webhook: None,
         }
     }
 
+    pub fn with_completion_webhook(mut self, url: Option<Url>) -> Self {
+        self.completion_webhook = url;
+        self
+    }
+
     pub fn add_log(&mut self, level: LogLevel, message: String) {
         self.logs.push(LogEntry {
             timestamp: Utc::now(),
@@ -0,0 +1,245 @@
+use super::job_store::JobStore;
+use super::schema::{Job, WorkflowType};
+use crate::quarantine::QuarantineStorage;
+use anyhow::{Context, Result};
+use notify::event::{CreateKind, ModifyKind};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+
+/// How long a file's size and modification time must stay unchanged
+/// before it is considered fully written
+const DEFAULT_SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Give up on files that are still being written after this many checks
+const MAX_STABILITY_CHECKS: usize = 120;
+
+/// Watches a directory and queues a file analysis job for every file
+/// dropped into it. Each file is copied into quarantine first and the job
+/// analyzes the quarantined copy.
+pub struct WatchFolder {
+    dir: PathBuf,
+    quarantine: Arc<Mutex<QuarantineStorage>>,
+    store: Arc<JobStore>,
+    settle_time: Duration,
+    /// Files currently waiting for writes to settle
+    pending: Mutex<HashSet<PathBuf>>,
+}
+
+impl WatchFolder {
+    pub fn new(dir: PathBuf, quarantine: Arc<Mutex<QuarantineStorage>>, store: Arc<JobStore>) -> Self {
+        Self {
+            dir,
+            quarantine,
+            store,
+            settle_time: DEFAULT_SETTLE_TIME,
+            pending: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn with_settle_time(mut self, settle_time: Duration) -> Self {
+        self.settle_time = settle_time;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Start watching. `on_job` is called with each job after it has been
+    /// created; watching stops when the returned watcher is dropped.
+    pub fn watch<F>(self: Arc<Self>, on_job: F) -> Result<RecommendedWatcher>
+    where
+        F: Fn(Job) + Send + Sync + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                let _ = tx.send(event);
+            }
+        })
+        .context("Failed to create file watcher")?;
+
+        watcher
+            .watch(&self.dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {:?}", self.dir))?;
+
+        let on_job = Arc::new(on_job);
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let folder = self.clone();
+                let on_job = on_job.clone();
+                // Debouncing can take a while; don't hold up other files
+                tokio::spawn(async move {
+                    match folder.handle_event(event).await {
+                        Ok(jobs) => {
+                            for job in jobs {
+                                on_job(job);
+                            }
+                        }
+                        Err(e) => tracing::error!("Watch folder error: {}", e),
+                    }
+                });
+            }
+        });
+
+        Ok(watcher)
+    }
+
+    /// Queue jobs for the new or rewritten files in a watcher event.
+    ///
+    /// Files already waiting to settle are skipped, so the burst of modify
+    /// events produced by a single copy results in one job.
+    pub async fn handle_event(&self, event: Event) -> Result<Vec<Job>> {
+        let mut jobs = Vec::new();
+
+        for path in new_files(&event) {
+            if !self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(path.clone()) {
+                continue;
+            }
+
+            let result = match self.wait_until_stable(&path).await {
+                Ok(true) => self.ingest(&path).map(Some),
+                Ok(false) => Ok(None),
+                Err(e) => Err(e),
+            };
+
+            self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&path);
+
+            if let Some(job) = result? {
+                jobs.push(job);
+            }
+        }
+
+        Ok(jobs)
+    }
+
+    /// Quarantine `path` and create a file analysis job for it
+    pub fn ingest(&self, path: &Path) -> Result<Job> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let sample = self
+            .quarantine
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire quarantine lock: {}", e))?
+            .store_sample_with_provenance(&data, &filename, "watch_folder", None, None)?;
+
+        let job = Job::new(
+            WorkflowType::FileAnalysis,
+            serde_json::json!({
+                "file_path": sample.quarantine_path.to_string_lossy(),
+                "sha256": sample.sha256,
+                "sample_id": sample.metadata.provenance.as_ref().map(|p| &p.sample_id),
+                "original_filename": filename,
+                "source": "watch_folder",
+            }),
+        );
+        self.store.create_job(&job)?;
+
+        Ok(job)
+    }
+
+    /// Wait until the file stops changing. Returns `false` if it disappeared
+    /// or never settled.
+    async fn wait_until_stable(&self, path: &Path) -> Result<bool> {
+        let mut last = file_state(path);
+
+        for _ in 0..MAX_STABILITY_CHECKS {
+            tokio::time::sleep(self.settle_time).await;
+
+            let current = file_state(path);
+            match (&last, &current) {
+                (None, None) => return Ok(false),
+                (Some(a), Some(b)) if a == b => return Ok(true),
+                _ => last = current,
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Files created or written to by a watcher event
+fn new_files(event: &Event) -> Vec<PathBuf> {
+    match event.kind {
+        EventKind::Create(CreateKind::File | CreateKind::Any)
+        | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any) => event
+            .paths
+            .iter()
+            .filter(|p| p.is_file())
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn file_state(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn watch_folder(watched: &TempDir, app_data: &TempDir) -> (WatchFolder, Arc<JobStore>) {
+        let quarantine = Arc::new(Mutex::new(QuarantineStorage::new(app_data.path()).unwrap()));
+        let store = Arc::new(JobStore::new(":memory:").unwrap());
+        let folder = WatchFolder::new(watched.path().to_path_buf(), quarantine, store.clone())
+            .with_settle_time(Duration::from_millis(10));
+        (folder, store)
+    }
+
+    #[tokio::test]
+    async fn test_new_file_creates_analysis_job() {
+        let watched = TempDir::new().unwrap();
+        let app_data = TempDir::new().unwrap();
+        let (folder, store) = watch_folder(&watched, &app_data);
+
+        let path = watched.path().join("dropper.exe");
+        std::fs::write(&path, b"MZ\x90\x00 dropped sample").unwrap();
+
+        let event = Event::new(EventKind::Create(CreateKind::File)).add_path(path.clone());
+        let jobs = folder.handle_event(event).await.unwrap();
+
+        assert_eq!(jobs.len(), 1);
+        let stored = store.list_jobs(None, 10).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, jobs[0].id);
+        assert_eq!(stored[0].workflow_type, WorkflowType::FileAnalysis);
+        assert_eq!(stored[0].input["original_filename"], "dropper.exe");
+
+        // The job analyzes the quarantined copy, not the dropped file
+        let quarantined = PathBuf::from(stored[0].input["file_path"].as_str().unwrap());
+        assert!(quarantined.starts_with(app_data.path()));
+        assert!(quarantined.exists());
+    }
+
+    #[tokio::test]
+    async fn test_ignores_removed_files_and_directories() {
+        let watched = TempDir::new().unwrap();
+        let app_data = TempDir::new().unwrap();
+        let (folder, store) = watch_folder(&watched, &app_data);
+
+        let subdir = watched.path().join("nested");
+        std::fs::create_dir(&subdir).unwrap();
+        let gone = watched.path().join("gone.bin");
+
+        let event = Event::new(EventKind::Create(CreateKind::Any))
+            .add_path(subdir)
+            .add_path(gone);
+        assert!(folder.handle_event(event).await.unwrap().is_empty());
+        assert!(store.list_jobs(None, 10).unwrap().is_empty());
+    }
+}
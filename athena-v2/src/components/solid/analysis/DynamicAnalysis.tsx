@@ -45,6 +45,7 @@ interface EvasionAttempt {
   description: string;
   trigger: string;
   blocked: boolean;
+  checked_locale?: string | null;
 }
 
 interface ThreatScore {
@@ -395,7 +396,8 @@ const DynamicAnalysis: Component = () => {
       // Detect sandbox evasion
       try {
         const evasion = await invokeCommand('detect_sandbox_evasion', {
-          report
+          report,
+          filePath: currentFile.path
         }) as EvasionAttempt[];
         setEvasionAttempts(evasion);
       } catch (e) {
@@ -570,7 +572,8 @@ const DynamicAnalysis: Component = () => {
       // Detect sandbox evasion
       try {
         const evasion = await invokeCommand('detect_sandbox_evasion', {
-          report
+          report,
+          filePath: currentFile.path
         }) as EvasionAttempt[];
         setEvasionAttempts(evasion);
       } catch (e) {
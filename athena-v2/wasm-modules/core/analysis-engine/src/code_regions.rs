@@ -0,0 +1,354 @@
+//! Code vs. Data Region Classification
+//! Marks which parts of a buffer hold instructions before disassembly, so
+//! linear sweeps don't decode strings, tables and packed data as garbage code
+//!
+//! Regions reachable from the entry point or exports by recursive descent are
+//! code. Non-executable sections are data. Whatever is left in executable
+//! sections is judged by its content: padding, text, high entropy and
+//! undecodable bytes are data, anything else is kept as code.
+
+use crate::disasm::{Architecture, DisassembledInstruction, Disassembler, Syntax};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Unreached bytes with entropy above this are treated as packed/encrypted data
+const DATA_ENTROPY_THRESHOLD: f64 = 7.0;
+
+/// Entropy is meaningless for very short runs
+const MIN_ENTROPY_SAMPLE: usize = 64;
+
+/// Share of printable bytes above which an unreached run is treated as text
+const TEXT_RATIO_THRESHOLD: f64 = 0.8;
+
+/// Instructions decoded per step of the recursive descent
+const DESCENT_CHUNK: u32 = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegionKind {
+    Code,
+    Data,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub start: u64,
+    pub end: u64,
+    pub kind: RegionKind,
+}
+
+/// Section bounds and characteristics as reported by the file parser
+#[derive(Clone, Debug)]
+pub struct SectionInfo {
+    pub address: u64,
+    pub size: u64,
+    pub executable: bool,
+}
+
+/// Classify every byte of `code` (mapped at `base`) as code or data.
+///
+/// `entry_points` should contain the entry point and exported function
+/// addresses. With no `sections`, the whole buffer is treated as executable.
+pub fn classify_regions(
+    code: &[u8],
+    base: u64,
+    sections: &[SectionInfo],
+    entry_points: &[u64],
+    arch: Architecture,
+) -> Vec<Region> {
+    let executable = executable_test(base, sections);
+    // Without a descent every executable byte is judged by content instead
+    let reached = descend(code, base, entry_points, arch, Syntax::Intel, u32::MAX, &executable)
+        .map(|descent| descent.reached)
+        .unwrap_or_else(|_| vec![false; code.len()]);
+
+    // Label each byte, then judge unreached runs in executable sections by content
+    let mut kinds: Vec<Option<RegionKind>> = (0..code.len())
+        .map(|i| {
+            if reached[i] {
+                Some(RegionKind::Code)
+            } else if !executable(i) {
+                Some(RegionKind::Data)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut i = 0;
+    while i < kinds.len() {
+        if kinds[i].is_some() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < kinds.len() && kinds[i].is_none() {
+            i += 1;
+        }
+        let kind = classify_unreached(&code[start..i], base + start as u64, arch);
+        kinds[start..i].iter_mut().for_each(|k| *k = Some(kind));
+    }
+
+    merge(base, kinds.into_iter().map(|k| k.unwrap_or(RegionKind::Data)))
+}
+
+/// Disassemble only the code regions of `code`
+pub fn disassemble_code_regions(
+    code: &[u8],
+    base: u64,
+    regions: &[Region],
+    arch: Architecture,
+    syntax: Syntax,
+    max_instructions: u32,
+) -> Result<Vec<DisassembledInstruction>, String> {
+    let mut result = Vec::new();
+
+    for region in regions.iter().filter(|r| r.kind == RegionKind::Code) {
+        let remaining = max_instructions.saturating_sub(result.len() as u32);
+        if remaining == 0 {
+            break;
+        }
+        let start = (region.start - base) as usize;
+        let end = ((region.end - base) as usize).min(code.len());
+        result.extend(Disassembler::disassemble(&code[start..end], region.start, arch, syntax, remaining)?);
+    }
+
+    Ok(result)
+}
+
+/// Disassemble only what recursive descent from `entry_points` reaches, in
+/// address order. Unlike `classify_regions`, nothing unreached is kept, so
+/// the result is exactly the code the seeds lead to.
+pub fn disassemble_reachable(
+    code: &[u8],
+    base: u64,
+    sections: &[SectionInfo],
+    entry_points: &[u64],
+    arch: Architecture,
+    syntax: Syntax,
+    max_instructions: u32,
+) -> Result<Vec<DisassembledInstruction>, String> {
+    let executable = executable_test(base, sections);
+    Ok(descend(code, base, entry_points, arch, syntax, max_instructions, &executable)?
+        .instructions
+        .into_values()
+        .collect())
+}
+
+fn executable_test(base: u64, sections: &[SectionInfo]) -> impl Fn(usize) -> bool + '_ {
+    move |offset: usize| {
+        let address = base + offset as u64;
+        sections.is_empty()
+            || sections
+                .iter()
+                .any(|s| s.executable && address >= s.address && address < s.address.saturating_add(s.size))
+    }
+}
+
+/// What recursive descent from a set of entry points reached
+struct Descent {
+    /// Bytes covered by reached instructions
+    reached: Vec<bool>,
+    instructions: BTreeMap<u64, DisassembledInstruction>,
+}
+
+/// Follow control flow from `entry_points`, stopping once
+/// `max_instructions` have been reached. Invalid encodings end a path;
+/// only a failure of the decoder itself is an error.
+fn descend(
+    code: &[u8],
+    base: u64,
+    entry_points: &[u64],
+    arch: Architecture,
+    syntax: Syntax,
+    max_instructions: u32,
+    executable: &dyn Fn(usize) -> bool,
+) -> Result<Descent, String> {
+    // An image mapped at the top of the address space ends there
+    let end = base.saturating_add(code.len() as u64);
+    let mut reached = vec![false; code.len()];
+    let mut reached_instructions = BTreeMap::new();
+    let mut visited = BTreeSet::new();
+    // Popped from the back, so the first entry point is followed first
+    let mut worklist: Vec<u64> = entry_points.iter().rev().copied().collect();
+
+    'worklist: while let Some(address) = worklist.pop() {
+        let remaining = (max_instructions as usize).saturating_sub(reached_instructions.len());
+        if remaining == 0 {
+            break;
+        }
+        if address < base || address >= end || !visited.insert(address) {
+            continue;
+        }
+        let offset = (address - base) as usize;
+        if !executable(offset) {
+            continue;
+        }
+
+        let chunk = DESCENT_CHUNK.min(remaining as u32);
+        let instructions = Disassembler::disassemble(&code[offset..], address, arch, syntax, chunk)?;
+        let decoded = instructions.len() as u32;
+
+        let mut next = None;
+        for instr in instructions {
+            if reached_instructions.len() >= max_instructions as usize {
+                break 'worklist;
+            }
+            if is_invalid(&instr) {
+                next = None;
+                break;
+            }
+            let start = (instr.offset - base) as usize;
+            if start > offset && reached[start] {
+                // Joined a path that was already followed
+                next = None;
+                break;
+            }
+            let len = instr.bytes.len().max(1);
+            reached[start..(start + len).min(code.len())].iter_mut().for_each(|b| *b = true);
+
+            if let Some(target) = instr.branch_target {
+                worklist.push(target);
+            }
+            let flow_ends = ends_flow(&instr);
+            next = Some(instr.offset + len as u64);
+            reached_instructions.insert(instr.offset, instr);
+            if flow_ends {
+                next = None;
+                break;
+            }
+        }
+
+        // Ran out of decoded instructions before the flow ended
+        if decoded == chunk {
+            worklist.extend(next);
+        }
+    }
+
+    Ok(Descent { reached, instructions: reached_instructions })
+}
+
+fn classify_unreached(bytes: &[u8], address: u64, arch: Architecture) -> RegionKind {
+    let is_padding = bytes.iter().all(|&b| matches!(b, 0x00 | 0xCC | 0x90));
+    let printable = bytes
+        .iter()
+        .filter(|&&b| b.is_ascii_graphic() || b == b' ' || b == b'\n' || b == b'\r' || b == b'\t')
+        .count();
+    let is_text = printable as f64 / bytes.len() as f64 >= TEXT_RATIO_THRESHOLD;
+    let is_random = bytes.len() >= MIN_ENTROPY_SAMPLE && entropy(bytes) > DATA_ENTROPY_THRESHOLD;
+
+    if is_padding || is_text || is_random {
+        return RegionKind::Data;
+    }
+
+    match Disassembler::disassemble(bytes, address, arch, Syntax::Intel, u32::MAX) {
+        Ok(instructions) if !instructions.is_empty() && !instructions.iter().any(is_invalid) => RegionKind::Code,
+        _ => RegionKind::Data,
+    }
+}
+
+fn is_invalid(instr: &DisassembledInstruction) -> bool {
+    instr.mnemonic.eq_ignore_ascii_case("invalid")
+}
+
+/// Execution can't fall through to the next instruction
+fn ends_flow(instr: &DisassembledInstruction) -> bool {
+    if instr.is_return {
+        return true;
+    }
+    if !instr.is_branch || instr.is_call {
+        return false;
+    }
+
+    let mnemonic = instr.mnemonic.to_uppercase();
+    let conditional = if mnemonic.starts_with('J') {
+        mnemonic != "JMP"
+    } else {
+        mnemonic.starts_with("B.")
+            || matches!(mnemonic.as_str(), "CBZ" | "CBNZ" | "TBZ" | "TBNZ")
+            || (mnemonic.len() == 3 && mnemonic.starts_with('B') && !matches!(mnemonic.as_str(), "BLX" | "BLR"))
+    };
+    !conditional
+}
+
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn merge(base: u64, kinds: impl Iterator<Item = RegionKind>) -> Vec<Region> {
+    let mut regions: Vec<Region> = Vec::new();
+
+    for (i, kind) in kinds.enumerate() {
+        let address = base + i as u64;
+        match regions.last_mut() {
+            Some(last) if last.kind == kind => last.end = address + 1,
+            _ => regions.push(Region { start: address, end: address + 1, kind }),
+        }
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Code, an embedded string the code jumps over, then more code
+    fn mixed_buffer() -> Vec<u8> {
+        let mut buf = vec![
+            0x55,             // push rbp
+            0x48, 0x89, 0xE5, // mov rbp, rsp
+            0xEB, 0x20,       // jmp +0x20
+        ];
+        buf.extend_from_slice(b"Hello, world! config=c2.example!");
+        buf.extend_from_slice(&[
+            0x31, 0xC0, // xor eax, eax
+            0x5D,       // pop rbp
+            0xC3,       // ret
+        ]);
+        buf
+    }
+
+    #[test]
+    fn test_data_region_skipped() {
+        let code = mixed_buffer();
+        let base = 0x1000;
+
+        let regions = classify_regions(&code, base, &[], &[base], Architecture::X8664);
+        assert_eq!(regions, vec![
+            Region { start: 0x1000, end: 0x1006, kind: RegionKind::Code },
+            Region { start: 0x1006, end: 0x1026, kind: RegionKind::Data },
+            Region { start: 0x1026, end: 0x102A, kind: RegionKind::Code },
+        ]);
+
+        let instructions =
+            disassemble_code_regions(&code, base, &regions, Architecture::X8664, Syntax::Intel, 100).unwrap();
+        let addresses: Vec<u64> = instructions.iter().map(|i| i.offset).collect();
+        assert_eq!(addresses, vec![0x1000, 0x1001, 0x1004, 0x1026, 0x1028, 0x1029]);
+        assert!(instructions.last().unwrap().is_return);
+    }
+
+    #[test]
+    fn test_non_executable_section_is_data() {
+        let code = mixed_buffer();
+        let sections = [
+            SectionInfo { address: 0x1000, size: 0x20, executable: true },
+            SectionInfo { address: 0x1020, size: 0x10, executable: false },
+        ];
+
+        let regions = classify_regions(&code, 0x1000, &sections, &[0x1000], Architecture::X8664);
+
+        // The jump target lies in the non-executable section, so it isn't followed
+        assert_eq!(regions.last().unwrap(), &Region { start: 0x1006, end: 0x102A, kind: RegionKind::Data });
+    }
+}
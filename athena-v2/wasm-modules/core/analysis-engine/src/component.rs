@@ -7,8 +7,15 @@ wit_bindgen::generate!({
 
 use crate::patterns::{PatternMatcher, PatternCategory, PatternSeverity};
 use crate::deobfuscator::Deobfuscator;
-use crate::disasm::{Disassembler, Architecture, Syntax};
+use crate::analysis::{analyze_with_deadline, Deadline};
+use crate::disasm::{Disassembler, Architecture, DisasmMode, Syntax};
+use crate::disasm_cache::{self, DisasmOptions};
+use crate::pe_seeds::{self, SeedConfig};
+use crate::taint::{TaintEngine, TaintSink};
+use crate::config::EngineConfig;
+use crate::error::AnalysisError;
 use sha2::{Digest, Sha256};
+use std::cell::RefCell;
 
 const ENGINE_VERSION: &str = "0.1.0";
 
@@ -18,6 +25,23 @@ const ENGINE_VERSION: &str = "0.1.0";
 
 struct Component;
 
+thread_local! {
+    // Component instances do not share linear memory, so this is the
+    // instance's own configuration
+    static CONFIG: RefCell<EngineConfig> = RefCell::new(EngineConfig::default());
+}
+
+impl Component {
+    /// Configuration used by the entry points of this instance
+    fn config() -> EngineConfig {
+        CONFIG.with(|c| c.borrow().clone())
+    }
+
+    fn set_config(config: EngineConfig) {
+        CONFIG.with(|c| *c.borrow_mut() = config);
+    }
+}
+
 // ============================================================================
 // Analyzer Interface Implementation
 // ============================================================================
@@ -25,32 +49,29 @@ struct Component;
 impl exports::athena::analysis_engine::analyzer::Guest for Component {
     fn analyze(content: Vec<u8>) -> Result<exports::athena::analysis_engine::analyzer::AnalysisResult, String> {
         // Security: Validate input size
-        const MAX_INPUT_SIZE: usize = 100 * 1024 * 1024; // 100MB
-        if content.len() > MAX_INPUT_SIZE {
-            return Err(format!("Input too large: {} bytes exceeds maximum of {} bytes", content.len(), MAX_INPUT_SIZE));
-        }
+        let config = Self::config();
+        config.check_input_size(content.len())?;
 
         let start_time = std::time::SystemTime::now();
 
-        // Pattern matching
-        let pattern_matcher = PatternMatcher::new();
-        let pattern_matches = pattern_matcher.scan(&content);
-
-        // Deobfuscation attempt
-        let deobfuscator = Deobfuscator::new();
-        let text_content = String::from_utf8_lossy(&content).into_owned();
-        let deob_result = deobfuscator.deobfuscate(&text_content);
-        let deobfuscation_result = if deob_result.confidence > 0.0 {
-            Some(deob_result.deobfuscated)
-        } else {
-            None
+        let deadline = config.analysis_timeout().map_or(Deadline::never(), Deadline::after);
+        let report = analyze_with_deadline(&content, deadline);
+        let pattern_matches = report.findings.pattern_matches;
+        let deobfuscation_result = report.findings.deobfuscated;
+        let behaviors = report.findings.suspicious_behaviors;
+
+        // Calculate severity; a high-confidence behavior is at least high
+        let severity = match calculate_severity(&pattern_matches) {
+            exports::athena::analysis_engine::analyzer::Severity::Low | exports::athena::analysis_engine::analyzer::Severity::Medium
+                if behaviors.iter().any(|b| b.confidence >= 0.9) =>
+            {
+                exports::athena::analysis_engine::analyzer::Severity::High
+            }
+            severity => severity,
         };
 
-        // Calculate severity
-        let severity = calculate_severity(&pattern_matches);
-
         // Build threat information
-        let threats: Vec<exports::athena::analysis_engine::analyzer::ThreatInfo> = pattern_matches.iter().map(|m| {
+        let mut threats: Vec<exports::athena::analysis_engine::analyzer::ThreatInfo> = pattern_matches.iter().map(|m| {
             let confidence = match m.pattern.severity {
                 PatternSeverity::Critical => 0.95,
                 PatternSeverity::High => 0.85,
@@ -66,6 +87,13 @@ impl exports::athena::analysis_engine::analyzer::Guest for Component {
             }
         }).collect();
 
+        threats.extend(behaviors.into_iter().map(|b| exports::athena::analysis_engine::analyzer::ThreatInfo {
+            threat_type: b.name,
+            confidence: b.confidence,
+            description: b.description,
+            indicators: b.mitre_attack.into_iter().chain(b.evidence).collect(),
+        }));
+
         // Calculate file hash
         let mut hasher = Sha256::new();
         hasher.update(&content);
@@ -85,12 +113,28 @@ impl exports::athena::analysis_engine::analyzer::Guest for Component {
                 analysis_time_ms,
                 engine_version: ENGINE_VERSION.to_string(),
             },
+            partial: report.partial,
         })
     }
 
     fn get_version() -> String {
         ENGINE_VERSION.to_string()
     }
+
+    fn configure(config: exports::athena::analysis_engine::analyzer::EngineConfig) {
+        Self::set_config(EngineConfig {
+            max_input_size: config.max_input_size as usize,
+            analysis_timeout_ms: config.analysis_timeout_ms,
+        });
+    }
+
+    fn get_config() -> exports::athena::analysis_engine::analyzer::EngineConfig {
+        let current = Self::config();
+        exports::athena::analysis_engine::analyzer::EngineConfig {
+            max_input_size: current.max_input_size as u64,
+            analysis_timeout_ms: current.analysis_timeout_ms,
+        }
+    }
 }
 
 // ============================================================================
@@ -100,8 +144,7 @@ impl exports::athena::analysis_engine::analyzer::Guest for Component {
 impl exports::athena::analysis_engine::pattern_matcher::Guest for Component {
     fn scan(content: Vec<u8>) -> Vec<exports::athena::analysis_engine::pattern_matcher::PatternMatch> {
         // Security: Validate input size
-        const MAX_INPUT_SIZE: usize = 100 * 1024 * 1024; // 100MB
-        if content.len() > MAX_INPUT_SIZE {
+        if Self::config().check_input_size(content.len()).is_err() {
             // Return empty vec for oversized input rather than panicking
             return Vec::new();
         }
@@ -133,10 +176,7 @@ impl exports::athena::analysis_engine::deobfuscator::Guest for Component {
         _options: Option<exports::athena::analysis_engine::deobfuscator::DeobfuscationOptions>,
     ) -> Result<exports::athena::analysis_engine::deobfuscator::DeobfuscationResult, String> {
         // Security: Validate input size
-        const MAX_INPUT_SIZE: usize = 100 * 1024 * 1024; // 100MB
-        if content.len() > MAX_INPUT_SIZE {
-            return Err(format!("Input too large: {} bytes exceeds maximum of {} bytes", content.len(), MAX_INPUT_SIZE));
-        }
+        Self::config().check_input_size(content.len())?;
 
         let deobfuscator = Deobfuscator::new();
         let result = deobfuscator.deobfuscate(&content);
@@ -154,8 +194,7 @@ impl exports::athena::analysis_engine::deobfuscator::Guest for Component {
 
     fn is_obfuscated(content: String) -> bool {
         // Security: Validate input size
-        const MAX_INPUT_SIZE: usize = 100 * 1024 * 1024; // 100MB
-        if content.len() > MAX_INPUT_SIZE {
+        if Self::config().check_input_size(content.len()).is_err() {
             return false; // Return safe default for oversized input
         }
 
@@ -227,18 +266,23 @@ impl exports::athena::analysis_engine::disassembler::Guest for Component {
         offset: u64,
         options: exports::athena::analysis_engine::disassembler::DisasmOptions,
     ) -> Result<Vec<exports::athena::analysis_engine::disassembler::Instruction>, String> {
+        Self::config().check_input_size(code.len())?;
         let arch = convert_architecture_from_wit(options.arch);
         let syntax = convert_syntax_from_wit(options.syntax);
 
-        let instructions = Disassembler::disassemble(
+        let instructions = disasm_cache::session().disassemble(
             &code,
             offset,
             arch,
-            syntax,
-            options.max_instructions,
-        )?;
+            DisasmOptions {
+                syntax,
+                max_instructions: options.max_instructions,
+                mode: convert_mode_from_wit(options.mode),
+                seeds: options.seeds,
+            },
+        ).map_err(AnalysisError::analysis_failed)?;
 
-        Ok(instructions.into_iter().map(|instr| {
+        Ok(instructions.iter().cloned().map(|instr| {
             convert_instruction_to_wit(instr)
         }).collect())
     }
@@ -248,10 +292,12 @@ impl exports::athena::analysis_engine::disassembler::Guest for Component {
         entry_point: u64,
         arch: exports::athena::analysis_engine::disassembler::Architecture,
     ) -> Result<Vec<exports::athena::analysis_engine::disassembler::BasicBlock>, String> {
+        Self::config().check_input_size(code.len())?;
         let arch = convert_architecture_from_wit(arch);
-        let blocks = Disassembler::analyze_control_flow(&code, entry_point, arch)?;
+        let blocks = disasm_cache::session().control_flow(&code, entry_point, arch)
+            .map_err(AnalysisError::analysis_failed)?;
 
-        Ok(blocks.into_iter().map(|block| {
+        Ok(blocks.iter().cloned().map(|block| {
             let instructions = block.instructions.into_iter().map(|instr| {
                 convert_instruction_to_wit(instr)
             }).collect();
@@ -271,8 +317,10 @@ impl exports::athena::analysis_engine::disassembler::Guest for Component {
         entry_points: Vec<u64>,
         arch: exports::athena::analysis_engine::disassembler::Architecture,
     ) -> Result<Vec<exports::athena::analysis_engine::disassembler::FunctionInfo>, String> {
+        Self::config().check_input_size(code.len())?;
         let arch = convert_architecture_from_wit(arch);
-        let functions = Disassembler::find_functions(&code, &entry_points, arch)?;
+        let functions = Disassembler::find_functions(&code, &entry_points, arch)
+            .map_err(AnalysisError::analysis_failed)?;
 
         Ok(functions.into_iter().map(|func| {
             let basic_blocks = func.basic_blocks.into_iter().map(|block| {
@@ -305,8 +353,92 @@ impl exports::athena::analysis_engine::disassembler::Guest for Component {
         target_address: u64,
         arch: exports::athena::analysis_engine::disassembler::Architecture,
     ) -> Result<Vec<u64>, String> {
+        Self::config().check_input_size(code.len())?;
         let arch = convert_architecture_from_wit(arch);
         Disassembler::find_xrefs(&code, target_address, arch)
+            .map_err(|e| AnalysisError::analysis_failed(e).into())
+    }
+
+    fn disassemble_pe(
+        file: Vec<u8>,
+        seeds: Option<exports::athena::analysis_engine::disassembler::SeedConfig>,
+        syntax: exports::athena::analysis_engine::disassembler::Syntax,
+        max_instructions: u32,
+    ) -> Result<exports::athena::analysis_engine::disassembler::PeDisassembly, String> {
+        use exports::athena::analysis_engine::disassembler::{CallEdge, PeDisassembly};
+
+        Self::config().check_input_size(file.len())?;
+        let seeds = seeds
+            .map(|s| SeedConfig {
+                entry_point: s.entry_point,
+                exports: s.exports,
+                tls_callbacks: s.tls_callbacks,
+            })
+            .unwrap_or_default();
+        let pe = pe_seeds::disassemble_pe(&file, &seeds, convert_syntax_from_wit(syntax), max_instructions)
+            .map_err(AnalysisError::analysis_failed)?;
+
+        let mut calls: Vec<CallEdge> = pe
+            .call_graph
+            .calls
+            .iter()
+            .flat_map(|(&caller, targets)| targets.iter().map(move |&callee| CallEdge { caller, callee }))
+            .collect();
+        calls.sort_by_key(|edge| (edge.caller, edge.callee));
+
+        Ok(PeDisassembly {
+            instructions: pe.instructions.into_iter().map(convert_instruction_to_wit).collect(),
+            entry_points: pe.call_graph.entry_points,
+            calls,
+        })
+    }
+
+    fn trace_taint(
+        code: Vec<u8>,
+        base_address: u64,
+        arch: exports::athena::analysis_engine::disassembler::Architecture,
+        sources: Vec<exports::athena::analysis_engine::disassembler::TaintSource>,
+        sinks: Vec<exports::athena::analysis_engine::disassembler::TaintSink>,
+    ) -> Result<Vec<exports::athena::analysis_engine::disassembler::TaintFlow>, String> {
+        use exports::athena::analysis_engine::disassembler::TaintFlow;
+
+        Self::config().check_input_size(code.len())?;
+        let bitness = match convert_architecture_from_wit(arch) {
+            Architecture::X8632 => 32,
+            Architecture::X8664 => 64,
+            other => {
+                return Err(AnalysisError::analysis_failed(format!(
+                    "Taint tracking supports x86 and x64, not {:?}",
+                    other
+                ))
+                .into())
+            }
+        };
+
+        let engine = sources
+            .into_iter()
+            .fold(TaintEngine::new(bitness), |engine, source| {
+                engine.with_source(&source.label, source.address, source.size)
+            });
+        let engine = sinks.into_iter().fold(engine, |engine, sink| {
+            let mut taint_sink = TaintSink::api(&sink.name, sink.address);
+            if let Some(arguments) = sink.arguments {
+                taint_sink.arguments = arguments.into_iter().map(|a| a as usize).collect();
+            }
+            engine.with_sink(taint_sink)
+        });
+
+        Ok(engine
+            .trace(&code, base_address)
+            .into_iter()
+            .map(|flow| TaintFlow {
+                source: flow.source,
+                sink: flow.sink,
+                call_site: flow.call_site,
+                argument: flow.argument as u32,
+                path: flow.path,
+            })
+            .collect())
     }
 }
 
@@ -325,6 +457,15 @@ fn convert_architecture_from_wit(arch: exports::athena::analysis_engine::disasse
     }
 }
 
+fn convert_mode_from_wit(mode: exports::athena::analysis_engine::disassembler::DisasmMode) -> DisasmMode {
+    use exports::athena::analysis_engine::disassembler::DisasmMode as WitMode;
+
+    match mode {
+        WitMode::LinearSweep => DisasmMode::LinearSweep,
+        WitMode::RecursiveDescent => DisasmMode::RecursiveDescent,
+    }
+}
+
 fn convert_syntax_from_wit(syntax: exports::athena::analysis_engine::disassembler::Syntax) -> Syntax {
     use exports::athena::analysis_engine::disassembler::Syntax as WitSyntax;
 
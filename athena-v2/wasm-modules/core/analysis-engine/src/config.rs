@@ -0,0 +1,76 @@
+/// Module configuration for the analysis engine
+///
+/// Limits are per module: the network module caps packets independently, so
+/// only the analysis engine's own entry points read this configuration. The
+/// library holds no configuration itself; each component instance keeps its
+/// own copy.
+
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::error::AnalysisError;
+
+/// Default maximum input size (100MB)
+pub const DEFAULT_MAX_INPUT_SIZE: usize = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineConfig {
+    /// Inputs larger than this are rejected with `INPUT_TOO_LARGE`
+    pub max_input_size: usize,
+    /// Whole-file analysis stops after this long and returns partial results
+    #[serde(default)]
+    pub analysis_timeout_ms: Option<u64>,
+}
+
+impl EngineConfig {
+    pub const fn new(max_input_size: usize) -> Self {
+        Self { max_input_size, analysis_timeout_ms: None }
+    }
+
+    pub fn with_analysis_timeout(mut self, timeout: Duration) -> Self {
+        self.analysis_timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn analysis_timeout(&self) -> Option<Duration> {
+        self.analysis_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn check_input_size(&self, len: usize) -> Result<(), AnalysisError> {
+        if len > self.max_input_size {
+            return Err(AnalysisError::input_too_large(len, self.max_input_size));
+        }
+        Ok(())
+    }
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_INPUT_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    #[test]
+    fn test_custom_cap_rejects_larger_input() {
+        let config = EngineConfig::new(1024 * 1024);
+        let input = vec![0u8; 2 * 1024 * 1024];
+
+        let err = config.check_input_size(input.len()).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InputTooLarge);
+        assert_eq!(err.details.as_ref().unwrap()["max"], 1024 * 1024);
+        assert_eq!(err.details.as_ref().unwrap()["size"], 2 * 1024 * 1024);
+
+        assert!(config.check_input_size(512 * 1024).is_ok());
+    }
+
+    #[test]
+    fn test_default_cap() {
+        let config = EngineConfig::default();
+        assert!(config.check_input_size(DEFAULT_MAX_INPUT_SIZE).is_ok());
+        assert!(config.check_input_size(DEFAULT_MAX_INPUT_SIZE + 1).is_err());
+    }
+}
@@ -12,6 +12,9 @@ use std::collections::{HashMap, HashSet};
 use crate::disasm::{DisassembledInstruction, BasicBlock};
 use crate::function_analysis::CallingConvention;
 use crate::ssa::SSABuilder;
+use crate::idioms::{self, JumpTable};
+use crate::xrefs::XrefDatabase;
+use crate::demangle;
 
 /// Intermediate Representation Operation
 #[derive(Clone, Debug)]
@@ -20,6 +23,7 @@ pub enum IROp {
     Add,
     Sub,
     Mul,
+    IMul, // Signed multiply
     Div,
     Mod,
     Neg,
@@ -195,11 +199,58 @@ pub enum CStatement {
         args: Vec<String>,
         result_var: Option<String>,
     },
+    Switch {
+        value: String,
+        cases: Vec<(Vec<u64>, Vec<CStatement>)>,
+        default: Option<Vec<CStatement>>,
+    },
     Comment {
         text: String,
     },
 }
 
+/// Decompiled function retaining per-block IR so it can be re-rendered
+#[derive(Clone, Debug)]
+pub struct DecompiledFunction {
+    pub name: String,
+    pub address: u64,
+    pub blocks: Vec<IRBlock>,
+    /// Known call targets (e.g. resolved imports): address -> API name
+    pub api_names: HashMap<u64, String>,
+}
+
+/// How variables are named in emitted pseudo-C
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariableNaming {
+    /// Keep register/SSA names as recovered (`rax_2`, `t0`)
+    Register,
+    /// Rename to `v1`, `v2`, ... in order of first appearance
+    Sequential,
+}
+
+/// Rendering options for [`emit_pseudo_c`]
+#[derive(Clone, Debug)]
+pub struct EmitOptions {
+    /// Prefix each basic block with its address
+    pub show_addresses: bool,
+    /// Annotate calls to known APIs with an inline comment
+    pub api_comments: bool,
+    /// Show C++ and Rust API names demangled
+    pub demangle_names: bool,
+    pub naming: VariableNaming,
+}
+
+impl Default for EmitOptions {
+    fn default() -> Self {
+        Self {
+            show_addresses: false,
+            api_comments: true,
+            demangle_names: true,
+            naming: VariableNaming::Register,
+        }
+    }
+}
+
 /// Maximum number of basic blocks to prevent excessive memory usage
 const MAX_BASIC_BLOCKS: usize = 100000;
 
@@ -210,6 +261,8 @@ const MAX_TOTAL_INSTRUCTIONS: usize = 1000000;
 pub struct Decompiler {
     temp_counter: u32,
     var_versions: HashMap<String, u32>,
+    xrefs: Option<XrefDatabase>,
+    switch_sites: HashMap<u64, JumpTable>, // block address -> jump table
 }
 
 impl Decompiler {
@@ -217,9 +270,17 @@ impl Decompiler {
         Self {
             temp_counter: 0,
             var_versions: HashMap::new(),
+            xrefs: None,
+            switch_sites: HashMap::new(),
         }
     }
 
+    /// Use cross-references to reconstruct switch statements from jump tables
+    pub fn with_xrefs(mut self, xrefs: XrefDatabase) -> Self {
+        self.xrefs = Some(xrefs);
+        self
+    }
+
     /// Main decompilation entry point
     pub fn decompile(&mut self, blocks: &[BasicBlock]) -> Result<String, String> {
         if blocks.is_empty() {
@@ -271,6 +332,33 @@ impl Decompiler {
         Ok(code)
     }
 
+    /// Decompile a function into simplified IR for rendering with [`emit_pseudo_c`]
+    pub fn decompile_function(
+        &mut self,
+        name: &str,
+        address: u64,
+        blocks: &[BasicBlock],
+        api_names: HashMap<u64, String>,
+    ) -> Result<DecompiledFunction, String> {
+        if blocks.len() > MAX_BASIC_BLOCKS {
+            return Err(format!(
+                "Too many basic blocks for decompilation: {} (max: {})",
+                blocks.len(),
+                MAX_BASIC_BLOCKS
+            ));
+        }
+
+        let ir_blocks = self.convert_to_ir(blocks)?;
+        let blocks = self.simplify_ir(ir_blocks)?;
+
+        Ok(DecompiledFunction {
+            name: name.to_string(),
+            address,
+            blocks,
+            api_names,
+        })
+    }
+
     /// Convert assembly basic blocks to IR
     fn convert_to_ir(&mut self, blocks: &[BasicBlock]) -> Result<Vec<IRBlock>, String> {
         let mut ir_blocks = Vec::new();
@@ -282,6 +370,15 @@ impl Decompiler {
                 // Convert individual instruction to IR
                 let ir_stmts = self.instruction_to_ir(instr)?;
                 statements.extend(ir_stmts);
+
+                // Indirect jump through a table known to xrefs
+                if instr.mnemonic.eq_ignore_ascii_case("jmp") && instr.branch_target.is_none() {
+                    if let Some(table) = self.xrefs.as_ref()
+                        .and_then(|x| idioms::find_jump_table(x, instr.offset, &instr.operands))
+                    {
+                        self.switch_sites.insert(block.start_offset, table);
+                    }
+                }
             }
 
             ir_blocks.push(IRBlock {
@@ -328,7 +425,7 @@ impl Decompiler {
                 stmts.push(self.create_binary_op_stmt(&instr.operands, IROp::Sub)?);
             }
             m if m.starts_with("imul") || m.starts_with("mul") => {
-                stmts.push(self.create_binary_op_stmt(&instr.operands, IROp::Mul)?);
+                stmts.extend(self.create_multiply_stmt(&instr.operands, m.starts_with("imul"))?);
             }
             m if m.starts_with("idiv") || m.starts_with("div") => {
                 stmts.push(self.create_binary_op_stmt(&instr.operands, IROp::Div)?);
@@ -478,6 +575,92 @@ impl Decompiler {
         })
     }
 
+    fn create_multiply_stmt(&mut self, operands: &str, signed: bool) -> Result<Vec<IRStmt>, String> {
+        let parts: Vec<&str> = operands.split(',').map(|s| s.trim()).collect();
+        let op = if signed { IROp::IMul } else { IROp::Mul };
+
+        match parts.len() {
+            // mul/imul src: widening multiply of the accumulator, low half in
+            // the accumulator and high half in edx/rdx (ax = al * src for bytes)
+            1 => {
+                let size = operand_size(parts[0]).unwrap_or(8);
+                let mut src = self.parse_value(parts[0])?;
+                let (acc, high) = match size {
+                    1 => ("al", None),
+                    2 => ("ax", Some("dx")),
+                    4 => ("eax", Some("edx")),
+                    _ => ("rax", Some("rdx")),
+                };
+
+                let mut stmts = Vec::new();
+                if let (Some(high), IRValue::Var(var)) = (high, &src) {
+                    // mul edx: keep the multiplier before edx is overwritten
+                    if var.name == high {
+                        let temp = self.next_temp();
+                        stmts.push(IRStmt::Assign { dest: temp.clone(), value: src });
+                        src = IRValue::Var(temp);
+                    }
+                }
+
+                let product = || IRValue::Expr(Box::new(IRExpr::binary(
+                    op.clone(),
+                    IRValue::Var(IRVar::new(acc.to_string(), size)),
+                    src.clone(),
+                    size * 2,
+                )));
+                match high {
+                    Some(high) => {
+                        let shift = if signed { IROp::Sar } else { IROp::Shr };
+                        stmts.push(IRStmt::Assign {
+                            dest: IRVar::new(high.to_string(), size),
+                            value: IRValue::Expr(Box::new(IRExpr::binary(
+                                shift,
+                                product(),
+                                IRValue::Const(size as i64 * 8),
+                                size,
+                            ))),
+                        });
+                        stmts.push(IRStmt::Assign {
+                            dest: IRVar::new(acc.to_string(), size),
+                            value: product(),
+                        });
+                    }
+                    None => stmts.push(IRStmt::Assign {
+                        dest: IRVar::new("ax".to_string(), 2),
+                        value: product(),
+                    }),
+                }
+                Ok(stmts)
+            }
+            // imul dest, src
+            2 => {
+                let size = operand_size(parts[0]).unwrap_or(8);
+                let dest = IRVar::new(parts[0].to_string(), size);
+                let expr = IRExpr::binary(op, IRValue::Var(dest.clone()), self.parse_value(parts[1])?, size);
+                Ok(vec![IRStmt::Assign {
+                    dest,
+                    value: IRValue::Expr(Box::new(expr)),
+                }])
+            }
+            // imul dest, src, imm
+            3 => {
+                let size = operand_size(parts[0]).unwrap_or(8);
+                let dest = IRVar::new(parts[0].to_string(), size);
+                let expr = IRExpr::binary(
+                    op,
+                    self.parse_value(parts[1])?,
+                    self.parse_value(parts[2])?,
+                    size,
+                );
+                Ok(vec![IRStmt::Assign {
+                    dest,
+                    value: IRValue::Expr(Box::new(expr)),
+                }])
+            }
+            _ => Err(format!("Invalid multiply operands: {}", operands)),
+        }
+    }
+
     fn create_call_stmt(&mut self, instr: &DisassembledInstruction) -> Result<IRStmt, String> {
         let target = if let Some(addr) = instr.branch_target {
             IRValue::Const(addr as i64)
@@ -871,6 +1054,11 @@ impl Decompiler {
 
     /// Simplify IR expressions
     fn simplify_ir(&self, mut blocks: Vec<IRBlock>) -> Result<Vec<IRBlock>, String> {
+        // Pass 0: Collapse compiler idioms (magic-number division)
+        for block in &mut blocks {
+            idioms::recover_magic_division(&mut block.statements);
+        }
+
         // Pass 1: Constant folding and propagation
         for block in &mut blocks {
             for stmt in &mut block.statements {
@@ -1113,7 +1301,7 @@ impl Decompiler {
                         // Replace with first operand
                     }
                 }
-                IROp::Mul => {
+                IROp::Mul | IROp::IMul => {
                     // x * 1 = x
                     if matches!(&expr.operands[1], IRValue::Const(1)) {
                         // Replace with first operand
@@ -1179,7 +1367,7 @@ impl Decompiler {
         match expr.op {
             IROp::Add if constants.len() == 2 => Some(constants[0].wrapping_add(constants[1])),
             IROp::Sub if constants.len() == 2 => Some(constants[0].wrapping_sub(constants[1])),
-            IROp::Mul if constants.len() == 2 => Some(constants[0].wrapping_mul(constants[1])),
+            IROp::Mul | IROp::IMul if constants.len() == 2 => Some(constants[0].wrapping_mul(constants[1])),
             IROp::Div if constants.len() == 2 && constants[1] != 0 => Some(constants[0] / constants[1]),
             IROp::Mod if constants.len() == 2 && constants[1] != 0 => Some(constants[0] % constants[1]),
             IROp::And if constants.len() == 2 => Some(constants[0] & constants[1]),
@@ -1227,6 +1415,10 @@ impl Decompiler {
         if visited.contains(&block_addr) {
             return Ok(Vec::new());
         }
+
+        if self.switch_sites.contains_key(&block_addr) {
+            return self.recover_switch(block_addr, None, block_map, visited);
+        }
         visited.insert(block_addr);
 
         let block = block_map.get(&block_addr)
@@ -1239,6 +1431,14 @@ impl Decompiler {
             let is_last = i == block.statements.len() - 1;
 
             match stmt {
+                IRStmt::BranchCond { true_target, false_target, .. }
+                    if is_last && self.switch_bounds_check(*true_target, *false_target).is_some() =>
+                {
+                    // Bounds check guarding a jump table - fold into the switch default
+                    if let Some((dispatch, default)) = self.switch_bounds_check(*true_target, *false_target) {
+                        statements.extend(self.recover_switch(dispatch, Some(default), block_map, visited)?);
+                    }
+                }
                 IRStmt::BranchCond { condition, true_target, false_target } if is_last => {
                     // This is a conditional branch - create if/else structure
                     let then_block = self.recover_block_structure(*true_target, block_map, visited)?;
@@ -1293,6 +1493,64 @@ impl Decompiler {
         Ok(statements)
     }
 
+    /// If one branch target dispatches through a jump table and the other
+    /// doesn't, return `(dispatch, default)`
+    fn switch_bounds_check(&self, true_target: u64, false_target: u64) -> Option<(u64, u64)> {
+        match (
+            self.switch_sites.contains_key(&true_target),
+            self.switch_sites.contains_key(&false_target),
+        ) {
+            (true, false) => Some((true_target, false_target)),
+            (false, true) => Some((false_target, true_target)),
+            _ => None,
+        }
+    }
+
+    /// Recover a jump table dispatch block as a switch statement
+    fn recover_switch(
+        &self,
+        block_addr: u64,
+        default: Option<u64>,
+        block_map: &HashMap<u64, &IRBlock>,
+        visited: &mut HashSet<u64>,
+    ) -> Result<Vec<CStatement>, String> {
+        visited.insert(block_addr);
+
+        let block = block_map.get(&block_addr)
+            .ok_or_else(|| format!("Block not found: 0x{:x}", block_addr))?;
+        let table = &self.switch_sites[&block_addr];
+
+        let mut statements = Vec::new();
+        for stmt in &block.statements {
+            if !matches!(stmt, IRStmt::Branch { .. } | IRStmt::BranchCond { .. }) {
+                statements.push(self.ir_stmt_to_c(stmt)?);
+            }
+        }
+
+        let mut cases = Vec::new();
+        for (values, target) in table.cases() {
+            // Table holes point at the default label
+            if Some(target) == default {
+                continue;
+            }
+            let body = self.recover_block_structure(target, block_map, visited)?;
+            cases.push((values, body));
+        }
+
+        let default = match default {
+            Some(target) => Some(self.recover_block_structure(target, block_map, visited)?),
+            None => None,
+        };
+
+        statements.push(CStatement::Switch {
+            value: table.index.clone(),
+            cases,
+            default,
+        });
+
+        Ok(statements)
+    }
+
     /// Find the loop condition by analyzing blocks in the loop range
     /// Looks for conditional branches that exit the loop
     fn find_loop_condition(
@@ -1455,6 +1713,27 @@ impl Decompiler {
                 result.push_str(&format!("{}}} while ({});\n", indent_str, condition));
                 result
             }
+            CStatement::Switch { value, cases, default } => {
+                let mut result = format!("{}switch ({}) {{\n", indent_str, value);
+                let case_indent = "  ".repeat(indent + 1);
+                for (values, body) in cases {
+                    for v in values {
+                        result.push_str(&format!("{}case {}:\n", case_indent, v));
+                    }
+                    for s in body {
+                        result.push_str(&self.format_c_statement(s, indent + 2));
+                    }
+                    result.push_str(&format!("{}  break;\n", case_indent));
+                }
+                if let Some(body) = default {
+                    result.push_str(&format!("{}default:\n", case_indent));
+                    for s in body {
+                        result.push_str(&self.format_c_statement(s, indent + 2));
+                    }
+                }
+                result.push_str(&format!("{}}}\n", indent_str));
+                result
+            }
             CStatement::Comment { text } => {
                 format!("{}{}\n", indent_str, text)
             }
@@ -1489,7 +1768,7 @@ impl Decompiler {
         let op_str = match expr.op {
             IROp::Add => "+",
             IROp::Sub => "-",
-            IROp::Mul => "*",
+            IROp::Mul | IROp::IMul => "*",
             IROp::Div => "/",
             IROp::Mod => "%",
             IROp::And => "&",
@@ -1527,6 +1806,203 @@ impl Decompiler {
     }
 }
 
+/// Size in bytes of a register or sized memory operand (`dword ptr [...]`)
+fn operand_size(operand: &str) -> Option<u32> {
+    let operand = operand.trim().to_lowercase();
+    for (prefix, size) in [("byte", 1), ("word", 2), ("dword", 4), ("qword", 8)] {
+        if operand.starts_with(prefix) && operand[prefix.len()..].trim_start().starts_with("ptr") {
+            return Some(size);
+        }
+    }
+
+    match operand.as_str() {
+        "al" | "ah" | "bl" | "bh" | "cl" | "ch" | "dl" | "dh" | "sil" | "dil" | "bpl" | "spl" => Some(1),
+        "ax" | "bx" | "cx" | "dx" | "si" | "di" | "bp" | "sp" => Some(2),
+        r if r.starts_with('e') && r.len() == 3 => Some(4),
+        r if r.starts_with('r') && r.len() == 3 && !r[1..].starts_with(|c: char| c.is_ascii_digit()) => Some(8),
+        r if r.starts_with('r') && r[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+            Some(match r.trim_start_matches(|c: char| c == 'r' || c.is_ascii_digit()) {
+                "b" => 1,
+                "w" => 2,
+                "d" => 4,
+                _ => 8,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Render a decompiled function as pseudo-C.
+///
+/// Blocks are emitted in address order with explicit `goto`s for branches;
+/// structured output is produced by [`Decompiler::decompile`].
+pub fn emit_pseudo_c(func: &DecompiledFunction, opts: EmitOptions) -> String {
+    let mut emitter = PseudoCEmitter {
+        func,
+        opts,
+        names: HashMap::new(),
+    };
+    emitter.emit()
+}
+
+struct PseudoCEmitter<'a> {
+    func: &'a DecompiledFunction,
+    opts: EmitOptions,
+    names: HashMap<String, String>,
+}
+
+impl PseudoCEmitter<'_> {
+    fn emit(&mut self) -> String {
+        let mut blocks: Vec<&IRBlock> = self.func.blocks.iter().collect();
+        blocks.sort_by_key(|b| b.address);
+
+        let mut output = String::new();
+        if self.opts.show_addresses {
+            output.push_str(&format!("// 0x{:x}\n", self.func.address));
+        }
+        output.push_str(&format!("void {}() {{\n", self.func.name));
+
+        // Branch targets always get a label, or their gotos would dangle
+        let targets: HashSet<u64> = blocks
+            .iter()
+            .flat_map(|b| &b.statements)
+            .flat_map(|stmt| match stmt {
+                IRStmt::Branch { target } => vec![*target],
+                IRStmt::BranchCond { true_target, false_target, .. } => vec![*true_target, *false_target],
+                _ => Vec::new(),
+            })
+            .collect();
+
+        for block in blocks {
+            if self.opts.show_addresses {
+                output.push_str(&format!("{}:  // 0x{:x}\n", self.label(block.address), block.address));
+            } else if targets.contains(&block.address) {
+                output.push_str(&format!("{}:\n", self.label(block.address)));
+            }
+            for stmt in &block.statements {
+                output.push_str("  ");
+                output.push_str(&self.stmt(stmt));
+                output.push('\n');
+            }
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn stmt(&mut self, stmt: &IRStmt) -> String {
+        match stmt {
+            IRStmt::Assign { dest, value } => {
+                format!("{} = {};", self.var(dest), self.value(value))
+            }
+            IRStmt::Store { address, value, .. } => {
+                format!("*({}) = {};", self.value(address), self.value(value))
+            }
+            IRStmt::Branch { target } => format!("goto {};", self.label(*target)),
+            IRStmt::BranchCond { condition, true_target, false_target } => {
+                format!(
+                    "if ({}) goto {}; else goto {};",
+                    self.value(condition),
+                    self.label(*true_target),
+                    self.label(*false_target)
+                )
+            }
+            IRStmt::Call { target, args, result } => {
+                let api = match target {
+                    IRValue::Const(addr) => self.func.api_names.get(&(*addr as u64)),
+                    _ => None,
+                };
+                let function = match target {
+                    IRValue::Const(addr) => format!("sub_{:x}", addr),
+                    _ => self.value(target),
+                };
+                let args: Vec<String> = args.iter().map(|a| self.value(a)).collect();
+                let call = format!("{}({})", function, args.join(", "));
+
+                let mut line = match result {
+                    Some(var) => format!("{} = {};", self.var(var), call),
+                    None => format!("{};", call),
+                };
+                if let (true, Some(name)) = (self.opts.api_comments, api) {
+                    line.push_str(&format!("  // {}", self.api_name(name)));
+                }
+                line
+            }
+            IRStmt::Return { value } => match value {
+                Some(v) => format!("return {};", self.value(v)),
+                None => "return;".to_string(),
+            },
+        }
+    }
+
+    fn label(&self, target: u64) -> String {
+        format!("loc_{:x}", target)
+    }
+
+    /// `module!symbol` with the symbol demangled if enabled
+    fn api_name(&self, name: &str) -> String {
+        if !self.opts.demangle_names {
+            return name.to_string();
+        }
+        match name.split_once('!') {
+            Some((module, symbol)) => format!("{}!{}", module, demangle::demangle(symbol)),
+            None => demangle::demangle(name),
+        }
+    }
+
+    fn var(&mut self, var: &IRVar) -> String {
+        let raw = if var.version > 0 {
+            format!("{}_{}", var.name, var.version)
+        } else {
+            var.name.clone()
+        };
+
+        match self.opts.naming {
+            VariableNaming::Register => raw,
+            VariableNaming::Sequential => {
+                let next = self.names.len() + 1;
+                self.names.entry(raw).or_insert_with(|| format!("v{}", next)).clone()
+            }
+        }
+    }
+
+    fn value(&mut self, value: &IRValue) -> String {
+        match value {
+            IRValue::Var(v) => self.var(v),
+            IRValue::Const(c) if *c > 255 => format!("0x{:x}", c),
+            IRValue::Const(c) => format!("{}", c),
+            IRValue::Expr(e) => {
+                let op = match e.op {
+                    IROp::Add => "+",
+                    IROp::Sub | IROp::Neg => "-",
+                    IROp::Mul | IROp::IMul => "*",
+                    IROp::Div => "/",
+                    IROp::Mod => "%",
+                    IROp::And => "&",
+                    IROp::Or => "|",
+                    IROp::Xor => "^",
+                    IROp::Shl => "<<",
+                    IROp::Shr | IROp::Sar => ">>",
+                    IROp::Eq => "==",
+                    IROp::Ne => "!=",
+                    IROp::Lt => "<",
+                    IROp::Le => "<=",
+                    IROp::Gt => ">",
+                    IROp::Ge => ">=",
+                    IROp::Not => "!",
+                    IROp::Load => "*",
+                    _ => "?",
+                };
+                match e.operands.as_slice() {
+                    [lhs, rhs] => format!("({} {} {})", self.value(lhs), op, self.value(rhs)),
+                    [operand] => format!("({}{})", op, self.value(operand)),
+                    _ => "???".to_string(),
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1705,4 +2181,217 @@ mod tests {
         // Verify return is kept
         assert!(matches!(stmts[2], IRStmt::Return { .. }));
     }
+
+    fn sample_function() -> DecompiledFunction {
+        let mut api_names = HashMap::new();
+        api_names.insert(0x402000, "kernel32!VirtualAlloc".to_string());
+
+        DecompiledFunction {
+            name: "sub_401000".to_string(),
+            address: 0x401000,
+            blocks: vec![IRBlock {
+                address: 0x401000,
+                statements: vec![
+                    IRStmt::Assign {
+                        dest: IRVar::new("rcx".to_string(), 8),
+                        value: IRValue::Const(0),
+                    },
+                    IRStmt::Call {
+                        target: IRValue::Const(0x402000),
+                        args: vec![IRValue::Var(IRVar::new("rcx".to_string(), 8))],
+                        result: Some(IRVar::new("rax".to_string(), 8)),
+                    },
+                    IRStmt::Return {
+                        value: Some(IRValue::Var(IRVar::new("rax".to_string(), 8))),
+                    },
+                ],
+                successors: vec![],
+                predecessors: vec![],
+            }],
+            api_names,
+        }
+    }
+
+    #[test]
+    fn test_emit_pseudo_c_api_comments() {
+        let func = sample_function();
+
+        let with_comments = emit_pseudo_c(&func, EmitOptions::default());
+        assert!(with_comments.contains("rax = sub_402000(rcx);  // kernel32!VirtualAlloc"));
+
+        let without_comments = emit_pseudo_c(&func, EmitOptions {
+            api_comments: false,
+            ..EmitOptions::default()
+        });
+        assert!(!without_comments.contains("VirtualAlloc"));
+        assert!(without_comments.contains("rax = sub_402000(rcx);"));
+    }
+
+    #[test]
+    fn test_emit_pseudo_c_addresses_and_naming() {
+        let func = sample_function();
+        let output = emit_pseudo_c(&func, EmitOptions {
+            show_addresses: true,
+            api_comments: false,
+            demangle_names: true,
+            naming: VariableNaming::Sequential,
+        });
+
+        assert!(output.contains("loc_401000:"));
+        assert!(output.contains("v1 = 0;"));
+        assert!(output.contains("v2 = sub_402000(v1);"));
+        assert!(output.contains("return v2;"));
+        assert!(!output.contains("rax"));
+    }
+
+    #[test]
+    fn test_emit_pseudo_c_labels_goto_targets() {
+        let mut func = sample_function();
+        func.blocks[0].statements.insert(0, IRStmt::BranchCond {
+            condition: IRValue::Var(IRVar::new("ZF".to_string(), 1)),
+            true_target: 0x401010,
+            false_target: 0x401020,
+        });
+        for address in [0x401010, 0x401020] {
+            func.blocks.push(IRBlock {
+                address,
+                statements: vec![IRStmt::Branch { target: 0x401000 }],
+                successors: vec![],
+                predecessors: vec![],
+            });
+        }
+
+        let output = emit_pseudo_c(&func, EmitOptions::default());
+
+        assert!(output.contains("if (ZF) goto loc_401010; else goto loc_401020;"), "{}", output);
+        for label in ["loc_401000:\n", "loc_401010:\n", "loc_401020:\n"] {
+            assert!(output.contains(label), "{}", output);
+        }
+        assert!(!output.contains("// 0x"), "{}", output);
+    }
+
+    #[test]
+    fn test_emit_pseudo_c_demangles_api_names() {
+        let mut func = sample_function();
+        func.api_names.insert(0x402000, "libcrypto!_ZN6Crypto7encryptEPKhm".to_string());
+
+        let output = emit_pseudo_c(&func, EmitOptions::default());
+        assert!(output.contains("// libcrypto!Crypto::encrypt(unsigned char const*, unsigned long)"));
+
+        let raw = emit_pseudo_c(&func, EmitOptions {
+            demangle_names: false,
+            ..EmitOptions::default()
+        });
+        assert!(raw.contains("// libcrypto!_ZN6Crypto7encryptEPKhm"));
+    }
+
+    #[test]
+    fn test_magic_division_renders_as_division() {
+        let mut decompiler = Decompiler::new();
+
+        // unsigned div3(unsigned x): mov eax, edi; mov ecx, 0xaaaaaaab; mul ecx; mov eax, edx; shr eax, 1
+        let mut statements = vec![
+            decompiler.create_move_stmt("eax, edi").unwrap(),
+            decompiler.create_move_stmt("ecx, 0xaaaaaaab").unwrap(),
+        ];
+        statements.extend(decompiler.create_multiply_stmt("ecx", false).unwrap());
+        statements.extend([
+            decompiler.create_move_stmt("eax, edx").unwrap(),
+            decompiler.create_binary_op_stmt("eax, 1", IROp::Shr).unwrap(),
+        ]);
+        statements.push(IRStmt::Return {
+            value: Some(IRValue::Var(IRVar::new("eax".to_string(), 4))),
+        });
+
+        let blocks = decompiler.simplify_ir(vec![IRBlock {
+            address: 0x1000,
+            statements,
+            successors: vec![],
+            predecessors: vec![],
+        }]).unwrap();
+
+        let func = DecompiledFunction {
+            name: "div3".to_string(),
+            address: 0x1000,
+            blocks,
+            api_names: HashMap::new(),
+        };
+        let output = emit_pseudo_c(&func, EmitOptions::default());
+
+        assert!(output.contains("eax = (edi / 3);"), "{}", output);
+        assert!(!output.contains("0xaaaaaaab"), "{}", output);
+        assert!(!output.contains(">>"), "{}", output);
+    }
+
+    fn assigned(stmt: &IRStmt) -> (&IRVar, &IRExpr) {
+        match stmt {
+            IRStmt::Assign { dest, value: IRValue::Expr(expr) } => (dest, expr),
+            other => panic!("expected an assignment of an expression, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_one_operand_multiply_assigns_both_halves() {
+        let mut decompiler = Decompiler::new();
+
+        let stmts = decompiler.create_multiply_stmt("ecx", false).unwrap();
+        assert_eq!(stmts.len(), 2);
+        let (high, shift) = assigned(&stmts[0]);
+        assert_eq!((high.name.as_str(), high.size), ("edx", 4));
+        assert!(matches!(shift.op, IROp::Shr));
+        assert!(matches!(shift.operands[1], IRValue::Const(32)));
+        let (low, product) = assigned(&stmts[1]);
+        assert_eq!((low.name.as_str(), low.size), ("eax", 4));
+        assert!(matches!(product.op, IROp::Mul));
+
+        // Signed: the high half is an arithmetic shift of a signed product
+        let stmts = decompiler.create_multiply_stmt("qword ptr [rbp - 8]", true).unwrap();
+        let (high, shift) = assigned(&stmts[0]);
+        assert_eq!((high.name.as_str(), high.size), ("rdx", 8));
+        assert!(matches!(shift.op, IROp::Sar));
+        assert!(matches!(shift.operands[1], IRValue::Const(64)));
+        let (low, product) = assigned(&stmts[1]);
+        assert_eq!((low.name.as_str(), low.size), ("rax", 8));
+        assert!(matches!(product.op, IROp::IMul));
+
+        // Bytes: ax = al * src, nothing in dx
+        let stmts = decompiler.create_multiply_stmt("cl", false).unwrap();
+        assert_eq!(stmts.len(), 1);
+        let (dest, product) = assigned(&stmts[0]);
+        assert_eq!((dest.name.as_str(), dest.size), ("ax", 2));
+        assert!(matches!(&product.operands[0], IRValue::Var(v) if v.name == "al"));
+    }
+
+    #[test]
+    fn test_multiply_by_high_register_reads_it_first() {
+        let mut decompiler = Decompiler::new();
+
+        // mul edx: the low half must use edx from before the high half lands in it
+        let stmts = decompiler.create_multiply_stmt("edx", false).unwrap();
+        assert_eq!(stmts.len(), 3);
+        let IRStmt::Assign { dest: temp, value: IRValue::Var(saved) } = &stmts[0] else { panic!("{:?}", stmts[0]) };
+        assert!(temp.is_temp);
+        assert_eq!(saved.name, "edx");
+        let (_, product) = assigned(&stmts[2]);
+        assert!(matches!(&product.operands[1], IRValue::Var(v) if v.name == temp.name));
+    }
+
+    #[test]
+    fn test_two_and_three_operand_imul() {
+        let mut decompiler = Decompiler::new();
+
+        let stmts = decompiler.create_multiply_stmt("eax, ecx", true).unwrap();
+        let (dest, product) = assigned(&stmts[0]);
+        assert_eq!((dest.name.as_str(), dest.size), ("eax", 4));
+        assert!(matches!(product.op, IROp::IMul));
+        assert_eq!(product.size, 4);
+        assert!(matches!(&product.operands[0], IRValue::Var(v) if v.name == "eax"));
+
+        let stmts = decompiler.create_multiply_stmt("r9w, word ptr [rsi], 0x10", true).unwrap();
+        let (dest, product) = assigned(&stmts[0]);
+        assert_eq!((dest.name.as_str(), dest.size), ("r9w", 2));
+        assert!(matches!(product.op, IROp::IMul));
+        assert_eq!(product.size, 2);
+        assert!(matches!(product.operands[1], IRValue::Const(0x10)));
+    }
 }
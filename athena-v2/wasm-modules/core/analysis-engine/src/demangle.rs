@@ -0,0 +1,109 @@
+//! Symbol Demangling
+//! Turns compiler-mangled import/export names back into readable paths
+//!
+//! Supports Itanium C++ (`_Z...`) through `cpp_demangle` and both Rust
+//! manglings, legacy (`_ZN...17h<hash>E`) and v0 (`_R...`), through
+//! `rustc-demangle`. Anything else, or a symbol either library rejects, is
+//! returned unchanged.
+
+use cpp_demangle::{DemangleOptions, Symbol};
+
+/// Demangle `name`, falling back to the raw name
+pub fn demangle(name: &str) -> String {
+    try_demangle(name).unwrap_or_else(|| name.to_string())
+}
+
+/// Demangle `name`, or `None` if it isn't a mangled name this module understands
+pub fn try_demangle(name: &str) -> Option<String> {
+    // Rust first: legacy Rust symbols are also valid Itanium names, but
+    // rustc-demangle rejects C++ ones that carry a parameter list
+    if let Ok(rust) = rustc_demangle::try_demangle(name) {
+        // `{:#}` drops the legacy hash suffix
+        return Some(format!("{:#}", rust));
+    }
+
+    let symbol = Symbol::new(name).ok()?;
+    symbol.demangle(&DemangleOptions::default()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demangle_cpp() {
+        assert_eq!(demangle("_ZN9wikipedia7article6formatEv"), "wikipedia::article::format()");
+        assert_eq!(
+            demangle("_ZNSt6vectorIiSaIiEE9push_backERKi"),
+            "std::vector<int, std::allocator<int> >::push_back(int const&)"
+        );
+        assert_eq!(demangle("_ZNK3Foo3getEPKcRKSs"), "Foo::get(char const*, std::string const&) const");
+        assert_eq!(demangle("_ZN3FooC1ERKS_"), "Foo::Foo(Foo const&)");
+        assert_eq!(demangle("_Z3maxIiET_S0_S0_"), "int max<int>(int, int)");
+        // Mach-O adds an extra leading underscore
+        assert_eq!(demangle("__ZN3foo3barEv"), "foo::bar()");
+    }
+
+    #[test]
+    fn test_demangle_cpp_templates() {
+        assert_eq!(demangle("_Z1gIJidEEvDpT_"), "void g<int, double>(int, double)");
+        assert_eq!(demangle("_Z1fILin1EEvv"), "void f<-1>()");
+        assert_eq!(
+            demangle("_ZStlsISt11char_traitsIcEERSt13basic_ostreamIcT_ES5_PKc"),
+            "std::basic_ostream<char, std::char_traits<char> >& std::operator<< <std::char_traits<char> >(std::basic_ostream<char, std::char_traits<char> >&, char const*)"
+        );
+    }
+
+    #[test]
+    fn test_reference_collapsing() {
+        assert_eq!(demangle("_Z1fIRiEvOT_"), "void f<int&>(int&)");
+        assert_eq!(demangle("_Z1fIOiEvRT_"), "void f<int&&>(int&)");
+        assert_eq!(demangle("_Z1fIOiEvOT_"), "void f<int&&>(int&&)");
+    }
+
+    #[test]
+    fn test_demangle_cpp_declarators() {
+        assert_eq!(demangle("_Z4funcRA10_i"), "func(int (&) [10])");
+        assert_eq!(demangle("_Z1fPA2_A3_i"), "f(int (*) [2][3])");
+        assert_eq!(demangle("_Z1fPFivE"), "f(int (*)())");
+        assert_eq!(demangle("_Z1fM1AKFivE"), "f(int (A::*)() const)");
+    }
+
+    #[test]
+    fn test_malformed_symbols_unchanged() {
+        for name in [
+            "_ZN",
+            "_Z1fS1_",
+            "_Z1fT_",
+            "_Z1fPA2_",
+            "_Z999999999999999999999a",
+            // Lengths that overflow usize when added to the position
+            "_ZN18446744073709551615aE",
+            "_RNvC18446744073709551615a1b",
+        ] {
+            assert_eq!(demangle(name), name);
+        }
+
+        let deep = format!("_Z1f{}iv", "P".repeat(1000));
+        assert_eq!(demangle(&deep), deep);
+    }
+
+    #[test]
+    fn test_demangle_rust() {
+        assert_eq!(demangle("_ZN4core3fmt9Formatter3pad17h0123456789abcdefE"), "core::fmt::Formatter::pad");
+        assert_eq!(
+            demangle("_ZN60_$LT$alloc..vec..Vec$LT$T$GT$$u20$as$u20$core..ops..Drop$GT$4drop17h5e0fd8c3b7a4a3e1E"),
+            "<alloc::vec::Vec<T> as core::ops::Drop>::drop"
+        );
+        assert_eq!(demangle("_RNvNtCs1234_7mycrate3foo3bar"), "mycrate::foo::bar");
+        assert_eq!(demangle("_RNvMCs4fqI2P2rA04_7mycrateNtB2_7Payload6decode"), "<mycrate::Payload>::decode");
+    }
+
+    #[test]
+    fn test_unmangled_names_unchanged() {
+        assert_eq!(demangle("VirtualAlloc"), "VirtualAlloc");
+        assert_eq!(demangle("_Z"), "_Z");
+        assert_eq!(demangle("_ZN3foo"), "_ZN3foo");
+        assert_eq!(try_demangle("_Rzz"), None);
+    }
+}
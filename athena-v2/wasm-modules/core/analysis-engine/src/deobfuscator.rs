@@ -28,11 +28,20 @@ impl Deobfuscator {
     }
 
     pub fn deobfuscate(&self, content: &str) -> DeobfuscationResult {
+        self.deobfuscate_interruptible(content, || false).expect("never interrupted")
+    }
+
+    /// Like `deobfuscate`, but checks `interrupted` before each decoding
+    /// pass and returns `None` once it returns true
+    pub fn deobfuscate_interruptible(&self, content: &str, interrupted: impl Fn() -> bool) -> Option<DeobfuscationResult> {
         let mut deobfuscated = content.to_string();
         let mut techniques_found = Vec::new();
         let mut confidence: f32 = 0.0;
 
         // Try Base64 decoding
+        if interrupted() {
+            return None;
+        }
         if let Some(decoded) = self.try_base64_decode(&deobfuscated) {
             if self.is_readable(&decoded) {
                 deobfuscated = decoded;
@@ -42,6 +51,9 @@ impl Deobfuscator {
         }
 
         // Try hex decoding
+        if interrupted() {
+            return None;
+        }
         if let Some(decoded) = self.try_hex_decode(&deobfuscated) {
             if self.is_readable(&decoded) {
                 deobfuscated = decoded;
@@ -51,6 +63,9 @@ impl Deobfuscator {
         }
 
         // Try Unicode unescape
+        if interrupted() {
+            return None;
+        }
         if let Some(decoded) = self.try_unicode_unescape(&deobfuscated) {
             deobfuscated = decoded;
             techniques_found.push(ObfuscationTechnique::UnicodeEscape);
@@ -58,6 +73,9 @@ impl Deobfuscator {
         }
 
         // Try character code concatenation
+        if interrupted() {
+            return None;
+        }
         if let Some(decoded) = self.try_charcode_decode(&deobfuscated) {
             deobfuscated = decoded;
             techniques_found.push(ObfuscationTechnique::CharCodeConcat);
@@ -66,6 +84,9 @@ impl Deobfuscator {
 
         // Try XOR with common keys
         for key in &[0x13, 0x37, 0x42, 0xAA, 0xFF] {
+            if interrupted() {
+                return None;
+            }
             if let Some(decoded) = self.try_xor_decode(&deobfuscated, *key) {
                 if self.is_readable(&decoded) && self.has_improved(&content, &decoded) {
                     deobfuscated = decoded;
@@ -83,12 +104,12 @@ impl Deobfuscator {
             confidence = 0.0;
         }
 
-        DeobfuscationResult {
+        Some(DeobfuscationResult {
             original: content.to_string(),
             deobfuscated,
             techniques_found,
             confidence,
-        }
+        })
     }
 
     fn try_base64_decode(&self, input: &str) -> Option<String> {
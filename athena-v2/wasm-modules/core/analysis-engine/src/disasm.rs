@@ -1,12 +1,12 @@
 use iced_x86::{
     Decoder, DecoderOptions, Formatter, Instruction as IcedInstruction,
     IntelFormatter, GasFormatter, MasmFormatter, NasmFormatter,
-    FlowControl, OpKind, InstructionInfoFactory, OpAccess, MemorySize
+    FlowControl, OpKind, InstructionInfoFactory, OpAccess, MemorySize, Register
 };
 use std::collections::{HashMap, HashSet};
 use crate::arm_disasm;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Architecture {
     X8632,
     X8664,
@@ -14,7 +14,20 @@ pub enum Architecture {
     Arm64,
 }
 
-#[derive(Clone, Copy)]
+impl Architecture {
+    /// Architecture from a PE file header's `Machine` field
+    pub fn from_pe_machine(machine: u16) -> Option<Self> {
+        match machine {
+            0x014C => Some(Architecture::X8632),
+            0x8664 => Some(Architecture::X8664),
+            0x01C0 | 0x01C2 | 0x01C4 => Some(Architecture::Arm),
+            0xAA64 => Some(Architecture::Arm64),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Syntax {
     Intel,
     Att,
@@ -22,6 +35,16 @@ pub enum Syntax {
     Nasm,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisasmMode {
+    /// Decode every byte in order
+    #[default]
+    LinearSweep,
+    /// Follow control flow from the start of the buffer, so data embedded
+    /// between functions isn't decoded as instructions
+    RecursiveDescent,
+}
+
 #[derive(Clone)]
 pub struct UsedRegister {
     pub register: String,
@@ -117,8 +140,81 @@ pub struct DisassembledInstruction {
     // Operand info
     pub op_count: u32,
     pub operand_kinds: Vec<String>,
+    pub decoded_operands: Vec<Operand>,
 }
 
+/// A decoded operand, so analysis passes don't have to re-parse operand text
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    Register(String),
+    Immediate(i64),
+    Memory {
+        base: Option<String>,
+        index: Option<String>,
+        scale: u32,
+        displacement: i64,
+        size: u32,
+    },
+    /// Target address of a direct branch or call
+    BranchTarget(u64),
+    /// Operand implied by the instruction (e.g. `[rsi]` of `movsb`), by kind
+    Implicit(String),
+}
+
+/// Instruction semantics consumed by CFG, xref and emulation passes
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    pub address: u64,
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+    pub operands: Vec<Operand>,
+    /// Registers read, including implicit ones such as the stack pointer
+    pub reads: Vec<String>,
+    /// Registers written, including implicit ones
+    pub writes: Vec<String>,
+    /// Any transfer of control other than a return: jumps, conditional
+    /// branches and calls
+    pub is_branch: bool,
+    pub is_call: bool,
+    pub is_return: bool,
+    pub branch_target: Option<u64>,
+}
+
+impl DisassembledInstruction {
+    pub fn to_instruction(&self) -> Instruction {
+        let mut reads = Vec::new();
+        let mut writes = Vec::new();
+        for used in &self.used_registers {
+            let (read, write) = match used.access {
+                RegisterAccess::None => (false, false),
+                RegisterAccess::Read | RegisterAccess::CondRead => (true, false),
+                RegisterAccess::Write | RegisterAccess::CondWrite => (false, true),
+                RegisterAccess::ReadWrite => (true, true),
+            };
+            if read && !reads.contains(&used.register) {
+                reads.push(used.register.clone());
+            }
+            if write && !writes.contains(&used.register) {
+                writes.push(used.register.clone());
+            }
+        }
+
+        Instruction {
+            address: self.offset,
+            bytes: self.bytes.clone(),
+            mnemonic: self.mnemonic.clone(),
+            operands: self.decoded_operands.clone(),
+            reads,
+            writes,
+            is_branch: self.is_branch || self.is_call,
+            is_call: self.is_call,
+            is_return: self.is_return,
+            branch_target: self.branch_target,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct BasicBlock {
     pub start_offset: u64,
     pub end_offset: u64,
@@ -136,6 +232,93 @@ pub struct FunctionInfo {
     pub called_from: Vec<u64>,
 }
 
+/// How a relocation entry adjusts the field it points at
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RelocationKind {
+    /// PE `IMAGE_REL_BASED_HIGHLOW`: add the load delta to a 32-bit field
+    HighLow,
+    /// PE `IMAGE_REL_BASED_DIR64`: add the load delta to a 64-bit field
+    Dir64,
+    /// ELF `R_*_RELATIVE`: store load base plus addend in a pointer-sized field
+    Relative { addend: i64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct Relocation {
+    /// Image-relative address (RVA) of the field to patch
+    pub address: u64,
+    pub kind: RelocationKind,
+}
+
+/// Where the code sits in a mapped image
+#[derive(Clone, Debug)]
+pub struct ImageLayout {
+    /// Address the image is loaded at
+    pub base_address: u64,
+    /// Base the image was linked for (PE `ImageBase`, 0 for PIE ELF)
+    pub preferred_base: u64,
+    /// Image-relative address of the first byte of `code`
+    pub code_rva: u64,
+    pub relocations: Vec<Relocation>,
+}
+
+impl ImageLayout {
+    /// The layout of `code_rva` in the PE or ELF `file` loaded at
+    /// `base_address`, with the relocations from its own `.reloc` or
+    /// `SHT_RELA`/`SHT_REL` tables. ELF files carrying relative
+    /// relocations are position independent, so they are linked at zero.
+    pub fn from_file(file: &[u8], base_address: u64, code_rva: u64) -> Result<Self, String> {
+        let (preferred_base, relocations) = match file.get(..4) {
+            Some([b'M', b'Z', ..]) => {
+                let pe = crate::pe_seeds::PeLayout::parse(file)?;
+                (pe.image_base, pe.relocations(file))
+            }
+            Some(b"\x7FELF") => (0, crate::relocations::elf_relocations(file)?),
+            _ => return Err("Not a PE or ELF image".to_string()),
+        };
+        Ok(Self { base_address, preferred_base, code_rva, relocations })
+    }
+
+    /// Copy of `code` with every relocation that falls inside it applied
+    pub fn apply_relocations(&self, code: &[u8], arch: Architecture) -> Vec<u8> {
+        let mut patched = code.to_vec();
+        let delta = self.base_address.wrapping_sub(self.preferred_base);
+        let pointer_size = match arch {
+            Architecture::X8632 | Architecture::Arm => 4,
+            Architecture::X8664 | Architecture::Arm64 => 8,
+        };
+
+        for reloc in &self.relocations {
+            let Some(start) = reloc.address.checked_sub(self.code_rva) else {
+                continue;
+            };
+            let width = match reloc.kind {
+                RelocationKind::HighLow => 4,
+                RelocationKind::Dir64 => 8,
+                RelocationKind::Relative { .. } => pointer_size,
+            };
+            let Some(field) = usize::try_from(start)
+                .ok()
+                .and_then(|start| patched.get_mut(start..start.checked_add(width)?))
+            else {
+                continue;
+            };
+
+            let mut raw = [0u8; 8];
+            raw[..width].copy_from_slice(field);
+            let value = match reloc.kind {
+                RelocationKind::HighLow | RelocationKind::Dir64 => {
+                    u64::from_le_bytes(raw).wrapping_add(delta)
+                }
+                RelocationKind::Relative { addend } => self.base_address.wrapping_add_signed(addend),
+            };
+            field.copy_from_slice(&value.to_le_bytes()[..width]);
+        }
+
+        patched
+    }
+}
+
 pub struct Disassembler;
 
 impl Disassembler {
@@ -335,6 +518,9 @@ impl Disassembler {
         let operand_kinds: Vec<String> = (0..op_count)
             .map(|i| format!("{:?}", instr.op_kind(i)))
             .collect();
+        let decoded_operands: Vec<Operand> = (0..op_count)
+            .map(|i| Self::decode_operand(instr, i, memory_size))
+            .collect();
 
         DisassembledInstruction {
             offset: instr.ip(),
@@ -370,6 +556,41 @@ impl Disassembler {
             encoding,
             op_count,
             operand_kinds,
+            decoded_operands,
+        }
+    }
+
+    fn decode_operand(instr: &IcedInstruction, operand: u32, memory_size: u32) -> Operand {
+        let register_name = |reg: Register| {
+            (reg != Register::None).then(|| format!("{:?}", reg))
+        };
+
+        match instr.op_kind(operand) {
+            OpKind::Register => Operand::Register(format!("{:?}", instr.op_register(operand))),
+            OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 => {
+                Operand::BranchTarget(instr.near_branch_target())
+            }
+            OpKind::FarBranch16 | OpKind::FarBranch32 => {
+                Operand::BranchTarget(instr.far_branch32() as u64)
+            }
+            OpKind::Immediate8
+            | OpKind::Immediate8_2nd
+            | OpKind::Immediate16
+            | OpKind::Immediate32
+            | OpKind::Immediate64
+            | OpKind::Immediate8to16
+            | OpKind::Immediate8to32
+            | OpKind::Immediate8to64
+            | OpKind::Immediate32to64 => Operand::Immediate(instr.immediate(operand) as i64),
+            OpKind::Memory => Operand::Memory {
+                // RIP-relative displacements are already resolved to an address
+                base: register_name(instr.memory_base()).filter(|_| !instr.is_ip_rel_memory_operand()),
+                index: register_name(instr.memory_index()),
+                scale: instr.memory_index_scale(),
+                displacement: instr.memory_displacement64() as i64,
+                size: memory_size,
+            },
+            kind => Operand::Implicit(format!("{:?}", kind)),
         }
     }
 
@@ -414,6 +635,32 @@ impl Disassembler {
         }
     }
 
+    /// Disassemble into structured instructions for analysis passes
+    pub fn disassemble_structured(
+        code: &[u8],
+        offset: u64,
+        arch: Architecture,
+        max_instructions: u32,
+    ) -> Result<Vec<Instruction>, String> {
+        Ok(Self::disassemble(code, offset, arch, Syntax::Intel, max_instructions)?
+            .iter()
+            .map(DisassembledInstruction::to_instruction)
+            .collect())
+    }
+
+    /// Disassemble code from a mapped image: addresses are reported relative
+    /// to `layout.base_address` and relocated operands hold their load-time values
+    pub fn disassemble_mapped(
+        code: &[u8],
+        layout: &ImageLayout,
+        arch: Architecture,
+        max_instructions: u32,
+    ) -> Result<Vec<Instruction>, String> {
+        let patched = layout.apply_relocations(code, arch);
+        let address = layout.base_address.wrapping_add(layout.code_rva);
+        Self::disassemble_structured(&patched, address, arch, max_instructions)
+    }
+
     pub fn analyze_control_flow(
         code: &[u8],
         entry_point: u64,
@@ -599,3 +846,94 @@ impl Disassembler {
         Ok(xrefs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structured_x64_call_and_memory_operand() {
+        let code = [
+            0xE8, 0x05, 0x00, 0x00, 0x00, // call 0x100a
+            0x48, 0x8B, 0x43, 0x08,       // mov rax, [rbx+8]
+            0xC3,                         // ret
+        ];
+
+        let insns = Disassembler::disassemble_structured(&code, 0x1000, Architecture::X8664, 10).unwrap();
+        assert_eq!(insns.len(), 3);
+
+        let call = &insns[0];
+        assert_eq!(call.address, 0x1000);
+        assert_eq!(call.bytes, vec![0xE8, 0x05, 0x00, 0x00, 0x00]);
+        assert!(call.is_branch);
+        assert!(call.is_call);
+        assert_eq!(call.branch_target, Some(0x100A));
+        assert_eq!(call.operands, vec![Operand::BranchTarget(0x100A)]);
+        assert!(call.writes.contains(&"RSP".to_string()));
+
+        let mov = &insns[1];
+        assert!(!mov.is_branch);
+        assert_eq!(mov.operands, vec![
+            Operand::Register("RAX".to_string()),
+            Operand::Memory {
+                base: Some("RBX".to_string()),
+                index: None,
+                scale: 1,
+                displacement: 8,
+                size: 8,
+            },
+        ]);
+        assert_eq!(mov.reads, vec!["RBX".to_string()]);
+        assert_eq!(mov.writes, vec!["RAX".to_string()]);
+
+        assert!(insns[2].is_return);
+        assert!(!insns[2].is_branch);
+    }
+
+    #[test]
+    fn test_relocated_absolute_memory_reference() {
+        // PE32 linked at 0x400000, code at RVA 0x1000, loaded at 0x10000000
+        let code = [
+            0x8B, 0x05, 0x00, 0x20, 0x40, 0x00, // mov eax, [0x402000]
+            0xFF, 0x15, 0x08, 0x20, 0x40, 0x00, // call [0x402008]
+        ];
+        let layout = ImageLayout {
+            base_address: 0x1000_0000,
+            preferred_base: 0x40_0000,
+            code_rva: 0x1000,
+            // Only the first reference has a relocation entry
+            relocations: vec![Relocation { address: 0x1002, kind: RelocationKind::HighLow }],
+        };
+
+        let insns = Disassembler::disassemble_mapped(&code, &layout, Architecture::X8632, 10).unwrap();
+
+        assert_eq!(insns[0].address, 0x1000_1000);
+        assert_eq!(insns[1].address, 0x1000_1006);
+        assert!(matches!(
+            insns[0].operands[1],
+            Operand::Memory { base: None, displacement: 0x1000_2000, .. }
+        ));
+        assert!(matches!(
+            insns[1].operands[0],
+            Operand::Memory { base: None, displacement: 0x40_2008, .. }
+        ));
+    }
+
+    #[test]
+    fn test_elf_relative_relocation() {
+        let layout = ImageLayout {
+            base_address: 0x5555_0000_0000,
+            preferred_base: 0,
+            code_rva: 0x100,
+            relocations: vec![
+                Relocation { address: 0x108, kind: RelocationKind::Relative { addend: 0x2040 } },
+                // Outside the code being disassembled
+                Relocation { address: 0x400, kind: RelocationKind::Relative { addend: 0 } },
+            ],
+        };
+
+        let patched = layout.apply_relocations(&[0u8; 16], Architecture::X8664);
+        assert_eq!(&patched[..8], &[0u8; 8]);
+        assert_eq!(u64::from_le_bytes(patched[8..16].try_into().unwrap()), 0x5555_0000_2040);
+    }
+}
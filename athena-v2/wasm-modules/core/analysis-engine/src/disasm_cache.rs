@@ -0,0 +1,415 @@
+//! Disassembly and CFG Cache
+//! Lets repeated passes over the same binary within a session reuse
+//! earlier disassembly and control-flow results
+//!
+//! Entries are keyed by content hash, architecture and base address, and
+//! CFGs also by the entry point they were built from. A request with
+//! different disassembly options replaces the entry. Storage is behind
+//! `CacheStore` so hosts can swap in their own.
+
+use crate::code_regions::disassemble_reachable;
+use crate::disasm::{Architecture, BasicBlock, DisasmMode, DisassembledInstruction, Disassembler, Syntax};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Default number of binaries the in-memory store keeps
+pub const DEFAULT_CACHE_CAPACITY: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// SHA-256 of the code, hex encoded
+    pub content_id: String,
+    pub arch: Architecture,
+    pub base_address: u64,
+    /// Where the CFG was built from; `None` for disassembly
+    pub entry_point: Option<u64>,
+}
+
+impl CacheKey {
+    pub fn new(code: &[u8], arch: Architecture, base_address: u64) -> Self {
+        Self {
+            content_id: hex::encode(Sha256::digest(code)),
+            arch,
+            base_address,
+            entry_point: None,
+        }
+    }
+
+    /// Key for the CFG of `code` loaded at `base_address` and walked from
+    /// `entry_point`
+    pub fn for_entry_point(code: &[u8], arch: Architecture, base_address: u64, entry_point: u64) -> Self {
+        Self { entry_point: Some(entry_point), ..Self::new(code, arch, base_address) }
+    }
+}
+
+/// Options that change the disassembly output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisasmOptions {
+    pub syntax: Syntax,
+    pub max_instructions: u32,
+    pub mode: DisasmMode,
+    /// Addresses recursive descent starts from; empty means the base
+    /// address. Ignored by linear sweep.
+    pub seeds: Vec<u64>,
+}
+
+/// Everything cached for one binary
+#[derive(Clone, Default)]
+pub struct CachedAnalysis {
+    pub disassembly: Option<(DisasmOptions, Arc<Vec<DisassembledInstruction>>)>,
+    pub blocks: Option<Arc<Vec<BasicBlock>>>,
+}
+
+/// Where cached results live
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &CacheKey) -> Option<CachedAnalysis>;
+    fn insert(&self, key: CacheKey, entry: CachedAnalysis);
+    fn clear(&self);
+}
+
+/// Bounded in-memory store; evicts the oldest binary when full
+pub struct MemoryStore {
+    capacity: usize,
+    entries: Mutex<(HashMap<CacheKey, CachedAnalysis>, VecDeque<CacheKey>)>,
+}
+
+impl MemoryStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &CacheKey) -> Option<CachedAnalysis> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.0.get(key).cloned()
+    }
+
+    fn insert(&self, key: CacheKey, entry: CachedAnalysis) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let (map, order) = &mut *entries;
+        if map.insert(key.clone(), entry).is_none() {
+            order.push_back(key);
+            while order.len() > self.capacity {
+                if let Some(oldest) = order.pop_front() {
+                    map.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.0.clear();
+        entries.1.clear();
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+pub struct AnalysisCache<S: CacheStore = MemoryStore> {
+    store: S,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl AnalysisCache<MemoryStore> {
+    pub fn new() -> Self {
+        Self::with_store(MemoryStore::default())
+    }
+}
+
+impl Default for AnalysisCache<MemoryStore> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: CacheStore> AnalysisCache<S> {
+    pub fn with_store(store: S) -> Self {
+        Self {
+            store,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// `Disassembler::disassemble`, or recursive descent from the seeds (or
+    /// `base_address`), reusing an earlier result for the same code,
+    /// architecture, base address and options
+    pub fn disassemble(
+        &self,
+        code: &[u8],
+        base_address: u64,
+        arch: Architecture,
+        options: DisasmOptions,
+    ) -> Result<Arc<Vec<DisassembledInstruction>>, String> {
+        let key = CacheKey::new(code, arch, base_address);
+        let cached = self.store.get(&key);
+
+        if let Some((cached_options, instructions)) = cached.as_ref().and_then(|c| c.disassembly.as_ref()) {
+            if *cached_options == options {
+                self.hit();
+                return Ok(instructions.clone());
+            }
+        }
+        self.miss();
+
+        let instructions = Arc::new(match options.mode {
+            DisasmMode::LinearSweep => {
+                Disassembler::disassemble(code, base_address, arch, options.syntax, options.max_instructions)?
+            }
+            DisasmMode::RecursiveDescent => {
+                let seeds = if options.seeds.is_empty() { vec![base_address] } else { options.seeds.clone() };
+                let end = base_address + code.len() as u64;
+                if let Some(seed) = seeds.iter().find(|&&seed| seed < base_address || seed >= end) {
+                    return Err(format!(
+                        "Seed 0x{:x} is outside the code at 0x{:x}-0x{:x}",
+                        seed, base_address, end
+                    ));
+                }
+                let instructions = disassemble_reachable(
+                    code,
+                    base_address,
+                    &[],
+                    &seeds,
+                    arch,
+                    options.syntax,
+                    options.max_instructions,
+                )?;
+                if instructions.is_empty() && options.max_instructions > 0 {
+                    return Err(format!("No valid instructions at the seeds {:x?}", seeds));
+                }
+                instructions
+            }
+        });
+        // Different options invalidate everything cached for this binary
+        let entry = match cached {
+            Some(entry) if entry.disassembly.is_none() => entry,
+            _ => CachedAnalysis::default(),
+        };
+        self.store.insert(key, CachedAnalysis {
+            disassembly: Some((options, instructions.clone())),
+            ..entry
+        });
+        Ok(instructions)
+    }
+
+    /// `Disassembler::analyze_control_flow`, reusing an earlier result for
+    /// the same code, architecture and entry point
+    pub fn control_flow(&self, code: &[u8], entry_point: u64, arch: Architecture) -> Result<Arc<Vec<BasicBlock>>, String> {
+        // The code is loaded at the entry point
+        let key = CacheKey::for_entry_point(code, arch, entry_point, entry_point);
+        let cached = self.store.get(&key);
+
+        if let Some(blocks) = cached.as_ref().and_then(|c| c.blocks.as_ref()) {
+            self.hit();
+            return Ok(blocks.clone());
+        }
+        self.miss();
+
+        let blocks = Arc::new(Disassembler::analyze_control_flow(code, entry_point, arch)?);
+        self.store.insert(key, CachedAnalysis {
+            blocks: Some(blocks.clone()),
+            ..cached.unwrap_or_default()
+        });
+        Ok(blocks)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+    }
+
+    fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Cache shared by the component entry points for the lifetime of the instance
+pub fn session() -> &'static AnalysisCache {
+    static SESSION: OnceLock<AnalysisCache> = OnceLock::new();
+    SESSION.get_or_init(AnalysisCache::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &[u8] = &[
+        0x55,             // push rbp
+        0x48, 0x89, 0xE5, // mov rbp, rsp
+        0x74, 0x02,       // je +2
+        0x31, 0xC0,       // xor eax, eax
+        0x5D,             // pop rbp
+        0xC3,             // ret
+    ];
+
+    fn intel(max_instructions: u32) -> DisasmOptions {
+        DisasmOptions { syntax: Syntax::Intel, max_instructions, mode: DisasmMode::LinearSweep, seeds: Vec::new() }
+    }
+
+    #[test]
+    fn test_identical_request_hits_cache() {
+        let cache = AnalysisCache::new();
+
+        let first = cache.disassemble(CODE, 0x1000, Architecture::X8664, intel(100)).unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 1 });
+
+        let second = cache.disassemble(CODE, 0x1000, Architecture::X8664, intel(100)).unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // A different base address is a different entry
+        cache.disassemble(CODE, 0x2000, Architecture::X8664, intel(100)).unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+    }
+
+    #[test]
+    fn test_option_change_invalidates_entry() {
+        let cache = AnalysisCache::new();
+
+        cache.disassemble(CODE, 0x1000, Architecture::X8664, intel(100)).unwrap();
+        let att = DisasmOptions { syntax: Syntax::Att, ..intel(100) };
+        let instructions = cache.disassemble(CODE, 0x1000, Architecture::X8664, att.clone()).unwrap();
+        assert!(instructions[0].full_text.contains("%rbp"));
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 2 });
+
+        cache.disassemble(CODE, 0x1000, Architecture::X8664, att).unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2 });
+    }
+
+    #[test]
+    fn test_cfg_keyed_by_entry_point() {
+        let cache = AnalysisCache::new();
+
+        cache.control_flow(CODE, 0x1000, Architecture::X8664).unwrap();
+        cache.control_flow(CODE, 0x1000, Architecture::X8664).unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+
+        // Disassembly at the same address is a separate entry, and changing
+        // its options leaves the CFG alone
+        cache.disassemble(CODE, 0x1000, Architecture::X8664, intel(100)).unwrap();
+        cache.disassemble(CODE, 0x1000, Architecture::X8664, DisasmOptions { syntax: Syntax::Att, ..intel(100) }).unwrap();
+        cache.control_flow(CODE, 0x1000, Architecture::X8664).unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 3 });
+
+        assert_ne!(
+            CacheKey::new(CODE, Architecture::X8664, 0x1000),
+            CacheKey::for_entry_point(CODE, Architecture::X8664, 0x1000, 0x1000)
+        );
+        let blocks = cache.control_flow(CODE, 0x2000, Architecture::X8664).unwrap();
+        assert_eq!(blocks[0].start_offset, 0x2000);
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 4 });
+    }
+
+    #[test]
+    fn test_memory_store_evicts_oldest() {
+        let cache = AnalysisCache::with_store(MemoryStore::new(1));
+
+        cache.disassemble(CODE, 0x1000, Architecture::X8664, intel(100)).unwrap();
+        cache.disassemble(CODE, 0x2000, Architecture::X8664, intel(100)).unwrap();
+        cache.disassemble(CODE, 0x1000, Architecture::X8664, intel(100)).unwrap();
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 3 });
+    }
+
+    #[test]
+    fn test_recursive_descent_skips_embedded_data() {
+        let mut code = vec![
+            0x55,       // push rbp
+            0xEB, 0x10, // jmp +0x10
+        ];
+        // A table the jump skips, which decodes as bogus instructions that
+        // swallow the real code after it
+        code.extend_from_slice(&[0x48, 0xB8, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x48, 0x8D, 0x05, 0x11, 0x22, 0x33]);
+        code.extend_from_slice(&[
+            0x31, 0xC0, // xor eax, eax
+            0x5D,       // pop rbp
+            0xC3,       // ret
+        ]);
+        let data = 0x1003..0x1013;
+        let cache = AnalysisCache::new();
+
+        let linear = cache.disassemble(&code, 0x1000, Architecture::X8664, intel(100)).unwrap();
+        assert!(linear.iter().any(|i| data.contains(&i.offset)));
+        assert!(!linear.iter().any(|i| i.offset == 0x1013), "linear sweep is out of step after the data");
+
+        let options = DisasmOptions { mode: DisasmMode::RecursiveDescent, ..intel(100) };
+        let descent = cache.disassemble(&code, 0x1000, Architecture::X8664, options).unwrap();
+        let addresses: Vec<u64> = descent.iter().map(|i| i.offset).collect();
+        assert_eq!(addresses, vec![0x1000, 0x1001, 0x1013, 0x1015, 0x1016]);
+        assert!(descent.last().unwrap().is_return);
+    }
+
+    fn descent(max_instructions: u32, seeds: Vec<u64>) -> DisasmOptions {
+        DisasmOptions { mode: DisasmMode::RecursiveDescent, seeds, ..intel(max_instructions) }
+    }
+
+    #[test]
+    fn test_recursive_descent_cap_is_exact() {
+        let cache = AnalysisCache::new();
+        // 300 nops: more than a descent chunk, so a per-chunk check overshoots
+        let code = [0x90; 300];
+
+        for cap in [1, 3, 255, 257] {
+            let instructions = cache.disassemble(&code, 0x1000, Architecture::X8664, descent(cap, Vec::new())).unwrap();
+            assert_eq!(instructions.len(), cap as usize);
+        }
+    }
+
+    #[test]
+    fn test_recursive_descent_from_seeds() {
+        let cache = AnalysisCache::new();
+        // Two functions with nothing calling the second
+        let code = [
+            0x31, 0xC0, // xor eax, eax
+            0xC3,       // ret
+            0xCC,       // int3 padding
+            0x90,       // nop
+            0xC3,       // ret
+        ];
+
+        let from_base = cache.disassemble(&code, 0x1000, Architecture::X8664, descent(100, Vec::new())).unwrap();
+        assert_eq!(from_base.iter().map(|i| i.offset).collect::<Vec<_>>(), vec![0x1000, 0x1002]);
+
+        let seeded = cache.disassemble(&code, 0x1000, Architecture::X8664, descent(100, vec![0x1000, 0x1004])).unwrap();
+        assert_eq!(seeded.iter().map(|i| i.offset).collect::<Vec<_>>(), vec![0x1000, 0x1002, 0x1004, 0x1005]);
+    }
+
+    #[test]
+    fn test_recursive_descent_reports_failures() {
+        let cache = AnalysisCache::new();
+
+        let outside = cache.disassemble(CODE, 0x1000, Architecture::X8664, descent(100, vec![0x2000]));
+        assert!(outside.err().unwrap().contains("outside the code"));
+
+        // 0x06 (push es) doesn't exist in 64-bit mode
+        let invalid = cache.disassemble(&[0x06, 0x06], 0x1000, Architecture::X8664, descent(100, Vec::new()));
+        assert!(invalid.err().unwrap().contains("No valid instructions"));
+    }
+}
@@ -9,11 +9,60 @@
 /// - Trace API calls
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use crate::disasm::{DisassembledInstruction, Architecture, Syntax};
 
 /// Maximum memory state size (10MB limit to prevent DoS)
 const MAX_MEMORY_STATE: usize = 10 * 1024 * 1024;
 
+/// Bytes a single instruction may write (push/call of a 64-bit value)
+const MAX_WRITE_PER_INSTRUCTION: usize = 8;
+
+/// Default bytes read behind a pointer argument of a hooked API
+pub const DEFAULT_ARGUMENT_CAPTURE_BYTES: usize = 256;
+
+/// Most bytes captured per argument, whatever the configured depth
+const MAX_ARGUMENT_CAPTURE_BYTES: usize = 4096;
+
+/// Limits on an unpacking run. When any of them is reached, emulation stops
+/// and whatever has been unpacked so far is returned with
+/// `EmulationResult::budget_exhausted` set.
+#[derive(Clone, Debug)]
+pub struct UnpackBudget {
+    /// Maximum instructions to execute (prevent infinite loops)
+    pub max_steps: usize,
+    /// Maximum bytes of emulated memory, capped at `MAX_MEMORY_STATE`
+    pub max_memory: usize,
+    /// Maximum wall-clock time in milliseconds
+    pub max_wall_ms: u64,
+}
+
+impl Default for UnpackBudget {
+    fn default() -> Self {
+        Self {
+            max_steps: 100000,
+            max_memory: MAX_MEMORY_STATE,
+            max_wall_ms: 5000,
+        }
+    }
+}
+
+/// How much of each hooked API call's arguments to record
+#[derive(Clone, Debug)]
+pub struct ArgumentCapture {
+    /// Bytes read behind a pointer argument, capped at
+    /// `MAX_ARGUMENT_CAPTURE_BYTES`; 0 records raw values only
+    pub max_bytes: usize,
+}
+
+impl Default for ArgumentCapture {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_ARGUMENT_CAPTURE_BYTES,
+        }
+    }
+}
+
 /// Emulator state
 pub struct Emulator {
     /// Register state (name -> value)
@@ -28,8 +77,10 @@ pub struct Emulator {
     flags: u64,
     /// Execution trace
     trace: Vec<TraceEntry>,
-    /// Maximum instructions to execute (prevent infinite loops)
-    max_instructions: usize,
+    /// Limits on the emulation run
+    budget: UnpackBudget,
+    /// How deep API arguments are captured
+    argument_capture: ArgumentCapture,
     /// Current instruction count
     instruction_count: usize,
     /// API call hooks
@@ -63,6 +114,8 @@ pub struct EmulationResult {
     pub api_calls: Vec<ApiCall>,
     pub unpacked_code: Option<Vec<u8>>,
     pub trace: Vec<TraceEntry>,
+    /// Emulation was cut short by the `UnpackBudget`; results are partial
+    pub budget_exhausted: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -70,9 +123,25 @@ pub struct ApiCall {
     pub address: u64,
     pub name: String,
     pub arguments: Vec<u64>,
+    /// What each of `arguments` points to, in the same order
+    pub captured_arguments: Vec<ArgumentValue>,
     pub return_value: Option<u64>,
 }
 
+/// An API argument as captured from emulated memory. Strings not
+/// terminated within the capture limit are cut off at it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArgumentValue {
+    /// Not a pointer into emulated memory (or capture is off)
+    Value(u64),
+    /// NUL-terminated UTF-16LE string, as taken by the W APIs
+    WideString(String),
+    /// NUL-terminated ASCII string, as taken by the A APIs
+    AnsiString(String),
+    /// Other data behind the pointer
+    Bytes(Vec<u8>),
+}
+
 impl Emulator {
     pub fn new(entry_point: u64, stack_base: u64) -> Self {
         let mut registers = HashMap::new();
@@ -102,13 +171,26 @@ impl Emulator {
             sp: stack_base,
             flags: 0,
             trace: Vec::new(),
-            max_instructions: 100000, // Safety limit
+            budget: UnpackBudget::default(),
+            argument_capture: ArgumentCapture::default(),
             instruction_count: 0,
             api_hooks: HashMap::new(),
             modified_regions: Vec::new(),
         }
     }
 
+    /// Set the limits for `emulate`
+    pub fn with_budget(mut self, budget: UnpackBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Set how much of hooked API calls' arguments is recorded
+    pub fn with_argument_capture(mut self, capture: ArgumentCapture) -> Self {
+        self.argument_capture = capture;
+        self
+    }
+
     /// Load code into memory
     pub fn load_code(&mut self, base_address: u64, code: &[u8]) -> Result<(), String> {
         // Check if loading this code would exceed memory limit
@@ -139,7 +221,24 @@ impl Emulator {
         let mut api_calls = Vec::new();
         let mut modified_memory = Vec::new();
 
-        while self.instruction_count < self.max_instructions {
+        let start = Instant::now();
+        let max_wall = Duration::from_millis(self.budget.max_wall_ms);
+        // Leave room for the next instruction's writes so the hard memory
+        // limit in push/call is never what stops us
+        let max_memory = self.budget.max_memory
+            .min(MAX_MEMORY_STATE)
+            .saturating_sub(MAX_WRITE_PER_INSTRUCTION);
+        let mut budget_exhausted = false;
+
+        loop {
+            if self.instruction_count >= self.budget.max_steps
+                || self.memory.len() >= max_memory
+                || start.elapsed() >= max_wall
+            {
+                budget_exhausted = true;
+                break;
+            }
+
             // Check for API call hooks
             if let Some(api_name) = self.api_hooks.get(&self.ip) {
                 let call = self.handle_api_call(api_name.clone())?;
@@ -214,6 +313,7 @@ impl Emulator {
             api_calls,
             unpacked_code,
             trace: self.trace.clone(),
+            budget_exhausted,
         })
     }
 
@@ -413,6 +513,8 @@ impl Emulator {
             *self.registers.get("r9").unwrap_or(&0),
         ];
 
+        let captured_arguments = args.iter().map(|&arg| self.capture_argument(arg)).collect();
+
         // Simulate return value
         let return_value = Some(0);
         self.registers.insert("rax".to_string(), 0);
@@ -424,10 +526,44 @@ impl Emulator {
             address: self.ip,
             name: api_name,
             arguments: args,
+            captured_arguments,
             return_value,
         })
     }
 
+    /// Dereference `value` if it points into emulated memory, reading no
+    /// further than the capture limit or the first unmapped byte
+    fn capture_argument(&self, value: u64) -> ArgumentValue {
+        let limit = self.argument_capture.max_bytes.min(MAX_ARGUMENT_CAPTURE_BYTES) as u64;
+        let bytes: Vec<u8> = (0..limit)
+            .map_while(|i| value.checked_add(i).and_then(|addr| self.memory.get(&addr).copied()))
+            .collect();
+        if bytes.is_empty() {
+            return ArgumentValue::Value(value);
+        }
+
+        // "C\0:\0" is wide; plain ASCII never has a NUL second byte
+        if bytes.len() >= 2 && bytes[0] != 0 && bytes[1] == 0 {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .take_while(|&unit| unit != 0)
+                .collect();
+            if let Ok(text) = String::from_utf16(&units) {
+                if text.chars().all(|c| !c.is_control() || c.is_ascii_whitespace()) {
+                    return ArgumentValue::WideString(text);
+                }
+            }
+        }
+
+        let text: Vec<u8> = bytes.iter().copied().take_while(|&b| b != 0).collect();
+        if !text.is_empty() && text.iter().all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace()) {
+            return ArgumentValue::AnsiString(String::from_utf8_lossy(&text).into_owned());
+        }
+
+        ArgumentValue::Bytes(bytes)
+    }
+
     fn get_value(&self, operand: &str) -> Result<u64, String> {
         let operand = operand.trim();
 
@@ -673,6 +809,80 @@ mod tests {
         assert_eq!(unpacked[1], 0x48); // REX.W prefix
     }
 
+    #[test]
+    fn test_step_budget_returns_partial_results() {
+        // loop: push rax; jmp loop
+        let code = [0x50, 0xEB, 0xFD];
+        let mut emu = Emulator::new(0x1000, 0x10000).with_budget(UnpackBudget {
+            max_steps: 50,
+            ..Default::default()
+        });
+
+        let result = emu.emulate(&code, 0x1000).unwrap();
+
+        assert!(result.budget_exhausted);
+        assert_eq!(result.executed_instructions, 50);
+        assert_eq!(result.trace.len(), 50);
+        // The stack written so far is still dumped
+        let unpacked = result.unpacked_code.expect("partial dump");
+        assert_eq!(unpacked.len(), 25 * 8);
+    }
+
+    #[test]
+    fn test_emulation_within_budget() {
+        // xor rax, rax; ret
+        let code = [0x48, 0x31, 0xC0, 0xC3];
+        let mut emu = Emulator::new(0x1000, 0x10000);
+
+        let result = emu.emulate(&code, 0x1000).unwrap();
+
+        assert!(!result.budget_exhausted);
+        assert_eq!(result.executed_instructions, 2);
+    }
+
+    #[test]
+    fn test_create_file_path_argument_captured() {
+        // CreateFileW is hooked at the entry point; ret follows the call
+        let code = [0x90, 0x90, 0x90, 0x90, 0x90, 0xC3];
+        let path: Vec<u8> = "C:\\Users\\Public\\payload.dll\0"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+
+        let mut emu = Emulator::new(0x1000, 0x10000);
+        emu.add_api_hook(0x1000, "CreateFileW".to_string());
+        emu.load_code(0x3000, &path).unwrap();
+        emu.load_code(0x4000, b"rb\0").unwrap();
+        emu.registers.insert("rcx".to_string(), 0x3000);
+        emu.registers.insert("rdx".to_string(), 0x4000);
+        emu.registers.insert("r8".to_string(), 0x40000000);
+
+        let result = emu.emulate(&code, 0x1000).unwrap();
+
+        let call = &result.api_calls[0];
+        assert_eq!(call.name, "CreateFileW");
+        assert_eq!(call.arguments[0], 0x3000);
+        assert_eq!(
+            call.captured_arguments,
+            vec![
+                ArgumentValue::WideString("C:\\Users\\Public\\payload.dll".to_string()),
+                ArgumentValue::AnsiString("rb".to_string()),
+                ArgumentValue::Value(0x40000000),
+                ArgumentValue::Value(0),
+            ]
+        );
+
+        // The capture depth bounds how much of the path is read
+        let mut emu = Emulator::new(0x1000, 0x10000)
+            .with_argument_capture(ArgumentCapture { max_bytes: 6 });
+        emu.add_api_hook(0x1000, "CreateFileW".to_string());
+        emu.load_code(0x3000, &path).unwrap();
+        emu.registers.insert("rcx".to_string(), 0x3000);
+
+        let result = emu.emulate(&code, 0x1000).unwrap();
+        assert_eq!(result.api_calls[0].captured_arguments[0], ArgumentValue::WideString("C:\\".to_string()));
+    }
+
     #[test]
     fn test_has_code_patterns() {
         let emu = Emulator::new(0x1000, 0x10000);
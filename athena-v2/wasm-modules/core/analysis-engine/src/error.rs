@@ -0,0 +1,107 @@
+/// Structured errors for the component boundary
+///
+/// WIT functions report failures as `result<_, string>`. Instead of a free-form
+/// message the string carries a JSON-encoded `AnalysisError`, so the frontend can
+/// branch on `code` without parsing human-readable text.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InputTooLarge,
+    UnsupportedFormat,
+    InvalidInput,
+    AnalysisFailed,
+}
+
+#[derive(Error, Debug, Clone, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct AnalysisError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl AnalysisError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn input_too_large(size: usize, max: usize) -> Self {
+        Self::new(
+            ErrorCode::InputTooLarge,
+            format!("Input too large: {} bytes exceeds maximum of {} bytes", size, max),
+        )
+        .with_details(serde_json::json!({ "size": size, "max": max }))
+    }
+
+    pub fn unsupported_format(format: impl Into<String>) -> Self {
+        let format = format.into();
+        Self::new(ErrorCode::UnsupportedFormat, format!("Unsupported format: {}", format))
+            .with_details(serde_json::json!({ "format": format }))
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn analysis_failed(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::AnalysisFailed, message)
+    }
+
+    /// Serialize for the WIT error string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| {
+            format!(r#"{{"code":"ANALYSIS_FAILED","message":{:?}}}"#, self.message)
+        })
+    }
+}
+
+impl From<AnalysisError> for String {
+    fn from(err: AnalysisError) -> Self {
+        err.to_json()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{EngineConfig, DEFAULT_MAX_INPUT_SIZE};
+
+    #[test]
+    fn test_oversized_input_is_input_too_large() {
+        let err = EngineConfig::default().check_input_size(DEFAULT_MAX_INPUT_SIZE + 1).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InputTooLarge);
+
+        // What crosses the component boundary
+        let wire: String = err.into();
+        let json: serde_json::Value = serde_json::from_str(&wire).unwrap();
+        assert_eq!(json["code"], "INPUT_TOO_LARGE");
+        assert_eq!(json["details"]["size"], DEFAULT_MAX_INPUT_SIZE + 1);
+        assert_eq!(json["details"]["max"], DEFAULT_MAX_INPUT_SIZE);
+    }
+
+    #[test]
+    fn test_error_round_trip() {
+        let err = AnalysisError::unsupported_format("mach-o fat");
+        let parsed: AnalysisError = serde_json::from_str(&err.to_json()).unwrap();
+
+        assert_eq!(parsed.code, ErrorCode::UnsupportedFormat);
+        assert_eq!(parsed.message, "Unsupported format: mach-o fat");
+
+        let plain = AnalysisError::invalid_input("empty code buffer").to_json();
+        assert!(!plain.contains("details"));
+    }
+}
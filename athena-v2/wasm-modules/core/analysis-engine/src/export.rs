@@ -5,6 +5,7 @@
 
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use crate::demangle::demangle;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AnalysisExport {
@@ -86,6 +87,30 @@ pub enum AnnotationType {
     Note,
 }
 
+/// Rendering options for the HTML and C header exports
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    /// Show C++ and Rust import/export names demangled
+    pub demangle_names: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { demangle_names: true }
+    }
+}
+
+impl ExportOptions {
+    /// `name` as it should be shown
+    fn symbol(&self, name: &str) -> String {
+        if self.demangle_names {
+            demangle(name)
+        } else {
+            name.to_string()
+        }
+    }
+}
+
 pub struct Exporter;
 
 impl Exporter {
@@ -97,6 +122,11 @@ impl Exporter {
 
     /// Export to HTML report (for human-readable analysis)
     pub fn to_html(analysis: &AnalysisExport) -> String {
+        Self::to_html_with_options(analysis, &ExportOptions::default())
+    }
+
+    /// Export to HTML report, rendering names as `opts` asks
+    pub fn to_html_with_options(analysis: &AnalysisExport, opts: &ExportOptions) -> String {
         let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n");
         html.push_str("<title>Athena Analysis Report</title>\n");
         html.push_str("<style>\n");
@@ -140,7 +170,22 @@ impl Exporter {
             for imp in &analysis.imports {
                 html.push_str(&format!(
                     "<tr><td>{}</td><td>{}</td></tr>\n",
-                    imp.library, imp.function
+                    imp.library,
+                    html_escape(&opts.symbol(&imp.function))
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        // Exports
+        if !analysis.exports.is_empty() {
+            html.push_str("<h2>Exports</h2>\n");
+            html.push_str("<table>\n<tr><th>Address</th><th>Name</th></tr>\n");
+            for exp in &analysis.exports {
+                html.push_str(&format!(
+                    "<tr><td>0x{:x}</td><td>{}</td></tr>\n",
+                    exp.address,
+                    html_escape(&opts.symbol(&exp.name))
                 ));
             }
             html.push_str("</table>\n");
@@ -208,6 +253,11 @@ impl Exporter {
 
     /// Export to C header file (for integration with C/C++ tools)
     pub fn to_c_header(analysis: &AnalysisExport) -> String {
+        Self::to_c_header_with_options(analysis, &ExportOptions::default())
+    }
+
+    /// Export to C header file, noting demangled import names if `opts` asks
+    pub fn to_c_header_with_options(analysis: &AnalysisExport, opts: &ExportOptions) -> String {
         let mut c = String::from("/* Athena Analysis Export */\n");
         c.push_str(&format!("/* File: {} */\n", analysis.metadata.file_name));
         c.push_str(&format!("/* Hash: {} */\n\n", analysis.metadata.file_hash));
@@ -230,7 +280,16 @@ impl Exporter {
         if !analysis.imports.is_empty() {
             c.push_str("/* Imports */\n");
             for imp in &analysis.imports {
-                c.push_str(&format!("extern void {}(void); /* from {} */\n", imp.function, imp.library));
+                // Keep the linkable symbol, note what it demangles to
+                let demangled = opts.symbol(&imp.function);
+                if demangled == imp.function {
+                    c.push_str(&format!("extern void {}(void); /* from {} */\n", imp.function, imp.library));
+                } else {
+                    c.push_str(&format!(
+                        "extern void {}(void); /* {} from {} */\n",
+                        imp.function, demangled, imp.library
+                    ));
+                }
             }
             c.push_str("\n");
         }
@@ -311,4 +370,51 @@ mod tests {
         assert!(html.contains("<!DOCTYPE html>"));
         assert!(html.contains("Athena"));
     }
+
+    #[test]
+    fn test_import_export_names_demangled() {
+        let export = AnalysisExport {
+            metadata: ExportMetadata {
+                tool_name: "Athena".to_string(),
+                tool_version: "1.0.0".to_string(),
+                analysis_date: "2025-01-01".to_string(),
+                file_name: "libloader.so".to_string(),
+                file_hash: "abc123".to_string(),
+                file_type: "ELF64".to_string(),
+                architecture: "x86-64".to_string(),
+            },
+            functions: vec![],
+            xrefs: vec![],
+            strings: vec![],
+            imports: vec![ImportExport {
+                library: "libstdc++.so.6".to_string(),
+                function: "_ZNSt8ios_base4InitC1Ev".to_string(),
+                address: None,
+            }],
+            exports: vec![ExportSymbol {
+                name: "_ZN6loader7payload6decode17h0123456789abcdefE".to_string(),
+                address: 0x1000,
+            }],
+            cfg: None,
+            decompilation: HashMap::new(),
+            annotations: vec![],
+        };
+
+        let html = Exporter::to_html(&export);
+        assert!(html.contains("<td>std::ios_base::Init::Init()</td>"));
+        assert!(html.contains("<td>loader::payload::decode</td>"));
+
+        let header = Exporter::to_c_header(&export);
+        assert!(header.contains(
+            "extern void _ZNSt8ios_base4InitC1Ev(void); /* std::ios_base::Init::Init() from libstdc++.so.6 */"
+        ));
+
+        let raw = ExportOptions { demangle_names: false };
+        let html = Exporter::to_html_with_options(&export, &raw);
+        assert!(html.contains("<td>_ZNSt8ios_base4InitC1Ev</td>"));
+        assert!(html.contains("<td>_ZN6loader7payload6decode17h0123456789abcdefE</td>"));
+
+        let header = Exporter::to_c_header_with_options(&export, &raw);
+        assert!(header.contains("extern void _ZNSt8ios_base4InitC1Ev(void); /* from libstdc++.so.6 */"));
+    }
 }
@@ -0,0 +1,261 @@
+/// Compiler Idiom Recognition
+/// Collapses code sequences that compilers emit for speed back into the
+/// source-level construct they implement
+///
+/// Recognized idioms:
+/// - Unsigned division by a constant (multiply by a magic number, then shift)
+/// - Switch statements dispatched through jump tables
+///
+/// References:
+/// - "Division by Invariant Integers using Multiplication" by Granlund & Montgomery
+
+use std::collections::HashMap;
+use crate::decompiler::{IRStmt, IRValue, IRExpr, IROp};
+use crate::xrefs::{XrefDatabase, XrefType};
+
+/// Maximum depth when substituting local definitions into an expression
+const MAX_INLINE_DEPTH: usize = 4;
+
+/// Match `(x * M) >> s` where `M` is the magic multiplier for an unsigned
+/// division by `N`, returning `(x, N)`
+pub fn match_magic_division(expr: &IRExpr) -> Option<(IRValue, u64)> {
+    // Peel the shift chain: ((x * M) >> 32) >> 1 is the same as (x * M) >> 33
+    let mut shift: u32 = 0;
+    let mut current = expr;
+    loop {
+        match (&current.op, current.operands.as_slice()) {
+            (IROp::Shr, [IRValue::Expr(inner), IRValue::Const(amount)]) if (0..128).contains(amount) => {
+                shift += *amount as u32;
+                current = inner;
+            }
+            _ => break,
+        }
+    }
+
+    // The high half of a widening multiply is always taken, so anything
+    // shifted by less than 32 isn't a magic division
+    if !(32..128).contains(&shift) {
+        return None;
+    }
+
+    let (x, magic) = match (&current.op, current.operands.as_slice()) {
+        (IROp::Mul, [IRValue::Const(m), x]) | (IROp::Mul, [x, IRValue::Const(m)]) if *m > 1 => {
+            (x.clone(), *m as u128)
+        }
+        _ => return None,
+    };
+
+    let power = 1u128 << shift;
+    let divisor = (power + magic / 2) / magic;
+    if divisor < 2 || divisor > u64::MAX as u128 {
+        return None;
+    }
+
+    // M = ceil(2^s / N) is only exact for every w-bit x when the rounding
+    // error M*N - 2^s is at most 2^(s-w)
+    let operand_bits = if shift >= 64 { 64 } else { 32 };
+    let error = (magic * divisor).checked_sub(power)?;
+    if error > 1u128 << (shift - operand_bits) {
+        return None;
+    }
+
+    Some((x, divisor as u64))
+}
+
+/// Rewrite magic-number division sequences within a block as `x / N`.
+///
+/// Definitions are substituted locally so that a multiply and shift split
+/// across several statements are still recognized. Statements left unused by
+/// the rewrite are removed by dead code elimination.
+pub fn recover_magic_division(stmts: &mut [IRStmt]) {
+    let mut defs: HashMap<String, IRValue> = HashMap::new();
+
+    for stmt in stmts.iter_mut() {
+        match stmt {
+            IRStmt::Assign { dest, value } => {
+                let mut inlined = inline_value(value, &defs, 0);
+
+                if let IRValue::Expr(expr) = &inlined {
+                    if let Some((x, divisor)) = match_magic_division(expr) {
+                        *value = IRValue::Expr(Box::new(IRExpr::binary(
+                            IROp::Div,
+                            x,
+                            IRValue::Const(divisor as i64),
+                            dest.size,
+                        )));
+                        inlined = value.clone();
+                    }
+                }
+
+                // Forget anything computed from the old value of dest
+                defs.retain(|_, v| !mentions(v, &dest.name));
+                if mentions(&inlined, &dest.name) {
+                    defs.remove(&dest.name);
+                } else {
+                    defs.insert(dest.name.clone(), inlined);
+                }
+            }
+            IRStmt::Call { .. } | IRStmt::Store { .. } => defs.clear(),
+            _ => {}
+        }
+    }
+}
+
+fn inline_value(value: &IRValue, defs: &HashMap<String, IRValue>, depth: usize) -> IRValue {
+    if depth >= MAX_INLINE_DEPTH {
+        return value.clone();
+    }
+
+    match value {
+        IRValue::Var(var) => match defs.get(&var.name) {
+            Some(def) => inline_value(def, defs, depth + 1),
+            None => value.clone(),
+        },
+        IRValue::Const(_) => value.clone(),
+        IRValue::Expr(expr) => IRValue::Expr(Box::new(IRExpr {
+            op: expr.op.clone(),
+            operands: expr.operands.iter().map(|o| inline_value(o, defs, depth + 1)).collect(),
+            size: expr.size,
+        })),
+    }
+}
+
+fn mentions(value: &IRValue, name: &str) -> bool {
+    match value {
+        IRValue::Var(var) => var.name == name,
+        IRValue::Const(_) => false,
+        IRValue::Expr(expr) => expr.operands.iter().any(|o| mentions(o, name)),
+    }
+}
+
+/// Jump table dispatch recovered from cross-references
+#[derive(Clone, Debug)]
+pub struct JumpTable {
+    /// Address of the indirect jump
+    pub dispatch: u64,
+    /// Expression indexing the table
+    pub index: String,
+    /// Target for each table slot, in slot order
+    pub targets: Vec<u64>,
+}
+
+impl JumpTable {
+    /// Group case values by target, in order of first appearance
+    pub fn cases(&self) -> Vec<(Vec<u64>, u64)> {
+        let mut cases: Vec<(Vec<u64>, u64)> = Vec::new();
+        for (value, target) in self.targets.iter().enumerate() {
+            match cases.iter_mut().find(|(_, t)| t == target) {
+                Some((values, _)) => values.push(value as u64),
+                None => cases.push((vec![value as u64], *target)),
+            }
+        }
+        cases
+    }
+}
+
+/// Look up the jump table used by the indirect jump at `dispatch`.
+///
+/// Table entries are the `Jump` xrefs originating at the dispatch
+/// instruction; when the xref carries an offset it is the slot index.
+pub fn find_jump_table(xrefs: &XrefDatabase, dispatch: u64, operands: &str) -> Option<JumpTable> {
+    let mut entries: Vec<(u32, u64)> = xrefs.get_refs_from(dispatch)
+        .into_iter()
+        .filter(|x| x.xref_type == XrefType::Jump)
+        .enumerate()
+        .map(|(i, x)| (x.offset.unwrap_or(i as u32), x.to))
+        .collect();
+
+    // A single target is a plain jump, not a switch
+    if entries.len() < 2 {
+        return None;
+    }
+
+    entries.sort_by_key(|(slot, _)| *slot);
+
+    Some(JumpTable {
+        dispatch,
+        index: jump_table_index(operands),
+        targets: entries.into_iter().map(|(_, to)| to).collect(),
+    })
+}
+
+/// Extract the index register from `qword ptr [rax*8 + 0x401000]`
+fn jump_table_index(operands: &str) -> String {
+    let operands = operands.trim();
+    let Some(star) = operands.find('*') else {
+        return operands.to_string();
+    };
+
+    let start = operands[..star]
+        .rfind(|c: char| c == '[' || c == '+' || c.is_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    operands[start..star].trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::IRVar;
+    use crate::xrefs::Xref;
+
+    fn var(name: &str) -> IRValue {
+        IRValue::Var(IRVar::new(name.to_string(), 4))
+    }
+
+    fn shr(value: IRValue, amount: i64) -> IRValue {
+        IRValue::Expr(Box::new(IRExpr::binary(IROp::Shr, value, IRValue::Const(amount), 4)))
+    }
+
+    fn mul(left: IRValue, right: IRValue) -> IRValue {
+        IRValue::Expr(Box::new(IRExpr::binary(IROp::Mul, left, right, 4)))
+    }
+
+    #[test]
+    fn test_match_magic_division() {
+        // x / 3 (32-bit): (x * 0xAAAAAAAB) >> 33
+        let IRValue::Expr(expr) = shr(mul(var("ecx"), IRValue::Const(0xAAAA_AAAB)), 33) else { unreachable!() };
+        let (x, divisor) = match_magic_division(&expr).unwrap();
+        assert!(matches!(x, IRValue::Var(ref v) if v.name == "ecx"));
+        assert_eq!(divisor, 3);
+
+        // x / 10 split as high half then shift: ((x * 0xCCCCCCCD) >> 32) >> 3
+        let IRValue::Expr(expr) = shr(shr(mul(IRValue::Const(0xCCCC_CCCD), var("edi")), 32), 3) else { unreachable!() };
+        assert_eq!(match_magic_division(&expr).unwrap().1, 10);
+    }
+
+    #[test]
+    fn test_non_magic_multiply_is_ignored() {
+        let IRValue::Expr(expr) = shr(mul(var("ecx"), IRValue::Const(12345)), 33) else { unreachable!() };
+        assert!(match_magic_division(&expr).is_none());
+
+        let IRValue::Expr(expr) = shr(mul(var("ecx"), IRValue::Const(0xAAAA_AAAB)), 4) else { unreachable!() };
+        assert!(match_magic_division(&expr).is_none());
+    }
+
+    #[test]
+    fn test_find_jump_table() {
+        let mut xrefs = XrefDatabase::new();
+        for (slot, target) in [0x500u64, 0x520, 0x500, 0x540].iter().enumerate() {
+            xrefs.add_xref(Xref {
+                from: 0x400,
+                to: *target,
+                xref_type: XrefType::Jump,
+                instruction: None,
+                offset: Some(slot as u32),
+            });
+        }
+
+        let table = find_jump_table(&xrefs, 0x400, "qword ptr [rax*8 + 0x401000]").unwrap();
+        assert_eq!(table.index, "rax");
+        assert_eq!(table.targets, vec![0x500, 0x520, 0x500, 0x540]);
+        assert_eq!(table.cases(), vec![
+            (vec![0, 2], 0x500),
+            (vec![1], 0x520),
+            (vec![3], 0x540),
+        ]);
+
+        assert!(find_jump_table(&xrefs, 0x999, "rax").is_none());
+    }
+}
@@ -1,16 +1,28 @@
 // Component Model implementation
 mod component;
 
+pub mod config;
+pub mod analysis;
+pub mod bench;
+pub mod error;
 pub mod patterns;
+pub mod loaders;
 pub mod deobfuscator;
 pub mod disasm;
+pub mod disasm_cache;
 pub mod arm_disasm;
+pub mod code_regions;
+pub mod pe_seeds;
+pub mod relocations;
+pub mod demangle;
 pub mod decompiler;
+pub mod idioms;
 pub mod emulator;
 pub mod type_inference;
 pub mod function_analysis;
 pub mod ssa;
 pub mod xrefs;
+pub mod taint;
 pub mod cfg;
 pub mod cape_parser;
 pub mod export;
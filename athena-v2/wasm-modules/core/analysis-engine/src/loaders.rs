@@ -0,0 +1,348 @@
+//! Reflective Loader and Manual-Mapping Detection
+//! Recognizes shellcode that loads a PE image itself instead of going
+//! through the OS loader (MITRE T1620, Reflective Code Loading)
+//!
+//! A reflective loader finds kernel32 by walking the PEB's module list,
+//! resolves its imports by walking export tables (usually comparing name
+//! hashes), then applies base relocations to the copied image. A manual
+//! mapper does the same header parsing and relocation work on a buffer it
+//! was handed. Each step is recognized from the instructions it needs, so
+//! the stub is found whatever it is embedded in.
+
+use iced_x86::{Decoder, DecoderOptions, Instruction, Mnemonic, OpKind, Register};
+use serde::{Deserialize, Serialize};
+
+pub const REFLECTIVE_CODE_LOADING: &str = "T1620";
+
+/// Behavior that shows intent on its own, with the evidence for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuspiciousBehavior {
+    pub name: String,
+    pub description: String,
+    pub confidence: f32,
+    pub mitre_attack: Vec<String>,
+    /// What was seen and where, e.g. "PEB access at 0x1a"
+    pub evidence: Vec<String>,
+}
+
+/// How often the decode loop polls for interruption
+const POLL_INTERVAL: usize = 4096;
+
+/// Bytes of code a stub's steps have to sit within, about one loader
+/// function. Without it, a header check in the CRT and page arithmetic
+/// elsewhere in a large binary add up to a loader.
+const STEP_WINDOW: u64 = 0x800;
+
+/// Every offset each loader step was seen at, in ascending order
+#[derive(Debug, Default)]
+struct StepOffsets {
+    peb_access: Vec<u64>,
+    name_hashing: Vec<u64>,
+    e_lfanew: Vec<u64>,
+    export_directory: Vec<u64>,
+    mz_check: Vec<u64>,
+    pe_check: Vec<u64>,
+    reloc_type: Vec<u64>,
+    reloc_offset: Vec<u64>,
+}
+
+impl StepOffsets {
+    /// The first occurrence of each step in `start..start + STEP_WINDOW`
+    fn window(&self, start: u64) -> LoaderSteps {
+        let end = start.saturating_add(STEP_WINDOW);
+        let first = |offsets: &[u64]| {
+            let i = offsets.partition_point(|&offset| offset < start);
+            offsets.get(i).copied().filter(|&offset| offset < end)
+        };
+        LoaderSteps {
+            peb_access: first(&self.peb_access),
+            name_hashing: first(&self.name_hashing),
+            e_lfanew: first(&self.e_lfanew),
+            export_directory: first(&self.export_directory),
+            mz_check: first(&self.mz_check),
+            pe_check: first(&self.pe_check),
+            reloc_type: first(&self.reloc_type),
+            reloc_offset: first(&self.reloc_offset),
+        }
+    }
+
+    /// Where windows worth judging start: at a relocation step, since
+    /// every verdict needs one, or at any step before it in range
+    fn window_starts(&self) -> Vec<u64> {
+        let mut starts: Vec<u64> = [
+            &self.peb_access,
+            &self.name_hashing,
+            &self.e_lfanew,
+            &self.export_directory,
+            &self.mz_check,
+            &self.pe_check,
+            &self.reloc_type,
+            &self.reloc_offset,
+        ]
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect();
+        starts.sort_unstable();
+        starts.dedup();
+        starts
+    }
+}
+
+/// First offset each loader step was seen at within one window
+#[derive(Debug, Default)]
+struct LoaderSteps {
+    peb_access: Option<u64>,
+    name_hashing: Option<u64>,
+    e_lfanew: Option<u64>,
+    export_directory: Option<u64>,
+    mz_check: Option<u64>,
+    pe_check: Option<u64>,
+    reloc_type: Option<u64>,
+    reloc_offset: Option<u64>,
+}
+
+impl LoaderSteps {
+    fn export_resolution(&self) -> Option<u64> {
+        self.name_hashing.or(self.e_lfanew.and(self.export_directory))
+    }
+
+    fn header_parsing(&self) -> Option<u64> {
+        self.mz_check.and(self.pe_check)
+    }
+
+    fn relocation_processing(&self) -> Option<u64> {
+        self.reloc_type.and(self.reloc_offset)
+    }
+
+    fn evidence(&self) -> Vec<String> {
+        [
+            ("PEB access", self.peb_access),
+            ("API name hashing", self.name_hashing),
+            ("Export directory walk", self.e_lfanew.and(self.export_directory)),
+            ("MZ/PE header check", self.header_parsing()),
+            ("Base relocation processing", self.relocation_processing()),
+        ]
+        .into_iter()
+        .filter_map(|(step, offset)| offset.map(|offset| format!("{} at {:#x}", step, offset)))
+        .collect()
+    }
+}
+
+fn immediate(instr: &Instruction, operand: u32) -> Option<u64> {
+    match instr.op_kind(operand) {
+        OpKind::Immediate8
+        | OpKind::Immediate16
+        | OpKind::Immediate32
+        | OpKind::Immediate64
+        | OpKind::Immediate8to16
+        | OpKind::Immediate8to32
+        | OpKind::Immediate8to64
+        | OpKind::Immediate32to64 => Some(instr.immediate(operand)),
+        _ => None,
+    }
+}
+
+fn has_memory_operand(instr: &Instruction) -> bool {
+    (0..instr.op_count()).any(|i| instr.op_kind(i) == OpKind::Memory)
+}
+
+fn record(offsets: &mut Vec<u64>, offset: u64) {
+    offsets.push(offset);
+}
+
+/// Linear sweep of `data` as `bitness`-bit code, noting loader steps
+fn scan_steps(data: &[u8], bitness: u32, interrupted: &impl Fn() -> bool) -> Option<StepOffsets> {
+    // PEB pointer: fs:[0x30] on x86, gs:[0x60] on x64
+    let (peb_segment, peb_offset, export_dir_offset) = match bitness {
+        32 => (Register::FS, 0x30, 0x78),
+        _ => (Register::GS, 0x60, 0x88),
+    };
+
+    let mut steps = StepOffsets::default();
+    let mut decoder = Decoder::with_ip(bitness, data, 0, DecoderOptions::NONE);
+    let mut instr = Instruction::default();
+    let mut decoded = 0usize;
+
+    while decoder.can_decode() {
+        decoded += 1;
+        if decoded.is_multiple_of(POLL_INTERVAL) && interrupted() {
+            return None;
+        }
+
+        decoder.decode_out(&mut instr);
+        if instr.is_invalid() {
+            continue;
+        }
+        let offset = instr.ip();
+
+        if has_memory_operand(&instr) {
+            let displacement = instr.memory_displacement64();
+            if instr.segment_prefix() == peb_segment && displacement == peb_offset {
+                record(&mut steps.peb_access, offset);
+            }
+            if instr.memory_base() != Register::None && instr.segment_prefix() == Register::None {
+                if displacement == 0x3C {
+                    record(&mut steps.e_lfanew, offset);
+                } else if displacement == export_dir_offset {
+                    record(&mut steps.export_directory, offset);
+                }
+            }
+        }
+
+        match (instr.mnemonic(), immediate(&instr, 1)) {
+            // ROR-13 is the classic shellcode API hash
+            (Mnemonic::Ror | Mnemonic::Rol, Some(0x0D)) => record(&mut steps.name_hashing, offset),
+            (Mnemonic::Cmp, Some(0x5A4D)) => record(&mut steps.mz_check, offset),
+            (Mnemonic::Cmp, Some(0x4550)) => record(&mut steps.pe_check, offset),
+            // A relocation entry is a 4-bit type and a 12-bit page offset
+            (Mnemonic::Shr, Some(0x0C)) => record(&mut steps.reloc_type, offset),
+            (Mnemonic::And, Some(0x0FFF)) => record(&mut steps.reloc_offset, offset),
+            _ => {}
+        }
+    }
+
+    Some(steps)
+}
+
+fn judge(steps: &LoaderSteps) -> Option<SuspiciousBehavior> {
+    // Both kinds of loader have to relocate the image they map
+    steps.relocation_processing()?;
+
+    if steps.peb_access.is_some() && steps.export_resolution().is_some() {
+        let confidence = if steps.header_parsing().is_some() { 0.95 } else { 0.9 };
+        return Some(SuspiciousBehavior {
+            name: "Reflective DLL loader".to_string(),
+            description: "Shellcode walks the PEB and export tables to resolve APIs itself and applies base relocations, as reflective DLL loaders do".to_string(),
+            confidence,
+            mitre_attack: vec![REFLECTIVE_CODE_LOADING.to_string()],
+            evidence: steps.evidence(),
+        });
+    }
+
+    steps.header_parsing().map(|_| SuspiciousBehavior {
+        name: "Manual PE mapping".to_string(),
+        description: "Code validates MZ/PE headers and applies base relocations to map an image without the OS loader".to_string(),
+        confidence: 0.8,
+        mitre_attack: vec![REFLECTIVE_CODE_LOADING.to_string()],
+        evidence: steps.evidence(),
+    })
+}
+
+/// Find reflective loading and manual-mapping stubs in `data`
+pub fn detect_loaders(data: &[u8]) -> Vec<SuspiciousBehavior> {
+    detect_loaders_interruptible(data, || false).unwrap_or_default()
+}
+
+/// Like `detect_loaders`, but gives up and returns `None` once
+/// `interrupted` returns true
+pub fn detect_loaders_interruptible(data: &[u8], interrupted: impl Fn() -> bool) -> Option<Vec<SuspiciousBehavior>> {
+    let mut best: Option<SuspiciousBehavior> = None;
+    for bitness in [32, 64] {
+        let offsets = scan_steps(data, bitness, &interrupted)?;
+        // Decoding in the wrong mode can still turn up some of the steps,
+        // so keep only the strongest reading of the stub
+        for (i, start) in offsets.window_starts().into_iter().enumerate() {
+            if i % POLL_INTERVAL == POLL_INTERVAL - 1 && interrupted() {
+                return None;
+            }
+            if let Some(behavior) = judge(&offsets.window(start)) {
+                if best.as_ref().is_none_or(|b| behavior.confidence > b.confidence) {
+                    best = Some(behavior);
+                }
+            }
+        }
+    }
+    Some(best.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// x86 reflective loader skeleton: PEB walk to the module list, ROR-13
+    /// export name hashing, header checks and a relocation block loop
+    const REFLECTIVE_STUB: &[u8] = &[
+        0x64, 0xA1, 0x30, 0x00, 0x00, 0x00, // mov eax, fs:[0x30]
+        0x8B, 0x40, 0x0C,                   // mov eax, [eax+0xC]   ; PEB->Ldr
+        0x8B, 0x70, 0x14,                   // mov esi, [eax+0x14]  ; InMemoryOrderModuleList
+        0xAD,                               // lodsd
+        0x8B, 0x58, 0x10,                   // mov ebx, [eax+0x10]  ; DllBase
+        0x8B, 0x53, 0x3C,                   // mov edx, [ebx+0x3C]  ; e_lfanew
+        0x8B, 0x54, 0x1A, 0x78,             // mov edx, [edx+ebx+0x78] ; export directory
+        0x31, 0xFF,                         // xor edi, edi
+        0xAC,                               // lodsb
+        0xC1, 0xCF, 0x0D,                   // ror edi, 0xD
+        0x01, 0xC7,                         // add edi, eax
+        0x66, 0x81, 0x3B, 0x4D, 0x5A,       // cmp word [ebx], 0x5A4D ; 'MZ'
+        0x81, 0x3A, 0x50, 0x45, 0x00, 0x00, // cmp dword [edx], 0x4550 ; 'PE'
+        0x0F, 0xB7, 0x0E,                   // movzx ecx, word [esi]   ; relocation entry
+        0x89, 0xC8,                         // mov eax, ecx
+        0xC1, 0xE8, 0x0C,                   // shr eax, 0xC            ; type
+        0x81, 0xE1, 0xFF, 0x0F, 0x00, 0x00, // and ecx, 0xFFF          ; page offset
+        0x01, 0x14, 0x0B,                   // add [ebx+ecx], edx      ; apply delta
+        0xC3,                               // ret
+    ];
+
+    #[test]
+    fn test_reflective_loader_detected() {
+        let mut data = vec![0x90; 64];
+        data.extend_from_slice(REFLECTIVE_STUB);
+        data.extend_from_slice(&[0xCC; 32]);
+
+        let behaviors = detect_loaders(&data);
+
+        assert_eq!(behaviors.len(), 1);
+        let behavior = &behaviors[0];
+        assert_eq!(behavior.name, "Reflective DLL loader");
+        assert_eq!(behavior.mitre_attack, vec!["T1620"]);
+        assert!(behavior.confidence >= 0.9);
+        assert!(behavior.evidence.iter().any(|e| e == "PEB access at 0x40"));
+        assert!(behavior.evidence.iter().any(|e| e.starts_with("Base relocation processing")));
+    }
+
+    #[test]
+    fn test_scattered_steps_in_benign_pe_not_flagged() {
+        // A CRT-style image base check, as MSVC's startup code has
+        const VALIDATE_IMAGE_BASE: &[u8] = &[
+            0x8B, 0x44, 0x24, 0x04,             // mov eax, [esp+4]
+            0x66, 0x81, 0x38, 0x4D, 0x5A,       // cmp word [eax], 0x5A4D
+            0x75, 0x10,                         // jne fail
+            0x8B, 0x48, 0x3C,                   // mov ecx, [eax+0x3C]
+            0x81, 0x3C, 0x01, 0x50, 0x45, 0x00, 0x00, // cmp dword [ecx+eax], 0x4550
+            0xC3,                               // ret
+        ];
+        // Page arithmetic in an allocator, much further on
+        const PAGE_MATH: &[u8] = &[
+            0x89, 0xC8,                         // mov eax, ecx
+            0xC1, 0xE8, 0x0C,                   // shr eax, 0xC
+            0x81, 0xE1, 0xFF, 0x0F, 0x00, 0x00, // and ecx, 0xFFF
+            0xC3,                               // ret
+        ];
+
+        let mut image = vec![0u8; 0x400];
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+        image.extend_from_slice(VALIDATE_IMAGE_BASE);
+        image.resize(0x400 + 0x4000, 0xCC);
+        image.extend_from_slice(PAGE_MATH);
+        image.resize(image.len() + 0x100, 0xCC);
+        assert!(detect_loaders(&image).is_empty());
+
+        // The same instructions in one function are a manual mapper
+        let mut mapper = VALIDATE_IMAGE_BASE[..VALIDATE_IMAGE_BASE.len() - 1].to_vec();
+        mapper.extend_from_slice(PAGE_MATH);
+        let behaviors = detect_loaders(&mapper);
+        assert_eq!(behaviors.len(), 1);
+        assert_eq!(behaviors[0].name, "Manual PE mapping");
+    }
+
+    #[test]
+    fn test_peb_access_alone_not_flagged() {
+        // Reading the PEB (e.g. BeingDebugged) is common in ordinary code
+        let data = [0x64, 0xA1, 0x30, 0x00, 0x00, 0x00, 0x0F, 0xB6, 0x40, 0x02, 0xC3];
+
+        assert!(detect_loaders(&data).is_empty());
+    }
+}
@@ -33,8 +33,12 @@ pub enum PatternCategory {
     Phishing,
 }
 
+/// Bytes of surrounding text kept on each side of a match by default
+pub const DEFAULT_CONTEXT_SIZE: usize = 50;
+
 pub struct PatternMatcher {
     patterns: Vec<CompiledPattern>,
+    context_size: usize,
 }
 
 pub struct CompiledPattern {
@@ -45,7 +49,13 @@ pub struct CompiledPattern {
 impl PatternMatcher {
     pub fn new() -> Self {
         let patterns = Self::load_default_patterns();
-        PatternMatcher { patterns }
+        PatternMatcher { patterns, context_size: DEFAULT_CONTEXT_SIZE }
+    }
+
+    /// Keep `context_size` bytes of text on each side of a match
+    pub fn with_context_size(mut self, context_size: usize) -> Self {
+        self.context_size = context_size;
+        self
     }
 
     fn load_default_patterns() -> Vec<CompiledPattern> {
@@ -95,10 +105,19 @@ impl PatternMatcher {
     }
 
     pub fn scan(&self, content: &[u8]) -> Vec<PatternMatch> {
+        self.scan_interruptible(content, || false).unwrap_or_default()
+    }
+
+    /// Like `scan`, but checks `interrupted` between patterns and returns
+    /// `None` as soon as it reports true
+    pub fn scan_interruptible(&self, content: &[u8], interrupted: impl Fn() -> bool) -> Option<Vec<PatternMatch>> {
         let text = String::from_utf8_lossy(content);
         let mut matches = Vec::new();
 
         for compiled in &self.patterns {
+            if interrupted() {
+                return None;
+            }
             if let Some(m) = compiled.regex.find(&text) {
                 matches.push(PatternMatch {
                     pattern: compiled.pattern.clone(),
@@ -109,16 +128,23 @@ impl PatternMatcher {
             }
         }
 
-        matches
+        matches.sort_by(|a, b| a.offset.cmp(&b.offset).then_with(|| a.pattern.name.cmp(&b.pattern.name)));
+        Some(matches)
     }
 
+    /// Text around a match, widened to the nearest character boundaries so
+    /// multi-byte characters are never split
     fn extract_context(&self, text: &str, offset: usize, length: usize) -> String {
-        const CONTEXT_SIZE: usize = 50;
-        
-        let start = offset.saturating_sub(CONTEXT_SIZE);
-        let end = (offset + length + CONTEXT_SIZE).min(text.len());
-        
-        text[start..end].to_string()
+        let mut start = offset.saturating_sub(self.context_size).min(text.len());
+        while !text.is_char_boundary(start) {
+            start -= 1;
+        }
+        let mut end = offset.saturating_add(length).saturating_add(self.context_size).min(text.len());
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+
+        text.get(start..end).unwrap_or_default().to_string()
     }
 }
 
@@ -128,4 +154,23 @@ pub struct PatternMatch {
     pub offset: usize,
     pub length: usize,
     pub context: String,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_respects_multibyte_boundaries() {
+        let matcher = PatternMatcher::new().with_context_size(2);
+
+        // Invalid bytes become 3-byte U+FFFD, so 2 bytes back lands mid-character
+        let matches = matcher.scan(b"\xff\xff\xffeval(atob('YQ=='))");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].offset, 9);
+        assert_eq!(matches[0].context, "\u{FFFD}eval(atob('Y");
+
+        let matches = matcher.scan("eval(atob(€€".as_bytes());
+        assert_eq!(matches[0].context, "eval(atob(€");
+    }
+}
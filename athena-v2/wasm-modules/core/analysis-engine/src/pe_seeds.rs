@@ -0,0 +1,560 @@
+//! PE Disassembly Seeding
+//! Starts recursive descent where the loader and other modules actually
+//! enter an image, instead of sweeping it linearly
+//!
+//! The entry point, every exported function and every TLS callback are
+//! places execution can begin. Code reachable only from an export (a DLL's
+//! real payload behind a do-nothing `DllMain`) or only from a TLS callback
+//! (anti-debug that runs before the entry point) is missed when descent
+//! starts from the entry point alone.
+
+use crate::code_regions::{disassemble_reachable, SectionInfo};
+use crate::disasm::{Architecture, DisassembledInstruction, Relocation, RelocationKind, Syntax};
+use crate::xrefs::{CallGraph, XrefBuilder};
+use serde::{Deserialize, Serialize};
+
+const IMAGE_DIRECTORY_ENTRY_EXPORT: usize = 0;
+const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+const IMAGE_DIRECTORY_ENTRY_TLS: usize = 9;
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x0000_0020;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+const PE32_MAGIC: u16 = 0x10B;
+const PE32_PLUS_MAGIC: u16 = 0x20B;
+
+const SECTION_HEADER_SIZE: usize = 40;
+
+/// Base relocation types; `IMAGE_REL_BASED_ABSOLUTE` pads a block and
+/// anything else is left unapplied
+const IMAGE_REL_BASED_HIGHLOW: u16 = 3;
+const IMAGE_REL_BASED_DIR64: u16 = 10;
+
+/// Images mapped larger than this are truncated; a `SizeOfImage` in the
+/// gigabytes is a malformed or hostile header, not a real image
+const MAX_MAPPED_SIZE: usize = 64 * 1024 * 1024;
+
+/// Exports and TLS callbacks read per image, so a corrupt count can't make
+/// us walk the whole file
+const MAX_SEEDS_PER_KIND: usize = 4096;
+
+/// Which addresses to start recursive descent from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeedConfig {
+    pub entry_point: bool,
+    pub exports: bool,
+    pub tls_callbacks: bool,
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        Self {
+            entry_point: true,
+            exports: true,
+            tls_callbacks: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RawSection {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_offset: u32,
+    raw_size: u32,
+    characteristics: u32,
+}
+
+impl RawSection {
+    fn executable(&self) -> bool {
+        self.characteristics & (IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_CNT_CODE) != 0
+    }
+
+    /// Bytes the section occupies once mapped
+    fn mapped_size(&self) -> u32 {
+        self.virtual_size.max(self.raw_size)
+    }
+}
+
+/// The parts of a PE's headers that say where code starts. Addresses are
+/// virtual addresses, i.e. `image_base` plus the RVA.
+#[derive(Clone, Debug)]
+pub struct PeLayout {
+    pub image_base: u64,
+    pub arch: Architecture,
+    pub entry_point: Option<u64>,
+    /// Exported functions, without forwarders (which point at a string)
+    pub exports: Vec<u64>,
+    pub tls_callbacks: Vec<u64>,
+    sections: Vec<RawSection>,
+    headers_size: usize,
+    /// RVA and size of the `.reloc` table
+    base_relocations: Option<(u32, u32)>,
+}
+
+impl PeLayout {
+    /// Read the headers of `file`
+    pub fn parse(file: &[u8]) -> Result<Self, String> {
+        if file.get(..2) != Some(b"MZ") {
+            return Err("Missing MZ signature".to_string());
+        }
+        let pe = read_u32(file, 0x3C).ok_or("Truncated DOS header")? as usize;
+        if file.get(pe..pe + 4) != Some(b"PE\0\0") {
+            return Err("Missing PE signature".to_string());
+        }
+
+        let machine = read_u16(file, pe + 4).ok_or("Truncated file header")?;
+        let arch = Architecture::from_pe_machine(machine)
+            .ok_or_else(|| format!("Unsupported machine type {:#x}", machine))?;
+        let section_count = read_u16(file, pe + 6).ok_or("Truncated file header")? as usize;
+        let optional_size = read_u16(file, pe + 20).ok_or("Truncated file header")? as usize;
+
+        let optional = pe + 24;
+        let magic = read_u16(file, optional).ok_or("Truncated optional header")?;
+        let (image_base, directory_count_at) = match magic {
+            PE32_MAGIC => (read_u32(file, optional + 28).map(u64::from), optional + 92),
+            PE32_PLUS_MAGIC => (read_u64(file, optional + 24), optional + 108),
+            _ => return Err(format!("Unknown optional header magic {:#x}", magic)),
+        };
+        let image_base = image_base.ok_or("Truncated optional header")?;
+        let entry_rva = read_u32(file, optional + 16).ok_or("Truncated optional header")?;
+        let headers_size = read_u32(file, optional + 60).unwrap_or(0) as usize;
+        let directory_count = read_u32(file, directory_count_at).unwrap_or(0) as usize;
+        let directory = |index: usize| {
+            if index >= directory_count {
+                return None;
+            }
+            let at = directory_count_at + 4 + index * 8;
+            let rva = read_u32(file, at)?;
+            let size = read_u32(file, at + 4)?;
+            (rva != 0).then_some((rva, size))
+        };
+
+        let sections_at = optional + optional_size;
+        let sections = (0..section_count)
+            .map_while(|i| {
+                let at = sections_at + i * SECTION_HEADER_SIZE;
+                Some(RawSection {
+                    virtual_size: read_u32(file, at + 8)?,
+                    virtual_address: read_u32(file, at + 12)?,
+                    raw_size: read_u32(file, at + 16)?,
+                    raw_offset: read_u32(file, at + 20)?,
+                    characteristics: read_u32(file, at + 36)?,
+                })
+            })
+            .collect();
+
+        let mut layout = Self {
+            image_base,
+            arch,
+            // A DLL with no DllMain has an entry point of zero
+            entry_point: (entry_rva != 0).then(|| image_base.checked_add(entry_rva as u64)).flatten(),
+            exports: Vec::new(),
+            tls_callbacks: Vec::new(),
+            sections,
+            headers_size,
+            base_relocations: directory(IMAGE_DIRECTORY_ENTRY_BASERELOC),
+        };
+
+        if let Some((rva, size)) = directory(IMAGE_DIRECTORY_ENTRY_EXPORT) {
+            layout.exports = layout.read_exports(file, rva, size);
+        }
+        if let Some((rva, _)) = directory(IMAGE_DIRECTORY_ENTRY_TLS) {
+            layout.tls_callbacks = layout.read_tls_callbacks(file, rva, magic == PE32_PLUS_MAGIC);
+        }
+
+        Ok(layout)
+    }
+
+    /// Executable and non-executable sections at their virtual addresses;
+    /// a section the image base pushes past the address space is left out
+    pub fn sections(&self) -> Vec<SectionInfo> {
+        self.sections
+            .iter()
+            .filter_map(|s| {
+                Some(SectionInfo {
+                    address: self.image_base.checked_add(s.virtual_address as u64)?,
+                    size: s.mapped_size() as u64,
+                    executable: s.executable(),
+                })
+            })
+            .collect()
+    }
+
+    /// Addresses to start descent from, entry point first
+    pub fn seeds(&self, config: &SeedConfig) -> Vec<u64> {
+        let mut seeds = Vec::new();
+        if config.entry_point {
+            seeds.extend(self.entry_point);
+        }
+        if config.tls_callbacks {
+            seeds.extend(&self.tls_callbacks);
+        }
+        if config.exports {
+            seeds.extend(&self.exports);
+        }
+        // An export can be the entry point itself
+        let mut seen = std::collections::HashSet::new();
+        seeds.retain(|seed| seen.insert(*seed));
+        seeds
+    }
+
+    /// Mark the seeds as call graph roots, so callees of an export or TLS
+    /// callback get a call depth like those of the entry point
+    pub fn add_entry_points(&self, config: &SeedConfig, call_graph: &mut CallGraph) {
+        for seed in self.seeds(config) {
+            call_graph.add_entry_point(seed);
+        }
+    }
+
+    /// The image as the loader would map it: headers, then each section's
+    /// raw data at its RVA, zero-filled up to its virtual size
+    pub fn map_image(&self, file: &[u8]) -> Vec<u8> {
+        let size = self
+            .sections
+            .iter()
+            .map(|s| s.virtual_address as usize + s.mapped_size() as usize)
+            .max()
+            .unwrap_or(0)
+            .max(self.headers_size.min(file.len()))
+            .min(MAX_MAPPED_SIZE);
+        let mut image = vec![0u8; size];
+
+        let headers = self.headers_size.min(file.len()).min(size);
+        image[..headers].copy_from_slice(&file[..headers]);
+
+        for section in &self.sections {
+            let start = section.raw_offset as usize;
+            let Some(raw) = file.get(start..).map(|rest| &rest[..rest.len().min(section.raw_size as usize)]) else {
+                continue;
+            };
+            let at = section.virtual_address as usize;
+            if at >= size {
+                continue;
+            }
+            let len = raw.len().min(size - at);
+            image[at..at + len].copy_from_slice(&raw[..len]);
+        }
+
+        image
+    }
+
+    /// The `.reloc` table's `HIGHLOW` and `DIR64` entries, by RVA. Blocks
+    /// are read until the table ends or one has a size too small to hold
+    /// its own header.
+    pub fn relocations(&self, file: &[u8]) -> Vec<Relocation> {
+        let Some((rva, size)) = self.base_relocations else {
+            return Vec::new();
+        };
+        let Some(table) = self
+            .rva_to_offset(rva)
+            .and_then(|start| file.get(start..start.saturating_add(size as usize).min(file.len())))
+        else {
+            return Vec::new();
+        };
+
+        let mut relocations = Vec::new();
+        let mut block = 0;
+        while let (Some(page), Some(block_size)) = (read_u32(table, block), read_u32(table, block + 4)) {
+            let block_size = block_size as usize;
+            if block_size < 8 {
+                break;
+            }
+            for entry in (block + 8..block.saturating_add(block_size)).step_by(2) {
+                let Some(entry) = read_u16(table, entry) else {
+                    break;
+                };
+                let kind = match entry >> 12 {
+                    IMAGE_REL_BASED_HIGHLOW => RelocationKind::HighLow,
+                    IMAGE_REL_BASED_DIR64 => RelocationKind::Dir64,
+                    _ => continue,
+                };
+                relocations.push(Relocation { address: page as u64 + (entry & 0xFFF) as u64, kind });
+            }
+            block = block.saturating_add(block_size);
+        }
+        relocations
+    }
+
+    fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        if (rva as usize) < self.headers_size {
+            return Some(rva as usize);
+        }
+        self.sections.iter().find_map(|s| {
+            let delta = rva.checked_sub(s.virtual_address)?;
+            (delta < s.raw_size).then(|| s.raw_offset as usize + delta as usize)
+        })
+    }
+
+    fn read_exports(&self, file: &[u8], directory_rva: u32, directory_size: u32) -> Vec<u64> {
+        let Some(directory) = self.rva_to_offset(directory_rva) else {
+            return Vec::new();
+        };
+        let count = read_u32(file, directory + 20).unwrap_or(0) as usize;
+        let Some(functions) = read_u32(file, directory + 28).and_then(|rva| self.rva_to_offset(rva)) else {
+            return Vec::new();
+        };
+
+        let forwarders = directory_rva..directory_rva.saturating_add(directory_size);
+        let mut exports: Vec<u64> = (0..count.min(MAX_SEEDS_PER_KIND))
+            .map_while(|i| read_u32(file, functions + i * 4))
+            .filter(|&rva| rva != 0 && !forwarders.contains(&rva))
+            .filter_map(|rva| self.image_base.checked_add(rva as u64))
+            .collect();
+        exports.sort_unstable();
+        exports.dedup();
+        exports
+    }
+
+    fn read_tls_callbacks(&self, file: &[u8], directory_rva: u32, pe32_plus: bool) -> Vec<u64> {
+        let Some(directory) = self.rva_to_offset(directory_rva) else {
+            return Vec::new();
+        };
+        let (pointer_size, callbacks_at) = if pe32_plus { (8, directory + 24) } else { (4, directory + 12) };
+        let read_pointer = |at: usize| {
+            if pe32_plus {
+                read_u64(file, at)
+            } else {
+                read_u32(file, at).map(u64::from)
+            }
+        };
+
+        // AddressOfCallBacks is a VA of a null-terminated array of VAs
+        let Some(array) = read_pointer(callbacks_at)
+            .and_then(|va| va.checked_sub(self.image_base))
+            .and_then(|rva| u32::try_from(rva).ok())
+            .and_then(|rva| self.rva_to_offset(rva))
+        else {
+            return Vec::new();
+        };
+
+        (0..MAX_SEEDS_PER_KIND)
+            .map_while(|i| read_pointer(array + i * pointer_size))
+            .take_while(|&va| va != 0)
+            .collect()
+    }
+}
+
+/// A PE disassembled from its seeds, with the calls between what was found
+#[derive(Clone)]
+pub struct PeDisassembly {
+    pub instructions: Vec<DisassembledInstruction>,
+    /// Rooted at the seeds, so depth is measured from wherever the loader
+    /// can enter the image
+    pub call_graph: CallGraph,
+}
+
+/// Disassemble a PE by recursive descent from the seeds `config` enables
+pub fn disassemble_pe(
+    file: &[u8],
+    config: &SeedConfig,
+    syntax: Syntax,
+    max_instructions: u32,
+) -> Result<PeDisassembly, String> {
+    let layout = PeLayout::parse(file)?;
+    let image = layout.map_image(file);
+    let instructions = disassemble_reachable(
+        &image,
+        layout.image_base,
+        &layout.sections(),
+        &layout.seeds(config),
+        layout.arch,
+        syntax,
+        max_instructions,
+    )?;
+
+    let mut xrefs = XrefBuilder::new();
+    let decoded: Vec<(u64, String, Option<u64>)> = instructions
+        .iter()
+        .map(|i| (i.offset, i.full_text.clone(), i.branch_target))
+        .collect();
+    xrefs.analyze_instructions(&decoded)?;
+    let mut call_graph = xrefs.build().call_graph;
+    layout.add_entry_points(config, &mut call_graph);
+
+    Ok(PeDisassembly { instructions, call_graph })
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at.checked_add(2)?)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at.checked_add(4)?)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(at..at.checked_add(8)?)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::{Disassembler, ImageLayout, Operand};
+
+    const IMAGE_BASE: u64 = 0x1_4000_0000;
+
+    fn put(buf: &mut [u8], at: usize, bytes: &[u8]) {
+        buf[at..at + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// A PE32+ DLL: `.text` at RVA 0x1000 holds the entry point, an exported
+    /// function and a TLS callback, none of which call each other. `.rdata`
+    /// at RVA 0x2000 holds the export and TLS directories.
+    fn sample_dll() -> Vec<u8> {
+        let mut file = vec![0u8; 0x600];
+        put(&mut file, 0, b"MZ");
+        put(&mut file, 0x3C, &0x80u32.to_le_bytes());
+        put(&mut file, 0x80, b"PE\0\0");
+
+        let coff = 0x84;
+        put(&mut file, coff, &0x8664u16.to_le_bytes());
+        put(&mut file, coff + 2, &2u16.to_le_bytes());
+        put(&mut file, coff + 16, &240u16.to_le_bytes());
+
+        let optional = 0x98;
+        put(&mut file, optional, &PE32_PLUS_MAGIC.to_le_bytes());
+        put(&mut file, optional + 16, &0x1000u32.to_le_bytes());
+        put(&mut file, optional + 24, &IMAGE_BASE.to_le_bytes());
+        put(&mut file, optional + 60, &0x200u32.to_le_bytes());
+        put(&mut file, optional + 108, &16u32.to_le_bytes());
+        put(&mut file, optional + 112, &0x2000u32.to_le_bytes());
+        put(&mut file, optional + 116, &0x50u32.to_le_bytes());
+        put(&mut file, optional + 112 + 9 * 8, &0x2080u32.to_le_bytes());
+        put(&mut file, optional + 116 + 9 * 8, &40u32.to_le_bytes());
+
+        let sections = optional + 240;
+        for (i, (name, rva, offset, characteristics)) in
+            [(b".text\0\0\0", 0x1000u32, 0x200u32, 0x6000_0020u32), (b".rdata\0\0", 0x2000, 0x400, 0x4000_0040)]
+                .into_iter()
+                .enumerate()
+        {
+            let at = sections + i * SECTION_HEADER_SIZE;
+            put(&mut file, at, name);
+            put(&mut file, at + 8, &0x200u32.to_le_bytes());
+            put(&mut file, at + 12, &rva.to_le_bytes());
+            put(&mut file, at + 16, &0x200u32.to_le_bytes());
+            put(&mut file, at + 20, &offset.to_le_bytes());
+            put(&mut file, at + 36, &characteristics.to_le_bytes());
+        }
+
+        file[0x200..0x400].fill(0xCC);
+        put(&mut file, 0x200, &[0xB8, 0x01, 0x00, 0x00, 0x00, 0xC3]); // entry: mov eax, 1; ret
+        put(&mut file, 0x210, &[0x31, 0xC0, 0xFF, 0xC0, 0xC3]); // export: xor eax, eax; inc eax; ret
+        put(&mut file, 0x220, &[0x90, 0xC3]); // TLS callback: nop; ret
+
+        // Export directory: one function at RVA 0x1010
+        put(&mut file, 0x400 + 20, &1u32.to_le_bytes());
+        put(&mut file, 0x400 + 28, &0x2040u32.to_le_bytes());
+        put(&mut file, 0x440, &0x1010u32.to_le_bytes());
+
+        // TLS directory: callbacks array at RVA 0x20A8 holding RVA 0x1020
+        put(&mut file, 0x480 + 24, &(IMAGE_BASE + 0x20A8).to_le_bytes());
+        put(&mut file, 0x4A8, &(IMAGE_BASE + 0x1020).to_le_bytes());
+
+        file
+    }
+
+    fn disassembled(config: SeedConfig) -> Vec<u64> {
+        disassemble_pe(&sample_dll(), &config, Syntax::Intel, 1000)
+            .unwrap()
+            .instructions
+            .iter()
+            .map(|i| i.offset)
+            .collect()
+    }
+
+    #[test]
+    fn test_headers_parsed() {
+        let layout = PeLayout::parse(&sample_dll()).unwrap();
+
+        assert_eq!(layout.arch, Architecture::X8664);
+        assert_eq!(layout.entry_point, Some(IMAGE_BASE + 0x1000));
+        assert_eq!(layout.exports, vec![IMAGE_BASE + 0x1010]);
+        assert_eq!(layout.tls_callbacks, vec![IMAGE_BASE + 0x1020]);
+        assert!(layout.sections()[0].executable);
+        assert!(!layout.sections()[1].executable);
+    }
+
+    #[test]
+    fn test_image_base_overflow_drops_seeds() {
+        let mut file = sample_dll();
+        let image_base = u64::MAX - 0xFFF;
+        put(&mut file, 0x98 + 24, &image_base.to_le_bytes());
+
+        let layout = PeLayout::parse(&file).unwrap();
+        assert_eq!(layout.entry_point, None);
+        assert!(layout.exports.is_empty());
+        assert!(layout.sections().is_empty());
+
+        let disassembly = disassemble_pe(&file, &SeedConfig::default(), Syntax::Intel, 1000).unwrap();
+        assert!(disassembly.instructions.is_empty());
+    }
+
+    #[test]
+    fn test_export_only_function_needs_export_seeding() {
+        let export = IMAGE_BASE + 0x1010;
+
+        let entry_only = disassembled(SeedConfig { exports: false, tls_callbacks: false, ..SeedConfig::default() });
+        assert_eq!(entry_only, vec![IMAGE_BASE + 0x1000, IMAGE_BASE + 0x1005]);
+
+        let all = disassembled(SeedConfig::default());
+        assert!(all.contains(&export));
+        assert!(all.contains(&(export + 4)), "the export's ret is reached");
+        assert!(all.contains(&(IMAGE_BASE + 0x1020)), "the TLS callback is reached");
+
+        // Padding between the functions is never decoded
+        assert!(!all.iter().any(|&a| (IMAGE_BASE + 0x1006..export).contains(&a)));
+
+        let mut call_graph = CallGraph::new();
+        PeLayout::parse(&sample_dll()).unwrap().add_entry_points(&SeedConfig::default(), &mut call_graph);
+        assert_eq!(call_graph.entry_points, vec![IMAGE_BASE + 0x1000, IMAGE_BASE + 0x1020, export]);
+    }
+
+    #[test]
+    fn test_call_graph_rooted_at_seeds() {
+        let export = IMAGE_BASE + 0x1010;
+        let mut file = sample_dll();
+        // The TLS callback calls the export: call rel32 to 0x1010; ret
+        put(&mut file, 0x220, &[0xE8, 0xEB, 0xFF, 0xFF, 0xFF, 0xC3]);
+
+        let pe = disassemble_pe(&file, &SeedConfig { exports: false, ..SeedConfig::default() }, Syntax::Intel, 1000)
+            .unwrap();
+        assert_eq!(pe.call_graph.entry_points, vec![IMAGE_BASE + 0x1000, IMAGE_BASE + 0x1020]);
+        assert_eq!(pe.call_graph.get_callees(IMAGE_BASE + 0x1020), vec![export]);
+        assert_eq!(pe.call_graph.get_call_depth(export), Some(1));
+        assert!(pe.instructions.iter().any(|i| i.offset == export), "the callee is reached without export seeding");
+    }
+
+    #[test]
+    fn test_base_relocations_applied_at_load_address() {
+        let mut file = sample_dll();
+        // mov rax, [abs64] at RVA 0x1030 reads a pointer to .rdata
+        put(&mut file, 0x230, &[0x48, 0xA1]);
+        put(&mut file, 0x232, &(IMAGE_BASE + 0x2000).to_le_bytes());
+        // .reloc at RVA 0x2100: one block for page 0x1000 with a DIR64
+        // entry at 0x1032, an unknown type and ABSOLUTE padding
+        let optional = 0x98;
+        put(&mut file, optional + 112 + 5 * 8, &0x2100u32.to_le_bytes());
+        put(&mut file, optional + 116 + 5 * 8, &16u32.to_le_bytes());
+        put(&mut file, 0x500, &0x1000u32.to_le_bytes());
+        put(&mut file, 0x504, &16u32.to_le_bytes());
+        put(&mut file, 0x508, &((IMAGE_REL_BASED_DIR64 << 12) | 0x032).to_le_bytes());
+        put(&mut file, 0x50A, &((7u16 << 12) | 0x040).to_le_bytes());
+
+        let relocations = PeLayout::parse(&file).unwrap().relocations(&file);
+        assert_eq!(relocations.len(), 1);
+        assert_eq!(relocations[0].address, 0x1032);
+        assert_eq!(relocations[0].kind, RelocationKind::Dir64);
+
+        let load_address = 0x7FF6_0000_0000;
+        let layout = ImageLayout::from_file(&file, load_address, 0x1030).unwrap();
+        let insns = Disassembler::disassemble_mapped(&file[0x230..0x23A], &layout, Architecture::X8664, 1).unwrap();
+        assert_eq!(insns[0].address, load_address + 0x1030);
+        assert!(matches!(
+            insns[0].operands[1],
+            Operand::Memory { displacement, .. } if displacement as u64 == load_address + 0x2000
+        ), "{:?}", insns[0].operands);
+    }
+}
@@ -0,0 +1,196 @@
+//! ELF Relocation Tables
+//! Reads the `R_*_RELATIVE` entries of an ELF's `SHT_RELA` and `SHT_REL`
+//! sections, the ones that make position-independent code point at its
+//! own data once loaded
+//!
+//! Symbol relocations need a resolved import and are left alone; they
+//! patch GOT slots, not code. Only little-endian files are read.
+
+use crate::disasm::{Relocation, RelocationKind};
+
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+
+const SHT_RELA: u32 = 4;
+const SHT_REL: u32 = 9;
+
+const EM_386: u16 = 3;
+const EM_ARM: u16 = 40;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+
+/// Sections read per file, so a corrupt `e_shnum` can't make us walk the
+/// whole file
+const MAX_SECTIONS: usize = 4096;
+
+/// `R_*_RELATIVE` for each machine whose relocations are applied
+fn relative_type(machine: u16) -> Option<u32> {
+    match machine {
+        EM_386 | EM_X86_64 => Some(8),
+        EM_ARM => Some(23),
+        EM_AARCH64 => Some(1027),
+        _ => None,
+    }
+}
+
+/// The relative relocations of the ELF `file`, by virtual address. A RELA
+/// entry stores base plus its addend; a REL entry's addend is the field
+/// itself, so it is applied by adding the load base like a PE `HIGHLOW`.
+pub fn elf_relocations(file: &[u8]) -> Result<Vec<Relocation>, String> {
+    if file.get(..4) != Some(b"\x7FELF") {
+        return Err("Missing ELF signature".to_string());
+    }
+    if file.get(5) != Some(&ELFDATA2LSB) {
+        return Err("Big-endian ELF relocations aren't supported".to_string());
+    }
+    let is_64 = match file.get(4) {
+        Some(&ELFCLASS64) => true,
+        Some(&ELFCLASS32) => false,
+        _ => return Err("Unknown ELF class".to_string()),
+    };
+
+    let machine = read_u16(file, 18).ok_or("Truncated ELF header")?;
+    let Some(relative) = relative_type(machine) else {
+        return Err(format!("Unsupported ELF machine {}", machine));
+    };
+    let (section_headers, entry_size, count) = if is_64 {
+        (read_u64(file, 0x28), read_u16(file, 0x3A), read_u16(file, 0x3C))
+    } else {
+        (read_u32(file, 0x20).map(u64::from), read_u16(file, 0x2E), read_u16(file, 0x30))
+    };
+    let (Some(section_headers), Some(entry_size), Some(count)) = (section_headers, entry_size, count) else {
+        return Err("Truncated ELF header".to_string());
+    };
+
+    let mut relocations = Vec::new();
+    for i in 0..(count as usize).min(MAX_SECTIONS) {
+        let Some(header) = (section_headers as usize)
+            .checked_add(i * entry_size as usize)
+            .filter(|&header| header < file.len())
+        else {
+            break;
+        };
+        let section_type = read_u32(file, header + 4);
+        let (offset, size) = if is_64 {
+            (read_u64(file, header + 0x18), read_u64(file, header + 0x20))
+        } else {
+            (read_u32(file, header + 0x10).map(u64::from), read_u32(file, header + 0x14).map(u64::from))
+        };
+        let (Some(section_type), Some(offset), Some(size)) = (section_type, offset, size) else {
+            break;
+        };
+        let with_addend = match section_type {
+            SHT_RELA => true,
+            SHT_REL => false,
+            _ => continue,
+        };
+        let Some(table) = file.get(offset as usize..(offset.saturating_add(size) as usize).min(file.len())) else {
+            continue;
+        };
+
+        let word = if is_64 { 8 } else { 4 };
+        let stride = if with_addend { word * 3 } else { word * 2 };
+        for entry in table.chunks_exact(stride) {
+            let (address, info, addend) = if is_64 {
+                (read_u64(entry, 0), read_u64(entry, 8).map(|info| info & 0xFFFF_FFFF), read_u64(entry, 16))
+            } else {
+                (
+                    read_u32(entry, 0).map(u64::from),
+                    read_u32(entry, 4).map(|info| u64::from(info & 0xFF)),
+                    read_u32(entry, 8).map(|addend| addend as i32 as u64),
+                )
+            };
+            let (Some(address), Some(info)) = (address, info) else {
+                continue;
+            };
+            if info != relative as u64 {
+                continue;
+            }
+            let kind = match addend {
+                Some(addend) if with_addend => RelocationKind::Relative { addend: addend as i64 },
+                _ if is_64 => RelocationKind::Dir64,
+                _ => RelocationKind::HighLow,
+            };
+            relocations.push(Relocation { address, kind });
+        }
+    }
+
+    Ok(relocations)
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at.checked_add(2)?)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at.checked_add(4)?)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(at..at.checked_add(8)?)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(buf: &mut [u8], at: usize, bytes: &[u8]) {
+        buf[at..at + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// An x86-64 PIE with one `.rela.dyn` section at 0x100 holding a
+    /// relative relocation, a symbol relocation and a second relative one
+    fn pie_with_rela() -> Vec<u8> {
+        let mut file = vec![0u8; 0x200];
+        put(&mut file, 0, b"\x7FELF");
+        file[4] = ELFCLASS64;
+        file[5] = ELFDATA2LSB;
+        put(&mut file, 16, &3u16.to_le_bytes()); // ET_DYN
+        put(&mut file, 18, &EM_X86_64.to_le_bytes());
+        put(&mut file, 0x28, &0x180u64.to_le_bytes());
+        put(&mut file, 0x3A, &64u16.to_le_bytes());
+        put(&mut file, 0x3C, &2u16.to_le_bytes());
+
+        // Section 0 is the null section; section 1 is SHT_RELA
+        put(&mut file, 0x1C0 + 4, &SHT_RELA.to_le_bytes());
+        put(&mut file, 0x1C0 + 0x18, &0x100u64.to_le_bytes());
+        put(&mut file, 0x1C0 + 0x20, &72u64.to_le_bytes());
+
+        for (i, (offset, info, addend)) in [(0x3DF0u64, 8u64, 0x1130i64), (0x3FD8, (1 << 32) | 6, 0), (0x4008, 8, 0x4008)]
+            .into_iter()
+            .enumerate()
+        {
+            let at = 0x100 + i * 24;
+            put(&mut file, at, &offset.to_le_bytes());
+            put(&mut file, at + 8, &info.to_le_bytes());
+            put(&mut file, at + 16, &addend.to_le_bytes());
+        }
+        file
+    }
+
+    #[test]
+    fn test_relative_rela_entries_read() {
+        let relocations = elf_relocations(&pie_with_rela()).unwrap();
+
+        assert_eq!(relocations.len(), 2);
+        assert_eq!(relocations[0].address, 0x3DF0);
+        assert_eq!(relocations[0].kind, RelocationKind::Relative { addend: 0x1130 });
+        assert_eq!(relocations[1].address, 0x4008);
+        assert_eq!(relocations[1].kind, RelocationKind::Relative { addend: 0x4008 });
+    }
+
+    #[test]
+    fn test_malformed_headers_rejected() {
+        assert!(elf_relocations(b"MZ\x90\x00").is_err());
+
+        let mut big_endian = pie_with_rela();
+        big_endian[5] = 2;
+        assert!(elf_relocations(&big_endian).is_err());
+
+        // A section table past the end of the file has nothing to read
+        let mut file = pie_with_rela();
+        put(&mut file, 0x28, &u64::MAX.to_le_bytes());
+        assert!(elf_relocations(&file).unwrap().is_empty());
+    }
+}
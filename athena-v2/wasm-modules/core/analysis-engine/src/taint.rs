@@ -0,0 +1,503 @@
+//! Instruction-Level Taint Tracking
+//! Follows data from a source buffer (a decrypted config, a collected
+//! credential) through registers and memory until it is handed to an API
+//! that sends or stores it, to explain how a sample exfiltrates
+//!
+//! Instructions are decoded in address order and branches aren't followed,
+//! so this suits the straight-line code between a decode routine and the
+//! call that ships its output. Register values are tracked only as far as
+//! needed to resolve addresses: constants, `lea`, stack pointer changes and
+//! word-sized values stored to memory. A sink argument is tainted when it
+//! holds tainted data or points at a tainted byte.
+
+use iced_x86::{
+    Decoder, DecoderOptions, FlowControl, Instruction, InstructionInfoFactory, Mnemonic, OpAccess, OpKind, Register,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Value `rsp`/`esp` starts with, so stack slots have concrete addresses
+const STACK_BASE: u64 = 0x7FFF_0000;
+
+/// State is kept per full-width register, which for `esp` is `rsp` too
+const STACK_POINTER: Register = Register::RSP;
+
+/// Default number of instructions decoded per trace
+pub const DEFAULT_MAX_INSTRUCTIONS: usize = 100_000;
+
+/// Bytes marked per source; larger sources are truncated
+const MAX_SOURCE_BYTES: u64 = 1024 * 1024;
+
+/// Instructions kept in a flow's path; later ones are dropped
+const MAX_PATH_LEN: usize = 64;
+
+/// Argument registers of the Windows x64 calling convention
+const X64_ARGUMENT_REGISTERS: [Register; 4] = [Register::RCX, Register::RDX, Register::R8, Register::R9];
+
+/// Registers a call may clobber, per calling convention
+const X64_VOLATILE_REGISTERS: [Register; 7] =
+    [Register::RAX, Register::RCX, Register::RDX, Register::R8, Register::R9, Register::R10, Register::R11];
+const X86_VOLATILE_REGISTERS: [Register; 3] = [Register::EAX, Register::ECX, Register::EDX];
+
+/// Which arguments of well-known sinks carry the data sent or written
+const SINK_ARGUMENTS: &[(&str, &[usize])] = &[
+    ("send", &[1]),
+    ("sendto", &[1]),
+    ("WSASend", &[1]),
+    ("WriteFile", &[1]),
+    ("InternetWriteFile", &[1]),
+    ("HttpSendRequestA", &[1, 3]),
+    ("HttpSendRequestW", &[1, 3]),
+    ("WinHttpSendRequest", &[1, 3]),
+    ("WinHttpWriteData", &[1]),
+    ("RegSetValueExA", &[4]),
+    ("RegSetValueExW", &[4]),
+];
+
+/// Bytes to treat as tainted before tracing starts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaintSource {
+    pub label: String,
+    pub address: u64,
+    pub size: u64,
+}
+
+/// A function whose arguments must not receive tainted data. `address` is
+/// what the call instruction names: the function itself for a direct call,
+/// the import slot for `call [slot]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaintSink {
+    pub name: String,
+    pub address: u64,
+    /// Zero-based indices of the arguments to check
+    pub arguments: Vec<usize>,
+}
+
+impl TaintSink {
+    /// A sink for the API `name`, checking its data arguments if it is a
+    /// known network or file API and its first four otherwise
+    pub fn api(name: &str, address: u64) -> Self {
+        let arguments = SINK_ARGUMENTS
+            .iter()
+            .find(|(api, _)| *api == name)
+            .map_or_else(|| vec![0, 1, 2, 3], |(_, arguments)| arguments.to_vec());
+        Self { name: name.to_string(), address, arguments }
+    }
+}
+
+/// Tainted data reaching a sink
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaintFlow {
+    /// Label of the `TaintSource` the data came from
+    pub source: String,
+    pub sink: String,
+    pub call_site: u64,
+    pub argument: usize,
+    /// The instructions that moved the data, ending with the call
+    pub path: Vec<u64>,
+}
+
+/// Where a tainted value came from and how it got here
+#[derive(Debug, Clone)]
+struct Taint {
+    source: usize,
+    path: Vec<u64>,
+}
+
+impl Taint {
+    fn through(&self, address: u64) -> Self {
+        let mut path = self.path.clone();
+        if path.len() < MAX_PATH_LEN {
+            path.push(address);
+        }
+        Self { source: self.source, path }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Taint of each full-width register
+    registers: HashMap<Register, Taint>,
+    /// Known values of full-width registers
+    values: HashMap<Register, u64>,
+    /// Taint of each memory byte
+    memory: HashMap<u64, Taint>,
+    /// Known word-sized values stored in memory, by address
+    stored: HashMap<u64, u64>,
+}
+
+impl State {
+    /// Value of `register`, for address computation. Segment bases are
+    /// taken as zero.
+    fn value(&self, register: Register) -> Option<u64> {
+        if register.is_segment_register() {
+            return Some(0);
+        }
+        let value = *self.values.get(&register.full_register())?;
+        Some(match register.size() {
+            4 => value & 0xFFFF_FFFF,
+            _ => value,
+        })
+    }
+
+    fn memory_taint(&self, address: u64, size: usize) -> Option<&Taint> {
+        (0..size as u64).find_map(|i| self.memory.get(&address.wrapping_add(i)))
+    }
+}
+
+/// Traces taint from sources to sinks over x86 or x64 code
+#[derive(Debug, Clone)]
+pub struct TaintEngine {
+    bitness: u32,
+    sources: Vec<TaintSource>,
+    sinks: HashMap<u64, TaintSink>,
+    max_instructions: usize,
+}
+
+impl TaintEngine {
+    /// An engine for 32- or 64-bit code
+    pub fn new(bitness: u32) -> Self {
+        Self {
+            bitness,
+            sources: Vec::new(),
+            sinks: HashMap::new(),
+            max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+        }
+    }
+
+    pub fn with_source(mut self, label: &str, address: u64, size: u64) -> Self {
+        self.sources.push(TaintSource { label: label.to_string(), address, size });
+        self
+    }
+
+    pub fn with_sink(mut self, sink: TaintSink) -> Self {
+        self.sinks.insert(sink.address, sink);
+        self
+    }
+
+    pub fn with_max_instructions(mut self, max_instructions: usize) -> Self {
+        self.max_instructions = max_instructions;
+        self
+    }
+
+    /// Every sink argument that receives tainted data in `code`, mapped at
+    /// `base`, in the order the calls happen
+    pub fn trace(&self, code: &[u8], base: u64) -> Vec<TaintFlow> {
+        let mut state = State::default();
+        state.values.insert(STACK_POINTER, STACK_BASE);
+        for (index, source) in self.sources.iter().enumerate() {
+            for offset in 0..source.size.min(MAX_SOURCE_BYTES) {
+                state.memory.insert(source.address.wrapping_add(offset), Taint { source: index, path: Vec::new() });
+            }
+        }
+
+        let mut flows = Vec::new();
+        let mut decoder = Decoder::with_ip(self.bitness, code, base, DecoderOptions::NONE);
+        let mut factory = InstructionInfoFactory::new();
+        let mut instr = Instruction::default();
+
+        for _ in 0..self.max_instructions {
+            if !decoder.can_decode() {
+                break;
+            }
+            decoder.decode_out(&mut instr);
+            if instr.is_invalid() {
+                continue;
+            }
+
+            match instr.flow_control() {
+                FlowControl::Call | FlowControl::IndirectCall => {
+                    if let Some(sink) = self.call_target(&instr, &state).and_then(|t| self.sinks.get(&t)) {
+                        flows.extend(self.check_sink(sink, &instr, &state));
+                    }
+                    self.clobber_volatile(&mut state);
+                }
+                _ => self.propagate(&instr, &mut factory, &mut state),
+            }
+        }
+
+        flows
+    }
+
+    /// The address a call goes to, or for `call [slot]`, the slot
+    fn call_target(&self, instr: &Instruction, state: &State) -> Option<u64> {
+        match instr.op0_kind() {
+            OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 => Some(instr.near_branch_target()),
+            OpKind::Memory => instr.virtual_address(0, 0, |register, _, _| state.value(register)),
+            OpKind::Register => state.value(instr.op0_register()),
+            _ => None,
+        }
+    }
+
+    fn check_sink(&self, sink: &TaintSink, instr: &Instruction, state: &State) -> Vec<TaintFlow> {
+        let word = (self.bitness / 8) as u64;
+        let stack = state.value(STACK_POINTER);
+
+        sink.arguments
+            .iter()
+            .filter_map(|&argument| {
+                // Register arguments first on x64 (past the 32-byte shadow
+                // space for the rest); all on the stack on x86
+                let (data, pointer) = match (self.bitness, X64_ARGUMENT_REGISTERS.get(argument)) {
+                    (64, Some(&register)) => (state.registers.get(&register), state.value(register)),
+                    _ => {
+                        let slot = match self.bitness {
+                            64 => stack? + 0x20 + (argument as u64 - 4) * word,
+                            _ => stack? + argument as u64 * word,
+                        };
+                        (state.memory_taint(slot, word as usize), state.stored.get(&slot).copied())
+                    }
+                };
+                let taint = data.or_else(|| pointer.and_then(|p| state.memory.get(&p)))?;
+                let path = taint.through(instr.ip()).path;
+                Some(TaintFlow {
+                    source: self.sources[taint.source].label.clone(),
+                    sink: sink.name.clone(),
+                    call_site: instr.ip(),
+                    argument,
+                    path,
+                })
+            })
+            .collect()
+    }
+
+    /// The callee isn't followed, so whatever it may overwrite is unknown
+    /// afterwards
+    fn clobber_volatile(&self, state: &mut State) {
+        let volatile: &[Register] =
+            if self.bitness == 64 { &X64_VOLATILE_REGISTERS } else { &X86_VOLATILE_REGISTERS };
+        for register in volatile {
+            let register = register.full_register();
+            state.registers.remove(&register);
+            state.values.remove(&register);
+        }
+    }
+
+    /// Move taint from what `instr` reads to what it writes, and update the
+    /// known register and memory values
+    fn propagate(&self, instr: &Instruction, factory: &mut InstructionInfoFactory, state: &mut State) {
+        let info = factory.info(instr);
+
+        // Registers that only form an address don't pass their data on
+        let address_registers: HashSet<Register> = info
+            .used_memory()
+            .iter()
+            .flat_map(|m| [m.base(), m.index()])
+            .filter(|&r| r != Register::None)
+            .map(|r| r.full_register())
+            .collect();
+
+        let zeroing = matches!(instr.mnemonic(), Mnemonic::Xor | Mnemonic::Sub | Mnemonic::Pxor | Mnemonic::Xorps)
+            && instr.op_count() == 2
+            && instr.op0_kind() == OpKind::Register
+            && instr.op1_kind() == OpKind::Register
+            && instr.op0_register() == instr.op1_register();
+
+        let mut input = None;
+        if !zeroing {
+            input = info
+                .used_registers()
+                .iter()
+                .filter(|r| matches!(r.access(), OpAccess::Read | OpAccess::ReadWrite | OpAccess::CondRead))
+                .map(|r| r.register().full_register())
+                .filter(|r| !address_registers.contains(r))
+                .find_map(|r| state.registers.get(&r).cloned());
+            for memory in info.used_memory() {
+                if input.is_some() {
+                    break;
+                }
+                if !matches!(memory.access(), OpAccess::Read | OpAccess::ReadWrite | OpAccess::CondRead) {
+                    continue;
+                }
+                if let Some(address) = memory.virtual_address(0, |register, _, _| state.value(register)) {
+                    input = state.memory_taint(address, memory.memory_size().size()).cloned();
+                }
+            }
+        }
+        let output = input.map(|taint| taint.through(instr.ip()));
+
+        // Memory writes are resolved before the stack pointer moves
+        let mut writes = Vec::new();
+        for memory in info.used_memory() {
+            let access = memory.access();
+            if !matches!(access, OpAccess::Write | OpAccess::ReadWrite | OpAccess::CondWrite) {
+                continue;
+            }
+            if let Some(address) = memory.virtual_address(0, |register, _, _| state.value(register)) {
+                writes.push((address, memory.memory_size().size(), access));
+            }
+        }
+        let stored_value = self.stored_value(instr, state);
+        for (address, size, access) in writes {
+            for offset in 0..size as u64 {
+                let byte = address.wrapping_add(offset);
+                match &output {
+                    Some(taint) => {
+                        state.memory.insert(byte, taint.clone());
+                    }
+                    None if access != OpAccess::CondWrite => {
+                        state.memory.remove(&byte);
+                    }
+                    None => {}
+                }
+                state.stored.remove(&byte);
+            }
+            if let Some(value) = stored_value.filter(|_| size == (self.bitness / 8) as usize) {
+                state.stored.insert(address, value);
+            }
+        }
+
+        let written_value = self.written_value(instr, state);
+        let destination = (instr.op0_kind() == OpKind::Register).then(|| instr.op0_register().full_register());
+        for used in info.used_registers() {
+            let access = used.access();
+            if !matches!(access, OpAccess::Write | OpAccess::ReadWrite | OpAccess::CondWrite) {
+                continue;
+            }
+            let register = used.register().full_register();
+            if address_registers.contains(&register) || register == STACK_POINTER {
+                continue;
+            }
+            // Writing part of a register keeps whatever taint the rest holds
+            let whole = used.register().size() >= 4;
+            match &output {
+                Some(taint) => {
+                    state.registers.insert(register, taint.clone());
+                }
+                None if whole && access != OpAccess::CondWrite => {
+                    state.registers.remove(&register);
+                }
+                None => {}
+            }
+            match written_value.filter(|_| whole && destination == Some(register)) {
+                Some(value) => {
+                    state.values.insert(register, value);
+                }
+                None => {
+                    state.values.remove(&register);
+                }
+            }
+        }
+
+        let increment = instr.stack_pointer_increment();
+        if increment != 0 {
+            if let Some(value) = state.values.get_mut(&STACK_POINTER) {
+                *value = value.wrapping_add(increment as i64 as u64);
+            }
+        }
+    }
+
+    /// The value `instr` puts in its destination register, when known
+    fn written_value(&self, instr: &Instruction, state: &State) -> Option<u64> {
+        let value = match (instr.mnemonic(), instr.op1_kind()) {
+            (Mnemonic::Lea, _) => instr.virtual_address(1, 0, |register, _, _| state.value(register))?,
+            (Mnemonic::Mov, OpKind::Register) => state.value(instr.op1_register())?,
+            (Mnemonic::Mov, OpKind::Memory) => {
+                let address = instr.virtual_address(1, 0, |register, _, _| state.value(register))?;
+                *state.stored.get(&address)?
+            }
+            (Mnemonic::Mov, _) => instr.try_immediate(1).ok()?,
+            (Mnemonic::Add | Mnemonic::Sub, _) => {
+                let left = state.value(instr.op0_register())?;
+                let right = match instr.op1_kind() {
+                    OpKind::Register => state.value(instr.op1_register())?,
+                    _ => instr.try_immediate(1).ok()?,
+                };
+                if instr.mnemonic() == Mnemonic::Add { left.wrapping_add(right) } else { left.wrapping_sub(right) }
+            }
+            (Mnemonic::Xor, OpKind::Register) if instr.op0_register() == instr.op1_register() => 0,
+            _ => return None,
+        };
+        // 32-bit writes zero the upper half
+        Some(if instr.op0_register().size() == 4 { value & 0xFFFF_FFFF } else { value })
+    }
+
+    /// The value `instr` stores to memory, when known
+    fn stored_value(&self, instr: &Instruction, state: &State) -> Option<u64> {
+        let operand = match instr.mnemonic() {
+            Mnemonic::Push => 0,
+            Mnemonic::Mov if instr.op0_kind() == OpKind::Memory => 1,
+            _ => return None,
+        };
+        match instr.op_kind(operand) {
+            OpKind::Register => state.value(instr.op_register(operand)),
+            _ => instr.try_immediate(operand).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: u64 = 0x3000;
+    const SEND_SLOT: u64 = 0x2000;
+
+    /// Copies eight bytes of the decoded config to a stack buffer and sends
+    /// the buffer: `send(rbx, &buf, 8, 0)` through the import slot
+    const EXFILTRATE: &[u8] = &[
+        0x48, 0x8D, 0x35, 0xF9, 0x1F, 0x00, 0x00, // 0x1000 lea rsi, [rip+0x1ff9]  ; config
+        0x48, 0x8B, 0x06,                         // 0x1007 mov rax, [rsi]
+        0x48, 0x89, 0x44, 0x24, 0x40,             // 0x100a mov [rsp+0x40], rax
+        0x48, 0x8D, 0x54, 0x24, 0x40,             // 0x100f lea rdx, [rsp+0x40]
+        0x41, 0xB8, 0x08, 0x00, 0x00, 0x00,       // 0x1014 mov r8d, 8
+        0x48, 0x89, 0xD9,                         // 0x101a mov rcx, rbx
+        0xFF, 0x15, 0xDD, 0x0F, 0x00, 0x00,       // 0x101d call [rip+0xfdd]  ; send
+        0xC3,                                     // 0x1023 ret
+    ];
+
+    fn engine() -> TaintEngine {
+        TaintEngine::new(64)
+            .with_source("decoded config", CONFIG, 64)
+            .with_sink(TaintSink::api("send", SEND_SLOT))
+    }
+
+    #[test]
+    fn test_tainted_buffer_reaches_send() {
+        let flows = engine().trace(EXFILTRATE, 0x1000);
+
+        assert_eq!(flows, vec![TaintFlow {
+            source: "decoded config".to_string(),
+            sink: "send".to_string(),
+            call_site: 0x101D,
+            argument: 1,
+            path: vec![0x1007, 0x100A, 0x101D],
+        }]);
+    }
+
+    #[test]
+    fn test_overwritten_data_not_reported() {
+        let mut code = EXFILTRATE.to_vec();
+        // xor eax, eax; nop before the store
+        code[7..10].copy_from_slice(&[0x31, 0xC0, 0x90]);
+        assert!(engine().trace(&code, 0x1000).is_empty());
+
+        // A source elsewhere, or a sink at another address, isn't a flow
+        let other = TaintEngine::new(64)
+            .with_source("decoded config", 0x4000, 64)
+            .with_sink(TaintSink::api("send", SEND_SLOT));
+        assert!(other.trace(EXFILTRATE, 0x1000).is_empty());
+        assert!(TaintEngine::new(64).with_source("decoded config", CONFIG, 64).trace(EXFILTRATE, 0x1000).is_empty());
+    }
+
+    #[test]
+    fn test_x86_stack_argument_points_at_taint() {
+        // push 8; push 0x3000; push esi; call 0x2000 (WriteFile, lpBuffer is
+        // the second argument)
+        let code = [
+            0x6A, 0x08,                         // 0x1000 push 8
+            0x68, 0x00, 0x30, 0x00, 0x00,       // 0x1002 push 0x3000
+            0x56,                               // 0x1007 push esi
+            0xE8, 0xF3, 0x0F, 0x00, 0x00,       // 0x1008 call 0x2000
+        ];
+        let flows = TaintEngine::new(32)
+            .with_source("harvested credentials", CONFIG, 16)
+            .with_sink(TaintSink::api("WriteFile", 0x2000))
+            .trace(&code, 0x1000);
+
+        assert_eq!(flows.len(), 1);
+        assert_eq!(flows[0].argument, 1);
+        assert_eq!(flows[0].source, "harvested credentials");
+        assert_eq!(flows[0].path, vec![0x1008]);
+    }
+}
@@ -200,6 +200,15 @@ impl TypeInference {
                 self.infer_from_size(expr.size)
             }
 
+            // Signed multiply -> signed integer
+            IROp::IMul => match expr.size {
+                1 => InferredType::Integer(IntegerType::I8),
+                2 => InferredType::Integer(IntegerType::I16),
+                4 => InferredType::Integer(IntegerType::I32),
+                8 => InferredType::Integer(IntegerType::I64),
+                _ => InferredType::Unknown,
+            },
+
             // Bitwise operations -> integer
             IROp::And | IROp::Or | IROp::Xor | IROp::Not |
             IROp::Shl | IROp::Shr | IROp::Sar => {
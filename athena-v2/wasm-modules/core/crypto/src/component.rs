@@ -351,6 +351,27 @@ impl exports::athena::crypto::rsa::Guest for Component {
     }
 }
 
+// ============================================================================
+// ECDSA Interface Implementation
+// ============================================================================
+
+impl exports::athena::crypto::ecdsa::Guest for Component {
+    fn verify(
+        curve: exports::athena::crypto::ecdsa::Curve,
+        public_key: Vec<u8>,
+        message: Vec<u8>,
+        signature: Vec<u8>,
+    ) -> Result<bool, String> {
+        use exports::athena::crypto::ecdsa::Curve as WitCurve;
+        let curve = match curve {
+            WitCurve::P256 => crate::ecdsa::Curve::P256,
+            WitCurve::Secp256k1 => crate::ecdsa::Curve::Secp256k1,
+        };
+
+        crate::ecdsa::ecdsa_verify(&public_key, &message, &signature, curve)
+    }
+}
+
 // ============================================================================
 // Utils Interface Implementation
 // ============================================================================
@@ -1,5 +1,6 @@
 /// Elliptic Curve Digital Signature Algorithm (ECDSA) implementation
-/// Supports P-256 (NIST P-256, secp256r1) and P-384 (NIST P-384, secp384r1)
+/// Supports P-256 (NIST P-256, secp256r1) and P-384 (NIST P-384, secp384r1),
+/// plus verification-only support for secp256k1
 
 use p256::ecdsa::{
     SigningKey as P256SigningKey,
@@ -17,6 +18,11 @@ use p384::ecdsa::{
 };
 use p256::pkcs8::{EncodePrivateKey, EncodePublicKey, DecodePrivateKey, DecodePublicKey};
 use p384::pkcs8::{EncodePrivateKey as EncodePrivateKeyP384, EncodePublicKey as EncodePublicKeyP384, DecodePrivateKey as DecodePrivateKeyP384, DecodePublicKey as DecodePublicKeyP384};
+use k256::ecdsa::{
+    VerifyingKey as K256VerifyingKey,
+    Signature as K256Signature,
+    signature::Verifier as K256Verifier,
+};
 use rand::rngs::OsRng;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 
@@ -162,10 +168,124 @@ impl EcdsaP384 {
     }
 }
 
+/// Curves supported by [`ecdsa_verify`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    P256,
+    Secp256k1,
+}
+
+/// Verify an ECDSA/SHA-256 signature over `msg`.
+///
+/// `pubkey` is a SEC1-encoded point (33-byte compressed or 65-byte uncompressed).
+/// `sig` is either DER-encoded or the 64-byte fixed-size `r || s` form.
+/// Returns `Ok(false)` for a well-formed signature that doesn't match, and `Err`
+/// when the key or signature can't be decoded.
+pub fn ecdsa_verify(pubkey: &[u8], msg: &[u8], sig: &[u8], curve: Curve) -> Result<bool, String> {
+    validate_sec1_point(pubkey)?;
+
+    match curve {
+        Curve::P256 => {
+            let verifying_key = P256VerifyingKey::from_sec1_bytes(pubkey)
+                .map_err(|_| "Invalid P-256 public key: not a point on the curve".to_string())?;
+            let signature = decode_signature(sig, P256Signature::from_der, P256Signature::from_slice)?;
+            let signature = signature.normalize_s().unwrap_or(signature);
+
+            Ok(verifying_key.verify(msg, &signature).is_ok())
+        }
+        Curve::Secp256k1 => {
+            let verifying_key = K256VerifyingKey::from_sec1_bytes(pubkey)
+                .map_err(|_| "Invalid secp256k1 public key: not a point on the curve".to_string())?;
+            let signature = decode_signature(sig, K256Signature::from_der, K256Signature::from_slice)?;
+            // secp256k1 verifiers reject high-S signatures; accept either form
+            let signature = signature.normalize_s().unwrap_or(signature);
+
+            Ok(verifying_key.verify(msg, &signature).is_ok())
+        }
+    }
+}
+
+fn validate_sec1_point(pubkey: &[u8]) -> Result<(), String> {
+    match (pubkey.first(), pubkey.len()) {
+        (None, _) => Err("Invalid public key: empty".to_string()),
+        (Some(0x02) | Some(0x03), 33) | (Some(0x04), 65) => Ok(()),
+        (Some(0x02) | Some(0x03) | Some(0x04), len) => Err(format!(
+            "Invalid public key length: {} bytes (expected 33 compressed or 65 uncompressed)", len
+        )),
+        (Some(tag), _) => Err(format!("Invalid public key encoding: unknown SEC1 tag 0x{:02x}", tag)),
+    }
+}
+
+fn decode_signature<S, E1, E2>(
+    sig: &[u8],
+    from_der: impl Fn(&[u8]) -> Result<S, E1>,
+    from_slice: impl Fn(&[u8]) -> Result<S, E2>,
+) -> Result<S, String> {
+    if sig.is_empty() {
+        return Err("Invalid signature: empty".to_string());
+    }
+
+    if sig[0] == 0x30 {
+        if let Ok(signature) = from_der(sig) {
+            return Ok(signature);
+        }
+        if sig.len() != 64 {
+            return Err("Invalid signature: malformed DER encoding".to_string());
+        }
+    }
+
+    if sig.len() == 64 {
+        return from_slice(sig).map_err(|_| "Invalid signature: r or s out of range".to_string());
+    }
+
+    Err(format!("Invalid signature length: {} bytes (expected DER or 64-byte r||s)", sig.len()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const SIGNED_MESSAGE: &[u8] = b"athena rule bundle v1";
+
+    const SECP256K1_PUBKEY: &str = "02bb50e2d89a4ed70663d080659fe0ad4b9bc3e06c17a227433966cb59ceee020d";
+    const SECP256K1_SIG: &str = "3045022100d916e2255c233fd731113a579117e25ad3a4139b72b7ad10f21be9ffa0672c4602205e7a0a7c8e0eb8746cee466925c73ba231dd27138c214659c450febe282f838f";
+
+    const P256_PUBKEY: &str = "02471c3e758c4904285bba7e53118ed0f524adeb0757d25bd2f8e7b0d76dfa714c";
+    const P256_SIG: &str = "304302205e714a14163b3c05a166bf14ca05b9df0c7d683c731ebd901908b725e3d153a6021f452b5236d76cfe90d96d1ef5c4daf4f1126aefd74113b100accedfee304b56";
+
+    #[test]
+    fn test_ecdsa_verify_secp256k1_known_signature() {
+        let pubkey = hex::decode(SECP256K1_PUBKEY).unwrap();
+        let sig = hex::decode(SECP256K1_SIG).unwrap();
+
+        assert_eq!(ecdsa_verify(&pubkey, SIGNED_MESSAGE, &sig, Curve::Secp256k1), Ok(true));
+        assert_eq!(ecdsa_verify(&pubkey, b"athena rule bundle v2", &sig, Curve::Secp256k1), Ok(false));
+    }
+
+    #[test]
+    fn test_ecdsa_verify_p256_known_signature() {
+        let pubkey = hex::decode(P256_PUBKEY).unwrap();
+        let sig = hex::decode(P256_SIG).unwrap();
+
+        assert_eq!(ecdsa_verify(&pubkey, SIGNED_MESSAGE, &sig, Curve::P256), Ok(true));
+        assert_eq!(ecdsa_verify(&pubkey, b"athena rule bundle v2", &sig, Curve::P256), Ok(false));
+    }
+
+    #[test]
+    fn test_ecdsa_verify_rejects_bad_encoding() {
+        let pubkey = hex::decode(SECP256K1_PUBKEY).unwrap();
+        let sig = hex::decode(SECP256K1_SIG).unwrap();
+
+        let err = ecdsa_verify(&pubkey[..20], SIGNED_MESSAGE, &sig, Curve::Secp256k1).unwrap_err();
+        assert!(err.contains("public key length"));
+
+        let err = ecdsa_verify(&pubkey, SIGNED_MESSAGE, &sig[..40], Curve::Secp256k1).unwrap_err();
+        assert!(err.contains("DER"));
+
+        let err = ecdsa_verify(&[0x05; 33], SIGNED_MESSAGE, &sig, Curve::P256).unwrap_err();
+        assert!(err.contains("SEC1 tag"));
+    }
+
     #[test]
     fn test_p256_keypair_generation() {
         let result = EcdsaP256::generate_keypair();
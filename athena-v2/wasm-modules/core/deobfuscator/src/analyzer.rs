@@ -1,5 +1,7 @@
 use crate::types::*;
 use crate::cfg_analysis::{detect_control_flow_flattening, SimpleCfg};
+use crate::techniques::encoding::{Ascii85Decoder, Base32Decoder, Base58Decoder};
+use crate::techniques::DeobfuscationTechnique;
 use regex::Regex;
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
@@ -20,6 +22,17 @@ static EVAL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?i)(eval|execute|invoke-expression|iex)\s*\(").unwrap()
 });
 
+/// `var _0x4e1f = ['...'`: the string table obfuscator.io-style tools move
+/// every literal into, replacing each use with an indexed lookup
+static STRING_ARRAY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:var|let|const)\s+([A-Za-z_$][\w$]*)\s*=\s*\[\s*["']"#).unwrap()
+});
+
+/// Start of a Script Encoder block: marker, then the base64 length field
+static SCRIPT_ENCODED_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"#@~\^[A-Za-z0-9+/]{6}==").unwrap()
+});
+
 static CHARCODE_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"String\.fromCharCode\s*\(\s*(?:\d+\s*,?\s*){3,}\s*\)").unwrap()
 });
@@ -56,6 +69,17 @@ const DES_IP_SIGNATURE: [u8; 8] = [58, 50, 42, 34, 26, 18, 10, 2];
 // DES PC1 permutation table (used in key schedule)
 const DES_PC1_SIGNATURE: [u8; 8] = [57, 49, 41, 33, 25, 17, 9, 1];
 
+// ChaCha20/Salsa20 "expand 32-byte k" constant, as the ASCII string and as little-endian words
+const CHACHA_SIGMA: &[u8] = b"expand 32-byte k";
+const CHACHA_SIGMA_WORDS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+// Quarter-round rotate-left amounts
+const CHACHA_ROTATIONS: [u8; 4] = [16, 12, 8, 7];
+const SALSA_ROTATIONS: [u8; 4] = [7, 9, 13, 18];
+
+// Window (in bytes) within which split constants or rotations must appear together
+const CHACHA_WINDOW: usize = 64;
+
 // Common crypto library function names
 const CRYPTO_FUNCTION_NAMES: &[&str] = &[
     "AES_encrypt", "AES_decrypt", "AES_set_encrypt_key", "AES_set_decrypt_key",
@@ -79,6 +103,12 @@ impl ObfuscationAnalyzer {
         // Calculate entropy
         let entropy = self.calculate_entropy(content.as_bytes());
         
+        // Check for .vbe/.jse style Script Encoder blocks
+        if SCRIPT_ENCODED_REGEX.is_match(content) {
+            detected_techniques.push((ObfuscationTechnique::ScriptEncoded, 0.99));
+            scores.insert("script_encoded", 0.99);
+        }
+
         // Check for Base64
         if let Some(confidence) = self.detect_base64(content) {
             detected_techniques.push((ObfuscationTechnique::Base64Encoding, confidence));
@@ -91,6 +121,20 @@ impl ObfuscationAnalyzer {
             scores.insert("hex", confidence);
         }
 
+        // Check for Base32, Ascii85 and Base58; the decoders themselves
+        // judge alphabet fit and whether the result reads as text
+        let radix_encodings: [(&dyn DeobfuscationTechnique, ObfuscationTechnique, &str); 3] = [
+            (&Base32Decoder::new(), ObfuscationTechnique::Base32Encoding, "base32"),
+            (&Ascii85Decoder::new(), ObfuscationTechnique::Base85Encoding, "base85"),
+            (&Base58Decoder::new(), ObfuscationTechnique::Base58Encoding, "base58"),
+        ];
+        for (decoder, technique, name) in radix_encodings {
+            if let Some(confidence) = decoder.can_deobfuscate(content) {
+                detected_techniques.push((technique, confidence));
+                scores.insert(name, confidence);
+            }
+        }
+
         // Check for Unicode escapes
         if let Some(confidence) = self.detect_unicode_escape(content) {
             detected_techniques.push((ObfuscationTechnique::UnicodeEscape, confidence));
@@ -103,6 +147,12 @@ impl ObfuscationAnalyzer {
             scores.insert("charcode", confidence);
         }
 
+        // Check for literals pulled out of a string table by index
+        if let Some(confidence) = self.detect_string_array(content) {
+            detected_techniques.push((ObfuscationTechnique::JsObfuscatorIo, confidence));
+            scores.insert("string_array", confidence);
+        }
+
         // Check for eval chains
         if let Some(confidence) = self.detect_eval_chain(content) {
             detected_techniques.push((ObfuscationTechnique::JsEvalChain, confidence));
@@ -133,6 +183,12 @@ impl ObfuscationAnalyzer {
             }
         }
 
+        // Check for RC4-encrypted blobs whose key sits in a nearby string
+        if !crate::techniques::crypto::find_rc4_configs(content.as_bytes()).is_empty() {
+            detected_techniques.push((ObfuscationTechnique::Rc4Encryption, 0.85));
+            scores.insert("rc4", 0.85);
+        }
+
         // Check for crypto constants in binary content
         let crypto_detections = self.detect_crypto_constants(content.as_bytes());
         for detection in crypto_detections {
@@ -243,6 +299,24 @@ impl ObfuscationAnalyzer {
         Some(0.95) // High confidence when found
     }
 
+    fn detect_string_array(&self, content: &str) -> Option<f32> {
+        let lookups = STRING_ARRAY_REGEX
+            .captures_iter(content)
+            .filter_map(|caps| {
+                let name = regex::escape(&caps[1]);
+                Regex::new(&format!(r"{}\[\s*(?:0x[0-9a-fA-F]+|\d+)\s*\]", name)).ok()
+            })
+            .map(|lookup| lookup.find_iter(content).count())
+            .max()?;
+
+        // One lookup is just an array being used
+        if lookups < 2 {
+            return None;
+        }
+
+        Some(if lookups >= 5 { 0.9 } else { 0.7 })
+    }
+
     fn detect_eval_chain(&self, content: &str) -> Option<f32> {
         let eval_count = EVAL_REGEX.find_iter(content).count();
         if eval_count == 0 {
@@ -327,12 +401,18 @@ impl ObfuscationAnalyzer {
     fn determine_deobfuscation_order(&self, techniques: &[(ObfuscationTechnique, f32)]) -> Vec<ObfuscationTechnique> {
         // Define priority order for techniques
         let priority_map: HashMap<&str, i32> = [
+            ("script_encoded", 0),
             ("url", 1),
             ("base64", 2),
             ("hex", 3),
+            ("base32", 3),
+            ("base85", 3),
+            ("base58", 3),
             ("unicode", 4),
             ("charcode", 5),
+            ("string_array", 5),
             ("xor", 6),
+            ("rc4", 6),
             ("eval", 7),
             ("ps_encoded", 8),
         ].iter().cloned().collect();
@@ -343,12 +423,18 @@ impl ObfuscationAnalyzer {
 
         ordered_techniques.sort_by_key(|tech| {
             let tech_name = match tech {
+                ObfuscationTechnique::ScriptEncoded => "script_encoded",
                 ObfuscationTechnique::UrlEncoding => "url",
                 ObfuscationTechnique::Base64Encoding => "base64",
                 ObfuscationTechnique::HexEncoding => "hex",
+                ObfuscationTechnique::Base32Encoding => "base32",
+                ObfuscationTechnique::Base85Encoding => "base85",
+                ObfuscationTechnique::Base58Encoding => "base58",
                 ObfuscationTechnique::UnicodeEscape => "unicode",
                 ObfuscationTechnique::CharCodeConcat => "charcode",
+                ObfuscationTechnique::JsObfuscatorIo => "string_array",
                 ObfuscationTechnique::XorEncryption { .. } => "xor",
+                ObfuscationTechnique::Rc4Encryption => "rc4",
                 ObfuscationTechnique::JsEvalChain => "eval",
                 ObfuscationTechnique::PsEncodedCommand => "ps_encoded",
                 _ => "other",
@@ -432,9 +518,123 @@ impl ObfuscationAnalyzer {
             });
         }
 
+        detections.extend(self.detect_chacha_salsa(data));
+
         detections
     }
 
+    /// Detect ChaCha20/Salsa20 via the sigma constant (as a string or as four
+    /// inlined u32 immediates) and via the quarter-round rotation amounts.
+    fn detect_chacha_salsa(&self, data: &[u8]) -> Vec<crate::types::CryptoDetection> {
+        let mut detections = Vec::new();
+
+        if let Some(offset) = self.find_pattern(data, CHACHA_SIGMA) {
+            detections.push(crate::types::CryptoDetection {
+                algorithm: "ChaCha20".to_string(),
+                offset,
+                confidence: 0.90,
+                context: "ChaCha20/Salsa20 \"expand 32-byte k\" constant detected".to_string(),
+            });
+        } else if let Some(offset) = self.find_split_sigma_words(data) {
+            detections.push(crate::types::CryptoDetection {
+                algorithm: "ChaCha20".to_string(),
+                offset,
+                confidence: 0.85,
+                context: "ChaCha20/Salsa20 sigma constant words detected".to_string(),
+            });
+        }
+
+        let rotations = self.collect_rotations(data);
+        if let Some(offset) = self.find_rotation_set(&rotations, &CHACHA_ROTATIONS) {
+            detections.push(crate::types::CryptoDetection {
+                algorithm: "ChaCha20".to_string(),
+                offset,
+                confidence: 0.75,
+                context: "ChaCha20 quarter-round rotations (16, 12, 8, 7) detected".to_string(),
+            });
+        }
+        if let Some(offset) = self.find_rotation_set(&rotations, &SALSA_ROTATIONS) {
+            detections.push(crate::types::CryptoDetection {
+                algorithm: "Salsa20".to_string(),
+                offset,
+                confidence: 0.75,
+                context: "Salsa20 quarter-round rotations (7, 9, 13, 18) detected".to_string(),
+            });
+        }
+
+        detections
+    }
+
+    /// Find the four sigma words in order, each within `CHACHA_WINDOW` bytes of
+    /// the first (e.g. as `mov dword [state+N], imm32` immediates).
+    fn find_split_sigma_words(&self, data: &[u8]) -> Option<usize> {
+        let first = CHACHA_SIGMA_WORDS[0].to_le_bytes();
+        let mut search_from = 0;
+
+        while let Some(rel) = self.find_pattern(&data[search_from..], &first) {
+            let start = search_from + rel;
+            let window_end = (start + CHACHA_WINDOW).min(data.len());
+            let mut cursor = start + 4;
+            let mut found_all = true;
+
+            for word in &CHACHA_SIGMA_WORDS[1..] {
+                match self.find_pattern(&data[cursor..window_end], &word.to_le_bytes()) {
+                    Some(pos) => cursor += pos + 4,
+                    None => {
+                        found_all = false;
+                        break;
+                    }
+                }
+            }
+
+            if found_all {
+                return Some(start);
+            }
+            search_from = start + 1;
+        }
+
+        None
+    }
+
+    /// Collect x86 `rol/ror r32, imm8` instructions as (offset, rotate-left amount)
+    fn collect_rotations(&self, data: &[u8]) -> Vec<(usize, u8)> {
+        let mut rotations = Vec::new();
+
+        for i in 0..data.len().saturating_sub(2) {
+            if data[i] != 0xC1 {
+                continue;
+            }
+            let modrm = data[i + 1];
+            let imm = data[i + 2] & 0x1F;
+            if imm == 0 {
+                continue;
+            }
+            match modrm {
+                0xC0..=0xC7 => rotations.push((i, imm)),      // rol
+                0xC8..=0xCF => rotations.push((i, 32 - imm)), // ror n == rol 32-n
+                _ => {}
+            }
+        }
+
+        rotations
+    }
+
+    /// Find a window containing all of the given rotation amounts
+    fn find_rotation_set(&self, rotations: &[(usize, u8)], wanted: &[u8; 4]) -> Option<usize> {
+        for (idx, &(start, _)) in rotations.iter().enumerate() {
+            let in_window: Vec<u8> = rotations[idx..].iter()
+                .take_while(|(offset, _)| offset - start < CHACHA_WINDOW)
+                .map(|&(_, amount)| amount)
+                .collect();
+
+            if wanted.iter().all(|w| in_window.contains(w)) {
+                return Some(start);
+            }
+        }
+
+        None
+    }
+
     /// Search for a byte pattern in data, returns offset if found
     fn find_pattern(&self, data: &[u8], pattern: &[u8]) -> Option<usize> {
         if pattern.is_empty() || data.len() < pattern.len() {
@@ -1,10 +1,16 @@
 use crate::types::*;
 use crate::techniques;
+use crate::ml::patterns::PatternDetector;
 use std::time::{Duration, Instant};
 
+/// Called once per decoded layer with the layer index and the technique that
+/// produced it
+pub type ProgressCallback<'a> = &'a mut dyn FnMut(u32, &ObfuscationTechnique);
+
 pub struct DeobfuscationChain {
     config: DeobfuscatorConfig,
     techniques: Vec<Box<dyn techniques::DeobfuscationTechnique>>,
+    ioc_detector: PatternDetector,
 }
 
 impl DeobfuscationChain {
@@ -13,6 +19,7 @@ impl DeobfuscationChain {
         Self {
             config,
             techniques,
+            ioc_detector: PatternDetector::new(),
         }
     }
 
@@ -25,13 +32,20 @@ impl DeobfuscationChain {
             Box::new(encoding::HexDecoder::new()),
             Box::new(encoding::UnicodeDecoder::new()),
             Box::new(encoding::HtmlEntityDecoder::new()),
+            Box::new(script_encoder::ScriptEncoderDecoder::new()),
             Box::new(crypto::XorDecryptor::new()),
             Box::new(crypto::Rc4Decryptor::new()),
-            Box::new(javascript::JsDeobfuscator::new()),
+            Box::new(javascript::JsDeobfuscator::new().with_ast(config.enable_js_ast)),
             Box::new(javascript::JsUnpacker::new()),
             Box::new(powershell::PsDeobfuscator::new()),
         ];
 
+        if config.enable_radix_decoders {
+            techs.push(Box::new(encoding::Base32Decoder::new()));
+            techs.push(Box::new(encoding::Ascii85Decoder::new()));
+            techs.push(Box::new(encoding::Base58Decoder::new()));
+        }
+
         if config.detect_packers {
             techs.push(Box::new(binary::BinaryUnpacker::new()));
         }
@@ -40,6 +54,40 @@ impl DeobfuscationChain {
     }
 
     pub fn deobfuscate(&self, content: &str, analysis: &ObfuscationAnalysis) -> Result<DeobfuscationResult> {
+        self.deobfuscate_layers(content, analysis, 0, None)
+            .map(|result| self.with_iocs(result))
+    }
+
+    /// Like `deobfuscate`, reporting each decoded layer to `progress` as it
+    /// is peeled so callers can show progress on large inputs
+    pub fn deobfuscate_with_progress(
+        &self,
+        content: &str,
+        analysis: &ObfuscationAnalysis,
+        progress: ProgressCallback,
+    ) -> Result<DeobfuscationResult> {
+        self.deobfuscate_layers(content, analysis, 0, Some(progress))
+            .map(|result| self.with_iocs(result))
+    }
+
+    /// Attach the IOCs in the fully decoded content that clear
+    /// `min_ioc_confidence`
+    fn with_iocs(&self, mut result: DeobfuscationResult) -> DeobfuscationResult {
+        result.metadata.iocs = self.ioc_detector
+            .extract_scored_iocs(&result.deobfuscated)
+            .into_iter()
+            .filter(|ioc| ioc.confidence >= self.config.min_ioc_confidence)
+            .collect();
+        result
+    }
+
+    fn deobfuscate_layers(
+        &self,
+        content: &str,
+        analysis: &ObfuscationAnalysis,
+        base_layer: u32,
+        mut progress: Option<ProgressCallback>,
+    ) -> Result<DeobfuscationResult> {
         let start_time = Instant::now();
         let original_entropy = self.calculate_entropy(content.as_bytes());
         
@@ -70,6 +118,9 @@ impl DeobfuscationChain {
                                         }
                                         
                                         current_content = new_content;
+                                        if let Some(callback) = progress.as_mut() {
+                                            callback(base_layer + layer, technique_type);
+                                        }
                                         applied_techniques.push(AppliedTechnique {
                                             technique: technique_type.clone(),
                                             confidence,
@@ -103,7 +154,7 @@ impl DeobfuscationChain {
             let new_analysis = crate::analyzer::ObfuscationAnalyzer::new().analyze(&current_content);
             if !new_analysis.detected_techniques.is_empty() {
                 // Recursively deobfuscate
-                match self.deobfuscate(&current_content, &new_analysis) {
+                match self.deobfuscate_layers(&current_content, &new_analysis, base_layer + layer, progress) {
                     Ok(recursive_result) => {
                         // Merge results
                         for tech in recursive_result.techniques_applied {
@@ -145,6 +196,7 @@ impl DeobfuscationChain {
                 suspicious_patterns,
                 extracted_strings,
                 ml_predictions: None,
+                iocs: Vec::new(),
             },
         })
     }
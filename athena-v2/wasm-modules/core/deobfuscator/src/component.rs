@@ -130,6 +130,9 @@ impl exports::athena::deobfuscator::deobfuscator::Guest for Component {
             timeout_ms: config.timeout_ms,
             extract_strings: config.extract_strings,
             detect_packers: config.detect_packers,
+            min_ioc_confidence: config.min_ioc_confidence,
+            enable_js_ast: true,
+            enable_radix_decoders: config.enable_radix_decoders,
         };
         exports::athena::deobfuscator::deobfuscator::Deobfuscator::new(
             DeobfuscatorResource::new(DeobfuscatorInstance::with_config(internal_config))
@@ -155,6 +158,10 @@ impl exports::athena::deobfuscator::deobfuscator::Guest for Component {
             exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::UnicodeEscape,
             exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::UrlEncoding,
             exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::HtmlEntityEncoding,
+            exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::CustomEncoding("base32".to_string()),
+            exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::CustomEncoding("base85".to_string()),
+            exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::CustomEncoding("base58".to_string()),
+            exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::CustomEncoding("script_encoder".to_string()),
             exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::CharcodeConcat,
             exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::StringReverse,
             exports::athena::deobfuscator::deobfuscator::ObfuscationTechnique::JsEvalChain,
@@ -211,6 +218,10 @@ fn convert_technique_to_wit(technique: &ObfuscationTechnique) -> exports::athena
         ObfuscationTechnique::UnicodeEscape => WitTech::UnicodeEscape,
         ObfuscationTechnique::UrlEncoding => WitTech::UrlEncoding,
         ObfuscationTechnique::HtmlEntityEncoding => WitTech::HtmlEntityEncoding,
+        ObfuscationTechnique::Base32Encoding => WitTech::CustomEncoding("base32".to_string()),
+        ObfuscationTechnique::Base85Encoding => WitTech::CustomEncoding("base85".to_string()),
+        ObfuscationTechnique::Base58Encoding => WitTech::CustomEncoding("base58".to_string()),
+        ObfuscationTechnique::ScriptEncoded => WitTech::CustomEncoding("script_encoder".to_string()),
         ObfuscationTechnique::CharCodeConcat => WitTech::CharcodeConcat,
         ObfuscationTechnique::StringReverse => WitTech::StringReverse,
         ObfuscationTechnique::StringSplit => WitTech::StringSplit,
@@ -240,6 +251,17 @@ fn convert_technique_to_wit(technique: &ObfuscationTechnique) -> exports::athena
     }
 }
 
+fn convert_ioc_kind_to_wit(kind: IocKind) -> exports::athena::deobfuscator::deobfuscator::IocKind {
+    use exports::athena::deobfuscator::deobfuscator::IocKind as WitKind;
+
+    match kind {
+        IocKind::Url => WitKind::Url,
+        IocKind::Domain => WitKind::Domain,
+        IocKind::Ip => WitKind::Ip,
+        IocKind::Path => WitKind::Path,
+    }
+}
+
 fn convert_result_to_wit(result: DeobfuscationResult) -> exports::athena::deobfuscator::deobfuscator::DeobfuscationResult {
     let techniques_applied: Vec<exports::athena::deobfuscator::deobfuscator::AppliedTechnique> =
         result.techniques_applied.iter()
@@ -269,6 +291,14 @@ fn convert_result_to_wit(result: DeobfuscationResult) -> exports::athena::deobfu
         }
     });
 
+    let iocs = result.metadata.iocs.into_iter()
+        .map(|ioc| exports::athena::deobfuscator::deobfuscator::ExtractedIoc {
+            kind: convert_ioc_kind_to_wit(ioc.kind),
+            value: ioc.value,
+            confidence: ioc.confidence,
+        })
+        .collect();
+
     let metadata = exports::athena::deobfuscator::deobfuscator::DeobfuscationMetadata {
         entropy_before: result.metadata.entropy_before,
         entropy_after: result.metadata.entropy_after,
@@ -277,6 +307,7 @@ fn convert_result_to_wit(result: DeobfuscationResult) -> exports::athena::deobfu
         suspicious_patterns: result.metadata.suspicious_patterns,
         extracted_strings,
         ml_predictions,
+        iocs,
     };
 
     exports::athena::deobfuscator::deobfuscator::DeobfuscationResult {
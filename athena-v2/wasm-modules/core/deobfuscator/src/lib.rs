@@ -4,6 +4,7 @@ mod component;
 pub mod types;
 pub mod analyzer;
 pub mod chain;
+pub mod streaming;
 pub mod techniques;
 pub mod ml;
 pub mod tests;
@@ -132,6 +133,8 @@ mod integration_tests {
         assert_eq!(config.timeout_ms, 30000);
         assert!(config.extract_strings);
         assert!(config.detect_packers);
+        assert!(config.enable_js_ast);
+        assert!(config.enable_radix_decoders);
     }
 
     #[test]
@@ -294,6 +297,67 @@ mod integration_tests {
         assert!(detections.len() >= 2);
     }
 
+    #[test]
+    fn test_chacha_sigma_string_detection() {
+        let analyzer = ObfuscationAnalyzer::new();
+
+        let mut data = vec![0u8; 128];
+        data[40..56].copy_from_slice(b"expand 32-byte k");
+
+        let detections = analyzer.detect_crypto_constants(&data);
+
+        assert!(detections.iter().any(|d| d.algorithm == "ChaCha20" && d.offset == 40));
+    }
+
+    #[test]
+    fn test_chacha_split_constant_words_detection() {
+        let analyzer = ObfuscationAnalyzer::new();
+
+        // mov dword [rdi+N], imm32 for each sigma word, as emitted by an inlined ChaCha setup
+        let mut code = Vec::new();
+        for (i, word) in [0x61707865u32, 0x3320646e, 0x79622d32, 0x6b206574].iter().enumerate() {
+            code.extend_from_slice(&[0xC7, 0x47, (i * 4) as u8]);
+            code.extend_from_slice(&word.to_le_bytes());
+        }
+
+        let mut data = vec![0x90u8; 300];
+        data[200..200 + code.len()].copy_from_slice(&code);
+
+        let detections = analyzer.detect_crypto_constants(&data);
+
+        let chacha = detections.iter()
+            .find(|d| d.algorithm == "ChaCha20")
+            .expect("split sigma words should be detected");
+        assert_eq!(chacha.offset, 203);
+        assert!(!detections.iter().any(|d| d.context.contains("\"expand 32-byte k\"")));
+    }
+
+    #[test]
+    fn test_chacha_quarter_round_rotation_detection() {
+        let analyzer = ObfuscationAnalyzer::new();
+
+        // add/xor/rol sequence of one ChaCha quarter round
+        let quarter_round: Vec<u8> = vec![
+            0x01, 0xD8,             // add eax, ebx
+            0x31, 0xC2,             // xor edx, eax
+            0xC1, 0xC2, 0x10,       // rol edx, 16
+            0x01, 0xD1,             // add ecx, edx
+            0x31, 0xCB,             // xor ebx, ecx
+            0xC1, 0xC3, 0x0C,       // rol ebx, 12
+            0x01, 0xD8,             // add eax, ebx
+            0x31, 0xC2,             // xor edx, eax
+            0xC1, 0xCA, 0x18,       // ror edx, 24 (rol 8)
+            0x01, 0xD1,             // add ecx, edx
+            0x31, 0xCB,             // xor ebx, ecx
+            0xC1, 0xC3, 0x07,       // rol ebx, 7
+        ];
+
+        let detections = analyzer.detect_crypto_constants(&quarter_round);
+
+        assert!(detections.iter().any(|d| d.algorithm == "ChaCha20" && d.context.contains("quarter-round")));
+        assert!(!detections.iter().any(|d| d.algorithm == "Salsa20"));
+    }
+
     #[test]
     fn test_control_flow_flattening_detection() {
         use crate::cfg_analysis::{SimpleCfg, SimpleBlock};
@@ -379,4 +443,36 @@ mod integration_tests {
         // Should have some confidence based on pattern matching
         assert!(result.confidence >= 0.0);
     }
+
+    #[test]
+    fn test_progress_callback_per_layer() {
+        // Hex escapes wrapped in base64: two layers to peel
+        let inner = "\\x48\\x65\\x6c\\x6c\\x6f\\x20\\x57\\x6f\\x72\\x6c\\x64\\x21";
+        let content = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, inner);
+
+        let analysis = ObfuscationAnalyzer::new().analyze(&content);
+        let chain = DeobfuscationChain::new(DeobfuscatorConfig::default());
+
+        let mut calls = Vec::new();
+        let mut progress = |layer: u32, technique: &ObfuscationTechnique| {
+            calls.push((layer, technique.clone()));
+        };
+        let result = chain.deobfuscate_with_progress(&content, &analysis, &mut progress).unwrap();
+
+        assert_eq!(result.deobfuscated, "Hello World!");
+        assert_eq!(calls.len(), result.techniques_applied.len());
+        assert_eq!(calls.iter().map(|(layer, _)| *layer).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(matches!(calls[0].1, ObfuscationTechnique::Base64Encoding));
+        assert!(matches!(calls[1].1, ObfuscationTechnique::HexEncoding));
+    }
+
+    #[test]
+    fn test_long_concatenation_chain_does_not_overflow() {
+        let content = format!("var s = {}; eval(s);", vec!["'a'"; 50_000].join("+"));
+
+        let analysis = ObfuscationAnalyzer::new().analyze(&content);
+        let result = DeobfuscationChain::new(DeobfuscatorConfig::default()).deobfuscate(&content, &analysis);
+
+        assert!(result.is_ok());
+    }
 }
@@ -2,7 +2,7 @@ pub mod entropy;
 pub mod patterns;
 
 use crate::types::MlPredictions;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct MlPredictor {
     entropy_analyzer: entropy::EntropyAnalyzer,
@@ -76,8 +76,8 @@ impl MlPredictor {
         &self,
         entropy: &entropy::EntropyFeatures,
         patterns: &patterns::PatternFeatures,
-    ) -> HashMap<String, f32> {
-        let mut probs = HashMap::new();
+    ) -> BTreeMap<String, f32> {
+        let mut probs = BTreeMap::new();
         
         // Base64 - moderate entropy, specific character distribution
         if entropy.global_entropy > 4.0 && entropy.global_entropy < 6.5 {
@@ -1,5 +1,6 @@
 use regex::Regex;
 use once_cell::sync::Lazy;
+use crate::types::{ExtractedIoc, IocKind, DEFAULT_MIN_IOC_CONFIDENCE};
 
 #[derive(Debug, Clone)]
 pub struct PatternFeatures {
@@ -19,6 +20,36 @@ static HEX_PATTERN: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?:\\x[0-9a-fA-F]{2}|0x[0-9a-fA-F]+|[0-9a-fA-F]{8,})").unwrap()
 });
 
+static URL_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://[^\s<>]+").unwrap());
+
+static IP_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}").unwrap()
+});
+
+/// Anything shaped like a host name; loose, so matches outside URLs score low
+static DOMAIN_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?\.)+[a-z]{2,24}\b").unwrap()
+});
+
+static WIN_PATH_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"[A-Za-z]:[/\\][^<>"\|\*\?]+"#).unwrap());
+
+static UNIX_PATH_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"/[A-Za-z0-9_\-./]+").unwrap());
+
+/// Extensions that make a dotted name a file rather than a host
+const FILE_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "sys", "bat", "cmd", "ps1", "psm1", "vbs", "js", "jse", "hta",
+    "lnk", "txt", "log", "dat", "tmp", "ini", "xml", "json", "zip", "doc", "docx",
+    "xls", "xlsx", "pdf",
+];
+
+/// Top-level domains a bare dotted name has to end in to pass as a host
+/// rather than a member access like `document.write`
+const KNOWN_TLDS: &[&str] = &[
+    "com", "net", "org", "info", "biz", "io", "co", "me", "cc", "tv", "xyz", "top", "site", "online",
+    "club", "live", "shop", "pw", "tk", "ml", "ga", "cf", "gq", "su", "ru", "cn", "ua", "kz", "ir",
+    "kp", "in", "br", "de", "fr", "uk", "nl", "pl", "jp", "kr", "us", "eu", "onion", "bit", "local",
+];
+
 pub struct PatternDetector {
     suspicious_patterns: Vec<(Regex, f32, &'static str)>,
     js_patterns: Vec<(Regex, f32)>,
@@ -178,49 +209,86 @@ impl PatternDetector {
         (max_score * 0.7 + avg_score * 0.3).min(1.0)
     }
 
+    /// IOCs at least `DEFAULT_MIN_IOC_CONFIDENCE` sure, as `kind: value`
     pub fn extract_iocs(&self, content: &str) -> Vec<String> {
+        self.extract_scored_iocs(content)
+            .into_iter()
+            .filter(|ioc| ioc.confidence >= DEFAULT_MIN_IOC_CONFIDENCE)
+            .map(|ioc| format!("{}: {}", ioc.kind.label(), ioc.value))
+            .collect()
+    }
+
+    /// IOCs in `content`, each scored by how likely it is to be real
+    pub fn extract_scored_iocs(&self, content: &str) -> Vec<ExtractedIoc> {
         let mut iocs = Vec::new();
-        
-        // Extract URLs
-        if let Ok(url_pattern) = Regex::new(r"https?://[^\s<>]+") {
-            for mat in url_pattern.find_iter(content) {
-                iocs.push(format!("URL: {}", mat.as_str()));
+        let mut push = |kind, value: &str, confidence| {
+            if !iocs.iter().any(|ioc: &ExtractedIoc| ioc.kind == kind && ioc.value == value) {
+                iocs.push(ExtractedIoc { kind, value: value.to_string(), confidence });
+            }
+        };
+
+        // URLs, and the hosts they name
+        let mut url_spans = Vec::new();
+        for mat in URL_PATTERN.find_iter(content) {
+            url_spans.push(mat.range());
+            push(IocKind::Url, mat.as_str(), 0.9);
+            let host = mat.as_str()
+                .split_once("://")
+                .and_then(|(_, rest)| rest.split(['/', '?', '#', ':']).next())
+                .unwrap_or("");
+            if DOMAIN_PATTERN.is_match(host) && !is_ip(host) {
+                push(IocKind::Domain, &host.to_lowercase(), 0.8);
             }
         }
-        
-        // Extract IPs - simplified pattern
-        if let Ok(ip_pattern) = Regex::new(r"\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}") {
-            for mat in ip_pattern.find_iter(content) {
-                let ip = mat.as_str();
-                // Basic validation
-                let valid = ip.split('.').all(|octet| {
-                    octet.parse::<u8>().is_ok()
-                });
-                if valid {
-                    iocs.push(format!("IP: {}", ip));
-                }
+
+        for mat in IP_PATTERN.find_iter(content) {
+            if is_ip(mat.as_str()) {
+                push(IocKind::Ip, mat.as_str(), 0.7);
             }
         }
-        
-        // Extract file paths - Windows style (simplified)
-        if let Ok(win_path_pattern) = Regex::new(r#"[A-Za-z]:[/\\][^<>"\|\*\?]+"#) {
-            for mat in win_path_pattern.find_iter(content) {
-                if mat.as_str().len() > 5 {
-                    iocs.push(format!("Path: {}", mat.as_str()));
-                }
+
+        // Bare names outside any URL: could be a host, could be anything
+        // dotted. Member accesses are skipped, and names that don't end in
+        // a known TLD score below the default threshold.
+        for mat in DOMAIN_PATTERN.find_iter(content) {
+            let inside_url = url_spans.iter().any(|span| span.contains(&mat.start()));
+            let tld = mat.as_str().rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+            if inside_url || is_ip(mat.as_str()) || FILE_EXTENSIONS.contains(&tld.as_str()) {
+                continue;
             }
+            if is_member_access(content, mat.start(), mat.end()) {
+                continue;
+            }
+            let confidence = if KNOWN_TLDS.contains(&tld.as_str()) { 0.4 } else { 0.2 };
+            push(IocKind::Domain, &mat.as_str().to_lowercase(), confidence);
         }
-        
-        // Extract file paths - Unix style
-        if let Ok(unix_path_pattern) = Regex::new(r"/[A-Za-z0-9_\-./]+") {
-            for mat in unix_path_pattern.find_iter(content) {
-                let path = mat.as_str();
-                if path.len() > 5 && !path.starts_with("//") {
-                    iocs.push(format!("Path: {}", path));
-                }
+
+        for mat in WIN_PATH_PATTERN.find_iter(content) {
+            if mat.as_str().len() > 5 {
+                push(IocKind::Path, mat.as_str(), 0.6);
             }
         }
-        
+
+        for mat in UNIX_PATH_PATTERN.find_iter(content) {
+            let path = mat.as_str();
+            if path.len() > 5 && !path.starts_with("//") {
+                push(IocKind::Path, path, 0.5);
+            }
+        }
+
         iocs
     }
-}
\ No newline at end of file
+}
+
+/// Whether the dotted name at `start..end` is code rather than text: a
+/// variable's member (`$x.y`), or called, indexed, chained on or assigned to
+fn is_member_access(content: &str, start: usize, end: usize) -> bool {
+    let before = content[..start].chars().next_back();
+    let after = content[end..].trim_start_matches([' ', '\t']).chars().next();
+    matches!(before, Some('.' | '$' | '_')) || matches!(after, Some('(' | '[' | '.' | '='))
+}
+
+fn is_ip(s: &str) -> bool {
+    let octets: Vec<&str> = s.split('.').collect();
+    octets.len() == 4 && octets.iter().all(|octet| octet.parse::<u8>().is_ok())
+}
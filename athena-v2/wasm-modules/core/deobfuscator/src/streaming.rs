@@ -0,0 +1,329 @@
+use crate::analyzer::ObfuscationAnalyzer;
+use crate::chain::DeobfuscationChain;
+use crate::types::*;
+
+/// Default cap on bytes held between `process_chunk` calls (16MB)
+pub const DEFAULT_MAX_BUFFER_BYTES: usize = 16 * 1024 * 1024;
+
+/// Called once per decoded layer of each chunk with the chunk's offset in
+/// the stream, the layer index and the technique that produced it
+pub type StreamProgressCallback<'a> = &'a mut StreamProgress<'a>;
+
+type StreamProgress<'a> = dyn FnMut(usize, u32, &ObfuscationTechnique) + 'a;
+
+/// Deobfuscates input that arrives in pieces, processing it in
+/// `chunk_size` units as enough data accumulates.
+pub struct StreamingDeobfuscator {
+    analyzer: ObfuscationAnalyzer,
+    chain: DeobfuscationChain,
+    chunk_size: usize,
+    max_buffer_bytes: usize,
+    align_base64: bool,
+    buffer: Vec<u8>,
+    offset: usize,
+}
+
+impl StreamingDeobfuscator {
+    pub fn new(config: DeobfuscatorConfig, chunk_size: usize) -> Self {
+        Self {
+            analyzer: ObfuscationAnalyzer::new(),
+            chain: DeobfuscationChain::new(config),
+            chunk_size: chunk_size.max(1),
+            max_buffer_bytes: DEFAULT_MAX_BUFFER_BYTES,
+            align_base64: false,
+            buffer: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Limit how much unprocessed data may be held at once
+    pub fn with_max_buffer_bytes(mut self, max_buffer_bytes: usize) -> Self {
+        self.max_buffer_bytes = max_buffer_bytes.max(1);
+        self
+    }
+
+    /// Keep runs of base64 characters split on 4-character group boundaries
+    pub fn with_base64_alignment(mut self, align: bool) -> Self {
+        self.align_base64 = align;
+        self
+    }
+
+    /// Bytes received but not yet processed
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Feed the next piece of input.
+    ///
+    /// If accepting `data` would exceed `max_buffer_bytes`, whatever is already
+    /// buffered is processed first even if it is shorter than `chunk_size`.
+    /// Input that can't fit even into an empty buffer is rejected with
+    /// `BufferFull` without being consumed; the caller should split it or
+    /// slow down.
+    pub fn process_chunk(&mut self, data: &[u8]) -> Result<Vec<StreamingDeobfuscationChunk>> {
+        self.process_chunk_inner(data, None)
+    }
+
+    /// Like `process_chunk`, reporting each layer peeled off each processed
+    /// chunk to `progress`
+    pub fn process_chunk_with_progress(
+        &mut self,
+        data: &[u8],
+        progress: StreamProgressCallback,
+    ) -> Result<Vec<StreamingDeobfuscationChunk>> {
+        self.process_chunk_inner(data, Some(progress))
+    }
+
+    fn process_chunk_inner(
+        &mut self,
+        data: &[u8],
+        mut progress: Option<StreamProgressCallback>,
+    ) -> Result<Vec<StreamingDeobfuscationChunk>> {
+        if data.len() > self.max_buffer_bytes {
+            return Err(DeobfuscationError::BufferFull {
+                buffered: self.buffer.len(),
+                limit: self.max_buffer_bytes,
+            });
+        }
+
+        let mut chunks = Vec::new();
+
+        if self.buffer.len() + data.len() > self.max_buffer_bytes && !self.buffer.is_empty() {
+            let len = self.chunk_boundary(self.buffer.len());
+            chunks.push(self.drain(len, progress.as_deref_mut()));
+        }
+
+        self.buffer.extend_from_slice(data);
+
+        while self.buffer.len() >= self.chunk_size {
+            let len = self.chunk_boundary(self.chunk_size);
+            chunks.push(self.drain(len, progress.as_deref_mut()));
+        }
+
+        Ok(chunks)
+    }
+
+    /// Process whatever remains buffered at end of input
+    pub fn flush(&mut self) -> Option<StreamingDeobfuscationChunk> {
+        self.flush_inner(None)
+    }
+
+    /// Like `flush`, reporting each layer peeled off the last chunk to
+    /// `progress`
+    pub fn flush_with_progress(&mut self, progress: StreamProgressCallback) -> Option<StreamingDeobfuscationChunk> {
+        self.flush_inner(Some(progress))
+    }
+
+    fn flush_inner(&mut self, progress: Option<StreamProgressCallback>) -> Option<StreamingDeobfuscationChunk> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        let available = self.buffer.len();
+        Some(self.drain(available, progress))
+    }
+
+    /// Pull `len` back so a chunk never ends inside a UTF-8 sequence (or,
+    /// with base64 alignment, inside a 4-character group). The remainder
+    /// stays buffered for the next chunk.
+    fn chunk_boundary(&self, len: usize) -> usize {
+        let mut end = len;
+        while end > 0 && end < self.buffer.len() && is_utf8_continuation(self.buffer[end]) {
+            end -= 1;
+        }
+
+        // The buffer itself may end partway through a character whose
+        // remaining bytes haven't arrived yet
+        if end == self.buffer.len() {
+            if let Some(lead) = self.buffer[..end].iter().rposition(|&b| !is_utf8_continuation(b)) {
+                if end - lead < 4 && lead + utf8_sequence_len(self.buffer[lead]) > end {
+                    end = lead;
+                }
+            }
+        }
+
+        if self.align_base64 && end < self.buffer.len() && is_base64_char(self.buffer[end]) {
+            let run_start = self.buffer[..end]
+                .iter()
+                .rposition(|&b| !is_base64_char(b))
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            let aligned = run_start + (end - run_start) / 4 * 4;
+            if aligned > 0 {
+                end = aligned;
+            }
+        }
+
+        // A boundary at 0 would make no progress; fall back to the raw size
+        if end == 0 {
+            len
+        } else {
+            end
+        }
+    }
+
+    fn drain<'p>(
+        &mut self,
+        len: usize,
+        progress: Option<&mut StreamProgress<'p>>,
+    ) -> StreamingDeobfuscationChunk {
+        let bytes: Vec<u8> = self.buffer.drain(..len).collect();
+        let offset = self.offset;
+        self.offset += bytes.len();
+
+        let (result, error) = match String::from_utf8(bytes) {
+            Ok(text) => {
+                let analysis = self.analyzer.analyze(&text);
+                let deobfuscated = match progress {
+                    Some(progress) => self.chain.deobfuscate_with_progress(&text, &analysis, &mut |layer, technique| {
+                        progress(offset, layer, technique)
+                    }),
+                    None => self.chain.deobfuscate(&text, &analysis),
+                };
+                match deobfuscated {
+                    Ok(result) => (Some(result), None),
+                    Err(e) => (None, Some(e.to_string())),
+                }
+            }
+            Err(e) => (None, Some(format!("Invalid UTF-8: {}", e))),
+        };
+
+        StreamingDeobfuscationChunk {
+            offset,
+            size: len,
+            result,
+            error,
+        }
+    }
+}
+
+fn is_utf8_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+fn utf8_sequence_len(lead: u8) -> usize {
+    match lead {
+        0xF0..=0xF7 => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
+
+fn is_base64_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'/' | b'=')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_drain_at_chunk_size() {
+        let mut stream = StreamingDeobfuscator::new(DeobfuscatorConfig::default(), 16);
+
+        assert!(stream.process_chunk(b"plain text ").unwrap().is_empty());
+        let chunks = stream.process_chunk(b"continues here and more").unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].offset, chunks[0].size), (0, 16));
+        assert_eq!((chunks[1].offset, chunks[1].size), (16, 16));
+        assert_eq!(stream.buffered_bytes(), 2);
+
+        let last = stream.flush().unwrap();
+        assert_eq!((last.offset, last.size), (32, 2));
+        assert!(stream.flush().is_none());
+    }
+
+    #[test]
+    fn test_backpressure_past_buffer_limit() {
+        let mut stream = StreamingDeobfuscator::new(DeobfuscatorConfig::default(), 1024)
+            .with_max_buffer_bytes(64);
+
+        assert!(stream.process_chunk(&[b'a'; 50]).unwrap().is_empty());
+        assert_eq!(stream.buffered_bytes(), 50);
+
+        // Doesn't fit alongside what's buffered: the buffered data is processed early
+        let chunks = stream.process_chunk(&[b'b'; 50]).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].size, 50);
+        assert_eq!(stream.buffered_bytes(), 50);
+
+        // Larger than the whole buffer: rejected without being consumed
+        match stream.process_chunk(&[b'c'; 100]) {
+            Err(DeobfuscationError::BufferFull { buffered, limit }) => {
+                assert_eq!(buffered, 50);
+                assert_eq!(limit, 64);
+            }
+            other => panic!("expected BufferFull, got {:?}", other.map(|c| c.len())),
+        }
+        assert_eq!(stream.buffered_bytes(), 50);
+    }
+
+    #[test]
+    fn test_chunk_boundary_keeps_multibyte_characters_whole() {
+        // 'ö' is two bytes and straddles the 8-byte chunk boundary
+        let input = "hello wörld, ça va très bien";
+        assert!(!input.is_char_boundary(8));
+
+        let bytes = input.as_bytes();
+
+        // Whole input at once: the drain boundary backs off to the character edge
+        let mut stream = StreamingDeobfuscator::new(DeobfuscatorConfig::default(), 8);
+        let mut chunks = stream.process_chunk(bytes).unwrap();
+        chunks.extend(stream.flush());
+        assert_eq!(chunks[0].size, 7);
+
+        // Producer splits the character itself: the lead byte is carried over
+        let mut stream = StreamingDeobfuscator::new(DeobfuscatorConfig::default(), 8);
+        let mut chunks = stream.process_chunk(&bytes[..8]).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].size, 7);
+        assert_eq!(stream.buffered_bytes(), 1);
+        chunks.extend(stream.process_chunk(&bytes[8..]).unwrap());
+        chunks.extend(stream.flush());
+
+        assert!(chunks.iter().all(|c| c.error.is_none()));
+
+        let rebuilt: String = chunks.iter()
+            .map(|c| c.result.as_ref().unwrap().original.as_str())
+            .collect();
+        assert_eq!(rebuilt, input);
+        assert_eq!(chunks.iter().map(|c| c.size).sum::<usize>(), input.len());
+    }
+
+    #[test]
+    fn test_progress_reported_per_chunk_layer() {
+        let mut stream = StreamingDeobfuscator::new(DeobfuscatorConfig::default(), 52);
+        let encoded = b"SGVsbG8gV29ybGQhIFRoaXMgaXMgYSBsb25nZXIgc3RyaW5nLg==";
+
+        let mut layers = Vec::new();
+        let mut record = |offset: usize, layer: u32, technique: &ObfuscationTechnique| {
+            layers.push((offset, layer, technique.clone()));
+        };
+        assert_eq!(stream.process_chunk_with_progress(encoded, &mut record).unwrap().len(), 1);
+        assert_eq!(stream.process_chunk_with_progress(encoded, &mut record).unwrap().len(), 1);
+        assert!(stream.flush_with_progress(&mut record).is_none());
+
+        assert_eq!(
+            layers,
+            vec![
+                (0, 0, ObfuscationTechnique::Base64Encoding),
+                (52, 0, ObfuscationTechnique::Base64Encoding),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunk_boundary_aligns_base64_groups() {
+        let mut stream = StreamingDeobfuscator::new(DeobfuscatorConfig::default(), 10)
+            .with_base64_alignment(true);
+
+        let chunks = stream.process_chunk(b"SGVsbG8gV29ybGQh").unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].size, 8);
+        assert_eq!(stream.buffered_bytes(), 8);
+
+        let last = stream.flush().unwrap();
+        assert_eq!((last.offset, last.size), (8, 8));
+    }
+}
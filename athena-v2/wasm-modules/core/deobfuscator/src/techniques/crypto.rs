@@ -118,33 +118,6 @@ impl Rc4Decryptor {
         Self
     }
 
-    fn rc4_decrypt(&self, data: &[u8], key: &[u8]) -> Vec<u8> {
-        let mut s: Vec<u8> = (0..=255).collect();
-        let mut j = 0u8;
-
-        // Key scheduling
-        for i in 0..256 {
-            j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
-            s.swap(i, j as usize);
-        }
-
-        // Pseudo-random generation
-        let mut i = 0u8;
-        let mut j = 0u8;
-        let mut output = Vec::with_capacity(data.len());
-
-        for &byte in data {
-            i = i.wrapping_add(1);
-            j = j.wrapping_add(s[i as usize]);
-            s.swap(i as usize, j as usize);
-            
-            let k = s[(s[i as usize].wrapping_add(s[j as usize])) as usize];
-            output.push(byte ^ k);
-        }
-
-        output
-    }
-
     fn try_common_rc4_keys(&self, data: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
         let common_keys: Vec<&[u8]> = vec![
             b"key",
@@ -159,7 +132,7 @@ impl Rc4Decryptor {
         ];
 
         for key in &common_keys {
-            let decrypted = self.rc4_decrypt(data, key);
+            let decrypted = rc4_decrypt(key, data);
             
             // Check if result is readable
             let printable_ratio = decrypted.iter()
@@ -193,6 +166,9 @@ impl DeobfuscationTechnique for Rc4Decryptor {
             } else {
                 Some(0.3) // Low confidence without successful decryption
             }
+        } else if !find_rc4_configs(bytes).is_empty() {
+            // Embedded configs are small and rarely push whole-input entropy up
+            Some(0.85)
         } else {
             None
         }
@@ -211,7 +187,25 @@ impl DeobfuscationTechnique for Rc4Decryptor {
                 Err(_) => Err("Failed to decode RC4 result as UTF-8".to_string()),
             }
         } else {
-            Err("Could not decrypt with common RC4 keys".to_string())
+            let configs = find_rc4_configs(bytes);
+            if configs.is_empty() {
+                return Err("Could not decrypt with common RC4 keys".to_string());
+            }
+
+            let output = configs.iter()
+                .map(|c| String::from_utf8_lossy(&c.plaintext).to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let context = configs.iter()
+                .map(|c| format!("RC4 config at offset {} decrypted with key {:?}", c.offset, String::from_utf8_lossy(&c.key)))
+                .collect::<Vec<_>>()
+                .join("; ");
+
+            Ok(TechniqueResult {
+                success: true,
+                output,
+                context: Some(context),
+            })
         }
     }
 
@@ -220,6 +214,149 @@ impl DeobfuscationTechnique for Rc4Decryptor {
     }
 }
 
+/// Decrypt (or encrypt) `data` with RC4 under `key`.
+pub fn rc4_decrypt(key: &[u8], data: &[u8]) -> Vec<u8> {
+    if key.is_empty() {
+        return data.to_vec();
+    }
+
+    let mut s: Vec<u8> = (0..=255).collect();
+    let mut j = 0u8;
+
+    // Key scheduling
+    for i in 0..256 {
+        j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+        s.swap(i, j as usize);
+    }
+
+    // Pseudo-random generation
+    let mut i = 0u8;
+    let mut j = 0u8;
+    let mut output = Vec::with_capacity(data.len());
+
+    for &byte in data {
+        i = i.wrapping_add(1);
+        j = j.wrapping_add(s[i as usize]);
+        s.swap(i as usize, j as usize);
+
+        let k = s[(s[i as usize].wrapping_add(s[j as usize])) as usize];
+        output.push(byte ^ k);
+    }
+
+    output
+}
+
+/// An RC4-encrypted blob recovered using a key found in nearby strings
+#[derive(Debug, Clone)]
+pub struct Rc4Config {
+    pub key: Vec<u8>,
+    pub offset: usize,
+    pub length: usize,
+    pub plaintext: Vec<u8>,
+}
+
+const RC4_MIN_KEY_LEN: usize = 4;
+const RC4_MAX_KEY_LEN: usize = 64;
+const RC4_MAX_CANDIDATES: usize = 32;
+const RC4_MAX_BLOB_LEN: usize = 4096;
+const RC4_MIN_PLAINTEXT_LEN: usize = 8;
+
+/// Look for config blobs encrypted with a key stored as a plain string in the same buffer.
+///
+/// Malware commonly stores the RC4 key as a NUL-terminated string right next to
+/// the encrypted config. Every printable string is tried as a key against every
+/// blob that follows a string; a decryption is accepted when it yields readable,
+/// NUL-terminated text.
+pub fn find_rc4_configs(data: &[u8]) -> Vec<Rc4Config> {
+    let strings = printable_runs(data);
+
+    let keys: Vec<&[u8]> = strings.iter()
+        .filter(|(start, end)| (RC4_MIN_KEY_LEN..=RC4_MAX_KEY_LEN).contains(&(end - start)))
+        .map(|&(start, end)| &data[start..end])
+        .take(RC4_MAX_CANDIDATES)
+        .collect();
+
+    let blobs: Vec<(usize, usize)> = strings.iter()
+        .filter_map(|&(_, end)| {
+            let start = end + data[end..].iter().take_while(|&&b| b == 0).count();
+            let blob_end = find_blob_end(data, start);
+            if blob_end - start >= RC4_MIN_PLAINTEXT_LEN {
+                Some((start, blob_end))
+            } else {
+                None
+            }
+        })
+        .take(RC4_MAX_CANDIDATES)
+        .collect();
+
+    let mut configs = Vec::new();
+    for &(start, end) in &blobs {
+        if configs.iter().any(|c: &Rc4Config| c.offset == start) {
+            continue;
+        }
+
+        for key in &keys {
+            let decrypted = rc4_decrypt(key, &data[start..end]);
+            let text_len = decrypted.iter().position(|&b| b == 0).unwrap_or(decrypted.len());
+            let text = &decrypted[..text_len];
+
+            if text.len() >= RC4_MIN_PLAINTEXT_LEN && is_readable(text) {
+                configs.push(Rc4Config {
+                    key: key.to_vec(),
+                    offset: start,
+                    length: text_len,
+                    plaintext: text.to_vec(),
+                });
+                break;
+            }
+        }
+    }
+
+    configs
+}
+
+/// Ranges of printable ASCII runs (candidate key strings)
+fn printable_runs(data: &[u8]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+
+    for (i, &b) in data.iter().enumerate() {
+        let printable = (0x21..0x7f).contains(&b);
+        match (printable, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if i - s >= RC4_MIN_KEY_LEN && b == 0 {
+                    runs.push((s, i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    runs
+}
+
+/// A blob ends at a run of 4 NUL bytes, the end of data, or the size cap
+fn find_blob_end(data: &[u8], start: usize) -> usize {
+    let limit = (start + RC4_MAX_BLOB_LEN).min(data.len());
+    let mut i = start;
+    while i < limit {
+        if data[i..limit.min(i + 4)] == [0, 0, 0, 0] {
+            return i;
+        }
+        i += 1;
+    }
+    limit
+}
+
+fn is_readable(text: &[u8]) -> bool {
+    let printable = text.iter()
+        .filter(|&&b| (0x20..0x7f).contains(&b) || b == b'\n' || b == b'\r' || b == b'\t')
+        .count();
+    printable as f32 / text.len() as f32 >= 0.95
+}
+
 fn calculate_entropy(data: &[u8]) -> f32 {
     if data.is_empty() {
         return 0.0;
@@ -241,4 +378,44 @@ fn calculate_entropy(data: &[u8]) -> f32 {
     }
 
     entropy
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc4_round_trip() {
+        let plaintext = b"Attack at dawn";
+        let ciphertext = rc4_decrypt(b"Secret", plaintext);
+
+        // Known RC4 test vector
+        assert_eq!(hex::encode(&ciphertext), "45a01f645fc35b383552544b9bf5");
+        assert_eq!(rc4_decrypt(b"Secret", &ciphertext), plaintext);
+    }
+
+    #[test]
+    fn test_recover_config_with_nearby_key() {
+        let key = b"s3cr3tK3y";
+        let config = b"host=evil.example.com;port=443;sleep=60\0";
+
+        let mut data = b"MZ\x90\x00".to_vec();
+        data.extend_from_slice(key);
+        data.push(0);
+        data.extend_from_slice(&rc4_decrypt(key, config));
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        data.extend_from_slice(b"kernel32.dll\0");
+
+        let configs = find_rc4_configs(&data);
+
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].key, key.to_vec());
+        assert_eq!(configs[0].offset, 4 + key.len() + 1);
+        assert_eq!(configs[0].plaintext, b"host=evil.example.com;port=443;sleep=60".to_vec());
+    }
+
+    #[test]
+    fn test_no_config_in_plain_text() {
+        assert!(find_rc4_configs(b"just some ordinary text\0with strings\0").is_empty());
+    }
+}
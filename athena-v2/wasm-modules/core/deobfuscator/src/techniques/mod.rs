@@ -1,7 +1,9 @@
 pub mod encoding;
 pub mod crypto;
 pub mod javascript;
+pub mod js_ast;
 pub mod powershell;
+pub mod script_encoder;
 pub mod binary;
 
 use crate::types::ObfuscationTechnique;
@@ -0,0 +1,232 @@
+use super::{DeobfuscationTechnique, TechniqueResult};
+use crate::types::ObfuscationTechnique;
+use base64::{Engine as _, engine::general_purpose};
+use regex::Regex;
+
+/// Closes an encoded block, after the checksum field
+const END_MARKER: &str = "^#~@";
+
+/// Length and checksum are each six base64 characters then `==`
+const FIELD_LEN: usize = 8;
+
+/// Which of the three table columns decodes the character at each position
+/// of the block, cycling every 64 characters
+const COLUMN_ORDER: [usize; 64] = [
+    0, 1, 2, 0, 1, 2, 1, 2, 2, 1, 2, 1, 0, 2, 1, 2, 0, 2, 1, 2, 0, 0, 1, 2, 2, 1, 0, 2, 1, 2, 2, 1,
+    0, 0, 2, 1, 2, 1, 2, 0, 2, 0, 0, 1, 2, 0, 2, 1, 0, 2, 1, 2, 0, 0, 1, 2, 2, 0, 0, 1, 2, 0, 2, 1,
+];
+
+/// Plaintext of an encoded tab under each column
+const TAB_ROW: [u8; 3] = [0x57, 0x6E, 0x7B];
+
+/// Plaintext of encoded characters 0x20 to 0x7F under each column. `<`, `>`
+/// and `@` are escaped rather than encoded, so their rows are never used.
+const PRINTABLE_ROWS: [[u8; 3]; 96] = [
+    [0x2E, 0x2D, 0x32], [0x47, 0x75, 0x30], [0x7A, 0x52, 0x21], [0x56, 0x60, 0x29],
+    [0x42, 0x71, 0x5B], [0x6A, 0x5E, 0x38], [0x2F, 0x49, 0x33], [0x26, 0x5C, 0x3D],
+    [0x49, 0x62, 0x58], [0x41, 0x7D, 0x3A], [0x34, 0x29, 0x35], [0x32, 0x36, 0x65],
+    [0x5B, 0x20, 0x39], [0x76, 0x7C, 0x5C], [0x72, 0x7A, 0x56], [0x43, 0x7F, 0x73],
+    [0x38, 0x6B, 0x66], [0x39, 0x63, 0x4E], [0x70, 0x33, 0x45], [0x45, 0x2B, 0x6B],
+    [0x68, 0x68, 0x62], [0x71, 0x51, 0x59], [0x4F, 0x66, 0x78], [0x09, 0x76, 0x5E],
+    [0x62, 0x31, 0x7D], [0x44, 0x64, 0x4A], [0x23, 0x54, 0x6D], [0x75, 0x43, 0x71],
+    [0x3C, 0x3C, 0x3C], [0x7E, 0x3A, 0x60], [0x3E, 0x3E, 0x3E], [0x5E, 0x7E, 0x53],
+    [0x40, 0x40, 0x40], [0x77, 0x45, 0x42], [0x4A, 0x2C, 0x27], [0x61, 0x2A, 0x48],
+    [0x5D, 0x74, 0x72], [0x22, 0x27, 0x75], [0x4B, 0x37, 0x31], [0x6F, 0x44, 0x37],
+    [0x4E, 0x79, 0x4D], [0x3B, 0x59, 0x52], [0x4C, 0x2F, 0x22], [0x50, 0x6F, 0x54],
+    [0x67, 0x26, 0x6A], [0x2A, 0x72, 0x47], [0x7D, 0x6A, 0x64], [0x74, 0x39, 0x2D],
+    [0x54, 0x7B, 0x20], [0x2B, 0x3F, 0x7F], [0x2D, 0x38, 0x2E], [0x2C, 0x77, 0x4C],
+    [0x30, 0x67, 0x5D], [0x6E, 0x53, 0x7E], [0x6B, 0x47, 0x6C], [0x66, 0x34, 0x6F],
+    [0x35, 0x78, 0x79], [0x25, 0x5D, 0x74], [0x21, 0x30, 0x43], [0x64, 0x23, 0x26],
+    [0x4D, 0x5A, 0x76], [0x52, 0x5B, 0x25], [0x63, 0x6C, 0x24], [0x3F, 0x48, 0x2B],
+    [0x7B, 0x55, 0x28], [0x78, 0x70, 0x23], [0x29, 0x69, 0x41], [0x28, 0x2E, 0x34],
+    [0x73, 0x4C, 0x09], [0x59, 0x21, 0x2A], [0x33, 0x24, 0x44], [0x7F, 0x4E, 0x3F],
+    [0x6D, 0x50, 0x77], [0x55, 0x09, 0x3B], [0x53, 0x56, 0x55], [0x7C, 0x73, 0x69],
+    [0x3A, 0x35, 0x61], [0x5F, 0x61, 0x63], [0x65, 0x4B, 0x50], [0x46, 0x58, 0x67],
+    [0x58, 0x3B, 0x51], [0x31, 0x57, 0x49], [0x69, 0x22, 0x4F], [0x6C, 0x6D, 0x46],
+    [0x5A, 0x4D, 0x68], [0x48, 0x25, 0x7C], [0x27, 0x28, 0x36], [0x5C, 0x46, 0x70],
+    [0x3D, 0x4A, 0x6E], [0x24, 0x32, 0x7A], [0x79, 0x41, 0x2F], [0x37, 0x3D, 0x5F],
+    [0x60, 0x5F, 0x4B], [0x51, 0x4F, 0x5A], [0x20, 0x42, 0x2C], [0x36, 0x65, 0x57],
+];
+
+/// A decoded `#@~^...^#~@` block
+struct DecodedBlock {
+    start: usize,
+    end: usize,
+    script: String,
+    checksum_ok: bool,
+}
+
+/// Decodes scripts encoded by Microsoft's Script Encoder: `.vbe` and `.jse`
+/// files, and `VBScript.Encode`/`JScript.Encode` blocks in HTML and WSF
+pub struct ScriptEncoderDecoder {
+    header_pattern: Regex,
+}
+
+impl ScriptEncoderDecoder {
+    pub fn new() -> Self {
+        Self {
+            header_pattern: Regex::new(r"#@~\^[A-Za-z0-9+/]{6}==").unwrap(),
+        }
+    }
+
+    fn decode_blocks(&self, content: &str) -> Vec<DecodedBlock> {
+        let mut blocks = Vec::new();
+        let mut search_from = 0;
+
+        while let Some(header) = self.header_pattern.find_at(content, search_from) {
+            search_from = header.end();
+            let Some(end_offset) = content[header.end()..].find(END_MARKER) else { break };
+            let trailer = header.end() + end_offset;
+            let Some(data_end) = trailer.checked_sub(FIELD_LEN).filter(|&e| e >= header.end()) else { continue };
+
+            let script = decode_script(&content[header.end()..data_end]);
+            let checksum_ok = field_value(&content[data_end..trailer]) == Some(checksum(&script));
+            blocks.push(DecodedBlock {
+                start: header.start(),
+                end: trailer + END_MARKER.len(),
+                script,
+                checksum_ok,
+            });
+            search_from = trailer + END_MARKER.len();
+        }
+
+        blocks
+    }
+}
+
+/// Undo the `@` escapes and the per-position substitution. The position
+/// counts every ASCII character after unescaping, so an escape pair takes
+/// one slot; anything beyond ASCII is copied through and takes none.
+fn decode_script(encoded: &str) -> String {
+    let mut script = String::with_capacity(encoded.len());
+    let mut position = 0;
+    let mut chars = encoded.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let c = match (c, chars.peek()) {
+            ('@', Some(&escaped @ ('&' | '#' | '*' | '!' | '$'))) => {
+                chars.next();
+                match escaped {
+                    '&' => '\n',
+                    '#' => '\r',
+                    '*' => '>',
+                    '!' => '<',
+                    _ => '@',
+                }
+            }
+            _ => c,
+        };
+        if !c.is_ascii() {
+            script.push(c);
+            continue;
+        }
+
+        let row = match c as u8 {
+            b'\t' => Some(&TAB_ROW),
+            b'<' | b'>' | b'@' => None,
+            byte @ 0x20..=0x7F => Some(&PRINTABLE_ROWS[(byte - 0x20) as usize]),
+            _ => None,
+        };
+        script.push(row.map_or(c, |row| row[COLUMN_ORDER[position % 64]] as char));
+        position += 1;
+    }
+
+    script
+}
+
+/// A length or checksum field: a little-endian u32 in base64
+fn field_value(field: &str) -> Option<u32> {
+    let bytes = general_purpose::STANDARD.decode(field).ok()?;
+    Some(u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?))
+}
+
+/// The sum of the plaintext characters, which the encoder stores after
+/// the data
+fn checksum(script: &str) -> u32 {
+    script.chars().fold(0u32, |sum, c| sum.wrapping_add(c as u32))
+}
+
+impl DeobfuscationTechnique for ScriptEncoderDecoder {
+    fn name(&self) -> &'static str {
+        "Script Encoder Decoder"
+    }
+
+    fn can_deobfuscate(&self, content: &str) -> Option<f32> {
+        if self.header_pattern.is_match(content) && content.contains(END_MARKER) {
+            Some(0.99)
+        } else {
+            None
+        }
+    }
+
+    fn deobfuscate(&self, content: &str) -> Result<TechniqueResult, String> {
+        let blocks = self.decode_blocks(content);
+        if blocks.is_empty() {
+            return Ok(TechniqueResult {
+                success: false,
+                output: content.to_string(),
+                context: None,
+            });
+        }
+
+        let mut output = String::with_capacity(content.len());
+        let mut copied_to = 0;
+        for block in &blocks {
+            output.push_str(&content[copied_to..block.start]);
+            output.push_str(&block.script);
+            copied_to = block.end;
+        }
+        output.push_str(&content[copied_to..]);
+
+        // A bad checksum means the block was tampered with or truncated;
+        // Windows refuses to run it, but the text is still worth showing
+        let bad_checksums = blocks.iter().filter(|b| !b.checksum_ok).count();
+        let mut context = format!("Decoded {} Script Encoder block(s)", blocks.len());
+        if bad_checksums > 0 {
+            context.push_str(&format!(", {} with a bad checksum", bad_checksums));
+        }
+
+        Ok(TechniqueResult {
+            success: true,
+            output,
+            context: Some(context),
+        })
+    }
+
+    fn matches_type(&self, technique_type: &ObfuscationTechnique) -> bool {
+        matches!(technique_type, ObfuscationTechnique::ScriptEncoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoded_vbscript_recovered() {
+        let decoder = ScriptEncoderDecoder::new();
+        let vbe = "#@~^DgAAAA==\\ko$K6,JC\x7fV^GJqAQAAA==^#~@";
+
+        assert_eq!(decoder.can_deobfuscate(vbe), Some(0.99));
+        let result = decoder.deobfuscate(vbe).unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "MsgBox \"Hello\"");
+        assert_eq!(result.context.unwrap(), "Decoded 1 Script Encoder block(s)");
+    }
+
+    #[test]
+    fn test_escaped_line_breaks_and_html_block() {
+        let html = "<SCRIPT LANGUAGE=\"VBScript.Encode\">#@~^QwAAAA==j\x7fY~kt,'P;D\x7fCY\x7fr8L\x7fmOcr\x7f?1.kaYRU4nV^J*@#@&/4 \"EUPr^:9P&^,htGCskE~,TlhQAAA==^#~@</SCRIPT>";
+
+        let result = ScriptEncoderDecoder::new().deobfuscate(html).unwrap();
+        assert_eq!(
+            result.output,
+            "<SCRIPT LANGUAGE=\"VBScript.Encode\">Set sh = CreateObject(\"WScript.Shell\")\r\nsh.Run \"cmd /c whoami\", 0</SCRIPT>"
+        );
+
+        // Flipping a data character breaks the checksum but still decodes
+        let tampered = html.replacen("/4 ", "/5 ", 1);
+        let result = ScriptEncoderDecoder::new().deobfuscate(&tampered).unwrap();
+        assert!(result.context.unwrap().contains("1 with a bad checksum"));
+    }
+}
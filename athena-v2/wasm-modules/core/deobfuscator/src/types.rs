@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeobfuscationResult {
@@ -26,6 +26,13 @@ pub enum ObfuscationTechnique {
     UnicodeEscape,
     UrlEncoding,
     HtmlEntityEncoding,
+    /// RFC 4648 Base32
+    Base32Encoding,
+    /// Ascii85
+    Base85Encoding,
+    Base58Encoding,
+    /// Microsoft Script Encoder, as in `.vbe` and `.jse` files
+    ScriptEncoded,
     
     // String manipulation
     CharCodeConcat,
@@ -77,6 +84,10 @@ pub struct DeobfuscationMetadata {
     pub suspicious_patterns: Vec<String>,
     pub extracted_strings: Vec<ExtractedString>,
     pub ml_predictions: Option<MlPredictions>,
+    /// Indicators found in the decoded content at or above
+    /// `DeobfuscatorConfig::min_ioc_confidence`
+    #[serde(default)]
+    pub iocs: Vec<ExtractedIoc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +98,34 @@ pub struct ExtractedString {
     pub offset: usize,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedIoc {
+    pub kind: IocKind,
+    pub value: String,
+    /// How sure the extractor is that this is a real indicator: high for a
+    /// full URL, low for a bare name that merely looks like a domain
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IocKind {
+    Url,
+    Domain,
+    Ip,
+    Path,
+}
+
+impl IocKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IocKind::Url => "URL",
+            IocKind::Domain => "Domain",
+            IocKind::Ip => "IP",
+            IocKind::Path => "Path",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CryptoDetection {
     pub algorithm: String,
@@ -98,7 +137,7 @@ pub struct CryptoDetection {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MlPredictions {
     pub obfuscation_probability: f32,
-    pub technique_probabilities: HashMap<String, f32>,
+    pub technique_probabilities: BTreeMap<String, f32>,
     pub malware_probability: f32,
 }
 
@@ -110,6 +149,21 @@ pub struct ObfuscationAnalysis {
     pub ml_hints: Option<MlPredictions>,
 }
 
+/// IOCs below this confidence are dropped unless configured otherwise
+pub const DEFAULT_MIN_IOC_CONFIDENCE: f32 = 0.3;
+
+fn default_min_ioc_confidence() -> f32 {
+    DEFAULT_MIN_IOC_CONFIDENCE
+}
+
+fn default_enable_js_ast() -> bool {
+    true
+}
+
+fn default_enable_radix_decoders() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeobfuscatorConfig {
     pub max_layers: u32,
@@ -118,6 +172,17 @@ pub struct DeobfuscatorConfig {
     pub timeout_ms: u64,
     pub extract_strings: bool,
     pub detect_packers: bool,
+    /// Only IOCs at least this confident are reported
+    #[serde(default = "default_min_ioc_confidence")]
+    pub min_ioc_confidence: f32,
+    /// Parse JavaScript and fold it on the syntax tree before the regex
+    /// heuristics run
+    #[serde(default = "default_enable_js_ast")]
+    pub enable_js_ast: bool,
+    /// Try the Base32, Ascii85 and Base58 decoders. Plain alphanumeric text
+    /// is often valid in all three, so noisy inputs may want them off
+    #[serde(default = "default_enable_radix_decoders")]
+    pub enable_radix_decoders: bool,
 }
 
 impl Default for DeobfuscatorConfig {
@@ -129,6 +194,9 @@ impl Default for DeobfuscatorConfig {
             timeout_ms: 30000,
             extract_strings: true,
             detect_packers: true,
+            min_ioc_confidence: DEFAULT_MIN_IOC_CONFIDENCE,
+            enable_js_ast: true,
+            enable_radix_decoders: true,
         }
     }
 }
@@ -151,6 +219,7 @@ pub enum DeobfuscationError {
     MemoryLimitExceeded,
     UnsupportedFormat(String),
     ParseError(String),
+    BufferFull { buffered: usize, limit: usize },
 }
 
 impl std::fmt::Display for DeobfuscationError {
@@ -164,6 +233,9 @@ impl std::fmt::Display for DeobfuscationError {
             Self::MemoryLimitExceeded => write!(f, "Memory limit exceeded"),
             Self::UnsupportedFormat(fmt) => write!(f, "Unsupported format: {}", fmt),
             Self::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            Self::BufferFull { buffered, limit } => {
+                write!(f, "Stream buffer full: {} bytes buffered, limit {}", buffered, limit)
+            }
         }
     }
 }
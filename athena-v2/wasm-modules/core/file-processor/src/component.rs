@@ -9,7 +9,10 @@ use crate::detector::FileDetector;
 use crate::validator::FileValidator;
 use crate::extractor::ContentExtractor;
 use crate::types::FileFormat as InternalFileFormat;
+use crate::types::{AnalysisBudget, BudgetLimit, NetworkIndicatorType, ParserLimits, PatternLimits};
 use crate::parser;
+use crate::recursive;
+use crate::secrets::{SecretKind, SecretScanner, SecretScannerConfig};
 
 // ============================================================================
 // Component struct - implements all interfaces
@@ -38,6 +41,37 @@ impl exports::athena::file_processor::detector::Guest for Component {
         let internal_format = convert_format_from_wit(format);
         detector.get_mime_type(internal_format)
     }
+
+    fn detect_extension_mismatch(
+        buffer: Vec<u8>,
+        filename: String,
+    ) -> Option<exports::athena::file_processor::detector::MismatchWarning> {
+        let content_format = FileDetector::new().detect_format(&buffer, None);
+        crate::detector::detect_extension_mismatch(&content_format, &filename).map(|w| {
+            exports::athena::file_processor::detector::MismatchWarning {
+                extension: w.extension,
+                expected_format: convert_format_to_wit(w.expected_format),
+                actual_format: convert_format_to_wit(w.actual_format),
+                disguised_executable: w.disguised_executable,
+                description: w.description,
+            }
+        })
+    }
+
+    fn detect_polyglot(buffer: Vec<u8>) -> Vec<exports::athena::file_processor::detector::FileFormat> {
+        crate::detector::detect_polyglot(&buffer)
+            .into_iter()
+            .map(convert_format_to_wit)
+            .collect()
+    }
+
+    fn register_mime_override(format: exports::athena::file_processor::detector::FileFormat, mime_type: String) {
+        crate::detector::register_mime_override(convert_format_from_wit(format), mime_type);
+    }
+
+    fn clear_mime_override(format: exports::athena::file_processor::detector::FileFormat) {
+        crate::detector::clear_mime_override(&convert_format_from_wit(format));
+    }
 }
 
 // ============================================================================
@@ -72,6 +106,14 @@ impl exports::athena::file_processor::parser::Guest for Component {
     fn parse_file(
         buffer: Vec<u8>,
         format_hint: Option<exports::athena::file_processor::detector::FileFormat>,
+    ) -> Result<exports::athena::file_processor::parser::ParsedFile, String> {
+        Self::parse_file_with_limits(buffer, format_hint, None)
+    }
+
+    fn parse_file_with_limits(
+        buffer: Vec<u8>,
+        format_hint: Option<exports::athena::file_processor::detector::FileFormat>,
+        limits: Option<exports::athena::file_processor::parser::ParserLimits>,
     ) -> Result<exports::athena::file_processor::parser::ParsedFile, String> {
         let detector = FileDetector::new();
         let format = if let Some(hint) = format_hint {
@@ -79,58 +121,53 @@ impl exports::athena::file_processor::parser::Guest for Component {
         } else {
             detector.detect_format(&buffer, None)
         };
+        parser::parse_file_with_limits(&buffer, format, &convert_limits_from_wit(limits))
+            .map(convert_parsed_file_to_wit)
+            .map_err(|e| e.to_string())
+    }
 
-        match parser::parse_file(&buffer, format) {
-            Ok(parsed) => {
-                // Convert parsed file to WIT format
-                Ok(exports::athena::file_processor::parser::ParsedFile {
-                    format: convert_format_to_wit(parsed.format),
-                    metadata: exports::athena::file_processor::parser::FileMetadata {
-                        size: parsed.metadata.size as u64,
-                        hash: parsed.metadata.hash,
-                        mime_type: parsed.metadata.mime_type,
-                        created_at: parsed.metadata.created_at,
-                        modified_at: parsed.metadata.modified_at,
-                        attributes: parsed.metadata.attributes.into_iter().collect(),
-                    },
-                    sections: parsed.sections.into_iter().map(|s| {
-                        exports::athena::file_processor::parser::FileSection {
-                            name: s.name,
-                            offset: s.offset as u64,
-                            size: s.size as u64,
-                            entropy: s.entropy,
-                            section_flags: s.flags,
-                        }
-                    }).collect(),
-                    embedded_files: parsed.embedded_files.into_iter().map(|e| {
-                        exports::athena::file_processor::parser::EmbeddedFile {
-                            name: e.name,
-                            format: convert_format_to_wit(e.format),
-                            offset: e.offset as u64,
-                            size: e.size as u64,
-                            hash: e.hash,
-                        }
-                    }).collect(),
-                    strings: parsed.strings.into_iter().map(|s| s.value).collect(),
-                    suspicious_indicators: parsed.suspicious_indicators.into_iter().map(|i| {
-                        exports::athena::file_processor::parser::SuspiciousIndicator {
-                            indicator_type: i.indicator_type,
-                            description: i.description,
-                            severity: convert_severity_to_wit(i.severity),
-                            location: i.location,
-                            evidence: i.evidence,
-                        }
-                    }).collect(),
-                    integrity: exports::athena::file_processor::parser::FileIntegrity {
-                        valid_structure: parsed.integrity.valid_structure,
-                        checksum_valid: parsed.integrity.checksum_valid,
-                        signature_valid: parsed.integrity.signature_valid,
-                        issues: parsed.integrity.issues,
-                    },
-                })
-            }
-            Err(e) => Err(e.to_string()),
-        }
+    fn analyze_recursive(
+        buffer: Vec<u8>,
+        filename: Option<String>,
+        budget: Option<exports::athena::file_processor::parser::AnalysisBudget>,
+        limits: Option<exports::athena::file_processor::parser::ParserLimits>,
+    ) -> Result<exports::athena::file_processor::parser::RecursiveAnalysis, String> {
+        use exports::athena::file_processor::parser::{AnalyzedSubfile, RecursiveAnalysis, SkippedSubfile};
+
+        let budget = budget.map_or_else(AnalysisBudget::default, |b| AnalysisBudget {
+            max_subfiles: b.max_subfiles as usize,
+            max_total_bytes: b.max_total_bytes,
+            max_wall_ms: b.max_wall_ms,
+        });
+        let analysis = recursive::analyze_recursive_with_limits(
+            &buffer,
+            filename.as_deref(),
+            &budget,
+            &convert_limits_from_wit(limits),
+        )
+        .map_err(|e| e.to_string())?;
+        let budget_exhausted = analysis.budget_exhausted();
+
+        Ok(RecursiveAnalysis {
+            root: convert_parsed_file_to_wit(analysis.root),
+            subfiles: analysis.subfiles.into_iter().map(|subfile| AnalyzedSubfile {
+                parent_hash: subfile.parent_hash,
+                depth: subfile.depth as u32,
+                embedded: convert_embedded_file_to_wit(subfile.embedded),
+                parsed: subfile.parsed.map(convert_parsed_file_to_wit),
+                error: subfile.error,
+            }).collect(),
+            skipped: analysis.skipped.into_iter().map(|skipped| SkippedSubfile {
+                parent_hash: skipped.parent_hash,
+                depth: skipped.depth as u32,
+                name: skipped.name,
+                size: skipped.size as u64,
+                hash: skipped.hash,
+                reason: convert_budget_limit_to_wit(skipped.reason),
+            }).collect(),
+            bytes_analyzed: analysis.bytes_analyzed,
+            budget_exhausted,
+        })
     }
 
     fn extract_metadata(
@@ -174,16 +211,78 @@ impl exports::athena::file_processor::extractor::Guest for Component {
         }).collect()
     }
 
+    fn extract_network_indicators(buffer: Vec<u8>) -> Vec<exports::athena::file_processor::extractor::NetworkIndicator> {
+        use exports::athena::file_processor::extractor::NetworkIndicatorType as WitIndicatorType;
+
+        let extractor = ContentExtractor::new();
+        extractor.extract_network_indicators(&buffer).into_iter().map(|i| {
+            exports::athena::file_processor::extractor::NetworkIndicator {
+                indicator_type: match i.indicator_type {
+                    NetworkIndicatorType::Api => WitIndicatorType::Api,
+                    NetworkIndicatorType::URL => WitIndicatorType::Url,
+                    NetworkIndicatorType::IPAddress => WitIndicatorType::IpAddress,
+                },
+                value: i.value,
+                offset: i.offset as u64,
+                count: i.count as u32,
+            }
+        }).collect()
+    }
+
     fn extract_suspicious_patterns(content: String) -> Vec<exports::athena::file_processor::extractor::SuspiciousPattern> {
         let extractor = ContentExtractor::new();
         let patterns = extractor.extract_suspicious_patterns(&content);
 
-        patterns.into_iter().map(|p| {
-            exports::athena::file_processor::extractor::SuspiciousPattern {
-                pattern_type: convert_pattern_type_to_wit(p.pattern_type),
-                value: p.value,
-                context: p.context,
-                confidence: p.confidence,
+        patterns.into_iter().map(convert_pattern_to_wit).collect()
+    }
+
+    fn extract_suspicious_patterns_limited(
+        content: String,
+        limits: Option<exports::athena::file_processor::extractor::PatternLimits>,
+    ) -> exports::athena::file_processor::extractor::PatternExtraction {
+        let limits = limits.map_or_else(PatternLimits::default, |l| PatternLimits {
+            max_urls: l.max_urls as usize,
+            max_ips: l.max_ips as usize,
+            max_domains: l.max_domains as usize,
+        });
+        let extraction = ContentExtractor::new().with_limits(limits).extract_suspicious_patterns_limited(&content);
+
+        exports::athena::file_processor::extractor::PatternExtraction {
+            patterns: extraction.patterns.into_iter().map(convert_pattern_to_wit).collect(),
+            urls_truncated: extraction.urls_truncated,
+            ips_truncated: extraction.ips_truncated,
+            domains_truncated: extraction.domains_truncated,
+        }
+    }
+
+    fn scan_secrets(
+        content: String,
+        config: Option<exports::athena::file_processor::extractor::SecretScannerConfig>,
+    ) -> Vec<exports::athena::file_processor::extractor::SecretFinding> {
+        use exports::athena::file_processor::extractor::SecretKind as WitSecretKind;
+
+        let config = config.map_or_else(SecretScannerConfig::default, |c| SecretScannerConfig {
+            detect_jwts: c.detect_jwts,
+            detect_cloud_keys: c.detect_cloud_keys,
+            detect_private_keys: c.detect_private_keys,
+            detect_assigned_secrets: c.detect_assigned_secrets,
+            min_entropy: c.min_entropy,
+        });
+
+        SecretScanner::new().with_config(config).scan(&content).into_iter().map(|f| {
+            exports::athena::file_processor::extractor::SecretFinding {
+                kind: match f.kind {
+                    SecretKind::Jwt => WitSecretKind::Jwt,
+                    SecretKind::AwsAccessKey => WitSecretKind::AwsAccessKey,
+                    SecretKind::GcpApiKey => WitSecretKind::GcpApiKey,
+                    SecretKind::PrivateKey => WitSecretKind::PrivateKey,
+                    SecretKind::AssignedSecret => WitSecretKind::AssignedSecret,
+                },
+                offset: f.offset as u64,
+                length: f.length as u64,
+                redacted: f.redacted,
+                fingerprint: f.fingerprint,
+                confidence: f.confidence,
             }
         }).collect()
     }
@@ -193,6 +292,75 @@ impl exports::athena::file_processor::extractor::Guest for Component {
 // Helper Functions - Format Conversion
 // ============================================================================
 
+fn convert_parsed_file_to_wit(parsed: crate::types::ParsedFile) -> exports::athena::file_processor::parser::ParsedFile {
+    exports::athena::file_processor::parser::ParsedFile {
+        format: convert_format_to_wit(parsed.format),
+        metadata: exports::athena::file_processor::parser::FileMetadata {
+            size: parsed.metadata.size as u64,
+            hash: parsed.metadata.hash,
+            mime_type: parsed.metadata.mime_type,
+            created_at: parsed.metadata.created_at,
+            modified_at: parsed.metadata.modified_at,
+            attributes: parsed.metadata.attributes.into_iter().collect(),
+        },
+        sections: parsed.sections.into_iter().map(|s| {
+            exports::athena::file_processor::parser::FileSection {
+                name: s.name,
+                offset: s.offset as u64,
+                size: s.size as u64,
+                entropy: s.entropy,
+                section_flags: s.flags,
+            }
+        }).collect(),
+        embedded_files: parsed.embedded_files.into_iter().map(convert_embedded_file_to_wit).collect(),
+        strings: parsed.strings.into_iter().map(|s| s.value).collect(),
+        suspicious_indicators: parsed.suspicious_indicators.into_iter().map(|i| {
+            exports::athena::file_processor::parser::SuspiciousIndicator {
+                indicator_type: i.indicator_type,
+                description: i.description,
+                severity: convert_severity_to_wit(i.severity),
+                location: i.location,
+                evidence: i.evidence,
+            }
+        }).collect(),
+        integrity: exports::athena::file_processor::parser::FileIntegrity {
+            valid_structure: parsed.integrity.valid_structure,
+            checksum_valid: parsed.integrity.checksum_valid,
+            signature_valid: parsed.integrity.signature_valid,
+            issues: parsed.integrity.issues,
+        },
+    }
+}
+
+fn convert_embedded_file_to_wit(e: crate::types::EmbeddedFile) -> exports::athena::file_processor::parser::EmbeddedFile {
+    exports::athena::file_processor::parser::EmbeddedFile {
+        name: e.name,
+        format: convert_format_to_wit(e.format),
+        offset: e.offset as u64,
+        size: e.size as u64,
+        hash: e.hash,
+        suspicious: e.suspicious,
+    }
+}
+
+fn convert_limits_from_wit(limits: Option<exports::athena::file_processor::parser::ParserLimits>) -> ParserLimits {
+    limits.map_or_else(ParserLimits::default, |l| ParserLimits {
+        max_sections: l.max_sections as usize,
+        max_load_commands: l.max_load_commands as usize,
+        max_resources: l.max_resources as usize,
+    })
+}
+
+fn convert_budget_limit_to_wit(limit: BudgetLimit) -> exports::athena::file_processor::parser::BudgetLimit {
+    use exports::athena::file_processor::parser::BudgetLimit as WitLimit;
+
+    match limit {
+        BudgetLimit::Subfiles => WitLimit::Subfiles,
+        BudgetLimit::TotalBytes => WitLimit::TotalBytes,
+        BudgetLimit::WallTime => WitLimit::WallTime,
+    }
+}
+
 fn convert_format_to_wit(format: InternalFileFormat) -> exports::athena::file_processor::detector::FileFormat {
     use exports::athena::file_processor::detector::FileFormat as WitFormat;
 
@@ -207,6 +375,8 @@ fn convert_format_to_wit(format: InternalFileFormat) -> exports::athena::file_pr
         InternalFileFormat::XLSX => WitFormat::Xlsx,
         InternalFileFormat::PPTX => WitFormat::Pptx,
         InternalFileFormat::ODT => WitFormat::Odt,
+        InternalFileFormat::OneNote => WitFormat::Onenote,
+        InternalFileFormat::RTF => WitFormat::Rtf,
         InternalFileFormat::ZIP => WitFormat::Zip,
         InternalFileFormat::RAR => WitFormat::Rar,
         InternalFileFormat::SevenZ => WitFormat::Sevenz,
@@ -224,6 +394,7 @@ fn convert_format_to_wit(format: InternalFileFormat) -> exports::athena::file_pr
         InternalFileFormat::XML => WitFormat::Xml,
         InternalFileFormat::JSON => WitFormat::Json,
         InternalFileFormat::CSS => WitFormat::Css,
+        InternalFileFormat::LNK => WitFormat::Lnk,
         InternalFileFormat::PlainText => WitFormat::PlainText,
         InternalFileFormat::Binary => WitFormat::Binary,
         InternalFileFormat::Unknown => WitFormat::Unknown,
@@ -244,6 +415,8 @@ fn convert_format_from_wit(format: exports::athena::file_processor::detector::Fi
         WitFormat::Xlsx => InternalFileFormat::XLSX,
         WitFormat::Pptx => InternalFileFormat::PPTX,
         WitFormat::Odt => InternalFileFormat::ODT,
+        WitFormat::Onenote => InternalFileFormat::OneNote,
+        WitFormat::Rtf => InternalFileFormat::RTF,
         WitFormat::Zip => InternalFileFormat::ZIP,
         WitFormat::Rar => InternalFileFormat::RAR,
         WitFormat::Sevenz => InternalFileFormat::SevenZ,
@@ -261,6 +434,7 @@ fn convert_format_from_wit(format: exports::athena::file_processor::detector::Fi
         WitFormat::Xml => InternalFileFormat::XML,
         WitFormat::Json => InternalFileFormat::JSON,
         WitFormat::Css => InternalFileFormat::CSS,
+        WitFormat::Lnk => InternalFileFormat::LNK,
         WitFormat::PlainText => InternalFileFormat::PlainText,
         WitFormat::Binary => InternalFileFormat::Binary,
         WitFormat::Unknown => InternalFileFormat::Unknown,
@@ -278,6 +452,15 @@ fn convert_severity_to_wit(severity: crate::types::SuspiciousSeverity) -> export
     }
 }
 
+fn convert_pattern_to_wit(p: crate::types::SuspiciousPattern) -> exports::athena::file_processor::extractor::SuspiciousPattern {
+    exports::athena::file_processor::extractor::SuspiciousPattern {
+        pattern_type: convert_pattern_type_to_wit(p.pattern_type),
+        value: p.value,
+        context: p.context,
+        confidence: p.confidence,
+    }
+}
+
 fn convert_pattern_type_to_wit(pattern_type: crate::types::PatternType) -> exports::athena::file_processor::extractor::PatternType {
     use exports::athena::file_processor::extractor::PatternType as WitPatternType;
 
@@ -1,5 +1,6 @@
-use crate::types::{FileFormat};
+use crate::types::{FileFormat, MismatchWarning};
 use std::collections::HashMap;
+use std::sync::RwLock;
 use once_cell::sync::Lazy;
 
 /// Magic byte signatures for file format detection
@@ -19,6 +20,9 @@ static MAGIC_BYTES: Lazy<HashMap<Vec<u8>, FileFormat>> = Lazy::new(|| {
     m.insert(vec![0x50, 0x4B, 0x03, 0x04], FileFormat::ZIP); // ZIP (also DOCX, XLSX, etc.)
     m.insert(vec![0x50, 0x4B, 0x05, 0x06], FileFormat::ZIP); // ZIP empty
     m.insert(vec![0x50, 0x4B, 0x07, 0x08], FileFormat::ZIP); // ZIP spanned
+    m.insert(crate::parser::rtf::RTF_SIGNATURE.to_vec(), FileFormat::RTF); // {\rt
+    m.insert(crate::parser::onenote::ONENOTE_SIGNATURE.to_vec(), FileFormat::OneNote); // OneNote revision store
+    m.insert(crate::parser::lnk::LNK_SIGNATURE.to_vec(), FileFormat::LNK); // Shell Link header + CLSID
     
     // Archives
     m.insert(vec![0x52, 0x61, 0x72, 0x21], FileFormat::RAR); // Rar!
@@ -41,6 +45,10 @@ static EXTENSION_MAP: Lazy<HashMap<&'static str, FileFormat>> = Lazy::new(|| {
     m.insert("exe", FileFormat::PE32);
     m.insert("dll", FileFormat::PE32);
     m.insert("sys", FileFormat::PE32);
+    m.insert("scr", FileFormat::PE32);
+    m.insert("com", FileFormat::PE32);
+    m.insert("cpl", FileFormat::PE32);
+    m.insert("ocx", FileFormat::PE32);
     m.insert("elf", FileFormat::ELF32);
     m.insert("so", FileFormat::ELF32);
     m.insert("dylib", FileFormat::MachO);
@@ -50,7 +58,31 @@ static EXTENSION_MAP: Lazy<HashMap<&'static str, FileFormat>> = Lazy::new(|| {
     m.insert("docx", FileFormat::DOCX);
     m.insert("xlsx", FileFormat::XLSX);
     m.insert("pptx", FileFormat::PPTX);
+    m.insert("docm", FileFormat::DOCX);
+    m.insert("xlsm", FileFormat::XLSX);
+    m.insert("pptm", FileFormat::PPTX);
     m.insert("odt", FileFormat::ODT);
+    m.insert("one", FileFormat::OneNote);
+    m.insert("rtf", FileFormat::RTF);
+    m.insert("lnk", FileFormat::LNK);
+    
+    // Formats without a FileFormat of their own: legacy OLE documents and
+    // media. As Binary they still differ from every executable, so a PE
+    // named .jpg or .doc is caught, while their real content (detected as
+    // Binary) is never flagged
+    m.insert("doc", FileFormat::Binary);
+    m.insert("xls", FileFormat::Binary);
+    m.insert("ppt", FileFormat::Binary);
+    m.insert("msi", FileFormat::Binary);
+    m.insert("jpg", FileFormat::Binary);
+    m.insert("jpeg", FileFormat::Binary);
+    m.insert("png", FileFormat::Binary);
+    m.insert("gif", FileFormat::Binary);
+    m.insert("bmp", FileFormat::Binary);
+    m.insert("ico", FileFormat::Binary);
+    m.insert("mp3", FileFormat::Binary);
+    m.insert("mp4", FileFormat::Binary);
+    m.insert("avi", FileFormat::Binary);
     
     // Archives
     m.insert("zip", FileFormat::ZIP);
@@ -58,6 +90,8 @@ static EXTENSION_MAP: Lazy<HashMap<&'static str, FileFormat>> = Lazy::new(|| {
     m.insert("7z", FileFormat::SevenZ);
     m.insert("tar", FileFormat::TAR);
     m.insert("gz", FileFormat::GZIP);
+    m.insert("jar", FileFormat::ZIP);
+    m.insert("apk", FileFormat::ZIP);
     
     // Scripts
     m.insert("js", FileFormat::JavaScript);
@@ -88,6 +122,160 @@ static EXTENSION_MAP: Lazy<HashMap<&'static str, FileFormat>> = Lazy::new(|| {
     m
 });
 
+/// MIME types registered at runtime, consulted before the built-in table
+static MIME_OVERRIDES: Lazy<RwLock<HashMap<FileFormat, String>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Report `mime_type` for `format` from now on, replacing the built-in
+/// mapping (or an earlier override)
+pub fn register_mime_override(format: FileFormat, mime_type: impl Into<String>) {
+    MIME_OVERRIDES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(format, mime_type.into());
+}
+
+/// Go back to the built-in MIME type for `format`
+pub fn clear_mime_override(format: &FileFormat) {
+    MIME_OVERRIDES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(format);
+}
+
+/// Formats that can't be told apart by content alone, so an extension
+/// naming one of them is satisfied by any other
+fn format_family(format: &FileFormat) -> u8 {
+    match format {
+        FileFormat::PE32 | FileFormat::PE64 => 0,
+        FileFormat::ELF32 | FileFormat::ELF64 => 1,
+        FileFormat::MachO => 2,
+        // OOXML and ODF documents are ZIP archives
+        FileFormat::ZIP | FileFormat::DOCX | FileFormat::XLSX | FileFormat::PPTX | FileFormat::ODT => 3,
+        // Content sniffing for text formats is loose, and any text file can
+        // legitimately contain another language's keywords
+        FileFormat::JavaScript | FileFormat::TypeScript | FileFormat::Python | FileFormat::PowerShell |
+        FileFormat::Batch | FileFormat::Shell | FileFormat::PHP | FileFormat::Ruby | FileFormat::HTML |
+        FileFormat::XML | FileFormat::JSON | FileFormat::CSS | FileFormat::PlainText => 4,
+        FileFormat::PDF => 5,
+        FileFormat::RTF => 6,
+        FileFormat::OneNote => 7,
+        FileFormat::LNK => 8,
+        FileFormat::RAR => 9,
+        FileFormat::SevenZ => 10,
+        FileFormat::TAR => 11,
+        FileFormat::GZIP => 12,
+        FileFormat::Binary | FileFormat::Unknown => 13,
+    }
+}
+
+fn is_executable_format(format: &FileFormat) -> bool {
+    matches!(
+        format,
+        FileFormat::PE32 | FileFormat::PE64 | FileFormat::ELF32 | FileFormat::ELF64 | FileFormat::MachO | FileFormat::LNK
+    )
+}
+
+/// Compare the format detected from content with the one `filename`'s
+/// extension implies. Unknown extensions and unrecognized content give no
+/// warning, since there is nothing to compare.
+pub fn detect_extension_mismatch(content_format: &FileFormat, filename: &str) -> Option<MismatchWarning> {
+    let (_, extension) = filename.rsplit_once('.')?;
+    let extension = extension.to_lowercase();
+    let expected_format = EXTENSION_MAP.get(extension.as_str())?;
+
+    if matches!(content_format, FileFormat::Binary | FileFormat::Unknown)
+        || format_family(expected_format) == format_family(content_format)
+    {
+        return None;
+    }
+
+    let disguised_executable = is_executable_format(content_format) && !is_executable_format(expected_format);
+    let description = if disguised_executable {
+        format!("{} is a {:?} executable disguised with a .{} extension", filename, content_format, extension)
+    } else {
+        format!("{} has a .{} extension but its content is {:?}", filename, extension, content_format)
+    };
+
+    Some(MismatchWarning {
+        extension,
+        expected_format: expected_format.clone(),
+        actual_format: content_format.clone(),
+        disguised_executable,
+        description,
+    })
+}
+
+/// PDF readers accept the header anywhere in the first kilobyte
+const PDF_HEADER_WINDOW: usize = 1024;
+
+/// An end-of-central-directory record is 22 bytes plus a comment of up to 64KB
+const ZIP_EOCD_WINDOW: usize = 22 + 0xFFFF;
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn has_pdf_structure(data: &[u8]) -> bool {
+    let window = &data[..data.len().min(PDF_HEADER_WINDOW)];
+    find(window, b"%PDF-").is_some_and(|header| find(&data[header..], b"%%EOF").is_some())
+}
+
+/// ZIP readers work back from the end-of-central-directory record, so a ZIP
+/// can follow any other content. Accepts a central directory at its stated
+/// offset or, for archives with data prepended, right before the EOCD.
+fn has_zip_structure(data: &[u8]) -> bool {
+    let tail_start = data.len().saturating_sub(ZIP_EOCD_WINDOW);
+    let Some(eocd) = data[tail_start..]
+        .windows(4)
+        .rposition(|w| w == b"PK\x05\x06")
+        .map(|pos| tail_start + pos)
+    else {
+        return false;
+    };
+    let Some(record) = data.get(eocd..eocd + 22) else {
+        return false;
+    };
+
+    let cd_size = u32::from_le_bytes([record[12], record[13], record[14], record[15]]) as usize;
+    let cd_offset = u32::from_le_bytes([record[16], record[17], record[18], record[19]]) as usize;
+    let is_central_directory = |offset: usize| data.get(offset..offset + 4) == Some(b"PK\x01\x02".as_slice());
+
+    cd_size > 0 && (is_central_directory(cd_offset) || eocd.checked_sub(cd_size).is_some_and(is_central_directory))
+}
+
+/// Every format `data` is valid as: the one its leading magic claims, plus
+/// formats whose readers look elsewhere (a PDF header a little way in, an
+/// archive appended to the end). More than one entry means a polyglot that
+/// type-based controls will see differently from the program that opens it.
+pub fn detect_polyglot(data: &[u8]) -> Vec<FileFormat> {
+    let mut formats = Vec::new();
+    if let Some(format) = FileDetector::new().detect_by_magic(data) {
+        formats.push(format);
+    }
+
+    let mut add = |format: FileFormat| {
+        if !formats.contains(&format) {
+            formats.push(format);
+        }
+    };
+    if has_pdf_structure(data) {
+        add(FileFormat::PDF);
+    }
+    if has_zip_structure(data) {
+        add(FileFormat::ZIP);
+    }
+    // Archives appended to other content, as self-extractors do
+    let after_magic = data.get(1..).unwrap_or_default();
+    if find(after_magic, b"Rar!\x1A\x07").is_some() {
+        add(FileFormat::RAR);
+    }
+    if find(after_magic, &[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C]).is_some() {
+        add(FileFormat::SevenZ);
+    }
+
+    formats
+}
+
 pub struct FileDetector {
     magic_bytes: &'static HashMap<Vec<u8>, FileFormat>,
     extension_map: &'static HashMap<&'static str, FileFormat>,
@@ -157,7 +345,12 @@ impl FileDetector {
             .next()?
             .to_lowercase();
         
-        self.extension_map.get(extension.as_str()).cloned()
+        // Binary entries only exist for mismatch checks; the text test
+        // below still gets the last word on what those files are
+        self.extension_map
+            .get(extension.as_str())
+            .filter(|format| **format != FileFormat::Binary)
+            .cloned()
     }
 
     /// Detect format by content analysis
@@ -244,11 +437,19 @@ impl FileDetector {
 
     /// Get MIME type for file format
     pub fn get_mime_type(&self, format: FileFormat) -> String {
+        let overrides = MIME_OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(mime_type) = overrides.get(&format) {
+            return mime_type.clone();
+        }
+
         match format {
             FileFormat::PE32 | FileFormat::PE64 => "application/x-msdownload",
             FileFormat::ELF32 | FileFormat::ELF64 => "application/x-executable",
             FileFormat::MachO => "application/x-mach-binary",
             FileFormat::PDF => "application/pdf",
+            FileFormat::RTF => "application/rtf",
+            FileFormat::OneNote => "application/onenote",
+            FileFormat::LNK => "application/x-ms-shortcut",
             FileFormat::DOCX => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
             FileFormat::XLSX => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
             FileFormat::PPTX => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
@@ -324,4 +525,102 @@ mod tests {
         assert!(!detector.is_text_file(b"\x00\x01\x02\x03"));
         assert!(!detector.is_text_file(b"Text\x00with\x00nulls"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mime_override_takes_effect() {
+        let detector = FileDetector::new();
+        assert_eq!(detector.get_mime_type(FileFormat::Unknown), "application/octet-stream");
+
+        register_mime_override(FileFormat::Unknown, "application/x-athena-sample");
+        assert_eq!(detector.get_mime_type(FileFormat::Unknown), "application/x-athena-sample");
+        // Other formats keep their built-in types
+        assert_eq!(detector.get_mime_type(FileFormat::PDF), "application/pdf");
+
+        clear_mime_override(&FileFormat::Unknown);
+        assert_eq!(detector.get_mime_type(FileFormat::Unknown), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_extension_mismatch() {
+        let detector = FileDetector::new();
+        let pe = b"MZ\x90\x00\x03\x00\x00\x00";
+        let content_format = detector.detect_format(pe, None);
+
+        let warning = detect_extension_mismatch(&content_format, "Invoice_2024.PDF").unwrap();
+        assert_eq!(warning.extension, "pdf");
+        assert_eq!(warning.expected_format, FileFormat::PDF);
+        assert_eq!(warning.actual_format, FileFormat::PE32);
+        assert!(warning.disguised_executable);
+
+        assert_eq!(detect_extension_mismatch(&content_format, "setup.exe"), None);
+        assert_eq!(detect_extension_mismatch(&content_format, "driver.sys"), None);
+        assert_eq!(detect_extension_mismatch(&FileFormat::PDF, "report.pdf"), None);
+        assert_eq!(detect_extension_mismatch(&FileFormat::ZIP, "budget.xlsx"), None);
+        assert_eq!(detect_extension_mismatch(&FileFormat::JavaScript, "notes.txt"), None);
+        assert_eq!(detect_extension_mismatch(&content_format, "no_extension"), None);
+        assert_eq!(detect_extension_mismatch(&content_format, "photo.heic"), None);
+    }
+
+    #[test]
+    fn test_executable_disguised_as_image_or_legacy_document() {
+        let detector = FileDetector::new();
+        let pe = b"MZ\x90\x00\x03\x00\x00\x00";
+        let content_format = detector.detect_format(pe, None);
+
+        let warning = detect_extension_mismatch(&content_format, "holiday.jpg").unwrap();
+        assert_eq!(warning.extension, "jpg");
+        assert_eq!(warning.actual_format, FileFormat::PE32);
+        assert!(warning.disguised_executable);
+        for name in ["scan.png", "cat.gif", "contract.doc", "payroll.xls"] {
+            assert!(detect_extension_mismatch(&content_format, name).unwrap().disguised_executable, "{}", name);
+        }
+
+        // Executable extensions accept the PE, and real images aren't flagged
+        assert_eq!(detect_extension_mismatch(&content_format, "screensaver.scr"), None);
+        assert_eq!(detect_extension_mismatch(&content_format, "command.com"), None);
+        let jpeg = b"\xFF\xD8\xFF\xE0\x00\x10JFIF\x00";
+        assert_eq!(detect_extension_mismatch(&detector.detect_format(jpeg, None), "holiday.jpg"), None);
+    }
+
+    /// A one-file ZIP holding `name`, stored uncompressed
+    fn stored_zip(name: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut zip = b"PK\x03\x04\x0a\x00\x00\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        zip.extend_from_slice(&[0; 4]); // crc32, unchecked here
+        zip.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip.extend_from_slice(name);
+        zip.extend_from_slice(content);
+
+        let cd_offset = zip.len();
+        zip.extend_from_slice(b"PK\x01\x02\x14\x00\x0a\x00\x00\x00\x00\x00\x00\x00\x00\x00");
+        zip.extend_from_slice(&[0; 4]);
+        zip.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0; 12]);
+        zip.extend_from_slice(&0u32.to_le_bytes()); // local header offset
+        zip.extend_from_slice(name);
+        let cd_size = zip.len() - cd_offset;
+
+        zip.extend_from_slice(b"PK\x05\x06\x00\x00\x00\x00\x01\x00\x01\x00");
+        zip.extend_from_slice(&(cd_size as u32).to_le_bytes());
+        zip.extend_from_slice(&(cd_offset as u32).to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip
+    }
+
+    #[test]
+    fn test_pdf_zip_polyglot_reports_both() {
+        let mut data = b"%PDF-1.7\n1 0 obj<</Type/Catalog>>endobj\ntrailer<</Root 1 0 R>>\n%%EOF\n".to_vec();
+        data.extend(stored_zip(b"payload.js", b"WScript.Echo(1)"));
+
+        assert_eq!(detect_polyglot(&data), vec![FileFormat::PDF, FileFormat::ZIP]);
+
+        // Either half on its own is a single format
+        assert_eq!(detect_polyglot(&stored_zip(b"a.txt", b"hello")), vec![FileFormat::ZIP]);
+        assert_eq!(detect_polyglot(b"%PDF-1.4\n%%EOF\n"), vec![FileFormat::PDF]);
+        assert!(detect_polyglot(b"").is_empty());
+    }
+}
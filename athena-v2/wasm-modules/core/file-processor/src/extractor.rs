@@ -1,17 +1,18 @@
-use crate::types::{ExtractedString, SuspiciousPattern, PatternType};
+use crate::types::{
+    ExtractedString, NetworkIndicator, NetworkIndicatorType, PatternExtraction, PatternLimits, PatternType,
+    SuspiciousPattern,
+};
+use std::collections::hash_map::{Entry, HashMap};
 use regex::Regex;
 use once_cell::sync::Lazy;
 use encoding_rs::{UTF_16LE, UTF_16BE};
+use std::net::Ipv4Addr;
 
 /// Regular expressions for pattern detection
 static URL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"https?://[^\s<>"']+"#).unwrap()
 });
 
-static IP_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\b(?:[0-9]{1,3}\.){3}[0-9]{1,3}\b").unwrap()
-});
-
 static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Z|a-z]{2,}\b").unwrap()
 });
@@ -33,6 +34,30 @@ static CRYPTO_WALLET_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"\b(?:[13][a-km-zA-HJ-NP-Z1-9]{25,34}|0x[a-fA-F0-9]{40})\b").unwrap()
 });
 
+/// TLDs that are cheap or free to register and favored for throwaway C2
+const SUSPICIOUS_TLDS: &[&str] = &["tk", "ml", "ga", "cf", "gq", "top", "xyz", "zip", "onion", "bit"];
+
+/// Hosts commonly used to stage payloads or tunnel C2
+const STAGING_HOSTS: &[&str] = &["pastebin.com", "paste.ee", "hastebin.com", "transfer.sh", "ngrok.io", "ngrok-free.app"];
+
+/// Bytes of surrounding text kept on each side of a pattern by default
+pub const DEFAULT_CONTEXT_SIZE: usize = 30;
+
+/// Socket, WinINet and WinHTTP functions whose names show up as strings
+/// in import tables and dynamically resolved code
+const NETWORK_APIS: &[&str] = &[
+    "socket", "connect", "bind", "listen", "accept", "send", "recv", "sendto", "recvfrom",
+    "gethostbyname", "getaddrinfo", "WSAStartup", "WSASocketA", "WSASocketW", "WSAConnect",
+    "InternetOpenA", "InternetOpenW", "InternetOpenUrlA", "InternetOpenUrlW",
+    "InternetConnectA", "InternetConnectW", "HttpOpenRequestA", "HttpOpenRequestW",
+    "HttpSendRequestA", "HttpSendRequestW", "InternetReadFile",
+    "WinHttpOpen", "WinHttpConnect", "WinHttpOpenRequest", "WinHttpSendRequest",
+    "URLDownloadToFileA", "URLDownloadToFileW",
+];
+
+/// Paths that fetch something runnable
+const PAYLOAD_EXTENSIONS: &[&str] = &[".exe", ".dll", ".scr", ".ps1", ".bat", ".vbs", ".hta", ".jar", ".msi", ".bin"];
+
 pub struct ContentExtractor {
     min_string_length: usize,
     extract_urls: bool,
@@ -40,6 +65,8 @@ pub struct ContentExtractor {
     extract_emails: bool,
     extract_base64: bool,
     max_string_length: usize,
+    limits: PatternLimits,
+    context_size: usize,
 }
 
 impl ContentExtractor {
@@ -51,9 +78,23 @@ impl ContentExtractor {
             extract_emails: true,
             extract_base64: true,
             max_string_length: 1024,
+            limits: PatternLimits::default(),
+            context_size: DEFAULT_CONTEXT_SIZE,
         }
     }
 
+    /// Keep `context_size` bytes of text on each side of a pattern
+    pub fn with_context_size(mut self, context_size: usize) -> Self {
+        self.context_size = context_size;
+        self
+    }
+
+    /// Cap how many URLs, IPs and domains pattern extraction returns
+    pub fn with_limits(mut self, limits: PatternLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Extract strings from binary data
     pub fn extract_strings(&self, buffer: &[u8], min_length: usize) -> Vec<ExtractedString> {
         let mut strings = Vec::new();
@@ -77,6 +118,46 @@ impl ContentExtractor {
         strings
     }
 
+    /// Extract networking APIs, URLs and IPs referenced by binary data. Each
+    /// `(type, value)` appears once, at its first offset, with the number of
+    /// times it was referenced.
+    pub fn extract_network_indicators(&self, buffer: &[u8]) -> Vec<NetworkIndicator> {
+        let mut strings = Vec::new();
+        self.extract_ascii_strings(buffer, self.min_string_length, &mut strings);
+        self.extract_utf16_strings(buffer, self.min_string_length, &mut strings);
+        strings.sort_by_key(|s| s.offset);
+
+        let mut indicators: Vec<NetworkIndicator> = Vec::new();
+        let mut seen: HashMap<(NetworkIndicatorType, String), usize> = HashMap::new();
+        let mut add = |indicator_type: NetworkIndicatorType, value: &str, offset: usize| {
+            match seen.entry((indicator_type, value.to_string())) {
+                Entry::Occupied(entry) => indicators[*entry.get()].count += 1,
+                Entry::Vacant(entry) => {
+                    entry.insert(indicators.len());
+                    indicators.push(NetworkIndicator { indicator_type, value: value.to_string(), offset, count: 1 });
+                }
+            }
+        };
+
+        for string in &strings {
+            if NETWORK_APIS.contains(&string.value.as_str()) {
+                add(NetworkIndicatorType::Api, &string.value, string.offset);
+                continue;
+            }
+            for url in URL_REGEX.find_iter(&string.value) {
+                add(NetworkIndicatorType::URL, url.as_str(), string.offset);
+            }
+            for (start, end) in ipv4_spans(&string.value) {
+                let ip = &string.value[start..end];
+                if self.is_valid_ip(ip) {
+                    add(NetworkIndicatorType::IPAddress, ip, string.offset);
+                }
+            }
+        }
+
+        indicators
+    }
+
     /// Extract ASCII strings
     fn extract_ascii_strings(&self, buffer: &[u8], min_length: usize, strings: &mut Vec<ExtractedString>) {
         let mut current = Vec::new();
@@ -186,7 +267,7 @@ impl ContentExtractor {
         }
 
         // Check for IPs
-        if self.extract_ips && IP_REGEX.is_match(s) {
+        if self.extract_ips && ipv4_spans(s).next().is_some() {
             return true;
         }
 
@@ -216,36 +297,42 @@ impl ContentExtractor {
         suspicious_commands.iter().any(|&cmd| s_lower.contains(cmd))
     }
 
-    /// Extract suspicious patterns from text content
+    /// Extract suspicious patterns from text content, capped by the
+    /// configured limits
     pub fn extract_suspicious_patterns(&self, content: &str) -> Vec<SuspiciousPattern> {
-        let mut patterns = Vec::new();
+        self.extract_suspicious_patterns_limited(content).patterns
+    }
+
+    /// Extract suspicious patterns from text content. URLs, IPs and domains
+    /// over their cap are dropped least suspicious first and the category
+    /// is marked truncated.
+    pub fn extract_suspicious_patterns_limited(&self, content: &str) -> PatternExtraction {
+        let mut extraction = PatternExtraction::default();
+        let patterns = &mut extraction.patterns;
 
         // Extract URLs
         if self.extract_urls {
-            for capture in URL_REGEX.find_iter(content) {
-                patterns.push(SuspiciousPattern {
-                    pattern_type: PatternType::URL,
-                    value: capture.as_str().to_string(),
-                    context: Some(self.get_context(content, capture.start(), capture.end())),
-                    confidence: 0.9,
-                });
-            }
+            let urls = URL_REGEX.find_iter(content).map(|capture| SuspiciousPattern {
+                pattern_type: PatternType::URL,
+                value: capture.as_str().to_string(),
+                context: Some(self.get_context(content, capture.start(), capture.end())),
+                confidence: self.url_confidence(capture.as_str()),
+            });
+            extraction.urls_truncated = keep_most_confident(urls.collect(), self.limits.max_urls, patterns);
         }
 
         // Extract IPs
         if self.extract_ips {
-            for capture in IP_REGEX.find_iter(content) {
-                let ip = capture.as_str();
+            let ips = ipv4_spans(content)
                 // Filter out version numbers and other false positives
-                if self.is_valid_ip(ip) {
-                    patterns.push(SuspiciousPattern {
-                        pattern_type: PatternType::IPAddress,
-                        value: ip.to_string(),
-                        context: Some(self.get_context(content, capture.start(), capture.end())),
-                        confidence: 0.85,
-                    });
-                }
-            }
+                .filter(|&(start, end)| self.is_valid_ip(&content[start..end]))
+                .map(|(start, end)| SuspiciousPattern {
+                    pattern_type: PatternType::IPAddress,
+                    value: content[start..end].to_string(),
+                    context: Some(self.get_context(content, start, end)),
+                    confidence: ip_confidence(&content[start..end]),
+                });
+            extraction.ips_truncated = keep_most_confident(ips.collect(), self.limits.max_ips, patterns);
         }
 
         // Extract emails
@@ -261,18 +348,16 @@ impl ContentExtractor {
         }
 
         // Extract domains
-        for capture in DOMAIN_REGEX.find_iter(content) {
-            let domain = capture.as_str();
+        let domains = DOMAIN_REGEX.find_iter(content)
             // Filter out common false positives
-            if !self.is_common_false_positive(domain) {
-                patterns.push(SuspiciousPattern {
-                    pattern_type: PatternType::Domain,
-                    value: domain.to_string(),
-                    context: Some(self.get_context(content, capture.start(), capture.end())),
-                    confidence: 0.8,
-                });
-            }
-        }
+            .filter(|capture| !self.is_common_false_positive(capture.as_str()))
+            .map(|capture| SuspiciousPattern {
+                pattern_type: PatternType::Domain,
+                value: capture.as_str().to_string(),
+                context: Some(self.get_context(content, capture.start(), capture.end())),
+                confidence: if has_suspicious_tld(capture.as_str()) { 0.9 } else { 0.8 },
+            });
+        extraction.domains_truncated = keep_most_confident(domains.collect(), self.limits.max_domains, patterns);
 
         // Extract Base64
         if self.extract_base64 {
@@ -299,16 +384,47 @@ impl ContentExtractor {
             });
         }
 
-        patterns
+        extraction
+    }
+
+    /// URLs score higher for each sign of a payload or C2 endpoint: a raw
+    /// IP host, an unusual port, a throwaway TLD, a staging host or a
+    /// runnable file
+    fn url_confidence(&self, url: &str) -> f32 {
+        let url = url.to_ascii_lowercase();
+        let rest = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        };
+        let path = path.split(['?', '#']).next().unwrap_or("");
+
+        let signals = [
+            host.parse::<Ipv4Addr>().is_ok(),
+            port.is_some_and(|port| port != "80" && port != "443"),
+            has_suspicious_tld(host),
+            STAGING_HOSTS.iter().any(|h| host == *h || host.ends_with(&format!(".{}", h))),
+            PAYLOAD_EXTENSIONS.iter().any(|ext| path.ends_with(ext)),
+        ];
+        let count = signals.iter().filter(|&&signal| signal).count();
+
+        (0.9 + 0.03 * count as f32).min(0.99)
     }
 
-    /// Get context around a match
+    /// Get context around a match, widened to the nearest character
+    /// boundaries so multi-byte characters are never split
     fn get_context(&self, content: &str, start: usize, end: usize) -> String {
-        let context_chars = 30;
-        let context_start = start.saturating_sub(context_chars);
-        let context_end = (end + context_chars).min(content.len());
-        
-        let mut context = content[context_start..context_end].to_string();
+        let mut context_start = start.saturating_sub(self.context_size).min(content.len());
+        while !content.is_char_boundary(context_start) {
+            context_start -= 1;
+        }
+        let mut context_end = end.saturating_add(self.context_size).min(content.len());
+        while !content.is_char_boundary(context_end) {
+            context_end += 1;
+        }
+
+        let mut context = content.get(context_start..context_end).unwrap_or_default().to_string();
         
         // Replace newlines with spaces for readability
         context = context.replace('\n', " ").replace('\r', " ");
@@ -358,6 +474,102 @@ impl ContentExtractor {
     }
 }
 
+/// Bytes that can't directly precede or follow an address, since they make
+/// it part of a longer word or number
+fn joins_ipv4(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Parse a dotted-quad IPv4 address starting exactly at `pos`, returning it
+/// and the offset just past it. Requires exactly four octets of at most
+/// 255 with no leading zeros (`010` could be octal), and rejects addresses
+/// glued to surrounding words or longer dotted numbers like `1.2.3.4.5`.
+pub fn find_ipv4_at(text: &[u8], pos: usize) -> Option<(Ipv4Addr, usize)> {
+    if pos > 0 && text.get(pos - 1).is_some_and(|&b| joins_ipv4(b) || b == b'.') {
+        return None;
+    }
+
+    let mut octets = [0u8; 4];
+    let mut i = pos;
+    for (n, octet) in octets.iter_mut().enumerate() {
+        if n > 0 {
+            if text.get(i) != Some(&b'.') {
+                return None;
+            }
+            i += 1;
+        }
+
+        let field = text.get(i..)?;
+        let digits = field.iter().take(4).take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 || digits > 3 || (digits > 1 && field[0] == b'0') {
+            return None;
+        }
+        let value = field[..digits].iter().fold(0u16, |v, d| v * 10 + u16::from(d - b'0'));
+        *octet = u8::try_from(value).ok()?;
+        i += digits;
+    }
+
+    match text.get(i) {
+        Some(&b) if joins_ipv4(b) => None,
+        // A trailing full stop is fine, a fifth octet is not
+        Some(b'.') if text.get(i + 1).is_some_and(u8::is_ascii_digit) => None,
+        _ => Some((Ipv4Addr::from(octets), i)),
+    }
+}
+
+/// Byte ranges of the IPv4 addresses in `text`
+fn ipv4_spans(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let bytes = text.as_bytes();
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        while pos < bytes.len() {
+            let start = pos;
+            if let Some((_, end)) = find_ipv4_at(bytes, start) {
+                pos = end;
+                return Some((start, end));
+            }
+            // Skip to the start of the next run of digits
+            pos += 1;
+            while pos < bytes.len() && bytes[pos].is_ascii_digit() && bytes[pos - 1].is_ascii_digit() {
+                pos += 1;
+            }
+        }
+        None
+    })
+}
+
+fn has_suspicious_tld(host: &str) -> bool {
+    host.rsplit('.').next().is_some_and(|tld| SUSPICIOUS_TLDS.iter().any(|t| tld.eq_ignore_ascii_case(t)))
+}
+
+/// Private and loopback addresses matter less than routable ones
+fn ip_confidence(ip: &str) -> f32 {
+    match ip.parse::<Ipv4Addr>() {
+        Ok(addr) if addr.is_private() || addr.is_loopback() || addr.is_link_local() => 0.75,
+        _ => 0.85,
+    }
+}
+
+/// Append `found` to `patterns`, keeping only the `max` most confident in
+/// the order they were found. Returns whether any were dropped.
+fn keep_most_confident(found: Vec<SuspiciousPattern>, max: usize, patterns: &mut Vec<SuspiciousPattern>) -> bool {
+    if found.len() <= max {
+        patterns.extend(found);
+        return false;
+    }
+
+    // Stable, so ties go to the earliest match
+    let mut ranked: Vec<usize> = (0..found.len()).collect();
+    ranked.sort_by(|&a, &b| found[b].confidence.total_cmp(&found[a].confidence));
+    let mut keep = vec![false; found.len()];
+    for &i in &ranked[..max] {
+        keep[i] = true;
+    }
+
+    patterns.extend(found.into_iter().zip(keep).filter_map(|(pattern, keep)| keep.then_some(pattern)));
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,4 +627,77 @@ mod tests {
         assert!(extractor.is_suspicious_string("password=secret123"));
         assert!(!extractor.is_suspicious_string("Hello World"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_url_cap_keeps_suspicious_urls() {
+        let extractor = ContentExtractor::new().with_limits(PatternLimits { max_urls: 5, ..PatternLimits::default() });
+        let mut content: String = (0..50).map(|i| format!("https://cdn{}.example.org/lib.js\n", i)).collect();
+        content.push_str("http://185.220.101.4:8080/stage2.exe\n");
+        content.push_str("https://update-check.xyz/a\n");
+
+        let extraction = extractor.extract_suspicious_patterns_limited(&content);
+        let urls: Vec<&str> = extraction.patterns.iter()
+            .filter(|p| matches!(p.pattern_type, PatternType::URL))
+            .map(|p| p.value.as_str())
+            .collect();
+
+        assert!(extraction.urls_truncated);
+        assert!(!extraction.ips_truncated);
+        assert_eq!(urls.len(), 5);
+        assert!(urls.contains(&"http://185.220.101.4:8080/stage2.exe"));
+        assert!(urls.contains(&"https://update-check.xyz/a"));
+        assert_eq!(&urls[..3], ["https://cdn0.example.org/lib.js", "https://cdn1.example.org/lib.js", "https://cdn2.example.org/lib.js"]);
+    }
+
+    #[test]
+    fn test_network_indicators_deduplicated() {
+        let extractor = ContentExtractor::new();
+        let mut data = b"\x00\x00socket\x00connect\x00".to_vec();
+        data.extend_from_slice(b"\x90\x90socket\x00http://10.20.30.40/gate.php\x00");
+        data.extend_from_slice(b"\xff\xffsocket\x00http://10.20.30.40/gate.php\x00");
+
+        let indicators = extractor.extract_network_indicators(&data);
+
+        let socket: Vec<_> = indicators.iter().filter(|i| i.value == "socket").collect();
+        assert_eq!(socket.len(), 1);
+        assert_eq!(socket[0].indicator_type, NetworkIndicatorType::Api);
+        assert_eq!(socket[0].count, 3);
+        assert_eq!(socket[0].offset, 2);
+
+        let url = indicators.iter().find(|i| i.indicator_type == NetworkIndicatorType::URL).unwrap();
+        assert_eq!(url.value, "http://10.20.30.40/gate.php");
+        assert_eq!(url.count, 2);
+        assert!(indicators.iter().any(|i| i.indicator_type == NetworkIndicatorType::IPAddress && i.count == 2));
+        assert!(indicators.iter().any(|i| i.value == "connect" && i.count == 1));
+    }
+
+    #[test]
+    fn test_context_respects_multibyte_boundaries() {
+        let extractor = ContentExtractor::new().with_context_size(5);
+        let content = "Zugriff über http://bad.example.net/x für Überwachung";
+
+        let patterns = extractor.extract_suspicious_patterns(content);
+        let url = patterns.iter().find(|p| matches!(p.pattern_type, PatternType::URL)).unwrap();
+
+        // 5 bytes back from the URL splits 'ü', so the context starts before it
+        assert_eq!(url.context.as_deref(), Some("...über http://bad.example.net/x für..."));
+    }
+
+    #[test]
+    fn test_ipv4_scanning() {
+        assert_eq!(find_ipv4_at(b"256.1.1.1", 0), None);
+        assert_eq!(find_ipv4_at(b"999.999.999.999", 0), None);
+        assert_eq!(find_ipv4_at(b"1.2.3", 0), None);
+        assert_eq!(find_ipv4_at(b"1.2.3.", 0), None);
+        assert_eq!(find_ipv4_at(b"010.0.0.1", 0), None);
+        assert_eq!(find_ipv4_at(b"1.2.3.4.5", 0), None);
+        assert_eq!(find_ipv4_at(b"v1.2.3.4", 1), None);
+        assert_eq!(find_ipv4_at(b"1.2.3.4000", 0), None);
+
+        let text = b"beacon to 10.0.0.1.";
+        assert_eq!(find_ipv4_at(text, 10), Some((Ipv4Addr::new(10, 0, 0, 1), 18)));
+
+        let spans: Vec<_> = ipv4_spans("256.1.1.1 1.2.3 then 10.0.0.1").collect();
+        assert_eq!(spans, vec![(21, 29)]);
+    }
+}
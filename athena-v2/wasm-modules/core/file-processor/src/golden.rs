@@ -0,0 +1,147 @@
+// Golden-result regression harness
+//
+// Each fixture in tests/fixtures/golden/ is parsed and the result compared
+// against the expected JSON stored next to it (`<fixture>.json`). Fields that
+// legitimately change between runs are ignored; anything else that differs
+// fails the test with a list of the changed paths.
+//
+// After an intentional change to the heuristics, regenerate the golden files
+// with `UPDATE_GOLDEN=1 cargo test golden` and review the diff.
+
+use crate::detector::FileDetector;
+use crate::parser::parse_file;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Object keys excluded from comparison; the content hash is deterministic and
+/// catches a changed fixture, so only timestamps are skipped
+const IGNORED_KEYS: &[&str] = &["createdAt", "modifiedAt", "timestamp"];
+
+/// Floats closer than this are considered equal
+const FLOAT_TOLERANCE: f64 = 1e-6;
+
+const FIXTURES: &[&str] = &["injector.exe", "launcher.pdf", "dropper.js"];
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden")
+}
+
+/// Run the analysis a golden file records
+fn analyze_fixture(name: &str, data: &[u8]) -> Value {
+    let format = FileDetector::new().detect_format(data, Some(name));
+    match parse_file(data, format) {
+        Ok(parsed) => serde_json::to_value(parsed).expect("ParsedFile serializes"),
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    }
+}
+
+/// Paths at which `actual` meaningfully differs from `golden`
+fn compare(actual: &Value, golden: &Value) -> Vec<String> {
+    let mut diffs = Vec::new();
+    compare_at("$", actual, golden, &mut diffs);
+    diffs
+}
+
+fn compare_at(path: &str, actual: &Value, golden: &Value, diffs: &mut Vec<String>) {
+    match (actual, golden) {
+        (Value::Object(a), Value::Object(g)) => {
+            let mut keys: Vec<&String> = a.keys().chain(g.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                if IGNORED_KEYS.contains(&key.as_str()) {
+                    continue;
+                }
+                let child = format!("{}.{}", path, key);
+                match (a.get(key), g.get(key)) {
+                    (Some(av), Some(gv)) => compare_at(&child, av, gv, diffs),
+                    (Some(_), None) => diffs.push(format!("{}: unexpected field", child)),
+                    (None, Some(_)) => diffs.push(format!("{}: missing field", child)),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(g)) => {
+            if a.len() != g.len() {
+                diffs.push(format!("{}: length {} != golden {}", path, a.len(), g.len()));
+            }
+            for (i, (av, gv)) in a.iter().zip(g.iter()).enumerate() {
+                compare_at(&format!("{}[{}]", path, i), av, gv, diffs);
+            }
+        }
+        (Value::Number(a), Value::Number(g)) => {
+            let (a, g) = (a.as_f64().unwrap_or(f64::NAN), g.as_f64().unwrap_or(f64::NAN));
+            if (a - g).abs() > FLOAT_TOLERANCE {
+                diffs.push(format!("{}: {} != golden {}", path, a, g));
+            }
+        }
+        _ if actual != golden => diffs.push(format!("{}: {} != golden {}", path, actual, golden)),
+        _ => {}
+    }
+}
+
+#[test]
+fn test_golden_fixtures() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let dir = fixtures_dir();
+    let mut failures = Vec::new();
+
+    for name in FIXTURES {
+        let data = std::fs::read(dir.join(name)).unwrap_or_else(|e| panic!("fixture {}: {}", name, e));
+        let actual = analyze_fixture(name, &data);
+        let golden_path = dir.join(format!("{}.json", name));
+
+        if update {
+            let json = serde_json::to_string_pretty(&actual).unwrap() + "\n";
+            std::fs::write(&golden_path, json).unwrap();
+            continue;
+        }
+
+        let golden: Value = serde_json::from_str(
+            &std::fs::read_to_string(&golden_path)
+                .unwrap_or_else(|e| panic!("golden file for {}: {}", name, e)),
+        )
+        .unwrap();
+
+        let diffs = compare(&actual, &golden);
+        if !diffs.is_empty() {
+            failures.push(format!("{}:\n  {}", name, diffs.join("\n  ")));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "results differ from golden files (UPDATE_GOLDEN=1 to accept):\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_comparator_flags_changed_verdict() {
+    let golden: Value = serde_json::from_str(
+        &std::fs::read_to_string(fixtures_dir().join("dropper.js.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(compare(&golden, &golden).is_empty());
+
+    // Run-specific fields don't count
+    let mut restamped = golden.clone();
+    restamped["metadata"]["modifiedAt"] = Value::String("2024-01-01T00:00:00Z".to_string());
+    assert!(compare(&restamped, &golden).is_empty());
+
+    // A different hash means different content
+    let mut rehashed = golden.clone();
+    rehashed["metadata"]["hash"] = Value::String("0".repeat(64));
+    assert_eq!(compare(&rehashed, &golden).len(), 1);
+
+    // A changed verdict does
+    let mut downgraded = golden.clone();
+    let indicators = downgraded["suspiciousIndicators"].as_array_mut().unwrap();
+    assert!(!indicators.is_empty(), "fixture should produce indicators");
+    indicators[0]["severity"] = Value::String("low".to_string());
+    indicators.pop();
+
+    let diffs = compare(&downgraded, &golden);
+    assert!(diffs.iter().any(|d| d.starts_with("$.suspiciousIndicators: length")));
+}
@@ -9,6 +9,11 @@ pub mod types;
 pub mod utils;
 pub mod packer_detection;
 pub mod pdb_parser;
+pub mod recursive;
+pub mod secrets;
+
+#[cfg(test)]
+mod golden;
 
 #[cfg(test)]
 mod tests {
@@ -27,8 +27,84 @@ pub struct PackerSignature {
     pub indicators: Vec<String>,
 }
 
+/// Entropy above which data counts as compressed or encrypted
+pub const DEFAULT_ENTROPY_THRESHOLD: f64 = 7.0;
+
+/// Import tables with fewer functions than this are typical of packer stubs
+pub const DEFAULT_TINY_IMPORT_COUNT: usize = 8;
+
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+
+const PT_LOAD: u32 = 1;
+const PF_X: u32 = 0x1;
+const PF_W: u32 = 0x2;
+
+/// Tuning for the heuristic (signature-less) packed verdict
+#[derive(Clone, Debug)]
+pub struct PackerDetectorConfig {
+    /// File or section entropy above this is a packing signal
+    pub entropy_threshold: f64,
+    /// A PE importing fewer functions than this is a packing signal
+    pub tiny_import_count: usize,
+    /// Number of independent signals needed to call a file packed without a
+    /// signature match: high entropy, a W+X section or segment, and for PE a
+    /// tiny import table, for ELF a missing section header table
+    pub min_signals: usize,
+}
+
+impl Default for PackerDetectorConfig {
+    fn default() -> Self {
+        Self {
+            entropy_threshold: DEFAULT_ENTROPY_THRESHOLD,
+            tiny_import_count: DEFAULT_TINY_IMPORT_COUNT,
+            min_signals: 2,
+        }
+    }
+}
+
 pub struct PackerDetector {
     signatures: Vec<PackerSignatureDefinition>,
+    config: PackerDetectorConfig,
+}
+
+/// A PE section header, as far as packer detection needs it
+#[derive(Clone, Debug)]
+struct PeSection {
+    name: String,
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_offset: u32,
+    raw_size: u32,
+    characteristics: u32,
+}
+
+impl PeSection {
+    fn is_writable_and_executable(&self) -> bool {
+        self.characteristics & IMAGE_SCN_MEM_WRITE != 0 && self.characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+    }
+
+    fn raw_data<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        let start = (self.raw_offset as usize).min(data.len());
+        let end = start.saturating_add(self.raw_size as usize).min(data.len());
+        &data[start..end]
+    }
+}
+
+/// A loadable ELF segment, as far as packer detection needs it
+#[derive(Clone, Debug)]
+struct ElfSegment {
+    offset: u64,
+    file_size: u64,
+    flags: u32,
+}
+
+impl ElfSegment {
+    fn raw_data<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        let start = usize::try_from(self.offset).unwrap_or(usize::MAX).min(data.len());
+        let size = usize::try_from(self.file_size).unwrap_or(usize::MAX);
+        &data[start..start.saturating_add(size).min(data.len())]
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -43,8 +119,13 @@ struct PackerSignatureDefinition {
 
 impl PackerDetector {
     pub fn new() -> Self {
+        Self::with_config(PackerDetectorConfig::default())
+    }
+
+    pub fn with_config(config: PackerDetectorConfig) -> Self {
         Self {
             signatures: Self::load_builtin_signatures(),
+            config,
         }
     }
 
@@ -63,9 +144,11 @@ impl PackerDetector {
         // Calculate overall entropy
         result.entropy_score = self.calculate_entropy(data);
 
-        // High entropy is suspicious (> 7.0 indicates encryption/compression)
-        if result.entropy_score > 7.0 {
-            result.is_packed = true;
+        // High entropy alone is suspicious but not conclusive: compressed
+        // media and archives look the same
+        let mut signals = 0;
+        if result.entropy_score > self.config.entropy_threshold {
+            signals += 1;
             result.suspicious_indicators.push(format!(
                 "Very high entropy: {:.2}",
                 result.entropy_score
@@ -75,8 +158,16 @@ impl PackerDetector {
         // Check for packer signatures
         if file_type == "PE" || file_type == "PE32" || file_type == "PE64" {
             self.detect_pe_packers(data, &mut result);
+            signals += self.pe_structure_signals(data, signals > 0, &mut result);
         } else if file_type == "ELF32" || file_type == "ELF64" {
             self.detect_elf_packers(data, &mut result);
+            signals += self.elf_structure_signals(data, signals > 0, &mut result);
+        }
+
+        let heuristic_packed = signals >= self.config.min_signals.max(1);
+        if heuristic_packed {
+            result.is_packed = true;
+            *result.detection_methods.entry("heuristic".to_string()).or_insert(0) += 1;
         }
 
         // Calculate overall confidence
@@ -85,15 +176,94 @@ impl PackerDetector {
                 .map(|p| p.confidence)
                 .max_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(0.0);
-        } else if result.entropy_score > 7.5 {
-            result.confidence = 0.7; // High entropy alone
-        } else if result.entropy_score > 7.0 {
-            result.confidence = 0.5;
+        } else if heuristic_packed {
+            result.confidence = (0.4 + 0.2 * signals as f64).min(0.9);
+        } else if signals > 0 {
+            result.confidence = 0.3;
         }
 
         result
     }
 
+    /// Section-level packing signals: high-entropy sections, sections that
+    /// are both writable and executable, and a tiny import table. Returns
+    /// how many signals fired, not counting section entropy when the whole
+    /// file was already high-entropy.
+    fn pe_structure_signals(&self, data: &[u8], file_high_entropy: bool, result: &mut PackerDetectionResult) -> usize {
+        let Some(sections) = self.parse_pe_sections(data) else {
+            return 0;
+        };
+        let mut signals = 0;
+
+        for section in &sections {
+            let raw = section.raw_data(data);
+            if !raw.is_empty() && self.calculate_entropy(raw) > self.config.entropy_threshold {
+                result.high_entropy_sections.push(section.name.clone());
+            }
+        }
+        if !file_high_entropy && !result.high_entropy_sections.is_empty() {
+            signals += 1;
+        }
+
+        let wx_sections: Vec<&str> = sections.iter()
+            .filter(|s| s.is_writable_and_executable())
+            .map(|s| s.name.as_str())
+            .collect();
+        if !wx_sections.is_empty() {
+            signals += 1;
+            result.suspicious_indicators.push(format!(
+                "Writable and executable section: {}",
+                wx_sections.join(", ")
+            ));
+            *result.detection_methods.entry("wx_section".to_string()).or_insert(0) += 1;
+        }
+
+        if let Some(count) = self.count_pe_imported_functions(data, &sections) {
+            if count < self.config.tiny_import_count {
+                signals += 1;
+                result.suspicious_indicators.push(format!("Tiny import table: {} functions", count));
+                *result.detection_methods.entry("tiny_import_table".to_string()).or_insert(0) += 1;
+            }
+        }
+
+        signals
+    }
+
+    /// Segment-level packing signals for ELF: high-entropy loadable
+    /// segments, segments that are both writable and executable, and a
+    /// missing section header table, which packers drop and linkers never
+    /// do. Counted the same way as `pe_structure_signals`.
+    fn elf_structure_signals(&self, data: &[u8], file_high_entropy: bool, result: &mut PackerDetectionResult) -> usize {
+        let Some((segments, section_count)) = parse_elf_layout(data) else {
+            return 0;
+        };
+        let mut signals = 0;
+
+        for (i, segment) in segments.iter().enumerate() {
+            let raw = segment.raw_data(data);
+            if !raw.is_empty() && self.calculate_entropy(raw) > self.config.entropy_threshold {
+                result.high_entropy_sections.push(format!("LOAD[{}]", i));
+            }
+        }
+        if !file_high_entropy && !result.high_entropy_sections.is_empty() {
+            signals += 1;
+        }
+
+        if segments.iter().any(|s| s.flags & PF_W != 0 && s.flags & PF_X != 0) {
+            signals += 1;
+            result.suspicious_indicators.push("Writable and executable segment".to_string());
+            *result.detection_methods.entry("wx_segment".to_string()).or_insert(0) += 1;
+        }
+
+        if section_count == 0 {
+            signals += 1;
+            result.suspicious_indicators.push("No section header table".to_string());
+            *result.detection_methods.entry("no_section_headers".to_string()).or_insert(0) += 1;
+        }
+
+        signals
+    }
+
     fn detect_pe_packers(&self, data: &[u8], result: &mut PackerDetectionResult) {
         // Check entry point signatures
         for sig in &self.signatures {
@@ -140,10 +310,6 @@ impl PackerDetector {
                 }
             }
 
-            // Check for high entropy sections
-            for section_name in section_names {
-                result.high_entropy_sections.push(section_name);
-            }
         }
 
         // Check import patterns for known packer libraries
@@ -281,43 +447,95 @@ impl PackerDetector {
     }
 
     fn extract_pe_section_names(&self, data: &[u8]) -> Option<Vec<String>> {
-        if data.len() < 64 {
+        self.parse_pe_sections(data)
+            .map(|sections| sections.into_iter().map(|s| s.name).collect())
+    }
+
+    /// Offset of the optional header, checking for the PE signature
+    fn pe_optional_header_offset(data: &[u8]) -> Option<usize> {
+        let e_lfanew = read_u32(data, 0x3c)? as usize;
+        if data.get(e_lfanew..e_lfanew.checked_add(4)?)? != b"PE\0\0" {
             return None;
         }
+        Some(e_lfanew + 24)
+    }
 
-        // Read e_lfanew
-        let e_lfanew = u32::from_le_bytes([
-            data[0x3c], data[0x3d], data[0x3e], data[0x3f]
-        ]) as usize;
+    fn parse_pe_sections(&self, data: &[u8]) -> Option<Vec<PeSection>> {
+        let optional_header = Self::pe_optional_header_offset(data)?;
+        let num_sections = read_u16(data, optional_header - 20)? as usize;
+        let optional_header_size = read_u16(data, optional_header - 4)? as usize;
+        let sections_offset = optional_header + optional_header_size;
 
-        if e_lfanew + 24 > data.len() {
-            return None;
+        let mut sections = Vec::new();
+        for i in 0..num_sections.min(96) {
+            let header = sections_offset + i * 40;
+            let Some(name_bytes) = data.get(header..header + 8) else {
+                break;
+            };
+            let Some(characteristics) = read_u32(data, header + 36) else {
+                break;
+            };
+            sections.push(PeSection {
+                name: String::from_utf8_lossy(name_bytes).trim_end_matches('\0').to_string(),
+                virtual_size: read_u32(data, header + 8)?,
+                virtual_address: read_u32(data, header + 12)?,
+                raw_size: read_u32(data, header + 16)?,
+                raw_offset: read_u32(data, header + 20)?,
+                characteristics,
+            });
         }
 
-        // Read number of sections
-        let num_sections = u16::from_le_bytes([
-            data[e_lfanew + 6], data[e_lfanew + 7]
-        ]) as usize;
+        Some(sections)
+    }
+
+    /// Number of functions in the PE import table, or `None` if the
+    /// headers can't be read. A missing import directory counts as zero.
+    fn count_pe_imported_functions(&self, data: &[u8], sections: &[PeSection]) -> Option<usize> {
+        let optional_header = Self::pe_optional_header_offset(data)?;
+        let (import_dir, thunk_size) = match read_u16(data, optional_header)? {
+            0x10b => (optional_header + 104, 4),
+            0x20b => (optional_header + 120, 8),
+            _ => return None,
+        };
+        let import_rva = read_u32(data, import_dir)?;
+        if import_rva == 0 {
+            return Some(0);
+        }
 
-        // Section table starts after optional header
-        // Simplified offset calculation
-        let sections_offset = e_lfanew + 24 + 224; // COFF + Optional header (PE32)
+        let rva_to_offset = |rva: u32| {
+            sections.iter()
+                .find(|s| rva >= s.virtual_address && rva - s.virtual_address < s.virtual_size.max(s.raw_size))
+                .and_then(|s| s.raw_offset.checked_add(rva - s.virtual_address))
+                .map(|offset| offset as usize)
+        };
 
-        let mut section_names = Vec::new();
-        for i in 0..num_sections.min(20) {
-            let section_offset = sections_offset + (i * 40);
-            if section_offset + 8 > data.len() {
+        let mut count = 0;
+        let mut descriptor = rva_to_offset(import_rva)?;
+        // Bounded so a corrupt table can't keep us walking
+        for _ in 0..1024 {
+            let original_first_thunk = read_u32(data, descriptor)?;
+            let first_thunk = read_u32(data, descriptor + 16)?;
+            if original_first_thunk == 0 && first_thunk == 0 {
                 break;
             }
-
-            // Section name is first 8 bytes
-            let name_bytes = &data[section_offset..section_offset + 8];
-            if let Ok(name) = std::str::from_utf8(name_bytes) {
-                section_names.push(name.trim_end_matches('\0').to_string());
+            let thunks = if original_first_thunk != 0 { original_first_thunk } else { first_thunk };
+            if let Some(mut thunk) = rva_to_offset(thunks) {
+                while count < 65536 {
+                    let value = match thunk_size {
+                        4 => read_u32(data, thunk).map(u64::from),
+                        _ => data.get(thunk..thunk + 8).map(|b| u64::from_le_bytes(b.try_into().unwrap())),
+                    };
+                    match value {
+                        Some(0) | None => break,
+                        Some(_) => count += 1,
+                    }
+                    thunk += thunk_size;
+                }
             }
+            descriptor += 20;
         }
 
-        Some(section_names)
+        Some(count)
     }
 
     fn calculate_entropy(&self, data: &[u8]) -> f64 {
@@ -472,6 +690,78 @@ impl PackerDetector {
     }
 }
 
+/// The PT_LOAD segments of an ELF file and its section header count (zero
+/// when there is no section header table)
+fn parse_elf_layout(data: &[u8]) -> Option<(Vec<ElfSegment>, u16)> {
+    if data.get(0..4)? != b"\x7fELF" {
+        return None;
+    }
+    let is_64 = match data.get(4)? {
+        1 => false,
+        2 => true,
+        _ => return None,
+    };
+    let little_endian = match data.get(5)? {
+        1 => true,
+        2 => false,
+        _ => return None,
+    };
+    let read = |offset: usize, size: usize| -> Option<u64> {
+        let bytes = data.get(offset..offset.checked_add(size)?)?;
+        let fold = |value: u64, byte: &u8| (value << 8) | u64::from(*byte);
+        Some(if little_endian {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        })
+    };
+
+    // e_phoff, e_shoff, e_phentsize, e_phnum, e_shnum
+    let (phoff, shoff, phentsize, phnum, shnum) = if is_64 {
+        (read(0x20, 8)?, read(0x28, 8)?, read(0x36, 2)?, read(0x38, 2)?, read(0x3c, 2)?)
+    } else {
+        (read(0x1c, 4)?, read(0x20, 4)?, read(0x2a, 2)?, read(0x2c, 2)?, read(0x30, 2)?)
+    };
+    let section_count = if shoff == 0 { 0 } else { shnum as u16 };
+
+    let mut segments = Vec::new();
+    let phoff = usize::try_from(phoff).ok()?;
+    for i in 0..phnum.min(256) as usize {
+        let header = phoff.checked_add(i.checked_mul(phentsize as usize)?)?;
+        let Some(p_type) = read(header, 4) else {
+            break;
+        };
+        if p_type as u32 != PT_LOAD {
+            continue;
+        }
+        // Elf64_Phdr and Elf32_Phdr order their fields differently
+        let segment = if is_64 {
+            ElfSegment {
+                flags: read(header + 4, 4)? as u32,
+                offset: read(header + 8, 8)?,
+                file_size: read(header + 0x20, 8)?,
+            }
+        } else {
+            ElfSegment {
+                offset: read(header + 4, 4)?,
+                file_size: read(header + 0x10, 4)?,
+                flags: read(header + 0x18, 4)? as u32,
+            }
+        };
+        segments.push(segment);
+    }
+
+    Some((segments, section_count))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset.checked_add(2)?).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(4)?).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -516,6 +806,134 @@ mod tests {
         assert!(result.is_packed || !result.suspicious_indicators.is_empty());
     }
 
+    /// Deterministic bytes with near-maximal entropy
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    /// Minimal PE32 with one section holding `payload`
+    fn pe_with_section(characteristics: u32, payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 0x400];
+        data[0..2].copy_from_slice(b"MZ");
+        data[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        data[0x80..0x84].copy_from_slice(b"PE\0\0");
+        data[0x84..0x86].copy_from_slice(&0x14cu16.to_le_bytes()); // i386
+        data[0x86..0x88].copy_from_slice(&1u16.to_le_bytes()); // sections
+        data[0x94..0x96].copy_from_slice(&224u16.to_le_bytes()); // optional header size
+        data[0x98..0x9a].copy_from_slice(&0x10bu16.to_le_bytes()); // PE32 magic
+
+        let section = 0x98 + 224;
+        data[section..section + 8].copy_from_slice(b".text\0\0\0");
+        data[section + 8..section + 12].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+        data[section + 12..section + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        data[section + 16..section + 20].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+        data[section + 20..section + 24].copy_from_slice(&0x400u32.to_le_bytes());
+        data[section + 36..section + 40].copy_from_slice(&characteristics.to_le_bytes());
+
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn test_high_entropy_media_not_packed_without_structural_signal() {
+        let detector = PackerDetector::new();
+
+        // JPEG header followed by compressed scan data
+        let mut media = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00];
+        media.extend(noise(64 * 1024));
+
+        let result = detector.detect(&media, "JPEG");
+        assert!(result.entropy_score > DEFAULT_ENTROPY_THRESHOLD);
+        assert!(!result.is_packed);
+        assert!(result.suspicious_indicators.iter().any(|i| i.starts_with("Very high entropy")));
+
+        // The same payload in a writable and executable section is packed
+        let wx = IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_MEM_WRITE | 0x20; // + CNT_CODE
+        let pe = pe_with_section(wx, &noise(64 * 1024));
+        let result = detector.detect(&pe, "PE32");
+        assert!(result.is_packed);
+        assert_eq!(result.high_entropy_sections, vec![".text"]);
+        assert_eq!(result.detection_methods.get("wx_section"), Some(&1));
+    }
+
+    #[test]
+    fn test_entropy_threshold_configurable() {
+        // Low-entropy stub: plain read/execute code section, no imports
+        let text = IMAGE_SCN_MEM_EXECUTE | 0x4000_0000 | 0x20;
+        let payload: Vec<u8> = (0..4096).map(|i| (i % 64) as u8).collect();
+        let pe = pe_with_section(text, &payload);
+
+        let default = PackerDetector::new().detect(&pe, "PE32");
+        assert!(default.entropy_score < DEFAULT_ENTROPY_THRESHOLD);
+        assert!(!default.is_packed);
+        assert_eq!(default.detection_methods.get("tiny_import_table"), Some(&1));
+
+        // A custom packer whose output sits below the default threshold
+        let lowered = PackerDetector::with_config(PackerDetectorConfig {
+            entropy_threshold: 5.5,
+            ..PackerDetectorConfig::default()
+        });
+        assert!(lowered.detect(&pe, "PE32").is_packed);
+    }
+
+    /// Minimal little-endian ELF64 with one PT_LOAD segment holding
+    /// `payload`, and a section header table unless `sections` is zero
+    fn elf_with_segment(flags: u32, sections: u16, payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 0x1000];
+        data[0..4].copy_from_slice(b"\x7fELF");
+        data[4] = 2; // ELFCLASS64
+        data[5] = 1; // ELFDATA2LSB
+        data[0x20..0x28].copy_from_slice(&0x40u64.to_le_bytes()); // e_phoff
+        if sections > 0 {
+            data[0x28..0x30].copy_from_slice(&0x200u64.to_le_bytes()); // e_shoff
+        }
+        data[0x36..0x38].copy_from_slice(&56u16.to_le_bytes()); // e_phentsize
+        data[0x38..0x3a].copy_from_slice(&1u16.to_le_bytes()); // e_phnum
+        data[0x3c..0x3e].copy_from_slice(&sections.to_le_bytes()); // e_shnum
+
+        let phdr = 0x40;
+        data[phdr..phdr + 4].copy_from_slice(&PT_LOAD.to_le_bytes());
+        data[phdr + 4..phdr + 8].copy_from_slice(&flags.to_le_bytes());
+        data[phdr + 8..phdr + 16].copy_from_slice(&0x1000u64.to_le_bytes());
+        data[phdr + 0x20..phdr + 0x28].copy_from_slice(&(payload.len() as u64).to_le_bytes());
+
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn test_elf_packed_by_structure_not_entropy_alone() {
+        let detector = PackerDetector::new();
+        let rx = PF_X | 0x4;
+
+        // A normal binary carrying compressed data is only high-entropy
+        let benign = detector.detect(&elf_with_segment(rx, 12, &noise(64 * 1024)), "ELF64");
+        assert!(benign.entropy_score > DEFAULT_ENTROPY_THRESHOLD);
+        assert!(!benign.is_packed);
+
+        // Section headers dropped and a segment the stub unpacks into,
+        // as packer stubs leave them
+        let stub: Vec<u8> = (0..4096).map(|i| (i % 64) as u8).collect();
+        let packed = detector.detect(&elf_with_segment(rx | PF_W, 0, &stub), "ELF64");
+        assert!(packed.entropy_score < DEFAULT_ENTROPY_THRESHOLD);
+        assert!(packed.is_packed);
+        assert_eq!(packed.detection_methods.get("wx_segment"), Some(&1));
+        assert_eq!(packed.detection_methods.get("no_section_headers"), Some(&1));
+
+        // High entropy plus a missing section table is enough too
+        let compressed = detector.detect(&elf_with_segment(rx, 0, &noise(64 * 1024)), "ELF64");
+        assert!(compressed.is_packed);
+        assert_eq!(compressed.high_entropy_sections, vec!["LOAD[0]"]);
+    }
+
     #[test]
     fn test_get_known_packers() {
         let detector = PackerDetector::new();
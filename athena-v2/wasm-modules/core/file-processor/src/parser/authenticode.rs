@@ -17,7 +17,7 @@ use goblin::pe::certificate_table::{AttributeCertificate, AttributeCertificateTy
 use goblin::pe::PE;
 use sha2::{Sha256, Digest};
 use sha1::Sha1;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 // Microsoft OIDs for Authenticode
 const OID_SPC_INDIRECT_DATA: &str = "1.3.6.1.4.1.311.2.1.4";
@@ -881,7 +881,7 @@ pub struct CertificateValidationResult {
     pub signature_algorithm: Option<String>,
     pub chain_length: usize,
     pub errors: Vec<String>,
-    pub info: HashMap<String, String>,
+    pub info: BTreeMap<String, String>,
 }
 
 impl Default for CertificateValidationResult {
@@ -898,7 +898,7 @@ impl Default for CertificateValidationResult {
             signature_algorithm: None,
             chain_length: 0,
             errors: Vec::new(),
-            info: HashMap::new(),
+            info: BTreeMap::new(),
         }
     }
 }
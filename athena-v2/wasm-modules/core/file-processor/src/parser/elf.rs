@@ -1,20 +1,28 @@
 use crate::types::{
     FileFormat, ParsedFile, FileMetadata, FileSection, ProcessorResult, FileProcessorError,
-    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity
+    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity, ParserLimits
 };
 use crate::extractor::ContentExtractor;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use goblin::elf::Elf;
 
 /// Parse ELF (Executable and Linkable Format) files using goblin
 pub fn parse_elf(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile> {
+    parse_elf_with_limits(buffer, format, &ParserLimits::default())
+}
+
+/// Parse an ELF, rejecting it as malformed if it declares more section or
+/// program headers than `limits` allow
+pub fn parse_elf_with_limits(buffer: &[u8], format: FileFormat, limits: &ParserLimits) -> ProcessorResult<ParsedFile> {
+    check_elf_limits(buffer, limits)?;
+
     // Parse ELF using goblin
     let elf = Elf::parse(buffer).map_err(|e| {
         FileProcessorError::MalformedStructure(format!("Failed to parse ELF: {}", e))
     })?;
 
     // Create metadata
-    let mut attributes = HashMap::new();
+    let mut attributes = BTreeMap::new();
 
     // Add ELF-specific attributes
     attributes.insert("class".to_string(),
@@ -228,6 +236,7 @@ pub fn parse_elf(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFil
 
 /// Extract ELF metadata only (lighter weight than full parsing)
 pub fn extract_elf_metadata(buffer: &[u8], metadata: &mut FileMetadata) -> ProcessorResult<()> {
+    check_elf_limits(buffer, &ParserLimits::default())?;
     let elf = Elf::parse(buffer).map_err(|e| {
         FileProcessorError::MalformedStructure(format!("Failed to parse ELF: {}", e))
     })?;
@@ -260,6 +269,37 @@ pub fn extract_elf_metadata(buffer: &[u8], metadata: &mut FileMetadata) -> Proce
     Ok(())
 }
 
+/// Reject an ELF whose header declares more section headers or program
+/// headers than `limits` allow, before goblin sizes its tables from them
+pub fn check_elf_limits(buffer: &[u8], limits: &ParserLimits) -> ProcessorResult<()> {
+    let (Some(&class), Some(&data)) = (buffer.get(4), buffer.get(5)) else {
+        return Ok(());
+    };
+    // e_phnum and e_shnum, by class
+    let (phnum_at, shnum_at) = match class {
+        1 => (0x2C, 0x30),
+        2 => (0x38, 0x3C),
+        _ => return Ok(()),
+    };
+    let read_u16 = |offset: usize| {
+        let bytes: [u8; 2] = buffer.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if data == 2 { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) })
+    };
+
+    for (count, limit, what) in [
+        (read_u16(shnum_at), limits.max_sections, "section headers"),
+        (read_u16(phnum_at), limits.max_load_commands, "program headers"),
+    ] {
+        if let Some(count) = count.filter(|&count| count as usize > limit) {
+            return Err(FileProcessorError::MalformedStructure(format!(
+                "ELF header declares {} {}, more than the limit of {}",
+                count, what, limit
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Calculate Shannon entropy of data (0.0 to 8.0)
 fn calculate_entropy(data: &[u8]) -> f64 {
     if data.is_empty() {
@@ -283,3 +323,55 @@ fn calculate_entropy(data: &[u8]) -> f64 {
 
     entropy
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An x86-64 executable header declaring `phnum` program headers and
+    /// `shnum` section headers, with no tables behind them
+    fn elf64_header(phnum: u16, shnum: u16) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        data[..4].copy_from_slice(b"\x7FELF");
+        data[4] = 2; // ELFCLASS64
+        data[5] = 1; // little endian
+        data[6] = 1;
+        data[16..18].copy_from_slice(&2u16.to_le_bytes()); // ET_EXEC
+        data[18..20].copy_from_slice(&goblin::elf::header::EM_X86_64.to_le_bytes());
+        data[20..24].copy_from_slice(&1u32.to_le_bytes());
+        data[32..40].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+        data[40..48].copy_from_slice(&64u64.to_le_bytes()); // e_shoff
+        data[52..54].copy_from_slice(&64u16.to_le_bytes());
+        data[54..56].copy_from_slice(&56u16.to_le_bytes());
+        data[56..58].copy_from_slice(&phnum.to_le_bytes());
+        data[58..60].copy_from_slice(&64u16.to_le_bytes());
+        data[60..62].copy_from_slice(&shnum.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_absurd_section_header_count_rejected() {
+        let data = elf64_header(0, 0xFFFF);
+
+        let err = parse_elf(&data, FileFormat::ELF64).unwrap_err();
+        assert!(matches!(&err, FileProcessorError::MalformedStructure(m) if m.contains("65535 section headers")), "{}", err);
+        assert!(crate::parser::extract_metadata(&data, FileFormat::ELF64).is_err());
+
+        // Past the cap, goblin finds the table runs off the end of the file
+        let limits = ParserLimits { max_sections: 0x10000, ..ParserLimits::default() };
+        let err = parse_elf_with_limits(&data, FileFormat::ELF64, &limits).unwrap_err();
+        assert!(err.to_string().contains("Failed to parse ELF"), "{}", err);
+    }
+
+    #[test]
+    fn test_program_header_limit() {
+        let data = elf64_header(2000, 0);
+
+        let err = parse_elf(&data, FileFormat::ELF64).unwrap_err();
+        assert!(err.to_string().contains("2000 program headers"), "{}", err);
+
+        let limits = ParserLimits { max_load_commands: 1, ..ParserLimits::default() };
+        assert!(check_elf_limits(&elf64_header(1, 0), &limits).is_ok());
+        assert!(check_elf_limits(&elf64_header(2, 0), &limits).is_err());
+    }
+}
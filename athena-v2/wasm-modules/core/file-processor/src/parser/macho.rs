@@ -1,14 +1,26 @@
 use crate::types::{
     FileFormat, ParsedFile, FileMetadata, FileSection, ProcessorResult, FileProcessorError,
-    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity
+    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity, ParserLimits
 };
 use crate::extractor::ContentExtractor;
 use crate::parser::codesign;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use goblin::mach::{Mach, MachO};
 
+/// Segment load commands, which carry their sections' headers
+const LC_SEGMENT: u32 = 0x1;
+const LC_SEGMENT_64: u32 = 0x19;
+
 /// Parse Mach-O (macOS/iOS executables) files using goblin
 pub fn parse_macho(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile> {
+    parse_macho_with_limits(buffer, format, &ParserLimits::default())
+}
+
+/// Parse a Mach-O, rejecting it as malformed if it declares more load
+/// commands or sections than `limits` allow
+pub fn parse_macho_with_limits(buffer: &[u8], format: FileFormat, limits: &ParserLimits) -> ProcessorResult<ParsedFile> {
+    check_macho_limits(buffer, limits)?;
+
     // Parse Mach-O using goblin (handles both single and fat binaries)
     let mach = Mach::parse(buffer).map_err(|e| {
         FileProcessorError::MalformedStructure(format!("Failed to parse Mach-O: {}", e))
@@ -23,7 +35,7 @@ pub fn parse_macho(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedF
 /// Parse a single Mach-O binary
 fn parse_single_macho(buffer: &[u8], macho: &MachO, format: FileFormat) -> ProcessorResult<ParsedFile> {
     // Create metadata
-    let mut attributes = HashMap::new();
+    let mut attributes = BTreeMap::new();
 
     // Add Mach-O header attributes
     attributes.insert("cputype".to_string(), format!("{:#x}", macho.header.cputype));
@@ -259,7 +271,7 @@ fn parse_single_macho(buffer: &[u8], macho: &MachO, format: FileFormat) -> Proce
 
 /// Parse a fat Mach-O binary (universal binary with multiple architectures)
 fn parse_fat_macho(buffer: &[u8], multi: goblin::mach::MultiArch, format: FileFormat) -> ProcessorResult<ParsedFile> {
-    let mut attributes = HashMap::new();
+    let mut attributes = BTreeMap::new();
     let arch_count = multi.iter_arches().count();
 
     attributes.insert("fat_binary".to_string(), "true".to_string());
@@ -313,6 +325,7 @@ fn parse_fat_macho(buffer: &[u8], multi: goblin::mach::MultiArch, format: FileFo
 
 /// Extract Mach-O metadata only (lighter weight than full parsing)
 pub fn extract_macho_metadata(buffer: &[u8], metadata: &mut FileMetadata) -> ProcessorResult<()> {
+    check_macho_limits(buffer, &ParserLimits::default())?;
     let mach = Mach::parse(buffer).map_err(|e| {
         FileProcessorError::MalformedStructure(format!("Failed to parse Mach-O: {}", e))
     })?;
@@ -333,3 +346,104 @@ pub fn extract_macho_metadata(buffer: &[u8], metadata: &mut FileMetadata) -> Pro
 
     Ok(())
 }
+
+/// Reject a thin Mach-O whose header declares more load commands, or whose
+/// segments declare more sections, than `limits` allow. Fat binaries are
+/// let through: their slices aren't parsed.
+pub fn check_macho_limits(buffer: &[u8], limits: &ParserLimits) -> ProcessorResult<()> {
+    let Some(magic) = buffer.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])) else {
+        return Ok(());
+    };
+    let (big_endian, header_size) = match magic {
+        0xFEED_FACE => (false, 28),
+        0xFEED_FACF => (false, 32),
+        0xCEFA_EDFE => (true, 28),
+        0xCFFA_EDFE => (true, 32),
+        _ => return Ok(()),
+    };
+    let read_u32 = |offset: usize| {
+        let bytes: [u8; 4] = buffer.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+        Some(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    };
+    let malformed = |message: String| Err(FileProcessorError::MalformedStructure(message));
+
+    let Some(ncmds) = read_u32(16) else {
+        return Ok(());
+    };
+    if ncmds as usize > limits.max_load_commands {
+        return malformed(format!(
+            "Mach-O header declares {} load commands, more than the limit of {}",
+            ncmds, limits.max_load_commands
+        ));
+    }
+
+    let mut sections = 0usize;
+    let mut offset = header_size;
+    for _ in 0..ncmds {
+        let (Some(cmd), Some(cmdsize)) = (read_u32(offset), read_u32(offset + 4)) else {
+            break;
+        };
+        let nsects = match cmd {
+            LC_SEGMENT => read_u32(offset + 48),
+            LC_SEGMENT_64 => read_u32(offset + 64),
+            _ => None,
+        };
+        sections = sections.saturating_add(nsects.unwrap_or(0) as usize);
+        if sections > limits.max_sections {
+            return malformed(format!(
+                "Mach-O segments declare more than {} sections",
+                limits.max_sections
+            ));
+        }
+        if cmdsize < 8 {
+            break;
+        }
+        offset += cmdsize as usize;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 64-bit x86-64 executable whose one `LC_SEGMENT_64` command declares
+    /// `nsects` sections, and whose header claims `ncmds` load commands
+    fn macho64(ncmds: u32, nsects: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 32 + 72];
+        data[..4].copy_from_slice(&0xFEED_FACFu32.to_le_bytes());
+        data[4..8].copy_from_slice(&0x0100_0007u32.to_le_bytes()); // CPU_TYPE_X86_64
+        data[8..12].copy_from_slice(&3u32.to_le_bytes());
+        data[12..16].copy_from_slice(&2u32.to_le_bytes()); // MH_EXECUTE
+        data[16..20].copy_from_slice(&ncmds.to_le_bytes());
+        data[20..24].copy_from_slice(&72u32.to_le_bytes());
+        data[32..36].copy_from_slice(&LC_SEGMENT_64.to_le_bytes());
+        data[36..40].copy_from_slice(&72u32.to_le_bytes());
+        data[96..100].copy_from_slice(&nsects.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_absurd_load_command_count_rejected() {
+        let data = macho64(0x10000, 0);
+
+        let err = parse_macho(&data, FileFormat::MachO).unwrap_err();
+        assert!(matches!(&err, FileProcessorError::MalformedStructure(m) if m.contains("65536 load commands")), "{}", err);
+        assert!(crate::parser::extract_metadata(&data, FileFormat::MachO).is_err());
+
+        let limits = ParserLimits { max_load_commands: 0x10000, ..ParserLimits::default() };
+        assert!(check_macho_limits(&data, &limits).is_ok());
+    }
+
+    #[test]
+    fn test_segment_section_limit() {
+        let data = macho64(1, 5000);
+
+        let err = parse_macho(&data, FileFormat::MachO).unwrap_err();
+        assert!(err.to_string().contains("more than 1024 sections"), "{}", err);
+
+        let limits = ParserLimits { max_sections: 5000, ..ParserLimits::default() };
+        assert!(check_macho_limits(&data, &limits).is_ok());
+        assert!(check_macho_limits(&macho64(1, 0), &ParserLimits::default()).is_ok());
+    }
+}
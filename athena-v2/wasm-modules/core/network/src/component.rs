@@ -106,6 +106,14 @@ impl NetworkAnalyzerInstance {
         }).collect())
     }
 
+    fn detect_cc_patterns_internal(&self, traffic_json: &str, min_ioc_confidence: Option<f32>) -> std::result::Result<String, String> {
+        let min_ioc_confidence = min_ioc_confidence.unwrap_or(patterns::DEFAULT_MIN_IOC_CONFIDENCE);
+        let cc_patterns = patterns::detect_cc_patterns_with_min_ioc_confidence(traffic_json, min_ioc_confidence)
+            .map_err(|e| e.to_string())?;
+
+        serde_json::to_string(&cc_patterns).map_err(|e| e.to_string())
+    }
+
     fn get_version_internal(&self) -> String {
         self.version.clone()
     }
@@ -144,6 +152,10 @@ impl exports::athena::network::network::Guest for Component {
         handle.get::<NetworkAnalyzerResource>().instance.borrow().detect_anomalies_internal(&traffic_data)
     }
 
+    fn detect_cc_patterns(handle: exports::athena::network::network::NetworkAnalyzer, traffic_json: String, min_ioc_confidence: Option<f32>) -> std::result::Result<String, String> {
+        handle.get::<NetworkAnalyzerResource>().instance.borrow().detect_cc_patterns_internal(&traffic_json, min_ioc_confidence)
+    }
+
     fn get_version(handle: exports::athena::network::network::NetworkAnalyzer) -> String {
         handle.get::<NetworkAnalyzerResource>().instance.borrow().get_version_internal()
     }
@@ -186,6 +198,10 @@ impl exports::athena::network::network::GuestNetworkAnalyzer for NetworkAnalyzer
         self.instance.borrow().detect_anomalies_internal(&traffic_data)
     }
 
+    fn detect_cc_patterns(&self, traffic_json: String, min_ioc_confidence: Option<f32>) -> std::result::Result<String, String> {
+        self.instance.borrow().detect_cc_patterns_internal(&traffic_json, min_ioc_confidence)
+    }
+
     fn get_version(&self) -> String {
         self.instance.borrow().get_version_internal()
     }
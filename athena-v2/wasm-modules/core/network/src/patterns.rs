@@ -1,9 +1,13 @@
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use crate::{TrafficPattern, PacketAnalysis};
 
+/// IOCs from C&C patterns less confident than this are dropped unless
+/// configured otherwise; the same default as the deobfuscator's
+pub const DEFAULT_MIN_IOC_CONFIDENCE: f32 = 0.3;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CCPattern {
     pub pattern_type: String,
@@ -20,7 +24,7 @@ pub struct TrafficFlow {
     pub packet_count: usize,
     pub byte_count: usize,
     pub duration_ms: u64,
-    pub flags: HashSet<String>,
+    pub flags: BTreeSet<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +74,12 @@ pub fn analyze_traffic_pattern(packets_json: &str) -> Result<Vec<TrafficPattern>
 }
 
 pub fn detect_cc_patterns(traffic_json: &str) -> Result<Value> {
+    detect_cc_patterns_with_min_ioc_confidence(traffic_json, DEFAULT_MIN_IOC_CONFIDENCE)
+}
+
+/// `detect_cc_patterns`, keeping only the IOCs of patterns at least
+/// `min_ioc_confidence` sure; weaker patterns are still reported, without IOCs
+pub fn detect_cc_patterns_with_min_ioc_confidence(traffic_json: &str, min_ioc_confidence: f32) -> Result<Value> {
     let packets: Vec<PacketAnalysis> = serde_json::from_str(traffic_json)
         .map_err(|e| anyhow!("Failed to parse traffic JSON: {}", e))?;
 
@@ -101,6 +111,12 @@ pub fn detect_cc_patterns(traffic_json: &str) -> Result<Value> {
         });
     }
 
+    for pattern in &mut cc_patterns {
+        if pattern.confidence < f64::from(min_ioc_confidence) {
+            pattern.iocs.clear();
+        }
+    }
+
     Ok(json!({
         "patterns": cc_patterns,
         "total_detected": cc_patterns.len(),
@@ -108,8 +124,8 @@ pub fn detect_cc_patterns(traffic_json: &str) -> Result<Value> {
     }))
 }
 
-fn analyze_flows(packets: &[PacketAnalysis]) -> HashMap<String, TrafficFlow> {
-    let mut flows = HashMap::new();
+fn analyze_flows(packets: &[PacketAnalysis]) -> BTreeMap<String, TrafficFlow> {
+    let mut flows = BTreeMap::new();
 
     for packet in packets {
         if let (Some(src), Some(dst)) = (&packet.source_ip, &packet.dest_ip) {
@@ -122,7 +138,7 @@ fn analyze_flows(packets: &[PacketAnalysis]) -> HashMap<String, TrafficFlow> {
                 packet_count: 0,
                 byte_count: 0,
                 duration_ms: 0,
-                flags: HashSet::new(),
+                flags: BTreeSet::new(),
             });
 
             flow.packet_count += 1;
@@ -176,7 +192,7 @@ fn detect_flow_pattern(flow: &TrafficFlow) -> Option<TrafficPattern> {
 
 fn detect_beaconing(packets: &[PacketAnalysis]) -> Option<BeaconingPattern> {
     // Group packets by destination
-    let mut dest_packets: HashMap<String, Vec<i64>> = HashMap::new();
+    let mut dest_packets: BTreeMap<String, Vec<i64>> = BTreeMap::new();
 
     for packet in packets {
         if let (Some(dst), Some(ts)) = (&packet.dest_ip, packet.timestamp) {
@@ -221,13 +237,13 @@ fn detect_beaconing(packets: &[PacketAnalysis]) -> Option<BeaconingPattern> {
 }
 
 fn detect_scanning_pattern(packets: &[PacketAnalysis]) -> Option<TrafficPattern> {
-    let mut source_targets: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut source_targets: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
     let mut syn_packets = 0;
     let mut total_packets = 0;
 
     for packet in packets {
         if let (Some(src), Some(dst)) = (&packet.source_ip, &packet.dest_ip) {
-            source_targets.entry(src.clone()).or_insert_with(HashSet::new).insert(dst.clone());
+            source_targets.entry(src.clone()).or_insert_with(BTreeSet::new).insert(dst.clone());
             
             if packet.flags.contains(&"SYN".to_string()) && !packet.flags.contains(&"ACK".to_string()) {
                 syn_packets += 1;
@@ -442,4 +458,38 @@ mod tests {
         let beaconing = detect_beaconing(&packets);
         assert!(beaconing.is_none()); // Not enough packets for detection
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cc_iocs_dropped_below_min_confidence() {
+        // A beacon with some jitter, so its confidence lands near 0.83
+        let packets: Vec<Value> = [0, 10, 24, 34, 48, 58].iter().map(|ts| json!({
+            "packet_type": "ethernet",
+            "source_ip": "192.168.1.100",
+            "dest_ip": "203.0.113.7",
+            "source_port": 50000,
+            "dest_port": 443,
+            "protocol": "TCP",
+            "payload_size": 100,
+            "flags": ["ACK"],
+            "timestamp": ts,
+        })).collect();
+        let traffic = serde_json::to_string(&packets).unwrap();
+        let callback = |result: Value| {
+            result["patterns"].as_array().unwrap().iter()
+                .find(|p| p["pattern_type"] == "Periodic Callback")
+                .cloned()
+                .unwrap()
+        };
+
+        let kept = callback(detect_cc_patterns(&traffic).unwrap());
+        let confidence = kept["confidence"].as_f64().unwrap();
+        assert!(confidence > f64::from(DEFAULT_MIN_IOC_CONFIDENCE) && confidence < 0.9);
+        assert_eq!(kept["iocs"], json!(["203.0.113.7"]));
+
+        // A stricter threshold keeps the pattern but not its IOCs
+        let dropped = callback(detect_cc_patterns_with_min_ioc_confidence(&traffic, 0.9).unwrap());
+        assert_eq!(dropped["confidence"], kept["confidence"]);
+        assert_eq!(dropped["iocs"], json!([]));
+        assert!(!dropped["indicators"].as_array().unwrap().is_empty());
+    }
+}
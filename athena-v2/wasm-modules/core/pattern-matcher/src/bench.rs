@@ -0,0 +1,184 @@
+// Throughput benchmarking for analyzers
+
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::time::Instant;
+
+thread_local! {
+    /// Allocations made by this thread. Counted per thread so tests running
+    /// alongside a benchmark don't add to its figures.
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    // Unavailable while the thread is being torn down
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+fn allocations() -> u64 {
+    ALLOCATIONS.try_with(Cell::get).unwrap_or(0)
+}
+
+/// Allocator wrapper that counts heap allocations.
+///
+/// Install it with `#[global_allocator]` in a test or bench binary to have
+/// `BenchReport::allocations` populated; without it the count stays at zero.
+/// Only allocations on the benchmarking thread are counted.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub iterations: usize,
+    pub bytes_per_iteration: usize,
+    pub total_time_us: u64,
+    pub bytes_per_sec: f64,
+    pub p50_latency_us: f64,
+    pub p95_latency_us: f64,
+    /// Heap allocations per iteration (requires `CountingAllocator`)
+    pub allocations: u64,
+}
+
+/// Run `f` over `data` `iterations` times and collect throughput and latency figures.
+pub fn run<F>(data: &[u8], iterations: usize, mut f: F) -> BenchReport
+where
+    F: FnMut(&[u8]),
+{
+    let iterations = iterations.max(1);
+    let mut latencies = Vec::with_capacity(iterations);
+
+    let allocs_before = allocations();
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        let iter_start = Instant::now();
+        f(data);
+        latencies.push(iter_start.elapsed().as_nanos() as f64 / 1000.0);
+    }
+
+    let total = start.elapsed();
+    let allocs = allocations().saturating_sub(allocs_before);
+
+    latencies.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let total_secs = total.as_secs_f64();
+    let bytes_per_sec = if total_secs > 0.0 {
+        (data.len() * iterations) as f64 / total_secs
+    } else {
+        0.0
+    };
+
+    BenchReport {
+        iterations,
+        bytes_per_iteration: data.len(),
+        total_time_us: total.as_micros() as u64,
+        bytes_per_sec,
+        p50_latency_us: percentile(&latencies, 0.50),
+        p95_latency_us: percentile(&latencies, 0.95),
+        allocations: allocs / iterations as u64,
+    }
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::PatternMatcher;
+    use crate::types::*;
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    fn bench_matcher() -> PatternMatcher {
+        let mut matcher = PatternMatcher::new();
+        matcher.load_rules(vec![Rule {
+            id: "bench_rule".to_string(),
+            name: "Bench Rule".to_string(),
+            description: "Benchmark rule".to_string(),
+            patterns: vec![Pattern {
+                id: "p1".to_string(),
+                pattern_type: PatternType::Exact,
+                value: b"malware".to_vec(),
+                mask: None,
+                description: "Benchmark pattern".to_string(),
+                weight: 1.0,
+            }],
+            condition: Condition::All,
+            severity: Severity::High,
+            category: ThreatCategory::Malware,
+            tags: vec![],
+            metadata: serde_json::Value::Null,
+        }]).unwrap();
+        matcher
+    }
+
+    #[test]
+    fn test_benchmark_reports_throughput() {
+        let mut matcher = bench_matcher();
+        let mut data = vec![b'A'; 64 * 1024];
+        data[1000..1007].copy_from_slice(b"malware");
+
+        let report = matcher.benchmark(&data, 20);
+
+        assert_eq!(report.iterations, 20);
+        assert_eq!(report.bytes_per_iteration, data.len());
+        assert!(report.bytes_per_sec > 0.0);
+        assert!(report.p50_latency_us > 0.0);
+        assert!(report.p95_latency_us >= report.p50_latency_us);
+        assert!(report.allocations > 0);
+    }
+
+    #[test]
+    fn test_allocations_counted_per_thread() {
+        // Another thread allocating throughout the run isn't counted
+        let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let noise = std::thread::spawn({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(std::sync::atomic::Ordering::Relaxed) {
+                    std::hint::black_box(vec![0u8; 64]);
+                }
+            }
+        });
+
+        let report = run(b"abc", 1000, |data| {
+            std::hint::black_box(data.len());
+        });
+        stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        noise.join().unwrap();
+
+        assert_eq!(report.allocations, 0);
+    }
+
+    #[test]
+    fn test_benchmark_zero_iterations_runs_once() {
+        let mut calls = 0;
+        let report = run(b"abc", 0, |_| calls += 1);
+
+        assert_eq!(calls, 1);
+        assert_eq!(report.iterations, 1);
+    }
+}
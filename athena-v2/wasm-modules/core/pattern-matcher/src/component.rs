@@ -8,6 +8,7 @@ wit_bindgen::generate!({
 use crate::types::*;
 use crate::matcher::PatternMatcher as InternalMatcher;
 use crate::signatures::SignatureDatabase;
+use crate::streaming::StreamingScanner;
 use std::cell::RefCell;
 
 // ============================================================================
@@ -71,6 +72,12 @@ impl MatcherInstance {
     fn clear_rules_internal(&mut self) {
         self.internal.clear_rules();
     }
+
+    fn configure_internal(&mut self, config: exports::athena::pattern_matcher::pattern_matcher::MatcherConfig) {
+        // A limit past the address space can't truncate anything
+        let limit = config.max_scan_bytes.map(|limit| usize::try_from(limit).unwrap_or(usize::MAX));
+        self.internal.set_max_scan_bytes(limit);
+    }
 }
 
 // ============================================================================
@@ -95,6 +102,19 @@ impl exports::athena::pattern_matcher::pattern_matcher::Guest for Component {
         )
     }
 
+    fn new_with_config(config: exports::athena::pattern_matcher::pattern_matcher::MatcherConfig) -> exports::athena::pattern_matcher::pattern_matcher::Matcher {
+        let mut instance = MatcherInstance::new();
+        let _ = instance.load_default_rules_internal();
+        instance.configure_internal(config);
+        exports::athena::pattern_matcher::pattern_matcher::Matcher::new(
+            MatcherResource::new(instance)
+        )
+    }
+
+    fn configure(handle: exports::athena::pattern_matcher::pattern_matcher::Matcher, config: exports::athena::pattern_matcher::pattern_matcher::MatcherConfig) {
+        handle.get::<MatcherResource>().instance.borrow_mut().configure_internal(config);
+    }
+
     fn load_default_rules(handle: exports::athena::pattern_matcher::pattern_matcher::Matcher) -> std::result::Result<(), String> {
         handle.get::<MatcherResource>().instance.borrow_mut().load_default_rules_internal()
     }
@@ -166,6 +186,10 @@ impl exports::athena::pattern_matcher::pattern_matcher::GuestMatcher for Matcher
     fn clear_rules(&self) {
         self.instance.borrow_mut().clear_rules_internal();
     }
+
+    fn configure(&self, config: exports::athena::pattern_matcher::pattern_matcher::MatcherConfig) {
+        self.instance.borrow_mut().configure_internal(config);
+    }
 }
 
 // ============================================================================
@@ -173,9 +197,7 @@ impl exports::athena::pattern_matcher::pattern_matcher::GuestMatcher for Matcher
 // ============================================================================
 
 struct StreamingScannerResource {
-    matcher: RefCell<InternalMatcher>,
-    buffer: RefCell<Vec<u8>>,
-    chunk_size: usize,
+    scanner: RefCell<StreamingScanner>,
 }
 
 impl StreamingScannerResource {
@@ -186,9 +208,7 @@ impl StreamingScannerResource {
             .map_err(|e| e.to_string())?;
 
         Ok(Self {
-            matcher: RefCell::new(matcher),
-            buffer: RefCell::new(Vec::new()),
-            chunk_size: chunk_size as usize,
+            scanner: RefCell::new(StreamingScanner::new(matcher, chunk_size as usize)),
         })
     }
 }
@@ -198,49 +218,20 @@ impl exports::athena::pattern_matcher::pattern_matcher::GuestStreamingScanner fo
         Self::new(chunk_size).expect("Failed to create streaming scanner")
     }
 
+    fn set_overlap(&self, bytes: u32) {
+        self.scanner.borrow_mut().set_overlap(bytes as usize);
+    }
+
     fn process_chunk(&self, chunk: Vec<u8>) -> std::result::Result<exports::athena::pattern_matcher::pattern_matcher::ScanChunk, String> {
-        let mut buffer = self.buffer.borrow_mut();
-        buffer.extend_from_slice(&chunk);
-
-        if buffer.len() >= self.chunk_size {
-            let result = self.matcher.borrow_mut().scan(&buffer)
-                .map_err(|e| e.to_string())?;
-
-            // Keep last 1KB for overlap detection
-            let overlap_size = 1024.min(buffer.len());
-            let new_buffer = buffer[buffer.len() - overlap_size..].to_vec();
-            *buffer = new_buffer;
-
-            Ok(exports::athena::pattern_matcher::pattern_matcher::ScanChunk {
-                has_result: true,
-                scan_result: Some(convert_scan_result(result)),
-            })
-        } else {
-            Ok(exports::athena::pattern_matcher::pattern_matcher::ScanChunk {
-                has_result: false,
-                scan_result: None,
-            })
-        }
+        let result = self.scanner.borrow_mut().process_chunk(&chunk)
+            .map_err(|e| e.to_string())?;
+        Ok(convert_scan_chunk(result))
     }
 
     fn finish(&self) -> std::result::Result<exports::athena::pattern_matcher::pattern_matcher::ScanChunk, String> {
-        let mut buffer = self.buffer.borrow_mut();
-
-        if !buffer.is_empty() {
-            let result = self.matcher.borrow_mut().scan(&buffer)
-                .map_err(|e| e.to_string())?;
-            buffer.clear();
-
-            Ok(exports::athena::pattern_matcher::pattern_matcher::ScanChunk {
-                has_result: true,
-                scan_result: Some(convert_scan_result(result)),
-            })
-        } else {
-            Ok(exports::athena::pattern_matcher::pattern_matcher::ScanChunk {
-                has_result: false,
-                scan_result: None,
-            })
-        }
+        let result = self.scanner.borrow_mut().finish()
+            .map_err(|e| e.to_string())?;
+        Ok(convert_scan_chunk(result))
     }
 }
 
@@ -248,6 +239,13 @@ impl exports::athena::pattern_matcher::pattern_matcher::GuestStreamingScanner fo
 // Helper Functions - Conversion
 // ============================================================================
 
+fn convert_scan_chunk(result: Option<ScanResult>) -> exports::athena::pattern_matcher::pattern_matcher::ScanChunk {
+    exports::athena::pattern_matcher::pattern_matcher::ScanChunk {
+        has_result: result.is_some(),
+        scan_result: result.map(convert_scan_result),
+    }
+}
+
 fn convert_scan_result(result: ScanResult) -> exports::athena::pattern_matcher::pattern_matcher::ScanResult {
     exports::athena::pattern_matcher::pattern_matcher::ScanResult {
         matches: result.matches.into_iter().map(convert_match).collect(),
@@ -255,6 +253,7 @@ fn convert_scan_result(result: ScanResult) -> exports::athena::pattern_matcher::
         scan_time_ms: result.scan_time_ms,
         bytes_scanned: result.bytes_scanned as u64,
         threat_score: result.threat_score,
+        scan_truncated: result.scan_truncated,
     }
 }
 
@@ -1,6 +1,7 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
 use rustc_hash::FxHashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::types::*;
 use crate::fuzzy::{FuzzyMatcher, FuzzyConfig, FuzzyAlgorithm};
@@ -41,7 +42,9 @@ impl PatternEngine {
         Ok(())
     }
 
+    /// Rebuild the engine from `rules`, replacing whatever it held
     pub fn compile(&mut self, rules: &[CompiledRule]) -> Result<()> {
+        self.clear();
         let mut exact_patterns_bytes = Vec::new();
         let mut exact_pattern_info = Vec::new();
 
@@ -109,11 +112,20 @@ impl PatternEngine {
     }
 
     pub fn scan(&self, data: &[u8]) -> Result<Vec<Match>> {
+        self.scan_timed(data).map(|(matches, _)| matches)
+    }
+
+    /// `scan`, also returning the time spent on each rule. Exact patterns
+    /// share one Aho-Corasick pass, whose time is split evenly between the
+    /// rules that have them.
+    pub fn scan_timed(&self, data: &[u8]) -> Result<(Vec<Match>, FxHashMap<String, Duration>)> {
         let mut matches = Vec::new();
         let mut pattern_matches: FxHashMap<String, Vec<(usize, usize)>> = FxHashMap::default();
+        let mut timings: FxHashMap<String, Duration> = FxHashMap::default();
 
         // Scan with Aho-Corasick for exact patterns
         if let Some(ref ac) = self.exact_matcher {
+            let started = Instant::now();
             for mat in ac.find_iter(data) {
                 let pattern_idx = mat.pattern().as_usize();
                 if let Some((pattern_id, rule_id, weight)) = self.exact_patterns.get(pattern_idx) {
@@ -140,10 +152,18 @@ impl PatternEngine {
                     }
                 }
             }
+            let mut exact_rules: Vec<&String> = self.exact_patterns.iter().map(|(_, rule_id, _)| rule_id).collect();
+            exact_rules.sort_unstable();
+            exact_rules.dedup();
+            let share = started.elapsed() / exact_rules.len().max(1) as u32;
+            for rule_id in exact_rules {
+                *timings.entry(rule_id.clone()).or_default() += share;
+            }
         }
 
         // Scan regex patterns
         for (pattern_id, rule_id, regex, weight) in &self.regex_patterns {
+            let started = Instant::now();
             // Convert bytes to string for regex matching
             if let Ok(text) = std::str::from_utf8(data) {
                 for mat in regex.find_iter(text) {
@@ -170,10 +190,12 @@ impl PatternEngine {
                     }
                 }
             }
+            *timings.entry(rule_id.clone()).or_default() += started.elapsed();
         }
 
         // Scan binary patterns with masks
         for (pattern_id, rule_id, pattern, mask, weight) in &self.binary_patterns {
+            let started = Instant::now();
             for offset in 0..data.len().saturating_sub(pattern.len() - 1) {
                 if Self::matches_with_mask(&data[offset..], pattern, mask) {
                     let length = pattern.len();
@@ -198,10 +220,12 @@ impl PatternEngine {
                     }
                 }
             }
+            *timings.entry(rule_id.clone()).or_default() += started.elapsed();
         }
 
         // Scan fuzzy patterns
         for (pattern_id, rule_id, pattern, weight) in &self.fuzzy_patterns {
+            let started = Instant::now();
             let positions = self.fuzzy_matcher.find_all(pattern, data);
 
             for offset in positions {
@@ -226,12 +250,13 @@ impl PatternEngine {
                     });
                 }
             }
+            *timings.entry(rule_id.clone()).or_default() += started.elapsed();
         }
 
         // Evaluate rule conditions and filter matches
         matches = self.evaluate_conditions(&matches, &pattern_matches);
 
-        Ok(matches)
+        Ok((matches, timings))
     }
 
     /// Evaluate rule conditions and filter matches
@@ -1,11 +1,13 @@
 // Component Model implementation
 mod component;
 
+pub mod bench;
 pub mod engine;
 pub mod fuzzy;
 pub mod matcher;
 pub mod rules;
 pub mod signatures;
+pub mod streaming;
 pub mod types;
 pub mod utils;
 pub mod yara_modules;
@@ -1,8 +1,9 @@
+use crate::bench::{self, BenchReport};
 use crate::engine::PatternEngine;
 use crate::rules::{RuleCompiler, RuleParser};
 use crate::types::*;
 use rustc_hash::FxHashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub struct PatternMatcher {
     engine: PatternEngine,
@@ -10,6 +11,10 @@ pub struct PatternMatcher {
     compiled_rules: Vec<CompiledRule>,
     rule_index: FxHashMap<String, usize>,
     stats: MatcherStats,
+    /// Time and match count per rule id, summed over scans
+    rule_profile: FxHashMap<String, (Duration, usize)>,
+    /// Scan at most this many bytes from the start of the data
+    max_scan_bytes: Option<usize>,
 }
 
 #[derive(Debug, Default)]
@@ -28,9 +33,23 @@ impl PatternMatcher {
             compiled_rules: Vec::new(),
             rule_index: FxHashMap::default(),
             stats: MatcherStats::default(),
+            rule_profile: FxHashMap::default(),
+            max_scan_bytes: None,
         }
     }
 
+    /// Scan only the first `limit` bytes of the data, e.g. to triage a
+    /// huge file on its header
+    pub fn with_max_scan_bytes(mut self, limit: usize) -> Self {
+        self.max_scan_bytes = Some(limit);
+        self
+    }
+
+    /// Change the scan limit; `None` scans all of the data
+    pub fn set_max_scan_bytes(&mut self, limit: Option<usize>) {
+        self.max_scan_bytes = limit;
+    }
+
     pub fn load_rules(&mut self, rules: Vec<Rule>) -> Result<()> {
         self.rules = rules;
         self.compile_all_rules()?;
@@ -77,9 +96,28 @@ impl PatternMatcher {
 
     pub fn scan(&mut self, data: &[u8]) -> Result<ScanResult> {
         let start = Instant::now();
-        
-        let matches = self.engine.scan(data)?;
-        let matches_with_confidence = self.apply_confidence_scoring(matches, data);
+
+        let scan_truncated = self.max_scan_bytes.is_some_and(|limit| data.len() > limit);
+        let data = match self.max_scan_bytes {
+            Some(limit) if scan_truncated => &data[..limit],
+            _ => data,
+        };
+
+        let (matches, timings) = self.engine.scan_timed(data)?;
+        for (rule_id, time) in timings {
+            self.rule_profile.entry(rule_id).or_default().0 += time;
+        }
+        for m in &matches {
+            self.rule_profile.entry(m.rule_id.clone()).or_default().1 += 1;
+        }
+        let mut matches_with_confidence = self.apply_confidence_scoring(matches, data);
+        // Engine passes report in pattern-type order; report by position
+        matches_with_confidence.sort_by(|a, b| {
+            a.offset.cmp(&b.offset)
+                .then_with(|| a.rule_id.cmp(&b.rule_id))
+                .then_with(|| a.pattern_id.cmp(&b.pattern_id))
+                .then_with(|| a.length.cmp(&b.length))
+        });
         
         let scan_time_ms = start.elapsed().as_millis() as u64;
         let threat_score = self.calculate_threat_score(&matches_with_confidence);
@@ -96,6 +134,7 @@ impl PatternMatcher {
             scan_time_ms,
             bytes_scanned: data.len(),
             threat_score,
+            scan_truncated,
         })
     }
 
@@ -195,6 +234,18 @@ impl PatternMatcher {
         normalized
     }
 
+    /// Length of the longest loaded pattern with a fixed length. Regex
+    /// matches have no fixed length and are not counted.
+    pub fn longest_pattern_len(&self) -> usize {
+        self.rules
+            .iter()
+            .flat_map(|rule| &rule.patterns)
+            .filter(|pattern| pattern.pattern_type != PatternType::Regex)
+            .map(|pattern| pattern.value.len())
+            .max()
+            .unwrap_or(0)
+    }
+
     pub fn get_rule_count(&self) -> usize {
         self.rules.len()
     }
@@ -209,7 +260,24 @@ impl PatternMatcher {
         (self.stats.total_scans, self.stats.total_matches, avg_time)
     }
 
+    /// Time and matches per rule over all scans so far, slowest first, to
+    /// find rules worth pruning
+    pub fn get_rule_profile(&self) -> Vec<RulePerf> {
+        let mut profile: Vec<RulePerf> = self
+            .rule_profile
+            .iter()
+            .map(|(rule_id, (time, match_count))| RulePerf {
+                rule_id: rule_id.clone(),
+                total_time_ns: time.as_nanos() as u64,
+                match_count: *match_count,
+            })
+            .collect();
+        profile.sort_by(|a, b| b.total_time_ns.cmp(&a.total_time_ns).then_with(|| a.rule_id.cmp(&b.rule_id)));
+        profile
+    }
+
     pub fn clear_rules(&mut self) {
+        self.rule_profile.clear();
         self.rules.clear();
         self.compiled_rules.clear();
         self.rule_index.clear();
@@ -224,6 +292,13 @@ impl PatternMatcher {
         let bytes_per_second = (self.stats.total_bytes_scanned as f64 * 1000.0) / self.stats.total_time_ms as f64;
         bytes_per_second / (1024.0 * 1024.0) // Convert to MB/s
     }
+
+    /// Scan `data` repeatedly with the loaded rules and report throughput and latency.
+    pub fn benchmark(&mut self, data: &[u8], iterations: usize) -> BenchReport {
+        bench::run(data, iterations, |buf| {
+            let _ = self.scan(buf);
+        })
+    }
 }
 
 #[cfg(test)]
@@ -263,6 +338,83 @@ mod tests {
         assert!(result.threat_score > 0.0);
     }
 
+    fn literal_rule(id: &str, literal: &[u8]) -> Rule {
+        Rule {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            patterns: vec![Pattern {
+                id: "p1".to_string(),
+                pattern_type: PatternType::Exact,
+                value: literal.to_vec(),
+                mask: None,
+                description: String::new(),
+                weight: 1.0,
+            }],
+            condition: Condition::All,
+            severity: Severity::Medium,
+            category: ThreatCategory::Suspicious,
+            tags: vec![],
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_rule_profile_ranks_expensive_rule_first() {
+        let mut broad = literal_rule("broad_regex", br"\b\w+\s+\w+\s+\d+\b");
+        broad.patterns[0].pattern_type = PatternType::Regex;
+        let mut matcher = PatternMatcher::new();
+        matcher.load_rules(vec![literal_rule("cheap_literal", b"XQZJ"), broad]).unwrap();
+
+        let data = "lorem ipsum dolor sit amet ".repeat(20_000);
+        for _ in 0..3 {
+            matcher.scan(data.as_bytes()).unwrap();
+        }
+        matcher.scan(b"XQZJ seen at build 2024").unwrap();
+
+        let profile = matcher.get_rule_profile();
+        assert_eq!(profile.len(), 2);
+        assert_eq!(profile[0].rule_id, "broad_regex");
+        assert!(profile[0].total_time_ns > profile[1].total_time_ns);
+        assert_eq!(profile[0].match_count, 1);
+        assert_eq!(profile[1].match_count, 1);
+    }
+
+    #[test]
+    fn test_scan_stops_at_byte_limit() {
+        let mut data = vec![0u8; 1024 * 1024];
+        data[..2].copy_from_slice(b"MZ");
+        data[100..108].copy_from_slice(b"UPX0UPX1");
+        data[512 * 1024..512 * 1024 + 10].copy_from_slice(b"beacon.dll");
+        let rules = vec![literal_rule("upx", b"UPX0UPX1"), literal_rule("cobalt_strike", b"beacon.dll")];
+
+        let mut matcher = PatternMatcher::new().with_max_scan_bytes(4096);
+        matcher.load_rules(rules.clone()).unwrap();
+        let result = matcher.scan(&data).unwrap();
+
+        assert!(result.scan_truncated);
+        assert_eq!(result.bytes_scanned, 4096);
+        let ids: Vec<&str> = result.matches.iter().map(|m| m.rule_id.as_str()).collect();
+        assert_eq!(ids, vec!["upx"]);
+
+        // Data within the limit is scanned whole
+        let result = matcher.scan(&data[..4096]).unwrap();
+        assert!(!result.scan_truncated);
+
+        let mut unlimited = PatternMatcher::new();
+        unlimited.load_rules(rules).unwrap();
+        let result = unlimited.scan(&data).unwrap();
+        assert!(!result.scan_truncated);
+        assert_eq!(result.bytes_scanned, data.len());
+        assert_eq!(result.matches.len(), 2);
+
+        // The limit can be set, and lifted, after the matcher is built
+        unlimited.set_max_scan_bytes(Some(4096));
+        assert!(unlimited.scan(&data).unwrap().scan_truncated);
+        unlimited.set_max_scan_bytes(None);
+        assert!(!unlimited.scan(&data).unwrap().scan_truncated);
+    }
+
     #[test]
     fn test_confidence_scoring() {
         let mut matcher = PatternMatcher::new();
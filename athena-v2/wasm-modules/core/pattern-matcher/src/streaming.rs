@@ -0,0 +1,155 @@
+use crate::matcher::PatternMatcher;
+use crate::types::*;
+
+/// Bytes carried from one chunk into the next unless configured otherwise
+pub const DEFAULT_STREAM_OVERLAP: usize = 1024;
+
+/// Scans data that arrives in pieces. Data is buffered until `chunk_size`
+/// bytes are available, then scanned; the tail of each scanned buffer is
+/// carried into the next so signatures straddling a boundary are still
+/// found.
+pub struct StreamingScanner {
+    matcher: PatternMatcher,
+    buffer: Vec<u8>,
+    chunk_size: usize,
+    overlap: usize,
+    /// Stream offset of `buffer[0]`
+    buffer_offset: usize,
+    /// Leading bytes of `buffer` carried over from the previous scan
+    carried: usize,
+}
+
+impl StreamingScanner {
+    pub fn new(matcher: PatternMatcher, chunk_size: usize) -> Self {
+        Self {
+            matcher,
+            buffer: Vec::new(),
+            chunk_size,
+            overlap: DEFAULT_STREAM_OVERLAP,
+            buffer_offset: 0,
+            carried: 0,
+        }
+    }
+
+    /// Carry `overlap` bytes between chunks; `overlap()` never goes below
+    /// what the longest loaded pattern needs
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    pub fn set_overlap(&mut self, overlap: usize) {
+        self.overlap = overlap;
+    }
+
+    /// Bytes actually carried between chunks: the configured overlap,
+    /// widened so the longest pattern always fits across a boundary
+    pub fn overlap(&self) -> usize {
+        self.overlap.max(self.matcher.longest_pattern_len().saturating_sub(1))
+    }
+
+    /// Add `chunk` to the stream, scanning once a full chunk is buffered
+    pub fn process_chunk(&mut self, chunk: &[u8]) -> Result<Option<ScanResult>> {
+        self.buffer.extend_from_slice(chunk);
+        if self.buffer.len() < self.chunk_size {
+            return Ok(None);
+        }
+
+        let result = self.scan_buffer()?;
+
+        let overlap = self.overlap().min(self.buffer.len());
+        let consumed = self.buffer.len() - overlap;
+        self.buffer.drain(..consumed);
+        self.buffer_offset += consumed;
+        self.carried = overlap;
+
+        Ok(Some(result))
+    }
+
+    /// Scan whatever is still buffered at the end of the stream
+    pub fn finish(&mut self) -> Result<Option<ScanResult>> {
+        if self.buffer.len() <= self.carried {
+            self.buffer.clear();
+            return Ok(None);
+        }
+
+        let result = self.scan_buffer()?;
+        self.buffer_offset += self.buffer.len();
+        self.buffer.clear();
+        self.carried = 0;
+        Ok(Some(result))
+    }
+
+    /// Scan the buffer, dropping matches that lie wholly in the carried
+    /// bytes (the previous scan reported them) and making offsets relative
+    /// to the start of the stream
+    fn scan_buffer(&mut self) -> Result<ScanResult> {
+        let mut result = self.matcher.scan(&self.buffer)?;
+        let carried = self.carried;
+        result.matches.retain(|m| m.offset + m.length > carried);
+        for m in &mut result.matches {
+            m.offset += self.buffer_offset;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long_signature_matcher(signature: &[u8]) -> PatternMatcher {
+        let mut matcher = PatternMatcher::new();
+        matcher
+            .load_rules(vec![Rule {
+                id: "long_sig".to_string(),
+                name: "Long Signature".to_string(),
+                description: String::new(),
+                patterns: vec![Pattern {
+                    id: "p1".to_string(),
+                    pattern_type: PatternType::Exact,
+                    value: signature.to_vec(),
+                    mask: None,
+                    description: String::new(),
+                    weight: 1.0,
+                }],
+                condition: Condition::All,
+                severity: Severity::High,
+                category: ThreatCategory::Malware,
+                tags: vec![],
+                metadata: serde_json::Value::Null,
+            }])
+            .unwrap();
+        matcher
+    }
+
+    #[test]
+    fn test_long_signature_across_chunk_boundary() {
+        // 2000 bytes, longer than the default overlap
+        let signature: Vec<u8> = (0..2000u32).map(|i| (i % 251) as u8 + 1).collect();
+        let mut data = vec![0u8; 8192];
+        let start = 4096 - 1500;
+        data[start..start + signature.len()].copy_from_slice(&signature);
+
+        let mut scanner = StreamingScanner::new(long_signature_matcher(&signature), 4096);
+        assert!(scanner.overlap() >= signature.len() - 1);
+
+        let mut matches = Vec::new();
+        for chunk in data.chunks(4096) {
+            if let Some(result) = scanner.process_chunk(chunk).unwrap() {
+                matches.extend(result.matches);
+            }
+        }
+        if let Some(result) = scanner.finish().unwrap() {
+            matches.extend(result.matches);
+        }
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].offset, start);
+        assert_eq!(matches[0].length, signature.len());
+
+        // A wider configured overlap is kept as is
+        let scanner = StreamingScanner::new(long_signature_matcher(&signature), 4096).with_overlap(3000);
+        assert_eq!(scanner.overlap(), 3000);
+    }
+}
@@ -80,6 +80,9 @@ pub struct ScanResult {
     pub scan_time_ms: u64,
     pub bytes_scanned: usize,
     pub threat_score: f32,
+    /// Only the first `bytes_scanned` bytes were scanned, because of the
+    /// matcher's `max_scan_bytes`
+    pub scan_truncated: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -145,6 +148,15 @@ impl std::fmt::Display for PatternMatcherError {
 
 impl std::error::Error for PatternMatcherError {}
 
+/// Time and matches one rule has accumulated over a matcher's scans
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulePerf {
+    pub rule_id: String,
+    /// Time spent matching the rule's patterns, in nanoseconds
+    pub total_time_ns: u64,
+    pub match_count: usize,
+}
+
 pub struct PatternStats {
     pub total_patterns: usize,
     pub exact_patterns: usize,
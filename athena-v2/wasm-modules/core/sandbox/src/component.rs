@@ -5,7 +5,10 @@ wit_bindgen::generate!({
     path: "wit",
 });
 
-use crate::policy::ExecutionPolicy;
+use crate::policy::{
+    ExecutionPolicy, FakeResponse, FileSystemPolicy, NetworkEmulation, NetworkPolicy, PolicyWarningSeverity, SeedFile,
+    SensitivePathRule, SyscallPolicy,
+};
 use crate::monitor::ResourceMonitor;
 use crate::instance::SandboxInstance;
 use crate::{SecurityEventType, SecuritySeverity};
@@ -39,15 +42,19 @@ impl SandboxManagerInstance {
         }
     }
 
-    fn create_instance_internal(&mut self, _policy: Option<exports::athena::sandbox::sandbox::ExecutionPolicy>) -> std::result::Result<String, String> {
-        // Use default policy (complex policy conversion omitted for simplicity)
-        let policy = self.default_policy.clone();
+    fn create_instance_internal(&mut self, policy: Option<exports::athena::sandbox::sandbox::ExecutionPolicy>) -> std::result::Result<String, String> {
+        let policy = match policy {
+            Some(policy) => convert_policy(policy)?,
+            None => self.default_policy.clone(),
+        };
 
         let instance_id = format!("sandbox-{}", self.next_instance_id);
         self.next_instance_id += 1;
 
-        let instance = SandboxInstance::new(instance_id.clone(), policy)
+        let mut instance = SandboxInstance::new(instance_id.clone(), policy)
             .map_err(|e| e.to_string())?;
+        instance.initialize().map_err(|e| e.to_string())?;
+        instance.start().map_err(|e| e.to_string())?;
 
         self.instances.insert(instance_id.clone(), instance);
 
@@ -196,6 +203,67 @@ impl exports::athena::sandbox::sandbox::GuestSandboxManager for SandboxManagerRe
 // Helper Functions - Conversion
 // ============================================================================
 
+/// Build the sandbox policy a WIT policy describes, starting from the
+/// default for anything the WIT record can't express. Policies that can't
+/// produce a run are rejected.
+fn convert_policy(policy: exports::athena::sandbox::sandbox::ExecutionPolicy) -> std::result::Result<ExecutionPolicy, String> {
+    let mut converted = ExecutionPolicy::default();
+
+    let limits = &mut converted.resource_limits;
+    limits.max_memory_bytes = usize::try_from(policy.max_memory_bytes).unwrap_or(usize::MAX);
+    // The executor measures wall-clock time against a single limit
+    limits.max_cpu_time_ms = policy.max_cpu_time_ms.min(policy.max_execution_time_ms);
+
+    let security = &mut converted.security_policy;
+    if policy.allow_network {
+        security.network_policy = NetworkPolicy::DenyList(Default::default());
+    }
+    if !policy.allow_file_access {
+        security.file_system_policy = FileSystemPolicy::Disabled;
+    }
+    if !policy.allowed_syscalls.is_empty() {
+        security.syscall_policy = SyscallPolicy::AllowList(policy.allowed_syscalls.into_iter().collect());
+    }
+
+    converted.vfs_seed = policy.vfs_seed.into_iter().map(|seed| SeedFile {
+        path: seed.path,
+        content: seed.content,
+        writable: seed.writable,
+    }).collect();
+    converted.random_seed = policy.random_seed;
+    converted.virtual_time = policy.virtual_time;
+    converted.network_emulation = NetworkEmulation {
+        responses: policy.network_emulation.responses.into_iter().map(|r| FakeResponse {
+            host: r.host,
+            port: r.port,
+            response: r.response,
+        }).collect(),
+        default_response: policy.network_emulation.default_response,
+    };
+    if let Some(rules) = policy.sensitive_paths {
+        converted.monitoring.sensitive_paths = rules.into_iter().map(|rule| SensitivePathRule {
+            name: rule.name,
+            path_contains: rule.path_contains,
+            extensions: rule.extensions,
+            reads: rule.reads,
+            severity: convert_wit_severity(rule.severity),
+        }).collect();
+    }
+    converted.monitoring.trusted_paths = policy.trusted_paths;
+
+    if let Err(warnings) = converted.validate() {
+        let errors: Vec<String> = warnings.into_iter()
+            .filter(|w| w.severity == PolicyWarningSeverity::Error)
+            .map(|w| format!("{}: {}", w.field, w.message))
+            .collect();
+        if !errors.is_empty() {
+            return Err(format!("Invalid execution policy: {}", errors.join("; ")));
+        }
+    }
+
+    Ok(converted)
+}
+
 fn convert_event_type(event_type: SecurityEventType) -> exports::athena::sandbox::sandbox::SecurityEventType {
     use exports::athena::sandbox::sandbox::SecurityEventType as WitType;
     match event_type {
@@ -218,8 +286,107 @@ fn convert_severity(severity: SecuritySeverity) -> exports::athena::sandbox::san
     }
 }
 
+fn convert_wit_severity(severity: exports::athena::sandbox::sandbox::SecuritySeverity) -> SecuritySeverity {
+    use exports::athena::sandbox::sandbox::SecuritySeverity as WitSeverity;
+    match severity {
+        WitSeverity::Low => SecuritySeverity::Low,
+        WitSeverity::Medium => SecuritySeverity::Medium,
+        WitSeverity::High => SecuritySeverity::High,
+        WitSeverity::Critical => SecuritySeverity::Critical,
+    }
+}
+
 // ============================================================================
 // Export Component Implementations
 // ============================================================================
 
 export!(Component);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exports::athena::sandbox::sandbox as wit;
+
+    fn wit_policy() -> wit::ExecutionPolicy {
+        wit::ExecutionPolicy {
+            max_memory_bytes: 64 * 1024 * 1024,
+            max_cpu_time_ms: 20_000,
+            max_execution_time_ms: 5_000,
+            allow_network: false,
+            allow_file_access: true,
+            allowed_syscalls: Vec::new(),
+            vfs_seed: vec![wit::SeedFile {
+                path: "/home/analyst/Documents/passwords.txt".to_string(),
+                content: b"hunter2".to_vec(),
+                writable: false,
+            }],
+            random_seed: None,
+            virtual_time: None,
+            network_emulation: wit::NetworkEmulation {
+                responses: vec![wit::FakeResponse {
+                    host: "update.example".to_string(),
+                    port: None,
+                    response: b"OK".to_vec(),
+                }],
+                default_response: None,
+            },
+            sensitive_paths: None,
+            trusted_paths: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_create_instance_honours_policy() {
+        let mut manager = SandboxManagerInstance::new();
+        let id = manager.create_instance_internal(Some(wit_policy())).unwrap();
+
+        let policy = &manager.instances[&id].policy;
+        assert_eq!(policy.resource_limits.max_memory_bytes, 64 * 1024 * 1024);
+        assert_eq!(policy.resource_limits.max_cpu_time_ms, 5_000);
+        assert_eq!(policy.vfs_seed[0].content, b"hunter2");
+        assert_eq!(policy.network_emulation.response_for("update.example", 443), Some(&b"OK"[..]));
+
+        let result = manager
+            .execute_internal(&id, b"creds = open('/home/analyst/Documents/passwords.txt').read()")
+            .unwrap();
+        assert!(result.success);
+        assert!(result.stdout.contains("File operations: 3"));
+    }
+
+    #[test]
+    fn test_sensitive_path_rules_from_policy() {
+        let mut manager = SandboxManagerInstance::new();
+        let defaults = manager.create_instance_internal(Some(wit_policy())).unwrap();
+        assert_eq!(manager.instances[&defaults].policy.monitoring.sensitive_paths, SensitivePathRule::defaults());
+
+        let policy = wit::ExecutionPolicy {
+            sensitive_paths: Some(vec![wit::SensitivePathRule {
+                name: "Browser profile".to_string(),
+                path_contains: vec!["/.mozilla/firefox/".to_string()],
+                extensions: Vec::new(),
+                reads: false,
+                severity: wit::SecuritySeverity::Critical,
+            }]),
+            trusted_paths: vec!["/tmp/decoys/".to_string()],
+            ..wit_policy()
+        };
+        let id = manager.create_instance_internal(Some(policy)).unwrap();
+        let result = manager
+            .execute_internal(&id, b"open('/home/u/.mozilla/firefox/profiles/x/logins.json', 'w')\nopen('/tmp/decoys/a.exe', 'w')")
+            .unwrap();
+
+        // The custom rule fires and fails the run; the built-in temp rule is gone
+        assert!(!result.success);
+        assert_eq!(result.security_events.len(), 1);
+        assert!(result.security_events[0].description.starts_with("Write to Browser profile"));
+    }
+
+    #[test]
+    fn test_unusable_policy_rejected() {
+        let mut manager = SandboxManagerInstance::new();
+        let policy = wit::ExecutionPolicy { max_memory_bytes: 4096, ..wit_policy() };
+        let err = manager.create_instance_internal(Some(policy)).unwrap_err();
+        assert!(err.contains("max_memory_bytes"));
+        assert!(manager.instances.is_empty());
+    }
+}
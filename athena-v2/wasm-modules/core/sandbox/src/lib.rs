@@ -60,6 +60,8 @@ pub mod instance;
 pub mod executor;
 pub mod pool;
 pub mod metrics;
+pub mod redact;
+pub mod export;
 
 use policy::ExecutionPolicy;
 use monitor::{ResourceMonitor, ResourceUsage};
@@ -97,6 +99,9 @@ pub struct ExecutionResult {
     pub security_events: Vec<SecurityEvent>,
     pub execution_time_ms: u64,
     pub success: bool,
+    /// Paths touched by simulated file operations
+    #[serde(default)]
+    pub file_operations: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,7 +122,7 @@ pub enum SecurityEventType {
     SuspiciousBehavior,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SecuritySeverity {
     Low,
     Medium,
@@ -159,6 +164,7 @@ mod tests {
             security_events: vec![],
             execution_time_ms: 150,
             success: true,
+            file_operations: vec![],
         };
 
         let json = serde_json::to_string(&result).unwrap();
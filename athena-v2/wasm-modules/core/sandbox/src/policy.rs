@@ -1,14 +1,91 @@
+use crate::SecuritySeverity;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The only syscalls `ExecutionPolicy::strict()` lets through: enough to
+/// compute and report, nothing that reaches files, processes or the network
+pub const STRICT_ALLOWED_SYSCALLS: &[&str] = &[
+    "read", "write", "close", "fstat", "lseek", "mmap", "munmap", "brk",
+    "clock_gettime", "exit", "exit_group",
+];
+
+/// Below this a sample can't get past its runtime's own start-up, so runs
+/// end in a memory error instead of showing any behaviour
+pub const MIN_MEMORY_BYTES: usize = 1024 * 1024;
+
+/// Policies are stored and sent as JSON, so every struct here fills in
+/// missing fields from its `Default`: a policy saved before a field existed
+/// still loads. Variant and field names are part of that format.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ExecutionPolicy {
     pub resource_limits: ResourceLimits,
     pub security_policy: SecurityPolicy,
     pub monitoring: MonitoringPolicy,
+    /// Files placed in the virtual file system before the sample runs, for
+    /// samples that look for decoy documents or browser profiles first
+    pub vfs_seed: Vec<SeedFile>,
+    /// Seed for the randomness the sandbox hands the sample, so mutex
+    /// suffixes and DGA domains come out the same on every run. Unseeded
+    /// runs draw from the clock.
+    pub random_seed: Option<u64>,
+    /// Wall-clock time (Unix milliseconds) the sample sees when it starts,
+    /// to push the date past a time bomb's trigger. With `random_seed` set
+    /// the clock also advances deterministically instead of in real time.
+    pub virtual_time: Option<i64>,
+    /// Canned replies to the sample's network requests
+    pub network_emulation: NetworkEmulation,
+}
+
+/// A file to create in the virtual file system. Seeded files replace the
+/// built-in ones at the same path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedFile {
+    pub path: String,
+    #[serde(default)]
+    pub content: Vec<u8>,
+    #[serde(default)]
+    pub writable: bool,
+}
+
+/// Fake services that answer the sample, fakenet-style, so a sample
+/// waiting on its C2 goes on to show its next stage. Emulated replies never
+/// leave the sandbox, so they are served whatever the network policy says.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkEmulation {
+    pub responses: Vec<FakeResponse>,
+    /// Reply for hosts without a response of their own
+    pub default_response: Option<Vec<u8>>,
+}
+
+/// What a fake host answers. Without a port it answers on every port.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FakeResponse {
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub response: Vec<u8>,
+}
+
+impl NetworkEmulation {
+    /// Reply for `host:port`; a response for the exact port wins over one
+    /// for any port
+    pub fn response_for(&self, host: &str, port: u16) -> Option<&[u8]> {
+        let matching = |r: &&FakeResponse| r.host.eq_ignore_ascii_case(host);
+        self.responses
+            .iter()
+            .filter(matching)
+            .find(|r| r.port == Some(port))
+            .or_else(|| self.responses.iter().filter(matching).find(|r| r.port.is_none()))
+            .map(|r| r.response.as_slice())
+            .or(self.default_response.as_deref())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ResourceLimits {
     pub max_memory_bytes: usize,
     pub max_cpu_time_ms: u64,
@@ -17,36 +94,158 @@ pub struct ResourceLimits {
     pub max_output_size: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SecurityPolicy {
     pub syscall_policy: SyscallPolicy,
     pub network_policy: NetworkPolicy,
     pub file_system_policy: FileSystemPolicy,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MonitoringPolicy {
     pub trace_execution: bool,
     pub collect_metrics: bool,
     pub snapshot_interval_ms: Option<u64>,
     pub log_security_events: bool,
+    /// Paths whose writes, and for some rules reads, are reported as
+    /// suspicious, checked in order
+    pub sensitive_paths: Vec<SensitivePathRule>,
+    /// Paths never reported, whatever `sensitive_paths` says, e.g. a
+    /// decoy folder the sample is expected to write to
+    pub trusted_paths: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a sample writing, or for `reads` rules reading, a file is worth
+/// reporting. Paths are compared
+/// case-insensitively with `\` read as `/`, so one rule covers both
+/// `C:\Windows\System32\` and `c:/windows/system32/`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensitivePathRule {
+    pub name: String,
+    /// The rule matches paths containing any of these
+    pub path_contains: Vec<String>,
+    /// Only files with one of these extensions match; empty matches any file
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Reads match too, e.g. of a credential file
+    #[serde(default)]
+    pub reads: bool,
+    pub severity: SecuritySeverity,
+}
+
+/// Extensions Windows runs directly or through a script host
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "scr", "com", "pif", "cpl", "sys", "bat", "cmd", "ps1", "vbs", "vbe", "js", "jse",
+    "wsf", "hta", "lnk", "msi",
+];
+
+impl SensitivePathRule {
+    /// Credential files and shell startup files, read or written; startup
+    /// folders and autostart directories, the system directories, and
+    /// executables dropped in a temp directory
+    pub fn defaults() -> Vec<Self> {
+        let rule = |name: &str, path_contains: &[&str], extensions: &[&str], severity| SensitivePathRule {
+            name: name.to_string(),
+            path_contains: path_contains.iter().map(|p| p.to_string()).collect(),
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            reads: false,
+            severity,
+        };
+        vec![
+            SensitivePathRule {
+                reads: true,
+                ..rule("Credential file", &["/etc/passwd", "/etc/shadow", "/.ssh/"], &[], SecuritySeverity::Critical)
+            },
+            SensitivePathRule {
+                reads: true,
+                ..rule("Shell startup file", &["/.bashrc", "/.bash_profile", "/.zshrc"], &[], SecuritySeverity::High)
+            },
+            rule(
+                "Startup folder",
+                &["/start menu/programs/startup/", "/.config/autostart/", "/library/launchagents/", "/library/launchdaemons/"],
+                &[],
+                SecuritySeverity::High,
+            ),
+            rule("System directory", &["/windows/system32/", "/windows/syswow64/"], &[], SecuritySeverity::High),
+            rule("Executable in temp directory", &["/temp/", "/tmp/"], EXECUTABLE_EXTENSIONS, SecuritySeverity::Medium),
+        ]
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let path = normalize_path(path);
+        if !self.path_contains.iter().any(|p| path.contains(&normalize_path(p))) {
+            return false;
+        }
+        if self.extensions.is_empty() {
+            return true;
+        }
+        let file_name = path.rsplit('/').next().unwrap_or(&path);
+        file_name
+            .rsplit_once('.')
+            .is_some_and(|(_, ext)| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+}
+
+impl MonitoringPolicy {
+    /// The first sensitive-path rule a write to `path` falls under, unless
+    /// the path is trusted
+    pub fn classify_write(&self, path: &str) -> Option<&SensitivePathRule> {
+        self.classify(path, false)
+    }
+
+    /// The first `reads` rule a read of `path` falls under, unless the path
+    /// is trusted
+    pub fn classify_read(&self, path: &str) -> Option<&SensitivePathRule> {
+        self.classify(path, true)
+    }
+
+    fn classify(&self, path: &str, read: bool) -> Option<&SensitivePathRule> {
+        let normalized = normalize_path(path);
+        if self.trusted_paths.iter().any(|t| normalized.starts_with(&normalize_path(t))) {
+            return None;
+        }
+        self.sensitive_paths.iter().find(|rule| (rule.reads || !read) && rule.matches(path))
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+/// Something `ExecutionPolicy::validate` found wrong with a policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyWarning {
+    pub severity: PolicyWarningSeverity,
+    /// Path of the offending field, e.g. `resource_limits.max_memory_bytes`
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyWarningSeverity {
+    /// Runs, but probably not the way it was meant to
+    Warning,
+    /// Can't produce a useful run
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SyscallPolicy {
     AllowList(HashSet<String>),
     DenyList(HashSet<String>),
     DenyAll,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum NetworkPolicy {
     Disabled,
     AllowList(HashSet<String>), // Allowed domains/IPs
     DenyList(HashSet<String>),  // Blocked domains/IPs
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FileSystemPolicy {
     Disabled,
     ReadOnly(Vec<String>),     // Read-only paths
@@ -54,16 +253,6 @@ pub enum FileSystemPolicy {
     Virtual,                   // Virtual file system only
 }
 
-impl Default for ExecutionPolicy {
-    fn default() -> Self {
-        ExecutionPolicy {
-            resource_limits: ResourceLimits::default(),
-            security_policy: SecurityPolicy::default(),
-            monitoring: MonitoringPolicy::default(),
-        }
-    }
-}
-
 impl Default for ResourceLimits {
     fn default() -> Self {
         ResourceLimits {
@@ -93,6 +282,8 @@ impl Default for MonitoringPolicy {
             collect_metrics: true,
             snapshot_interval_ms: None,
             log_security_events: true,
+            sensitive_paths: SensitivePathRule::defaults(),
+            trusted_paths: Vec::new(),
         }
     }
 }
@@ -122,10 +313,19 @@ impl ExecutionPolicy {
                 collect_metrics: true,
                 snapshot_interval_ms: Some(1000),
                 log_security_events: true,
+                sensitive_paths: SensitivePathRule::defaults(),
+                trusted_paths: Vec::new(),
             },
+            vfs_seed: Vec::new(),
+            random_seed: None,
+            virtual_time: None,
+            network_emulation: NetworkEmulation::default(),
         }
     }
     
+    /// Deny-by-default containment for first contact with an untrusted
+    /// sample: allowlisted syscalls only, no network or file system, and
+    /// tight memory, time and output caps
     pub fn strict() -> Self {
         ExecutionPolicy {
             resource_limits: ResourceLimits {
@@ -136,7 +336,9 @@ impl ExecutionPolicy {
                 max_output_size: 5 * 1024 * 1024,     // 5MB
             },
             security_policy: SecurityPolicy {
-                syscall_policy: SyscallPolicy::DenyAll,
+                syscall_policy: SyscallPolicy::AllowList(
+                    STRICT_ALLOWED_SYSCALLS.iter().map(|s| s.to_string()).collect()
+                ),
                 network_policy: NetworkPolicy::Disabled,
                 file_system_policy: FileSystemPolicy::Disabled,
             },
@@ -145,7 +347,13 @@ impl ExecutionPolicy {
                 collect_metrics: true,
                 snapshot_interval_ms: Some(500),
                 log_security_events: true,
+                sensitive_paths: SensitivePathRule::defaults(),
+                trusted_paths: Vec::new(),
             },
+            vfs_seed: Vec::new(),
+            random_seed: None,
+            virtual_time: None,
+            network_emulation: NetworkEmulation::default(),
         }
     }
     
@@ -185,7 +393,104 @@ impl ExecutionPolicy {
                 collect_metrics: true,
                 snapshot_interval_ms: Some(100),
                 log_security_events: true,
+                sensitive_paths: SensitivePathRule::defaults(),
+                trusted_paths: Vec::new(),
             },
+            vfs_seed: Vec::new(),
+            random_seed: None,
+            virtual_time: None,
+            network_emulation: NetworkEmulation::default(),
+        }
+    }
+
+    /// Check the policy for settings that contradict each other or can't
+    /// work, without running anything
+    pub fn validate(&self) -> Result<(), Vec<PolicyWarning>> {
+        let mut warnings = Vec::new();
+        let mut warn = |severity, field: &str, message: String| {
+            warnings.push(PolicyWarning { severity, field: field.to_string(), message });
+        };
+        use PolicyWarningSeverity::{Error, Warning};
+
+        let limits = &self.resource_limits;
+        if limits.max_memory_bytes < MIN_MEMORY_BYTES {
+            warn(Error, "resource_limits.max_memory_bytes", format!(
+                "{} bytes is below the {} byte floor; raise the memory limit",
+                limits.max_memory_bytes, MIN_MEMORY_BYTES
+            ));
+        }
+        if limits.max_cpu_time_ms == 0 {
+            warn(Error, "resource_limits.max_cpu_time_ms", "A zero CPU time limit stops every run at once".to_string());
+        }
+        if limits.max_threads == 0 {
+            warn(Error, "resource_limits.max_threads", "At least one thread is needed to run the sample".to_string());
+        }
+        if limits.max_output_size == 0 {
+            warn(Warning, "resource_limits.max_output_size", "Nothing the sample prints will be kept".to_string());
+        }
+
+        let security = &self.security_policy;
+        match &security.syscall_policy {
+            SyscallPolicy::AllowList(allowed) if allowed.is_empty() => warn(
+                Warning,
+                "security_policy.syscall_policy",
+                "Allowlist mode with no allowed syscalls blocks every syscall; list the ones to allow, or use DenyAll to mean it".to_string(),
+            ),
+            SyscallPolicy::DenyList(denied) if denied.is_empty() => warn(
+                Warning,
+                "security_policy.syscall_policy",
+                "Denylist mode with no denied syscalls allows every syscall".to_string(),
+            ),
+            _ => {}
+        }
+
+        match &security.network_policy {
+            NetworkPolicy::AllowList(allowed) if allowed.is_empty() => warn(
+                Warning,
+                "security_policy.network_policy",
+                "Network allowlist is empty, which is the same as Disabled".to_string(),
+            ),
+            NetworkPolicy::DenyList(denied) if denied.is_empty() => warn(
+                Warning,
+                "security_policy.network_policy",
+                "Network denylist is empty, so the sample can reach any host".to_string(),
+            ),
+            _ => {}
+        }
+
+        match &security.file_system_policy {
+            FileSystemPolicy::ReadOnly(paths) | FileSystemPolicy::ReadWrite(paths) if paths.is_empty() => warn(
+                Warning,
+                "security_policy.file_system_policy",
+                "No paths are listed, so the sample can't reach any file".to_string(),
+            ),
+            FileSystemPolicy::Disabled if !self.vfs_seed.is_empty() => warn(
+                Warning,
+                "vfs_seed",
+                "Seeded files are never seen with the file system disabled".to_string(),
+            ),
+            _ => {}
+        }
+
+        let mut seen = HashSet::new();
+        for response in &self.network_emulation.responses {
+            if !seen.insert((response.host.to_ascii_lowercase(), response.port)) {
+                warn(Warning, "network_emulation.responses", format!(
+                    "More than one response for {}{}; only the first is served",
+                    response.host,
+                    response.port.map(|p| format!(":{}", p)).unwrap_or_default()
+                ));
+            }
+        }
+
+        if self.monitoring.snapshot_interval_ms == Some(0) {
+            warn(Error, "monitoring.snapshot_interval_ms", "A zero snapshot interval never lets the sample run; unset it to disable snapshots".to_string());
+        }
+
+        if warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(warnings)
         }
     }
 }
@@ -207,7 +512,18 @@ mod tests {
         let policy = ExecutionPolicy::strict();
         assert_eq!(policy.resource_limits.max_memory_bytes, 50 * 1024 * 1024);
         assert_eq!(policy.resource_limits.max_cpu_time_ms, 10000);
+        assert_eq!(policy.resource_limits.max_output_size, 5 * 1024 * 1024);
         assert!(policy.monitoring.trace_execution);
+        assert!(matches!(policy.security_policy.network_policy, NetworkPolicy::Disabled));
+        assert!(matches!(policy.security_policy.file_system_policy, FileSystemPolicy::Disabled));
+        match &policy.security_policy.syscall_policy {
+            SyscallPolicy::AllowList(allowed) => {
+                assert!(allowed.contains("read"));
+                assert!(!allowed.contains("socket"));
+                assert!(!allowed.contains("execve"));
+            }
+            other => panic!("expected allowlist syscall mode, got {:?}", other),
+        }
     }
     
     #[test]
@@ -216,4 +532,56 @@ mod tests {
         assert_eq!(policy.resource_limits.max_memory_bytes, 500 * 1024 * 1024);
         assert!(matches!(policy.security_policy.syscall_policy, SyscallPolicy::DenyList(_)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_policy_json_round_trip() {
+        for policy in [ExecutionPolicy::default(), ExecutionPolicy::strict(), ExecutionPolicy::relaxed(), ExecutionPolicy::debug()] {
+            let json = serde_json::to_string(&policy).unwrap();
+            let restored: ExecutionPolicy = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored, policy);
+        }
+    }
+
+    #[test]
+    fn test_partial_policy_json_uses_defaults() {
+        let json = r#"{
+            "resource_limits": { "max_memory_bytes": 1048576 },
+            "security_policy": { "network_policy": { "AllowList": ["10.0.0.5"] } }
+        }"#;
+
+        let policy: ExecutionPolicy = serde_json::from_str(json).unwrap();
+        let defaults = ExecutionPolicy::default();
+
+        assert_eq!(policy.resource_limits.max_memory_bytes, 1048576);
+        assert_eq!(policy.resource_limits.max_cpu_time_ms, defaults.resource_limits.max_cpu_time_ms);
+        assert_eq!(
+            policy.security_policy.network_policy,
+            NetworkPolicy::AllowList(["10.0.0.5".to_string()].into_iter().collect())
+        );
+        assert_eq!(policy.security_policy.syscall_policy, SyscallPolicy::DenyAll);
+        assert_eq!(policy.monitoring, defaults.monitoring);
+
+        let empty: ExecutionPolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty, defaults);
+    }
+
+    #[test]
+    fn test_validate_flags_empty_syscall_allowlist() {
+        let mut policy = ExecutionPolicy::default();
+        policy.security_policy.syscall_policy = SyscallPolicy::AllowList(HashSet::new());
+
+        let warnings = policy.validate().unwrap_err();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, PolicyWarningSeverity::Warning);
+        assert_eq!(warnings[0].field, "security_policy.syscall_policy");
+
+        policy.resource_limits.max_memory_bytes = 4096;
+        let warnings = policy.validate().unwrap_err();
+        assert!(warnings.iter().any(|w| w.severity == PolicyWarningSeverity::Error
+            && w.field == "resource_limits.max_memory_bytes"));
+
+        for sane in [ExecutionPolicy::default(), ExecutionPolicy::strict(), ExecutionPolicy::relaxed(), ExecutionPolicy::debug()] {
+            assert_eq!(sane.validate(), Ok(()));
+        }
+    }
+}
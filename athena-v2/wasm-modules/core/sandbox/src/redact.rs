@@ -0,0 +1,83 @@
+use regex::Regex;
+use crate::ExecutionResult;
+
+/// Mask sensitive substrings (usernames, internal paths, hostnames) in an
+/// execution result before it is shared outside the analysis environment.
+///
+/// Every match of every pattern in stdout, stderr and the recorded file
+/// operation paths is replaced with `replacement`. Exit code, resource usage
+/// and security events are left untouched.
+pub fn redact(result: &mut ExecutionResult, patterns: &[Regex], replacement: &str) {
+    result.stdout = redact_str(&result.stdout, patterns, replacement);
+    result.stderr = redact_str(&result.stderr, patterns, replacement);
+
+    for path in result.file_operations.iter_mut() {
+        *path = redact_str(path, patterns, replacement);
+    }
+}
+
+fn redact_str(input: &str, patterns: &[Regex], replacement: &str) -> String {
+    let mut output = input.to_string();
+    for pattern in patterns {
+        if pattern.is_match(&output) {
+            output = pattern.replace_all(&output, regex::NoExpand(replacement)).into_owned();
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::ResourceUsage;
+
+    fn sample_result() -> ExecutionResult {
+        ExecutionResult {
+            stdout: "Loaded config from /home/jdoe/.config/app.ini\nDone\n".to_string(),
+            stderr: "warning: C:\\Users\\jdoe\\AppData is not writable\n".to_string(),
+            exit_code: 0,
+            resource_usage: ResourceUsage {
+                memory_bytes: 1024,
+                cpu_time_ms: 10,
+                file_handles: 2,
+                threads: 1,
+                output_size: 64,
+                peak_memory_bytes: 2048,
+            },
+            security_events: vec![],
+            execution_time_ms: 12,
+            success: true,
+            file_operations: vec![
+                "/home/jdoe/.ssh/id_rsa".to_string(),
+                "/etc/hosts".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_redact_username() {
+        let mut result = sample_result();
+        let patterns = vec![Regex::new(r"jdoe").unwrap()];
+
+        redact(&mut result, &patterns, "[REDACTED]");
+
+        assert!(!result.stdout.contains("jdoe"));
+        assert!(result.stdout.contains("/home/[REDACTED]/.config/app.ini"));
+        assert!(result.stdout.contains("Done"));
+        assert!(result.stderr.contains("C:\\Users\\[REDACTED]\\AppData"));
+        assert_eq!(result.file_operations[0], "/home/[REDACTED]/.ssh/id_rsa");
+        assert_eq!(result.file_operations[1], "/etc/hosts");
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.execution_time_ms, 12);
+    }
+
+    #[test]
+    fn test_redact_replacement_is_literal() {
+        let mut result = sample_result();
+        let patterns = vec![Regex::new(r"(jdoe)").unwrap()];
+
+        redact(&mut result, &patterns, "$1-hidden");
+
+        assert_eq!(result.file_operations[0], "/home/$1-hidden/.ssh/id_rsa");
+    }
+}